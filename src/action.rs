@@ -0,0 +1,368 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
+
+use crate::app::AppState;
+
+// What the user asked for, apart from the key or mouse event that asked for it. Events are
+// mapped to actions here and carried out by App::update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    NextPane,
+    PreviousPane,
+    OpenFinder,
+    ResizePanes,
+    OpenShell,
+    ShowHelp,
+    CycleTheme,
+    ShowLastError,
+    OpenJsonViewer,
+    OpenCommandLine,
+    RetryTimedOut,
+    OpenCustomCommands,
+    TogglePreview,
+    UploadAndMatch,
+    UploadFromClipboard,
+    OpenLastDownload,
+    ShowCommandHistory,
+    FilterLog,
+    SearchJson,
+    OpenSearch,
+    SearchRevisions,
+    ShowLog,
+    ShowBasket,
+    ShowDeletions,
+    ShowJobs,
+    ShowMatchHistory,
+    ShowPropertySchema,
+    ShowViolations,
+    ImportMetadata,
+    ToggleLogPane,
+    ToggleZoom,
+    CyclePaneLayout,
+    Refresh,
+    ToggleOffline,
+    Undo,
+    ToggleMetrics,
+    Scroll(isize, Position), // Move the selection of the pane under the pointer by this many rows
+    Click(Position),
+    Drag(Position),    // The pointer moved with the left button held
+    Release(Position), // The left button was let go
+}
+
+impl Action {
+    // What the action does, for the help screen
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit (asks first while background jobs run)",
+            Action::NextPane => "Next pane",
+            Action::PreviousPane => "Previous pane",
+            Action::OpenFinder => "Fuzzy-find assets by name in every cached folder",
+            Action::ResizePanes => "Resize the panes",
+            Action::OpenShell => "Suspend to a shell ($PCLI2_TUI_FOLDER, $PCLI2_TUI_ASSET_UUID)",
+            Action::ShowHelp => "Show this help",
+            Action::CycleTheme => "Cycle the color theme",
+            Action::ShowLastError => "Show details of the last failed command",
+            Action::OpenJsonViewer => "View the raw JSON output of the last pcli2 command",
+            Action::OpenCommandLine => "Run arbitrary pcli2 arguments (:sh opens a shell)",
+            Action::RetryTimedOut => "Retry a timed out command with a longer timeout",
+            Action::OpenCustomCommands => "Run a custom command on the selected asset",
+            Action::TogglePreview => "Show or hide the thumbnail preview",
+            Action::UploadAndMatch => "Upload a local file, wait for processing and match it",
+            Action::UploadFromClipboard => "Upload the file whose path is on the clipboard",
+            Action::OpenLastDownload => "Open the most recent download in its viewer",
+            Action::ShowCommandHistory => "Command history",
+            Action::FilterLog => "Filter log entries by text",
+            Action::SearchJson => "Search the JSON",
+            Action::OpenSearch => "Search assets",
+            Action::SearchRevisions => "Search the folder and below for the selected asset's name",
+            Action::ShowLog => "Log view",
+            Action::ToggleLogPane => "Hide or show the log pane",
+            Action::ToggleZoom => "Zoom the active pane to the whole content area, or back",
+            Action::CyclePaneLayout => "Cycle the layout: automatic, side by side, stacked",
+            Action::Refresh => "Reload the folders and assets on screen, past the cache",
+            Action::ToggleOffline => "Go offline (browse cached folders only) or back online",
+            Action::ShowBasket => "Open the basket",
+            Action::ShowDeletions => "Review and delete the assets staged for deletion",
+            Action::ShowJobs => "Background jobs and prefetches",
+            Action::ShowMatchHistory => "Reopen an earlier geometric match",
+            Action::ShowPropertySchema => "Metadata properties of the tenant and their usage",
+            Action::ShowViolations => "List the metadata rule violations in the folder and below",
+            Action::ImportMetadata => "Import metadata values from a CSV file, after a preview",
+            Action::Undo => "Undo the last move, rename or delete",
+            Action::ToggleMetrics => "Show or hide the performance overlay",
+            Action::Scroll(..) => "Scroll",
+            Action::Click(_) => "Click",
+            Action::Drag(_) => "Drag",
+            Action::Release(_) => "Release",
+        }
+    }
+}
+
+// Modifiers a binding asks for; keys without them match whatever else is held
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    Any,
+    Control,
+    Shift,
+    NoAlt,
+}
+
+impl Modifier {
+    pub fn matches(self, modifiers: KeyModifiers) -> bool {
+        match self {
+            Modifier::Any => true,
+            Modifier::Control => modifiers.contains(KeyModifiers::CONTROL),
+            Modifier::Shift => modifiers.contains(KeyModifiers::SHIFT),
+            Modifier::NoAlt => !modifiers.contains(KeyModifiers::ALT),
+        }
+    }
+
+    // The modifiers of a key press the binding takes
+    pub fn key_event(self, code: KeyCode) -> KeyEvent {
+        let modifiers = match self {
+            Modifier::Control => KeyModifiers::CONTROL,
+            Modifier::Shift => KeyModifiers::SHIFT,
+            Modifier::Any | Modifier::NoAlt => KeyModifiers::NONE,
+        };
+        KeyEvent::new(code, modifiers)
+    }
+
+    // How a key held with it is written in the help, e.g. "Ctrl+F"
+    pub fn label(self, code: KeyCode) -> String {
+        let key = key_label(code);
+        match self {
+            Modifier::Control => format!("Ctrl+{}", key.to_uppercase()),
+            Modifier::Shift => format!("Shift+{}", key),
+            Modifier::Any | Modifier::NoAlt => key,
+        }
+    }
+}
+
+// The views a binding works in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum When {
+    Always,
+    Browsing, // The folder and asset views
+    In(AppState),
+    NotIn(AppState),
+}
+
+impl When {
+    pub fn applies(self, state: &AppState) -> bool {
+        match self {
+            When::Always => true,
+            When::Browsing => matches!(state, AppState::Folders | AppState::Assets),
+            When::In(only) => *state == only,
+            When::NotIn(except) => *state != except,
+        }
+    }
+}
+
+// A key of the global keymap and what it does
+#[derive(Debug, Clone, Copy)]
+pub struct Binding {
+    pub code: KeyCode,
+    pub modifier: Modifier,
+    pub when: When,
+    pub action: Action,
+}
+
+impl Binding {
+    fn matches(&self, key: KeyEvent, state: &AppState) -> bool {
+        key.code == self.code && self.modifier.matches(key.modifiers) && self.when.applies(state)
+    }
+
+    // How the key is written in the help, e.g. "Ctrl+F"
+    pub fn label(&self) -> String {
+        self.modifier.label(self.code)
+    }
+}
+
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        code => code.to_string(),
+    }
+}
+
+const fn key(code: KeyCode, when: When, action: Action) -> Binding {
+    Binding {
+        code,
+        modifier: Modifier::Any,
+        when,
+        action,
+    }
+}
+
+const fn with(code: KeyCode, modifier: Modifier, when: When, action: Action) -> Binding {
+    Binding {
+        code,
+        modifier,
+        when,
+        action,
+    }
+}
+
+// Keys that work in every view (or every view named), in the order they are tried; the help
+// screen lists them from here
+pub const GLOBAL_KEYS: &[Binding] = &[
+    key(KeyCode::Char('q'), When::Always, Action::Quit),
+    key(KeyCode::BackTab, When::Always, Action::PreviousPane),
+    with(
+        KeyCode::Tab,
+        Modifier::Shift,
+        When::Always,
+        Action::PreviousPane,
+    ),
+    with(
+        KeyCode::Tab,
+        Modifier::NoAlt,
+        When::Always,
+        Action::NextPane,
+    ),
+    with(
+        KeyCode::Char('f'),
+        Modifier::Control,
+        When::Always,
+        Action::OpenFinder,
+    ),
+    with(
+        KeyCode::Char('n'),
+        Modifier::Control,
+        When::Always,
+        Action::ResizePanes,
+    ),
+    with(
+        KeyCode::Char('z'),
+        Modifier::Control,
+        When::Always,
+        Action::OpenShell,
+    ),
+    key(KeyCode::Char('h'), When::Always, Action::ShowHelp),
+    key(KeyCode::Char('T'), When::Always, Action::CycleTheme),
+    key(KeyCode::Char('E'), When::Always, Action::ShowLastError),
+    key(KeyCode::Char('J'), When::Always, Action::OpenJsonViewer),
+    key(KeyCode::Char(':'), When::Always, Action::OpenCommandLine),
+    key(KeyCode::Char('R'), When::Always, Action::RetryTimedOut),
+    key(KeyCode::Char('!'), When::Always, Action::OpenCustomCommands),
+    key(KeyCode::Char('p'), When::Browsing, Action::TogglePreview),
+    key(KeyCode::Char('M'), When::Browsing, Action::UploadAndMatch),
+    key(
+        KeyCode::Char('V'),
+        When::Browsing,
+        Action::UploadFromClipboard,
+    ),
+    key(KeyCode::Char('o'), When::Always, Action::OpenLastDownload),
+    // In the Log view 'c' copies the selected entry
+    key(
+        KeyCode::Char('c'),
+        When::NotIn(AppState::Log),
+        Action::ShowCommandHistory,
+    ),
+    key(
+        KeyCode::Char('/'),
+        When::In(AppState::Log),
+        Action::FilterLog,
+    ),
+    key(
+        KeyCode::Char('/'),
+        When::In(AppState::JsonView),
+        Action::SearchJson,
+    ),
+    key(KeyCode::Char('/'), When::Always, Action::OpenSearch),
+    // Shift+/ on most keyboards
+    key(KeyCode::Char('?'), When::Browsing, Action::SearchRevisions),
+    key(KeyCode::Char('l'), When::Always, Action::ShowLog),
+    key(KeyCode::Char('L'), When::Always, Action::ToggleLogPane),
+    key(KeyCode::Char('Z'), When::Browsing, Action::ToggleZoom),
+    key(KeyCode::Char('|'), When::Browsing, Action::CyclePaneLayout),
+    key(KeyCode::F(5), When::Browsing, Action::Refresh),
+    with(
+        KeyCode::Char('r'),
+        Modifier::Control,
+        When::Browsing,
+        Action::Refresh,
+    ),
+    key(KeyCode::Char('O'), When::Browsing, Action::ToggleOffline),
+    key(KeyCode::Char('B'), When::Always, Action::ShowBasket),
+    with(
+        KeyCode::Char('x'),
+        Modifier::Control,
+        When::Always,
+        Action::ShowDeletions,
+    ),
+    key(KeyCode::Char('W'), When::Always, Action::ShowJobs),
+    key(KeyCode::Char('G'), When::Browsing, Action::ShowMatchHistory),
+    key(
+        KeyCode::Char('P'),
+        When::Browsing,
+        Action::ShowPropertySchema,
+    ),
+    key(KeyCode::Char('A'), When::Browsing, Action::ShowViolations),
+    key(KeyCode::Char('I'), When::Browsing, Action::ImportMetadata),
+    // 'u' is upload mode
+    key(KeyCode::Char('U'), When::Always, Action::Undo),
+    key(KeyCode::F(12), When::Always, Action::ToggleMetrics),
+];
+
+// The action of a key that works in every view, when no dialog or inline input has the keys
+pub fn global_action(key: KeyEvent, state: &AppState) -> Option<Action> {
+    GLOBAL_KEYS
+        .iter()
+        .find(|binding| binding.matches(key, state))
+        .map(|binding| binding.action)
+}
+
+pub fn mouse_action(mouse: MouseEvent) -> Option<Action> {
+    let position = Position::new(mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::ScrollDown => Some(Action::Scroll(1, position)),
+        MouseEventKind::ScrollUp => Some(Action::Scroll(-1, position)),
+        MouseEventKind::Down(MouseButton::Left) => Some(Action::Click(position)),
+        MouseEventKind::Drag(MouseButton::Left) => Some(Action::Drag(position)),
+        MouseEventKind::Up(MouseButton::Left) => Some(Action::Release(position)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(kind: MouseEventKind) -> Option<Action> {
+        mouse_action(MouseEvent {
+            kind,
+            column: 3,
+            row: 7,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    #[test]
+    fn only_the_left_button_clicks() {
+        let position = Position::new(3, 7);
+        assert_eq!(
+            mouse(MouseEventKind::Down(MouseButton::Left)),
+            Some(Action::Click(position))
+        );
+        assert_eq!(mouse(MouseEventKind::Down(MouseButton::Right)), None);
+        assert_eq!(mouse(MouseEventKind::Down(MouseButton::Middle)), None);
+        assert_eq!(mouse(MouseEventKind::Drag(MouseButton::Right)), None);
+        assert_eq!(mouse(MouseEventKind::Up(MouseButton::Right)), None);
+        assert_eq!(
+            mouse(MouseEventKind::ScrollDown),
+            Some(Action::Scroll(1, position))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+use std::process::Stdio;
+use tokio::process::Command;
+
+// Ring the terminal bell; most terminals flag the window or tab when it is not in front
+pub fn bell() {
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+}
+
+// AppleScript string literal
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Show a desktop notification with the platform's notifier (notify-send on Linux, osascript
+// on macOS); the notifier runs on its own and is not waited for
+pub fn desktop(title: &str, body: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        let script = format!(
+            "display notification {} with title {}",
+            quoted(body),
+            quoted(title)
+        );
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "pcli2-tui", title, body]);
+        command
+    } else {
+        bail!("Desktop notifications are not supported on this platform");
+    };
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {} for a desktop notification", program))?;
+    Ok(())
+}
@@ -1,10 +1,67 @@
 use crossterm::event::{KeyCode, KeyEvent};
 use serde::{Deserialize, Serialize};
 
-use crate::pcli_commands;
+use crate::action::{self, Action};
+use crate::alert;
+use crate::assembly::{self, AssemblyDownload};
+use crate::asset_table::AssetTable;
+use crate::basket::{self, Basket};
+use crate::cell_edit::CellEditor;
+use crate::config::{self, Config};
+use crate::csv_import::{self, ImportPlan};
+use crate::demo;
+use crate::dialog::{self, Confirm, Prompt};
+use crate::export;
+use crate::external::{self, ExternalCommand};
+use crate::finder::Finder;
+use crate::folder_diff::{self, FolderDiff};
+use crate::folder_download::{self, FolderDownload};
+use crate::folder_picker::{self, FolderPicker};
+use crate::geometry::{GeometryFetcher, GeometryState, LengthUnit};
+use crate::help::Help;
+use crate::hooks::{HookEvent, Hooks};
+use crate::jobs::{JobKind, Jobs};
+use crate::journal::{InterruptedJobs, Journal, Work};
+use crate::json_view::JsonViewer;
+use crate::keymap::{self, KeyContext, ViewAction};
+use crate::log_entry::{HistoryEntry, LogEntry, LogStatus};
+use crate::match_history::{self, MatchHistory, MatchSession};
+use crate::metadata_diff::MetadataDiff;
+use crate::metrics::Metrics;
+use crate::modal::{Modal, ModalStack};
+use crate::notes::Notes;
+use crate::notifications::{NotificationLevel, Notifications};
+use crate::offline;
+use crate::pcli_commands::{self, CommandRun, LastOutput, PcliCommand, PcliError, PcliTimeout};
+use crate::permissions::{self, Permission};
+use crate::pipeline::MatchPipeline;
+use crate::prefetch::Prefetcher;
+use crate::preview::{Previewer, Protocol};
+use crate::processing;
+use crate::rate_limit;
+use crate::report::{Links, ReportAsset};
+use crate::review::{Decision, Review, ReviewPair};
+use crate::schema::{self, PropertySchema};
+use crate::search_pattern::{SearchMode, SearchPattern};
+use crate::setup::{self, Setup, SetupStep};
+use crate::sync::{self, SyncPlan};
+use crate::tags::{self, TagPicker, TagPickerMode, Tags};
+use crate::text;
+use crate::text_input::TextInput;
+use crate::theme::{self, ColorSupport, Theme};
+use crate::tour::Tour;
+use crate::undo::{UndoHistory, Undoable};
+use crate::validation::{Rules, ValidationReport};
 use chrono::prelude::*;
 use std::collections::HashMap;
-use arboard;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// How long the folder selection has to stay put before its assets are loaded
+const SELECTION_LOAD_DELAY: Duration = Duration::from_millis(150);
+
+// Queries kept for Up/Down in the search input
+const SEARCH_HISTORY_LIMIT: usize = 100;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Folder {
@@ -24,28 +81,229 @@ pub struct Asset {
     pub folder_uuid: String,
     pub file_type: String,
     pub size: Option<u64>,
-    pub path: String,        // Add path field to store the full path
-    pub metadata: serde_json::Value,  // Add metadata field
+    pub path: String,                // Add path field to store the full path
+    pub metadata: serde_json::Value, // Add metadata field
+    pub is_assembly: bool,
+    #[serde(default)]
+    pub processing_status: String, // Empty in files saved before it was kept
 }
 
-#[derive(Debug, Clone)]
+impl Asset {
+    // Path of the folder the asset is in; empty at the top level
+    pub fn folder_path(&self) -> &str {
+        self.path.rsplit_once('/').map_or("", |(folder, _)| folder)
+    }
+
+    // A metadata value as text, for a key in any letter case
+    pub fn metadata_value_ignoring_case(&self, key: &str) -> Option<String> {
+        let key = metadata_keys([self])
+            .into_iter()
+            .find(|candidate| candidate.eq_ignore_ascii_case(key))?;
+        self.metadata_value(&key)
+    }
+
+    // A metadata value as text; pcli2 sometimes nests the metadata in a "meta" object
+    pub fn metadata_value(&self, key: &str) -> Option<String> {
+        let obj = self.metadata.as_object()?;
+        let value = match obj.get("meta").and_then(|meta| meta.as_object()) {
+            Some(meta) => meta.get(key)?,
+            None => obj.get(key)?,
+        };
+        Some(
+            value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string),
+        )
+    }
+}
+
+// Sorted metadata keys found in any of the assets, looking into "meta" objects
+pub fn metadata_keys<'a>(assets: impl IntoIterator<Item = &'a Asset>) -> Vec<String> {
+    let mut keys = std::collections::BTreeSet::new();
+    for asset in assets {
+        let Some(obj) = asset.metadata.as_object() else {
+            continue;
+        };
+        for (key, value) in obj {
+            match value.as_object() {
+                Some(meta) if key == "meta" => keys.extend(meta.keys().cloned()),
+                _ => {
+                    keys.insert(key.clone());
+                }
+            }
+        }
+    }
+    keys.into_iter().collect()
+}
+
+// Order metadata values numerically when both are numbers, otherwise as text; missing values
+// go last
+fn compare_values(a: Option<String>, b: Option<String>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.total_cmp(&y),
+            _ => a.to_lowercase().cmp(&b.to_lowercase()),
+        },
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FolderCache {
     pub folders: Vec<Folder>,
     pub assets: Vec<Asset>,
     pub timestamp: std::time::SystemTime,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl FolderCache {
+    // Cached data is trusted for 5 minutes, and for as long as the app is offline
+    pub fn is_fresh(&self) -> bool {
+        offline::is_enabled()
+            || self.timestamp.elapsed().unwrap_or(std::time::Duration::MAX)
+                < std::time::Duration::from_secs(300)
+    }
+}
+
+// Where the parts of the main view were drawn in the last frame; set by the UI, None for the
+// parts not on screen
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScreenAreas {
+    pub folders: Option<ratatui::layout::Rect>,
+    pub assets: Option<ratatui::layout::Rect>,
+    pub log: Option<ratatui::layout::Rect>,
+    pub status: Option<ratatui::layout::Rect>,
+    pub hints: Option<ratatui::layout::Rect>,
+    pub folder_rows: Option<ratatui::layout::Rect>, // The rows of the folders list, the first at the top
+    pub asset_rows: Option<ratatui::layout::Rect>, // The rows of the assets table, below its header
+}
+
+impl ScreenAreas {
+    // The row of a list under a position, counted from the first row drawn
+    fn row_at(
+        rows: Option<ratatui::layout::Rect>,
+        position: ratatui::layout::Position,
+    ) -> Option<usize> {
+        rows.filter(|rows| rows.contains(position))
+            .map(|rows| (position.y - rows.y) as usize)
+    }
+
+    // The pane drawn at a position, e.g. under the mouse pointer
+    pub fn pane_at(&self, position: ratatui::layout::Position) -> Option<ActivePane> {
+        let contains =
+            |area: Option<ratatui::layout::Rect>| area.is_some_and(|area| area.contains(position));
+        if contains(self.folders) {
+            Some(ActivePane::Folders)
+        } else if contains(self.assets) {
+            Some(ActivePane::Assets)
+        } else if contains(self.log) {
+            Some(ActivePane::Log)
+        } else {
+            None
+        }
+    }
+}
+
+// Where a pane's listing came from: pcli2, or the folder cache, and when pcli2 returned it
+#[derive(Debug, Clone)]
+pub struct DataSource {
+    pub path: String, // Folder the listing is of, "" for the top level
+    pub fetched: std::time::SystemTime,
+    pub cached: bool,
+}
+
+impl DataSource {
+    fn fetched(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            fetched: std::time::SystemTime::now(),
+            cached: false,
+        }
+    }
+
+    fn cached(path: &str, fetched: std::time::SystemTime) -> Self {
+        Self {
+            path: path.to_string(),
+            fetched,
+            cached: true,
+        }
+    }
+
+    // "cached 3m ago", or "loaded 3m ago" once fetched data is a minute old; None while it is
+    // fresh from pcli2
+    pub fn age_label(&self) -> Option<String> {
+        let age = self.fetched.elapsed().unwrap_or_default();
+        if self.cached && offline::is_enabled() {
+            Some(format!("offline · cached {} ago", text::format_age(age)))
+        } else if self.cached {
+            Some(format!("cached {} ago", text::format_age(age)))
+        } else if age >= Duration::from_secs(60) {
+            Some(format!("loaded {} ago", text::format_age(age)))
+        } else {
+            None
+        }
+    }
+}
+
+// Convert pcli folders to the Folders pane listing of `parent_path`, led by a ".." entry
+// unless the listing is the root
+pub fn folder_listing(
+    parent_path: &str,
+    pcli_folders: Vec<pcli_commands::PcliFolder>,
+) -> Vec<Folder> {
+    // Convert pcli folders to our internal representation
+    let mut folders: Vec<Folder> = pcli_folders
+        .into_iter()
+        .map(|f| Folder {
+            uuid: f.id, // Map 'id' from pcli to 'uuid' in our struct
+            name: f.name,
+            path: f.path, // Store the full path
+            folders_count: f.folders_count,
+            assets_count: f.assets_count,
+            parent_uuid: None, // pcli doesn't provide parent info in list
+            children: vec![],
+        })
+        .collect();
+
+    // Add parent directory indicator if we're not at the root
+    if !parent_path.is_empty() {
+        // A top-level folder's parent is the root
+        let grandparent = parent_path
+            .rfind('/')
+            .map(|pos| &parent_path[..pos])
+            .unwrap_or_default();
+        folders.insert(
+            0,
+            Folder {
+                uuid: String::from(".."), // Special identifier for parent
+                name: String::from(".."),
+                path: grandparent.to_string(), // Parent path
+                folders_count: 0,
+                assets_count: 0,
+                parent_uuid: None,
+                children: vec![],
+            },
+        );
+    }
+
+    folders
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppState {
     Folders,
     Assets,
-    Search,
     Uploading,
     Downloading,
-    Help,
     CommandHistory,
     Log,
     PaneResize,
+    RawOutput,
+    JsonView,
+    Basket,
+    Deletions,
+    Jobs,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -63,32 +321,136 @@ pub struct App {
     pub selected_folder_index: usize,
     pub selected_asset_index: usize,
     pub search_query: String,
-    pub status_message: String,
+    pub status_message: String, // Transient status of the current operation
+    pub notifications: Notifications, // Toast queue and notification history
+    pub show_notification_history: bool, // Whether the Log view shows notifications instead of log entries
     pub should_quit: bool,
     pub active_pane: ActivePane,
     pub folder_cache: HashMap<String, FolderCache>,
     pub assets_loading_for_selection: bool, // Flag to indicate if assets are being loaded for selected folder
-    pub last_executed_command: String,      // Track the last executed PCLI2 command
-    pub command_history: Vec<String>,       // Track command history
-    pub log_entries: Vec<String>,           // Track log entries (commands and outputs)
-    pub log_scroll_position: usize,         // Track scroll position in log
-    pub show_search_modal: bool,            // Whether to show the search modal
-    pub search_input_buffer: String,        // Buffer for search input
-    pub command_in_progress: bool,          // Whether a PCLI2 command is currently running
-    pub resize_mode_active: bool,           // Whether pane resize mode is active
-    pub resize_delta_x: i32,                // Horizontal resize adjustment
-    pub resize_delta_y: i32,                // Vertical resize adjustment
-    pub search_results: Vec<Asset>,          // Store search results separately from folder assets
+    pub last_executed_command: PcliCommand, // Track the last executed PCLI2 command
+    pub command_history: Vec<HistoryEntry>, // Track command history
+    pub command_history_index: usize,       // Selected entry in the Command History view
+    pub command_edit_buffer: Option<TextInput>, // History command being edited before it is run
+    pub raw_command_input: Option<TextInput>, // pcli2 arguments being typed in ':' command mode
+    pub raw_result: Option<RawCommandResult>, // Output of the last ':' command
+    pub raw_output_scroll: u16,             // Scroll offset in the raw output view
+    pub json_viewer: Option<JsonViewer>,    // Raw output of the last pcli2 call, opened with 'J'
+    pub log_entries: Vec<LogEntry>,         // Track log entries (commands and outputs)
+    pub last_error: Option<ErrorDetail>,    // Most recent command failure, opened with 'E'
+    pub last_download: Option<PathBuf>,     // Most recently downloaded file, opened with 'o'
+    pub downloaded: HashMap<String, PathBuf>, // Local files downloaded this session, by asset UUID
+    pub undo: UndoHistory,                  // Operations that 'U' can reverse
+    pub pending_external: Option<ExternalCommand>, // Program to run with the TUI suspended
+    pub custom_command_menu: Option<usize>, // Selected entry while the custom command menu is open
+    pub assembly_download_prompt: Option<Asset>, // Assembly waiting for "with dependencies?" answer
+    pub confirm: Option<Confirm<ConfirmAction>>, // Question waiting for a yes or no
+    pub finder: Option<Finder>,             // Ctrl+F fuzzy finder, while open
+    pub folder_picker: Option<FolderPicker<PickAction>>, // Destination folder being chosen
+    pub basket: Basket,                     // Assets marked in any folder for batch actions
+    pub deletions: Basket, // Assets staged for deletion; none is deleted until they're committed
+    pub notes: Notes,      // Local notes on assets, by UUID
+    pub tags: Tags,        // Tags of assets, locally or in their metadata
+    pub tag_picker: Option<TagPicker>, // Tags of an asset, or the tag filter, being chosen
+    pub tag_filter: Option<String>, // Only assets with this tag are listed
+    pub all_assets: Vec<Asset>, // The asset listing before the tag filter
+    pub diff_mark: Option<String>, // Folder marked with '=' to compare with another
+    pub folder_diff: Option<FolderDiff>, // Comparison of two folders, while it is open
+    pub asset_diff_mark: Option<Asset>, // Asset marked with '=' to compare with another
+    pub metadata_diff: Option<MetadataDiff>, // Metadata of two assets side by side, while open
+    pub prompt: Option<Prompt<PromptAction>>, // Path or name being typed in the hint bar
+    pub sync_plan: Option<SyncPlan>, // Sync plan waiting to be reviewed and executed
+    pub pipeline: MatchPipeline, // Upload-and-match runs in the background
+    pub error_modal: Option<ErrorDetail>, // Error currently shown in the error detail modal
+    pub error_modal_scroll: u16, // Scroll offset within the error detail modal
+    pub last_operation: Option<Operation>, // The most recent pcli2-backed operation
+    pub timed_out_operation: Option<(Operation, Duration)>, // Operation that timed out, with the timeout it hit
+    pub log_scroll_position: usize,                         // Track scroll position in log
+    pub log_filter: LogFilter,                              // Status filter applied in the Log view
+    pub log_filter_text: String, // Substring filter applied in the Log view
+    pub log_filter_editing: bool, // Whether the substring filter is being typed
+    pub log_detail_index: Option<usize>, // Log entry shown in the log detail modal
+    pub log_detail_scroll: u16,  // Scroll offset within the log detail modal
+    pub log_detail_copy_button: Option<ratatui::layout::Rect>, // Where the copy button was drawn, for mouse clicks
+    pub active_pane_area: Option<ratatui::layout::Rect>, // Where the active pane was drawn, for the busy indicator
+    pub jobs_indicator: Option<ratatui::layout::Rect>, // Where the status line counted background jobs, for mouse clicks
+    pub hint_targets: Vec<(ratatui::layout::Rect, KeyEvent)>, // Where the hint bar drew its keys, and the key a click presses
+    pub asset_drag: Option<(usize, usize)>, // First and last asset of a mouse drag in progress
+    pub hovered_asset: Option<usize>, // The asset under the mouse pointer, for the metadata strip
+    pub cell_editor: Option<CellEditor>, // Editing metadata cells of the assets table, while it is on
+    pub asset_table: Option<AssetTable>, // The assets table as last drawn
+    pub table_version: u64,              // Counts changes to what the assets table shows
+    pub screen_areas: ScreenAreas, // Where the panes were drawn, for the tour and the mouse wheel
+    pub modals: ModalStack,        // Open dialogs and prompts; the top one gets the keys
+    pub search_input_buffer: TextInput, // Buffer for search input
+    pub command_in_progress: bool, // Whether a PCLI2 command is currently running
+    pub command_started: Option<Instant>, // When the running PCLI2 command was started
+    pub selection_load_at: Option<Instant>, // When to load assets for the newly selected folder
+    pub prefetcher: Prefetcher,    // Background loading of neighboring folders
+    pub hooks: Hooks,              // User scripts run on lifecycle events
+    pub jobs: Jobs,                // Batch jobs running in the background
+    pub previewer: Previewer,      // Thumbnails of the selected asset
+    pub geometry: GeometryFetcher, // Bounding box, volume and surface area of assets
+    pub geometry_unit: LengthUnit, // Unit geometry is displayed in
+    pub show_preview: bool,        // Whether the preview panel is shown
+    pub preview_pending: Option<(String, Instant)>, // Selected asset and since when, to settle first
+    pub preview_graphics: Option<(String, ratatui::layout::Rect)>, // Thumbnail to draw with a graphics protocol, set by the UI
+    pub resize_mode_active: bool, // Whether pane resize mode is active
+    pub show_log_pane: bool,      // Whether the log pane is shown below the content
+    pub show_metrics: bool,       // Whether the performance overlay is shown
+    pub metrics: Metrics,         // Frame times and input latency for the overlay
+    pub focused: bool,            // Whether the terminal has the focus, if it tells
+    pub last_input: Instant,      // When the last key, click or pointer move came in
+    pub zoomed: bool,             // The active pane fills the content area, like tmux zoom
+    pub folders_source: Option<DataSource>, // Where the Folders pane listing came from
+    pub assets_source: Option<DataSource>, // Where the Assets pane listing came from
+    pub pane_layout: PaneLayout,  // Folders beside or above assets
+    pub resize_delta_x: i32,      // Horizontal resize adjustment
+    pub resize_delta_y: i32,      // Vertical resize adjustment
+    pub search_results: Vec<Asset>, // Store search results separately from folder assets
     pub search_modal_focus: SearchModalFocus, // Track which element has focus in search modal
-    pub selected_search_result_index: usize,  // Track selected index in search results separately
-    pub geometric_match_results: Vec<(Asset, f64)>,  // Store geometric match results with similarity scores
-    pub show_geometric_match_modal: bool,     // Whether to show the geometric match modal
+    pub search_scope: SearchScope,
+    pub search_mode: SearchMode, // Plain text, glob or regex
+    pub search_sort: Option<(SearchSortColumn, bool)>, // Sort column of the results and whether it is descending
+    pub search_column_offset: usize, // Result columns scrolled past, after the pinned name
+    pub search_history: Vec<String>, // Queries of this session, oldest first
+    pub search_history_index: Option<usize>, // History entry shown in the input while browsing it
+    pub search_save_name: Option<TextInput>, // Name being typed for saving the current query
+    pub show_saved_searches: bool,   // Whether the search dialog lists the saved searches
+    pub selected_saved_search: usize,
+    pub selected_search_result_index: usize, // Track selected index in search results separately
+    pub geometric_match_results: Vec<(Asset, f64)>, // Store geometric match results with similarity scores
+    pub geometric_match_source: Option<String>,     // UUID of the asset the results match
+    pub geometric_match_source_folder: Option<String>, // Folder of that asset, when it is known
+    pub geometric_match_min_similarity: Option<f64>, // Matches below this (%) are hidden
+    pub geometric_match_hide_self: bool,            // Hide the matched asset itself
+    pub geometric_match_hide_same_folder: bool,     // Hide matches in the matched asset's folder
+    pub match_queue: Vec<(Asset, f64)>, // Matches queued for review, in the order queued
+    pub match_review: Option<Review>,   // Review of the queued matches; kept when closed
+    pub match_session_started: Option<DateTime<Local>>, // When the shown match results were first saved
+    pub match_history: Option<MatchHistory>, // Saved match sessions, while the list is open
+    pub interrupted_jobs: Option<InterruptedJobs>, // Jobs to resume, offered on launch
+    pub property_schema: Option<PropertySchema>, // Property definitions and their usage, while open
+    pub metadata_rules: Rules,               // The metadata rules of the config, compiled
+    pub validation_report: Option<ValidationReport>, // Metadata rule violations, while listed
+    pub metadata_import: Option<ImportPlan>, // Preview of a CSV metadata import
+    pub setup: Option<Setup>,                // The first-run setup, while it is open
+    pub profile_picker: Option<usize>,       // Profile selected in the startup picker, while open
+    pub tour: Option<Tour>,                  // The tour of the screen, while it is running
+    pub help: Option<Help>,                  // The help screen, while it is open
     pub geometric_match_scroll_position: usize, // Track scroll position in geometric match results
     pub geometric_match_horizontal_scroll: u16, // Track horizontal scroll position for many columns
-    pub show_asset_details_modal: bool,       // Whether to show the asset details modal
+    pub where_used_results: Vec<Asset>,      // Assemblies that contain the part looked up with 'w'
+    pub where_used_part: Option<String>,     // Part shown in the where-used modal, while it is open
+    pub where_used_selected: usize,          // Selected assembly in the where-used modal
     pub selected_asset_details: Option<AssetDetails>, // Details of the selected asset
+    pub asset_details_scroll: u16,           // Scroll offset of the asset details modal
     pub last_entered_folder_path: Option<String>, // Track the last folder entered to re-select it when going back
-    pub clipboard: Option<arboard::Clipboard>, // Clipboard for copying log entries
+    pub clipboard: Option<arboard::Clipboard>,    // Clipboard for copying log entries
+    pub config: Config,                           // User configuration loaded at startup
+    pub theme: Theme,                             // Active color palette used by the UI
+    pub theme_name: String,                       // Name of the active theme
+    pub color_support: ColorSupport,              // Colors the terminal can display
 }
 
 impl std::fmt::Debug for App {
@@ -102,41 +464,384 @@ impl std::fmt::Debug for App {
             .field("selected_asset_index", &self.selected_asset_index)
             .field("search_query", &self.search_query)
             .field("status_message", &self.status_message)
+            .field("notifications", &self.notifications)
+            .field("show_notification_history", &self.show_notification_history)
             .field("should_quit", &self.should_quit)
             .field("active_pane", &self.active_pane)
             .field("folder_cache", &self.folder_cache)
-            .field("assets_loading_for_selection", &self.assets_loading_for_selection)
+            .field(
+                "assets_loading_for_selection",
+                &self.assets_loading_for_selection,
+            )
             .field("last_executed_command", &self.last_executed_command)
             .field("command_history", &self.command_history)
+            .field("command_history_index", &self.command_history_index)
+            .field("command_edit_buffer", &self.command_edit_buffer)
+            .field("raw_command_input", &self.raw_command_input)
+            .field("raw_result", &self.raw_result)
+            .field("raw_output_scroll", &self.raw_output_scroll)
+            .field("json_viewer", &self.json_viewer)
             .field("log_entries", &self.log_entries)
+            .field("last_error", &self.last_error)
+            .field("last_download", &self.last_download)
+            .field("downloaded", &self.downloaded)
+            .field("undo", &self.undo)
+            .field("pending_external", &self.pending_external)
+            .field("custom_command_menu", &self.custom_command_menu)
+            .field("assembly_download_prompt", &self.assembly_download_prompt)
+            .field("confirm", &self.confirm)
+            .field("finder", &self.finder)
+            .field("folder_picker", &self.folder_picker)
+            .field("basket", &self.basket)
+            .field("deletions", &self.deletions)
+            .field("notes", &self.notes)
+            .field("tags", &self.tags)
+            .field("tag_picker", &self.tag_picker)
+            .field("tag_filter", &self.tag_filter)
+            .field("all_assets", &self.all_assets)
+            .field("diff_mark", &self.diff_mark)
+            .field("folder_diff", &self.folder_diff)
+            .field("asset_diff_mark", &self.asset_diff_mark)
+            .field("metadata_diff", &self.metadata_diff)
+            .field("prompt", &self.prompt)
+            .field("sync_plan", &self.sync_plan)
+            .field("pipeline", &self.pipeline)
+            .field("error_modal", &self.error_modal)
+            .field("error_modal_scroll", &self.error_modal_scroll)
+            .field("last_operation", &self.last_operation)
+            .field("timed_out_operation", &self.timed_out_operation)
             .field("log_scroll_position", &self.log_scroll_position)
-            .field("show_search_modal", &self.show_search_modal)
+            .field("log_filter", &self.log_filter)
+            .field("log_filter_text", &self.log_filter_text)
+            .field("log_filter_editing", &self.log_filter_editing)
+            .field("log_detail_index", &self.log_detail_index)
+            .field("log_detail_scroll", &self.log_detail_scroll)
+            .field("active_pane_area", &self.active_pane_area)
+            .field("jobs_indicator", &self.jobs_indicator)
+            .field("hint_targets", &self.hint_targets)
+            .field("asset_drag", &self.asset_drag)
+            .field("hovered_asset", &self.hovered_asset)
+            .field("cell_editor", &self.cell_editor)
+            .field("asset_table", &self.asset_table)
+            .field("table_version", &self.table_version)
+            .field("screen_areas", &self.screen_areas)
+            .field("modals", &self.modals)
             .field("search_input_buffer", &self.search_input_buffer)
             .field("command_in_progress", &self.command_in_progress)
+            .field("command_started", &self.command_started)
+            .field("selection_load_at", &self.selection_load_at)
+            .field("prefetcher", &self.prefetcher)
+            .field("hooks", &self.hooks)
+            .field("jobs", &self.jobs)
+            .field("previewer", &self.previewer)
+            .field("geometry", &self.geometry)
+            .field("geometry_unit", &self.geometry_unit)
+            .field("show_preview", &self.show_preview)
+            .field("preview_pending", &self.preview_pending)
+            .field("preview_graphics", &self.preview_graphics)
             .field("resize_mode_active", &self.resize_mode_active)
+            .field("show_log_pane", &self.show_log_pane)
+            .field("show_metrics", &self.show_metrics)
+            .field("metrics", &self.metrics)
+            .field("focused", &self.focused)
+            .field("last_input", &self.last_input)
+            .field("zoomed", &self.zoomed)
+            .field("folders_source", &self.folders_source)
+            .field("assets_source", &self.assets_source)
+            .field("pane_layout", &self.pane_layout)
             .field("resize_delta_x", &self.resize_delta_x)
             .field("resize_delta_y", &self.resize_delta_y)
             .field("search_results", &self.search_results)
             .field("search_modal_focus", &self.search_modal_focus)
-            .field("selected_search_result_index", &self.selected_search_result_index)
+            .field("search_scope", &self.search_scope)
+            .field("search_mode", &self.search_mode)
+            .field("search_sort", &self.search_sort)
+            .field("search_column_offset", &self.search_column_offset)
+            .field("search_history", &self.search_history)
+            .field("search_history_index", &self.search_history_index)
+            .field("search_save_name", &self.search_save_name)
+            .field("show_saved_searches", &self.show_saved_searches)
+            .field("selected_saved_search", &self.selected_saved_search)
+            .field(
+                "selected_search_result_index",
+                &self.selected_search_result_index,
+            )
             .field("geometric_match_results", &self.geometric_match_results)
-            .field("show_geometric_match_modal", &self.show_geometric_match_modal)
-            .field("show_asset_details_modal", &self.show_asset_details_modal)
+            .field("geometric_match_source", &self.geometric_match_source)
+            .field(
+                "geometric_match_source_folder",
+                &self.geometric_match_source_folder,
+            )
+            .field(
+                "geometric_match_min_similarity",
+                &self.geometric_match_min_similarity,
+            )
+            .field("geometric_match_hide_self", &self.geometric_match_hide_self)
+            .field(
+                "geometric_match_hide_same_folder",
+                &self.geometric_match_hide_same_folder,
+            )
+            .field("match_queue", &self.match_queue)
+            .field("match_review", &self.match_review)
+            .field("match_session_started", &self.match_session_started)
+            .field("match_history", &self.match_history)
+            .field("interrupted_jobs", &self.interrupted_jobs)
+            .field("property_schema", &self.property_schema)
+            .field("metadata_rules", &self.metadata_rules)
+            .field("validation_report", &self.validation_report)
+            .field("metadata_import", &self.metadata_import)
+            .field("setup", &self.setup)
+            .field("profile_picker", &self.profile_picker)
+            .field("tour", &self.tour)
+            .field("help", &self.help)
+            .field("where_used_results", &self.where_used_results)
+            .field("where_used_part", &self.where_used_part)
+            .field("where_used_selected", &self.where_used_selected)
             .field("selected_asset_details", &self.selected_asset_details)
+            .field("asset_details_scroll", &self.asset_details_scroll)
             .field("last_entered_folder_path", &self.last_entered_folder_path)
             .field("clipboard", &"Option<Clipboard>") // Skip printing clipboard content
+            .field("config", &self.config)
+            .field("theme_name", &self.theme_name)
+            .field("color_support", &self.color_support)
             .finish()
     }
 }
 
+// How the folder and asset panes are arranged, cycled with '|'
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneLayout {
+    Auto,       // Stacked in terminals narrower than layout.stack_below
+    SideBySide, // Folders left of assets
+    Stacked,    // Folders above assets
+}
+
+impl PaneLayout {
+    pub fn next(self) -> Self {
+        match self {
+            PaneLayout::Auto => PaneLayout::SideBySide,
+            PaneLayout::SideBySide => PaneLayout::Stacked,
+            PaneLayout::Stacked => PaneLayout::Auto,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaneLayout::Auto => "automatic",
+            PaneLayout::SideBySide => "side by side",
+            PaneLayout::Stacked => "stacked",
+        }
+    }
+}
+
+// Status filter for the Log view, cycled with 's'
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFilter {
+    All,
+    Success,
+    Error,
+    Cached,
+}
+
+impl LogFilter {
+    pub fn next(self) -> Self {
+        match self {
+            LogFilter::All => LogFilter::Success,
+            LogFilter::Success => LogFilter::Error,
+            LogFilter::Error => LogFilter::Cached,
+            LogFilter::Cached => LogFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFilter::All => "All",
+            LogFilter::Success => "Success",
+            LogFilter::Error => "Error",
+            LogFilter::Cached => "Cached",
+        }
+    }
+
+    fn matches(self, entry: &LogEntry) -> bool {
+        match self {
+            LogFilter::All => true,
+            LogFilter::Success => entry.status == LogStatus::Success,
+            LogFilter::Error => matches!(entry.status, LogStatus::Error | LogStatus::Timeout),
+            LogFilter::Cached => entry.status == LogStatus::Cached,
+        }
+    }
+}
+
+// What the text typed into the prompt in the hint bar is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptAction {
+    Upload,                       // File to upload to the current folder
+    UploadAndMatch,               // File to upload to the staging folder and geometric-match
+    Sync(String),                 // Local directory to sync the folder with
+    Move(Asset),                  // Remote folder to move the asset into
+    Rename(Asset),                // New name of the asset
+    CreateFolder(Option<String>), // Name of a new folder in this one (None: at the top level)
+    BasketMetadata,               // "name=value" to set on every asset in the basket
+    Note(String),                 // Local note on the asset with this UUID; empty removes it
+    MinSimilarity, // Lowest similarity (%) of the geometric matches shown; empty shows all
+    CreateProperty, // "name" or "name:type" of a new metadata property definition
+    ImportMetadata, // CSV file of metadata values to preview and apply
+}
+
+// What the folder chosen in the folder picker is for
+#[derive(Debug, Clone)]
+pub enum PickAction {
+    MoveFolder(Folder), // New parent of the folder
+    MoveBasket,         // Where every asset in the basket goes
+}
+
+// What answering yes to a confirmation does
+#[derive(Debug, Clone)]
+pub enum ConfirmAction {
+    DeleteStaged,                              // Delete the assets staged for deletion
+    Overwrite(Asset), // Download the asset over the local file of the same name
+    Redownload { uuid: String, name: String }, // Try again after a download came out the wrong size
+    Upload(PathBuf),  // Upload a file to the current folder, e.g. from a clipboard path
+    Quit,             // Quit although background jobs are still running
+    DeleteProperty(String), // Delete the metadata property definition by this name
+    DownloadFolder(FolderDownload), // Download the listed folder tree
+}
+
+// A local file path as file managers and export dialogs put it on the clipboard: possibly
+// quoted, as a file:// URL with percent-escapes, or starting with "~"
+fn clipboard_path(text: &str) -> Option<PathBuf> {
+    let text = text
+        .lines()
+        .next()?
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'');
+    let path = match text.strip_prefix("file://") {
+        Some(url) => {
+            let mut bytes = Vec::with_capacity(url.len());
+            let mut rest = url.as_bytes();
+            while let Some((&byte, tail)) = rest.split_first() {
+                let escaped = tail
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(decoded) if byte == b'%' => {
+                        bytes.push(decoded);
+                        rest = &tail[2..];
+                    }
+                    _ => {
+                        bytes.push(byte);
+                        rest = tail;
+                    }
+                }
+            }
+            PathBuf::from(String::from_utf8(bytes).ok()?)
+        }
+        None => config::expand_home(text),
+    };
+    path.is_file().then_some(path)
+}
+
+// A metadata property and its value, as name=value
+fn metadata_assignment(input: &str) -> Result<(), String> {
+    dialog::not_empty(input)?;
+    match input.split_once('=') {
+        Some((name, _)) if !name.trim().is_empty() => Ok(()),
+        _ => Err("type name=value".to_string()),
+    }
+}
+
+// A percentage from 0 to 100, or nothing
+fn optional_percentage(input: &str) -> Result<(), String> {
+    match input.trim() {
+        "" => Ok(()),
+        input => match input.trim_end_matches('%').trim().parse::<f64>() {
+            Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(()),
+            _ => Err("type a percentage from 0 to 100".to_string()),
+        },
+    }
+}
+
+// "name" or "name:type" of a metadata property
+fn property_definition(input: &str) -> Result<(), String> {
+    if schema::parse_definition(input).0.is_empty() {
+        return Err("type name or name:type".to_string());
+    }
+    Ok(())
+}
+
+// Local paths typed into a prompt may start with "~"
+fn existing_file(input: &str) -> Result<(), String> {
+    dialog::not_empty(input)?;
+    if !config::expand_home(input).is_file() {
+        return Err("no such file".to_string());
+    }
+    Ok(())
+}
+
+fn existing_directory(input: &str) -> Result<(), String> {
+    dialog::not_empty(input)?;
+    if !config::expand_home(input).is_dir() {
+        return Err("no such directory".to_string());
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SearchModalFocus {
     Input,
     Results,
 }
 
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+// Column the search results are sorted by
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchSortColumn {
+    Name,
+    Folder,
+    Size,
+    Metadata(String),
+}
+
+impl SearchSortColumn {
+    pub fn label(&self) -> &str {
+        match self {
+            SearchSortColumn::Name => "Name",
+            SearchSortColumn::Folder => "Folder",
+            SearchSortColumn::Size => "Size",
+            SearchSortColumn::Metadata(key) => key,
+        }
+    }
+}
+
+// How much of the tenant a search covers, relative to the current folder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    #[default]
+    Tenant,
+    Folder,  // Only the assets directly in the current folder
+    Subtree, // The current folder and everything below it
+}
+
+impl SearchScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchScope::Tenant => "whole tenant",
+            SearchScope::Folder => "current folder",
+            SearchScope::Subtree => "folder subtree",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            SearchScope::Tenant => SearchScope::Folder,
+            SearchScope::Folder => SearchScope::Subtree,
+            SearchScope::Subtree => SearchScope::Tenant,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct AssetDetails {
     pub uuid: String,
     pub name: String,
@@ -150,11 +855,184 @@ pub struct AssetDetails {
     pub tenant_id: String,
     pub folder_id: String,
     pub state: String,
+    pub metadata: serde_json::Value,
+}
+
+// A pcli2-backed operation that can be run again, e.g. after it timed out
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    LoadFolders,
+    LoadAssets,
+    LoadSelectedFolderAssets,
+    Search(String),
+    AssetDetails,
+    GeometricMatch(String),
+    WhereUsed(String),
+    Download { uuid: String, name: String },
+    BrowseFolder(Option<String>), // List the subfolders of a path (None for the root)
+    BrowseAssets(String),         // List the assets of a folder path
+    ShowAsset(String),            // Show the details of an asset by UUID
+}
+
+impl Operation {
+    // Recognize a pcli2 command issued by the TUI, e.g. from the command history
+    pub fn from_command(command: &PcliCommand) -> Option<Operation> {
+        let rest = &command.args;
+
+        let option = |name: &str| {
+            rest.iter()
+                .position(|arg| arg == name)
+                .and_then(|i| rest.get(i + 1))
+                .cloned()
+        };
+
+        match (rest.first()?.as_str(), rest.get(1)?.as_str()) {
+            ("folder", "list") => Some(Operation::BrowseFolder(option("--folder-path"))),
+            ("asset", "list") => option("--folder-path").map(Operation::BrowseAssets),
+            ("asset", "text-match") => option("--text").map(Operation::Search),
+            ("asset", "get") => option("--uuid").map(Operation::ShowAsset),
+            ("asset", "geometric-match") => option("--uuid").map(Operation::GeometricMatch),
+            ("asset", "where-used") => option("--uuid").map(Operation::WhereUsed),
+            ("asset", "download") => option("--uuid").map(|uuid| Operation::Download {
+                name: uuid.clone(),
+                uuid,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Result of a command typed in ':' command mode
+#[derive(Debug, Clone)]
+pub struct RawCommandResult {
+    pub command_line: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub message: Option<String>, // Error that isn't part of the command output, e.g. a timeout
+}
+
+// Everything known about a failed command, shown in the error detail modal
+#[derive(Debug, Clone)]
+pub struct ErrorDetail {
+    pub timestamp: DateTime<Local>,
+    pub command_line: String,
+    pub exit_code: Option<i32>,
+    pub message: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ErrorDetail {
+    // Capture the full output when the error came from pcli2 itself; otherwise keep the message
+    pub fn from_error(error: &anyhow::Error, command_line: &str) -> Self {
+        if let Some(timeout) = error.downcast_ref::<PcliTimeout>() {
+            return Self {
+                timestamp: Local::now(),
+                command_line: timeout.command_line.clone(),
+                exit_code: None,
+                message: timeout.to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+            };
+        }
+
+        match error.downcast_ref::<PcliError>() {
+            Some(pcli_error) => Self {
+                timestamp: Local::now(),
+                command_line: pcli_error.command_line.clone(),
+                exit_code: pcli_error.exit_code,
+                message: pcli_error.to_string(),
+                stdout: pcli_error.stdout.clone(),
+                stderr: pcli_error.stderr.clone(),
+            },
+            None => Self {
+                timestamp: Local::now(),
+                command_line: command_line.to_string(),
+                exit_code: None,
+                message: format!("{:#}", error),
+                stdout: String::new(),
+                stderr: String::new(),
+            },
+        }
+    }
+
+    // Plain-text rendering used for the modal and for copying to the clipboard
+    pub fn to_text(&self) -> String {
+        let exit_code = self
+            .exit_code
+            .map_or_else(|| "n/a".to_string(), |code| code.to_string());
+        let mut text = format!(
+            "Time: {}\nCommand: {}\nExit code: {}\n\nError: {}\n",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.command_line,
+            exit_code,
+            self.message
+        );
+
+        if !self.stderr.trim().is_empty() {
+            text.push_str(&format!("\n--- stderr ---\n{}\n", self.stderr.trim_end()));
+        }
+        if !self.stdout.trim().is_empty() {
+            text.push_str(&format!("\n--- stdout ---\n{}\n", self.stdout.trim_end()));
+        }
+
+        text
+    }
 }
 
 impl App {
-    pub fn new() -> Self {
-        Self {
+    pub fn new(config: Config) -> Self {
+        let mut startup_warnings = Vec::new();
+
+        // Downgrade colors for terminals without true color support (or with NO_COLOR set)
+        let color_support =
+            ColorSupport::from_config(config.color_mode.as_deref()).unwrap_or_else(|e| {
+                startup_warnings.push(e.to_string());
+                ColorSupport::detect()
+            });
+
+        // Resolve the configured theme once; fall back to the default palette if it's invalid
+        let requested_theme = config
+            .theme
+            .clone()
+            .unwrap_or_else(|| theme::DEFAULT_THEME.to_string());
+        let (theme, theme_name) = match Theme::resolve(&requested_theme, &config) {
+            Ok(theme) => (theme, requested_theme),
+            Err(e) => {
+                startup_warnings.push(format!("{}; using the default theme", e));
+                (Theme::default(), theme::DEFAULT_THEME.to_string())
+            }
+        };
+
+        pcli_commands::set_retry_policy(config.retry.clone());
+        pcli_commands::set_timeout_policy(config.timeouts.clone());
+        pcli_commands::set_concurrency_limit(config.concurrency.max_commands);
+        pcli_commands::set_invocation(&config);
+        permissions::configure(&config);
+        config::set_data_profile(config.active_profile.as_deref());
+
+        let notes = Notes::load().unwrap_or_else(|e| {
+            startup_warnings.push(format!("{:#}; notes are kept for this session only", e));
+            Notes::default()
+        });
+        let tags = Tags::load(&config.tags).unwrap_or_else(|e| {
+            startup_warnings.push(format!("{:#}; tags are kept for this session only", e));
+            Tags::default()
+        });
+        let (metadata_rules, rule_warnings) = Rules::compile(&config.metadata_rules);
+        startup_warnings.extend(rule_warnings);
+        // The demo tenant's folders stay out of the real cache
+        let folder_cache = if demo::is_enabled() {
+            HashMap::new()
+        } else {
+            offline::load_cache().unwrap_or_else(|e| {
+                startup_warnings.push(format!("{:#}; starting with an empty cache", e));
+                HashMap::new()
+            })
+        };
+
+        let mut app = Self {
             current_state: AppState::Folders,
             folders: vec![],
             assets: vec![],
@@ -163,656 +1041,1533 @@ impl App {
             selected_asset_index: 0,
             search_query: String::new(),
             status_message: "Ready".to_string(),
+            notifications: Notifications::default(),
+            show_notification_history: false,
             should_quit: false,
             active_pane: ActivePane::Folders,
-            folder_cache: HashMap::new(),
+            folder_cache,
             assets_loading_for_selection: false,
-            last_executed_command: String::new(),
+            last_executed_command: PcliCommand::default(),
             command_history: Vec::new(),
+            command_history_index: 0,
+            command_edit_buffer: None,
+            raw_command_input: None,
+            raw_result: None,
+            raw_output_scroll: 0,
+            json_viewer: None,
             log_entries: Vec::new(),
+            last_error: None,
+            last_download: None,
+            downloaded: HashMap::new(),
+            undo: UndoHistory::default(),
+            pending_external: None,
+            custom_command_menu: None,
+            assembly_download_prompt: None,
+            confirm: None,
+            finder: None,
+            folder_picker: None,
+            basket: Basket::default(),
+            deletions: Basket::default(),
+            notes,
+            tags,
+            tag_picker: None,
+            tag_filter: None,
+            all_assets: vec![],
+            diff_mark: None,
+            folder_diff: None,
+            asset_diff_mark: None,
+            metadata_diff: None,
+            prompt: None,
+            sync_plan: None,
+            pipeline: MatchPipeline::new(),
+            error_modal: None,
+            error_modal_scroll: 0,
+            last_operation: None,
+            timed_out_operation: None,
             log_scroll_position: 0,
-            show_search_modal: false,
-            search_input_buffer: String::new(),
+            log_filter: LogFilter::All,
+            log_filter_text: String::new(),
+            log_filter_editing: false,
+            log_detail_index: None,
+            log_detail_scroll: 0,
+            log_detail_copy_button: None,
+            active_pane_area: None,
+            jobs_indicator: None,
+            hint_targets: Vec::new(),
+            asset_drag: None,
+            hovered_asset: None,
+            cell_editor: None,
+            asset_table: None,
+            table_version: 0,
+            screen_areas: ScreenAreas::default(),
+            modals: ModalStack::default(),
+            search_input_buffer: TextInput::default(),
             command_in_progress: false,
+            command_started: None,
+            selection_load_at: None,
+            prefetcher: Prefetcher::new(),
+            hooks: Hooks::new(config.hooks.clone()),
+            jobs: Jobs::new(),
+            previewer: Previewer::new(Protocol::detect(&config.preview.protocol)),
+            geometry: GeometryFetcher::new(),
+            geometry_unit: LengthUnit::parse(&config.geometry.units).unwrap_or_else(|| {
+                tracing::warn!(units = %config.geometry.units, "unknown geometry units, using mm");
+                LengthUnit::Millimeter
+            }),
+            show_preview: config.preview.enabled,
+            preview_pending: None,
+            preview_graphics: None,
             resize_mode_active: false,
+            show_log_pane: true,
+            show_metrics: false,
+            metrics: Metrics::default(),
+            focused: true,
+            last_input: Instant::now(),
+            zoomed: false,
+            folders_source: None,
+            assets_source: None,
+            pane_layout: PaneLayout::Auto,
             resize_delta_x: 0,
             resize_delta_y: 0,
             search_results: vec![],
             search_modal_focus: SearchModalFocus::Input,
+            search_scope: SearchScope::Tenant,
+            search_mode: SearchMode::Text,
+            search_sort: None,
+            search_column_offset: 0,
+            search_history: Vec::new(),
+            search_history_index: None,
+            search_save_name: None,
+            show_saved_searches: false,
+            selected_saved_search: 0,
             selected_search_result_index: 0,
             geometric_match_results: vec![],
-            show_geometric_match_modal: false,
+            geometric_match_source: None,
+            geometric_match_source_folder: None,
+            geometric_match_min_similarity: None,
+            geometric_match_hide_self: false,
+            geometric_match_hide_same_folder: false,
+            match_queue: Vec::new(),
+            match_review: None,
+            match_session_started: None,
+            match_history: None,
+            interrupted_jobs: None,
+            property_schema: None,
+            metadata_rules,
+            validation_report: None,
+            metadata_import: None,
+            setup: None,
+            profile_picker: None,
+            tour: None,
+            help: None,
             geometric_match_scroll_position: 0,
             geometric_match_horizontal_scroll: 0,
-            show_asset_details_modal: false,
+            where_used_results: Vec::new(),
+            where_used_part: None,
+            where_used_selected: 0,
             selected_asset_details: None,
+            asset_details_scroll: 0,
             last_entered_folder_path: None,
-            clipboard: {
-                // Initialize the clipboard if available
-                match arboard::Clipboard::new() {
-                    Ok(clipboard) => Some(clipboard),
-                    Err(_) => {
-                        // If clipboard initialization fails, continue without it
-                        None
-                    }
-                }
-            },
+            // Initialize the clipboard if available; continue without it if that fails
+            clipboard: arboard::Clipboard::new().ok(),
+            config,
+            theme: theme.adapt(color_support),
+            theme_name,
+            color_support,
+        };
+
+        for warning in startup_warnings {
+            app.notify(NotificationLevel::Warning, warning);
         }
+        app.set_current_folder(app.profile_start_folder());
+
+        app
     }
 
-    pub fn copy_selected_log_entry_to_clipboard(&mut self) {
-        if !self.log_entries.is_empty() && self.log_scroll_position < self.log_entries.len() {
-            let log_entry = &self.log_entries[self.log_scroll_position];
+    // Folder a profile starts in; None for the top level
+    fn profile_start_folder(&self) -> Option<String> {
+        let folder = self
+            .config
+            .profile()?
+            .default_folder
+            .as_deref()?
+            .trim_matches('/');
+        (!folder.is_empty()).then(|| folder.to_string())
+    }
 
-            if let Some(ref mut clipboard) = self.clipboard {
-                if let Err(e) = clipboard.set_text(log_entry) {
-                    self.status_message = format!("Failed to copy to clipboard: {}", e);
-                } else {
-                    self.status_message = "Log entry copied to clipboard".to_string();
+    // Let the user pick one of the configured profiles before anything is loaded
+    pub fn open_profile_picker(&mut self) {
+        self.profile_picker = Some(0);
+        self.modals.push(Modal::ProfilePicker);
+    }
+
+    async fn handle_profile_picker_keys(&mut self, key: KeyEvent) {
+        let Some(selected) = self.profile_picker else {
+            return;
+        };
+        let last = self.config.profiles.len().saturating_sub(1);
+        match KeyContext::ProfilePicker.action(key) {
+            Some(ViewAction::Up) => {
+                self.profile_picker = Some(selected.saturating_sub(1));
+            }
+            Some(ViewAction::Down) => {
+                self.profile_picker = Some((selected + 1).min(last));
+            }
+            Some(ViewAction::Open) => {
+                let name = self.config.profiles.keys().nth(selected).cloned();
+                self.close_modal(Modal::ProfilePicker);
+                if let Some(name) = name {
+                    self.use_profile(&name);
                 }
-            } else {
-                self.status_message = "Clipboard not available".to_string();
+                self.load_start().await;
+            }
+            // Go on without a profile, with pcli2 as it is set up
+            Some(ViewAction::Back) => {
+                self.close_modal(Modal::ProfilePicker);
+                self.load_start().await;
             }
+            _ => {}
         }
     }
 
-    pub async fn handle_key_event(&mut self, key: KeyEvent) {
-        // Handle geometric match modal if it's active - make it modal and prevent other interactions
-        if self.show_geometric_match_modal {
-            self.handle_geometric_match_keys(key).await;
+    // Switch pcli2, the data kept between sessions and the start folder to a profile
+    fn use_profile(&mut self, name: &str) {
+        if let Err(e) = self.config.use_profile(name) {
+            self.notify(NotificationLevel::Error, format!("{:#}", e));
             return;
         }
+        pcli_commands::set_invocation(&self.config);
+        permissions::configure(&self.config);
+        config::set_data_profile(Some(name));
 
-        // Handle asset details modal if it's active
-        if self.show_asset_details_modal {
-            // Handle closing the asset details modal
-            if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
-                self.show_asset_details_modal = false;
-                return;
-            }
-        }
+        self.notes = Notes::load().unwrap_or_else(|e| {
+            self.notifications.push(
+                NotificationLevel::Warning,
+                format!("{:#}; notes are kept for this session only", e),
+            );
+            Notes::default()
+        });
+        self.tags = Tags::load(&self.config.tags).unwrap_or_else(|e| {
+            self.notifications.push(
+                NotificationLevel::Warning,
+                format!("{:#}; tags are kept for this session only", e),
+            );
+            Tags::default()
+        });
+        self.folder_cache = offline::load_cache().unwrap_or_else(|e| {
+            self.notifications.push(
+                NotificationLevel::Warning,
+                format!("{:#}; starting with an empty cache", e),
+            );
+            HashMap::new()
+        });
+        self.set_current_folder(self.profile_start_folder());
+        self.table_changed(); // Its notes and tags
+        self.notify(
+            NotificationLevel::Info,
+            format!("Using the {} profile", name),
+        );
+    }
 
-        // Handle search modal if it's active - make it modal and prevent other interactions
-        if self.show_search_modal {
-            self.handle_search_keys(key).await;
+    // Load the folders to start with, and the assets of the first one
+    pub async fn load_start(&mut self) {
+        self.offer_interrupted_jobs();
+        self.load_folders_for_current_context().await;
+        if self.folders.is_empty() {
             return;
         }
+        // Select the first folder (skip parent indicator if present)
+        if self.folders[0].uuid == ".." && self.folders.len() > 1 {
+            self.selected_folder_index = 1;
+        } else {
+            self.selected_folder_index = 0;
+        }
+        self.load_assets_for_selected_folder().await;
+    }
 
-        // Handle global keys that work in any state
-        // Only allow pane cycling when search modal is not active
-        if key.code == KeyCode::Tab && !key.modifiers.contains(crossterm::event::KeyModifiers::ALT)
-        {
-            // Cycle between panes forward (Tab without Alt)
-            self.active_pane = match self.active_pane {
-                ActivePane::Folders => ActivePane::Assets,
-                ActivePane::Assets => ActivePane::Log,
-                ActivePane::Log => ActivePane::Folders,
-            };
+    // Report the outcome of an operation as a toast; it also becomes the current status
+    pub fn notify(&mut self, level: NotificationLevel, message: impl Into<String>) {
+        let message = message.into();
+        self.status_message = message.clone();
+        self.notifications.push(level, message);
+    }
+
+    // Switch to the next available theme (built-ins first, then custom themes from the config)
+    // Open the guided setup, e.g. on the first start when there is no config file yet
+    pub fn start_setup(&mut self) {
+        let directory = match self.config.downloads.directory {
+            Some(ref directory) => directory.clone(),
+            None => self
+                .config
+                .downloads
+                .directory_for("")
+                .display()
+                .to_string(),
+        };
+        let themes = Theme::available_names(&self.config);
+        self.setup = Some(Setup::new(directory, themes, &self.theme_name));
+        self.modals.push(Modal::Setup);
+    }
+
+    async fn handle_setup_keys(&mut self, key: KeyEvent) {
+        let Some(step) = self.setup.as_ref().map(|setup| setup.step) else {
             return;
-        } else if key.code == KeyCode::BackTab
-            || (key.code == KeyCode::Tab
-                && key
-                    .modifiers
-                    .contains(crossterm::event::KeyModifiers::SHIFT))
-        {
-            // Cycle between panes in reverse order (Shift+Tab or BackTab)
-            self.active_pane = match self.active_pane {
-                ActivePane::Folders => ActivePane::Log,
-                ActivePane::Assets => ActivePane::Folders,
-                ActivePane::Log => ActivePane::Assets,
-            };
+        };
+        // Esc leaves the setup; the config file is written anyway so it doesn't open again
+        if key.code == KeyCode::Esc {
+            if step != SetupStep::Done {
+                self.skip_setup();
+            }
+            self.close_modal(Modal::Setup);
             return;
         }
+        match step {
+            SetupStep::Welcome if key.code == KeyCode::Enter => self.run_setup_checks().await,
+            SetupStep::Welcome => {}
+            SetupStep::Checks => match key.code {
+                KeyCode::Enter => self.set_setup_step(SetupStep::Downloads),
+                KeyCode::Char('r') => self.run_setup_checks().await,
+                _ => {}
+            },
+            SetupStep::Downloads => {
+                let Some(ref mut setup) = self.setup else {
+                    return;
+                };
+                if setup.directory.handle_key(key) || key.code != KeyCode::Enter {
+                    return;
+                }
+                let directory = config::expand_home(setup.directory.as_str().trim());
+                if directory.as_os_str().is_empty() {
+                    self.notify(
+                        NotificationLevel::Warning,
+                        "Type the directory downloads go to",
+                    );
+                } else if directory.exists() && !directory.is_dir() {
+                    let message = format!("{} is not a directory", directory.display());
+                    self.notify(NotificationLevel::Warning, message);
+                } else {
+                    self.set_setup_step(SetupStep::Theme);
+                }
+            }
+            SetupStep::Theme => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.move_setup_theme(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_setup_theme(1),
+                KeyCode::Enter => self.finish_setup(),
+                _ => {}
+            },
+            SetupStep::Done => match key.code {
+                KeyCode::Enter | KeyCode::Char('t') => {
+                    self.close_modal(Modal::Setup);
+                    self.start_tour();
+                }
+                KeyCode::Char('q') => self.close_modal(Modal::Setup),
+                _ => {}
+            },
+        }
+    }
 
-        // Handle pane resize mode activation (Ctrl+N)
-        if key.code == KeyCode::Char('n')
-            && key
-                .modifiers
-                .contains(crossterm::event::KeyModifiers::CONTROL)
-        {
-            self.resize_mode_active = true;
-            self.current_state = AppState::PaneResize;
-            self.status_message =
-                "Resize mode: Use arrow keys to resize, Enter to confirm, Esc to cancel"
-                    .to_string();
-            return;
+    fn set_setup_step(&mut self, step: SetupStep) {
+        if let Some(ref mut setup) = self.setup {
+            setup.step = step;
         }
+    }
 
-        // Handle help key globally
-        if key.code == KeyCode::Char('h') {
-            self.current_state = AppState::Help;
-            return;
+    // Look for pcli2 on PATH, ask for its version and list the folders to see that it can reach
+    // the tenant
+    async fn run_setup_checks(&mut self) {
+        let pcli2 = setup::find_on_path(&pcli_commands::program());
+        let mut version = None;
+        let mut sign_in = None;
+        if pcli2.is_some() {
+            self.last_executed_command = PcliCommand::new(&["--version"]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            let result = pcli_commands::run_raw(&["--version".to_string()]).await;
+            self.command_in_progress = false;
+            match result {
+                Ok(output) => {
+                    self.log_command_success();
+                    version = Some(output.trim().to_string());
+                }
+                Err(e) => self.log_command_error(&e),
+            }
+
+            self.last_executed_command = PcliCommand::new(&["folder", "list", "--format", "json"]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            let result = pcli_commands::list_folders().await;
+            self.command_in_progress = false;
+            sign_in = Some(match result {
+                Ok(folders) => {
+                    self.log_command_success();
+                    let top_level = folders
+                        .iter()
+                        .filter(|f| !f.path.trim_matches('/').contains('/'))
+                        .count();
+                    Ok(top_level)
+                }
+                Err(e) => {
+                    self.log_command_error(&e);
+                    Err(format!("{:#}", e))
+                }
+            });
         }
+        if let Some(ref mut setup) = self.setup {
+            setup.pcli2 = pcli2;
+            setup.version = version;
+            setup.sign_in = sign_in;
+            setup.step = SetupStep::Checks;
+        }
+    }
 
-        // Handle command history key globally
-        if key.code == KeyCode::Char('c') {
-            self.current_state = AppState::CommandHistory;
+    // Show the theme selected in the setup right away
+    fn move_setup_theme(&mut self, delta: isize) {
+        let Some(ref mut setup) = self.setup else {
             return;
+        };
+        setup.move_theme(delta);
+        let Some(name) = setup.chosen_theme().cloned() else {
+            return;
+        };
+        match Theme::resolve(&name, &self.config) {
+            Ok(theme) => {
+                self.theme = theme.adapt(self.color_support);
+                self.theme_name = name;
+            }
+            Err(e) => self.notify(NotificationLevel::Warning, e.to_string()),
         }
+    }
 
-        // Handle search key globally - show modal instead of changing state
-        if key.code == KeyCode::Char('/') {
-            self.show_search_modal = true;
-            self.search_input_buffer.clear();
+    // Write the chosen download directory and theme to the config file
+    fn finish_setup(&mut self) {
+        let Some(ref mut setup) = self.setup else {
             return;
+        };
+        let directory = setup.directory.as_str().trim().to_string();
+        setup.step = SetupStep::Done;
+        self.config.downloads.directory = Some(directory);
+        self.config.theme = Some(self.theme_name.clone());
+        match self.config.save() {
+            Ok(()) => {
+                let saved = Config::path().map(|path| path.display().to_string());
+                let message = format!("Settings saved to {}", saved.unwrap_or_default());
+                self.notify(NotificationLevel::Success, message);
+            }
+            Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
         }
+    }
 
-        // Handle log view key globally, but only when search modal is not active
-        if key.code == KeyCode::Char('l') && !self.show_search_modal && !self.show_geometric_match_modal {
-            self.current_state = AppState::Log;
-            return;
+    fn skip_setup(&mut self) {
+        match self.config.save() {
+            Ok(()) => self.notify(
+                NotificationLevel::Info,
+                "Setup skipped; pcli2-tui --setup runs it again",
+            ),
+            Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
         }
+    }
+
+    // Walk through the panes of the main view
+    pub fn start_tour(&mut self) {
+        self.current_state = AppState::Folders;
+        self.tour = Some(Tour::default());
+        self.modals.push(Modal::Tour);
+    }
 
-        // Handle search modal if it's active
-        if self.show_search_modal {
-            self.handle_search_keys(key).await;
+    fn handle_tour_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut tour) = self.tour else {
             return;
+        };
+        match key.code {
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l')
+                if tour.is_last() =>
+            {
+                self.close_modal(Modal::Tour);
+                self.notify(
+                    NotificationLevel::Info,
+                    "That's the tour; h lists every key",
+                );
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l') => {
+                tour.next();
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => tour.previous(),
+            KeyCode::Esc | KeyCode::Char('q') => self.close_modal(Modal::Tour),
+            _ => {}
         }
+    }
 
-        match self.current_state {
-            AppState::Folders => self.handle_folder_keys(key).await,
-            AppState::Assets => self.handle_asset_keys(key).await,
-            AppState::Search => self.handle_search_keys(key).await,
-            AppState::Uploading => {
-                // Handle upload specific keys
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.current_state = AppState::Folders;
-                        self.status_message = "Upload mode exited".to_string();
-                    }
-                    KeyCode::Char('u') => {
-                        // Trigger interactive upload
-                        self.upload_asset_interactive().await;
-                    }
-                    _ => {}
+    // The help screen, with the keys of the current view first
+    fn open_help(&mut self) {
+        self.help = Some(Help::new(self.key_context()));
+        self.modals.push(Modal::Help);
+    }
+
+    // Typing narrows the keys; Esc clears the filter, and closes the help once it is empty
+    fn handle_help_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut help) = self.help else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc if !help.filter.as_str().is_empty() => {
+                help.filter.clear();
+                help.scroll = 0;
+            }
+            KeyCode::Esc => self.close_modal(Modal::Help),
+            KeyCode::Up => help.scroll_by(-1),
+            KeyCode::Down => help.scroll_by(1),
+            KeyCode::PageUp => help.scroll_by(-10),
+            KeyCode::PageDown => help.scroll_by(10),
+            KeyCode::Home => help.scroll = 0,
+            KeyCode::End => help.scroll = usize::MAX,
+            _ => {
+                if help.filter.handle_key(key) {
+                    help.scroll = 0;
                 }
             }
-            AppState::Downloading => {
-                // Handle download specific keys
-                if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
-                    self.current_state = AppState::Folders;
-                    self.status_message = "Download mode exited".to_string();
+        }
+    }
+
+    // Which keys apply now, for the hint bar and the help
+    pub fn key_context(&self) -> KeyContext {
+        match self.cell_editor {
+            Some(ref editor) if editor.is_editing() => KeyContext::CellInput,
+            Some(_) => KeyContext::CellEdit,
+            None => KeyContext::of(self.current_state, self.active_pane),
+        }
+    }
+
+    // Every key, by context, for the help screen
+    pub fn key_sections(&self) -> Vec<keymap::KeySection> {
+        keymap::sections(&self.config.custom_commands)
+    }
+
+    pub fn cycle_theme(&mut self) {
+        let names = Theme::available_names(&self.config);
+        let current = names.iter().position(|name| *name == self.theme_name);
+        let mut next = current.map_or(0, |i| (i + 1) % names.len());
+
+        // Skip custom themes that fail to resolve so a broken entry doesn't block cycling
+        for _ in 0..names.len() {
+            match Theme::resolve(&names[next], &self.config) {
+                Ok(theme) => {
+                    self.theme = theme.adapt(self.color_support);
+                    self.theme_name = names[next].clone();
+                    self.notify(
+                        NotificationLevel::Info,
+                        format!("Theme: {}", self.theme_name),
+                    );
+                    return;
+                }
+                Err(e) => {
+                    self.notify(NotificationLevel::Warning, e.to_string());
+                    next = (next + 1) % names.len();
                 }
             }
-            AppState::Help => {
-                // Handle help specific keys
-                if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
-                    // Return to the previous state (default to Folders)
+        }
+    }
+
+    // Put text on the clipboard and report the outcome; `what` names the copied thing
+    pub fn copy_to_clipboard(&mut self, text: String, what: &str) {
+        if let Some(ref mut clipboard) = self.clipboard {
+            if let Err(e) = clipboard.set_text(text) {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Failed to copy to clipboard: {}", e),
+                );
+            } else {
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("{} copied to clipboard", what),
+                );
+            }
+        } else {
+            self.notify(NotificationLevel::Warning, "Clipboard not available");
+        }
+    }
+
+    // Offer to upload the file whose path is on the clipboard to the current folder
+    fn upload_from_clipboard(&mut self) {
+        if self.refuse_offline("Uploading") {
+            return;
+        }
+        let Some(folder) = self.current_folder.clone() else {
+            self.notify(
+                NotificationLevel::Warning,
+                "Open a folder to upload into first",
+            );
+            return;
+        };
+        let Some(ref mut clipboard) = self.clipboard else {
+            self.notify(NotificationLevel::Warning, "Clipboard not available");
+            return;
+        };
+        let text = clipboard.get_text().unwrap_or_default();
+        let Some(path) = clipboard_path(&text) else {
+            self.notify(
+                NotificationLevel::Warning,
+                "The clipboard doesn't hold the path of an existing file",
+            );
+            return;
+        };
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        let detail = path.display().to_string();
+        self.open_confirm(
+            Confirm::new(
+                " Upload from Clipboard ",
+                format!("Upload {} to {}?", name, folder),
+                ConfirmAction::Upload(path),
+            )
+            .with_detail(detail),
+        );
+    }
+
+    // Copy an asset's UUID, or with `full_path` its path, to the clipboard
+    fn copy_asset_reference(&mut self, asset: Option<Asset>, full_path: bool) {
+        let Some(asset) = asset else {
+            return;
+        };
+        if full_path {
+            self.copy_to_clipboard(asset.path, "Asset path");
+        } else {
+            self.copy_to_clipboard(asset.uuid, "Asset UUID");
+        }
+    }
+
+    // The pane filling the content area while zoomed; zoom only applies to the folder and asset
+    // view, and follows the active pane
+    pub fn zoomed_pane(&self) -> Option<ActivePane> {
+        (self.zoomed && matches!(self.current_state, AppState::Folders | AppState::Assets))
+            .then_some(self.active_pane)
+    }
+
+    // Whether the folder pane goes above the asset pane in a content area this wide
+    pub fn stacked(&self, width: u16) -> bool {
+        match self.pane_layout {
+            PaneLayout::Auto => width < self.config.layout.stack_below,
+            PaneLayout::SideBySide => false,
+            PaneLayout::Stacked => true,
+        }
+    }
+
+    // The tenant named in the status line: the profile in use and its tenant, the configured name,
+    // or the sample tenant in demo mode
+    // The terminal window title, e.g. "pcli2-tui — acme:/Parts", so tabs running the TUI
+    // against different tenants can be told apart
+    pub fn window_title(&self) -> String {
+        let folder = format!("/{}", self.current_folder.as_deref().unwrap_or_default());
+        match self.tenant_label() {
+            Some(tenant) => format!("pcli2-tui — {}:{}", tenant, folder),
+            None => format!("pcli2-tui — {}", folder),
+        }
+    }
+
+    pub fn tenant_label(&self) -> Option<String> {
+        let tenant = self
+            .config
+            .profile()
+            .and_then(|profile| profile.tenant.as_ref())
+            .or(self.config.tenant.as_ref());
+        match (self.config.active_profile.as_ref(), tenant) {
+            (Some(profile), Some(tenant)) if profile != tenant => {
+                Some(format!("{} ({})", profile, tenant))
+            }
+            (Some(profile), _) => Some(profile.clone()),
+            (None, Some(tenant)) => Some(tenant.clone()),
+            (None, None) if demo::is_enabled() => Some("demo".to_string()),
+            (None, None) => None,
+        }
+    }
+
+    // Directory for exports: the configured one, or the current directory
+    fn export_dir(&self) -> PathBuf {
+        self.config
+            .export_dir
+            .as_deref()
+            .map_or_else(|| PathBuf::from("."), PathBuf::from)
+    }
+
+    fn report_export(&mut self, what: &str, result: anyhow::Result<Vec<PathBuf>>) {
+        match result {
+            Ok(paths) => {
+                let files: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("{} exported to {}", what, files.join(", ")),
+                );
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Export failed: {:#}", e));
+            }
+        }
+    }
+
+    pub fn export_log(&mut self) {
+        let result = export::export_log(&self.export_dir(), &self.log_entries);
+        self.report_export("Log", result);
+    }
+
+    pub fn export_history(&mut self) {
+        let result = export::export_history(&self.export_dir(), &self.command_history);
+        self.report_export("Command history", result);
+    }
+
+    // Write a Markdown and an HTML report on the assets; an asset whose geometric matches are
+    // the ones last shown gets them listed, as filtered there
+    fn export_report(&mut self, assets: Vec<Asset>) {
+        if assets.is_empty() {
+            self.notify(NotificationLevel::Info, "No assets to report on");
+            return;
+        }
+        let matches = self.visible_geometric_matches();
+        let report: Vec<ReportAsset> = assets
+            .iter()
+            .map(|asset| ReportAsset {
+                asset,
+                matches: if self.geometric_match_source.as_deref() == Some(asset.uuid.as_str()) {
+                    matches.clone()
+                } else {
+                    Vec::new()
+                },
+            })
+            .collect();
+        let links = Links {
+            asset_url: self.config.asset_url.as_deref(),
+            compare_url: self.config.compare_url.as_deref(),
+        };
+        let result = export::export_report(&self.export_dir(), &report, links);
+        self.report_export("Report", result);
+    }
+
+    pub fn export_history_script(&mut self) {
+        let result = export::export_history_script(&self.export_dir(), &self.command_history)
+            .map(|path| vec![path]);
+        self.report_export("Command history script", result);
+    }
+
+    // Copy the command of the selected log entry so it can be pasted into a shell
+    pub fn copy_selected_log_entry_to_clipboard(&mut self) {
+        if let Some(log_entry) = self.log_entries.get(self.log_scroll_position) {
+            let command = log_entry.command.clone();
+            self.copy_to_clipboard(command, "Command");
+        }
+    }
+
+    pub async fn handle_key_event(&mut self, key: KeyEvent) {
+        // The most recently opened dialog or prompt takes every key
+        if let Some(modal) = self.modals.top() {
+            match modal {
+                Modal::Search => self.handle_search_keys(key).await,
+                Modal::Finder => self.handle_finder_keys(key).await,
+                Modal::FolderPicker => self.handle_folder_picker_keys(key).await,
+                Modal::TagPicker => self.handle_tag_picker_keys(key).await,
+                Modal::AssetDetails => self.handle_asset_details_keys(key),
+                Modal::GeometricMatch => self.handle_geometric_match_keys(key).await,
+                Modal::WhereUsed => self.handle_where_used_keys(key).await,
+                Modal::CustomCommands => self.handle_custom_command_menu_keys(key).await,
+                Modal::AssemblyDownload => self.handle_assembly_download_prompt_keys(key).await,
+                Modal::Confirm => self.handle_confirm_keys(key).await,
+                Modal::SyncPlan => self.handle_sync_plan_keys(key),
+                Modal::FolderDiff => self.handle_folder_diff_keys(key).await,
+                Modal::MetadataDiff => self.handle_metadata_diff_keys(key),
+                Modal::MatchReview => self.handle_match_review_keys(key).await,
+                Modal::MatchHistory => self.handle_match_history_keys(key),
+                Modal::InterruptedJobs => self.handle_interrupted_jobs_keys(key),
+                Modal::PropertySchema => self.handle_property_schema_keys(key),
+                Modal::Validation => self.handle_validation_keys(key).await,
+                Modal::MetadataImport => self.handle_metadata_import_keys(key),
+                Modal::Setup => self.handle_setup_keys(key).await,
+                Modal::ProfilePicker => self.handle_profile_picker_keys(key).await,
+                Modal::Tour => self.handle_tour_keys(key),
+                Modal::Help => self.handle_help_keys(key),
+                Modal::LogDetail => self.handle_log_detail_keys(key),
+                Modal::Error => self.handle_error_modal_keys(key),
+                Modal::Prompt => self.handle_prompt_keys(key).await,
+                Modal::CommandLine => self.handle_raw_command_input(key).await,
+            }
+            return;
+        }
+
+        // While the JSON viewer search is being typed, every key goes to it
+        if self
+            .json_viewer
+            .as_ref()
+            .is_some_and(|viewer| viewer.search_editing)
+        {
+            self.handle_json_search_input(key);
+            return;
+        }
+
+        // While a history command is being edited, every key goes to it
+        if self.command_edit_buffer.is_some() {
+            self.handle_command_edit_keys(key).await;
+            return;
+        }
+
+        // While the log filter is being typed, every key goes to it
+        if self.log_filter_editing {
+            self.handle_log_filter_input(key);
+            return;
+        }
+
+        // So do the keys while metadata cells are being edited
+        if self.cell_editor.is_some() {
+            self.handle_cell_edit_keys(key).await;
+            return;
+        }
+
+        // Keys that work in every view
+        if let Some(action) = action::global_action(key, &self.current_state) {
+            self.update(action).await;
+            return;
+        }
+
+        // Keys bound to custom commands act on the selected asset
+        if matches!(self.current_state, AppState::Folders | AppState::Assets)
+            && self.active_pane == ActivePane::Assets
+            && let KeyCode::Char(c) = key.code
+            && let Some(index) = self
+                .config
+                .custom_commands
+                .iter()
+                .position(|command| command.key == Some(c))
+        {
+            self.run_custom_command(index).await;
+            return;
+        }
+
+        match self.current_state {
+            AppState::Folders | AppState::Assets => self.handle_browser_keys(key).await,
+            AppState::Uploading => match KeyContext::UploadMode.action(key) {
+                Some(ViewAction::Back) => {
+                    self.current_state = AppState::Folders;
+                    self.notify(NotificationLevel::Info, "Upload mode exited");
+                }
+                Some(ViewAction::Upload) => self.upload_asset_interactive().await,
+                _ => {}
+            },
+            AppState::Downloading => {
+                if KeyContext::DownloadMode.action(key) == Some(ViewAction::Back) {
                     self.current_state = AppState::Folders;
+                    self.notify(NotificationLevel::Info, "Download mode exited");
                 }
             }
-            AppState::CommandHistory => {
-                // Handle command history specific keys
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        // Return to the previous state (default to Folders)
-                        self.current_state = AppState::Folders;
-                    }
-                    _ => {}
+            AppState::CommandHistory => self.handle_command_history_keys(key).await,
+            AppState::Basket => self.handle_basket_keys(key),
+            AppState::Deletions => self.handle_deletions_keys(key),
+            AppState::Jobs => {
+                if KeyContext::Jobs.action(key) == Some(ViewAction::Back) {
+                    self.current_state = AppState::Folders;
                 }
             }
             AppState::Log => {
                 // Handle log specific keys
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
+                match KeyContext::Log.action(key) {
+                    Some(ViewAction::Back) => {
                         // Return to the previous state (default to Folders)
                         self.current_state = AppState::Folders;
                     }
-                    KeyCode::Up => {
-                        // Scroll up in the log
-                        if self.log_scroll_position > 0 {
-                            self.log_scroll_position -= 1;
+                    // Scroll up in the log, skipping entries hidden by the filters
+                    Some(ViewAction::Up) => {
+                        if let Some(&previous) = self
+                            .visible_log_indices()
+                            .iter()
+                            .rev()
+                            .find(|&&i| i < self.log_scroll_position)
+                        {
+                            self.log_scroll_position = previous;
                         }
                     }
-                    KeyCode::Down => {
-                        // Scroll down in the log
-                        if self.log_scroll_position < self.log_entries.len().saturating_sub(1) {
-                            self.log_scroll_position += 1;
+                    // Scroll down in the log, skipping entries hidden by the filters
+                    Some(ViewAction::Down) => {
+                        if let Some(&next) = self
+                            .visible_log_indices()
+                            .iter()
+                            .find(|&&i| i > self.log_scroll_position)
+                        {
+                            self.log_scroll_position = next;
                         }
                     }
-                    KeyCode::Char('c') => {
+                    Some(ViewAction::Export) => self.export_log(),
+                    Some(ViewAction::StatusFilter) => {
+                        // Cycle the status filter
+                        self.log_filter = self.log_filter.next();
+                        self.snap_log_selection();
+                    }
+                    Some(ViewAction::Copy) => {
                         // Copy selected log entry to clipboard
                         self.copy_selected_log_entry_to_clipboard();
                     }
+                    Some(ViewAction::Notifications) => {
+                        // Toggle between log entries and the notification history
+                        self.show_notification_history = !self.show_notification_history;
+                    }
+                    Some(ViewAction::Open) if self.log_scroll_position < self.log_entries.len() => {
+                        // Show the selected entry in full
+                        self.log_detail_index = Some(self.log_scroll_position);
+                        self.modals.push(Modal::LogDetail);
+                        self.log_detail_scroll = 0;
+                    }
                     _ => {}
                 }
             }
             AppState::PaneResize => self.handle_resize_keys(key).await,
-        }
-    }
-
-    async fn handle_folder_keys(&mut self, key: KeyEvent) {
-        let prev_selected_folder_index = self.selected_folder_index;
-
-        match key.code {
-            KeyCode::Char('j') | KeyCode::Down => match self.active_pane {
-                ActivePane::Folders => {
-                    if !self.folders.is_empty() {
-                        self.selected_folder_index =
-                            (self.selected_folder_index + 1).min(self.folders.len() - 1);
-                    }
+            AppState::JsonView => self.handle_json_view_keys(key),
+            AppState::RawOutput => match KeyContext::RawOutput.action(key) {
+                Some(ViewAction::Back) => {
+                    self.current_state = AppState::Folders;
                 }
-                ActivePane::Assets => {
-                    if !self.assets.is_empty() {
-                        self.selected_asset_index =
-                            (self.selected_asset_index + 1).min(self.assets.len() - 1);
-                    }
+                Some(ViewAction::Up) => {
+                    self.raw_output_scroll = self.raw_output_scroll.saturating_sub(1);
                 }
-                ActivePane::Log => {
-                    // Scroll down in the log
-                    if self.log_scroll_position < self.log_entries.len().saturating_sub(1) {
-                        self.log_scroll_position += 1;
-                    }
+                Some(ViewAction::Down) => {
+                    self.raw_output_scroll = self.raw_output_scroll.saturating_add(1);
                 }
-            },
-            KeyCode::Char('k') | KeyCode::Up => match self.active_pane {
-                ActivePane::Folders => {
-                    if self.selected_folder_index > 0 {
-                        self.selected_folder_index -= 1;
-                    }
+                Some(ViewAction::PageUp) => {
+                    self.raw_output_scroll = self.raw_output_scroll.saturating_sub(10);
                 }
-                ActivePane::Assets => {
-                    if self.selected_asset_index > 0 {
-                        self.selected_asset_index -= 1;
-                    }
+                Some(ViewAction::PageDown) => {
+                    self.raw_output_scroll = self.raw_output_scroll.saturating_add(10);
                 }
-                ActivePane::Log => {
-                    // Scroll up in the log
-                    if self.log_scroll_position > 0 {
-                        self.log_scroll_position -= 1;
-                    }
+                _ => {}
+            },
+        }
+    }
+
+    // The folder and asset views: the keys of the active pane
+    async fn handle_browser_keys(&mut self, key: KeyEvent) {
+        let Some(action) = KeyContext::of(self.current_state, self.active_pane).action(key) else {
+            return;
+        };
+        match self.active_pane {
+            ActivePane::Folders => self.folder_pane_action(action).await,
+            ActivePane::Assets => self.assets_pane_action(action).await,
+            ActivePane::Log => match action {
+                ViewAction::Down
+                    if self.log_scroll_position < self.log_entries.len().saturating_sub(1) =>
+                {
+                    self.log_scroll_position += 1;
+                }
+                ViewAction::Up => {
+                    self.log_scroll_position = self.log_scroll_position.saturating_sub(1);
                 }
+                ViewAction::Back => self.go_back_to_parent_folder().await,
+                _ => {}
             },
-            KeyCode::Enter => {
-                match self.active_pane {
-                    ActivePane::Folders => {
-                        if !self.folders.is_empty()
-                            && self.selected_folder_index < self.folders.len()
-                        {
-                            let folder = &self.folders[self.selected_folder_index];
+        }
+    }
 
-                            // Check if this is the parent directory indicator
-                            if folder.uuid == ".." {
-                                self.go_back_to_parent_folder().await;
+    async fn folder_pane_action(&mut self, action: ViewAction) {
+        let prev_selected_folder_index = self.selected_folder_index;
 
-                                // After going back to parent, load assets for the parent folder
-                                self.load_assets_for_current_folder().await;
-                            } else {
-                                self.enter_folder(folder.path.clone()).await; // Use the full path
-                            }
-                        }
-                    }
-                    ActivePane::Assets => {
-                        // Perform action on selected asset (e.g., view details)
-                        if !self.assets.is_empty() && self.selected_asset_index < self.assets.len()
-                        {
-                            let asset = &self.assets[self.selected_asset_index];
-                            self.status_message = format!("Selected asset: {}", asset.name);
-                        }
-                    }
-                    ActivePane::Log => {
-                        // Perform action on selected log entry if needed
-                        // For now, just do nothing
+        match action {
+            ViewAction::Down if !self.folders.is_empty() => {
+                self.selected_folder_index =
+                    (self.selected_folder_index + 1).min(self.folders.len() - 1);
+            }
+            ViewAction::Up => {
+                self.selected_folder_index = self.selected_folder_index.saturating_sub(1);
+            }
+            ViewAction::Open => {
+                if let Some(folder) = self.folders.get(self.selected_folder_index) {
+                    // Check if this is the parent directory indicator
+                    if folder.uuid == ".." {
+                        self.go_back_to_parent_folder().await;
+
+                        // After going back to parent, load assets for the parent folder
+                        self.load_assets_for_current_folder().await;
+                    } else {
+                        self.enter_folder(folder.path.clone()).await; // Use the full path
                     }
                 }
 
                 // After entering a folder, we should return to avoid loading assets for selection
                 return;
             }
-            KeyCode::Char('a') => {
-                self.switch_to_assets_view().await;
-            }
-            KeyCode::Char('/') => {
-                self.current_state = AppState::Search;
+            ViewAction::Back => self.go_back_to_parent_folder().await,
+            ViewAction::ListAssets => self.switch_to_assets_view().await,
+            // Mark the selected folder for comparison, or compare it with the marked one
+            ViewAction::Compare => self.mark_folder_for_diff().await,
+            // Sync the selected folder with a local directory
+            ViewAction::Sync => self.start_sync_prompt(),
+            // Download the selected folder with its subfolders, after a summary
+            ViewAction::DownloadFolder => self.plan_folder_download().await,
+            // Create a folder in the current one
+            ViewAction::NewFolder => self.start_create_folder_prompt(),
+            // Move the selected folder under another parent
+            ViewAction::Move => {
+                if let Some(folder) = self.folders.get(self.selected_folder_index).cloned()
+                    && folder.uuid != ".."
+                {
+                    self.start_move_folder(folder);
+                }
             }
-            KeyCode::Char('u') => {
+            ViewAction::UploadMode => {
                 self.current_state = AppState::Uploading;
-                self.status_message = "Upload mode activated. Press 'q' to return.".to_string();
+                self.notify(
+                    NotificationLevel::Info,
+                    "Upload mode activated. Press Esc to return.".to_string(),
+                );
             }
-            KeyCode::Char('d') => {
+            ViewAction::DownloadMode => {
                 self.current_state = AppState::Downloading;
-                self.status_message = "Download mode activated. Press 'q' to return.".to_string();
+                self.notify(
+                    NotificationLevel::Info,
+                    "Download mode activated. Press Esc to return.".to_string(),
+                );
             }
-            KeyCode::Char('g') => {
-                // Perform geometric match on selected asset when in Folders state but Assets pane is active
-                if self.active_pane == ActivePane::Assets && !self.assets.is_empty() && self.selected_asset_index < self.assets.len() {
-                    let asset_uuid = self.assets[self.selected_asset_index].uuid.clone();
-                    let asset_name = self.assets[self.selected_asset_index].name.clone();
+            _ => {}
+        }
 
-                    self.perform_geometric_match(&asset_uuid).await;
-                    self.show_geometric_match_modal = true; // Show the geometric match modal
-                    self.status_message = format!("Geometric match performed on: {}", asset_name);
-                }
+        // If the selected folder changed, load its assets once the selection settles
+        if prev_selected_folder_index != self.selected_folder_index {
+            self.schedule_selection_load();
+        }
+    }
+
+    async fn assets_pane_action(&mut self, action: ViewAction) {
+        let selected = self.assets.get(self.selected_asset_index).cloned();
+        match (action, selected) {
+            (ViewAction::Down, _) if !self.assets.is_empty() => {
+                self.selected_asset_index =
+                    (self.selected_asset_index + 1).min(self.assets.len() - 1);
+            }
+            (ViewAction::Up, _) => {
+                self.selected_asset_index = self.selected_asset_index.saturating_sub(1);
             }
-            KeyCode::Esc | KeyCode::Backspace => {
-                self.go_back_to_parent_folder().await;
+            (ViewAction::Details, Some(_)) => self.show_asset_details().await,
+            (ViewAction::GeometricMatch, Some(asset)) => {
+                self.perform_geometric_match(&asset.uuid).await;
+                self.modals.push(Modal::GeometricMatch);
+                self.status_message = format!("Geometric match performed on: {}", asset.name);
             }
+            (ViewAction::Download, Some(asset)) => self.request_download(asset).await,
+            // List the assemblies that contain the selected asset
+            (ViewAction::WhereUsed, Some(asset)) => {
+                self.find_where_used(&asset.uuid, &asset.name).await;
+            }
+            // Mark the selected asset for comparison, or compare it with the marked one
+            (ViewAction::Compare, _) => self.mark_asset_for_diff().await,
+            // Open the selected asset's web page
+            (ViewAction::Browser, Some(asset)) => self.open_in_browser(&asset).await,
+            // Put the selected asset in the basket, or take it out
+            (ViewAction::Basket, _) => self.toggle_basket(),
+            (ViewAction::Tag, Some(asset)) => self.start_tag_picker(TagPickerMode::Edit(asset)),
+            // List only the assets with a tag, or all of them again
+            (ViewAction::TagFilter, _) => match self.tag_filter {
+                Some(_) => self.set_tag_filter(None),
+                None => self.start_tag_picker(TagPickerMode::Filter),
+            },
+            (ViewAction::Move, Some(asset)) => self.start_move_prompt(asset),
+            (ViewAction::Rename, Some(asset)) => self.start_rename_prompt(asset),
+            (ViewAction::EditCells, _) => self.start_cell_edit(),
+            // Write a report on the selected asset
+            (ViewAction::Report, Some(asset)) => self.export_report(vec![asset]),
+            // Stage the selected asset for deletion, or take it off the list
+            (ViewAction::StageDeletion, Some(asset)) => self.toggle_deletion(asset),
+            (ViewAction::CopyUuid | ViewAction::CopyPath, asset) => {
+                self.copy_asset_reference(asset, action == ViewAction::CopyPath);
+            }
+            (ViewAction::Back, _) => self.go_back_to_parent_folder().await,
             _ => {}
         }
+    }
 
-        // If the selected folder index changed in the folders pane, load assets for the selected folder
-        if self.active_pane == ActivePane::Folders
-            && prev_selected_folder_index != self.selected_folder_index
-        {
-            self.load_assets_for_selected_folder().await;
-        }
+    // Load the selected folder's assets after SELECTION_LOAD_DELAY, so holding j/k doesn't run
+    // pcli2 for every folder passed over
+    fn schedule_selection_load(&mut self) {
+        self.selection_load_at = Some(Instant::now() + SELECTION_LOAD_DELAY);
     }
 
-    async fn handle_asset_keys(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char('j') | KeyCode::Down => match self.active_pane {
-                ActivePane::Assets => {
-                    if !self.assets.is_empty() {
-                        self.selected_asset_index =
-                            (self.selected_asset_index + 1).min(self.assets.len() - 1);
-                    }
-                }
-                ActivePane::Folders => {
-                    if !self.folders.is_empty() {
-                        self.selected_folder_index =
-                            (self.selected_folder_index + 1).min(self.folders.len() - 1);
-                    }
-                }
-                ActivePane::Log => {
-                    // Scroll down in the log
-                    if self.log_scroll_position < self.log_entries.len().saturating_sub(1) {
-                        self.log_scroll_position += 1;
-                    }
-                }
-            },
-            KeyCode::Char('k') | KeyCode::Up => match self.active_pane {
-                ActivePane::Assets => {
-                    if self.selected_asset_index > 0 {
-                        self.selected_asset_index -= 1;
-                    }
-                }
-                ActivePane::Folders => {
-                    if self.selected_folder_index > 0 {
-                        self.selected_folder_index -= 1;
-                    }
-                }
-                ActivePane::Log => {
-                    // Scroll up in the log
-                    if self.log_scroll_position > 0 {
-                        self.log_scroll_position -= 1;
-                    }
-                }
-            },
-            KeyCode::Enter => {
-                match self.active_pane {
-                    ActivePane::Assets => {
-                        // Show detailed information for the selected asset
-                        if !self.assets.is_empty() && self.selected_asset_index < self.assets.len() {
-                            self.show_asset_details();
-                        }
-                    }
-                    ActivePane::Folders => {
-                        if !self.folders.is_empty()
-                            && self.selected_folder_index < self.folders.len()
-                        {
-                            let folder = &self.folders[self.selected_folder_index];
+    // How long the main loop waits for input before drawing again: the idle tick once the TUI
+    // has been left alone for a while or another window has the focus
+    pub fn tick(&self) -> Duration {
+        let tick = &self.config.tick;
+        let left_alone = tick.idle_after_secs > 0
+            && self.last_input.elapsed() >= Duration::from_secs(tick.idle_after_secs);
+        let idle = !self.focused || left_alone;
+        Duration::from_millis(if idle { tick.idle_ms } else { tick.active_ms }.max(1))
+    }
 
-                            // Check if this is the parent directory indicator
-                            if folder.uuid == ".." {
-                                self.go_back_to_parent_folder().await;
+    // The terminal reported gaining or losing the focus; coming back counts as input.
+    // Prefetching waits while another window has the focus.
+    pub fn focus_changed(&mut self, focused: bool) {
+        self.focused = focused;
+        self.prefetcher.set_paused(!focused);
+        if focused {
+            self.last_input = Instant::now();
+        }
+    }
 
-                                // After going back to parent, load assets for the parent folder
-                                self.load_assets_for_current_folder().await;
-                            } else {
-                                self.enter_folder(folder.path.clone()).await; // Use the full path
-                            }
-                        }
-                    }
-                    ActivePane::Log => {
-                        // Perform action on selected log entry if needed
-                        // For now, just do nothing
-                    }
-                }
-            },
-            KeyCode::Char('g') => {
-                // Perform geometric match on selected asset
-                if !self.assets.is_empty() && self.selected_asset_index < self.assets.len() {
-                    let asset_uuid = self.assets[self.selected_asset_index].uuid.clone();
-                    let asset_name = self.assets[self.selected_asset_index].name.clone();
+    // A key, click or pointer move came in, so the terminal has the focus whether or not it
+    // said so
+    pub fn input_received(&mut self) {
+        self.last_input = Instant::now();
+        if !self.focused {
+            self.focus_changed(true);
+        }
+    }
 
-                    self.perform_geometric_match(&asset_uuid).await;
-                    self.show_geometric_match_modal = true; // Show the geometric match modal
-                    self.status_message = format!("Geometric match performed on: {}", asset_name);
-                }
-            },
-            KeyCode::Char('d') => {
-                // Download selected asset
-                if !self.assets.is_empty() && self.selected_asset_index < self.assets.len() {
-                    let asset_uuid = self.assets[self.selected_asset_index].uuid.clone();
-                    let asset_name = self.assets[self.selected_asset_index].name.clone();
-                    self.download_asset_by_uuid(&asset_uuid, &asset_name).await;
-                }
-            }
-            KeyCode::Char('q') => {
-                // Go back to folder view
-                self.current_state = AppState::Folders;
-            }
-            KeyCode::Esc | KeyCode::Backspace => {
-                self.go_back_to_parent_folder().await;
+    // Whether the selection has settled and its assets should be loaded now
+    pub fn selection_load_due(&mut self) -> bool {
+        match self.selection_load_at {
+            Some(at) if Instant::now() >= at => {
+                self.selection_load_at = None;
+                true
             }
-            _ => {}
+            _ => false,
         }
     }
 
+    // Called when a selection-driven load was abandoned for new input; the load is scheduled
+    // again in case the input doesn't move the selection
+    pub fn cancel_selection_load(&mut self) {
+        self.assets_loading_for_selection = false;
+        self.command_in_progress = false;
+        self.status_message = "Loading assets cancelled".to_string();
+        tracing::debug!(command = %self.last_executed_command, "cancelled selection load");
+        self.schedule_selection_load();
+    }
+
     async fn handle_search_keys(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char(c) if c != '\n' => {
-                // Only add character if we're focused on the input field
-                if matches!(self.search_modal_focus, SearchModalFocus::Input) {
-                    self.search_input_buffer.push(c);
+        if self.search_save_name.is_some() {
+            self.handle_search_save_keys(key);
+            return;
+        }
+        if self.show_saved_searches {
+            self.handle_saved_search_keys(key).await;
+            return;
+        }
+
+        let action = KeyContext::Search.action(key);
+        let results = matches!(self.search_modal_focus, SearchModalFocus::Results);
+        match action {
+            // Copy the selected result's UUID (y) or path (Y) when focused on the results
+            Some(ViewAction::CopyUuid | ViewAction::CopyPath) if results => {
+                let asset = self
+                    .search_results
+                    .get(self.selected_search_result_index)
+                    .cloned();
+                self.copy_asset_reference(asset, action == Some(ViewAction::CopyPath));
+            }
+            // Cycle the scope of the next search
+            Some(ViewAction::CycleScope) => {
+                self.search_scope = self.search_scope.next();
+                self.status_message = format!("Search scope: {}", self.search_scope.label());
+            }
+            // Switch between plain text, glob and regex matching
+            Some(ViewAction::CycleMatching) => {
+                self.search_mode = self.search_mode.next();
+                self.status_message = format!("Search mode: {}", self.search_mode.label());
+            }
+            // Sort by the next column (s) or reverse the order (S)
+            Some(ViewAction::Sort) if results => self.cycle_search_sort(),
+            Some(ViewAction::ReverseSort) if results => {
+                if let Some((_, ref mut descending)) = self.search_sort {
+                    *descending = !*descending;
+                    self.sort_search_results();
                 }
             }
-            KeyCode::Tab => {
-                // Cyclic focus forward: Input -> Results -> Input -> ...
-                self.search_modal_focus = match self.search_modal_focus {
-                    SearchModalFocus::Input => SearchModalFocus::Results,
-                    SearchModalFocus::Results => SearchModalFocus::Input,
-                };
+            // Scroll the result columns sideways; the name stays in view
+            Some(ViewAction::Left) if results => {
+                self.search_column_offset = self.search_column_offset.saturating_sub(1);
+            }
+            Some(ViewAction::Right) if results => {
+                // Folder and Size come before the metadata columns
+                let columns = 2 + metadata_keys(&self.search_results).len();
+                self.search_column_offset = (self.search_column_offset + 1).min(columns - 1);
+            }
+            // The Assets pane's actions, on the selected result
+            Some(
+                action @ (ViewAction::Details
+                | ViewAction::GeometricMatch
+                | ViewAction::Download
+                | ViewAction::WhereUsed
+                | ViewAction::Browser
+                | ViewAction::Move
+                | ViewAction::Rename
+                | ViewAction::StageDeletion),
+            ) if results => {
+                if let Some(asset) = self
+                    .search_results
+                    .get(self.selected_search_result_index)
+                    .cloned()
+                {
+                    self.search_result_action(action, asset).await;
+                }
+            }
+            Some(ViewAction::SaveSearch) => {
+                if self.search_input_buffer.as_str().trim().is_empty() {
+                    self.notify(NotificationLevel::Warning, "Type a query to save first");
+                } else {
+                    self.search_save_name = Some(TextInput::default());
+                }
+            }
+            Some(ViewAction::SavedSearches) => {
+                self.show_saved_searches = true;
+                self.selected_saved_search = 0;
             }
-            KeyCode::BackTab => {
-                // Cyclic focus backward: Results -> Input -> Results -> ...
+            // Typing and readline-style editing, when focused on the input field
+            _ if !results && self.search_input_buffer.handle_key(key) => {
+                self.search_history_index = None;
+            }
+            // Cyclic focus: Input -> Results -> Input -> ...
+            Some(ViewAction::SwitchFocus) => {
                 self.search_modal_focus = match self.search_modal_focus {
-                    SearchModalFocus::Results => SearchModalFocus::Input,
                     SearchModalFocus::Input => SearchModalFocus::Results,
+                    SearchModalFocus::Results => SearchModalFocus::Input,
                 };
             }
-            KeyCode::Backspace => {
-                // Only process backspace if focused on input
-                if matches!(self.search_modal_focus, SearchModalFocus::Input) {
-                    self.search_input_buffer.pop();
-                }
-            }
-            KeyCode::Enter => {
+            Some(ViewAction::Open) => {
                 match self.search_modal_focus {
                     SearchModalFocus::Input => {
                         // Perform search when Enter is pressed in input field
-                        self.search_query = self.search_input_buffer.clone();
+                        self.search_query = self.search_input_buffer.as_str().to_string();
                         self.perform_search().await;
                         // Switch focus to results after search
                         self.search_modal_focus = SearchModalFocus::Results;
                     }
+                    // Show the selected result in the browser, in its folder
                     SearchModalFocus::Results => {
-                        // Allow user to go back to input field when Enter is pressed in results
+                        let Some(asset) = self
+                            .search_results
+                            .get(self.selected_search_result_index)
+                            .cloned()
+                        else {
+                            self.search_modal_focus = SearchModalFocus::Input;
+                            return;
+                        };
+                        self.modals.close(Modal::Search);
                         self.search_modal_focus = SearchModalFocus::Input;
+                        self.go_to_asset(&asset).await;
                     }
                 }
             }
-            KeyCode::Esc => {
-                self.show_search_modal = false;
+            Some(ViewAction::Back) => {
+                self.modals.close(Modal::Search);
                 self.search_input_buffer.clear();
                 self.search_modal_focus = SearchModalFocus::Input; // Reset focus
             }
-            KeyCode::Down => {
-                // Navigate down in search results only if focused on results
-                if matches!(self.search_modal_focus, SearchModalFocus::Results) {
-                    if !self.search_results.is_empty() {
-                        self.selected_search_result_index =
-                            (self.selected_search_result_index + 1).min(self.search_results.len() - 1);
-                    }
-                }
+            // In the input, Up and Down browse the queries of this session
+            Some(ViewAction::Up) if !results && !self.search_history.is_empty() => {
+                let index = match self.search_history_index {
+                    Some(index) => index.saturating_sub(1),
+                    None => self.search_history.len() - 1,
+                };
+                self.search_history_index = Some(index);
+                self.search_input_buffer
+                    .set(self.search_history[index].clone());
             }
-            KeyCode::Up => {
-                // Navigate up in search results only if focused on results
-                if matches!(self.search_modal_focus, SearchModalFocus::Results) {
-                    if self.selected_search_result_index > 0 {
-                        self.selected_search_result_index -= 1;
+            Some(ViewAction::Down) if !results => {
+                match self.search_history_index {
+                    Some(index) if index + 1 < self.search_history.len() => {
+                        self.search_history_index = Some(index + 1);
+                        self.search_input_buffer
+                            .set(self.search_history[index + 1].clone());
+                    }
+                    // Past the newest entry the input is empty again
+                    Some(_) => {
+                        self.search_history_index = None;
+                        self.search_input_buffer.clear();
                     }
+                    None => {}
                 }
             }
-            KeyCode::Char('d')
-                if matches!(self.search_modal_focus, SearchModalFocus::Results) &&
-                   !self.search_results.is_empty() && self.selected_search_result_index < self.search_results.len() =>
-            {
-                // Download selected asset from search results
-                let asset_uuid = self.search_results[self.selected_search_result_index].uuid.clone();
-                let asset_name = self.search_results[self.selected_search_result_index].name.clone();
-                self.download_asset_by_uuid(&asset_uuid, &asset_name).await;
+            // Navigate down in search results only if focused on results
+            Some(ViewAction::Down) if results && !self.search_results.is_empty() => {
+                self.selected_search_result_index =
+                    (self.selected_search_result_index + 1).min(self.search_results.len() - 1);
+            }
+            // Navigate up in search results only if focused on results
+            Some(ViewAction::Up) if results && self.selected_search_result_index > 0 => {
+                self.selected_search_result_index -= 1;
             }
             _ => {}
         }
     }
 
-    async fn handle_resize_keys(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Up => {
-                // Increase vertical size of the current pane (decrease the one below)
-                self.resize_delta_y -= 1;
-                self.status_message =
-                    format!("Resize: Adjusting vertically ({})", self.resize_delta_y);
-            }
-            KeyCode::Down => {
-                // Decrease vertical size of the current pane (increase the one below)
-                self.resize_delta_y += 1;
-                self.status_message =
+    // Name, folder, size, then each metadata key, and around again
+    fn cycle_search_sort(&mut self) {
+        let mut columns = vec![
+            SearchSortColumn::Name,
+            SearchSortColumn::Folder,
+            SearchSortColumn::Size,
+        ];
+        columns.extend(
+            metadata_keys(&self.search_results)
+                .into_iter()
+                .map(SearchSortColumn::Metadata),
+        );
+        let next = match self.search_sort {
+            Some((ref column, _)) => columns
+                .iter()
+                .position(|c| c == column)
+                .map_or(0, |i| (i + 1) % columns.len()),
+            None => 0,
+        };
+        self.search_sort = Some((columns.swap_remove(next), false));
+        self.sort_search_results();
+    }
+
+    // Apply the sort order, keeping the selected result selected
+    fn sort_search_results(&mut self) {
+        let Some((ref column, descending)) = self.search_sort else {
+            return;
+        };
+        let selected = self
+            .search_results
+            .get(self.selected_search_result_index)
+            .map(|asset| asset.uuid.clone());
+
+        let folder = |asset: &Asset| {
+            asset
+                .path
+                .rsplit_once('/')
+                .map(|(folder, _)| folder.to_lowercase())
+                .unwrap_or_default()
+        };
+        self.search_results.sort_by(|a, b| {
+            let order = match column {
+                SearchSortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SearchSortColumn::Folder => folder(a)
+                    .cmp(&folder(b))
+                    .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
+                SearchSortColumn::Size => a.size.cmp(&b.size),
+                SearchSortColumn::Metadata(key) => {
+                    compare_values(a.metadata_value(key), b.metadata_value(key))
+                }
+            };
+            if descending { order.reverse() } else { order }
+        });
+
+        if let Some(uuid) = selected
+            && let Some(index) = self.search_results.iter().position(|a| a.uuid == uuid)
+        {
+            self.selected_search_result_index = index;
+        }
+    }
+
+    async fn search_result_action(&mut self, action: ViewAction, asset: Asset) {
+        match action {
+            ViewAction::Details => self.show_details_of(&asset).await,
+            ViewAction::GeometricMatch => {
+                self.perform_geometric_match(&asset.uuid).await;
+                self.modals.push(Modal::GeometricMatch);
+                self.status_message = format!("Geometric match performed on: {}", asset.name);
+            }
+            ViewAction::Download => self.request_download(asset).await,
+            ViewAction::WhereUsed => self.find_where_used(&asset.uuid, &asset.name).await,
+            ViewAction::Browser => self.open_in_browser(&asset).await,
+            ViewAction::Move => self.start_move_prompt(asset),
+            ViewAction::Rename => self.start_rename_prompt(asset),
+            ViewAction::StageDeletion => self.toggle_deletion(asset),
+            _ => {}
+        }
+    }
+
+    // Typing the name for the query being saved
+    fn handle_search_save_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut name) = self.search_save_name else {
+            return;
+        };
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Open) => {
+                let name = name.as_str().trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+                self.search_save_name = None;
+                self.save_search(name, self.search_input_buffer.as_str().trim().to_string());
+            }
+            Some(ViewAction::Back) => self.search_save_name = None,
+            _ => {
+                name.handle_key(key);
+            }
+        }
+    }
+
+    // Store a named query in the config file; a query saved under an existing name replaces it
+    fn save_search(&mut self, name: String, query: String) {
+        let saved = &mut self.config.saved_searches;
+        match saved.iter_mut().find(|search| search.name == name) {
+            Some(search) => search.query = query,
+            None => saved.push(config::SavedSearch {
+                name: name.clone(),
+                query,
+            }),
+        }
+        match self.config.save() {
+            Ok(()) => self.notify(
+                NotificationLevel::Success,
+                format!("Saved search \"{}\"", name),
+            ),
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Search saved for this session only: {:#}", e),
+            ),
+        }
+    }
+
+    async fn handle_saved_search_keys(&mut self, key: KeyEvent) {
+        let count = self.config.saved_searches.len();
+        match KeyContext::SavedSearches.action(key) {
+            Some(ViewAction::Up) => {
+                self.selected_saved_search = self.selected_saved_search.saturating_sub(1);
+            }
+            Some(ViewAction::Down) => {
+                self.selected_saved_search =
+                    (self.selected_saved_search + 1).min(count.saturating_sub(1));
+            }
+            // Run the selected search
+            Some(ViewAction::Open) => {
+                let Some(search) = self.config.saved_searches.get(self.selected_saved_search)
+                else {
+                    return;
+                };
+                self.search_input_buffer.set(search.query.clone());
+                self.search_query = search.query.clone();
+                self.show_saved_searches = false;
+                self.perform_search().await;
+                self.search_modal_focus = SearchModalFocus::Results;
+            }
+            // Put the query into the input to edit it before searching
+            Some(ViewAction::Edit) => {
+                if let Some(search) = self.config.saved_searches.get(self.selected_saved_search) {
+                    self.search_input_buffer.set(search.query.clone());
+                    self.search_modal_focus = SearchModalFocus::Input;
+                    self.show_saved_searches = false;
+                }
+            }
+            Some(ViewAction::Remove) if self.selected_saved_search < count => {
+                let removed = self
+                    .config
+                    .saved_searches
+                    .remove(self.selected_saved_search);
+                self.selected_saved_search =
+                    self.selected_saved_search.min(count.saturating_sub(2));
+                match self.config.save() {
+                    Ok(()) => self.notify(
+                        NotificationLevel::Info,
+                        format!("Deleted saved search \"{}\"", removed.name),
+                    ),
+                    Err(e) => self.notify(
+                        NotificationLevel::Error,
+                        format!("Failed to update the config file: {:#}", e),
+                    ),
+                }
+            }
+            Some(ViewAction::Back) => self.show_saved_searches = false,
+            _ => {}
+        }
+    }
+
+    async fn handle_resize_keys(&mut self, key: KeyEvent) {
+        match KeyContext::PaneResize.action(key) {
+            Some(ViewAction::Up) => {
+                // Increase vertical size of the current pane (decrease the one below)
+                self.resize_delta_y -= 1;
+                self.status_message =
+                    format!("Resize: Adjusting vertically ({})", self.resize_delta_y);
+            }
+            Some(ViewAction::Down) => {
+                // Decrease vertical size of the current pane (increase the one below)
+                self.resize_delta_y += 1;
+                self.status_message =
                     format!("Resize: Adjusting vertically ({})", self.resize_delta_y);
             }
-            KeyCode::Left => {
+            Some(ViewAction::Left) => {
                 // Decrease horizontal size of the current pane (increase the one to the right)
                 self.resize_delta_x -= 1;
                 self.status_message =
                     format!("Resize: Adjusting horizontally ({})", self.resize_delta_x);
             }
-            KeyCode::Right => {
+            Some(ViewAction::Right) => {
                 // Increase horizontal size of the current pane (decrease the one to the right)
                 self.resize_delta_x += 1;
                 self.status_message =
                     format!("Resize: Adjusting horizontally ({})", self.resize_delta_x);
             }
-            KeyCode::Enter => {
+            Some(ViewAction::Open) => {
                 // Apply the resize changes and exit resize mode
                 self.resize_mode_active = false;
                 self.current_state = AppState::Folders; // Return to default state
-                self.status_message = format!(
-                    "Resize applied: dx={}, dy={}",
-                    self.resize_delta_x, self.resize_delta_y
+                self.notify(
+                    NotificationLevel::Info,
+                    format!(
+                        "Resize applied: dx={}, dy={}",
+                        self.resize_delta_x, self.resize_delta_y
+                    ),
                 );
             }
-            KeyCode::Esc | KeyCode::Char('q') => {
+            Some(ViewAction::Back) => {
                 // Cancel resize and return to previous state
                 self.resize_mode_active = false;
                 self.resize_delta_x = 0;
                 self.resize_delta_y = 0;
                 self.current_state = AppState::Folders; // Return to default state
-                self.status_message = "Resize cancelled".to_string();
+                self.notify(NotificationLevel::Info, "Resize cancelled");
             }
             _ => {}
         }
     }
 
     pub async fn load_folders_for_current_context(&mut self) {
-        match &self.current_folder {
+        match self.current_folder.clone() {
             Some(current_path) => {
                 // Check if we have cached data for this folder
-                if let Some(cached_data) = self.folder_cache.get(current_path) {
-                    // Check if cache is still valid (less than 5 minutes old)
-                    if cached_data
-                        .timestamp
-                        .elapsed()
-                        .unwrap_or(std::time::Duration::MAX)
-                        < std::time::Duration::from_secs(300)
-                    {
-                        // 5 minutes
+                if let Some(cached_data) = self.folder_cache.get(&current_path) {
+                    // Check if cache is still valid (less than 5 minutes old, or offline)
+                    if cached_data.is_fresh() {
                         self.folders = cached_data.folders.clone();
-                        self.assets = cached_data.assets.clone(); // Also update assets from cache
-                        self.status_message =
-                            format!("Loaded {} subfolders from cache", self.folders.len());
-                        self.last_executed_command = format!(
-                            "pcli2 folder list --folder-path \"{}\" --format json",
-                            current_path
+                        self.folders_source =
+                            Some(DataSource::cached(&current_path, cached_data.timestamp));
+                        self.assets_source =
+                            Some(DataSource::cached(&current_path, cached_data.timestamp));
+                        self.set_assets(cached_data.assets.clone()); // Also update assets from cache
+                        self.notify(
+                            NotificationLevel::Info,
+                            format!("Loaded {} subfolders from cache", self.folders.len()),
                         );
+                        self.last_executed_command = PcliCommand::new(&[
+                            "folder",
+                            "list",
+                            "--folder-path",
+                            &current_path,
+                            "--format",
+                            "json",
+                        ]);
                         self.command_history
-                            .push(self.last_executed_command.clone());
-                        self.add_log_entry(format!("[{}] ✓ CACHED: {} (would have executed: pcli2 folder list --folder-path \"{}\" --format json)",
-                            Local::now().format("%H:%M:%S"),
-                            self.last_executed_command,
-                            current_path));
+                            .push(HistoryEntry::new(self.last_executed_command.clone()));
+                        self.log_cache_hit();
                         return;
                     }
                 }
 
-                self.last_executed_command = format!(
-                    "pcli2 folder list --folder-path \"{}\" --format json",
-                    current_path
-                );
+                self.last_executed_command = PcliCommand::new(&[
+                    "folder",
+                    "list",
+                    "--folder-path",
+                    &current_path,
+                    "--format",
+                    "json",
+                ]);
                 self.command_history
-                    .push(self.last_executed_command.clone());
+                    .push(HistoryEntry::new(self.last_executed_command.clone()));
                 self.command_in_progress = true; // Set flag when command starts
+                self.command_started = Some(Instant::now());
                 self.status_message = format!("Loading subfolders for {}...", current_path);
 
-                match pcli_commands::list_subfolders_of_folder(current_path) {
+                self.last_operation = Some(Operation::LoadFolders);
+                match pcli_commands::list_subfolders_of_folder(&current_path).await {
                     Ok(pcli_folders) => {
-                        // Convert pcli folders to our internal representation
-                        let mut folders: Vec<Folder> = pcli_folders
-                            .into_iter()
-                            .map(|f| Folder {
-                                uuid: f.id, // Map 'id' from pcli to 'uuid' in our struct
-                                name: f.name,
-                                path: f.path, // Store the full path
-                                folders_count: f.folders_count,
-                                assets_count: f.assets_count,
-                                parent_uuid: None, // pcli doesn't provide parent info in list
-                                children: vec![],
-                            })
-                            .collect();
-
-                        // Add parent directory indicator if we're not at the root
-                        // Check if this is not a top-level folder (doesn't start with just the folder name)
-                        if current_path.contains('/') {
-                            if let Some(pos) = current_path.rfind('/') {
-                                let parent_path = &current_path[..pos];
-                                folders.insert(
-                                    0,
-                                    Folder {
-                                        uuid: String::from(".."), // Special identifier for parent
-                                        name: String::from(".."),
-                                        path: parent_path.to_string(), // Parent path
-                                        folders_count: 0,
-                                        assets_count: 0,
-                                        parent_uuid: None,
-                                        children: vec![],
-                                    },
-                                );
-                            }
-                        } else if !current_path.is_empty() {
-                            // If we're in a top-level folder, parent is root
-                            folders.insert(
-                                0,
-                                Folder {
-                                    uuid: String::from(".."), // Special identifier for parent
-                                    name: String::from(".."),
-                                    path: String::from(""), // Root path
-                                    folders_count: 0,
-                                    assets_count: 0,
-                                    parent_uuid: None,
-                                    children: vec![],
-                                },
-                            );
-                        }
+                        let folders = folder_listing(&current_path, pcli_folders);
 
                         // Cache the folder data
                         let cache_entry = FolderCache {
@@ -823,19 +2578,18 @@ impl App {
                         self.folder_cache.insert(current_path.clone(), cache_entry);
 
                         self.folders = folders;
-                        self.status_message = format!("Loaded {} subfolders", self.folders.len());
+                        self.folders_source = Some(DataSource::fetched(&current_path));
+                        self.report_listing("Loaded", self.folders.len(), "subfolders", "");
                         self.command_in_progress = false; // Clear flag when command completes
                     }
                     Err(e) => {
-                        self.status_message = format!("Error loading subfolders: {}", e);
+                        self.notify(
+                            NotificationLevel::Error,
+                            format!("Error loading subfolders: {}", e),
+                        );
 
                         // Log failed command with error indicator
-                        self.add_log_entry(format!(
-                            "[{}] ✗ ERROR: {} - {}",
-                            Local::now().format("%H:%M:%S"),
-                            self.last_executed_command,
-                            e
-                        ));
+                        self.log_command_error(&e);
                         self.command_in_progress = false; // Clear flag when command completes
                     }
                 }
@@ -848,17 +2602,33 @@ impl App {
     }
 
     pub async fn load_assets_for_current_folder(&mut self) {
+        // Offline the cached assets are all there is
+        if offline::is_enabled()
+            && let Some(ref folder_path) = self.current_folder
+            && let Some(cached) = self.folder_cache.get(folder_path)
+        {
+            self.assets_source = Some(DataSource::cached(folder_path, cached.timestamp));
+            self.set_assets(cached.assets.clone());
+            return;
+        }
         if let Some(ref folder_path) = self.current_folder {
-            self.last_executed_command = format!(
-                "pcli2 asset list --folder-path \"{}\" --format json --metadata",
-                folder_path
-            );
+            self.last_executed_command = PcliCommand::new(&[
+                "asset",
+                "list",
+                "--folder-path",
+                folder_path,
+                "--format",
+                "json",
+                "--metadata",
+            ]);
             self.command_history
-                .push(self.last_executed_command.clone());
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
             self.command_in_progress = true; // Set flag when command starts
+            self.command_started = Some(Instant::now());
             self.status_message = "Loading assets...".to_string();
 
-            match pcli_commands::list_assets_in_folder(folder_path) {
+            self.last_operation = Some(Operation::LoadAssets);
+            match pcli_commands::list_assets_in_folder(folder_path).await {
                 Ok(pcli_assets) => {
                     // Convert pcli assets to our internal representation
                     let assets: Vec<Asset> = pcli_assets
@@ -871,6 +2641,8 @@ impl App {
                             size: a.file_size,
                             path: a.path,
                             metadata: a.metadata,
+                            is_assembly: a.is_assembly,
+                            processing_status: a.processing_status,
                         })
                         .collect();
 
@@ -883,34 +2655,26 @@ impl App {
                     };
                     self.folder_cache.insert(folder_path.clone(), cache_entry);
 
-                    self.assets = assets;
+                    self.assets_source = Some(DataSource::fetched(folder_path));
+                    self.set_assets(assets);
                     // Only change state to Assets if we were already in Assets state or if we want to switch
                     // For now, let's not automatically change state - keep current state
-                    self.status_message = format!("Loaded {} assets", self.assets.len());
-
-                    // Log successful command with success indicator
-                    self.add_log_entry(format!(
-                        "[{}] ✓ SUCCESS: {}",
-                        Local::now().format("%H:%M:%S"),
-                        self.last_executed_command
-                    ));
+                    self.report_listing("Loaded", self.assets.len(), "assets", "");
                     self.command_in_progress = false; // Clear flag when command completes
                 }
                 Err(e) => {
-                    self.status_message = format!("Error loading assets: {}", e);
+                    self.notify(
+                        NotificationLevel::Error,
+                        format!("Error loading assets: {}", e),
+                    );
 
                     // Log failed command with error indicator
-                    self.add_log_entry(format!(
-                        "[{}] ✗ ERROR: {} - {}",
-                        Local::now().format("%H:%M:%S"),
-                        self.last_executed_command,
-                        e
-                    ));
+                    self.log_command_error(&e);
                     self.command_in_progress = false; // Clear flag when command completes
                 }
             }
         } else {
-            self.status_message = "No folder selected".to_string();
+            self.notify(NotificationLevel::Warning, "No folder selected");
         }
     }
 
@@ -919,58 +2683,69 @@ impl App {
             return; // No folders or invalid selection
         }
 
-        let selected_folder = &self.folders[self.selected_folder_index];
+        let selected_folder = self.folders[self.selected_folder_index].clone();
 
         // Don't load assets for the parent directory indicator
         if selected_folder.uuid == ".." {
-            self.assets.clear(); // Clear assets when selecting parent indicator
+            self.assets_source = None;
+            self.set_assets(Vec::new()); // Clear assets when selecting parent indicator
             return;
         }
 
         // Check if we have cached data for this folder
         if let Some(cached_data) = self.folder_cache.get(&selected_folder.path) {
-            // Check if cache is still valid (less than 5 minutes old)
-            if cached_data
-                .timestamp
-                .elapsed()
-                .unwrap_or(std::time::Duration::MAX)
-                < std::time::Duration::from_secs(300)
-            {
-                // 5 minutes
-                self.assets = cached_data.assets.clone();
-                self.status_message = format!(
-                    "Loaded {} assets from cache for {}",
-                    self.assets.len(),
-                    selected_folder.name
-                );
-                self.last_executed_command = format!(
-                    "pcli2 asset list --folder-path \"{}\" --format json --metadata",
-                    selected_folder.path
+            // Check if cache is still valid (less than 5 minutes old, or offline)
+            if cached_data.is_fresh() {
+                self.assets_source = Some(DataSource::cached(
+                    &selected_folder.path,
+                    cached_data.timestamp,
+                ));
+                self.set_assets(cached_data.assets.clone());
+                self.notify(
+                    NotificationLevel::Info,
+                    format!(
+                        "Loaded {} assets from cache for {}",
+                        self.assets.len(),
+                        selected_folder.name
+                    ),
                 );
+                self.last_executed_command = PcliCommand::new(&[
+                    "asset",
+                    "list",
+                    "--folder-path",
+                    &selected_folder.path,
+                    "--format",
+                    "json",
+                    "--metadata",
+                ]);
                 self.command_history
-                    .push(self.last_executed_command.clone());
-                self.add_log_entry(format!(
-                    "[{}] ✓ CACHED: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
+                    .push(HistoryEntry::new(self.last_executed_command.clone()));
+                self.log_cache_hit();
+                self.prefetch_neighbors();
                 return;
             }
         }
 
         // Set loading flag and status
         self.assets_loading_for_selection = true;
-        self.last_executed_command = format!(
-            "pcli2 asset list --folder-path \"{}\" --format json --metadata",
-            selected_folder.path
-        );
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "list",
+            "--folder-path",
+            &selected_folder.path,
+            "--format",
+            "json",
+            "--metadata",
+        ]);
         self.command_history
-            .push(self.last_executed_command.clone());
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
         self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
         self.status_message = format!("Loading assets for {}...", selected_folder.name);
 
         // Load assets in a separate task to avoid blocking the UI
-        match pcli_commands::list_assets_in_folder(&selected_folder.path) {
+        self.last_operation = Some(Operation::LoadSelectedFolderAssets);
+        match pcli_commands::list_assets_in_folder(&selected_folder.path).await {
             Ok(pcli_assets) => {
                 // Convert pcli assets to our internal representation
                 let assets: Vec<Asset> = pcli_assets
@@ -983,6 +2758,8 @@ impl App {
                         size: a.file_size,
                         path: a.path,
                         metadata: a.metadata,
+                        is_assembly: a.is_assembly,
+                        processing_status: a.processing_status,
                     })
                     .collect();
 
@@ -995,38 +2772,167 @@ impl App {
                 self.folder_cache
                     .insert(selected_folder.path.clone(), cache_entry);
 
-                self.assets = assets;
-                self.status_message = format!(
-                    "Loaded {} assets for {}",
-                    self.assets.len(),
-                    selected_folder.name
-                );
-
-                // Log successful command with success indicator
-                self.add_log_entry(format!(
-                    "[{}] ✓ SUCCESS: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
+                self.assets_source = Some(DataSource::fetched(&selected_folder.path));
+                self.set_assets(assets);
+                let context = format!(" for {}", selected_folder.name);
+                self.report_listing("Loaded", self.assets.len(), "assets", &context);
                 self.command_in_progress = false; // Clear flag when command completes
             }
             Err(e) => {
-                self.status_message =
-                    format!("Error loading assets for {}: {}", selected_folder.name, e);
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Error loading assets for {}: {}", selected_folder.name, e),
+                );
 
                 // Log failed command with error indicator
-                self.add_log_entry(format!(
-                    "[{}] ✗ ERROR: {} - {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command,
-                    e
-                ));
+                self.log_command_error(&e);
                 self.command_in_progress = false; // Clear flag when command completes
             }
         }
 
         // Clear loading flag
         self.assets_loading_for_selection = false;
+        self.prefetch_neighbors();
+    }
+
+    // Prefetch the assets of the folders below the selection and of the selected folder's
+    // subfolders, so walking down the list or into the folder doesn't wait for pcli2
+    fn prefetch_neighbors(&mut self) {
+        let count = self.config.prefetch.folders;
+        if count == 0 || offline::is_enabled() {
+            return;
+        }
+
+        let below = self
+            .folders
+            .iter()
+            .skip(self.selected_folder_index + 1)
+            .filter(|folder| folder.uuid != "..")
+            .take(count);
+        for folder in below {
+            let cached = self
+                .folder_cache
+                .get(&folder.path)
+                .is_some_and(FolderCache::is_fresh);
+            if !cached {
+                self.prefetcher
+                    .fetch_assets(folder.path.clone(), self.folders.clone());
+            }
+        }
+
+        if let Some(selected) = self.folders.get(self.selected_folder_index)
+            && selected.uuid != ".."
+        {
+            self.prefetcher.fetch_children(selected.path.clone(), count);
+        }
+    }
+
+    // Change the current folder, telling folder_changed hooks when it actually changes
+    fn set_current_folder(&mut self, folder: Option<String>) {
+        if folder == self.current_folder {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current_folder, folder);
+        self.hooks.emit(
+            HookEvent::FolderChanged,
+            serde_json::json!({
+                "folder": self.current_folder.as_deref().unwrap_or("/"),
+                "previous": previous.as_deref().unwrap_or("/"),
+            }),
+        );
+    }
+
+    // Collect fetched thumbnails and fetch the selected asset's once the selection has settled
+    pub fn update_preview(&mut self) {
+        self.previewer.apply_ready();
+        if !self.show_preview {
+            return;
+        }
+        let Some(uuid) = self
+            .assets
+            .get(self.selected_asset_index)
+            .map(|asset| asset.uuid.clone())
+        else {
+            return;
+        };
+
+        match self.preview_pending {
+            Some((ref pending, since)) if *pending == uuid => {
+                if since.elapsed() >= SELECTION_LOAD_DELAY {
+                    self.previewer.request(&uuid);
+                }
+            }
+            _ => self.preview_pending = Some((uuid, Instant::now())),
+        }
+    }
+
+    // Collect fetched geometry and, when the geometry columns are on, fetch it for every asset
+    pub fn update_geometry(&mut self) {
+        if self.geometry.apply_ready() {
+            self.table_changed();
+        }
+        if self.config.geometry.columns {
+            for asset in &self.assets {
+                self.geometry.request(&asset.uuid);
+            }
+        }
+    }
+
+    // Log hook scripts that failed since the last call
+    pub fn report_hook_failures(&mut self) {
+        for failure in self.hooks.take_failures() {
+            self.notify(
+                NotificationLevel::Warning,
+                format!("{} hook failed: {}", failure.event.name(), failure.error),
+            );
+            self.add_log_entry(
+                LogEntry::new(LogStatus::Error, failure.command_line).with_message(format!(
+                    "{} hook: {}",
+                    failure.event.name(),
+                    failure.error
+                )),
+            );
+        }
+    }
+
+    // Move finished prefetches into the folder cache, unless fresher data got there first
+    pub fn apply_prefetched(&mut self) {
+        for listing in self.prefetcher.take_listings() {
+            self.apply_folder_counts(&listing);
+        }
+        for prefetched in self.prefetcher.take_ready() {
+            if self
+                .folder_cache
+                .get(&prefetched.path)
+                .is_some_and(FolderCache::is_fresh)
+            {
+                continue;
+            }
+
+            let assets = prefetched
+                .assets
+                .into_iter()
+                .map(|a| Asset {
+                    uuid: a.uuid,
+                    name: a.name,
+                    folder_uuid: prefetched.path.clone(),
+                    file_type: a.file_type,
+                    size: a.file_size,
+                    path: a.path,
+                    metadata: a.metadata,
+                    is_assembly: a.is_assembly,
+                    processing_status: a.processing_status,
+                })
+                .collect();
+            self.folder_cache.insert(
+                prefetched.path,
+                FolderCache {
+                    folders: prefetched.listing,
+                    assets,
+                    timestamp: std::time::SystemTime::now(),
+                },
+            );
+        }
     }
 
     async fn load_all_folders(&mut self) {
@@ -1034,36 +2940,32 @@ impl App {
 
         // Check if we have cached data for root
         if let Some(cached_data) = self.folder_cache.get(root_path) {
-            // Check if cache is still valid (less than 5 minutes old)
-            if cached_data
-                .timestamp
-                .elapsed()
-                .unwrap_or(std::time::Duration::MAX)
-                < std::time::Duration::from_secs(300)
-            {
-                // 5 minutes
+            // Check if cache is still valid (less than 5 minutes old, or offline)
+            if cached_data.is_fresh() {
                 self.folders = cached_data.folders.clone();
-                self.status_message =
-                    format!("Loaded {} top-level folders from cache", self.folders.len());
-                self.last_executed_command = String::from("pcli2 folder list --format json");
+                self.folders_source = Some(DataSource::cached(root_path, cached_data.timestamp));
+                self.notify(
+                    NotificationLevel::Info,
+                    format!("Loaded {} top-level folders from cache", self.folders.len()),
+                );
+                self.last_executed_command =
+                    PcliCommand::new(&["folder", "list", "--format", "json"]);
                 self.command_history
-                    .push(self.last_executed_command.clone());
-                self.add_log_entry(format!(
-                    "[{}] ✓ CACHED: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
+                    .push(HistoryEntry::new(self.last_executed_command.clone()));
+                self.log_cache_hit();
                 return;
             }
         }
 
-        self.last_executed_command = String::from("pcli2 folder list --format json");
+        self.last_executed_command = PcliCommand::new(&["folder", "list", "--format", "json"]);
         self.command_history
-            .push(self.last_executed_command.clone());
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
         self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
         self.status_message = "Loading all folders...".to_string();
 
-        match pcli_commands::list_folders() {
+        self.last_operation = Some(Operation::LoadFolders);
+        match pcli_commands::list_folders().await {
             Ok(pcli_folders) => {
                 // Convert pcli folders to our internal representation
                 // Only include top-level folders (those without '/' in their path)
@@ -1090,45 +2992,65 @@ impl App {
                 self.folder_cache.insert(root_path.to_string(), cache_entry);
 
                 self.folders = folders;
-                self.status_message = format!("Loaded {} top-level folders", self.folders.len());
-
-                // Log successful command with success indicator
-                self.add_log_entry(format!(
-                    "[{}] ✓ SUCCESS: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
+                self.folders_source = Some(DataSource::fetched(root_path));
+                self.report_listing("Loaded", self.folders.len(), "top-level folders", "");
                 self.command_in_progress = false; // Clear flag when command completes
             }
             Err(e) => {
-                self.status_message = format!("Error loading folders: {}", e);
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Error loading folders: {}", e),
+                );
 
                 // Log failed command with error indicator
-                self.add_log_entry(format!(
-                    "[{}] ✗ ERROR: {} - {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command,
-                    e
-                ));
+                self.log_command_error(&e);
                 self.command_in_progress = false; // Clear flag when command completes
             }
         }
     }
 
     pub async fn enter_folder(&mut self, folder_path: String) {
+        // Offline, only the folders opened before have their subfolders in the cache; a cache
+        // entry filled from the parent's listing still lists the folder itself
+        let offline = offline::is_enabled();
+        if offline
+            && !self.folder_cache.get(&folder_path).is_some_and(|cache| {
+                cache
+                    .folders
+                    .iter()
+                    .all(|folder| folder.path != folder_path)
+            })
+        {
+            self.notify(
+                NotificationLevel::Warning,
+                format!(
+                    "Offline: {} was not opened before, so its subfolders are not cached",
+                    folder_path
+                ),
+            );
+            return;
+        }
+
         // Store the folder name being entered so we can select it when going back
-        let folder_name_entered = folder_path.split('/').last().unwrap_or(&folder_path).to_string();
+        let folder_name_entered = folder_path
+            .split('/')
+            .next_back()
+            .unwrap_or(&folder_path)
+            .to_string();
         self.last_entered_folder_path = Some(folder_name_entered);
 
         let folder_path_clone = folder_path.clone();
-        self.current_folder = Some(folder_path);
+        self.set_current_folder(Some(folder_path));
 
         // Force reload of folders by temporarily removing from cache
-        self.folder_cache.remove(&folder_path_clone);
+        if !offline {
+            self.folder_cache.remove(&folder_path_clone);
+        }
         self.load_folders_for_current_context().await;
 
         // Clear previous assets and load for the current folder
-        self.assets.clear();
+        self.assets_source = None;
+        self.set_assets(Vec::new());
         self.load_assets_for_current_folder().await;
 
         // Reset selection indices when entering a new folder
@@ -1154,7 +3076,7 @@ impl App {
                     // Extract the folder name that we're going back from
                     let folder_name_we_came_from = current_path[last_slash_idx + 1..].to_string();
 
-                    self.current_folder = Some(parent_path);
+                    self.set_current_folder(Some(parent_path));
 
                     // Reload both folders and assets for the new context
                     self.load_folders_for_current_context().await;
@@ -1162,11 +3084,19 @@ impl App {
 
                     // Find the index of the folder we came from in the current folder list
                     // First, try to find by name
-                    if let Some(index) = self.folders.iter().position(|f| f.name == folder_name_we_came_from) {
+                    if let Some(index) = self
+                        .folders
+                        .iter()
+                        .position(|f| f.name == folder_name_we_came_from)
+                    {
                         self.selected_folder_index = index;
                     } else {
                         // If not found by name, try to find by UUID (in case folder name and UUID differ)
-                        if let Some(index) = self.folders.iter().position(|f| f.uuid == folder_name_we_came_from) {
+                        if let Some(index) = self
+                            .folders
+                            .iter()
+                            .position(|f| f.uuid == folder_name_we_came_from)
+                        {
                             self.selected_folder_index = index;
                         } else {
                             // If still not found, default to the first item (or skip the parent indicator if present)
@@ -1182,14 +3112,18 @@ impl App {
                     // Extract the folder name we're coming from
                     let folder_name_we_came_from = current_path.clone();
 
-                    self.current_folder = None;
+                    self.set_current_folder(None);
 
                     // Reload both folders and assets for the new context
                     self.load_folders_for_current_context().await;
                     self.load_assets_for_current_folder().await;
 
                     // Find the index of the folder we came from in the current folder list
-                    if let Some(index) = self.folders.iter().position(|f| f.name == folder_name_we_came_from) {
+                    if let Some(index) = self
+                        .folders
+                        .iter()
+                        .position(|f| f.name == folder_name_we_came_from)
+                    {
                         self.selected_folder_index = index;
                     } else {
                         // If not found, default to the first item (or skip the parent indicator if present)
@@ -1207,7 +3141,7 @@ impl App {
             }
             None => {
                 // Already at root, nothing to go back to
-                self.status_message = "Already at root folder".to_string();
+                self.notify(NotificationLevel::Info, "Already at root folder");
             }
         }
     }
@@ -1219,337 +3153,4674 @@ impl App {
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn download_asset(&mut self, asset: &Asset) {
-        self.status_message = format!("Downloading asset: {}...", asset.name);
+    // Queue the file's configured viewer; the main loop runs it with the TUI suspended
+    fn open_in_viewer(&mut self, path: PathBuf) {
+        let name = path.display().to_string();
+        let Some(command_line) = self.config.viewers.command_for(&path) else {
+            self.notify(
+                NotificationLevel::Warning,
+                format!("No viewer configured for {}", name),
+            );
+            return;
+        };
+        match ExternalCommand::viewer(command_line, &path) {
+            Some(command) => self.pending_external = Some(command),
+            None => self.notify(
+                NotificationLevel::Warning,
+                format!("Viewer command for {} is empty", name),
+            ),
+        }
+    }
 
-        match pcli_commands::download_asset(&asset.uuid) {
-            Ok(()) => {
-                self.status_message = format!("Successfully downloaded: {}", asset.name);
+    // Queue the user's shell with the current folder and selected asset in its environment
+    fn open_shell(&mut self) {
+        let asset_uuid = self
+            .assets
+            .get(self.selected_asset_index)
+            .map(|asset| asset.uuid.clone());
+        self.pending_external = Some(ExternalCommand::shell(
+            self.current_folder.as_deref(),
+            asset_uuid.as_deref(),
+        ));
+    }
+
+    async fn handle_custom_command_menu_keys(&mut self, key: KeyEvent) {
+        let Some(selected) = self.custom_command_menu else {
+            return;
+        };
+        let count = self.config.custom_commands.len();
+
+        match KeyContext::CustomCommands.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::CustomCommands),
+            Some(ViewAction::Up) => {
+                self.custom_command_menu = Some(selected.saturating_sub(1));
             }
-            Err(e) => {
-                self.status_message = format!("Download failed: {}", e);
+            Some(ViewAction::Down) => {
+                self.custom_command_menu = Some((selected + 1).min(count.saturating_sub(1)));
+            }
+            Some(ViewAction::Open) => {
+                self.close_modal(Modal::CustomCommands);
+                self.run_custom_command(selected).await;
+            }
+            // A command's own key runs it straight from the menu
+            None if let KeyCode::Char(c) = key.code => {
+                if let Some(index) = self
+                    .config
+                    .custom_commands
+                    .iter()
+                    .position(|command| command.key == Some(c))
+                {
+                    self.close_modal(Modal::CustomCommands);
+                    self.run_custom_command(index).await;
+                }
             }
+            _ => {}
         }
     }
 
-    pub async fn download_asset_by_uuid(&mut self, asset_uuid: &str, asset_name: &str) {
-        self.status_message = format!("Downloading asset: {}...", asset_name);
+    // Run a custom command on the selected asset and log its output
+    async fn run_custom_command(&mut self, index: usize) {
+        let Some(command) = self.config.custom_commands.get(index).cloned() else {
+            return;
+        };
+        let Some(asset) = self.assets.get(self.selected_asset_index).cloned() else {
+            self.notify(
+                NotificationLevel::Warning,
+                format!("Select an asset to run {}", command.name),
+            );
+            return;
+        };
 
-        match pcli_commands::download_asset(asset_uuid) {
-            Ok(()) => {
-                self.status_message = format!("Successfully downloaded: {}", asset_name);
+        let download_path = self
+            .download_transfer(&asset.name, asset.folder_path(), asset.size)
+            .path
+            .display()
+            .to_string();
+        let folder = self
+            .current_folder
+            .clone()
+            .unwrap_or_else(|| "/".to_string());
+        let args = external::expand(
+            &command.command,
+            &[
+                ("uuid", &asset.uuid),
+                ("name", &asset.name),
+                ("path", &asset.path),
+                ("folder", &folder),
+                ("download_path", &download_path),
+            ],
+        );
+        let command_line = args.join(" ");
+        self.status_message = format!("Running {} on {}", command.name, asset.name);
+
+        let started = Instant::now();
+        let result = external::capture(&args).await;
+        let duration = Some(started.elapsed());
+
+        match result {
+            Ok(output) => {
+                let detail = ErrorDetail {
+                    timestamp: Local::now(),
+                    command_line: command_line.clone(),
+                    exit_code: output.status.code(),
+                    message: format!("{} exited with {}", command.name, output.status),
+                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                };
+                if output.status.success() {
+                    // The last line of output usually says what happened
+                    let summary = detail
+                        .stdout
+                        .lines()
+                        .rev()
+                        .find(|line| !line.trim().is_empty())
+                        .map_or_else(|| command.name.clone(), |line| line.trim().to_string());
+                    self.add_log_entry(
+                        LogEntry::new(LogStatus::Success, command_line)
+                            .with_duration(duration)
+                            .with_exit_code(output.status.code())
+                            .with_message(summary)
+                            .with_error(detail),
+                    );
+                    self.notify(
+                        NotificationLevel::Success,
+                        format!("{} finished", command.name),
+                    );
+                } else {
+                    self.add_log_entry(
+                        LogEntry::new(LogStatus::Error, command_line)
+                            .with_duration(duration)
+                            .with_exit_code(output.status.code())
+                            .with_message(detail.message.clone())
+                            .with_error(detail.clone()),
+                    );
+                    self.notify(NotificationLevel::Error, detail.message.clone());
+                    self.last_error = Some(detail);
+                }
             }
             Err(e) => {
-                self.status_message = format!("Download failed: {}", e);
+                let detail = ErrorDetail::from_error(&e, &command_line);
+                self.add_log_entry(
+                    LogEntry::new(LogStatus::Error, command_line)
+                        .with_duration(duration)
+                        .with_message(format!("{:#}", e))
+                        .with_error(detail.clone()),
+                );
+                self.notify(NotificationLevel::Error, format!("{:#}", e));
+                self.last_error = Some(detail);
             }
         }
     }
 
-    pub async fn upload_asset_interactive(&mut self) {
-        // In a real implementation, this would open a file picker dialog
-        // For now, we'll simulate with a placeholder
-        self.status_message =
-            "Upload feature: In a real implementation, this would open a file picker".to_string();
+    pub fn take_external_command(&mut self) -> Option<ExternalCommand> {
+        self.pending_external.take()
     }
 
-    pub async fn perform_search(&mut self) {
-        if self.search_query.trim().is_empty() {
-            self.status_message = "Empty search query".to_string();
-            return;
+    // Report how an external program run by the main loop ended
+    pub fn external_command_finished(
+        &mut self,
+        command: &ExternalCommand,
+        result: anyhow::Result<std::process::ExitStatus>,
+    ) {
+        match result {
+            Ok(status) if status.success() => {
+                self.status_message = format!("{} closed", command.description);
+            }
+            Ok(status) => self.notify(
+                NotificationLevel::Warning,
+                format!("{} exited with {}", command.description, status),
+            ),
+            Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
         }
+    }
 
-        self.last_executed_command = format!(
-            "pcli2 asset text-match --text \"{}\" --format json --metadata",
-            self.search_query
-        );
-        self.command_history
-            .push(self.last_executed_command.clone());
-        self.command_in_progress = true; // Set flag when command starts
-        self.status_message = format!("Searching for: {}", self.search_query);
-
-        match pcli_commands::search_assets(&self.search_query) {
-            Ok(pcli_assets) => {
-                // Store search results separately from folder assets
-                self.search_results = pcli_assets
-                    .into_iter()
-                    .map(|a| Asset {
-                        uuid: a.uuid,
-                        name: a.name,
-                        folder_uuid: a.path.split('/').next().unwrap_or_default().to_string(), // Extract folder from path
-                        file_type: a.file_type,
-                        size: a.file_size,
-                        path: a.path,
-                        metadata: a.metadata,
-                    })
-                    .collect();
+    // Where pcli2 saves a downloaded asset, watched for the transfer gauge
+    // A download into the directory configured for the folder the asset is in
+    fn download_transfer(
+        &self,
+        name: &str,
+        folder_path: &str,
+        size: Option<u64>,
+    ) -> pcli_commands::Transfer {
+        pcli_commands::Transfer {
+            label: name.to_string(),
+            path: self.config.downloads.directory_for(folder_path).join(name),
+            total: size,
+        }
+    }
 
-                self.status_message = format!("Found {} assets", self.search_results.len());
+    #[allow(dead_code)]
+    pub async fn download_asset(&mut self, asset: &Asset) {
+        self.status_message = format!("Downloading asset: {}...", asset.name);
 
-                // Log successful command with success indicator
-                self.add_log_entry(format!(
-                    "[{}] ✓ SUCCESS: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+        let transfer = self.download_transfer(&asset.name, asset.folder_path(), asset.size);
+        let path = transfer.path.clone();
+        match pcli_commands::download_asset(&asset.uuid, &path, Some(transfer)).await {
+            Ok(()) => {
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Successfully downloaded: {}", asset.name),
+                );
             }
             Err(e) => {
-                self.status_message = format!("Search failed: {}", e);
-
-                // Log failed command with error indicator
-                self.add_log_entry(format!(
-                    "[{}] ✗ ERROR: {} - {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command,
-                    e
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+                self.notify(NotificationLevel::Error, format!("Download failed: {}", e));
             }
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn upload_asset_to_current_folder(&mut self, file_path: &str) {
-        if let Some(ref folder_path) = self.current_folder {
-            self.status_message = format!("Uploading asset: {}...", file_path);
-
-            match pcli_commands::upload_asset_to_folder(file_path, folder_path) {
-                Ok(()) => {
-                    self.status_message = format!("Successfully uploaded: {}", file_path);
-                    // Reload assets to show the newly uploaded one
-                    self.load_assets_for_current_folder().await;
-                }
-                Err(e) => {
-                    self.status_message = format!("Upload failed: {}", e);
-                }
-            }
+    // Download an asset; for assemblies, first ask whether to include their dependencies
+    pub async fn request_download(&mut self, asset: Asset) {
+        let existing = Some(
+            self.download_transfer(&asset.name, asset.folder_path(), asset.size)
+                .path,
+        )
+        .filter(|path| path.exists());
+        if asset.is_assembly {
+            self.assembly_download_prompt = Some(asset);
+            self.modals.push(Modal::AssemblyDownload);
+        } else if let Some(path) = existing {
+            let message = format!("{} was downloaded before. Overwrite it?", asset.name);
+            self.open_confirm(
+                Confirm::new(" Overwrite File ", message, ConfirmAction::Overwrite(asset))
+                    .with_detail(path.display().to_string()),
+            );
         } else {
-            self.status_message = "No folder selected for upload".to_string();
+            self.download_asset_by_uuid(&asset.uuid, &asset.name).await;
         }
     }
 
-    fn add_log_entry(&mut self, entry: String) {
-        self.log_entries.push(entry);
-
-        // Limit log history to 200 entries
-        if self.log_entries.len() > 200 {
-            // Remove oldest entries, keeping the most recent 200
-            let excess = self.log_entries.len() - 200;
-            self.log_entries.drain(0..excess);
-
-            // Adjust scroll position if needed
-            if self.log_scroll_position >= excess {
-                self.log_scroll_position -= excess;
-            } else {
-                self.log_scroll_position = 0;
+    async fn handle_assembly_download_prompt_keys(&mut self, key: KeyEvent) {
+        let Some(asset) = self.assembly_download_prompt.clone() else {
+            return;
+        };
+        match KeyContext::AssemblyDownload.action(key) {
+            Some(ViewAction::Yes) => {
+                self.close_modal(Modal::AssemblyDownload);
+                self.start_assembly_download(asset);
+            }
+            Some(ViewAction::No) => {
+                self.close_modal(Modal::AssemblyDownload);
+                self.download_asset_by_uuid(&asset.uuid, &asset.name).await;
             }
+            Some(ViewAction::Back) => self.close_modal(Modal::AssemblyDownload),
+            _ => {}
         }
+    }
 
-        // Always auto-scroll to the bottom to show the latest log entry
-        self.log_scroll_position = self.log_entries.len().saturating_sub(1);
+    // Stage the asset for deletion, or take it off the list again; nothing is deleted until the
+    // list is committed from the pending deletions view
+    fn toggle_deletion(&mut self, asset: Asset) {
+        if !self.deletions.contains(&asset.uuid) && self.refuse_denied(Permission::Delete) {
+            return;
+        }
+        let name = asset.name.clone();
+        let message = if self.deletions.toggle(asset) {
+            format!(
+                "Staged {} for deletion ({} pending; Ctrl+X reviews and deletes them)",
+                name,
+                self.deletions.len()
+            )
+        } else {
+            format!("Took {} off the pending deletions", name)
+        };
+        self.notify(NotificationLevel::Info, message);
     }
 
-    async fn handle_geometric_match_keys(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                // Close the geometric match modal
-                self.show_geometric_match_modal = false;
-            }
-            KeyCode::Up => {
-                // Navigate up in geometric match results
-                if !self.geometric_match_results.is_empty() {
-                    if self.geometric_match_scroll_position > 0 {
-                        self.geometric_match_scroll_position -= 1;
-                    }
-                }
-            }
-            KeyCode::Down => {
-                // Navigate down in geometric match results
-                if !self.geometric_match_results.is_empty() {
-                    if self.geometric_match_scroll_position < self.geometric_match_results.len() - 1 {
-                        self.geometric_match_scroll_position += 1;
-                    }
+    fn handle_deletions_keys(&mut self, key: KeyEvent) {
+        match KeyContext::Deletions.action(key) {
+            Some(ViewAction::Back) => self.current_state = AppState::Folders,
+            Some(ViewAction::Up) => self.deletions.move_selection(-1),
+            Some(ViewAction::Down) => self.deletions.move_selection(1),
+            Some(ViewAction::PageUp) => self.deletions.move_selection(-10),
+            Some(ViewAction::PageDown) => self.deletions.move_selection(10),
+            Some(ViewAction::Remove) => {
+                if let Some(asset) = self.deletions.remove_selected() {
+                    let message = format!("Took {} off the pending deletions", asset.name);
+                    self.notify(NotificationLevel::Info, message);
                 }
             }
-            KeyCode::Left => {
-                // Scroll left in the table (horizontal scrolling)
-                if self.geometric_match_horizontal_scroll > 0 {
-                    self.geometric_match_horizontal_scroll -= 1;
-                }
+            Some(ViewAction::Clear) => {
+                self.deletions.clear();
+                let message = "Emptied the pending deletions; nothing was deleted";
+                self.notify(NotificationLevel::Info, message);
             }
-            KeyCode::Right => {
-                // Scroll right in the table (horizontal scrolling)
-                // We can't determine max columns without knowing the terminal width, so just increment
-                self.geometric_match_horizontal_scroll += 1;
+            _ if self.deletions.is_empty() => {}
+            Some(ViewAction::Export) => {
+                let result = export::export_deletions(&self.export_dir(), self.deletions.assets());
+                self.report_export("Pending deletions", result);
             }
+            Some(ViewAction::Open) => self.confirm_deletions(),
             _ => {}
         }
     }
-}
 
-impl App {
-    pub fn show_asset_details(&mut self) {
-        if self.assets.is_empty() || self.selected_asset_index >= self.assets.len() {
-            return; // No assets or invalid selection
+    // Ask to confirm deleting the staged assets
+    fn confirm_deletions(&mut self) {
+        if self.refuse_offline("Deleting assets") || self.refuse_denied(Permission::Delete) {
+            return;
         }
+        let count = self.deletions.len();
+        let restorable = self
+            .deletions
+            .assets()
+            .iter()
+            .filter(|asset| {
+                self.downloaded
+                    .get(&asset.uuid)
+                    .is_some_and(|file| file.is_file())
+            })
+            .count();
+        let dir = self.export_dir();
+        let dir = std::path::absolute(&dir).unwrap_or(dir);
+        self.open_confirm(
+            Confirm::new(
+                " 🗑 Delete Assets ",
+                format!("Delete {} assets? They are removed from the tenant.", count),
+                ConfirmAction::DeleteStaged,
+            )
+            .with_detail(format!(
+                "The list is exported to {} first; {} of them were downloaded in this session \
+                 and can be uploaded again with U",
+                dir.display(),
+                restorable
+            )),
+        );
+    }
 
-        let selected_asset = &self.assets[self.selected_asset_index];
-        let asset_uuid = &selected_asset.uuid;
-
-        self.last_executed_command = format!("pcli2 asset get --uuid \"{}\" --format json --metadata", asset_uuid);
-        self.command_history.push(self.last_executed_command.clone());
-        self.command_in_progress = true; // Set flag when command starts
-        self.status_message = format!("Loading details for asset: {}", selected_asset.name);
+    // Export the list of staged assets, then delete them one at a time in a job; those that
+    // fail stay staged. Nothing is deleted when the list can't be exported.
+    fn commit_deletions(&mut self) {
+        let assets = self.deletions.assets().to_vec();
+        let files = match export::export_deletions(&self.export_dir(), &assets) {
+            Ok(files) => files,
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Nothing deleted; the list couldn't be exported: {:#}", e),
+                );
+                return;
+            }
+        };
+        let files: Vec<String> = files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect();
+        self.notify(
+            NotificationLevel::Info,
+            format!(
+                "Deleting {} assets; the list is in {}",
+                assets.len(),
+                files.join(", ")
+            ),
+        );
+        self.start_work(
+            format!("Delete {} staged assets", assets.len()),
+            Work::Delete { assets },
+        );
+    }
 
-        match pcli_commands::get_asset_details(asset_uuid) {
-            Ok(pcli_asset_details) => {
-                // Convert from pcli_commands::AssetDetails to app::AssetDetails
-                let asset_details = crate::app::AssetDetails {
-                    uuid: pcli_asset_details.uuid,
-                    name: pcli_asset_details.name,
-                    path: pcli_asset_details.path,
-                    file_type: pcli_asset_details.file_type,
-                    file_size: pcli_asset_details.file_size,
-                    processing_status: pcli_asset_details.processing_status,
-                    created_at: pcli_asset_details.created_at,
-                    updated_at: pcli_asset_details.updated_at,
-                    is_assembly: pcli_asset_details.is_assembly,
-                    tenant_id: pcli_asset_details.tenant_id,
-                    folder_id: pcli_asset_details.folder_id,
-                    state: pcli_asset_details.state,
+    async fn handle_confirm_keys(&mut self, key: KeyEvent) {
+        match KeyContext::Confirm.action(key) {
+            Some(ViewAction::Yes) => {
+                let Some(confirm) = self.confirm.take() else {
+                    return;
                 };
+                self.modals.close(Modal::Confirm);
+                match confirm.action {
+                    ConfirmAction::DeleteStaged => self.commit_deletions(),
+                    ConfirmAction::Overwrite(asset) => {
+                        self.download_asset_by_uuid(&asset.uuid, &asset.name).await;
+                    }
+                    ConfirmAction::Redownload { uuid, name } => {
+                        self.download_asset_by_uuid(&uuid, &name).await;
+                    }
+                    ConfirmAction::Upload(path) => self.upload_asset_to_current_folder(path).await,
+                    ConfirmAction::Quit => self.should_quit = true,
+                    ConfirmAction::DeleteProperty(name) => self.delete_property(name).await,
+                    ConfirmAction::DownloadFolder(download) => self.start_folder_download(download),
+                }
+            }
+            Some(ViewAction::Back) => self.close_modal(Modal::Confirm),
+            _ => {}
+        }
+    }
 
-                self.selected_asset_details = Some(asset_details);
-                self.show_asset_details_modal = true;
-                self.status_message = format!("Loaded details for {}", selected_asset.name);
+    // Quit, after asking first when background jobs would be cut short
+    fn request_quit(&mut self) {
+        let running: Vec<&str> = self
+            .jobs
+            .running()
+            .iter()
+            .map(|job| job.title.as_str())
+            .collect();
+        if running.is_empty() {
+            self.should_quit = true;
+            return;
+        }
+        let detail = running.join(", ");
+        self.open_confirm(
+            Confirm::new(
+                " Quit ",
+                format!(
+                    "{} background job(s) still running and will be stopped. Quit anyway?",
+                    running.len()
+                ),
+                ConfirmAction::Quit,
+            )
+            .with_detail(detail),
+        );
+    }
 
-                // Log successful command with success indicator
-                self.add_log_entry(format!(
-                    "[{}] ✓ SUCCESS: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+    // A job deleted the asset: unstage it and drop it from every list it shows up in
+    fn forget_deleted(&mut self, asset: Asset) {
+        self.deletions.remove(&asset.uuid);
+        self.update_folder_counts(asset.folder_path(), 0, -1);
+        self.assets.retain(|a| a.uuid != asset.uuid);
+        self.all_assets.retain(|a| a.uuid != asset.uuid);
+        self.table_changed();
+        self.search_results.retain(|a| a.uuid != asset.uuid);
+        self.selected_asset_index = self
+            .selected_asset_index
+            .min(self.assets.len().saturating_sub(1));
+        self.selected_search_result_index = self
+            .selected_search_result_index
+            .min(self.search_results.len().saturating_sub(1));
+        // It can only come back from a local copy, as a new asset
+        if let Some(file) = self
+            .downloaded
+            .get(&asset.uuid)
+            .filter(|file| file.is_file())
+            .cloned()
+        {
+            self.undo.push(Undoable::Delete { asset, file });
+        }
+    }
+
+    // Reverse the most recent operation that can be undone
+    async fn undo_last(&mut self) {
+        if self.refuse_offline("Undoing changes") {
+            return;
+        }
+        let Some(entry) = self.undo.pop() else {
+            self.notify(
+                NotificationLevel::Info,
+                "Nothing to undo; moves, renames and deletes of downloaded assets can be undone",
+            );
+            return;
+        };
+        self.notify(
+            NotificationLevel::Info,
+            format!("Undo: {}", entry.describe()),
+        );
+        let undone = match entry.clone() {
+            Undoable::Move { asset, from } => {
+                // The asset is wherever it was moved to now
+                self.move_asset(asset, from).await
             }
-            Err(e) => {
-                self.status_message = format!("Failed to load asset details: {}", e);
+            Undoable::Rename { asset, previous } => self.rename_asset(asset, previous).await,
+            Undoable::MoveFolder { folder, from } => self.move_folder(folder, from).await.is_some(),
+            Undoable::Delete { asset, file } => self.upload_deleted(asset, file).await,
+        };
+        // Keep it to try again
+        if !undone {
+            self.undo.push(entry);
+        }
+    }
 
-                // Log failed command with error indicator
-                self.add_log_entry(format!(
-                    "[{}] ✗ ERROR: {} - {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command,
-                    e
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+    // Upload the local copy of a deleted asset into the folder it was in. It comes back as a
+    // new asset: the UUID and metadata are not restored.
+    async fn upload_deleted(&mut self, asset: Asset, file: PathBuf) -> bool {
+        let folder = match asset.folder_path() {
+            "" => "/".to_string(),
+            folder => folder.to_string(),
+        };
+        let file_path = file.display().to_string();
+        self.last_executed_command = PcliCommand::new(&[
+            "asset", "create", "--file", &file_path, "--folder", &folder, "--format", "json",
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Uploading {} to {}...", asset.name, folder);
+
+        let result = pcli_commands::create_asset(&file_path, &folder).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(_) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!(
+                        "Uploaded {} to {} again as a new asset; its metadata is not restored",
+                        asset.name, folder
+                    ),
+                );
+                self.update_folder_counts(&folder, 0, 1);
+                self.load_assets_for_current_folder().await;
+                true
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Upload failed: {}", e));
+                self.log_command_error(&e);
+                false
             }
         }
     }
-    pub async fn perform_geometric_match(&mut self, asset_uuid: &str) {
-        self.last_executed_command = format!(
-            "pcli2 asset geometric-match --uuid \"{}\" --format json --metadata",
-            asset_uuid
+
+    // Ask for the folder to move an asset into, starting from the one it is in
+    fn start_move_prompt(&mut self, asset: Asset) {
+        if self.refuse_offline("Moving assets") {
+            return;
+        }
+        let folder = asset
+            .path
+            .rsplit_once('/')
+            .map(|(folder, _)| format!("{}/", folder))
+            .unwrap_or_default();
+        self.open_prompt(
+            Prompt::new(
+                format!("Move {} to folder: ", asset.name),
+                PromptAction::Move(asset),
+                dialog::not_empty,
+            )
+            .with_input(folder),
+        );
+    }
+
+    // Ask for a new name, starting from the current one
+    fn start_rename_prompt(&mut self, asset: Asset) {
+        if self.refuse_offline("Renaming assets") {
+            return;
+        }
+        let name = asset.name.clone();
+        self.open_prompt(
+            Prompt::new(
+                format!("Rename {} to: ", asset.name),
+                PromptAction::Rename(asset),
+                dialog::name,
+            )
+            .with_input(name),
         );
+    }
+
+    // Ask for the name of a folder to create in the current one
+    fn start_create_folder_prompt(&mut self) {
+        if self.refuse_offline("Creating folders") || self.refuse_denied(Permission::CreateFolder) {
+            return;
+        }
+        let parent = self.current_folder.clone();
+        let label = match parent {
+            Some(ref parent) => format!("New folder in {}: ", parent),
+            None => "New top-level folder: ".to_string(),
+        };
+        self.open_prompt(Prompt::new(
+            label,
+            PromptAction::CreateFolder(parent),
+            dialog::name,
+        ));
+    }
+
+    // Returns whether the asset was moved
+    async fn move_asset(&mut self, asset: Asset, folder: String) -> bool {
+        let folder = match folder.trim_end_matches('/') {
+            "" => "/".to_string(),
+            folder => folder.to_string(),
+        };
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "move",
+            "--uuid",
+            &asset.uuid,
+            "--folder-path",
+            &folder,
+        ]);
         self.command_history
-            .push(self.last_executed_command.clone());
-        self.command_in_progress = true; // Set flag when command starts
-        self.status_message = format!("Performing geometric match on asset: {}", asset_uuid);
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Moving {} to {}...", asset.name, folder);
 
-        match pcli_commands::geometric_match(asset_uuid) {
-            Ok(pcli_match_results) => {
-                // Store geometric match results with similarity scores
-                self.geometric_match_results = pcli_match_results
-                    .into_iter()
-                    .map(|match_entry| {
-                        let asset = Asset {
-                            uuid: match_entry.asset.uuid,
-                            name: match_entry.asset.name,
-                            folder_uuid: match_entry.asset.path.split('/').next().unwrap_or_default().to_string(), // Extract folder from path
-                            file_type: match_entry.asset.file_type,
-                            size: match_entry.asset.file_size,
-                            path: match_entry.asset.path,
-                            metadata: match_entry.asset.metadata,
-                        };
-                        (asset, match_entry.similarity_score)
-                    })
-                    .collect();
+        let result = pcli_commands::move_asset(&asset.uuid, &folder).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Moved {} to {}", asset.name, folder),
+                );
+                self.update_folder_counts(asset.folder_path(), 0, -1);
+                self.update_folder_counts(&folder, 0, 1);
+                let path = format!("{}/{}", folder.trim_end_matches('/'), asset.name);
+                if let Some(result) = self
+                    .search_results
+                    .iter_mut()
+                    .find(|a| a.uuid == asset.uuid)
+                {
+                    result.path = path;
+                    result.folder_uuid = folder;
+                }
+                // The asset left or joined the folder on screen
+                self.load_assets_for_current_folder().await;
+                true
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Move failed: {}", e));
+                self.log_command_error(&e);
+                false
+            }
+        }
+    }
 
-                self.status_message = format!("Found {} geometric matches", self.geometric_match_results.len());
+    // Returns whether the asset was renamed
+    async fn rename_asset(&mut self, asset: Asset, name: String) -> bool {
+        self.last_executed_command =
+            PcliCommand::new(&["asset", "rename", "--uuid", &asset.uuid, "--name", &name]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Renaming {} to {}...", asset.name, name);
 
-                // Log successful command with success indicator
-                self.add_log_entry(format!(
-                    "[{}] ✓ SUCCESS: {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+        let result = pcli_commands::rename_asset(&asset.uuid, &name).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Renamed {} to {}", asset.name, name),
+                );
+                if let Some(result) = self
+                    .search_results
+                    .iter_mut()
+                    .find(|a| a.uuid == asset.uuid)
+                {
+                    result.path = match result.path.rsplit_once('/') {
+                        Some((folder, _)) => format!("{}/{}", folder, name),
+                        None => name.clone(),
+                    };
+                    result.name = name;
+                }
+                self.load_assets_for_current_folder().await;
+                true
             }
             Err(e) => {
-                self.status_message = format!("Geometric match failed: {}", e);
+                self.notify(NotificationLevel::Error, format!("Rename failed: {}", e));
+                self.log_command_error(&e);
+                false
+            }
+        }
+    }
 
-                // Log failed command with error indicator
-                self.add_log_entry(format!(
-                    "[{}] ✗ ERROR: {} - {}",
-                    Local::now().format("%H:%M:%S"),
-                    self.last_executed_command,
-                    e
-                ));
-                self.command_in_progress = false; // Clear flag when command completes
+    async fn create_folder(&mut self, parent: Option<String>, name: String) {
+        let mut command = PcliCommand::new(&["folder", "create", "--name", &name]);
+        if let Some(ref parent) = parent {
+            command
+                .args
+                .extend(["--parent-folder-path".to_string(), parent.clone()]);
+        }
+        self.last_executed_command = command;
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Creating folder {}...", name);
+
+        let result = pcli_commands::create_folder(&name, parent.as_deref()).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Created folder {}", name),
+                );
+                if let Some(ref parent) = parent {
+                    self.update_folder_counts(parent, 1, 0);
+                }
+                self.load_folders_for_current_context().await;
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Create folder failed: {}", e),
+                );
+                self.log_command_error(&e);
             }
         }
     }
-    pub async fn handle_mouse_event(&mut self, mouse: crossterm::event::MouseEvent) {
-        match mouse.kind {
-            crossterm::event::MouseEventKind::ScrollDown => {
-                // Handle scrolling down in the active pane
-                match self.active_pane {
-                    crate::app::ActivePane::Folders => {
-                        if !self.folders.is_empty() {
-                            self.selected_folder_index =
-                                (self.selected_folder_index + 1).min(self.folders.len() - 1);
-                        }
-                    }
-                    crate::app::ActivePane::Assets => {
-                        if !self.assets.is_empty() {
-                            self.selected_asset_index =
-                                (self.selected_asset_index + 1).min(self.assets.len() - 1);
-                        }
-                    }
-                    crate::app::ActivePane::Log => {
-                        // Scroll down in the log
-                        if self.log_scroll_position < self.log_entries.len().saturating_sub(1) {
-                            self.log_scroll_position += 1;
+
+    // Every folder path seen so far, "" being the top level
+    fn known_folder_paths(&self) -> Vec<String> {
+        let listed = self
+            .folder_cache
+            .values()
+            .flat_map(|cache| cache.folders.iter())
+            .chain(self.folders.iter())
+            .filter(|folder| folder.uuid != "..")
+            .map(|folder| folder.path.clone());
+        std::iter::once(String::new())
+            .chain(self.folder_cache.keys().cloned())
+            .chain(listed)
+            .collect()
+    }
+
+    // Pick the new parent of a folder; it can't go into itself or stay where it is
+    fn start_move_folder(&mut self, folder: Folder) {
+        if self.refuse_offline("Moving folders") {
+            return;
+        }
+        let subtree = format!("{}/", folder.path);
+        let parent = folder
+            .path
+            .rsplit_once('/')
+            .map_or("", |(parent, _)| parent);
+        let folders = self
+            .known_folder_paths()
+            .into_iter()
+            .filter(|path| path != &folder.path && !path.starts_with(&subtree) && path != parent)
+            .collect();
+        self.folder_picker = Some(FolderPicker::new(
+            format!(" Move {} to ", folder.name),
+            folders,
+            PickAction::MoveFolder(folder),
+        ));
+        self.modals.push(Modal::FolderPicker);
+    }
+
+    async fn handle_folder_picker_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut picker) = self.folder_picker else {
+            return;
+        };
+        match KeyContext::FolderPicker.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::FolderPicker),
+            Some(ViewAction::Up) => picker.move_selection(-1),
+            Some(ViewAction::Down) => picker.move_selection(1),
+            Some(ViewAction::PageUp) => picker.move_selection(-10),
+            Some(ViewAction::PageDown) => picker.move_selection(10),
+            _ if picker.input.handle_key(key) => picker.refresh(),
+            Some(ViewAction::Open) => {
+                let Some(destination) = picker.chosen() else {
+                    return;
+                };
+                let Some(picker) = self.folder_picker.take() else {
+                    return;
+                };
+                self.modals.close(Modal::FolderPicker);
+                match picker.action {
+                    PickAction::MoveFolder(folder) => {
+                        let from = folder
+                            .path
+                            .rsplit_once('/')
+                            .map_or("", |(parent, _)| parent);
+                        let from = from.to_string();
+                        if let Some(folder) = self.move_folder(folder, destination).await {
+                            self.undo.push(Undoable::MoveFolder { folder, from });
                         }
                     }
+                    PickAction::MoveBasket => self.start_basket_move(destination),
                 }
             }
-            crossterm::event::MouseEventKind::ScrollUp => {
-                // Handle scrolling up in the active pane
-                match self.active_pane {
-                    crate::app::ActivePane::Folders => {
-                        if self.selected_folder_index > 0 {
-                            self.selected_folder_index -= 1;
-                        }
+            _ => {}
+        }
+    }
+
+    // Move a folder and its subtree under `parent` ("" for the top level); returns the folder
+    // at its new path
+    async fn move_folder(&mut self, folder: Folder, parent: String) -> Option<Folder> {
+        let destination = folder_picker::label(&parent).to_string();
+        self.last_executed_command = PcliCommand::new(&[
+            "folder",
+            "move",
+            "--folder-path",
+            &folder.path,
+            "--parent-folder-path",
+            if parent.is_empty() { "/" } else { &parent },
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Moving {} to {}...", folder.name, destination);
+
+        let target = (!parent.is_empty()).then_some(parent.as_str());
+        let result = pcli_commands::move_folder(&folder.path, target).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Moved {} to {}", folder.name, destination),
+                );
+                // Both parents list different folders now, their own parents show different
+                // counts for them, and nothing cached below the folder has the right path
+                let parent_of =
+                    |path: &str| path.rsplit_once('/').map_or("", |(p, _)| p).to_string();
+                let old_parent = parent_of(&folder.path);
+                let stale = [
+                    parent_of(&old_parent),
+                    old_parent,
+                    parent_of(&parent),
+                    parent.clone(),
+                ];
+                let subtree = format!("{}/", folder.path);
+                self.folder_cache.retain(|path, _| {
+                    !stale.contains(path) && *path != folder.path && !path.starts_with(&subtree)
+                });
+                self.load_folders_for_current_context().await;
+                let path = match parent.as_str() {
+                    "" => folder.name.clone(),
+                    parent => format!("{}/{}", parent, folder.name),
+                };
+                Some(Folder { path, ..folder })
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Move failed: {}", e));
+                self.log_command_error(&e);
+                None
+            }
+        }
+    }
+
+    // Keep the folder cache for the next session and offline mode; the demo's is not kept
+    pub fn save_cache(&mut self) {
+        if demo::is_enabled() {
+            return;
+        }
+        if let Err(e) = offline::save_cache(&self.folder_cache) {
+            tracing::warn!(error = %format!("{:#}", e), "failed to save the folder cache");
+        }
+    }
+
+    // Report a switch to offline mode after pcli2 failed to reach the tenant
+    pub fn update_offline(&mut self) {
+        if offline::take_detected() {
+            self.notify(
+                NotificationLevel::Warning,
+                "Offline: pcli2 can't reach the tenant; browsing cached folders, changes are disabled (O goes back online)",
+            );
+        }
+    }
+
+    // Report that the tenant's API rate limit was hit, once each time it comes into effect
+    pub fn update_rate_limit(&mut self) {
+        if rate_limit::take_detected()
+            && let Some(remaining) = rate_limit::remaining()
+        {
+            self.notify(
+                NotificationLevel::Warning,
+                format!(
+                    "Rate limited by the tenant's API: pcli2 commands resume in {}s",
+                    remaining.as_secs_f64().ceil()
+                ),
+            );
+        }
+    }
+
+    // Changes need pcli2; offline they are turned down with a notice
+    fn refuse_offline(&mut self, what: &str) -> bool {
+        if !offline::is_enabled() {
+            return false;
+        }
+        self.notify(
+            NotificationLevel::Warning,
+            format!("Offline: {} needs pcli2 (O goes back online)", what),
+        );
+        true
+    }
+
+    // Changes the config denies are turned down with why, instead of tried. One the API refused
+    // before is tried again, as the refusal may have been about one folder or asset only.
+    fn refuse_denied(&mut self, permission: Permission) -> bool {
+        if let Some(reason) = permissions::denied(permission) {
+            self.notify(
+                NotificationLevel::Warning,
+                format!("{} isn't permitted: {}", permission.describe(), reason),
+            );
+            return true;
+        }
+        if let Some(error) = permissions::refusal(permission) {
+            self.notify(
+                NotificationLevel::Warning,
+                format!(
+                    "{} was refused last time ({}), trying again",
+                    permission.describe(),
+                    error
+                ),
+            );
+        }
+        false
+    }
+
+    // Search the cached folders the way pcli2 would search the tenant
+    fn search_cache(&mut self, pattern: &SearchPattern, folder: Option<&str>) {
+        let text = pattern.text.to_lowercase();
+        let subtree = folder.map(|folder| format!("{}/", folder));
+        let mut seen = std::collections::HashSet::new();
+        self.search_results = self
+            .cached_assets()
+            .filter(|asset| match (self.search_scope, folder) {
+                (SearchScope::Folder, Some(folder)) => asset.folder_path() == folder,
+                (_, Some(_)) => subtree
+                    .as_ref()
+                    .is_some_and(|subtree| asset.path.starts_with(subtree.as_str())),
+                (_, None) => true,
+            })
+            .filter(|asset| {
+                asset.name.to_lowercase().contains(&text) && pattern.matches(&asset.name)
+            })
+            .filter(|asset| seen.insert(asset.uuid.clone()))
+            .cloned()
+            .collect();
+        self.selected_search_result_index = 0;
+        self.sort_search_results();
+        let context = format!(" in {} cached folders (offline)", self.folder_cache.len());
+        self.report_listing("Found", self.search_results.len(), "assets", &context);
+    }
+
+    // Load the folder listing and the assets on screen from pcli2 again, past the cache
+    async fn refresh(&mut self) {
+        let folders_path = self.current_folder.clone().unwrap_or_default();
+        self.folder_cache.remove(&folders_path);
+        let assets_path = self
+            .assets_source
+            .as_ref()
+            .map(|source| source.path.clone());
+        if let Some(ref path) = assets_path {
+            self.folder_cache.remove(path);
+        }
+        self.load_folders_for_current_context().await;
+        match assets_path {
+            Some(path) if path == folders_path => self.load_assets_for_current_folder().await,
+            Some(_) => self.load_assets_for_selected_folder().await,
+            None => {}
+        }
+    }
+
+    // Every listed or cached entry of the folder at `path`
+    fn folder_entries_mut(&mut self, path: &str) -> impl Iterator<Item = &mut Folder> {
+        let path = path.trim_matches('/').to_string();
+        self.folders
+            .iter_mut()
+            .chain(
+                self.folder_cache
+                    .values_mut()
+                    .flat_map(|cache| cache.folders.iter_mut()),
+            )
+            .filter(move |folder| folder.uuid != ".." && folder.path.trim_matches('/') == path)
+    }
+
+    // Count the subfolders and assets added to (or taken from) a folder right away, and list
+    // its parent again in the background for the tenant's own counts
+    fn update_folder_counts(&mut self, path: &str, folders: i32, assets: i32) {
+        for folder in self.folder_entries_mut(path) {
+            folder.folders_count = folder.folders_count.saturating_add_signed(folders);
+            folder.assets_count = folder.assets_count.saturating_add_signed(assets);
+        }
+        self.refresh_folder_counts(path);
+    }
+
+    // List the parent of the folder at `path` again; the counts are applied when it arrives
+    fn refresh_folder_counts(&mut self, path: &str) {
+        if offline::is_enabled() {
+            return;
+        }
+        let path = path.trim_matches('/');
+        let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+        self.prefetcher.fetch_listing(parent.to_string());
+    }
+
+    // Take the counts of a freshly fetched listing into every entry of its folders
+    fn apply_folder_counts(&mut self, listing: &[Folder]) {
+        for fetched in listing.iter().filter(|folder| folder.uuid != "..") {
+            for folder in self.folder_entries_mut(&fetched.path) {
+                folder.folders_count = fetched.folders_count;
+                folder.assets_count = fetched.assets_count;
+            }
+        }
+    }
+
+    // What the assets table shows changed, so it's laid out again on the next frame
+    fn table_changed(&mut self) {
+        self.table_version += 1;
+    }
+
+    // Show a folder's assets, those with the filter tag only while there is one
+    fn set_assets(&mut self, assets: Vec<Asset>) {
+        self.all_assets = assets;
+        self.hovered_asset = None; // Until the pointer moves over the new list
+        self.apply_tag_filter();
+    }
+
+    fn apply_tag_filter(&mut self) {
+        self.table_changed();
+        self.assets = match self.tag_filter {
+            Some(ref tag) => self
+                .all_assets
+                .iter()
+                .filter(|asset| self.tags.of(asset).contains(tag))
+                .cloned()
+                .collect(),
+            None => self.all_assets.clone(),
+        };
+        self.selected_asset_index = self
+            .selected_asset_index
+            .min(self.assets.len().saturating_sub(1));
+    }
+
+    fn set_tag_filter(&mut self, tag: Option<String>) {
+        let message = match tag {
+            Some(ref tag) => format!("Listing assets tagged {}", tag),
+            None => "Listing all assets".to_string(),
+        };
+        self.tag_filter = tag;
+        self.apply_tag_filter();
+        self.notify(NotificationLevel::Info, message);
+    }
+
+    fn start_tag_picker(&mut self, mode: TagPickerMode) {
+        let known = self
+            .tags
+            .known(self.all_assets.iter().chain(self.cached_assets()));
+        if matches!(mode, TagPickerMode::Filter) && known.is_empty() {
+            self.notify(
+                NotificationLevel::Info,
+                "No asset is tagged yet; t tags one",
+            );
+            return;
+        }
+        let checked = match mode {
+            TagPickerMode::Edit(ref asset) => self.tags.of(asset),
+            TagPickerMode::Filter => Vec::new(),
+        };
+        self.tag_picker = Some(TagPicker::new(mode, known, checked));
+        self.modals.push(Modal::TagPicker);
+    }
+
+    async fn handle_tag_picker_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut picker) = self.tag_picker else {
+            return;
+        };
+        let editing = matches!(picker.mode, TagPickerMode::Edit(_));
+        match KeyContext::TagPicker.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::TagPicker),
+            Some(ViewAction::Up) => picker.move_selection(-1),
+            Some(ViewAction::Down) => picker.move_selection(1),
+            Some(ViewAction::PageUp) => picker.move_selection(-10),
+            Some(ViewAction::PageDown) => picker.move_selection(10),
+            // Tags are single words, so Space is free to check one
+            Some(ViewAction::Toggle) if editing => picker.toggle_selected(),
+            Some(ViewAction::Toggle) => {}
+            _ if picker.input.handle_key(key) => picker.refresh(),
+            Some(ViewAction::Open) => {
+                // A typed tag that doesn't exist yet is created; otherwise Enter is done
+                if editing && let Some(tag) = picker.new_tag() {
+                    picker.add(tag);
+                    return;
+                }
+                let Some(picker) = self.tag_picker.take() else {
+                    return;
+                };
+                self.modals.close(Modal::TagPicker);
+                match picker.mode {
+                    TagPickerMode::Edit(asset) => {
+                        self.set_tags(asset, picker.checked.into_iter().collect())
+                            .await;
                     }
-                    crate::app::ActivePane::Assets => {
-                        if self.selected_asset_index > 0 {
-                            self.selected_asset_index -= 1;
+                    TagPickerMode::Filter => {
+                        if let Some(tag) = picker.chosen().cloned() {
+                            self.set_tag_filter(Some(tag));
                         }
                     }
-                    crate::app::ActivePane::Log => {
-                        // Scroll up in the log
-                        if self.log_scroll_position > 0 {
-                            self.log_scroll_position -= 1;
-                        }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Give an asset exactly these tags, in the local file or the configured metadata property
+    async fn set_tags(&mut self, asset: Asset, tags: Vec<String>) {
+        let Some(key) = self.tags.metadata_key().map(str::to_string) else {
+            match self.tags.set_local(&asset.uuid, tags) {
+                Ok(()) => self.notify(NotificationLevel::Success, format!("Tagged {}", asset.name)),
+                Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
+            }
+            self.apply_tag_filter();
+            return;
+        };
+        if self.refuse_denied(Permission::EditMetadata) {
+            return;
+        }
+
+        let value = tags.join(",");
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "metadata",
+            "create",
+            "--uuid",
+            &asset.uuid,
+            "--name",
+            &key,
+            "--value",
+            &value,
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Tagging {}...", asset.name);
+
+        let result = pcli_commands::set_metadata(&asset.uuid, &key, &value).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(NotificationLevel::Success, format!("Tagged {}", asset.name));
+                // Every listing of the asset has its new tags
+                let listed = self
+                    .all_assets
+                    .iter_mut()
+                    .chain(self.search_results.iter_mut())
+                    .chain(
+                        self.folder_cache
+                            .values_mut()
+                            .flat_map(|cache| cache.assets.iter_mut()),
+                    )
+                    .filter(|listed| listed.uuid == asset.uuid);
+                for listed in listed {
+                    tags::set_metadata_value(&mut listed.metadata, &key, &value);
+                }
+                self.apply_tag_filter();
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Tagging failed: {}", e));
+                self.log_command_error(&e);
+            }
+        }
+    }
+
+    // The metadata columns of the assets table; tags kept in metadata are shown as chips instead
+    pub fn metadata_columns(&self) -> Vec<String> {
+        let mut keys = metadata_keys(&self.assets);
+        keys.retain(|key| Some(key.as_str()) != self.tags.metadata_key());
+        keys
+    }
+
+    // Edit the metadata cells of the assets table in place, starting at the first column
+    fn start_cell_edit(&mut self) {
+        if self.refuse_offline("Editing metadata") || self.refuse_denied(Permission::EditMetadata) {
+            return;
+        }
+        if self.metadata_columns().is_empty() {
+            self.notify(
+                NotificationLevel::Warning,
+                "The listed assets have no metadata columns to edit",
+            );
+            return;
+        }
+        self.cell_editor = Some(CellEditor::default());
+    }
+
+    // Arrows move between cells, Enter edits one and Enter again sets it; Esc leaves
+    async fn handle_cell_edit_keys(&mut self, key: KeyEvent) {
+        let columns = self.metadata_columns();
+        let Some(ref mut editor) = self.cell_editor else {
+            return;
+        };
+        editor.move_column(0, columns.len());
+
+        if let Some(ref mut input) = editor.input {
+            match KeyContext::CellInput.action(key) {
+                Some(ViewAction::Back) => editor.input = None,
+                Some(ViewAction::Open) => {
+                    let value = input.take();
+                    editor.input = None;
+                    let column = editor.column;
+                    if let (Some(asset), Some(key)) = (
+                        self.assets.get(self.selected_asset_index).cloned(),
+                        columns.get(column),
+                    ) {
+                        self.set_cell(asset, key.clone(), value).await;
                     }
                 }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return;
+        }
+
+        match KeyContext::CellEdit.action(key) {
+            Some(ViewAction::Left) => editor.move_column(-1, columns.len()),
+            Some(ViewAction::Right) => editor.move_column(1, columns.len()),
+            Some(ViewAction::Up) => {
+                self.selected_asset_index = self.selected_asset_index.saturating_sub(1);
             }
-            crossterm::event::MouseEventKind::Down(_) => {
-                // Handle click events - could be extended to handle clicks on specific UI elements
-                // For now, just handle scrolling based on which pane the mouse is in
+            Some(ViewAction::Down) => {
+                self.selected_asset_index =
+                    (self.selected_asset_index + 1).min(self.assets.len().saturating_sub(1));
+            }
+            Some(ViewAction::Edit) => {
+                if let (Some(asset), Some(key)) = (
+                    self.assets.get(self.selected_asset_index),
+                    columns.get(editor.column),
+                ) {
+                    let value = asset.metadata_value(key).unwrap_or_default();
+                    editor.input = Some(TextInput::new(value));
+                }
             }
+            Some(ViewAction::Back) => self.cell_editor = None,
             _ => {}
         }
     }
+
+    // Set a metadata cell and move down to the next asset, like a spreadsheet
+    async fn set_cell(&mut self, asset: Asset, key: String, value: String) {
+        let value = value.trim().to_string();
+        if asset.metadata_value(&key).unwrap_or_default() == value {
+            return;
+        }
+        if value.is_empty() {
+            self.notify(NotificationLevel::Warning, "Empty values are not set");
+            return;
+        }
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "metadata",
+            "create",
+            "--uuid",
+            &asset.uuid,
+            "--name",
+            &key,
+            "--value",
+            &value,
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Setting {} of {}...", key, asset.name);
+
+        let result = pcli_commands::set_metadata(&asset.uuid, &key, &value).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Set {} of {} to {}", key, asset.name, value),
+                );
+                // Every listing of the asset has the new value
+                let listed = self
+                    .all_assets
+                    .iter_mut()
+                    .chain(self.search_results.iter_mut())
+                    .chain(
+                        self.folder_cache
+                            .values_mut()
+                            .flat_map(|cache| cache.assets.iter_mut()),
+                    )
+                    .filter(|listed| listed.uuid == asset.uuid);
+                for listed in listed {
+                    tags::set_metadata_value(&mut listed.metadata, &key, &value);
+                }
+                self.apply_tag_filter();
+                self.selected_asset_index =
+                    (self.selected_asset_index + 1).min(self.assets.len().saturating_sub(1));
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Setting {} failed: {}", key, e),
+                );
+                self.log_command_error(&e);
+            }
+        }
+    }
+
+    fn set_note(&mut self, uuid: &str, note: &str) {
+        let message = if note.is_empty() {
+            "Note removed"
+        } else {
+            "Note saved"
+        };
+        match self.notes.set(uuid, note) {
+            Ok(()) => self.notify(NotificationLevel::Success, message),
+            Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
+        }
+        self.table_changed();
+    }
+
+    fn toggle_basket(&mut self) {
+        let Some(asset) = self.assets.get(self.selected_asset_index).cloned() else {
+            return;
+        };
+        let name = asset.name.clone();
+        let message = if self.basket.toggle(asset) {
+            format!("Added {} to the basket ({})", name, self.basket.len())
+        } else {
+            format!("Took {} out of the basket ({})", name, self.basket.len())
+        };
+        self.notify(NotificationLevel::Info, message);
+    }
+
+    fn handle_basket_keys(&mut self, key: KeyEvent) {
+        match KeyContext::Basket.action(key) {
+            Some(ViewAction::Back) => self.current_state = AppState::Folders,
+            Some(ViewAction::Up) => self.basket.move_selection(-1),
+            Some(ViewAction::Down) => self.basket.move_selection(1),
+            Some(ViewAction::PageUp) => self.basket.move_selection(-10),
+            Some(ViewAction::PageDown) => self.basket.move_selection(10),
+            Some(ViewAction::Remove) => {
+                if let Some(asset) = self.basket.remove_selected() {
+                    let message = format!("Took {} out of the basket", asset.name);
+                    self.notify(NotificationLevel::Info, message);
+                }
+            }
+            Some(ViewAction::Clear) => {
+                self.basket.clear();
+                self.notify(NotificationLevel::Info, "Emptied the basket");
+            }
+            _ if self.basket.is_empty() => {}
+            Some(ViewAction::Download) => self.start_basket_download(),
+            Some(ViewAction::Move) => {
+                self.folder_picker = Some(FolderPicker::new(
+                    format!(" Move {} basket assets to ", self.basket.len()),
+                    self.known_folder_paths(),
+                    PickAction::MoveBasket,
+                ));
+                self.modals.push(Modal::FolderPicker);
+            }
+            Some(ViewAction::SetMetadata) => {
+                if self.refuse_denied(Permission::EditMetadata) {
+                    return;
+                }
+                self.open_prompt(Prompt::new(
+                    format!(
+                        "Metadata for {} basket assets (name=value): ",
+                        self.basket.len()
+                    ),
+                    PromptAction::BasketMetadata,
+                    metadata_assignment,
+                ));
+            }
+            Some(ViewAction::Report) => self.export_report(self.basket.assets().to_vec()),
+            _ => {}
+        }
+    }
+
+    // Download every asset in the basket, each to the directory configured for its folder
+    fn start_basket_download(&mut self) {
+        let assets: Vec<(Asset, PathBuf)> = self
+            .basket
+            .assets()
+            .iter()
+            .map(|asset| {
+                let directory = self.config.downloads.directory_for(asset.folder_path());
+                (asset.clone(), directory.join(&asset.name))
+            })
+            .collect();
+        let job = self.jobs.start(
+            JobKind::Download,
+            format!("Download {} basket assets", assets.len()),
+            assets.len(),
+        );
+        tokio::spawn(pcli_commands::in_job(basket::download(job, assets)));
+    }
+
+    // Move every asset in the basket into `folder` ("" for the top level)
+    fn start_basket_move(&mut self, folder: String) {
+        if self.refuse_offline("Moving assets") {
+            return;
+        }
+        let assets = self.basket.assets().to_vec();
+        // Listings of the folders they leave and the one they join are out of date
+        for asset in &assets {
+            self.folder_cache.remove(asset.folder_path());
+        }
+        self.folder_cache.remove(&folder);
+        if self.current_folder.as_deref().unwrap_or_default() != folder {
+            self.assets
+                .retain(|asset| !assets.iter().any(|moved| moved.uuid == asset.uuid));
+            self.all_assets
+                .retain(|asset| !assets.iter().any(|moved| moved.uuid == asset.uuid));
+            self.table_changed();
+            self.selected_asset_index = self
+                .selected_asset_index
+                .min(self.assets.len().saturating_sub(1));
+        }
+        let title = format!(
+            "Move {} basket assets to {}",
+            assets.len(),
+            folder_picker::label(&folder)
+        );
+        let job = self.jobs.start(JobKind::Batch, title, assets.len());
+        let touched = assets.iter().map(|asset| asset.folder_path().to_string());
+        self.jobs
+            .touches(&job, touched.chain([folder.clone()]).collect::<Vec<_>>());
+        tokio::spawn(pcli_commands::in_job(basket::move_to(
+            job,
+            assets,
+            folder.clone(),
+        )));
+        // Assets that fail to move are listed in the job's log entry
+        self.basket.moved_to(&folder);
+    }
+
+    fn start_basket_metadata(&mut self, name: &str, value: &str) {
+        if self.refuse_offline("Setting metadata") || self.refuse_denied(Permission::EditMetadata) {
+            return;
+        }
+        let assets = self.basket.assets().to_vec();
+        self.start_work(
+            format!("Set {} on {} basket assets", name, assets.len()),
+            Work::Metadata {
+                name: name.to_string(),
+                value: value.to_string(),
+                assets,
+            },
+        );
+    }
+
+    // Open the asset's web page, from the asset_url template in the config
+    async fn open_in_browser(&mut self, asset: &Asset) {
+        let Some(ref template) = self.config.asset_url else {
+            self.notify(
+                NotificationLevel::Warning,
+                "Set asset_url in the config file to open assets in the browser",
+            );
+            return;
+        };
+        let url = template
+            .replace("{uuid}", &asset.uuid)
+            .replace("{path}", &asset.path);
+        self.open_url(url).await;
+    }
+
+    // Open a web page with the system's opener
+    async fn open_url(&mut self, url: String) {
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(target_os = "windows") {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+        match external::capture(&[opener.to_string(), url.clone()]).await {
+            Ok(output) if output.status.success() => {
+                self.notify(NotificationLevel::Info, format!("Opened {}", url));
+            }
+            Ok(output) => self.notify(
+                NotificationLevel::Error,
+                format!(
+                    "{} failed: {}",
+                    opener,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            ),
+            Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
+        }
+    }
+
+    // Download an assembly and its dependencies into a directory named after it, as a job
+    // List the selected folder's tree and ask whether to download it, saying how much it is
+    async fn plan_folder_download(&mut self) {
+        if self.refuse_offline("Downloading") {
+            return;
+        }
+        let Some(folder) = self
+            .folders
+            .get(self.selected_folder_index)
+            .filter(|folder| folder.uuid != "..")
+            .map(|folder| folder.path.clone())
+        else {
+            return;
+        };
+        let directory = self.config.downloads.directory_for(&folder);
+
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Listing {} and its subfolders...", folder);
+        let download = folder_download::scan(&folder, &directory).await;
+        self.command_in_progress = false;
+        let download = match download {
+            Ok(download) => download,
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Listing {} failed: {:#}", folder, e),
+                );
+                return;
+            }
+        };
+
+        if download.files.is_empty() && download.errors.is_empty() {
+            let message = match download.present {
+                0 => format!("{} and its subfolders have no assets", download.folder),
+                present => format!(
+                    "All {} files of {} are in {} already",
+                    present,
+                    download.folder,
+                    download.root.display()
+                ),
+            };
+            self.notify(NotificationLevel::Info, message);
+            return;
+        }
+        self.open_confirm(
+            Confirm::new(
+                " 📥 Download Folder ",
+                download.question(),
+                ConfirmAction::DownloadFolder(download.clone()),
+            )
+            .with_detail(download.detail()),
+        );
+    }
+
+    fn start_folder_download(&mut self, download: FolderDownload) {
+        self.notify(
+            NotificationLevel::Info,
+            format!(
+                "Downloading {} files of {} in the background",
+                download.files.len(),
+                download.folder
+            ),
+        );
+        self.start_work(
+            format!("Download {} with its subfolders", download.folder),
+            Work::FolderDownload { download },
+        );
+    }
+
+    // Open the search scoped to the current folder and below, with the selected asset's name
+    // less its extension typed in, to find the other revisions of a part
+    fn open_revision_search(&mut self) {
+        self.modals.push(Modal::Search);
+        self.search_scope = SearchScope::Subtree;
+        let Some(asset) = self.assets.get(self.selected_asset_index) else {
+            self.search_input_buffer.clear();
+            return;
+        };
+        let stem = std::path::Path::new(&asset.name).file_stem().map_or_else(
+            || asset.name.clone(),
+            |stem| stem.to_string_lossy().to_string(),
+        );
+        self.search_input_buffer.set(stem);
+        // The name is matched as it is, not as a glob or regex
+        self.search_mode = SearchMode::Text;
+    }
+
+    fn start_assembly_download(&mut self, asset: Asset) {
+        let stem = std::path::Path::new(&asset.name).file_stem().map_or_else(
+            || asset.name.clone(),
+            |stem| stem.to_string_lossy().to_string(),
+        );
+        let directory = self
+            .config
+            .downloads
+            .directory_for(asset.folder_path())
+            .join(stem);
+
+        let job = self.jobs.start(
+            JobKind::Download,
+            format!("Download {} with dependencies", asset.name),
+            1,
+        );
+        tokio::spawn(pcli_commands::in_job(assembly::download_with_dependencies(
+            job,
+            AssemblyDownload {
+                uuid: asset.uuid,
+                name: asset.name.clone(),
+                path: asset.path,
+                size: asset.size,
+                directory,
+            },
+        )));
+        self.notify(
+            NotificationLevel::Info,
+            format!(
+                "Downloading {} and its dependencies in the background",
+                asset.name
+            ),
+        );
+    }
+
+    // Batch jobs and prefetches running in the background
+    pub fn background_tasks(&self) -> usize {
+        self.jobs.running().len() + self.prefetcher.active().len()
+    }
+
+    // Ring the bell and send a desktop notification, as configured for the kind of job, when
+    // it ran long enough that the user has probably turned to something else
+    fn alert_if_long(
+        &mut self,
+        kind: JobKind,
+        elapsed: Option<Duration>,
+        success: bool,
+        summary: &str,
+    ) {
+        let alerts = &self.config.alerts;
+        if elapsed.is_none_or(|elapsed| elapsed < Duration::from_secs(alerts.after_secs)) {
+            return;
+        }
+        if alerts.bell.contains(&kind) {
+            alert::bell();
+        }
+        if alerts.desktop.contains(&kind) {
+            let title = if success {
+                "pcli2-tui: finished"
+            } else {
+                "pcli2-tui: failed"
+            };
+            if let Err(e) = alert::desktop(title, summary) {
+                self.notify(NotificationLevel::Warning, format!("{:#}", e));
+            }
+        }
+    }
+
+    // Report jobs that ended since the last frame
+    pub fn report_finished_jobs(&mut self) {
+        let finished = self.jobs.apply_updates();
+        for command in pcli_commands::take_job_commands() {
+            self.command_history.push(HistoryEntry::ran(command));
+        }
+        for asset in self.jobs.take_deleted() {
+            self.forget_deleted(asset);
+        }
+        for result in finished {
+            let detail = ErrorDetail {
+                timestamp: Local::now(),
+                command_line: result.title.clone(),
+                exit_code: None,
+                message: result.summary.clone(),
+                stdout: result.details,
+                stderr: String::new(),
+            };
+            let status = if result.success {
+                LogStatus::Success
+            } else {
+                LogStatus::Error
+            };
+            self.add_log_entry(
+                LogEntry::new(status, result.title.clone())
+                    .with_duration(Some(result.started.elapsed()))
+                    .with_message(result.summary.clone())
+                    .with_error(detail),
+            );
+            // Partly failed jobs changed some of the folders too
+            for folder in &result.folders {
+                self.refresh_folder_counts(folder);
+            }
+            self.alert_if_long(
+                result.kind,
+                Some(result.started.elapsed()),
+                result.success,
+                &result.summary,
+            );
+            if result.success {
+                self.notify(NotificationLevel::Success, result.summary);
+            } else {
+                self.notify(
+                    NotificationLevel::Warning,
+                    format!("{} (see the log for failures)", result.summary),
+                );
+            }
+        }
+    }
+
+    pub async fn download_asset_by_uuid(&mut self, asset_uuid: &str, asset_name: &str) {
+        let listed = self
+            .assets
+            .iter()
+            .chain(self.search_results.iter())
+            .find(|asset| asset.uuid == asset_uuid);
+        let size = listed.and_then(|asset| asset.size);
+        let folder = match listed {
+            Some(asset) => asset.folder_path().to_string(),
+            None => self.current_folder.clone().unwrap_or_default(),
+        };
+        let transfer = self.download_transfer(asset_name, &folder, size);
+        let path = transfer.path.clone();
+        let file = path.display().to_string();
+
+        self.last_executed_command =
+            PcliCommand::new(&["asset", "download", "--uuid", asset_uuid, "--file", &file]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Downloading asset: {}...", asset_name);
+
+        self.last_operation = Some(Operation::Download {
+            uuid: asset_uuid.to_string(),
+            name: asset_name.to_string(),
+        });
+        if let Some(directory) = path.parent()
+            && let Err(e) = std::fs::create_dir_all(directory)
+        {
+            self.notify(
+                NotificationLevel::Error,
+                format!(
+                    "Download failed: can't create {}: {}",
+                    directory.display(),
+                    e
+                ),
+            );
+            return;
+        }
+        match pcli_commands::download_asset(asset_uuid, &path, Some(transfer)).await {
+            Ok(()) => {
+                if let Err(e) = pcli_commands::verify_download(&path, size) {
+                    self.report_incomplete_download(asset_uuid, asset_name, &e);
+                    return;
+                }
+                let entry = self
+                    .command_entry(LogStatus::Success)
+                    .with_message(format!("saved to {}", file));
+                self.add_log_entry(entry);
+                let summary = format!("Downloaded {}", asset_name);
+                self.alert_if_long(JobKind::Download, self.command_duration(), true, &summary);
+
+                self.hooks.emit(
+                    HookEvent::AssetDownloaded,
+                    serde_json::json!({
+                        "uuid": asset_uuid,
+                        "name": asset_name,
+                        "file": path.display().to_string(),
+                    }),
+                );
+                self.last_download = Some(path.clone());
+                self.downloaded.insert(asset_uuid.to_string(), path.clone());
+                let has_viewer = self.config.viewers.command_for(&path).is_some();
+                if has_viewer && self.config.viewers.auto_open {
+                    self.open_in_viewer(path);
+                } else if has_viewer {
+                    self.notify(
+                        NotificationLevel::Success,
+                        format!(
+                            "Successfully downloaded: {} to {} (o: open)",
+                            asset_name, file
+                        ),
+                    );
+                } else {
+                    self.notify(
+                        NotificationLevel::Success,
+                        format!("Successfully downloaded: {} to {}", asset_name, file),
+                    );
+                }
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Download failed: {}", e));
+                self.log_command_error(&e);
+                let summary = format!("Download of {} failed", asset_name);
+                self.alert_if_long(JobKind::Download, self.command_duration(), false, &summary);
+            }
+        }
+    }
+
+    // Flag a download whose file can't be right and offer to download it again
+    fn report_incomplete_download(&mut self, uuid: &str, name: &str, error: &anyhow::Error) {
+        let detail = ErrorDetail::from_error(error, &self.last_executed_command.to_string());
+        let entry = self
+            .command_entry(LogStatus::Error)
+            .with_message(format!("incomplete download: {:#}", error))
+            .with_error(detail);
+        self.add_log_entry(entry);
+        let message = format!("{} was not downloaded completely. Download it again?", name);
+        let action = ConfirmAction::Redownload {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+        };
+        self.open_confirm(
+            Confirm::new(" Incomplete Download ", message, action)
+                .with_detail(format!("{:#}", error)),
+        );
+    }
+
+    pub async fn upload_asset_interactive(&mut self) {
+        if self.refuse_offline("Uploading") {
+            return;
+        }
+        self.open_prompt(Prompt::new(
+            "Upload file: ",
+            PromptAction::Upload,
+            existing_file,
+        ));
+    }
+
+    pub async fn perform_search(&mut self) {
+        if self.search_query.trim().is_empty() {
+            self.notify(NotificationLevel::Warning, "Empty search query");
+            return;
+        }
+
+        let query = self.search_query.trim().to_string();
+        self.search_history.retain(|previous| *previous != query);
+        self.search_history.push(query);
+        if self.search_history.len() > SEARCH_HISTORY_LIMIT {
+            self.search_history.remove(0);
+        }
+        self.search_history_index = None;
+
+        let pattern = match SearchPattern::new(self.search_mode, &self.search_query) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                self.notify(NotificationLevel::Warning, format!("{:#}", e));
+                return;
+            }
+        };
+
+        // A scoped search at the root covers the whole tenant anyway
+        let folder = match self.search_scope {
+            SearchScope::Tenant => None,
+            SearchScope::Folder | SearchScope::Subtree => self.current_folder.clone(),
+        };
+        if offline::is_enabled() {
+            self.search_cache(&pattern, folder.as_deref());
+            return;
+        }
+        self.last_executed_command = match folder {
+            Some(ref folder) => PcliCommand::new(&[
+                "asset",
+                "text-match",
+                "--text",
+                &pattern.text,
+                "--folder-path",
+                folder,
+                "--format",
+                "json",
+                "--metadata",
+            ]),
+            None => PcliCommand::new(&[
+                "asset",
+                "text-match",
+                "--text",
+                &pattern.text,
+                "--format",
+                "json",
+                "--metadata",
+            ]),
+        };
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Searching for: {}", self.search_query);
+
+        self.last_operation = Some(Operation::Search(self.search_query.clone()));
+        match pcli_commands::search_assets(&pattern.text, folder.as_deref()).await {
+            Ok(pcli_assets) => {
+                // pcli2 searches below the folder path; the folder scope keeps only its own assets
+                let direct_only = self.search_scope == SearchScope::Folder;
+                // Store search results separately from folder assets
+                self.search_results = pcli_assets
+                    .into_iter()
+                    .filter(|a| {
+                        !direct_only
+                            || a.path.rsplit_once('/').map(|(parent, _)| parent)
+                                == folder.as_deref()
+                    })
+                    // Glob and regex patterns are checked here; pcli2 only saw their literal part
+                    .filter(|a| pattern.matches(&a.name))
+                    .map(|a| Asset {
+                        uuid: a.uuid,
+                        name: a.name,
+                        folder_uuid: a.path.split('/').next().unwrap_or_default().to_string(), // Extract folder from path
+                        file_type: a.file_type,
+                        size: a.file_size,
+                        path: a.path,
+                        metadata: a.metadata,
+                        is_assembly: a.is_assembly,
+                        processing_status: a.processing_status,
+                    })
+                    .collect();
+
+                self.selected_search_result_index = 0;
+                self.sort_search_results();
+
+                self.report_listing("Found", self.search_results.len(), "assets", "");
+                self.command_in_progress = false; // Clear flag when command completes
+            }
+            Err(e) => {
+                self.notify(NotificationLevel::Error, format!("Search failed: {}", e));
+
+                // Log failed command with error indicator
+                self.log_command_error(&e);
+                self.command_in_progress = false; // Clear flag when command completes
+            }
+        }
+    }
+
+    pub async fn upload_asset_to_current_folder(&mut self, path: PathBuf) {
+        if !path.is_file() {
+            self.notify(
+                NotificationLevel::Error,
+                format!("{} is not a file", path.display()),
+            );
+            return;
+        }
+        if let Some(folder_path) = self.current_folder.clone() {
+            let file_path = path.display().to_string();
+            self.last_executed_command = PcliCommand::new(&[
+                "asset",
+                "create",
+                "--file",
+                &file_path,
+                "--folder",
+                &folder_path,
+                "--format",
+                "json",
+            ]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            self.status_message = format!("Uploading asset: {}...", file_path);
+
+            let result = pcli_commands::create_asset(&file_path, &folder_path).await;
+            self.command_in_progress = false;
+            match result {
+                Ok(asset) => {
+                    self.log_command_success();
+                    let summary = format!("Uploaded {}", file_path);
+                    self.alert_if_long(JobKind::Upload, self.command_duration(), true, &summary);
+                    self.notify(
+                        NotificationLevel::Success,
+                        format!(
+                            "Successfully uploaded: {}; waiting for processing",
+                            file_path
+                        ),
+                    );
+                    self.hooks.emit(
+                        HookEvent::AssetUploaded,
+                        serde_json::json!({
+                            "file": file_path,
+                            "folder": folder_path,
+                            "uuid": asset.uuid,
+                        }),
+                    );
+                    self.watch_processing(asset.uuid, asset.name);
+                    self.update_folder_counts(&folder_path, 0, 1);
+                    // Reload assets to show the newly uploaded one
+                    self.load_assets_for_current_folder().await;
+                }
+                Err(e) => {
+                    self.notify(NotificationLevel::Error, format!("Upload failed: {}", e));
+                    self.log_command_error(&e);
+                    let summary = format!("Upload of {} failed", file_path);
+                    self.alert_if_long(JobKind::Upload, self.command_duration(), false, &summary);
+                }
+            }
+        } else {
+            self.notify(NotificationLevel::Warning, "No folder selected for upload");
+        }
+    }
+
+    // Poll an uploaded asset's processing status as a job; its end is reported like any job's
+    fn watch_processing(&mut self, uuid: String, name: String) {
+        let job = self
+            .jobs
+            .start(JobKind::Processing, format!("Processing {}", name), 1);
+        tokio::spawn(processing::watch(
+            job,
+            uuid,
+            name,
+            self.config.processing.clone(),
+        ));
+    }
+
+    fn add_log_entry(&mut self, entry: LogEntry) {
+        // Record retried attempts first so they appear before the final outcome
+        for event in pcli_commands::take_retry_events() {
+            self.log_entries.push(
+                LogEntry::new(LogStatus::Retry, event.command_line).with_message(format!(
+                    "attempt {}/{} failed ({}), retrying in {:.1}s",
+                    event.attempt,
+                    event.attempts,
+                    event.reason,
+                    event.delay.as_secs_f64()
+                )),
+            );
+        }
+
+        // Output in a shape we don't recognize usually means pcli2 was upgraded
+        for warning in pcli_commands::take_schema_warnings() {
+            let message = format!(
+                "{} of {} records in an unknown pcli2 output schema",
+                warning.unrecognized, warning.total
+            );
+            self.notify(
+                NotificationLevel::Warning,
+                format!("Unrecognized pcli2 output: {}", message),
+            );
+            self.log_entries.push(
+                LogEntry::new(LogStatus::Warning, warning.command_line).with_message(message),
+            );
+        }
+
+        // Commands of batch jobs may have been recorded after the one this is about
+        if let Some(last) = self
+            .command_history
+            .iter_mut()
+            .rev()
+            .find(|command| command.status.is_none())
+        {
+            last.finish(&entry);
+        }
+        self.log_entries.push(entry);
+
+        // Limit log history to 200 entries
+        if self.log_entries.len() > 200 {
+            // Remove oldest entries, keeping the most recent 200
+            let excess = self.log_entries.len() - 200;
+            self.log_entries.drain(0..excess);
+
+            // Adjust scroll position if needed
+            if self.log_scroll_position >= excess {
+                self.log_scroll_position -= excess;
+            } else {
+                self.log_scroll_position = 0;
+            }
+        }
+
+        // Always auto-scroll to the bottom to show the latest log entry
+        self.log_scroll_position = self.log_entries.len().saturating_sub(1);
+        self.snap_log_selection();
+    }
+
+    // Indices of the log entries that pass the status and substring filters
+    pub fn visible_log_indices(&self) -> Vec<usize> {
+        let needle = self.log_filter_text.to_lowercase();
+        self.log_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.log_filter.matches(entry))
+            .filter(|(_, entry)| {
+                needle.is_empty() || entry.summary().to_lowercase().contains(&needle)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Move the selection onto a visible entry after the filters change
+    fn snap_log_selection(&mut self) {
+        let visible = self.visible_log_indices();
+        if !visible.contains(&self.log_scroll_position)
+            && let Some(&last) = visible.last()
+        {
+            self.log_scroll_position = last;
+        }
+    }
+
+    fn handle_log_filter_input(&mut self, key: KeyEvent) {
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Open) => {
+                self.log_filter_editing = false;
+            }
+            Some(ViewAction::Back) => {
+                // Cancel clears the substring filter
+                self.log_filter_editing = false;
+                self.log_filter_text.clear();
+            }
+            _ => match key.code {
+                KeyCode::Backspace => {
+                    self.log_filter_text.pop();
+                }
+                KeyCode::Char(c) => self.log_filter_text.push(c),
+                _ => {}
+            },
+        }
+        self.snap_log_selection();
+    }
+
+    // Time since the running command was started
+    fn command_duration(&self) -> Option<Duration> {
+        self.command_started.map(|started| started.elapsed())
+    }
+
+    // The pcli2 invocation behind the running command, once it ran
+    fn command_run(&self) -> Option<CommandRun> {
+        let started = self.command_started?;
+        pcli_commands::last_run().filter(|run| run.started >= started)
+    }
+
+    // Log entry for the running command with how long pcli2 ran and how it exited, or the time
+    // since the command started when it didn't get to run pcli2
+    fn command_entry(&self, status: LogStatus) -> LogEntry {
+        let entry = LogEntry::new(status, self.last_executed_command.to_string());
+        match self.command_run() {
+            Some(run) => entry
+                .with_duration(Some(run.duration))
+                .with_exit_code(run.exit_code),
+            None => entry.with_duration(self.command_duration()),
+        }
+    }
+
+    fn log_command_success(&mut self) {
+        let entry = self.command_entry(LogStatus::Success);
+        self.add_log_entry(entry);
+    }
+
+    fn log_cache_hit(&mut self) {
+        self.metrics.cache_hits += 1;
+        let entry = LogEntry::new(LogStatus::Cached, self.last_executed_command.to_string());
+        self.add_log_entry(entry);
+    }
+
+    // Log a failed command and keep its full output for the error detail modal
+    fn log_command_error(&mut self, error: &anyhow::Error) {
+        let detail = ErrorDetail::from_error(error, &self.last_executed_command.to_string());
+
+        if let Some(timeout) = error.downcast_ref::<PcliTimeout>() {
+            let entry = self
+                .command_entry(LogStatus::Timeout)
+                .with_message(format!("killed after {}s", timeout.timeout.as_secs()))
+                .with_error(detail.clone());
+            self.add_log_entry(entry);
+
+            // Offer to run the same operation again with twice the time
+            if let Some(operation) = self.last_operation.clone() {
+                let longer = timeout.timeout * 2;
+                self.timed_out_operation = Some((operation, longer));
+                self.notify(
+                    NotificationLevel::Warning,
+                    format!("Press R to retry with a {}s timeout", longer.as_secs()),
+                );
+            }
+        } else {
+            let entry = self
+                .command_entry(LogStatus::Error)
+                .with_message(error.to_string())
+                .with_error(detail.clone());
+            self.add_log_entry(entry);
+        }
+        self.last_error = Some(detail);
+    }
+
+    // Notify how many records a listing produced, log the command, and log each record that was
+    // skipped as malformed so its raw JSON can be inspected in the log detail view
+    fn report_listing(&mut self, verb: &str, loaded: usize, noun: &str, context: &str) {
+        let malformed = pcli_commands::take_malformed_records();
+        if malformed.is_empty() {
+            self.notify(
+                NotificationLevel::Info,
+                format!("{} {} {}{}", verb, loaded, noun, context),
+            );
+        } else {
+            self.notify(
+                NotificationLevel::Warning,
+                format!(
+                    "{} {} of {} {}{} ({} malformed)",
+                    verb,
+                    loaded,
+                    loaded + malformed.len(),
+                    noun,
+                    context,
+                    malformed.len()
+                ),
+            );
+        }
+
+        self.log_command_success();
+
+        for record in malformed {
+            let message = format!("record {} skipped: {}", record.index, record.error);
+            let detail = ErrorDetail {
+                timestamp: Local::now(),
+                command_line: record.command_line.clone(),
+                exit_code: None,
+                message: message.clone(),
+                stdout: record.raw,
+                stderr: String::new(),
+            };
+            let entry = LogEntry::new(LogStatus::Error, record.command_line)
+                .with_message(message)
+                .with_error(detail);
+            self.add_log_entry(entry);
+        }
+    }
+
+    // Run the operation that timed out again, giving it the longer timeout
+    pub async fn retry_timed_out_operation(&mut self) {
+        let Some((operation, timeout)) = self.timed_out_operation.take() else {
+            self.notify(NotificationLevel::Info, "Nothing to retry");
+            return;
+        };
+
+        // The override only applies to the next invocation; clear it in case the
+        // operation is served from the cache and never reaches pcli2
+        pcli_commands::set_timeout_override(Some(timeout));
+        self.run_operation(operation).await;
+        pcli_commands::set_timeout_override(None);
+    }
+
+    async fn run_operation(&mut self, operation: Operation) {
+        match operation {
+            Operation::LoadFolders => self.load_folders_for_current_context().await,
+            Operation::LoadAssets => self.load_assets_for_current_folder().await,
+            Operation::LoadSelectedFolderAssets => self.load_assets_for_selected_folder().await,
+            Operation::Search(query) => {
+                self.search_query = query;
+                self.perform_search().await;
+            }
+            Operation::AssetDetails => self.show_asset_details().await,
+            Operation::GeometricMatch(uuid) => {
+                self.perform_geometric_match(&uuid).await;
+                self.modals.push(Modal::GeometricMatch);
+            }
+            Operation::WhereUsed(uuid) => self.find_where_used(&uuid, &uuid).await,
+            Operation::Download { uuid, name } => self.download_asset_by_uuid(&uuid, &name).await,
+            Operation::BrowseFolder(path) => {
+                self.set_current_folder(path);
+                self.selected_folder_index = 0;
+                self.current_state = AppState::Folders;
+                self.load_folders_for_current_context().await;
+            }
+            Operation::BrowseAssets(path) => {
+                self.set_current_folder(Some(path));
+                self.selected_asset_index = 0;
+                self.load_assets_for_current_folder().await;
+                self.current_state = AppState::Assets;
+            }
+            Operation::ShowAsset(uuid) => {
+                match self.assets.iter().position(|asset| asset.uuid == uuid) {
+                    Some(index) => {
+                        self.selected_asset_index = index;
+                        self.show_asset_details().await;
+                    }
+                    None => self.notify(
+                        NotificationLevel::Warning,
+                        format!("Asset {} is not in the current folder", uuid),
+                    ),
+                }
+            }
+        }
+    }
+
+    // Re-run a command from the history by dispatching the operation it stands for;
+    // commands the TUI doesn't wrap are run as raw pcli2 commands
+    pub async fn rerun_command(&mut self, command: &PcliCommand) {
+        let Some(operation) = Operation::from_command(command) else {
+            self.run_raw_command(command.args.clone()).await;
+            return;
+        };
+
+        self.current_state = AppState::Folders;
+        if let Operation::Search(ref query) = operation {
+            // Show the results in the search dialog, as if the query had been typed there
+            self.modals.push(Modal::Search);
+            self.search_input_buffer.set(query.clone());
+            self.search_modal_focus = SearchModalFocus::Results;
+        }
+        self.run_operation(operation).await;
+    }
+
+    async fn handle_command_history_keys(&mut self, key: KeyEvent) {
+        match KeyContext::CommandHistory.action(key) {
+            Some(ViewAction::Back) => {
+                // Return to the previous state (default to Folders)
+                self.current_state = AppState::Folders;
+            }
+            // The list shows the most recent command first, so Up moves towards newer entries
+            Some(ViewAction::Up) if self.command_history_index + 1 < self.command_history.len() => {
+                self.command_history_index += 1;
+            }
+            Some(ViewAction::Down) => {
+                self.command_history_index = self.command_history_index.saturating_sub(1);
+            }
+            Some(ViewAction::Open) => {
+                if let Some(entry) = self.command_history.get(self.command_history_index) {
+                    let command = entry.command.clone();
+                    self.rerun_command(&command).await;
+                }
+            }
+            Some(ViewAction::Edit) => {
+                if let Some(entry) = self.command_history.get(self.command_history_index) {
+                    self.command_edit_buffer = Some(TextInput::new(entry.command.to_string()));
+                }
+            }
+            Some(ViewAction::Export) => self.export_history(),
+            Some(ViewAction::ExportScript) => self.export_history_script(),
+            _ => {}
+        }
+    }
+
+    pub fn open_json_viewer(&mut self) {
+        match pcli_commands::last_output() {
+            Some(LastOutput {
+                command_line,
+                stdout: Some(stdout),
+            }) => {
+                self.json_viewer = Some(JsonViewer::new(command_line, stdout));
+                self.current_state = AppState::JsonView;
+            }
+            Some(output) => self.notify(
+                NotificationLevel::Warning,
+                format!(
+                    "The output of {} was too large to keep",
+                    output.command_line
+                ),
+            ),
+            None => self.notify(NotificationLevel::Info, "No pcli2 output to show yet"),
+        }
+    }
+
+    fn handle_json_view_keys(&mut self, key: KeyEvent) {
+        let action = KeyContext::JsonView.action(key);
+        if action == Some(ViewAction::Back) {
+            self.current_state = AppState::Folders;
+            self.json_viewer = None;
+            return;
+        }
+
+        let Some(ref mut viewer) = self.json_viewer else {
+            return;
+        };
+        let found = match action {
+            Some(ViewAction::Up) => {
+                viewer.move_by(-1);
+                true
+            }
+            Some(ViewAction::Down) => {
+                viewer.move_by(1);
+                true
+            }
+            Some(ViewAction::PageUp) => {
+                viewer.move_by(-20);
+                true
+            }
+            Some(ViewAction::PageDown) => {
+                viewer.move_by(20);
+                true
+            }
+            Some(ViewAction::Top) => {
+                viewer.selected = 0;
+                true
+            }
+            Some(ViewAction::Bottom) => {
+                viewer.move_to_end();
+                true
+            }
+            Some(ViewAction::Fold) => {
+                viewer.toggle_fold();
+                true
+            }
+            Some(ViewAction::FoldAll) => {
+                viewer.collapse_all();
+                true
+            }
+            Some(ViewAction::UnfoldAll) => {
+                viewer.expand_all();
+                true
+            }
+            Some(ViewAction::NextHit) => viewer.find(true),
+            Some(ViewAction::PreviousHit) => viewer.find(false),
+            _ => true,
+        };
+
+        if !found {
+            self.notify(NotificationLevel::Info, "No match");
+        }
+    }
+
+    fn handle_json_search_input(&mut self, key: KeyEvent) {
+        let Some(ref mut viewer) = self.json_viewer else {
+            return;
+        };
+
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Back) => {
+                viewer.search_editing = false;
+                viewer.search.clear();
+            }
+            Some(ViewAction::Open) => {
+                viewer.search_editing = false;
+                if !viewer.find(true) {
+                    let message = format!("No match for '{}'", viewer.search);
+                    self.notify(NotificationLevel::Info, message);
+                }
+            }
+            _ => match key.code {
+                KeyCode::Backspace => {
+                    viewer.search.pop();
+                }
+                KeyCode::Char(c) => viewer.search.push(c),
+                _ => {}
+            },
+        }
+    }
+
+    async fn handle_raw_command_input(&mut self, key: KeyEvent) {
+        let Some(ref mut buffer) = self.raw_command_input else {
+            return;
+        };
+
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Back) => {
+                self.close_modal(Modal::CommandLine);
+            }
+            _ if buffer.handle_key(key) => {}
+            Some(ViewAction::Open) => {
+                let input = buffer.take();
+                self.close_modal(Modal::CommandLine);
+
+                // ":sh" suspends to a shell instead of running pcli2
+                if input.trim() == "sh" {
+                    self.open_shell();
+                    return;
+                }
+
+                // Accept the arguments with or without a leading "pcli2"
+                if let Some(command) = PcliCommand::parse(&input) {
+                    self.run_raw_command(command.args).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Run arbitrary pcli2 arguments and show the output in the raw output view
+    pub async fn run_raw_command(&mut self, args: Vec<String>) {
+        self.last_executed_command = PcliCommand {
+            program: "pcli2".to_string(),
+            args: args.clone(),
+        };
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Running: {}", self.last_executed_command);
+
+        self.last_operation = None;
+        let result = match pcli_commands::run_raw(&args).await {
+            Ok(stdout) => {
+                self.log_command_success();
+                RawCommandResult {
+                    command_line: self.last_executed_command.to_string(),
+                    exit_code: Some(0),
+                    stdout,
+                    stderr: String::new(),
+                    message: None,
+                }
+            }
+            Err(e) => {
+                self.log_command_error(&e);
+                match e.downcast_ref::<PcliError>() {
+                    Some(pcli_error) => RawCommandResult {
+                        command_line: pcli_error.command_line.clone(),
+                        exit_code: pcli_error.exit_code,
+                        stdout: pcli_error.stdout.clone(),
+                        stderr: pcli_error.stderr.clone(),
+                        message: None,
+                    },
+                    None => RawCommandResult {
+                        command_line: self.last_executed_command.to_string(),
+                        exit_code: None,
+                        stdout: String::new(),
+                        stderr: String::new(),
+                        message: Some(format!("{:#}", e)),
+                    },
+                }
+            }
+        };
+        self.command_in_progress = false; // Clear flag when command completes
+
+        self.status_message = match result.exit_code {
+            Some(code) => format!("Command finished with exit code {}", code),
+            None => "Command failed".to_string(),
+        };
+        self.raw_result = Some(result);
+        self.raw_output_scroll = 0;
+        self.current_state = AppState::RawOutput;
+    }
+
+    async fn handle_command_edit_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut buffer) = self.command_edit_buffer else {
+            return;
+        };
+
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Back) => {
+                self.command_edit_buffer = None;
+            }
+            _ if buffer.handle_key(key) => {}
+            Some(ViewAction::Open) => {
+                let line = buffer.take();
+                self.command_edit_buffer = None;
+                if let Some(command) = PcliCommand::parse(&line) {
+                    self.rerun_command(&command).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Close a modal and drop what it was showing
+    fn close_modal(&mut self, modal: Modal) {
+        self.modals.close(modal);
+        match modal {
+            Modal::Finder => self.finder = None,
+            Modal::FolderPicker => self.folder_picker = None,
+            Modal::TagPicker => self.tag_picker = None,
+            Modal::WhereUsed => self.where_used_part = None,
+            Modal::CustomCommands => self.custom_command_menu = None,
+            Modal::AssemblyDownload => self.assembly_download_prompt = None,
+            Modal::Confirm => self.confirm = None,
+            Modal::SyncPlan => self.sync_plan = None,
+            Modal::FolderDiff => self.folder_diff = None,
+            Modal::MetadataDiff => self.metadata_diff = None,
+            Modal::MatchHistory => self.match_history = None,
+            Modal::InterruptedJobs => self.interrupted_jobs = None,
+            Modal::PropertySchema => self.property_schema = None,
+            Modal::Validation => self.validation_report = None,
+            Modal::MetadataImport => self.metadata_import = None,
+            Modal::Setup => self.setup = None,
+            Modal::ProfilePicker => self.profile_picker = None,
+            Modal::Tour => self.tour = None,
+            Modal::Help => self.help = None,
+            Modal::LogDetail => self.log_detail_index = None,
+            Modal::Error => self.error_modal = None,
+            Modal::Prompt => self.prompt = None,
+            Modal::CommandLine => self.raw_command_input = None,
+            // Their results stay around for the next time they are opened
+            Modal::Search | Modal::AssetDetails | Modal::GeometricMatch | Modal::MatchReview => {}
+        }
+    }
+
+    fn open_prompt(&mut self, prompt: Prompt<PromptAction>) {
+        self.prompt = Some(prompt);
+        self.modals.push(Modal::Prompt);
+    }
+
+    fn open_confirm(&mut self, confirm: Confirm<ConfirmAction>) {
+        self.confirm = Some(confirm);
+        self.modals.push(Modal::Confirm);
+    }
+
+    pub fn open_error_modal(&mut self, detail: ErrorDetail) {
+        self.error_modal = Some(detail);
+        self.modals.push(Modal::Error);
+        self.error_modal_scroll = 0;
+    }
+
+    fn copy_log_detail_command(&mut self) {
+        let command = self
+            .log_detail_index
+            .and_then(|i| self.log_entries.get(i))
+            .map(|entry| entry.command.clone());
+        if let Some(command) = command {
+            self.copy_to_clipboard(command, "Command");
+        }
+    }
+
+    fn handle_log_detail_keys(&mut self, key: KeyEvent) {
+        match KeyContext::LogDetail.action(key) {
+            Some(ViewAction::Back) => {
+                self.close_modal(Modal::LogDetail);
+                self.log_detail_copy_button = None;
+            }
+            Some(ViewAction::Up) => {
+                self.log_detail_scroll = self.log_detail_scroll.saturating_sub(1);
+            }
+            Some(ViewAction::Down) => {
+                self.log_detail_scroll = self.log_detail_scroll.saturating_add(1);
+            }
+            Some(ViewAction::Copy) => self.copy_log_detail_command(),
+            Some(ViewAction::ShowError) => {
+                // Open the full output of a failed command on top of the entry
+                let detail = self
+                    .log_detail_index
+                    .and_then(|i| self.log_entries.get(i))
+                    .and_then(|entry| entry.error.clone());
+                if let Some(detail) = detail {
+                    self.open_error_modal(detail);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_asset_details_keys(&mut self, key: KeyEvent) {
+        match KeyContext::AssetDetails.action(key) {
+            Some(ViewAction::Back) => {
+                self.modals.close(Modal::AssetDetails);
+            }
+            Some(ViewAction::Up) => {
+                self.asset_details_scroll = self.asset_details_scroll.saturating_sub(1);
+            }
+            Some(ViewAction::Down) => {
+                self.asset_details_scroll = self.asset_details_scroll.saturating_add(1);
+            }
+            Some(ViewAction::PageUp) => {
+                self.asset_details_scroll = self.asset_details_scroll.saturating_sub(10);
+            }
+            Some(ViewAction::PageDown) => {
+                self.asset_details_scroll = self.asset_details_scroll.saturating_add(10);
+            }
+            Some(ViewAction::Top) => {
+                self.asset_details_scroll = 0;
+            }
+            // Write or change the asset's local note
+            Some(ViewAction::Note) => {
+                if let Some(ref details) = self.selected_asset_details {
+                    let note = self
+                        .notes
+                        .get(&details.uuid)
+                        .unwrap_or_default()
+                        .to_string();
+                    let prompt = Prompt::new(
+                        format!("Note on {} (empty removes it): ", details.name),
+                        PromptAction::Note(details.uuid.clone()),
+                        |_| Ok(()),
+                    );
+                    self.open_prompt(prompt.with_input(note));
+                }
+            }
+            Some(ViewAction::Copy) => {
+                let json = self.selected_asset_details.as_ref().map(|details| {
+                    let mut value = serde_json::to_value(details)?;
+                    if let Some(GeometryState::Ready(geometry)) = self.geometry.get(&details.uuid)
+                        && let Some(object) = value.as_object_mut()
+                    {
+                        object.insert("geometry".to_string(), serde_json::to_value(geometry)?);
+                    }
+                    serde_json::to_string_pretty(&value)
+                });
+                match json {
+                    Some(Ok(json)) => self.copy_to_clipboard(json, "Asset details"),
+                    Some(Err(e)) => self.notify(
+                        NotificationLevel::Error,
+                        format!("Failed to serialize asset details: {}", e),
+                    ),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_error_modal_keys(&mut self, key: KeyEvent) {
+        match KeyContext::ErrorDetails.action(key) {
+            Some(ViewAction::Back) => {
+                self.close_modal(Modal::Error);
+            }
+            Some(ViewAction::Up) => {
+                self.error_modal_scroll = self.error_modal_scroll.saturating_sub(1);
+            }
+            Some(ViewAction::Down) => {
+                self.error_modal_scroll = self.error_modal_scroll.saturating_add(1);
+            }
+            Some(ViewAction::PageUp) => {
+                self.error_modal_scroll = self.error_modal_scroll.saturating_sub(10);
+            }
+            Some(ViewAction::PageDown) => {
+                self.error_modal_scroll = self.error_modal_scroll.saturating_add(10);
+            }
+            Some(ViewAction::Top) => {
+                self.error_modal_scroll = 0;
+            }
+            Some(ViewAction::Copy) => {
+                if let Some(text) = self.error_modal.as_ref().map(ErrorDetail::to_text) {
+                    self.copy_to_clipboard(text, "Error details");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_geometric_match_keys(&mut self, key: KeyEvent) {
+        let action = KeyContext::GeometricMatch.action(key);
+        match action {
+            Some(ViewAction::Back) => {
+                self.modals.close(Modal::GeometricMatch);
+            }
+            // Navigate up in geometric match results
+            Some(ViewAction::Up) if self.geometric_match_scroll_position > 0 => {
+                self.geometric_match_scroll_position -= 1;
+            }
+            // Navigate down in geometric match results
+            Some(ViewAction::Down)
+                if self.geometric_match_scroll_position + 1
+                    < self.visible_geometric_matches().len() =>
+            {
+                self.geometric_match_scroll_position += 1;
+            }
+            // Scroll left in the table (horizontal scrolling)
+            Some(ViewAction::Left) if self.geometric_match_horizontal_scroll > 0 => {
+                self.geometric_match_horizontal_scroll -= 1;
+            }
+            Some(ViewAction::Right) => {
+                // Scroll right in the table (horizontal scrolling)
+                // We can't determine max columns without knowing the terminal width, so just increment
+                self.geometric_match_horizontal_scroll += 1;
+            }
+            // Compare the metadata of the matched asset and the selected match
+            Some(ViewAction::Compare) => {
+                if let Some(source) = self.geometric_match_source.clone()
+                    && let Some((asset, _)) = self
+                        .visible_geometric_matches()
+                        .get(self.geometric_match_scroll_position)
+                {
+                    let uuid = asset.uuid.clone();
+                    self.compare_metadata(source, uuid).await;
+                }
+            }
+            // Copy the selected match's UUID (y) or path (Y)
+            Some(ViewAction::CopyUuid | ViewAction::CopyPath) => {
+                let asset = self
+                    .visible_geometric_matches()
+                    .get(self.geometric_match_scroll_position)
+                    .map(|(asset, _)| asset.clone());
+                self.copy_asset_reference(asset, action == Some(ViewAction::CopyPath));
+            }
+            // Queue the selected match for review, or take it out of the queue
+            Some(ViewAction::Queue) => {
+                let Some((asset, similarity)) = self
+                    .visible_geometric_matches()
+                    .get(self.geometric_match_scroll_position)
+                    .map(|(asset, similarity)| (asset.clone(), *similarity))
+                else {
+                    return;
+                };
+                if let Some(index) = self
+                    .match_queue
+                    .iter()
+                    .position(|(queued, _)| queued.uuid == asset.uuid)
+                {
+                    self.match_queue.remove(index);
+                } else {
+                    self.match_queue.push((asset, similarity));
+                }
+                if self.geometric_match_scroll_position + 1 < self.visible_geometric_matches().len()
+                {
+                    self.geometric_match_scroll_position += 1;
+                }
+                self.save_match_session();
+            }
+            Some(ViewAction::Review) => self.start_match_review().await,
+            Some(ViewAction::HideSelf) => {
+                self.geometric_match_hide_self = !self.geometric_match_hide_self;
+                self.clamp_geometric_match_selection();
+                self.save_match_session();
+            }
+            Some(ViewAction::SameFolder) => {
+                if self.geometric_match_source_folder.is_none() {
+                    self.notify(
+                        NotificationLevel::Warning,
+                        "The folder of the matched asset is not known",
+                    );
+                    return;
+                }
+                self.geometric_match_hide_same_folder = !self.geometric_match_hide_same_folder;
+                self.clamp_geometric_match_selection();
+                self.save_match_session();
+            }
+            Some(ViewAction::MinSimilarity) => {
+                let current = self
+                    .geometric_match_min_similarity
+                    .map(|percent| percent.to_string())
+                    .unwrap_or_default();
+                self.open_prompt(
+                    Prompt::new(
+                        "Minimum similarity % (empty shows all): ",
+                        PromptAction::MinSimilarity,
+                        optional_percentage,
+                    )
+                    .with_input(current),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_prompt_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut prompt) = self.prompt else {
+            return;
+        };
+        match KeyContext::Prompt.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::Prompt),
+            _ if prompt.input.handle_key(key) => prompt.error = None,
+            Some(ViewAction::Open) => {
+                // Refused input stays in the prompt, with the reason next to it
+                let Some(input) = prompt.accept() else {
+                    return;
+                };
+                let Some(prompt) = self.prompt.take() else {
+                    return;
+                };
+                self.modals.close(Modal::Prompt);
+                // Remote paths and names are taken as typed; local paths may start with "~"
+                let path = config::expand_home(&input);
+                match prompt.action {
+                    PromptAction::Upload => self.upload_asset_to_current_folder(path).await,
+                    PromptAction::UploadAndMatch => self.start_upload_and_match(path),
+                    PromptAction::ImportMetadata => self.open_metadata_import(path),
+                    PromptAction::Sync(folder) => self.plan_sync(folder, path).await,
+                    PromptAction::Move(asset) => {
+                        let from = asset.folder_path().to_string();
+                        if self.move_asset(asset.clone(), input).await {
+                            self.undo.push(Undoable::Move { asset, from });
+                        }
+                    }
+                    PromptAction::Rename(asset) => {
+                        if self.rename_asset(asset.clone(), input.clone()).await {
+                            let previous = asset.name.clone();
+                            let asset = Asset {
+                                name: input,
+                                ..asset
+                            };
+                            self.undo.push(Undoable::Rename { asset, previous });
+                        }
+                    }
+                    PromptAction::CreateFolder(parent) => self.create_folder(parent, input).await,
+                    PromptAction::Note(uuid) => self.set_note(&uuid, &input),
+                    PromptAction::MinSimilarity => self.set_min_similarity(&input),
+                    PromptAction::CreateProperty => {
+                        let (name, property_type) = schema::parse_definition(&input);
+                        self.create_property(name.to_string(), property_type.to_string())
+                            .await;
+                    }
+                    PromptAction::BasketMetadata => {
+                        if let Some((name, value)) = input.split_once('=') {
+                            self.start_basket_metadata(name.trim(), value.trim());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Upload a file to the staging folder, wait for processing and geometric-match it
+    fn start_upload_and_match(&mut self, file: PathBuf) {
+        if self.refuse_offline("Uploading") {
+            return;
+        }
+        if !file.is_file() {
+            self.notify(
+                NotificationLevel::Error,
+                format!("{} is not a file", file.display()),
+            );
+            return;
+        }
+        let Some(folder) = self
+            .config
+            .staging_folder
+            .clone()
+            .or_else(|| self.current_folder.clone())
+        else {
+            self.notify(
+                NotificationLevel::Warning,
+                "Open a folder or set staging_folder in the config to upload to",
+            );
+            return;
+        };
+
+        let name = file.display().to_string();
+        let job = self
+            .jobs
+            .start(JobKind::Match, format!("Upload and match {}", name), 3);
+        self.jobs.touches(&job, [folder.clone()]);
+        self.pipeline
+            .start(job, file, folder.clone(), self.config.processing.clone());
+        self.notify(
+            NotificationLevel::Info,
+            format!("Uploading {} to {}; matches open when ready", name, folder),
+        );
+    }
+
+    // Show the matches of finished upload-and-match runs
+    pub fn update_pipeline(&mut self) {
+        for result in self.pipeline.take_ready() {
+            self.hooks.emit(
+                HookEvent::AssetUploaded,
+                serde_json::json!({
+                    "file": result.file.display().to_string(),
+                    "folder": result.folder,
+                    "uuid": result.uuid,
+                }),
+            );
+            let folder = result.folder.trim_matches('/').to_string();
+            self.set_geometric_match_results(&result.uuid, Some(folder), result.matches);
+            self.geometric_match_horizontal_scroll = 0;
+            self.modals.push(Modal::GeometricMatch);
+            self.status_message = format!("Geometric match performed on: {}", result.name);
+        }
+    }
+
+    // Ask for the local directory to sync the selected folder with, suggesting the configured one
+    fn start_sync_prompt(&mut self) {
+        let Some(folder) = self
+            .folders
+            .get(self.selected_folder_index)
+            .filter(|folder| folder.uuid != "..")
+            .map(|folder| folder.path.clone())
+        else {
+            return;
+        };
+        let directory = self.config.sync.get(&folder).cloned().unwrap_or_default();
+        self.open_prompt(
+            Prompt::new(
+                format!("Sync {} with directory: ", folder),
+                PromptAction::Sync(folder),
+                existing_directory,
+            )
+            .with_input(directory),
+        );
+    }
+
+    // Compare the folder with the directory and show what syncing would do
+    pub async fn plan_sync(&mut self, folder: String, directory: PathBuf) {
+        if self.refuse_offline("Syncing") {
+            return;
+        }
+        if !directory.is_dir() {
+            self.notify(
+                NotificationLevel::Error,
+                format!("{} is not a directory", directory.display()),
+            );
+            return;
+        }
+
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "list",
+            "--folder-path",
+            &folder,
+            "--format",
+            "json",
+            "--metadata",
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Planning sync of {} with {}", folder, directory.display());
+        let remote = pcli_commands::list_assets_in_folder(&folder).await;
+        self.command_in_progress = false;
+        let remote = match remote {
+            Ok(remote) => {
+                self.log_command_success();
+                remote
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Sync planning failed: {}", e),
+                );
+                self.log_command_error(&e);
+                return;
+            }
+        };
+
+        match SyncPlan::new(folder, directory, remote) {
+            Ok(plan) => {
+                self.sync_plan = Some(plan);
+                self.modals.push(Modal::SyncPlan);
+            }
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Cannot read the local directory: {:#}", e),
+            ),
+        }
+    }
+
+    fn handle_sync_plan_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut plan) = self.sync_plan else {
+            return;
+        };
+        let last = plan.entries.len().saturating_sub(1);
+        match KeyContext::SyncPlan.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::SyncPlan),
+            Some(ViewAction::Up) => plan.selected = plan.selected.saturating_sub(1),
+            Some(ViewAction::Down) => plan.selected = (plan.selected + 1).min(last),
+            Some(ViewAction::PageUp) => plan.selected = plan.selected.saturating_sub(10),
+            Some(ViewAction::PageDown) => plan.selected = (plan.selected + 10).min(last),
+            // Leave a file out of the sync, or put it back
+            Some(ViewAction::Toggle) => {
+                if let Some(entry) = plan.entries.get_mut(plan.selected)
+                    && entry.action.transfers()
+                {
+                    entry.included = !entry.included;
+                }
+            }
+            Some(ViewAction::Open) => {
+                let Some(plan) = self.sync_plan.take() else {
+                    return;
+                };
+                self.modals.close(Modal::SyncPlan);
+                let entries: Vec<_> = plan
+                    .entries
+                    .into_iter()
+                    .filter(|entry| entry.included && entry.action.transfers())
+                    .collect();
+                if entries.is_empty() {
+                    self.notify(NotificationLevel::Info, "Nothing to sync");
+                    return;
+                }
+                self.start_work(
+                    format!("Sync {} with {}", plan.folder, plan.directory.display()),
+                    Work::Sync {
+                        folder: plan.folder,
+                        directory: plan.directory,
+                        entries,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    // Every asset of the cached folder listings
+    fn cached_assets(&self) -> impl Iterator<Item = &Asset> {
+        self.folder_cache
+            .values()
+            .flat_map(|cache| cache.assets.iter())
+    }
+
+    async fn handle_finder_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut finder) = self.finder else {
+            return;
+        };
+        let action = KeyContext::Finder.action(key);
+        match action {
+            Some(ViewAction::Back) => self.close_modal(Modal::Finder),
+            // Also search the whole tenant on the server
+            Some(ViewAction::SearchServer) => {
+                if finder.input.as_str().trim().is_empty() {
+                    self.notify(
+                        NotificationLevel::Info,
+                        "Type a name to search the server for",
+                    );
+                } else {
+                    finder.search_server();
+                }
+            }
+            Some(ViewAction::Up) => finder.move_selection(-1),
+            Some(ViewAction::Down) => finder.move_selection(1),
+            Some(ViewAction::PageUp) => finder.move_selection(-10),
+            Some(ViewAction::PageDown) => finder.move_selection(10),
+            _ if finder.input.handle_key(key) => {
+                finder.selected = 0;
+                self.refresh_finder();
+            }
+            Some(ViewAction::Open) => {
+                let Some(asset) = finder.selected_asset().cloned() else {
+                    return;
+                };
+                self.close_modal(Modal::Finder);
+                self.go_to_asset(&asset).await;
+            }
+            _ => {}
+        }
+    }
+
+    fn refresh_finder(&mut self) {
+        let Some(mut finder) = self.finder.take() else {
+            return;
+        };
+        finder.refresh(self.cached_assets());
+        self.finder = Some(finder);
+    }
+
+    // Add the results of a finished server search to the finder
+    pub fn update_finder(&mut self) {
+        let Some(result) = self
+            .finder
+            .as_mut()
+            .and_then(|finder| finder.take_server_results())
+        else {
+            return;
+        };
+        match result {
+            Ok(count) => {
+                self.refresh_finder();
+                self.notify(
+                    NotificationLevel::Info,
+                    format!("Server search found {} assets", count),
+                );
+            }
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Server search failed: {}", e),
+            ),
+        }
+    }
+
+    async fn mark_folder_for_diff(&mut self) {
+        let Some(folder) = self
+            .folders
+            .get(self.selected_folder_index)
+            .filter(|folder| folder.uuid != "..")
+            .map(|folder| folder.path.clone())
+        else {
+            return;
+        };
+
+        match self.diff_mark.take() {
+            Some(marked) if marked == folder => {
+                self.notify(NotificationLevel::Info, "Comparison mark cleared");
+            }
+            Some(marked) => self.compare_folders(marked, folder).await,
+            None => {
+                self.notify(
+                    NotificationLevel::Info,
+                    format!("Marked {} — press = on another folder to compare", folder),
+                );
+                self.diff_mark = Some(folder);
+            }
+        }
+    }
+
+    async fn mark_asset_for_diff(&mut self) {
+        let Some(asset) = self.assets.get(self.selected_asset_index).cloned() else {
+            return;
+        };
+
+        match self.asset_diff_mark.take() {
+            Some(marked) if marked.uuid == asset.uuid => {
+                self.notify(NotificationLevel::Info, "Comparison mark cleared");
+            }
+            Some(marked) => self.compare_metadata(marked.uuid, asset.uuid).await,
+            None => {
+                self.notify(
+                    NotificationLevel::Info,
+                    format!(
+                        "Marked {} — press = on another asset to compare",
+                        asset.name
+                    ),
+                );
+                self.asset_diff_mark = Some(asset);
+            }
+        }
+    }
+
+    // Load the metadata of two assets and open them side by side
+    pub async fn compare_metadata(&mut self, a: String, b: String) {
+        let mut details = Vec::with_capacity(2);
+        for uuid in [&a, &b] {
+            self.last_executed_command = PcliCommand::new(&[
+                "asset",
+                "get",
+                "--uuid",
+                uuid,
+                "--format",
+                "json",
+                "--metadata",
+            ]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            match pcli_commands::get_asset_details(uuid).await {
+                Ok(asset) => {
+                    self.log_command_success();
+                    details.push(asset);
+                }
+                Err(e) => {
+                    self.notify(
+                        NotificationLevel::Error,
+                        format!("Comparison failed: {}", e),
+                    );
+                    self.log_command_error(&e);
+                    self.command_in_progress = false;
+                    return;
+                }
+            }
+        }
+        self.command_in_progress = false;
+
+        let (Some(b), Some(a)) = (details.pop(), details.pop()) else {
+            return;
+        };
+        let diff = MetadataDiff::new(a.name, &a.metadata, b.name, &b.metadata);
+        self.status_message = format!(
+            "{} of {} metadata properties differ",
+            diff.differences(),
+            diff.rows.len()
+        );
+        self.metadata_diff = Some(diff);
+        self.modals.push(Modal::MetadataDiff);
+    }
+
+    fn handle_metadata_diff_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut diff) = self.metadata_diff else {
+            return;
+        };
+        match KeyContext::MetadataDiff.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::MetadataDiff),
+            Some(ViewAction::Up) => diff.move_selection(-1),
+            Some(ViewAction::Down) => diff.move_selection(1),
+            Some(ViewAction::PageUp) => diff.move_selection(-10),
+            Some(ViewAction::PageDown) => diff.move_selection(10),
+            Some(ViewAction::OnlyDifferences) => diff.toggle_only_differences(),
+            _ => {}
+        }
+    }
+
+    // List the assets of two folders and open the comparison
+    pub async fn compare_folders(&mut self, a: String, b: String) {
+        self.status_message = format!("Comparing {} with {}", a, b);
+        let mut listings = Vec::with_capacity(2);
+        for path in [&a, &b] {
+            self.last_executed_command = PcliCommand::new(&[
+                "asset",
+                "list",
+                "--folder-path",
+                path,
+                "--format",
+                "json",
+                "--metadata",
+            ]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            match pcli_commands::list_assets_in_folder(path).await {
+                Ok(assets) => {
+                    self.log_command_success();
+                    let assets: Vec<Asset> = assets
+                        .into_iter()
+                        .map(|a| Asset {
+                            uuid: a.uuid,
+                            name: a.name,
+                            folder_uuid: path.clone(),
+                            file_type: a.file_type,
+                            size: a.file_size,
+                            path: a.path,
+                            metadata: a.metadata,
+                            is_assembly: a.is_assembly,
+                            processing_status: a.processing_status,
+                        })
+                        .collect();
+                    listings.push(assets);
+                }
+                Err(e) => {
+                    self.notify(
+                        NotificationLevel::Error,
+                        format!("Comparison failed: {}", e),
+                    );
+                    self.log_command_error(&e);
+                    self.command_in_progress = false;
+                    return;
+                }
+            }
+        }
+        self.command_in_progress = false;
+
+        let b_assets = listings.pop().unwrap_or_default();
+        let a_assets = listings.pop().unwrap_or_default();
+        let diff = FolderDiff::by_name(a, a_assets, b, b_assets);
+        self.status_message = format!(
+            "{} only in A, {} in both, {} only in B",
+            diff.only_a.len(),
+            diff.both.len(),
+            diff.only_b.len()
+        );
+        self.folder_diff = Some(diff);
+        self.modals.push(Modal::FolderDiff);
+    }
+
+    async fn handle_folder_diff_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut diff) = self.folder_diff else {
+            return;
+        };
+        match KeyContext::FolderDiff.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::FolderDiff),
+            Some(ViewAction::Left) => diff.column = diff.column.previous(),
+            Some(ViewAction::Right) => diff.column = diff.column.next(),
+            Some(ViewAction::Up) => diff.move_selection(-1),
+            Some(ViewAction::Down) => diff.move_selection(1),
+            Some(ViewAction::PageUp) => diff.move_selection(-10),
+            Some(ViewAction::PageDown) => diff.move_selection(10),
+            // Pair assets with different names that are the same model
+            Some(ViewAction::GeometricMatch) if !diff.matching && !diff.only_a.is_empty() => {
+                let title = format!("Geometric comparison of {}", diff.a);
+                let job = self.jobs.start(JobKind::Match, title, 0);
+                diff.start_geometric_match(job);
+            }
+            // Copy the selected asset or all of the column to the folder it is missing from
+            Some(ViewAction::CopyAcross) => {
+                if let Some((asset, folder)) = diff.selected_missing() {
+                    self.start_copy(vec![asset], folder);
+                }
+            }
+            Some(ViewAction::CopyAllAcross) => {
+                if let Some((assets, folder)) = diff.all_missing()
+                    && !assets.is_empty()
+                {
+                    self.start_copy(assets, folder);
+                }
+            }
+            // Compare again, e.g. after copying
+            Some(ViewAction::Refresh) => {
+                let (a, b) = (diff.a.clone(), diff.b.clone());
+                self.compare_folders(a, b).await;
+            }
+            _ => {}
+        }
+    }
+
+    fn start_copy(&mut self, assets: Vec<Asset>, folder: String) {
+        if self.refuse_offline("Copying assets") {
+            return;
+        }
+        let title = match assets.as_slice() {
+            [asset] => format!("Copy {} to {}", asset.name, folder),
+            _ => format!("Copy {} assets to {}", assets.len(), folder),
+        };
+        let job = self.jobs.start(JobKind::Batch, title.clone(), assets.len());
+        self.jobs.touches(&job, [folder.clone()]);
+        tokio::spawn(pcli_commands::in_job(folder_diff::copy_assets(
+            job, assets, folder,
+        )));
+        self.notify(
+            NotificationLevel::Info,
+            format!("{} in the background", title),
+        );
+    }
+
+    // Pick up geometric pairs found for the open folder comparison
+    pub fn update_folder_diff(&mut self) {
+        if let Some(ref mut diff) = self.folder_diff {
+            diff.apply_matches();
+        }
+    }
+
+    async fn handle_where_used_keys(&mut self, key: KeyEvent) {
+        let action = KeyContext::WhereUsed.action(key);
+        match action {
+            Some(ViewAction::Back) => self.close_modal(Modal::WhereUsed),
+            Some(ViewAction::Up) => {
+                self.where_used_selected = self.where_used_selected.saturating_sub(1);
+            }
+            Some(ViewAction::Down)
+                if self.where_used_selected + 1 < self.where_used_results.len() =>
+            {
+                self.where_used_selected += 1;
+            }
+            // Copy the selected assembly's UUID or path
+            Some(ViewAction::CopyUuid | ViewAction::CopyPath) => {
+                let asset = self
+                    .where_used_results
+                    .get(self.where_used_selected)
+                    .cloned();
+                self.copy_asset_reference(asset, action == Some(ViewAction::CopyPath));
+            }
+            // Jump to the selected assembly in its folder
+            Some(ViewAction::Open) => {
+                if let Some(asset) = self
+                    .where_used_results
+                    .get(self.where_used_selected)
+                    .cloned()
+                {
+                    self.close_modal(Modal::WhereUsed);
+                    self.go_to_asset(&asset).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Open the folder an asset lives in and select it in the Assets pane
+    pub async fn go_to_asset(&mut self, asset: &Asset) {
+        let folder = match asset.path.rfind('/') {
+            Some(index) if index > 0 => asset.path[..index].to_string(),
+            _ => {
+                self.notify(
+                    NotificationLevel::Warning,
+                    format!("No folder known for {}", asset.name),
+                );
+                return;
+            }
+        };
+
+        self.enter_folder(folder.clone()).await;
+        self.current_state = AppState::Folders;
+        self.active_pane = ActivePane::Assets;
+        match self.assets.iter().position(|a| a.uuid == asset.uuid) {
+            Some(index) => {
+                self.selected_asset_index = index;
+                self.status_message = format!("Selected asset: {}", asset.name);
+            }
+            None => self.notify(
+                NotificationLevel::Warning,
+                format!("{} was not found in {}", asset.name, folder),
+            ),
+        }
+    }
+}
+
+impl App {
+    pub async fn show_asset_details(&mut self) {
+        if self.assets.is_empty() || self.selected_asset_index >= self.assets.len() {
+            return; // No assets or invalid selection
+        }
+
+        let selected_asset = self.assets[self.selected_asset_index].clone();
+        self.show_details_of(&selected_asset).await;
+    }
+
+    // Load and show the details modal of any asset, e.g. a search result
+    pub async fn show_details_of(&mut self, selected_asset: &Asset) {
+        let asset_uuid = &selected_asset.uuid;
+
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "get",
+            "--uuid",
+            asset_uuid,
+            "--format",
+            "json",
+            "--metadata",
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Loading details for asset: {}", selected_asset.name);
+
+        self.last_operation = Some(Operation::AssetDetails);
+        match pcli_commands::get_asset_details(asset_uuid).await {
+            Ok(pcli_asset_details) => {
+                // Convert from pcli_commands::AssetDetails to app::AssetDetails
+                let asset_details = crate::app::AssetDetails {
+                    uuid: pcli_asset_details.uuid,
+                    name: pcli_asset_details.name,
+                    path: pcli_asset_details.path,
+                    file_type: pcli_asset_details.file_type,
+                    file_size: pcli_asset_details.file_size,
+                    processing_status: pcli_asset_details.processing_status,
+                    created_at: pcli_asset_details.created_at,
+                    updated_at: pcli_asset_details.updated_at,
+                    is_assembly: pcli_asset_details.is_assembly,
+                    tenant_id: pcli_asset_details.tenant_id,
+                    folder_id: pcli_asset_details.folder_id,
+                    state: pcli_asset_details.state,
+                    metadata: pcli_asset_details.metadata,
+                };
+
+                self.geometry.request(&asset_details.uuid);
+                self.selected_asset_details = Some(asset_details);
+                self.modals.push(Modal::AssetDetails);
+                self.asset_details_scroll = 0;
+                self.notify(
+                    NotificationLevel::Info,
+                    format!("Loaded details for {}", selected_asset.name),
+                );
+
+                // Log successful command with success indicator
+                self.log_command_success();
+                self.command_in_progress = false; // Clear flag when command completes
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Failed to load asset details: {}", e),
+                );
+
+                // Log failed command with error indicator
+                self.log_command_error(&e);
+                self.command_in_progress = false; // Clear flag when command completes
+            }
+        }
+    }
+    // Look up the assemblies that use a part and show them in the where-used modal
+    pub async fn find_where_used(&mut self, asset_uuid: &str, asset_name: &str) {
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "where-used",
+            "--uuid",
+            asset_uuid,
+            "--format",
+            "json",
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Finding assemblies that use: {}", asset_name);
+
+        self.last_operation = Some(Operation::WhereUsed(asset_uuid.to_string()));
+        match pcli_commands::where_used(asset_uuid).await {
+            Ok(assemblies) => {
+                self.where_used_results = assemblies
+                    .into_iter()
+                    .map(|a| Asset {
+                        uuid: a.uuid,
+                        name: a.name,
+                        folder_uuid: a
+                            .path
+                            .rsplit_once('/')
+                            .map(|(folder, _)| folder.to_string())
+                            .unwrap_or_default(),
+                        file_type: a.file_type,
+                        size: a.file_size,
+                        path: a.path,
+                        metadata: a.metadata,
+                        is_assembly: a.is_assembly,
+                        processing_status: a.processing_status,
+                    })
+                    .collect();
+                self.where_used_selected = 0;
+                self.where_used_part = Some(asset_name.to_string());
+                self.modals.push(Modal::WhereUsed);
+                self.notify(
+                    NotificationLevel::Info,
+                    format!(
+                        "{} is used in {} assemblies",
+                        asset_name,
+                        self.where_used_results.len()
+                    ),
+                );
+
+                self.log_command_success();
+                self.command_in_progress = false;
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Where-used lookup failed: {}", e),
+                );
+
+                self.log_command_error(&e);
+                self.command_in_progress = false;
+            }
+        }
+    }
+
+    pub async fn perform_geometric_match(&mut self, asset_uuid: &str) {
+        self.last_executed_command = PcliCommand::new(&[
+            "asset",
+            "geometric-match",
+            "--uuid",
+            asset_uuid,
+            "--format",
+            "json",
+            "--metadata",
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true; // Set flag when command starts
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Performing geometric match on asset: {}", asset_uuid);
+
+        self.last_operation = Some(Operation::GeometricMatch(asset_uuid.to_string()));
+        match pcli_commands::geometric_match(asset_uuid).await {
+            Ok(pcli_match_results) => {
+                let summary = format!("Geometric match found {} matches", pcli_match_results.len());
+                self.set_geometric_match_results(asset_uuid, None, pcli_match_results);
+
+                // Log successful command with success indicator
+                self.log_command_success();
+                self.command_in_progress = false; // Clear flag when command completes
+                self.alert_if_long(JobKind::Match, self.command_duration(), true, &summary);
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Geometric match failed: {}", e),
+                );
+
+                // Log failed command with error indicator
+                self.log_command_error(&e);
+                self.command_in_progress = false; // Clear flag when command completes
+                let summary = format!("Geometric match failed: {}", e);
+                self.alert_if_long(JobKind::Match, self.command_duration(), false, &summary);
+            }
+        }
+    }
+
+    // The geometric match results that pass the similarity threshold and the hide toggles
+    pub fn visible_geometric_matches(&self) -> Vec<&(Asset, f64)> {
+        let source = self.geometric_match_source.as_deref();
+        let source_folder = self.geometric_match_source_folder.as_deref();
+        self.geometric_match_results
+            .iter()
+            .filter(|(_, similarity)| {
+                self.geometric_match_min_similarity
+                    .is_none_or(|minimum| *similarity >= minimum)
+            })
+            .filter(|(asset, _)| {
+                !(self.geometric_match_hide_self && source == Some(asset.uuid.as_str()))
+            })
+            .filter(|(asset, _)| {
+                !(self.geometric_match_hide_same_folder
+                    && source_folder == Some(asset.folder_path()))
+            })
+            .collect()
+    }
+
+    // An asset from the listings and results seen so far
+    fn known_asset(&self, uuid: &str) -> Option<&Asset> {
+        self.all_assets
+            .iter()
+            .chain(self.cached_assets())
+            .chain(self.geometric_match_results.iter().map(|(asset, _)| asset))
+            .find(|asset| asset.uuid == uuid)
+    }
+
+    // Review the queued matches one by one; decisions of an earlier review of them are kept
+    async fn start_match_review(&mut self) {
+        if self.match_queue.is_empty() {
+            self.notify(
+                NotificationLevel::Warning,
+                "Queue matches with Space first, then r reviews them",
+            );
+            return;
+        }
+        let Some(uuid) = self.geometric_match_source.clone() else {
+            return;
+        };
+        let source = match self.known_asset(&uuid).cloned() {
+            Some(source) => source,
+            None => {
+                self.last_executed_command = PcliCommand::new(&[
+                    "asset",
+                    "get",
+                    "--uuid",
+                    &uuid,
+                    "--format",
+                    "json",
+                    "--metadata",
+                ]);
+                self.command_history
+                    .push(HistoryEntry::new(self.last_executed_command.clone()));
+                self.command_in_progress = true;
+                self.command_started = Some(Instant::now());
+                let result = pcli_commands::get_asset_details(&uuid).await;
+                self.command_in_progress = false;
+                match result {
+                    Ok(details) => {
+                        self.log_command_success();
+                        Asset {
+                            uuid: details.uuid,
+                            name: details.name,
+                            folder_uuid: details.folder_id,
+                            file_type: details.file_type,
+                            size: details.file_size,
+                            path: details.path,
+                            metadata: details.metadata,
+                            is_assembly: details.is_assembly,
+                            processing_status: details.processing_status,
+                        }
+                    }
+                    Err(e) => {
+                        self.notify(
+                            NotificationLevel::Error,
+                            format!("Failed to load the matched asset: {}", e),
+                        );
+                        self.log_command_error(&e);
+                        return;
+                    }
+                }
+            }
+        };
+
+        let earlier = self.match_review.take();
+        let pairs = self
+            .match_queue
+            .iter()
+            .map(|(asset, similarity)| ReviewPair {
+                asset: asset.clone(),
+                similarity: *similarity,
+                decision: earlier.as_ref().and_then(|review| {
+                    review
+                        .pairs
+                        .iter()
+                        .find(|pair| pair.asset.uuid == asset.uuid)
+                        .and_then(|pair| pair.decision)
+                }),
+            })
+            .collect();
+        self.match_review = Some(Review::new(source, pairs));
+        self.modals.push(Modal::MatchReview);
+        self.save_match_session();
+    }
+
+    async fn handle_match_review_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut review) = self.match_review else {
+            return;
+        };
+        match KeyContext::MatchReview.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::MatchReview),
+            Some(ViewAction::Left) => review.step(-1),
+            Some(ViewAction::Right) => review.step(1),
+            Some(ViewAction::Up) => review.diff.move_selection(-1),
+            Some(ViewAction::Down) => review.diff.move_selection(1),
+            Some(ViewAction::Keep) => self.decide_pair(Decision::Keep),
+            Some(ViewAction::Duplicate) => self.decide_pair(Decision::Duplicate),
+            Some(ViewAction::Ignore) => self.decide_pair(Decision::Ignore),
+            Some(ViewAction::Browser) => self.open_comparison().await,
+            Some(ViewAction::Export) => self.export_review(),
+            _ => {}
+        }
+    }
+
+    // The decisions are written out as soon as the last one is made
+    fn decide_pair(&mut self, decision: Decision) {
+        let Some(ref mut review) = self.match_review else {
+            return;
+        };
+        let was_complete = review.is_complete();
+        review.decide(decision);
+        if review.is_complete() && !was_complete {
+            self.export_review();
+        }
+        self.save_match_session();
+    }
+
+    // Save the shown match results with their filters, queue and decisions, so the session can
+    // be reopened from the match history
+    fn save_match_session(&mut self) {
+        let (Some(created), Some(source_uuid)) = (
+            self.match_session_started,
+            self.geometric_match_source.clone(),
+        ) else {
+            return;
+        };
+        let source = self
+            .match_review
+            .as_ref()
+            .map(|review| review.source.clone())
+            .or_else(|| self.known_asset(&source_uuid).cloned());
+        let session = MatchSession {
+            created,
+            source_uuid,
+            source,
+            source_folder: self.geometric_match_source_folder.clone(),
+            min_similarity: self.geometric_match_min_similarity,
+            hide_self: self.geometric_match_hide_self,
+            hide_same_folder: self.geometric_match_hide_same_folder,
+            results: self.geometric_match_results.clone(),
+            queue: self
+                .match_queue
+                .iter()
+                .map(|(asset, _)| asset.uuid.clone())
+                .collect(),
+            decisions: self
+                .match_review
+                .as_ref()
+                .map(|review| review.pairs.clone())
+                .unwrap_or_default(),
+        };
+        if let Err(e) = match_history::save(&session) {
+            self.notify(
+                NotificationLevel::Warning,
+                format!("Failed to save the match session: {:#}", e),
+            );
+        }
+    }
+
+    // Start a batch job, journaled so that it can be resumed on the next launch when the app
+    // stops before it's done; the demo's jobs are not kept
+    fn start_work(&mut self, title: String, work: Work) {
+        let mut job = self.jobs.start(work.kind(), title.clone(), work.count());
+        if !demo::is_enabled() {
+            match Journal::create(&title, &work) {
+                Ok(journal) => job = job.journaled(journal),
+                Err(e) => self.notify(
+                    NotificationLevel::Warning,
+                    format!("{:#}; {} can't be resumed if interrupted", e, title),
+                ),
+            }
+        }
+        match work {
+            Work::Sync {
+                folder,
+                directory,
+                entries,
+            } => {
+                self.jobs.touches(&job, [folder.clone()]);
+                tokio::spawn(pcli_commands::in_job(sync::execute(
+                    job, folder, directory, entries,
+                )));
+            }
+            Work::FolderDownload { download } => {
+                tokio::spawn(pcli_commands::in_job(folder_download::execute(
+                    job, download,
+                )));
+            }
+            Work::Metadata {
+                name,
+                value,
+                assets,
+            } => {
+                for asset in &assets {
+                    self.folder_cache.remove(asset.folder_path());
+                }
+                tokio::spawn(pcli_commands::in_job(basket::set_metadata(
+                    job, assets, name, value,
+                )));
+            }
+            Work::Import { rows } => {
+                for asset in rows.iter().filter_map(|row| row.asset.as_ref()) {
+                    self.folder_cache.remove(asset.folder_path());
+                }
+                tokio::spawn(pcli_commands::in_job(csv_import::apply(job, rows)));
+            }
+            Work::Delete { assets } => {
+                for asset in &assets {
+                    self.folder_cache.remove(asset.folder_path());
+                }
+                tokio::spawn(pcli_commands::in_job(basket::delete(job, assets)));
+            }
+        }
+    }
+
+    // List the jobs an earlier session stopped in the middle of, to resume or discard
+    fn offer_interrupted_jobs(&mut self) {
+        if demo::is_enabled() {
+            return;
+        }
+        match InterruptedJobs::load() {
+            Ok(interrupted) => {
+                if interrupted.unreadable > 0 {
+                    self.notify(
+                        NotificationLevel::Warning,
+                        format!("Skipped {} unreadable job journals", interrupted.unreadable),
+                    );
+                }
+                if !interrupted.jobs.is_empty() {
+                    self.interrupted_jobs = Some(interrupted);
+                    self.modals.push(Modal::InterruptedJobs);
+                }
+            }
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Failed to read the job journal: {:#}", e),
+            ),
+        }
+    }
+
+    fn handle_interrupted_jobs_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut interrupted) = self.interrupted_jobs else {
+            return;
+        };
+        match KeyContext::InterruptedJobs.action(key) {
+            // They are offered again on the next launch
+            Some(ViewAction::Back) => self.close_modal(Modal::InterruptedJobs),
+            Some(ViewAction::Up) => interrupted.move_selection(-1),
+            Some(ViewAction::Down) => interrupted.move_selection(1),
+            Some(ViewAction::Remove) => {
+                if let Some(job) = interrupted.take_selected() {
+                    let title = job.title.clone();
+                    match job.discard() {
+                        Ok(()) => {
+                            self.notify(NotificationLevel::Info, format!("Discarded {}", title))
+                        }
+                        Err(e) => self.notify(NotificationLevel::Error, format!("{:#}", e)),
+                    }
+                }
+            }
+            Some(ViewAction::Open) => {
+                if offline::is_enabled() {
+                    self.refuse_offline("Resuming a job");
+                    return;
+                }
+                if let Some(job) = interrupted.take_selected() {
+                    let title = job.title.clone();
+                    let (work, journal) = job.resume();
+                    let left = work.count();
+                    self.notify(
+                        NotificationLevel::Info,
+                        format!("Resuming {}: {} items left (W shows progress)", title, left),
+                    );
+                    self.start_work(title, work);
+                    if let Err(e) = journal.remove() {
+                        self.notify(NotificationLevel::Error, format!("{:#}", e));
+                    }
+                }
+            }
+            _ => {}
+        }
+        if self
+            .interrupted_jobs
+            .as_ref()
+            .is_some_and(|interrupted| interrupted.jobs.is_empty())
+        {
+            self.close_modal(Modal::InterruptedJobs);
+        }
+    }
+
+    fn open_match_history(&mut self) {
+        match MatchHistory::load() {
+            Ok(history) => {
+                if history.unreadable > 0 {
+                    self.notify(
+                        NotificationLevel::Warning,
+                        format!("Skipped {} unreadable match sessions", history.unreadable),
+                    );
+                }
+                self.match_history = Some(history);
+                self.modals.push(Modal::MatchHistory);
+            }
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Failed to load the match history: {:#}", e),
+            ),
+        }
+    }
+
+    fn handle_match_history_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut history) = self.match_history else {
+            return;
+        };
+        match KeyContext::MatchHistory.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::MatchHistory),
+            Some(ViewAction::Up) => history.move_selection(-1),
+            Some(ViewAction::Down) => history.move_selection(1),
+            Some(ViewAction::Remove) => {
+                if let Err(e) = history.remove_selected() {
+                    self.notify(NotificationLevel::Error, format!("{:#}", e));
+                }
+            }
+            Some(ViewAction::Open) => {
+                if let Some(session) = history.chosen().cloned() {
+                    self.close_modal(Modal::MatchHistory);
+                    self.reopen_match_session(session);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The tenant's metadata property definitions, with how many of the cached assets use each
+    // property. Offline, or when pcli2 can't list them, only the usage is shown.
+    async fn open_property_schema(&mut self) {
+        let definitions = if offline::is_enabled() {
+            None
+        } else {
+            self.last_executed_command =
+                PcliCommand::new(&["property", "list", "--format", "json"]);
+            self.command_history
+                .push(HistoryEntry::new(self.last_executed_command.clone()));
+            self.command_in_progress = true;
+            self.command_started = Some(Instant::now());
+            self.status_message = "Listing metadata properties...".to_string();
+
+            let result = pcli_commands::list_properties().await;
+            self.command_in_progress = false;
+            match result {
+                Ok(properties) => {
+                    self.log_command_success();
+                    Some(properties)
+                }
+                Err(e) => {
+                    self.notify(
+                        NotificationLevel::Error,
+                        format!("Listing properties failed, showing their usage only: {}", e),
+                    );
+                    self.log_command_error(&e);
+                    None
+                }
+            }
+        };
+        let cached = self
+            .folder_cache
+            .values()
+            .flat_map(|cache| cache.assets.iter());
+        let schema = PropertySchema::new(definitions, cached.chain(&self.all_assets));
+        self.property_schema = Some(schema);
+        self.modals.push(Modal::PropertySchema);
+    }
+
+    fn handle_property_schema_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut schema) = self.property_schema else {
+            return;
+        };
+        match KeyContext::PropertySchema.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::PropertySchema),
+            Some(ViewAction::Up) => schema.move_selection(-1),
+            Some(ViewAction::Down) => schema.move_selection(1),
+            Some(ViewAction::PageUp) => schema.move_selection(-10),
+            Some(ViewAction::PageDown) => schema.move_selection(10),
+            Some(ViewAction::Create) => {
+                if self.refuse_offline("Creating properties") {
+                    return;
+                }
+                let label = format!(
+                    "New property (name or name:type, default {}): ",
+                    schema::DEFAULT_PROPERTY_TYPE
+                );
+                self.open_prompt(Prompt::new(
+                    label,
+                    PromptAction::CreateProperty,
+                    property_definition,
+                ));
+            }
+            Some(ViewAction::Remove) => {
+                let Some(row) = schema.chosen().cloned() else {
+                    return;
+                };
+                if row.property_type.is_none() {
+                    self.notify(
+                        NotificationLevel::Info,
+                        format!(
+                            "{} is not defined in the tenant, only used by assets",
+                            row.name
+                        ),
+                    );
+                    return;
+                }
+                if self.refuse_offline("Deleting properties") {
+                    return;
+                }
+                let message = match row.assets {
+                    0 => format!("Delete the property {}? No cached asset uses it.", row.name),
+                    n => format!(
+                        "Delete the property {}? {} cached asset(s) have a value for it.",
+                        row.name, n
+                    ),
+                };
+                self.open_confirm(Confirm::new(
+                    " 🗑 Delete Property ",
+                    message,
+                    ConfirmAction::DeleteProperty(row.name),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    // Check the cached assets of the current folder and the folders below it against the
+    // metadata rules
+    fn open_validation_report(&mut self) {
+        if self.metadata_rules.is_empty() {
+            self.notify(
+                NotificationLevel::Info,
+                "No metadata rules; add metadata_rules to the config file",
+            );
+            return;
+        }
+        let folder = self.current_folder.clone().unwrap_or_default();
+        let cached = self
+            .folder_cache
+            .values()
+            .flat_map(|cache| cache.assets.iter());
+        let assets = cached.chain(&self.all_assets);
+        self.validation_report = Some(ValidationReport::new(&self.metadata_rules, &folder, assets));
+        self.modals.push(Modal::Validation);
+    }
+
+    async fn handle_validation_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut report) = self.validation_report else {
+            return;
+        };
+        match KeyContext::Validation.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::Validation),
+            Some(ViewAction::Up) => report.move_selection(-1),
+            Some(ViewAction::Down) => report.move_selection(1),
+            Some(ViewAction::PageUp) => report.move_selection(-10),
+            Some(ViewAction::PageDown) => report.move_selection(10),
+            Some(ViewAction::Open) => {
+                if let Some(asset) = report.chosen().cloned() {
+                    self.close_modal(Modal::Validation);
+                    self.go_to_asset(&asset).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Read a CSV file of metadata values and preview what it changes; assets are found in the
+    // cached listings
+    fn open_metadata_import(&mut self, file: PathBuf) {
+        let cached = self
+            .folder_cache
+            .values()
+            .flat_map(|cache| cache.assets.iter());
+        match ImportPlan::load(&file, cached.chain(&self.all_assets)) {
+            Ok(plan) => {
+                self.metadata_import = Some(plan);
+                self.modals.push(Modal::MetadataImport);
+            }
+            Err(e) => self.notify(
+                NotificationLevel::Error,
+                format!("Failed to read {}: {:#}", file.display(), e),
+            ),
+        }
+    }
+
+    fn handle_metadata_import_keys(&mut self, key: KeyEvent) {
+        let Some(ref mut plan) = self.metadata_import else {
+            return;
+        };
+        match KeyContext::MetadataImport.action(key) {
+            Some(ViewAction::Back) => self.close_modal(Modal::MetadataImport),
+            Some(ViewAction::Up) => plan.move_selection(-1),
+            Some(ViewAction::Down) => plan.move_selection(1),
+            Some(ViewAction::PageUp) => plan.move_selection(-10),
+            Some(ViewAction::PageDown) => plan.move_selection(10),
+            Some(ViewAction::Open) => self.start_metadata_import(),
+            _ => {}
+        }
+    }
+
+    // Apply the previewed rows as a batch job, one result per row
+    fn start_metadata_import(&mut self) {
+        if self.refuse_offline("Importing metadata") || self.refuse_denied(Permission::EditMetadata)
+        {
+            return;
+        }
+        let Some(ref plan) = self.metadata_import else {
+            return;
+        };
+        let rows: Vec<_> = plan.applicable().cloned().collect();
+        if rows.is_empty() {
+            self.notify(
+                NotificationLevel::Info,
+                "Nothing to import; every value is set already",
+            );
+            return;
+        }
+        let file = plan.file.file_name().map_or_else(
+            || plan.file.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        self.close_modal(Modal::MetadataImport);
+        self.start_work(
+            format!("Import metadata from {} ({} assets)", file, rows.len()),
+            Work::Import { rows },
+        );
+        self.notify(
+            NotificationLevel::Info,
+            format!(
+                "Importing metadata from {} in the background (W shows progress)",
+                file
+            ),
+        );
+    }
+
+    async fn create_property(&mut self, name: String, property_type: String) {
+        self.last_executed_command = PcliCommand::new(&[
+            "property",
+            "create",
+            "--name",
+            &name,
+            "--type",
+            &property_type,
+        ]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Creating property {}...", name);
+
+        let result = pcli_commands::create_property(&name, &property_type).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Created property {} ({})", name, property_type),
+                );
+                if let Some(ref mut schema) = self.property_schema {
+                    schema.define(&name, &property_type);
+                }
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Create property failed: {}", e),
+                );
+                self.log_command_error(&e);
+            }
+        }
+    }
+
+    async fn delete_property(&mut self, name: String) {
+        self.last_executed_command = PcliCommand::new(&["property", "delete", "--name", &name]);
+        self.command_history
+            .push(HistoryEntry::new(self.last_executed_command.clone()));
+        self.command_in_progress = true;
+        self.command_started = Some(Instant::now());
+        self.status_message = format!("Deleting property {}...", name);
+
+        let result = pcli_commands::delete_property(&name).await;
+        self.command_in_progress = false;
+        match result {
+            Ok(()) => {
+                self.log_command_success();
+                self.notify(
+                    NotificationLevel::Success,
+                    format!("Deleted property {}", name),
+                );
+                if let Some(ref mut schema) = self.property_schema {
+                    schema.undefine(&name);
+                }
+            }
+            Err(e) => {
+                self.notify(
+                    NotificationLevel::Error,
+                    format!("Delete property failed: {}", e),
+                );
+                self.log_command_error(&e);
+            }
+        }
+    }
+
+    // Show the results of a saved session again, as they were left, without matching again
+    fn reopen_match_session(&mut self, session: MatchSession) {
+        self.match_queue = session
+            .queue
+            .iter()
+            .filter_map(|uuid| {
+                session
+                    .results
+                    .iter()
+                    .find(|(asset, _)| asset.uuid == *uuid)
+                    .cloned()
+            })
+            .collect();
+        self.match_review = match session.source {
+            Some(source) if !session.decisions.is_empty() => {
+                Some(Review::new(source, session.decisions))
+            }
+            _ => None,
+        };
+        self.geometric_match_results = session.results;
+        self.geometric_match_source = Some(session.source_uuid);
+        self.geometric_match_source_folder = session.source_folder;
+        self.geometric_match_min_similarity = session.min_similarity;
+        self.geometric_match_hide_self = session.hide_self;
+        self.geometric_match_hide_same_folder = session.hide_same_folder;
+        self.match_session_started = Some(session.created);
+        self.geometric_match_scroll_position = 0;
+        self.geometric_match_horizontal_scroll = 0;
+        self.modals.push(Modal::GeometricMatch);
+    }
+
+    fn export_review(&mut self) {
+        let Some(ref review) = self.match_review else {
+            return;
+        };
+        let result = export::export_review(&self.export_dir(), review);
+        self.report_export("Review decisions", result);
+    }
+
+    // Open the comparison of the matched asset and the current match, from compare_url
+    async fn open_comparison(&mut self) {
+        let Some(ref template) = self.config.compare_url else {
+            self.notify(
+                NotificationLevel::Warning,
+                "Set compare_url in the config file to open comparisons in the browser",
+            );
+            return;
+        };
+        let Some(ref review) = self.match_review else {
+            return;
+        };
+        let Some(pair) = review.pair() else {
+            return;
+        };
+        let url = template
+            .replace("{uuid}", &review.source.uuid)
+            .replace("{match_uuid}", &pair.asset.uuid);
+        self.open_url(url).await;
+    }
+
+    fn clamp_geometric_match_selection(&mut self) {
+        let visible = self.visible_geometric_matches().len();
+        self.geometric_match_scroll_position = self
+            .geometric_match_scroll_position
+            .min(visible.saturating_sub(1));
+    }
+
+    fn set_min_similarity(&mut self, input: &str) {
+        self.geometric_match_min_similarity = input
+            .trim()
+            .trim_end_matches('%')
+            .trim()
+            .parse::<f64>()
+            .ok();
+        self.clamp_geometric_match_selection();
+        self.save_match_session();
+        let visible = self.visible_geometric_matches().len();
+        match self.geometric_match_min_similarity {
+            Some(minimum) => self.notify(
+                NotificationLevel::Info,
+                format!(
+                    "Showing {} of {} matches at {}% or more",
+                    visible,
+                    self.geometric_match_results.len(),
+                    minimum
+                ),
+            ),
+            None => self.notify(NotificationLevel::Info, "Showing every match"),
+        }
+    }
+
+    // Store geometric match results with similarity scores and tell the hooks about them. Without
+    // `source_folder` the folder of the matched asset is looked up in the listings seen so far.
+    fn set_geometric_match_results(
+        &mut self,
+        asset_uuid: &str,
+        source_folder: Option<String>,
+        pcli_match_results: Vec<pcli_commands::GeometricMatchEntry>,
+    ) {
+        self.geometric_match_scroll_position = 0;
+        self.geometric_match_source = Some(asset_uuid.to_string());
+        self.geometric_match_results = pcli_match_results
+            .into_iter()
+            .map(|match_entry| {
+                let asset = Asset {
+                    uuid: match_entry.asset.uuid,
+                    name: match_entry.asset.name,
+                    folder_uuid: match_entry
+                        .asset
+                        .path
+                        .split('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(), // Extract folder from path
+                    file_type: match_entry.asset.file_type,
+                    size: match_entry.asset.file_size,
+                    path: match_entry.asset.path,
+                    metadata: match_entry.asset.metadata,
+                    is_assembly: match_entry.asset.is_assembly,
+                    processing_status: match_entry.asset.processing_status,
+                };
+                (asset, match_entry.similarity_score)
+            })
+            .collect();
+        self.geometric_match_source_folder = source_folder.or_else(|| {
+            self.known_asset(asset_uuid)
+                .map(|asset| asset.folder_path().to_string())
+        });
+        self.match_queue.clear();
+        self.match_review = None;
+        self.match_session_started = Some(Local::now());
+        self.save_match_session();
+
+        self.notify(
+            NotificationLevel::Success,
+            format!(
+                "Found {} geometric matches",
+                self.geometric_match_results.len()
+            ),
+        );
+        let matches: Vec<serde_json::Value> = self
+            .geometric_match_results
+            .iter()
+            .map(|(asset, similarity)| {
+                serde_json::json!({
+                    "uuid": asset.uuid,
+                    "path": asset.path,
+                    "similarity": similarity,
+                })
+            })
+            .collect();
+        self.hooks.emit(
+            HookEvent::MatchCompleted,
+            serde_json::json!({ "uuid": asset_uuid, "matches": matches }),
+        );
+    }
+    pub async fn handle_mouse_event(&mut self, mouse: crossterm::event::MouseEvent) {
+        let Some(action) = action::mouse_action(mouse) else {
+            return;
+        };
+        // The panes behind an open dialog don't scroll
+        if self.modals.covers_view() && matches!(action, Action::Scroll(..)) {
+            return;
+        }
+        self.update(action).await;
+    }
+
+    // Carry out an action, whichever key or mouse event it came from
+    pub async fn update(&mut self, action: Action) {
+        match action {
+            // Quit, unless background jobs are running and the answer is no
+            Action::Quit => self.request_quit(),
+            // The log pane is skipped while it is hidden, unless it can be zoomed
+            Action::NextPane => {
+                self.active_pane = match self.active_pane {
+                    ActivePane::Folders => ActivePane::Assets,
+                    ActivePane::Assets if self.show_log_pane || self.zoomed => ActivePane::Log,
+                    ActivePane::Assets | ActivePane::Log => ActivePane::Folders,
+                };
+            }
+            Action::PreviousPane => {
+                self.active_pane = match self.active_pane {
+                    ActivePane::Folders if self.show_log_pane || self.zoomed => ActivePane::Log,
+                    ActivePane::Folders => ActivePane::Assets,
+                    ActivePane::Assets => ActivePane::Folders,
+                    ActivePane::Log => ActivePane::Assets,
+                };
+            }
+            Action::ToggleLogPane => {
+                self.show_log_pane = !self.show_log_pane;
+                if !self.show_log_pane && self.active_pane == ActivePane::Log {
+                    self.active_pane = ActivePane::Folders;
+                }
+            }
+            Action::CyclePaneLayout => {
+                self.pane_layout = self.pane_layout.next();
+                let message = match self.pane_layout {
+                    PaneLayout::Auto if self.config.layout.stack_below > 0 => format!(
+                        "Layout: automatic (stacked below {} columns)",
+                        self.config.layout.stack_below
+                    ),
+                    layout => format!("Layout: {}", layout.label()),
+                };
+                self.notify(NotificationLevel::Info, message);
+            }
+            Action::Refresh => self.refresh().await,
+            Action::ToggleOffline => {
+                if offline::is_enabled() {
+                    offline::disable();
+                    self.notify(
+                        NotificationLevel::Info,
+                        "Online: pcli2 is used again; F5 reloads what is on screen",
+                    );
+                } else {
+                    offline::enable();
+                    self.notify(
+                        NotificationLevel::Info,
+                        "Offline: browsing cached folders; changes are disabled",
+                    );
+                }
+            }
+            Action::ToggleZoom => {
+                self.zoomed = !self.zoomed;
+                if !self.zoomed && !self.show_log_pane && self.active_pane == ActivePane::Log {
+                    self.active_pane = ActivePane::Folders;
+                }
+            }
+            // Fuzzy-find assets across the cached folders
+            Action::OpenFinder => {
+                let mut finder = Finder::new();
+                finder.refresh(self.cached_assets());
+                self.finder = Some(finder);
+                self.modals.push(Modal::Finder);
+            }
+            Action::ResizePanes => {
+                self.resize_mode_active = true;
+                self.current_state = AppState::PaneResize;
+                self.status_message =
+                    "Resize mode: Use arrow keys to resize, Enter to confirm, Esc to cancel"
+                        .to_string();
+            }
+            // The TUI resumes when the shell exits
+            Action::OpenShell => self.open_shell(),
+            Action::ShowHelp => self.open_help(),
+            Action::CycleTheme => self.cycle_theme(),
+            // The details of the most recent command failure
+            Action::ShowLastError => match self.last_error.clone() {
+                Some(detail) => self.open_error_modal(detail),
+                None => self.notify(NotificationLevel::Info, "No errors to show"),
+            },
+            // The raw output of the last pcli2 call
+            Action::OpenJsonViewer => self.open_json_viewer(),
+            // Arbitrary pcli2 arguments
+            Action::OpenCommandLine => {
+                self.raw_command_input = Some(TextInput::default());
+                self.modals.push(Modal::CommandLine);
+            }
+            // Retry an operation that timed out, with a longer timeout
+            Action::RetryTimedOut => self.retry_timed_out_operation().await,
+            // The user-defined commands for the selected asset
+            Action::OpenCustomCommands => {
+                if self.config.custom_commands.is_empty() {
+                    self.notify(NotificationLevel::Info, "No custom commands configured");
+                } else {
+                    self.custom_command_menu = Some(0);
+                    self.modals.push(Modal::CustomCommands);
+                }
+            }
+            // The thumbnail preview below the Assets pane
+            Action::TogglePreview => {
+                self.show_preview = !self.show_preview;
+                self.preview_pending = None;
+            }
+            // Upload a local file to the staging folder and geometric-match it
+            Action::UploadFromClipboard => self.upload_from_clipboard(),
+            Action::ImportMetadata => {
+                if self.refuse_denied(Permission::EditMetadata) {
+                    return;
+                }
+                self.open_prompt(Prompt::new(
+                    "CSV file to import metadata from: ",
+                    PromptAction::ImportMetadata,
+                    existing_file,
+                ));
+            }
+            Action::UploadAndMatch => {
+                self.open_prompt(Prompt::new(
+                    "Upload and match file: ",
+                    PromptAction::UploadAndMatch,
+                    existing_file,
+                ));
+            }
+            // The most recent download, in its configured viewer
+            Action::OpenLastDownload => match self.last_download.clone() {
+                Some(path) => self.open_in_viewer(path),
+                None => self.notify(NotificationLevel::Info, "Nothing downloaded yet"),
+            },
+            Action::ShowCommandHistory => {
+                self.current_state = AppState::CommandHistory;
+                self.command_history_index = self.command_history.len().saturating_sub(1);
+            }
+            Action::FilterLog => self.log_filter_editing = true,
+            Action::SearchJson => {
+                if let Some(ref mut viewer) = self.json_viewer {
+                    viewer.search.clear();
+                    viewer.search_editing = true;
+                }
+            }
+            Action::OpenSearch => {
+                self.modals.push(Modal::Search);
+                self.search_input_buffer.clear();
+            }
+            Action::SearchRevisions => self.open_revision_search(),
+            Action::ShowLog => self.current_state = AppState::Log,
+            Action::ShowBasket => self.current_state = AppState::Basket,
+            Action::ShowDeletions => self.current_state = AppState::Deletions,
+            Action::ShowJobs => self.current_state = AppState::Jobs,
+            Action::ShowMatchHistory => self.open_match_history(),
+            Action::ShowPropertySchema => self.open_property_schema().await,
+            Action::ShowViolations => self.open_validation_report(),
+            Action::Undo => self.undo_last().await,
+            Action::ToggleMetrics => self.show_metrics = !self.show_metrics,
+            // The wheel scrolls the pane under the pointer without moving the focus; views
+            // without panes scroll as before
+            Action::Scroll(delta, position) => match self.screen_areas.pane_at(position) {
+                Some(pane) => self.scroll_pane(pane, delta),
+                None if !matches!(self.current_state, AppState::Folders | AppState::Assets) => {
+                    self.scroll_pane(self.active_pane, delta)
+                }
+                None => {}
+            },
+            // The copy button of the log detail modal and the jobs count in the status line
+            Action::Click(position) => {
+                if self.modals.top() == Some(Modal::LogDetail)
+                    && self
+                        .log_detail_copy_button
+                        .is_some_and(|button| button.contains(position))
+                {
+                    self.copy_log_detail_command();
+                } else if !self.modals.covers_view()
+                    && self
+                        .jobs_indicator
+                        .is_some_and(|indicator| indicator.contains(position))
+                {
+                    self.current_state = AppState::Jobs;
+                } else if !self.modals.covers_view()
+                    && let Some(&(_, key)) = self
+                        .hint_targets
+                        .iter()
+                        .find(|(target, _)| target.contains(position))
+                {
+                    // A key of the hint bar acts as if it was pressed
+                    Box::pin(self.handle_key_event(key)).await;
+                } else if !self.modals.covers_view() {
+                    self.click_list_row(position);
+                }
+            }
+            Action::Drag(position) => self.drag_over_assets(position),
+            Action::Release(_) => self.finish_asset_drag(),
+        }
+    }
+
+    // A click on a folder or asset selects it and focuses its pane; on an asset it also starts
+    // a drag that can select more of them
+    fn click_list_row(&mut self, position: ratatui::layout::Position) {
+        let areas = self.screen_areas;
+        if let Some(row) = ScreenAreas::row_at(areas.folder_rows, position)
+            && row < self.folders.len()
+        {
+            self.active_pane = ActivePane::Folders;
+            if row != self.selected_folder_index {
+                self.selected_folder_index = row;
+                self.schedule_selection_load();
+            }
+        } else if let Some(row) = ScreenAreas::row_at(areas.asset_rows, position)
+            && row < self.assets.len()
+        {
+            self.active_pane = ActivePane::Assets;
+            self.selected_asset_index = row;
+            self.asset_drag = Some((row, row));
+        }
+    }
+
+    // Extend a drag to the asset under the pointer; above or below the table it stops at the
+    // first or last row drawn
+    fn drag_over_assets(&mut self, position: ratatui::layout::Position) {
+        let (Some((first, _)), Some(rows)) = (self.asset_drag, self.screen_areas.asset_rows) else {
+            return;
+        };
+        let y = position.y.clamp(rows.y, rows.bottom().saturating_sub(1));
+        let row = ((y - rows.y) as usize).min(self.assets.len().saturating_sub(1));
+        self.asset_drag = Some((first, row));
+        self.selected_asset_index = row;
+    }
+
+    // Letting go after dragging over several assets puts them in the basket for batch work
+    fn finish_asset_drag(&mut self) {
+        let Some((first, last)) = self.asset_drag.take() else {
+            return;
+        };
+        if first == last {
+            return;
+        }
+        let range = first.min(last)..=first.max(last);
+        let count = range.clone().count();
+        let mut added = 0;
+        for asset in self.assets[range].iter().cloned() {
+            if self.basket.add(asset) {
+                added += 1;
+            }
+        }
+        let message = format!(
+            "Added {} of {} assets to the basket ({}); B opens it",
+            added,
+            count,
+            self.basket.len()
+        );
+        self.notify(NotificationLevel::Info, message);
+    }
+
+    // Follow the mouse pointer over the assets table
+    pub fn hover(&mut self, position: ratatui::layout::Position) {
+        self.hovered_asset = ScreenAreas::row_at(self.screen_areas.asset_rows, position)
+            .filter(|&row| row < self.assets.len() && !self.modals.covers_view());
+    }
+
+    // The assets covered by a drag in progress, for highlighting
+    pub fn dragged_assets(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        self.asset_drag
+            .filter(|(first, last)| first != last)
+            .map(|(first, last)| first.min(last)..=first.max(last))
+    }
+
+    // Move the selection of a pane, e.g. with the mouse wheel
+    fn scroll_pane(&mut self, pane: ActivePane, delta: isize) {
+        match pane {
+            ActivePane::Folders => {
+                let selected = self
+                    .selected_folder_index
+                    .saturating_add_signed(delta)
+                    .min(self.folders.len().saturating_sub(1));
+                if selected != self.selected_folder_index {
+                    self.selected_folder_index = selected;
+                    self.schedule_selection_load();
+                }
+            }
+            ActivePane::Assets => {
+                self.selected_asset_index = self
+                    .selected_asset_index
+                    .saturating_add_signed(delta)
+                    .min(self.assets.len().saturating_sub(1));
+            }
+            ActivePane::Log => {
+                self.log_scroll_position = self
+                    .log_scroll_position
+                    .saturating_add_signed(delta)
+                    .min(self.log_entries.len().saturating_sub(1));
+            }
+        }
+    }
 }
@@ -0,0 +1,195 @@
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+use crate::jobs::JobHandle;
+use crate::pcli_commands::{self, PcliAsset};
+
+// Written next to the downloaded files
+const MANIFEST_FILE: &str = "manifest.json";
+
+// An assembly to download together with everything it references
+#[derive(Debug, Clone)]
+pub struct AssemblyDownload {
+    pub uuid: String,
+    pub name: String,
+    pub path: String,       // Remote path of the assembly
+    pub size: Option<u64>,  // Recorded size of the assembly file
+    pub directory: PathBuf, // Local directory the files go into
+}
+
+// One downloaded (or failed) file, as recorded in the manifest
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    uuid: String,
+    name: String,
+    remote_path: String,
+    file: String,           // Relative to the download directory
+    parent: Option<String>, // UUID of the assembly that references it; None for the assembly itself
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    assembly: String,
+    name: String,
+    remote_path: String,
+    downloaded_at: String,
+    files: Vec<ManifestEntry>,
+}
+
+// A file to fetch: the asset and the assembly that references it
+struct Item {
+    uuid: String,
+    name: String,
+    remote_path: String,
+    size: Option<u64>, // Recorded size, checked after the download
+    parent: Option<String>,
+}
+
+// Dependencies keep their remote folder structure below the download directory, so parts with
+// the same name from different folders don't overwrite each other
+fn relative_file(remote_path: &str, name: &str) -> PathBuf {
+    let relative: PathBuf = Path::new(remote_path)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(name)
+    } else {
+        relative
+    }
+}
+
+// Walk the assembly tree breadth first; sub-assemblies are expanded once even when shared
+async fn resolve(job: &JobHandle, assembly: &AssemblyDownload) -> (Vec<Item>, Vec<String>) {
+    let mut items = vec![Item {
+        uuid: assembly.uuid.clone(),
+        name: assembly.name.clone(),
+        remote_path: assembly.path.clone(),
+        size: assembly.size,
+        parent: None,
+    }];
+    let mut errors = Vec::new();
+    let mut seen = HashSet::from([assembly.uuid.clone()]);
+    let mut pending = VecDeque::from([(assembly.uuid.clone(), assembly.name.clone())]);
+
+    while let Some((uuid, name)) = pending.pop_front() {
+        job.progress(
+            0,
+            items.len(),
+            format!("Resolving dependencies of {}", name),
+        );
+        let children: Vec<PcliAsset> =
+            match pcli_commands::in_background(pcli_commands::asset_dependencies(&uuid)).await {
+                Ok(children) => children,
+                Err(e) => {
+                    errors.push(format!("{}: {:#}", name, e));
+                    continue;
+                }
+            };
+        for child in children {
+            if !seen.insert(child.uuid.clone()) {
+                continue;
+            }
+            if child.is_assembly {
+                pending.push_back((child.uuid.clone(), child.name.clone()));
+            }
+            items.push(Item {
+                uuid: child.uuid,
+                name: child.name,
+                remote_path: child.path,
+                size: child.file_size,
+                parent: Some(uuid.clone()),
+            });
+        }
+    }
+    (items, errors)
+}
+
+// Download an assembly and all of its dependencies, then write the manifest
+pub async fn download_with_dependencies(job: JobHandle, assembly: AssemblyDownload) {
+    let (items, resolve_errors) = resolve(&job, &assembly).await;
+    let total = items.len();
+    let mut details: Vec<String> = resolve_errors
+        .iter()
+        .map(|error| format!("✗ dependencies of {}", error))
+        .collect();
+    let mut entries = Vec::with_capacity(total);
+    let mut failed = resolve_errors.len();
+
+    for (done, item) in items.into_iter().enumerate() {
+        job.progress(done, total, item.name.clone());
+        // The assembly itself sits at the top of the directory
+        let file = if item.parent.is_none() {
+            PathBuf::from(&item.name)
+        } else {
+            relative_file(&item.remote_path, &item.name)
+        };
+        let destination = assembly.directory.join(&file);
+
+        let result = match destination.parent() {
+            Some(parent) => tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(anyhow::Error::from),
+            None => Ok(()),
+        };
+        let result = match result {
+            Ok(()) => pcli_commands::in_background(pcli_commands::download_asset_to(
+                &item.uuid,
+                &destination,
+            ))
+            .await
+            .and_then(|()| pcli_commands::verify_download(&destination, item.size)),
+            Err(e) => Err(e),
+        };
+
+        let error = result.err().map(|e| format!("{:#}", e));
+        match error {
+            Some(ref error) => {
+                failed += 1;
+                details.push(format!("✗ {}: {}", file.display(), error));
+            }
+            None => details.push(format!("✓ {}", file.display())),
+        }
+        entries.push(ManifestEntry {
+            uuid: item.uuid,
+            name: item.name,
+            remote_path: item.remote_path,
+            file: file.display().to_string(),
+            parent: item.parent,
+            error,
+        });
+    }
+
+    let downloaded = entries.iter().filter(|entry| entry.error.is_none()).count();
+    let manifest = Manifest {
+        assembly: assembly.uuid.clone(),
+        name: assembly.name.clone(),
+        remote_path: assembly.path.clone(),
+        downloaded_at: chrono::Local::now().to_rfc3339(),
+        files: entries,
+    };
+    let manifest_path = assembly.directory.join(MANIFEST_FILE);
+    let written = match serde_json::to_string_pretty(&manifest) {
+        Ok(json) => tokio::fs::write(&manifest_path, json)
+            .await
+            .map_err(anyhow::Error::from),
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = written {
+        failed += 1;
+        details.push(format!("✗ {}: {:#}", MANIFEST_FILE, e));
+    }
+
+    let summary = format!(
+        "Downloaded {} of {} files to {}",
+        downloaded,
+        total,
+        assembly.directory.display()
+    );
+    job.finish(failed == 0, summary, details.join("\n"));
+}
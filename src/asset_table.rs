@@ -0,0 +1,344 @@
+use crate::app::App;
+use crate::geometry::{GeometryState, GeometryText};
+use crate::processing::Processing;
+use crate::text::{FitColumn, column_width, fit_columns, truncate_to_width};
+
+// Marks an asset with a local note
+pub const NOTE_MARK: &str = "✎";
+
+// Header and mark of the assembly column of the assets table
+pub const ASSEMBLY_MARK: &str = "⚙";
+
+// Narrowest the asset name, and other columns, are ellipsized to when the assets table is
+// wider than its pane
+const MIN_NAME_WIDTH: usize = 12;
+const MIN_COLUMN_WIDTH: usize = 6;
+
+// A metadata cell as drawn
+#[derive(Debug)]
+pub struct MetadataCell {
+    pub text: String, // Ellipsized to its column; "∅" for a missing value a rule requires
+    pub numeric: bool, // Right-aligned like other numbers
+    pub broken: bool, // Breaks a metadata rule
+}
+
+// An asset as a row of the table, its text already ellipsized to the columns
+#[derive(Debug)]
+pub struct AssetRow {
+    pub name: String,
+    pub path: String,
+    pub note: bool,
+    pub processing: Option<(Processing, String)>, // Its status and label, when that column is on
+    pub tags: Vec<String>,
+    pub geometry: Option<Vec<String>>, // Size, volume and area, when those columns are on
+    pub metadata: Vec<MetadataCell>,
+    pub breaks_rules: bool,
+}
+
+// The assets table laid out for a pane width: which columns it has, how wide they are and
+// the text of its cells. It's kept between frames and only built again when the assets,
+// their notes, tags or geometry change (App::table_version), the pane is resized or a
+// metadata cell is typed in, which widens its column.
+#[derive(Debug)]
+pub struct AssetTable {
+    version: u64,
+    width: u16,
+    typed: Option<(usize, usize, String)>, // Row, column and text of the cell being typed in
+    pub metadata_keys: Vec<String>,
+    pub headers: Vec<String>,
+    pub fitted: Vec<Option<usize>>, // Width of each column with its padding; None when left out
+    pub notes_column: bool,
+    pub processing_column: bool,
+    pub assembly_column: bool,
+    pub tags_column: bool,
+    pub hidden: usize,   // Columns left out for want of room
+    pub breaking: usize, // Assets breaking a metadata rule
+    pub rows: Vec<AssetRow>,
+}
+
+// The cell being typed in, if any: its row, its column and the text so far
+fn typed_cell(app: &App) -> Option<(usize, usize, &str)> {
+    let editor = app.cell_editor.as_ref()?;
+    let input = editor.input.as_ref()?;
+    Some((app.selected_asset_index, editor.column, input.as_str()))
+}
+
+impl AssetTable {
+    // Whether the table still shows the app's assets as they are, at this width
+    pub fn is_current(&self, app: &App, width: u16) -> bool {
+        let typed = self
+            .typed
+            .as_ref()
+            .map(|(row, column, text)| (*row, *column, text.as_str()));
+        self.version == app.table_version && self.width == width && typed == typed_cell(app)
+    }
+
+    pub fn build(app: &App, width: u16) -> Self {
+        let assets = &app.assets;
+
+        // Metadata keys of the assets, sorted
+        let metadata_keys = app.metadata_columns();
+
+        // Size, Volume and Area columns, when enabled, with the geometry of each asset as text
+        let geometry: Option<Vec<[String; 3]>> = app.config.geometry.columns.then(|| {
+            assets
+                .iter()
+                .map(|asset| match app.geometry.get(&asset.uuid) {
+                    Some(GeometryState::Ready(geometry)) => {
+                        let text = GeometryText::new(geometry, app.geometry_unit);
+                        [text.size, text.volume, text.surface_area]
+                    }
+                    Some(GeometryState::Loading) | None => {
+                        ["…".to_string(), "…".to_string(), "…".to_string()]
+                    }
+                    Some(GeometryState::Unavailable(_)) => Default::default(),
+                })
+                .collect()
+        });
+
+        // A column marking the assets with a local note, when any of them has one
+        let notes: Vec<bool> = assets
+            .iter()
+            .map(|asset| app.notes.get(&asset.uuid).is_some())
+            .collect();
+        let notes_column = notes.iter().any(|&note| note);
+
+        // The processing status of each asset, in a column when any asset is not ready yet
+        let processing: Vec<Option<Processing>> = assets
+            .iter()
+            .map(|asset| known_processing(&asset.processing_status))
+            .collect();
+        let processing_column = app.config.columns.processing
+            && processing.iter().any(|status| {
+                status
+                    .as_ref()
+                    .is_some_and(|status| *status != Processing::Ready)
+            });
+
+        // A column marking the assemblies, when any asset is one
+        let assembly_column =
+            app.config.columns.assembly && assets.iter().any(|asset| asset.is_assembly);
+
+        // The tags of each asset, in a column of their own when any asset has tags
+        let tags: Vec<Vec<String>> = assets.iter().map(|asset| app.tags.of(asset)).collect();
+        let tags_column = tags.iter().any(|tags| !tags.is_empty());
+        let tags_width = tags
+            .iter()
+            .map(|tags| tags.iter().map(|tag| column_width(tag) + 3).sum::<usize>())
+            .fold(column_width("Tags"), std::cmp::max);
+
+        // The metadata values of each asset, by key, as text
+        let mut metadata_values: Vec<Vec<String>> = assets
+            .iter()
+            .map(|asset| {
+                metadata_keys
+                    .iter()
+                    .map(|key| asset.metadata_value(key).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        // The column of the cell being typed in is as wide as the typed text and the cursor
+        let typed = typed_cell(app);
+        if let Some((row, column, text)) = typed
+            && let column = column.min(metadata_keys.len().saturating_sub(1))
+            && let Some(cell) = metadata_values
+                .get_mut(row)
+                .and_then(|values| values.get_mut(column))
+        {
+            *cell = format!("{} ", text);
+        }
+
+        // Define headers for the table
+        let mut headers = vec!["", "Name", "Path"]; // Icon, Name, Path (removed Type column)
+        if notes_column {
+            headers.insert(1, NOTE_MARK);
+        }
+        if processing_column {
+            headers.push("Status");
+        }
+        if assembly_column {
+            headers.push(ASSEMBLY_MARK);
+        }
+        if tags_column {
+            headers.push("Tags");
+        }
+        if geometry.is_some() {
+            headers.extend(["Size", "Volume", "Area"]);
+        }
+        for key in &metadata_keys {
+            headers.push(key.as_str());
+        }
+
+        // What each column needs (content plus one column of padding) and how readily it gives
+        // way when the pane is too narrow: metadata columns first, the sparsest of them before
+        // the rest and only they are left out; then the path, tags, geometry and finally the name
+        let fixed = assets.len() + 1; // Above any metadata column's count of values
+        let column = |width: usize, min: usize, priority: usize| FitColumn {
+            width: width + 1,
+            min: min.min(width) + 1,
+            priority,
+            droppable: false,
+        };
+        let max_width = |values: &mut dyn Iterator<Item = &str>, header: &str| {
+            values
+                .map(column_width)
+                .fold(column_width(header), std::cmp::max)
+        };
+        let mut columns = vec![column(1, 1, fixed + 4)]; // Icon
+        if notes_column {
+            columns.push(column(1, 1, fixed + 4));
+        }
+        columns.push(column(
+            max_width(&mut assets.iter().map(|asset| asset.name.as_str()), "Name"),
+            MIN_NAME_WIDTH,
+            fixed + 3,
+        ));
+        columns.push(column(
+            max_width(
+                &mut assets.iter().map(|asset| asset.folder_uuid.as_str()),
+                "Path",
+            ),
+            MIN_COLUMN_WIDTH,
+            fixed,
+        ));
+        if processing_column {
+            let width = processing
+                .iter()
+                .flatten()
+                .map(|status| column_width(&processing_label(status)))
+                .fold(column_width("Status"), std::cmp::max);
+            columns.push(column(width, MIN_COLUMN_WIDTH, fixed + 2));
+        }
+        if assembly_column {
+            columns.push(column(1, 1, fixed + 4));
+        }
+        if tags_column {
+            columns.push(column(tags_width, MIN_COLUMN_WIDTH, fixed + 1));
+        }
+        // Geometry columns are as wide as their widest value or header, and not shortened
+        if let Some(ref geometry) = geometry {
+            for (i, header) in ["Size", "Volume", "Area"].into_iter().enumerate() {
+                let values = &mut geometry.iter().map(|values| values[i].as_str());
+                let width = max_width(values, header);
+                columns.push(column(width, width, fixed + 2));
+            }
+        }
+        for (i, key) in metadata_keys.iter().enumerate() {
+            let values = &mut metadata_values.iter().map(|values| values[i].as_str());
+            let width = max_width(values, key);
+            let filled = metadata_values
+                .iter()
+                .filter(|values| !values[i].is_empty())
+                .count();
+            columns.push(FitColumn {
+                droppable: true,
+                ..column(width, MIN_COLUMN_WIDTH, filled)
+            });
+        }
+        let fitted = fit_columns(&columns, width.saturating_sub(2) as usize, 1);
+        // Room for the content of each column, less the padding
+        let room: Vec<usize> = fitted
+            .iter()
+            .map(|width| width.map_or(0, |width| width.saturating_sub(1)))
+            .collect();
+        let hidden = fitted.iter().filter(|width| width.is_none()).count();
+
+        let name_column = 1 + notes_column as usize;
+        let status_column = name_column + 2;
+        let geometry_column = status_column
+            + processing_column as usize
+            + assembly_column as usize
+            + tags_column as usize;
+        let first_metadata_column = headers.len() - metadata_keys.len();
+        let headers: Vec<String> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| truncate_to_width(header, room[i]).into_owned())
+            .collect();
+
+        // The metadata rules each asset breaks, by key; its name and those cells are marked
+        let mut geometry = geometry.map(Vec::into_iter);
+        let rows = assets
+            .iter()
+            .zip(metadata_values)
+            .zip(processing)
+            .zip(notes.into_iter().zip(tags))
+            .map(|(((asset, values), processing), (note, tags))| {
+                let broken = app.metadata_rules.broken_keys(asset);
+                let metadata = values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, value)| {
+                        let broken = broken.contains(&metadata_keys[j]);
+                        let shown = if broken && value.is_empty() {
+                            "∅"
+                        } else {
+                            value.as_str()
+                        };
+                        let room = room[first_metadata_column + j];
+                        MetadataCell {
+                            text: truncate_to_width(shown, room).into_owned(),
+                            numeric: !broken && value.parse::<f64>().is_ok(),
+                            broken,
+                        }
+                    })
+                    .collect();
+                let geometry = geometry.as_mut().and_then(Iterator::next).map(|values| {
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(j, value)| {
+                            truncate_to_width(value, room[geometry_column + j]).into_owned()
+                        })
+                        .collect()
+                });
+                AssetRow {
+                    name: truncate_to_width(&asset.name, room[name_column]).into_owned(),
+                    path: truncate_to_width(&asset.folder_uuid, room[name_column + 1]).into_owned(),
+                    note,
+                    processing: processing.filter(|_| processing_column).map(|status| {
+                        let label = processing_label(&status);
+                        let label = truncate_to_width(&label, room[status_column]).into_owned();
+                        (status, label)
+                    }),
+                    tags,
+                    geometry,
+                    metadata,
+                    breaks_rules: !broken.is_empty(),
+                }
+            })
+            .collect::<Vec<AssetRow>>();
+
+        Self {
+            version: app.table_version,
+            width,
+            typed: typed.map(|(row, column, text)| (row, column, text.to_string())),
+            metadata_keys,
+            headers,
+            fitted,
+            notes_column,
+            processing_column,
+            assembly_column,
+            tags_column,
+            hidden,
+            breaking: rows.iter().filter(|row| row.breaks_rules).count(),
+            rows,
+        }
+    }
+}
+
+// Where an asset's processing stands; None when pcli2 did not say
+fn known_processing(status: &str) -> Option<Processing> {
+    let status = status.trim();
+    (!status.is_empty() && !status.eq_ignore_ascii_case("unknown"))
+        .then(|| Processing::from_status(status))
+}
+
+fn processing_label(processing: &Processing) -> String {
+    match processing {
+        Processing::Ready => "✓ ready".to_string(),
+        Processing::Running(status) => format!("… {}", status.to_lowercase()),
+        Processing::Failed(status) => format!("✗ {}", status.to_lowercase()),
+    }
+}
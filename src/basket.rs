@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use crate::app::Asset;
+use crate::jobs::JobHandle;
+use crate::pcli_commands;
+
+// Assets collected from any folder for batch operations; kept while navigating
+#[derive(Debug, Default)]
+pub struct Basket {
+    assets: Vec<Asset>,
+    pub selected: usize,
+}
+
+impl Basket {
+    // Add the asset, or take it out again; returns whether it is in the basket now
+    pub fn toggle(&mut self, asset: Asset) -> bool {
+        if let Some(index) = self.assets.iter().position(|a| a.uuid == asset.uuid) {
+            self.assets.remove(index);
+            self.clamp_selection();
+            false
+        } else {
+            self.assets.push(asset);
+            true
+        }
+    }
+
+    // Add the asset unless it is in the basket already; returns whether it was added
+    pub fn add(&mut self, asset: Asset) -> bool {
+        if self.contains(&asset.uuid) {
+            return false;
+        }
+        self.assets.push(asset);
+        true
+    }
+
+    // Take the asset out if it is in the basket
+    pub fn remove(&mut self, uuid: &str) {
+        self.assets.retain(|asset| asset.uuid != uuid);
+        self.clamp_selection();
+    }
+
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.assets.iter().any(|asset| asset.uuid == uuid)
+    }
+
+    pub fn assets(&self) -> &[Asset] {
+        &self.assets
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    pub fn remove_selected(&mut self) -> Option<Asset> {
+        if self.selected >= self.assets.len() {
+            return None;
+        }
+        let asset = self.assets.remove(self.selected);
+        self.clamp_selection();
+        Some(asset)
+    }
+
+    pub fn clear(&mut self) {
+        self.assets.clear();
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self.selected.saturating_add_signed(delta);
+        self.clamp_selection();
+    }
+
+    // The assets are wherever they were moved to now
+    pub fn moved_to(&mut self, folder: &str) {
+        for asset in &mut self.assets {
+            asset.path = match folder {
+                "" => asset.name.clone(),
+                folder => format!("{}/{}", folder, asset.name),
+            };
+            asset.folder_uuid = folder.to_string();
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        self.selected = self.selected.min(self.assets.len().saturating_sub(1));
+    }
+}
+
+// Run `operation` on each item in turn, reporting progress, and sum up how it went
+async fn for_each<T, F, Fut>(job: JobHandle, verb: &str, items: Vec<(Asset, T)>, operation: F)
+where
+    F: Fn(Asset, T) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let total = items.len();
+    let mut details = Vec::with_capacity(total);
+    let mut failed = 0;
+    for (done, (asset, item)) in items.into_iter().enumerate() {
+        job.progress(done, total, asset.name.clone());
+        let path = asset.path.clone();
+        match pcli_commands::in_background(operation(asset, item)).await {
+            Ok(()) => details.push(format!("✓ {}", path)),
+            Err(e) => {
+                failed += 1;
+                details.push(format!("✗ {}: {:#}", path, e));
+            }
+        }
+    }
+    let summary = format!("{} {} of {} basket assets", verb, total - failed, total);
+    job.finish(failed == 0, summary, details.join("\n"));
+}
+
+// Download each asset to its file, checking the size of each
+pub async fn download(job: JobHandle, assets: Vec<(Asset, PathBuf)>) {
+    for_each(job, "Downloaded", assets, |asset, file| async move {
+        if let Some(directory) = file.parent() {
+            tokio::fs::create_dir_all(directory).await?;
+        }
+        pcli_commands::download_asset_to(&asset.uuid, &file).await?;
+        pcli_commands::verify_download(&file, asset.size)
+    })
+    .await;
+}
+
+// Delete each staged asset, reporting each one deleted as it goes
+pub async fn delete(job: JobHandle, assets: Vec<Asset>) {
+    let total = assets.len();
+    let mut details = Vec::with_capacity(total);
+    let mut failed = 0;
+    for (done, asset) in assets.into_iter().enumerate() {
+        job.progress(done, total, asset.name.clone());
+        match pcli_commands::in_background(pcli_commands::delete_asset(&asset.uuid)).await {
+            Ok(()) => {
+                details.push(format!("✓ {}", asset.path));
+                job.deleted(asset);
+            }
+            Err(e) => {
+                failed += 1;
+                details.push(format!("✗ {}: {:#}", asset.path, e));
+            }
+        }
+    }
+    let summary = format!("Deleted {} of {} staged assets", total - failed, total);
+    job.finish(failed == 0, summary, details.join("\n"));
+}
+
+pub async fn move_to(job: JobHandle, assets: Vec<Asset>, folder: String) {
+    let destination = if folder.is_empty() {
+        "/".to_string()
+    } else {
+        folder
+    };
+    let assets = assets.into_iter().map(|asset| (asset, ())).collect();
+    for_each(job, "Moved", assets, |asset, ()| {
+        let destination = destination.clone();
+        async move { pcli_commands::move_asset(&asset.uuid, &destination).await }
+    })
+    .await;
+}
+
+pub async fn set_metadata(job: JobHandle, assets: Vec<Asset>, name: String, value: String) {
+    let assets = assets.into_iter().map(|asset| (asset, ())).collect();
+    for_each(job, "Updated the metadata of", assets, |asset, ()| {
+        let (name, value) = (name.clone(), value.clone());
+        async move { pcli_commands::set_metadata(&asset.uuid, &name, &value).await }
+    })
+    .await;
+}
@@ -0,0 +1,22 @@
+use crate::text_input::TextInput;
+
+// Spreadsheet-like editing of the metadata cells of the assets table: the selected asset is
+// the row, `column` counts the metadata columns, and while `input` is open the cell is typed in
+#[derive(Debug, Default)]
+pub struct CellEditor {
+    pub column: usize,
+    pub input: Option<TextInput>,
+}
+
+impl CellEditor {
+    pub fn move_column(&mut self, delta: isize, columns: usize) {
+        self.column = self
+            .column
+            .saturating_add_signed(delta)
+            .min(columns.saturating_sub(1));
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+}
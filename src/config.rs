@@ -0,0 +1,554 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::jobs::JobKind;
+use crate::permissions::Permission;
+
+// User configuration, loaded from $XDG_CONFIG_HOME/pcli2-tui/config.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Name of the theme to start with
+    pub theme: Option<String>,
+    // Custom themes keyed by name
+    pub themes: HashMap<String, ThemeConfig>,
+    // auto, truecolor, 256, 16 or none
+    pub color_mode: Option<String>,
+    // Retry policy for transient pcli2 failures
+    pub retry: RetryConfig,
+    // How long a pcli2 command may run before it is killed
+    pub timeouts: TimeoutConfig,
+    // File logging of pcli2 invocations
+    pub logging: LoggingConfig,
+    // Where log and history exports are written
+    pub export_dir: Option<String>,
+    // Background loading of neighboring folders
+    pub prefetch: PrefetchConfig,
+    // How many pcli2 processes may run at once
+    pub concurrency: ConcurrencyConfig,
+    // External programs for opening downloaded files
+    pub viewers: ViewerConfig,
+    // User-defined commands on the selected asset
+    pub custom_commands: Vec<CustomCommand>,
+    // Scripts run on lifecycle events, keyed by event name
+    pub hooks: HashMap<String, Vec<String>>,
+    // Thumbnail preview of the selected asset
+    pub preview: PreviewConfig,
+    // Bounding box, volume and surface area display
+    pub geometry: GeometryConfig,
+    // Local directories synced with folders, keyed by folder path
+    pub sync: HashMap<String, String>,
+    // Where upload-and-match puts files; the current folder if unset
+    pub staging_folder: Option<String>,
+    // Waiting for uploaded assets to be processed
+    pub processing: ProcessingConfig,
+    // Named queries, saved from the search dialog
+    pub saved_searches: Vec<SavedSearch>,
+    // Web page of an asset; {uuid} and {path} are filled in
+    pub asset_url: Option<String>,
+    // Name of the tenant pcli2 uses, shown in the status line
+    pub tenant: Option<String>,
+    // Web page comparing two assets; {uuid} and {match_uuid} are filled in
+    pub compare_url: Option<String>,
+    // Where downloaded assets are written
+    pub downloads: DownloadConfig,
+    // Where asset tags are kept
+    pub tags: TagConfig,
+    // Side-by-side or stacked folder and asset panes
+    pub layout: LayoutConfig,
+    // How often the screen is redrawn, in use and idle
+    pub tick: TickConfig,
+    // Optional columns of the Assets pane
+    pub columns: AssetColumnsConfig,
+    // Key metadata of the selected asset under the Assets pane
+    pub metadata_strip: MetadataStripConfig,
+    // Expected metadata properties and value formats
+    pub metadata_rules: Vec<MetadataRule>,
+    // Bell and desktop notification when long jobs finish
+    pub alerts: AlertConfig,
+    // Tenants to pick from with --profile or at startup
+    pub profiles: BTreeMap<String, Profile>,
+    // Appended to every pcli2 invocation, e.g. --tenant
+    pub pcli2_args: Vec<String>,
+    // Changes the user's role doesn't allow, e.g. delete
+    pub deny: Vec<Permission>,
+    #[serde(skip)]
+    // Name of the profile picked for this session
+    pub active_profile: Option<String>,
+}
+
+// One of several tenants, e.g. a consultant's customers; the rest of the config is shared
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    // Shown in the status line next to the profile name
+    pub tenant: Option<String>,
+    // pcli2 executable to run; "pcli2" on the PATH if unset
+    pub pcli2: Option<String>,
+    // Environment of pcli2, e.g. credentials or its config directory
+    pub env: HashMap<String, String>,
+    // Folder opened at startup; the top level if unset
+    pub default_folder: Option<String>,
+    // Appended to every pcli2 invocation, after those of the config
+    pub pcli2_args: Vec<String>,
+    // Denied on this tenant besides the config's deny
+    pub deny: Vec<Permission>,
+}
+
+// Alerts for jobs that ran long enough to have been left alone, by the kind of job; none by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub after_secs: u64,       // Jobs that finish sooner don't alert
+    pub bell: Vec<JobKind>,    // Kinds of jobs that ring the terminal bell
+    pub desktop: Vec<JobKind>, // Kinds of jobs that send a desktop notification
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            after_secs: 30,
+            bell: Vec::new(),
+            desktop: Vec::new(),
+        }
+    }
+}
+
+// Columns of the Assets pane that show what is otherwise only in the details of each asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetColumnsConfig {
+    pub processing: bool, // Processing status, when any listed asset is not ready
+    pub assembly: bool,   // Assembly mark, when any listed asset is an assembly
+}
+
+impl Default for AssetColumnsConfig {
+    fn default() -> Self {
+        Self {
+            processing: true,
+            assembly: true,
+        }
+    }
+}
+
+// A strip under the Assets pane with a few metadata values of the selected asset (or the one
+// under the mouse pointer); "size" is the file size unless the metadata has a size of its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataStripConfig {
+    pub lines: u16,        // 0 hides the strip; 1 or 3 rows of values
+    pub keys: Vec<String>, // Metadata keys shown, in order; matched ignoring case
+}
+
+impl Default for MetadataStripConfig {
+    fn default() -> Self {
+        Self {
+            lines: 0,
+            keys: ["Part Number", "Material", "Revision", "size"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+// The folder and asset panes are stacked instead of side by side in narrow terminals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    // Terminal width (columns) below which the panes are stacked; 0 never stacks
+    pub stack_below: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self { stack_below: 100 }
+    }
+}
+
+// The screen is redrawn every tick while nothing else happens; input is handled right away
+// regardless, so a slower tick only delays toasts, progress and the like
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TickConfig {
+    pub active_ms: u64,       // Tick while the TUI is in use
+    pub idle_ms: u64,         // Tick while it is idle or the terminal is not focused
+    pub idle_after_secs: u64, // Without input for this long counts as idle; 0 never idles
+}
+
+impl Default for TickConfig {
+    fn default() -> Self {
+        Self {
+            active_ms: 16,
+            idle_ms: 250,
+            idle_after_secs: 10,
+        }
+    }
+}
+
+// Asset tags are kept locally unless `metadata_key` names a metadata property to keep them in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagConfig {
+    pub metadata_key: Option<String>, // e.g. "tags"; the value lists the tags separated by commas
+}
+
+// Download directories: a default plus overrides for the assets of particular folders
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadConfig {
+    pub directory: Option<String>,        // The current directory if unset
+    pub folders: HashMap<String, String>, // Directories keyed by folder path; subfolders included
+}
+
+impl DownloadConfig {
+    // Directory for the assets of a folder: the override of the folder or its closest configured
+    // ancestor, then the default directory
+    pub fn directory_for(&self, folder_path: &str) -> PathBuf {
+        let folder_path = folder_path.trim_matches('/');
+        let directory = self
+            .folders
+            .iter()
+            .filter(|(folder, _)| {
+                let folder = folder.trim_matches('/');
+                folder_path == folder
+                    || folder_path
+                        .strip_prefix(folder)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(folder, _)| folder.trim_matches('/').len())
+            .map(|(_, directory)| directory)
+            .or(self.directory.as_ref());
+        match directory {
+            Some(directory) => expand_home(directory),
+            None => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+}
+
+// How the processing status of an uploaded asset is polled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessingConfig {
+    pub poll_secs: u64,    // Time between two status checks
+    pub timeout_secs: u64, // Give up waiting after this long
+}
+
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        Self {
+            poll_secs: 5,
+            timeout_secs: 900,
+        }
+    }
+}
+
+// How asset geometry is displayed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeometryConfig {
+    pub units: String, // mm or inch
+    pub columns: bool, // Add Size, Volume and Area columns to the Assets pane
+}
+
+impl Default for GeometryConfig {
+    fn default() -> Self {
+        Self {
+            units: "mm".to_string(),
+            columns: false,
+        }
+    }
+}
+
+// Thumbnail preview below the Assets pane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    pub enabled: bool,    // Show the preview at startup; 'p' toggles it
+    pub protocol: String, // auto, kitty, iterm2, sixel or blocks
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: "auto".to_string(),
+        }
+    }
+}
+
+// An expected metadata property: assets without a value break the rule when it is required,
+// and a value breaks it when it doesn't match the whole pattern (a regex)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataRule {
+    pub key: String,
+    pub required: bool,
+    pub pattern: Option<String>,
+}
+
+// A user-defined command run on the selected asset, e.g. "./my-script.sh {uuid} {path}"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomCommand {
+    pub name: String,
+    pub key: Option<char>, // Runs the command from the Assets pane
+    pub command: String,   // {uuid}, {name}, {path}, {folder} and {download_path} are filled in
+}
+
+// Programs that open downloaded files, keyed by file extension
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewerConfig {
+    // Command lines by extension ("*" for any); "{}" is the file
+    pub commands: HashMap<String, String>,
+    pub auto_open: bool, // Open each download as soon as it finishes
+}
+
+impl ViewerConfig {
+    // Viewer command line for a file, falling back to the "*" entry
+    pub fn command_for(&self, path: &std::path::Path) -> Option<&str> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        extension
+            .and_then(|extension| self.commands.get(&extension))
+            .or_else(|| self.commands.get("*"))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    pub max_commands: usize, // pcli2 processes running at once; background jobs get one fewer
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self { max_commands: 4 }
+    }
+}
+
+// Background loading of the folders around the selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrefetchConfig {
+    // Folders below the selection (and subfolders of it) to prefetch; 0 disables
+    pub folders: usize,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self { folders: 3 }
+    }
+}
+
+// Structured log file written under $XDG_STATE_HOME/pcli2-tui
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: String,       // off, error, warn, info, debug or trace
+    pub max_file_bytes: u64, // Rotate the log file before it grows past this size
+    pub max_files: usize,    // Number of rotated files to keep besides the current one
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            max_file_bytes: 5 * 1024 * 1024,
+            max_files: 3,
+        }
+    }
+}
+
+// How often and how patiently to retry pcli2 commands that fail with network-ish errors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub attempts: u32, // Total attempts including the first one; 1 disables retries
+    pub initial_backoff_ms: u64, // Delay before the first retry, doubled for each further retry
+    pub max_backoff_ms: u64, // Upper bound for the delay between attempts
+    pub rate_limit_secs: u64, // Pause once the API's rate limit is hit, unless it says how long
+    pub max_rate_limit_secs: u64, // Upper bound for that pause, doubled for each hit in a row
+    pub rate_limit_attempts: u32, // Attempts of a command that keeps being rate limited
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8000,
+            rate_limit_secs: 30,
+            max_rate_limit_secs: 300,
+            rate_limit_attempts: 5,
+        }
+    }
+}
+
+// Per-command timeouts; commands are keyed by their first two arguments, e.g. "asset download"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    pub default_secs: u64,
+    pub commands: HashMap<String, u64>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            default_secs: 60,
+            // Transfers move whole files and legitimately take longer than listings
+            commands: HashMap::from([
+                ("asset download".to_string(), 600),
+                ("asset create".to_string(), 600),
+            ]),
+        }
+    }
+}
+
+impl TimeoutConfig {
+    pub fn for_command(&self, args: &[&str]) -> std::time::Duration {
+        let key = args.iter().take(2).copied().collect::<Vec<_>>().join(" ");
+        let secs = self
+            .commands
+            .get(&key)
+            .copied()
+            .unwrap_or(self.default_secs);
+        std::time::Duration::from_secs(secs.max(1))
+    }
+}
+
+impl RetryConfig {
+    // Delay before the given retry (1 = first retry)
+    pub fn backoff(&self, retry: u32) -> std::time::Duration {
+        let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+        let delay = self.initial_backoff_ms.saturating_mul(factor);
+        std::time::Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+}
+
+// A custom theme: a built-in base palette plus individual color overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub colors: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.json"))
+    }
+
+    // Load the config file, falling back to defaults when it doesn't exist
+    pub fn load() -> Result<Config> {
+        let Some(path) = Self::path() else {
+            return Ok(Config::default());
+        };
+
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let config = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+        Ok(config)
+    }
+
+    // The profile picked for this session, if any
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profiles.get(self.active_profile.as_deref()?)
+    }
+
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        if !self.profiles.contains_key(name) {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                bail!("Unknown profile {}: the config file has no profiles", name);
+            }
+            bail!(
+                "Unknown profile {}; the config file has {}",
+                name,
+                known.join(", ")
+            );
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    // Write the config file, e.g. after a search was saved from the TUI
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            anyhow::bail!("No config directory (HOME is not set)");
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write config file {}", path.display()))?;
+        Ok(())
+    }
+}
+
+// Directory holding the config file, following the XDG base directory convention
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(base.join("pcli2-tui"))
+}
+
+// Profile whose data (folder cache, notes, tags, match history) is kept apart from the others'
+static DATA_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_data_profile(profile: Option<&str>) {
+    if let Ok(mut current) = DATA_PROFILE.lock() {
+        *current = profile.map(|name| name.replace(['/', '\\'], "_"));
+    }
+}
+
+// Directory holding data the app keeps between sessions, following the XDG base directory
+// convention; each profile has its own below it
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+
+    let dir = base.join("pcli2-tui");
+    match DATA_PROFILE.lock().ok().and_then(|profile| profile.clone()) {
+        Some(profile) => Some(dir.join("profiles").join(profile)),
+        None => Some(dir),
+    }
+}
+
+// A path typed or configured by the user, with a leading "~" meaning the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,348 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::app::Asset;
+use crate::jobs::JobHandle;
+use crate::pcli_commands;
+
+// One metadata value a row sets; `old` is None when the asset has no value yet, or isn't
+// cached so its values aren't known
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub name: String,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+// A data row of the CSV file and what it does to its asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRow {
+    pub line: usize,    // Line number in the file, for the results
+    pub target: String, // The uuid or path as written in the file
+    pub uuid: Option<String>,
+    pub asset: Option<Asset>, // When it is in the cached listings
+    pub changes: Vec<Change>,
+    pub problem: Option<String>, // Why the row is skipped
+}
+
+impl ImportRow {
+    pub fn label(&self) -> &str {
+        self.asset
+            .as_ref()
+            .map_or(self.target.as_str(), |asset| asset.path.as_str())
+    }
+}
+
+// A CSV file of metadata values: a "uuid" or "path" column naming the asset and one column
+// per metadata property. Empty cells leave the property alone; values equal to the cached
+// ones are left out of the plan.
+#[derive(Debug)]
+pub struct ImportPlan {
+    pub file: PathBuf,
+    pub rows: Vec<ImportRow>,
+    pub selected: usize,
+}
+
+impl ImportPlan {
+    pub fn load<'a>(file: &Path, known: impl IntoIterator<Item = &'a Asset>) -> Result<Self> {
+        let text = std::fs::read_to_string(file)?;
+        let mut records = parse(&text)?.into_iter();
+        let Some((_, header)) = records.next() else {
+            bail!("The file is empty");
+        };
+        let Some(key_column) = header
+            .iter()
+            .position(|name| matches!(name.trim().to_lowercase().as_str(), "uuid" | "path"))
+        else {
+            bail!("No uuid or path column in the header line");
+        };
+        let by_uuid = header[key_column].trim().eq_ignore_ascii_case("uuid");
+
+        let mut assets: HashMap<&str, &Asset> = HashMap::new();
+        for asset in known {
+            assets.insert(if by_uuid { &asset.uuid } else { &asset.path }, asset);
+        }
+
+        let mut rows = Vec::new();
+        for (line, record) in records {
+            if record.iter().all(|cell| cell.trim().is_empty()) {
+                continue;
+            }
+            let target = record.get(key_column).map_or("", |cell| cell.trim());
+            let target = if by_uuid {
+                target
+            } else {
+                target.trim_start_matches('/')
+            };
+            let asset = assets.get(target).map(|asset| (*asset).clone());
+            let uuid = match asset {
+                Some(ref asset) => Some(asset.uuid.clone()),
+                None if by_uuid && !target.is_empty() => Some(target.to_string()),
+                None => None,
+            };
+            let mut changes = Vec::new();
+            for (column, value) in record.iter().enumerate() {
+                let (Some(name), value) = (header.get(column), value.trim()) else {
+                    continue;
+                };
+                let name = name.trim();
+                if column == key_column || name.is_empty() || value.is_empty() {
+                    continue;
+                }
+                let old = asset.as_ref().and_then(|asset| asset.metadata_value(name));
+                if old.as_deref() != Some(value) {
+                    changes.push(Change {
+                        name: name.to_string(),
+                        old,
+                        new: value.to_string(),
+                    });
+                }
+            }
+            let problem = match uuid {
+                _ if target.is_empty() => Some("no uuid or path".to_string()),
+                None => Some("not cached; open its folder first".to_string()),
+                Some(_) if record.len() > header.len() => Some(format!(
+                    "{} cells, the header has {}",
+                    record.len(),
+                    header.len()
+                )),
+                Some(_) => None,
+            };
+            rows.push(ImportRow {
+                line,
+                target: target.to_string(),
+                uuid,
+                asset,
+                changes,
+                problem,
+            });
+        }
+        Ok(Self {
+            file: file.to_path_buf(),
+            rows,
+            selected: 0,
+        })
+    }
+
+    // Rows that will be applied: without a problem and with something to change
+    pub fn applicable(&self) -> impl Iterator<Item = &ImportRow> {
+        self.rows
+            .iter()
+            .filter(|row| row.problem.is_none() && !row.changes.is_empty())
+    }
+
+    pub fn changes(&self) -> usize {
+        self.applicable().map(|row| row.changes.len()).sum()
+    }
+
+    pub fn problems(&self) -> usize {
+        self.rows.iter().filter(|row| row.problem.is_some()).count()
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+    }
+}
+
+// Records of a CSV file with their line numbers: comma-separated, fields in double quotes may
+// hold commas, line breaks and doubled quotes. Lines may end in CRLF, quoted line breaks too.
+fn parse(text: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1; // Line the current record starts on
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        bail!("Unterminated quoted field starting on line {}", start);
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+// Set the metadata of each row, reporting a result per row
+pub async fn apply(job: JobHandle, rows: Vec<ImportRow>) {
+    let total = rows.len();
+    let mut details = Vec::with_capacity(total);
+    let mut failed = 0;
+    for (done, row) in rows.into_iter().enumerate() {
+        job.progress(done, total, row.label().to_string());
+        let Some(ref uuid) = row.uuid else {
+            continue;
+        };
+        let mut errors = Vec::new();
+        for change in &row.changes {
+            let result = pcli_commands::in_background(pcli_commands::set_metadata(
+                uuid,
+                &change.name,
+                &change.new,
+            ))
+            .await;
+            if let Err(e) = result {
+                errors.push(format!("{}: {:#}", change.name, e));
+            }
+        }
+        let names: Vec<&str> = row
+            .changes
+            .iter()
+            .map(|change| change.name.as_str())
+            .collect();
+        if errors.is_empty() {
+            details.push(format!(
+                "✓ line {} {}: set {}",
+                row.line,
+                row.label(),
+                names.join(", ")
+            ));
+        } else {
+            failed += 1;
+            details.push(format!(
+                "✗ line {} {}: {}",
+                row.line,
+                row.label(),
+                errors.join("; ")
+            ));
+        }
+    }
+    let summary = format!(
+        "Imported the metadata of {} of {} rows",
+        total - failed,
+        total
+    );
+    job.finish(failed == 0, summary, details.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(records: &[(usize, Vec<String>)]) -> Vec<Vec<&str>> {
+        records
+            .iter()
+            .map(|(_, record)| record.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    fn lines(records: &[(usize, Vec<String>)]) -> Vec<usize> {
+        records.iter().map(|(line, _)| *line).collect()
+    }
+
+    #[test]
+    fn quoted_commas_and_line_breaks() {
+        let records = parse("uuid,note\nu1,\"a, b\"\nu2,\"two\nlines\"\nu3,plain\n").unwrap();
+        assert_eq!(
+            cells(&records),
+            [
+                vec!["uuid", "note"],
+                vec!["u1", "a, b"],
+                vec!["u2", "two\nlines"],
+                vec!["u3", "plain"]
+            ]
+        );
+        assert_eq!(lines(&records), [1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn doubled_quotes() {
+        let records = parse("a,b\n\"say \"\"hi\"\"\",\"\"\"\"\nx\"y,\"\"\n").unwrap();
+        assert_eq!(cells(&records)[1], ["say \"hi\"", "\""]);
+        assert_eq!(cells(&records)[2], ["x\"y", ""]);
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let records = parse("uuid,note\r\nu1,\"first\r\nsecond\"\r\nu2,x\r\n").unwrap();
+        assert_eq!(
+            cells(&records),
+            [
+                vec!["uuid", "note"],
+                vec!["u1", "first\nsecond"],
+                vec!["u2", "x"]
+            ]
+        );
+        assert_eq!(lines(&records), [1, 2, 4]);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let records = parse("\u{feff}uuid,material\nu1,steel").unwrap();
+        assert_eq!(
+            cells(&records),
+            [vec!["uuid", "material"], vec!["u1", "steel"]]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let error = parse("uuid,note\nu1,ok\nu2,\"open\nu3,x\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unterminated quoted field starting on line 3"
+        );
+    }
+
+    #[test]
+    fn row_line_numbers() {
+        let file =
+            std::env::temp_dir().join(format!("pcli2-tui-import-{}.csv", std::process::id()));
+        let text =
+            "UUID,material,note\r\n\r\nu1,steel,\"two\r\nlines\"\r\n,,\r\nu2,,x\r\n,brass,\r\n";
+        std::fs::write(&file, text).unwrap();
+        let plan = ImportPlan::load(&file, []);
+        std::fs::remove_file(&file).unwrap();
+        let plan = plan.unwrap();
+
+        let rows: Vec<(usize, &str, usize, Option<&str>)> = plan
+            .rows
+            .iter()
+            .map(|row| {
+                let problem = row.problem.as_deref();
+                (row.line, row.target.as_str(), row.changes.len(), problem)
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (3, "u1", 2, None),
+                (6, "u2", 1, None),
+                (7, "", 1, Some("no uuid or path"))
+            ]
+        );
+        assert_eq!(plan.rows[0].changes[1].new, "two\nlines");
+        assert_eq!(plan.changes(), 3);
+        assert_eq!(plan.problems(), 1);
+    }
+}
@@ -0,0 +1,494 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::pcli_commands::Geometry;
+
+// The tenant `--demo` runs against, bundled so no Physna tenant or pcli2 is needed
+const FIXTURES: &str = include_str!("../fixtures/demo.json");
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// The fixture tenant, loaded on first use; moves, renames, uploads and deletes change it
+static TENANT: Mutex<Option<Tenant>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize)]
+struct FixtureFolder {
+    id: String,
+    name: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FixtureAsset {
+    uuid: String,
+    path: String,
+    file_type: String,
+    file_size: Option<u64>,
+    created_at: String,
+    updated_at: String,
+    #[serde(default = "default_processing_status")]
+    processing_status: String,
+    #[serde(default)]
+    metadata: Value,
+    #[serde(default)]
+    is_assembly: bool,
+    #[serde(default)]
+    dependencies: Vec<String>, // UUIDs of the assets an assembly references
+    geometry: Option<Geometry>,
+}
+
+fn default_processing_status() -> String {
+    "finished".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureProperty {
+    name: String,
+    #[serde(rename = "type")]
+    property_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureMatch {
+    uuid: String,
+    score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tenant {
+    folders: Vec<FixtureFolder>,
+    assets: Vec<FixtureAsset>,
+    #[serde(default)]
+    matches: HashMap<String, Vec<FixtureMatch>>, // By the UUID of the asset matched
+    #[serde(default)]
+    properties: Vec<FixtureProperty>, // Metadata property definitions
+    #[serde(skip)]
+    created: usize, // Folders and assets created so far, for their UUIDs
+}
+
+// Answer pcli2 invocations from the fixtures instead of running pcli2
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// The value following a flag such as "--uuid"
+fn flag<'a>(args: &[&'a str], name: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| *arg == name)?;
+    args.get(index + 1).copied()
+}
+
+fn required<'a>(args: &[&'a str], name: &str) -> Result<&'a str, String> {
+    flag(args, name).ok_or_else(|| format!("error: the argument '{}' is required", name))
+}
+
+// The parent of a folder or asset path; empty at the top level
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+impl FixtureAsset {
+    // As `asset list` prints it
+    fn record(&self) -> Value {
+        json!({
+            "uuid": self.uuid,
+            "name": file_name(&self.path),
+            "path": self.path,
+            "file_type": self.file_type,
+            "file_size": self.file_size,
+            "processing_status": self.processing_status,
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+            "metadata": self.metadata,
+            "is_assembly": self.is_assembly,
+        })
+    }
+
+    // As `asset get` and the searches print it
+    fn detail(&self, folder_id: &str) -> Value {
+        json!({
+            "id": self.uuid,
+            "name": file_name(&self.path),
+            "path": self.path,
+            "type": self.file_type,
+            "file_size": self.file_size,
+            "processing_status": self.processing_status,
+            "state": self.processing_status,
+            "createdAt": self.created_at,
+            "updatedAt": self.updated_at,
+            "metadata": self.metadata,
+            "isAssembly": self.is_assembly,
+            "tenantId": "demo",
+            "folderId": folder_id,
+        })
+    }
+}
+
+impl Tenant {
+    fn load() -> Result<Self, String> {
+        serde_json::from_str(FIXTURES).map_err(|e| format!("invalid demo fixtures: {}", e))
+    }
+
+    fn next_uuid(&mut self, prefix: &str) -> String {
+        self.created += 1;
+        format!("{}-0000-4000-a000-{:012}", prefix, self.created)
+    }
+
+    fn folder_id(&self, path: &str) -> &str {
+        self.folders
+            .iter()
+            .find(|folder| folder.path == path)
+            .map_or("", |folder| folder.id.as_str())
+    }
+
+    // A folder given by path or by UUID, as `asset create --folder` accepts either
+    fn folder_path(&self, folder: &str) -> Result<String, String> {
+        self.folders
+            .iter()
+            .find(|f| f.path == folder || f.id == folder)
+            .map(|f| f.path.clone())
+            .ok_or_else(|| format!("error: folder not found: {}", folder))
+    }
+
+    fn asset(&self, uuid: &str) -> Result<&FixtureAsset, String> {
+        self.assets
+            .iter()
+            .find(|asset| asset.uuid == uuid)
+            .ok_or_else(|| format!("error: asset not found: {}", uuid))
+    }
+
+    fn asset_mut(&mut self, uuid: &str) -> Result<&mut FixtureAsset, String> {
+        self.assets
+            .iter_mut()
+            .find(|asset| asset.uuid == uuid)
+            .ok_or_else(|| format!("error: asset not found: {}", uuid))
+    }
+
+    fn detail(&self, asset: &FixtureAsset) -> Value {
+        asset.detail(self.folder_id(parent(&asset.path)))
+    }
+
+    fn folder_record(&self, folder: &FixtureFolder) -> Value {
+        let folders = self
+            .folders
+            .iter()
+            .filter(|f| parent(&f.path) == folder.path)
+            .count();
+        let assets = self
+            .assets
+            .iter()
+            .filter(|a| parent(&a.path) == folder.path)
+            .count();
+        json!({
+            "id": folder.id,
+            "name": folder.name,
+            "path": folder.path,
+            "assetsCount": assets,
+            "foldersCount": folders,
+        })
+    }
+
+    fn respond(&mut self, args: &[&str]) -> Result<Value, String> {
+        let command = (args.first().copied(), args.get(1).copied());
+        match command {
+            (Some("folder"), Some("list")) => {
+                let parent_path = flag(args, "--folder-path");
+                let folders = self
+                    .folders
+                    .iter()
+                    .filter(|f| parent_path.is_none_or(|path| parent(&f.path) == path))
+                    .map(|f| self.folder_record(f))
+                    .collect();
+                Ok(Value::Array(folders))
+            }
+            (Some("folder"), Some("create")) => {
+                let name = required(args, "--name")?;
+                let path = match flag(args, "--parent-folder-path") {
+                    Some(parent_path) => format!("{}/{}", self.folder_path(parent_path)?, name),
+                    None => name.to_string(),
+                };
+                if self.folders.iter().any(|f| f.path == path) {
+                    return Err(format!("error: folder already exists: {}", path));
+                }
+                let id = self.next_uuid("f0a1c2d3");
+                self.folders.push(FixtureFolder {
+                    id,
+                    name: name.to_string(),
+                    path,
+                });
+                Ok(Value::Null)
+            }
+            (Some("folder"), Some("move")) => {
+                let path = self.folder_path(required(args, "--folder-path")?)?;
+                let parent_path = match required(args, "--parent-folder-path")? {
+                    "/" => String::new(),
+                    parent_path => self.folder_path(parent_path)?,
+                };
+                if parent_path == path || parent_path.starts_with(&format!("{}/", path)) {
+                    return Err("error: a folder can't be moved into itself".to_string());
+                }
+                let moved = match parent_path.as_str() {
+                    "" => file_name(&path).to_string(),
+                    parent_path => format!("{}/{}", parent_path, file_name(&path)),
+                };
+                if self.folders.iter().any(|f| f.path == moved) {
+                    return Err(format!("error: folder already exists: {}", moved));
+                }
+                // Everything below the folder moves along with it
+                let subtree = format!("{}/", path);
+                let reparent = |p: &mut String| {
+                    if *p == path {
+                        *p = moved.clone();
+                    } else if let Some(rest) = p.strip_prefix(&subtree) {
+                        *p = format!("{}/{}", moved, rest);
+                    }
+                };
+                self.folders.iter_mut().for_each(|f| reparent(&mut f.path));
+                self.assets.iter_mut().for_each(|a| reparent(&mut a.path));
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("list")) => {
+                let folder = self.folder_path(required(args, "--folder-path")?)?;
+                let assets = self
+                    .assets
+                    .iter()
+                    .filter(|asset| parent(&asset.path) == folder)
+                    .map(FixtureAsset::record)
+                    .collect();
+                Ok(Value::Array(assets))
+            }
+            (Some("asset"), Some("get")) => {
+                let asset = self.asset(required(args, "--uuid")?)?;
+                Ok(self.detail(asset))
+            }
+            (Some("asset"), Some("geometry")) => {
+                let asset = self.asset(required(args, "--uuid")?)?;
+                match &asset.geometry {
+                    Some(geometry) => serde_json::to_value(geometry).map_err(|e| e.to_string()),
+                    None => Err(format!("error: no geometry for {}", file_name(&asset.path))),
+                }
+            }
+            (Some("asset"), Some("download")) => {
+                let asset = self.asset(required(args, "--uuid")?)?;
+                let file = match flag(args, "--file") {
+                    Some(file) => PathBuf::from(file),
+                    None => PathBuf::from(file_name(&asset.path)),
+                };
+                write_download(asset, &file)?;
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("thumbnail")) => {
+                let asset = self.asset(required(args, "--uuid")?)?;
+                write_thumbnail(asset, Path::new(required(args, "--file")?))?;
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("create")) => {
+                let file = required(args, "--file")?;
+                let folder = self.folder_path(required(args, "--folder")?)?;
+                let size = std::fs::metadata(file)
+                    .map_err(|e| format!("error: {}: {}", file, e))?
+                    .len();
+                let name = file_name(file);
+                let path = format!("{}/{}", folder, name);
+                if self.assets.iter().any(|asset| asset.path == path) {
+                    return Err(format!("error: asset already exists: {}", path));
+                }
+                let created = now();
+                let asset = FixtureAsset {
+                    uuid: self.next_uuid("a5e7b1c0"),
+                    file_type: name
+                        .rsplit_once('.')
+                        .map_or("", |(_, ext)| ext)
+                        .to_lowercase(),
+                    file_size: Some(size),
+                    created_at: created.clone(),
+                    updated_at: created,
+                    processing_status: default_processing_status(),
+                    metadata: json!({}),
+                    is_assembly: false,
+                    dependencies: Vec::new(),
+                    geometry: None,
+                    path,
+                };
+                let record = asset.record();
+                self.assets.push(asset);
+                Ok(record)
+            }
+            (Some("asset"), Some("delete")) => {
+                let uuid = required(args, "--uuid")?;
+                self.asset(uuid)?;
+                self.assets.retain(|asset| asset.uuid != uuid);
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("move")) => {
+                let folder = self.folder_path(required(args, "--folder-path")?)?;
+                let asset = self.asset_mut(required(args, "--uuid")?)?;
+                asset.path = format!("{}/{}", folder, file_name(&asset.path));
+                asset.updated_at = now();
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("rename")) => {
+                let name = required(args, "--name")?;
+                let asset = self.asset_mut(required(args, "--uuid")?)?;
+                asset.path = match parent(&asset.path) {
+                    "" => name.to_string(),
+                    folder => format!("{}/{}", folder, name),
+                };
+                asset.updated_at = now();
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("metadata")) if args.get(2) == Some(&"create") => {
+                let name = required(args, "--name")?;
+                let value = required(args, "--value")?;
+                let asset = self.asset_mut(required(args, "--uuid")?)?;
+                if !asset.metadata.is_object() {
+                    asset.metadata = json!({});
+                }
+                asset.metadata[name] = json!(value);
+                asset.updated_at = now();
+                Ok(Value::Null)
+            }
+            (Some("asset"), Some("where-used")) => {
+                let uuid = required(args, "--uuid")?;
+                self.asset(uuid)?;
+                let assemblies: Vec<Value> = self
+                    .assets
+                    .iter()
+                    .filter(|asset| asset.dependencies.iter().any(|d| d == uuid))
+                    .map(FixtureAsset::record)
+                    .collect();
+                Ok(json!({ "assemblies": assemblies }))
+            }
+            (Some("asset"), Some("dependencies")) => {
+                let asset = self.asset(required(args, "--uuid")?)?;
+                let dependencies: Vec<Value> = asset
+                    .dependencies
+                    .iter()
+                    .filter_map(|uuid| self.asset(uuid).ok())
+                    .map(FixtureAsset::record)
+                    .collect();
+                Ok(json!({ "dependencies": dependencies }))
+            }
+            (Some("asset"), Some("text-match")) => {
+                let text = required(args, "--text")?;
+                let query = text.to_lowercase();
+                let folder = flag(args, "--folder-path");
+                let matches: Vec<Value> = self
+                    .assets
+                    .iter()
+                    .filter(|asset| {
+                        folder.is_none_or(|folder| asset.path.starts_with(&format!("{}/", folder)))
+                    })
+                    .filter(|asset| file_name(&asset.path).to_lowercase().contains(&query))
+                    .map(|asset| json!({ "asset": self.detail(asset) }))
+                    .collect();
+                Ok(json!({ "searchQuery": text, "matches": matches }))
+            }
+            (Some("asset"), Some("geometric-match")) => {
+                let uuid = required(args, "--uuid")?;
+                self.asset(uuid)?;
+                let matches: Vec<Value> = self
+                    .matches
+                    .get(uuid)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|m| {
+                        let asset = self.asset(&m.uuid).ok()?;
+                        Some(json!({ "asset": self.detail(asset), "similarityScore": m.score }))
+                    })
+                    .collect();
+                Ok(json!({ "matches": matches }))
+            }
+            (Some("property"), Some("list")) => {
+                let properties = self
+                    .properties
+                    .iter()
+                    .map(|p| json!({ "name": p.name, "type": p.property_type }))
+                    .collect();
+                Ok(Value::Array(properties))
+            }
+            (Some("property"), Some("create")) => {
+                let name = required(args, "--name")?;
+                if self.properties.iter().any(|p| p.name == name) {
+                    return Err(format!("error: property already exists: {}", name));
+                }
+                self.properties.push(FixtureProperty {
+                    name: name.to_string(),
+                    property_type: required(args, "--type")?.to_string(),
+                });
+                Ok(Value::Null)
+            }
+            (Some("property"), Some("delete")) => {
+                let name = required(args, "--name")?;
+                if !self.properties.iter().any(|p| p.name == name) {
+                    return Err(format!("error: property not found: {}", name));
+                }
+                self.properties.retain(|p| p.name != name);
+                Ok(Value::Null)
+            }
+            _ => Err(format!(
+                "error: '{}' is not available in demo mode",
+                args.iter().take(2).copied().collect::<Vec<_>>().join(" ")
+            )),
+        }
+    }
+}
+
+// A stand-in for the downloaded file, as large as the asset says it is
+fn write_download(asset: &FixtureAsset, file: &Path) -> Result<(), String> {
+    let mut contents = format!("pcli2-tui demo download of {}\n", asset.path).into_bytes();
+    let size = asset.file_size.unwrap_or(contents.len() as u64) as usize;
+    contents.resize(size.max(contents.len()), b'\n');
+    std::fs::write(file, contents).map_err(|e| format!("error: {}: {}", file.display(), e))
+}
+
+// A shaded tile, tinted differently for each asset
+fn write_thumbnail(asset: &FixtureAsset, file: &Path) -> Result<(), String> {
+    let seed = asset.uuid.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as u32)
+    });
+    let tint = [
+        (seed >> 16) as u8 | 0x40,
+        (seed >> 8) as u8 | 0x40,
+        seed as u8 | 0x40,
+    ];
+    let image = image::RgbImage::from_fn(64, 64, |x, y| {
+        let shade = 255 - (x + y) as u16;
+        image::Rgb(tint.map(|channel| (channel as u16 * shade / 255) as u8))
+    });
+    image
+        .save_with_format(file, image::ImageFormat::Png)
+        .map_err(|e| format!("error: {}: {}", file.display(), e))
+}
+
+// What pcli2 would print for these arguments, or the error it would fail with
+pub fn respond(args: &[&str]) -> Result<String, String> {
+    let mut tenant = TENANT
+        .lock()
+        .map_err(|_| "demo tenant unavailable".to_string())?;
+    if tenant.is_none() {
+        *tenant = Some(Tenant::load()?);
+    }
+    let Some(tenant) = tenant.as_mut() else {
+        return Err("demo tenant unavailable".to_string());
+    };
+    match tenant.respond(args)? {
+        Value::Null => Ok(String::new()),
+        output => Ok(output.to_string()),
+    }
+}
@@ -0,0 +1,86 @@
+use crate::text_input::TextInput;
+
+// A yes/no question; `action` is carried out when it is answered with yes
+#[derive(Debug, Clone)]
+pub struct Confirm<A> {
+    pub title: String,
+    pub message: String,
+    pub detail: Option<String>, // Shown muted below the message, e.g. the path concerned
+    pub action: A,
+}
+
+impl<A> Confirm<A> {
+    pub fn new(title: impl Into<String>, message: impl Into<String>, action: A) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            detail: None,
+            action,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+// Checks the (trimmed) input of a prompt before it is accepted
+pub type Validator = fn(&str) -> Result<(), String>;
+
+// A line of text typed into the status bar; `action` receives it once the validator accepts it
+#[derive(Debug, Clone)]
+pub struct Prompt<A> {
+    pub label: String,
+    pub input: TextInput,
+    pub action: A,
+    pub error: Option<String>, // Why the input was refused, until it is edited
+    validator: Validator,
+}
+
+impl<A> Prompt<A> {
+    pub fn new(label: impl Into<String>, action: A, validator: Validator) -> Self {
+        Self {
+            label: label.into(),
+            input: TextInput::default(),
+            action,
+            error: None,
+            validator,
+        }
+    }
+
+    // Start with text already typed, e.g. the current name
+    pub fn with_input(mut self, input: impl Into<String>) -> Self {
+        self.input.set(input.into());
+        self
+    }
+
+    // The trimmed input if the validator accepts it; otherwise the error is kept for display
+    pub fn accept(&mut self) -> Option<String> {
+        let input = self.input.as_str().trim().to_string();
+        match (self.validator)(&input) {
+            Ok(()) => Some(input),
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+}
+
+pub fn not_empty(input: &str) -> Result<(), String> {
+    if input.is_empty() {
+        Err("type something first".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// A single folder or asset name, not a path
+pub fn name(input: &str) -> Result<(), String> {
+    not_empty(input)?;
+    if input.contains('/') {
+        return Err("a name cannot contain '/'".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::Asset;
+use crate::log_entry::{HistoryEntry, LogEntry};
+use crate::report::{self, Links, ReportAsset};
+use crate::review::{Decision, Review};
+use crate::text;
+
+// JSON form of a log entry
+#[derive(Serialize)]
+struct ExportedLogEntry<'a> {
+    timestamp: String,
+    status: &'static str,
+    command: &'a str,
+    duration_ms: Option<u64>,
+    message: Option<&'a str>,
+    exit_code: Option<i32>,
+    stderr: Option<&'a str>,
+    stdout: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ExportedCommand<'a> {
+    index: usize,
+    command: String, // Shell-quoted
+    program: &'a str,
+    args: &'a [String],
+    status: Option<&'static str>, // None when it hadn't finished
+    duration_ms: Option<u64>,
+    exit_code: Option<i32>,
+}
+
+// JSON form of a reviewed match
+#[derive(Serialize)]
+struct ExportedDecision<'a> {
+    source_uuid: &'a str,
+    source_path: &'a str,
+    source_size: Option<u64>,
+    match_uuid: &'a str,
+    match_path: &'a str,
+    match_size: Option<u64>,
+    similarity: f64,
+    decision: Option<&'static str>,
+    action: Option<&'static str>,
+}
+
+// JSON form of an asset about to be deleted
+#[derive(Serialize)]
+struct ExportedDeletion<'a> {
+    uuid: &'a str,
+    name: &'a str,
+    path: &'a str,
+    file_type: &'a str,
+    size: Option<u64>,
+    metadata: &'a serde_json::Value,
+}
+
+// File path like <dir>/pcli2-tui-log-20240131-154500.json
+fn timestamped_path(dir: &Path, kind: &str, extension: &str) -> PathBuf {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("pcli2-tui-{}-{}.{}", kind, stamp, extension))
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// A CSV field, quoted when it has to be
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Write the log as plain text and as JSON; returns the files written
+pub fn export_log(dir: &Path, entries: &[LogEntry]) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let text_path = timestamped_path(dir, "log", "txt");
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format!(
+            "{} {}\n",
+            entry.timestamp.format("%Y-%m-%d"),
+            entry.summary()
+        ));
+        if let Some(ref error) = entry.error
+            && !error.stderr.trim().is_empty()
+        {
+            for line in error.stderr.trim_end().lines() {
+                text.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+    write(&text_path, &text)?;
+
+    let json_path = timestamped_path(dir, "log", "json");
+    let exported: Vec<ExportedLogEntry> = entries
+        .iter()
+        .map(|entry| ExportedLogEntry {
+            timestamp: entry.timestamp.to_rfc3339(),
+            status: entry.status.label(),
+            command: &entry.command,
+            duration_ms: entry.duration.map(|d| d.as_millis() as u64),
+            message: entry.message.as_deref(),
+            exit_code: entry
+                .exit_code
+                .or_else(|| entry.error.as_ref().and_then(|error| error.exit_code)),
+            stderr: entry.error.as_ref().map(|error| error.stderr.as_str()),
+            stdout: entry.error.as_ref().map(|error| error.stdout.as_str()),
+        })
+        .collect();
+    write(&json_path, &serde_json::to_string_pretty(&exported)?)?;
+
+    Ok(vec![text_path, json_path])
+}
+
+// Write the command history as plain text and as JSON; returns the files written
+pub fn export_history(dir: &Path, history: &[HistoryEntry]) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let text_path = timestamped_path(dir, "history", "txt");
+    let mut text = String::new();
+    for entry in history {
+        text.push_str(&format!("{}\n", entry.command));
+    }
+    write(&text_path, &text)?;
+
+    let json_path = timestamped_path(dir, "history", "json");
+    let exported: Vec<ExportedCommand> = history
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| ExportedCommand {
+            index: index + 1,
+            command: entry.command.to_string(),
+            program: &entry.command.program,
+            args: &entry.command.args,
+            status: entry.status.map(|status| status.label()),
+            duration_ms: entry.duration.map(|d| d.as_millis() as u64),
+            exit_code: entry.exit_code,
+        })
+        .collect();
+    write(&json_path, &serde_json::to_string_pretty(&exported)?)?;
+
+    Ok(vec![text_path, json_path])
+}
+
+// Write the command history as a shell script that replays it, stopping at the first failure
+pub fn export_history_script(dir: &Path, history: &[HistoryEntry]) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let path = timestamped_path(dir, "history", "sh");
+    let mut script = format!(
+        "#!/bin/sh\n# Commands executed by pcli2-tui, exported {}\nset -e\n\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    for entry in history {
+        script.push_str(&format!("{}\n", entry.command));
+    }
+    write(&path, &script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", path.display()))?;
+    }
+
+    Ok(path)
+}
+
+// Write the assets staged for deletion as CSV and as JSON with their metadata, a record of
+// what is removed from the tenant. Returns the files written
+pub fn export_deletions(dir: &Path, assets: &[Asset]) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let csv_path = timestamped_path(dir, "deletions", "csv");
+    let mut csv = String::from("uuid,name,path,file_type,size\n");
+    for asset in assets {
+        let fields = [
+            csv_field(&asset.uuid),
+            csv_field(&asset.name),
+            csv_field(&asset.path),
+            csv_field(&asset.file_type),
+            asset.size.map(|size| size.to_string()).unwrap_or_default(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    write(&csv_path, &csv)?;
+
+    let exported: Vec<ExportedDeletion> = assets
+        .iter()
+        .map(|asset| ExportedDeletion {
+            uuid: &asset.uuid,
+            name: &asset.name,
+            path: &asset.path,
+            file_type: &asset.file_type,
+            size: asset.size,
+            metadata: &asset.metadata,
+        })
+        .collect();
+    let json_path = timestamped_path(dir, "deletions", "json");
+    write(&json_path, &serde_json::to_string_pretty(&exported)?)?;
+
+    Ok(vec![csv_path, json_path])
+}
+
+// Write the decisions of a match review as CSV, as JSON and as a Markdown report grouping the
+// matches by what happens to them; pairs without a decision are included with an empty one.
+// Returns the files written
+pub fn export_review(dir: &Path, review: &Review) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let exported: Vec<ExportedDecision> = review
+        .pairs
+        .iter()
+        .map(|pair| ExportedDecision {
+            source_uuid: &review.source.uuid,
+            source_path: &review.source.path,
+            source_size: review.source.size,
+            match_uuid: &pair.asset.uuid,
+            match_path: &pair.asset.path,
+            match_size: pair.asset.size,
+            similarity: pair.similarity,
+            decision: pair.decision.map(|decision| decision.label()),
+            action: pair.decision.map(|decision| decision.action()),
+        })
+        .collect();
+
+    let csv_path = timestamped_path(dir, "review", "csv");
+    let mut csv = String::from(
+        "source_uuid,source_path,source_size,match_uuid,match_path,match_size,similarity,\
+         decision,action\n",
+    );
+    let size = |size: Option<u64>| size.map(|size| size.to_string()).unwrap_or_default();
+    for row in &exported {
+        let fields = [
+            csv_field(row.source_uuid),
+            csv_field(row.source_path),
+            size(row.source_size),
+            csv_field(row.match_uuid),
+            csv_field(row.match_path),
+            size(row.match_size),
+            format!("{:.2}", row.similarity),
+            row.decision.unwrap_or_default().to_string(),
+            row.action.unwrap_or_default().to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    write(&csv_path, &csv)?;
+
+    let json_path = timestamped_path(dir, "review", "json");
+    write(&json_path, &serde_json::to_string_pretty(&exported)?)?;
+
+    let markdown_path = timestamped_path(dir, "review", "md");
+    write(&markdown_path, &review_markdown(review))?;
+
+    Ok(vec![csv_path, json_path, markdown_path])
+}
+
+// The review as evidence for a cleanup: the matched asset, then a table of the matches for each
+// decision, the duplicates to remove first
+fn review_markdown(review: &Review) -> String {
+    let source = &review.source;
+    let bytes = |size: Option<u64>| {
+        size.map(text::format_bytes)
+            .unwrap_or_else(|| "—".to_string())
+    };
+    let mut out = format!(
+        "# Duplicate review of {}\n\n{} of {} matches decided, exported by pcli2-tui on {}\n\n",
+        report::markdown_cell(&source.name),
+        review.decided(),
+        review.pairs.len(),
+        Local::now().format("%Y-%m-%d %H:%M"),
+    );
+    out.push_str("| Matched asset | Path | Size | UUID |\n| --- | --- | --- | --- |\n");
+    out.push_str(&format!(
+        "| {} | {} | {} | {} |\n",
+        report::markdown_cell(&source.name),
+        report::markdown_cell(&source.path),
+        bytes(source.size),
+        source.uuid,
+    ));
+
+    let groups = [
+        (Some(Decision::Duplicate), "Duplicates to remove"),
+        (Some(Decision::Keep), "Different parts to keep"),
+        (Some(Decision::Ignore), "Ignored, kept"),
+        (None, "Undecided"),
+    ];
+    for (decision, title) in groups {
+        let pairs: Vec<_> = review
+            .pairs
+            .iter()
+            .filter(|pair| pair.decision == decision)
+            .collect();
+        if pairs.is_empty() {
+            continue;
+        }
+        let total: u64 = pairs.iter().filter_map(|pair| pair.asset.size).sum();
+        out.push_str(&format!(
+            "\n## {} ({}, {})\n\n| Asset | Path | Size | Similarity | UUID |\n\
+             | --- | --- | --- | --- | --- |\n",
+            title,
+            pairs.len(),
+            text::format_bytes(total)
+        ));
+        for pair in pairs {
+            out.push_str(&format!(
+                "| {} | {} | {} | {:.2}% | {} |\n",
+                report::markdown_cell(&pair.asset.name),
+                report::markdown_cell(&pair.asset.path),
+                bytes(pair.asset.size),
+                pair.similarity,
+                pair.asset.uuid
+            ));
+        }
+    }
+    out
+}
+
+// Write a report on the assets, with their metadata and matches, as Markdown and as HTML.
+// Returns the files written
+pub fn export_report(dir: &Path, assets: &[ReportAsset], links: Links) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let markdown_path = timestamped_path(dir, "report", "md");
+    write(&markdown_path, &report::markdown(assets, links))?;
+
+    let html_path = timestamped_path(dir, "report", "html");
+    write(&html_path, &report::html(assets, links))?;
+
+    Ok(vec![markdown_path, html_path])
+}
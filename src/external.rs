@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{Terminal, backend::CrosstermBackend};
+use std::path::Path;
+use std::process::ExitStatus;
+
+use crate::pcli_commands;
+
+// A program run in the foreground while the TUI is suspended
+#[derive(Debug, Clone)]
+pub struct ExternalCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub description: String, // Shown in notifications, e.g. "Viewer for part.step"
+    pub env: Vec<(String, String)>,
+    pub notice: Option<String>, // Printed to the terminal before the program starts
+}
+
+impl ExternalCommand {
+    // Viewer invocation from a configured command line: "{}" arguments are replaced by the
+    // file, which is appended when there is no placeholder. None for an empty command line.
+    pub fn viewer(command_line: &str, path: &Path) -> Option<Self> {
+        let file = path.display().to_string();
+        let mut args = pcli_commands::split_command_line(command_line);
+        if args.is_empty() {
+            return None;
+        }
+        let program = args.remove(0);
+        if args.iter().any(|arg| arg.contains("{}")) {
+            for arg in &mut args {
+                *arg = arg.replace("{}", &file);
+            }
+        } else {
+            args.push(file);
+        }
+
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        Some(Self {
+            program,
+            args,
+            description: format!("Viewer for {}", name),
+            env: Vec::new(),
+            notice: None,
+        })
+    }
+
+    // The user's $SHELL, told about the folder and asset selected in the TUI through
+    // PCLI2_TUI_FOLDER and PCLI2_TUI_ASSET_UUID
+    pub fn shell(folder: Option<&str>, asset_uuid: Option<&str>) -> Self {
+        let program = std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .unwrap_or_else(|| "/bin/sh".to_string());
+        let mut env = vec![(
+            "PCLI2_TUI_FOLDER".to_string(),
+            folder.unwrap_or("/").to_string(),
+        )];
+        if let Some(uuid) = asset_uuid {
+            env.push(("PCLI2_TUI_ASSET_UUID".to_string(), uuid.to_string()));
+        }
+        Self {
+            program,
+            args: Vec::new(),
+            description: "Shell".to_string(),
+            env,
+            notice: Some("pcli2-tui is suspended; type 'exit' to return.".to_string()),
+        }
+    }
+}
+
+// Split a command line template into arguments and fill in its `{placeholder}`s. Values are
+// substituted per argument, so they stay single arguments whatever characters they contain.
+pub fn expand(template: &str, values: &[(&str, &str)]) -> Vec<String> {
+    pcli_commands::split_command_line(template)
+        .into_iter()
+        .map(|arg| {
+            values.iter().fold(arg, |arg, (name, value)| {
+                arg.replace(&format!("{{{}}}", name), value)
+            })
+        })
+        .collect()
+}
+
+// Run a program to completion in the background with its output captured
+pub async fn capture(args: &[String]) -> Result<std::process::Output> {
+    let (program, rest) = args.split_first().context("Empty command line")?;
+    tracing::info!(program = %program, args = ?rest, "running custom command");
+    tokio::process::Command::new(program)
+        .args(rest)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", program))
+}
+
+// Hand the terminal back to the shell's normal screen
+fn suspend(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+fn resume(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
+    // Whatever the program printed is still on screen, so force a full redraw
+    terminal.clear()?;
+    Ok(())
+}
+
+// Suspend the TUI, run the command attached to the terminal and restore the TUI when it exits
+pub async fn run(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    command: &ExternalCommand,
+) -> Result<ExitStatus> {
+    tracing::info!(program = %command.program, args = ?command.args, "running external command");
+    suspend(terminal)?;
+    if let Some(ref notice) = command.notice {
+        println!("{}", notice);
+    }
+    let status = tokio::process::Command::new(&command.program)
+        .args(&command.args)
+        .envs(command.env.iter().cloned())
+        .status()
+        .await;
+    resume(terminal)?;
+    status.with_context(|| format!("Failed to run {}", command.program))
+}
@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+
+use crate::app::Asset;
+use crate::pcli_commands;
+use crate::text_input::TextInput;
+
+// Most matches listed at once; the best ones come first
+const MAX_MATCHES: usize = 200;
+
+// Characters after which a match counts as the start of a word
+const WORD_SEPARATORS: &[char] = &[' ', '-', '_', '.', '/'];
+
+#[derive(Debug, Clone)]
+pub struct FinderMatch {
+    pub asset: Asset,
+    pub positions: Vec<usize>, // Matched characters of the name, for highlighting
+    pub remote: bool,          // Found by the server search rather than in the cache
+    score: i64,
+}
+
+// Ctrl+F: fuzzy-find assets by name among every cached folder listing, optionally joined by
+// a server-side search
+#[derive(Debug)]
+pub struct Finder {
+    pub input: TextInput,
+    pub matches: Vec<FinderMatch>,
+    pub selected: usize,
+    pub searching: bool, // Whether a server search is running
+    remote: Vec<Asset>,
+    sender: mpsc::UnboundedSender<Result<Vec<Asset>, String>>,
+    receiver: mpsc::UnboundedReceiver<Result<Vec<Asset>, String>>,
+}
+
+impl Finder {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            input: TextInput::default(),
+            matches: Vec::new(),
+            selected: 0,
+            searching: false,
+            remote: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    // Match the input against the cached assets and the server results
+    pub fn refresh<'a>(&mut self, cached: impl IntoIterator<Item = &'a Asset>) {
+        let mut seen = HashSet::new();
+        let candidates = cached
+            .into_iter()
+            .map(|asset| (asset, false))
+            .chain(self.remote.iter().map(|asset| (asset, true)));
+
+        let mut matches = Vec::new();
+        for (asset, remote) in candidates {
+            if !seen.insert(asset.uuid.as_str()) {
+                continue;
+            }
+            if let Some((score, positions)) = fuzzy_match(self.input.as_str(), &asset.name) {
+                matches.push(FinderMatch {
+                    asset: asset.clone(),
+                    positions,
+                    remote,
+                    score,
+                });
+            }
+        }
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.asset.name.cmp(&b.asset.name))
+        });
+        matches.truncate(MAX_MATCHES);
+
+        self.matches = matches;
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    // Search the whole tenant for the input in the background
+    pub fn search_server(&mut self) {
+        let query = self.input.as_str().trim().to_string();
+        let sender = self.sender.clone();
+        self.searching = true;
+        tokio::spawn(async move {
+            let result =
+                pcli_commands::in_background(pcli_commands::search_assets(&query, None)).await;
+            let result = result
+                .map(|assets| {
+                    assets
+                        .into_iter()
+                        .map(|a| Asset {
+                            uuid: a.uuid,
+                            name: a.name,
+                            folder_uuid: a
+                                .path
+                                .rsplit_once('/')
+                                .map(|(folder, _)| folder.to_string())
+                                .unwrap_or_default(),
+                            file_type: a.file_type,
+                            size: a.file_size,
+                            path: a.path,
+                            metadata: a.metadata,
+                            is_assembly: a.is_assembly,
+                            processing_status: a.processing_status,
+                        })
+                        .collect()
+                })
+                .map_err(|e| format!("{:#}", e));
+            // The receiver is gone when the finder was closed
+            let _ = sender.send(result);
+        });
+    }
+
+    // Take in a finished server search: the number of assets it found, or why it failed
+    pub fn take_server_results(&mut self) -> Option<Result<usize, String>> {
+        let result = self.receiver.try_recv().ok()?;
+        self.searching = false;
+        Some(result.map(|assets| {
+            self.remote = assets;
+            self.remote.len()
+        }))
+    }
+
+    pub fn selected_asset(&self) -> Option<&Asset> {
+        self.matches.get(self.selected).map(|m| &m.asset)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.matches.len().saturating_sub(1));
+    }
+}
+
+// Match the pattern's characters in order anywhere in the text, ignoring case. Runs of
+// consecutive characters and matches at the start of words score higher, gaps and long
+// names lower. None when the text doesn't contain the pattern's characters in order.
+fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let text: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0i64;
+    let mut next = 0;
+    for p in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let p = p.to_lowercase().collect::<String>();
+        let offset = text[next..]
+            .iter()
+            .position(|c| c.to_lowercase().collect::<String>() == p)?;
+        let index = next + offset;
+
+        score += 16;
+        if index == 0 || WORD_SEPARATORS.contains(&text[index - 1]) {
+            score += 10;
+        }
+        match positions.last() {
+            Some(&last) if last + 1 == index => score += 8,
+            Some(_) => score -= offset as i64,
+            None => {}
+        }
+        positions.push(index);
+        next = index + 1;
+    }
+    score -= text.len() as i64 / 4;
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(uuid: &str, name: &str) -> Asset {
+        Asset {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            folder_uuid: String::new(),
+            file_type: "step".to_string(),
+            size: None,
+            path: format!("Parts/{}", name),
+            metadata: serde_json::Value::Null,
+            is_assembly: false,
+            processing_status: String::new(),
+        }
+    }
+
+    fn score(pattern: &str, text: &str) -> i64 {
+        fuzzy_match(pattern, text).map_or(i64::MIN, |(score, _)| score)
+    }
+
+    #[test]
+    fn characters_match_in_order_ignoring_case_and_spaces() {
+        assert_eq!(
+            fuzzy_match("GBm", "gearbox-mount.step"),
+            Some((58, vec![0, 4, 8]))
+        );
+        assert_eq!(
+            fuzzy_match("gear box", "gearbox").map(|(_, positions)| positions),
+            Some(vec![0, 1, 2, 3, 4, 5, 6])
+        );
+        assert_eq!(fuzzy_match("mg", "gearbox-mount.step"), None);
+        assert_eq!(fuzzy_match("x", "bracket.step"), None);
+    }
+
+    #[test]
+    fn runs_and_word_starts_score_higher() {
+        // Consecutive characters beat ones spread over the name
+        assert!(score("bolt", "bolt.step") > score("bolt", "boxlet.step"));
+        // The start of a word beats the middle of one
+        assert!(score("m", "gearbox-mount") > score("m", "gearboxmount"));
+        // Gaps cost more the longer they are
+        assert!(score("ab", "a-b") > score("ab", "a----b"));
+        // Of two names matched the same way, the shorter one comes first
+        assert!(score("bolt", "bolt.step") > score("bolt", "bolt-kit-m8.step"));
+    }
+
+    #[test]
+    fn matches_are_ordered_by_score_then_name() {
+        let cached = [
+            asset("1", "boxlet.step"),
+            asset("2", "bolt-kit.step"),
+            asset("3", "bracket.step"),
+            asset("4", "bolt-set.step"),
+        ];
+        let mut finder = Finder::new();
+        finder.input.set("bolt");
+        finder.refresh(&cached);
+
+        let names: Vec<&str> = finder
+            .matches
+            .iter()
+            .map(|found| found.asset.name.as_str())
+            .collect();
+        assert_eq!(names, ["bolt-kit.step", "bolt-set.step", "boxlet.step"]);
+    }
+
+    #[test]
+    fn an_asset_in_the_cache_and_the_server_results_is_listed_once() {
+        let cached = [asset("1", "bolt.step")];
+        let mut finder = Finder::new();
+        finder.remote = vec![asset("1", "bolt.step"), asset("2", "bolts.step")];
+        finder.input.set("bolt");
+        finder.refresh(&cached);
+
+        let found: Vec<(&str, bool)> = finder
+            .matches
+            .iter()
+            .map(|found| (found.asset.uuid.as_str(), found.remote))
+            .collect();
+        assert_eq!(found, [("1", false), ("2", true)]);
+    }
+}
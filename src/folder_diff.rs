@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+
+use crate::app::Asset;
+use crate::jobs::JobHandle;
+use crate::pcli_commands;
+
+// Geometric match similarity (in percent) from which two assets count as the same model
+const GEOMETRIC_IDENTITY: f64 = 99.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffColumn {
+    OnlyA,
+    Both,
+    OnlyB,
+}
+
+impl DiffColumn {
+    pub fn index(self) -> usize {
+        match self {
+            DiffColumn::OnlyA => 0,
+            DiffColumn::Both => 1,
+            DiffColumn::OnlyB => 2,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            DiffColumn::OnlyA => DiffColumn::Both,
+            DiffColumn::Both => DiffColumn::OnlyB,
+            DiffColumn::OnlyB => DiffColumn::OnlyA,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            DiffColumn::OnlyA => DiffColumn::OnlyB,
+            DiffColumn::Both => DiffColumn::OnlyA,
+            DiffColumn::OnlyB => DiffColumn::Both,
+        }
+    }
+}
+
+// Assets present in both folders; `similarity` is set when they were paired by geometry
+// rather than by name
+#[derive(Debug, Clone)]
+pub struct DiffPair {
+    pub a: Asset,
+    pub b: Asset,
+    pub similarity: Option<f64>,
+}
+
+// Comparison of the assets of two folders
+#[derive(Debug)]
+pub struct FolderDiff {
+    pub a: String, // Folder paths
+    pub b: String,
+    pub only_a: Vec<Asset>,
+    pub only_b: Vec<Asset>,
+    pub both: Vec<DiffPair>,
+    pub column: DiffColumn,
+    pub selected: [usize; 3], // Selection in each column
+    pub matching: bool,       // Whether a geometric comparison is running
+    matches: Option<mpsc::UnboundedReceiver<(String, String, f64)>>,
+}
+
+impl FolderDiff {
+    // Pair assets by name
+    pub fn by_name(a: String, a_assets: Vec<Asset>, b: String, b_assets: Vec<Asset>) -> Self {
+        let mut b_by_name: HashMap<String, Asset> = b_assets
+            .into_iter()
+            .map(|asset| (asset.name.clone(), asset))
+            .collect();
+        let mut only_a = Vec::new();
+        let mut both = Vec::new();
+        for asset in a_assets {
+            match b_by_name.remove(&asset.name) {
+                Some(other) => both.push(DiffPair {
+                    a: asset,
+                    b: other,
+                    similarity: None,
+                }),
+                None => only_a.push(asset),
+            }
+        }
+        let mut only_b: Vec<Asset> = b_by_name.into_values().collect();
+
+        only_a.sort_by(|x, y| x.name.cmp(&y.name));
+        only_b.sort_by(|x, y| x.name.cmp(&y.name));
+        both.sort_by(|x, y| x.a.name.cmp(&y.a.name));
+        Self {
+            a,
+            b,
+            only_a,
+            only_b,
+            both,
+            column: DiffColumn::OnlyA,
+            selected: [0; 3],
+            matching: false,
+            matches: None,
+        }
+    }
+
+    pub fn len(&self, column: DiffColumn) -> usize {
+        match column {
+            DiffColumn::OnlyA => self.only_a.len(),
+            DiffColumn::Both => self.both.len(),
+            DiffColumn::OnlyB => self.only_b.len(),
+        }
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.len(self.column);
+        let selected = &mut self.selected[self.column.index()];
+        *selected = selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    // The selected asset of a one-sided column, with the folder it is missing from
+    pub fn selected_missing(&self) -> Option<(Asset, String)> {
+        let index = self.selected[self.column.index()];
+        match self.column {
+            DiffColumn::OnlyA => self.only_a.get(index).map(|a| (a.clone(), self.b.clone())),
+            DiffColumn::OnlyB => self.only_b.get(index).map(|b| (b.clone(), self.a.clone())),
+            DiffColumn::Both => None,
+        }
+    }
+
+    // Every asset of the focused one-sided column, with the folder they are missing from
+    pub fn all_missing(&self) -> Option<(Vec<Asset>, String)> {
+        match self.column {
+            DiffColumn::OnlyA => Some((self.only_a.clone(), self.b.clone())),
+            DiffColumn::OnlyB => Some((self.only_b.clone(), self.a.clone())),
+            DiffColumn::Both => None,
+        }
+    }
+
+    // Geometric-match every asset only in A and pair it with an asset only in B that is the
+    // same model under another name
+    pub fn start_geometric_match(&mut self, job: JobHandle) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.matches = Some(receiver);
+        self.matching = true;
+
+        let candidates: Vec<(String, String)> = self
+            .only_a
+            .iter()
+            .map(|asset| (asset.uuid.clone(), asset.name.clone()))
+            .collect();
+        let targets: HashSet<String> = self.only_b.iter().map(|b| b.uuid.clone()).collect();
+        tokio::spawn(pcli_commands::in_job(async move {
+            let total = candidates.len();
+            let mut paired = 0;
+            let mut details = Vec::new();
+            for (done, (uuid, name)) in candidates.into_iter().enumerate() {
+                job.progress(done, total, name.clone());
+                let matches =
+                    pcli_commands::in_background(pcli_commands::geometric_match(&uuid)).await;
+                let matches = match matches {
+                    Ok(matches) => matches,
+                    Err(e) => {
+                        details.push(format!("✗ {}: {:#}", name, e));
+                        continue;
+                    }
+                };
+                let best = matches
+                    .into_iter()
+                    .filter(|m| {
+                        m.similarity_score >= GEOMETRIC_IDENTITY && targets.contains(&m.asset.uuid)
+                    })
+                    .max_by(|x, y| x.similarity_score.total_cmp(&y.similarity_score));
+                if let Some(best) = best {
+                    paired += 1;
+                    details.push(format!("≈ {} = {}", name, best.asset.name));
+                    // The receiver is gone when the diff was closed; nothing left to do then
+                    if sender
+                        .send((uuid, best.asset.uuid, best.similarity_score))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+            job.finish(
+                details.iter().all(|line| !line.starts_with('✗')),
+                format!("{} of {} assets matched geometrically", paired, total),
+                details.join("\n"),
+            );
+        }));
+    }
+
+    // Move geometric pairs found since the last call into the "in both" column
+    pub fn apply_matches(&mut self) {
+        let Some(ref mut receiver) = self.matches else {
+            return;
+        };
+        let mut found = Vec::new();
+        loop {
+            match receiver.try_recv() {
+                Ok(pair) => found.push(pair),
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    self.matches = None;
+                    self.matching = false;
+                    break;
+                }
+            }
+        }
+
+        for (a_uuid, b_uuid, similarity) in found {
+            let a = self.only_a.iter().position(|asset| asset.uuid == a_uuid);
+            let b = self.only_b.iter().position(|asset| asset.uuid == b_uuid);
+            if let (Some(a), Some(b)) = (a, b) {
+                self.both.push(DiffPair {
+                    a: self.only_a.remove(a),
+                    b: self.only_b.remove(b),
+                    similarity: Some(similarity),
+                });
+            }
+        }
+        for column in [DiffColumn::OnlyA, DiffColumn::Both, DiffColumn::OnlyB] {
+            let len = self.len(column);
+            let selected = &mut self.selected[column.index()];
+            *selected = (*selected).min(len.saturating_sub(1));
+        }
+    }
+}
+
+// Copy assets into a folder by downloading them and uploading the files there
+pub async fn copy_assets(job: JobHandle, assets: Vec<Asset>, folder: String) {
+    let total = assets.len();
+    let directory = std::env::temp_dir().join(format!("pcli2-tui-copy-{}", std::process::id()));
+    let mut details = Vec::new();
+    let mut copied = 0;
+    for (done, asset) in assets.into_iter().enumerate() {
+        job.progress(done, total, asset.name.clone());
+        let file = directory.join(&asset.name);
+        let result = async {
+            tokio::fs::create_dir_all(&directory).await?;
+            pcli_commands::download_asset_to(&asset.uuid, &file).await?;
+            pcli_commands::upload_asset_to_folder(&file.display().to_string(), &folder).await
+        };
+        match pcli_commands::in_background(result).await {
+            Ok(()) => {
+                copied += 1;
+                details.push(format!("✓ {}", asset.name));
+            }
+            Err(e) => details.push(format!("✗ {}: {:#}", asset.name, e)),
+        }
+        let _ = tokio::fs::remove_file(&file).await;
+    }
+    let _ = tokio::fs::remove_dir(&directory).await;
+
+    job.finish(
+        copied == total,
+        format!("Copied {} of {} assets to {}", copied, total, folder),
+        details.join("\n"),
+    );
+}
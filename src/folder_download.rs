@@ -0,0 +1,265 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::jobs::JobHandle;
+use crate::pcli_commands;
+use crate::text;
+
+// Added to a file's name while it downloads; it's renamed to the real name only once it's
+// complete and the right size, so a file without it is whole
+const PART_SUFFIX: &str = ".part";
+
+// An asset of the folder tree and the file it goes to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderFile {
+    pub uuid: String,
+    pub remote_path: String,
+    pub size: Option<u64>,
+    pub file: PathBuf, // Relative to the download's root, mirroring the folder tree
+}
+
+// A folder and everything below it, listed for downloading into a local directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderDownload {
+    pub folder: String,
+    pub root: PathBuf,          // Local directory named after the folder
+    pub subfolders: usize,      // Folders found below the folder
+    pub files: Vec<FolderFile>, // Files still to download
+    pub present: usize,         // Files a download before (maybe interrupted) already fetched
+    pub errors: Vec<String>,    // Folders that couldn't be listed, so their files are missing
+}
+
+impl FolderDownload {
+    // Total recorded size of the files to download; assets without a size count as empty
+    pub fn bytes(&self) -> u64 {
+        self.files.iter().filter_map(|file| file.size).sum()
+    }
+
+    // The pre-flight question, e.g. "Download 124 files (~2.3 GB) from Parts and its 5
+    // subfolders?"
+    pub fn question(&self) -> String {
+        let subfolders = match self.subfolders {
+            0 => String::new(),
+            1 => " and its subfolder".to_string(),
+            count => format!(" and its {} subfolders", count),
+        };
+        format!(
+            "Download {} files (~{}) from {}{}?",
+            self.files.len(),
+            text::format_bytes(self.bytes()),
+            self.folder,
+            subfolders
+        )
+    }
+
+    // Where the files go, what is skipped and what couldn't be listed
+    pub fn detail(&self) -> String {
+        let mut detail = format!("To {}", self.root.display());
+        if self.present > 0 {
+            detail.push_str(&format!(
+                "; {} files downloaded before are skipped",
+                self.present
+            ));
+        }
+        if !self.errors.is_empty() {
+            detail.push_str(&format!(
+                "; {} folders couldn't be listed: {}",
+                self.errors.len(),
+                self.errors.join(", ")
+            ));
+        }
+        detail
+    }
+}
+
+// A folder or asset name as one component of a local path, so a name can't climb out of the
+// download's root or reach into another directory
+fn file_name(name: &str) -> String {
+    match name.replace(['/', '\\'], "_") {
+        name if name.is_empty() || name == "." || name == ".." => "_".to_string(),
+        name => name,
+    }
+}
+
+fn part_file(file: &Path) -> PathBuf {
+    let mut name = OsString::from(file.as_os_str());
+    name.push(PART_SUFFIX);
+    PathBuf::from(name)
+}
+
+// A file downloaded whole before: it exists at its final name with the recorded size
+fn is_present(path: &Path, size: Option<u64>) -> bool {
+    std::fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && size.is_none_or(|size| metadata.len() == size))
+}
+
+// List the folder and its subfolders breadth first, one pcli2 listing of subfolders and one of
+// assets per folder. Only a failure to list the folder itself is an error; a subfolder that
+// can't be listed is noted and the rest of the tree is still downloaded.
+pub async fn scan(folder: &str, directory: &Path) -> Result<FolderDownload> {
+    let folder = folder.trim_matches('/').to_string();
+    let name = folder.rsplit('/').next().unwrap_or(&folder);
+    let root = directory.join(file_name(name));
+
+    let mut download = FolderDownload {
+        folder: folder.clone(),
+        root,
+        subfolders: 0,
+        files: Vec::new(),
+        present: 0,
+        errors: Vec::new(),
+    };
+    let mut pending = VecDeque::from([(folder.clone(), PathBuf::new())]);
+    while let Some((path, relative)) = pending.pop_front() {
+        let listing = async {
+            let subfolders = pcli_commands::list_subfolders_of_folder(&path).await?;
+            let assets = pcli_commands::list_assets_in_folder(&path).await?;
+            anyhow::Ok((subfolders, assets))
+        };
+        let (subfolders, assets) = match listing.await {
+            Ok(listing) => listing,
+            Err(e) if path == folder => return Err(e),
+            Err(e) => {
+                download.errors.push(format!("{} ({:#})", path, e));
+                continue;
+            }
+        };
+
+        for subfolder in subfolders {
+            download.subfolders += 1;
+            let relative = relative.join(file_name(&subfolder.name));
+            pending.push_back((subfolder.path.trim_matches('/').to_string(), relative));
+        }
+        for asset in assets {
+            let file = relative.join(file_name(&asset.name));
+            if is_present(&download.root.join(&file), asset.file_size) {
+                download.present += 1;
+                continue;
+            }
+            download.files.push(FolderFile {
+                uuid: asset.uuid,
+                remote_path: asset.path,
+                size: asset.file_size,
+                file,
+            });
+        }
+    }
+    Ok(download)
+}
+
+// Download a file by way of its part file
+async fn fetch(file: &FolderFile, destination: &Path) -> Result<()> {
+    if let Some(directory) = destination.parent() {
+        tokio::fs::create_dir_all(directory).await?;
+    }
+    let part = part_file(destination);
+    pcli_commands::download_asset_to(&file.uuid, &part).await?;
+    pcli_commands::verify_download(&part, file.size)?;
+    tokio::fs::rename(&part, destination).await?;
+    Ok(())
+}
+
+// Download the listed files one at a time, counting the bytes received. A file that fails is
+// left as a part file; scanning the folder again skips the files that made it and fetches the
+// rest.
+pub async fn execute(job: JobHandle, download: FolderDownload) {
+    let total = download.files.len();
+    let bytes = download.bytes();
+    let mut received = 0;
+    let mut details: Vec<String> = download
+        .errors
+        .iter()
+        .map(|error| format!("✗ listing of {}", error))
+        .collect();
+    let mut failed = 0;
+
+    for (done, file) in download.files.iter().enumerate() {
+        job.progress(
+            done,
+            total,
+            format!(
+                "{} · {} of {}",
+                file.remote_path,
+                text::format_bytes(received),
+                text::format_bytes(bytes)
+            ),
+        );
+        let destination = download.root.join(&file.file);
+        match pcli_commands::in_background(fetch(file, &destination)).await {
+            Ok(()) => {
+                received += file.size.unwrap_or_default();
+                details.push(format!("✓ {}", file.file.display()));
+            }
+            Err(e) => {
+                failed += 1;
+                details.push(format!("✗ {}: {:#}", file.file.display(), e));
+            }
+        }
+    }
+
+    let mut summary = format!(
+        "Downloaded {} of {} files ({}) of {} to {}",
+        total - failed,
+        total,
+        text::format_bytes(received),
+        download.folder,
+        download.root.display()
+    );
+    if failed > 0 || !download.errors.is_empty() {
+        summary.push_str("; D on the folder again fetches what is missing");
+    }
+    job.finish(
+        failed == 0 && download.errors.is_empty(),
+        summary,
+        details.join("\n"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_stay_one_path_component() {
+        assert_eq!(file_name("bracket.step"), "bracket.step");
+        assert_eq!(file_name("Parts"), "Parts");
+        assert_eq!(file_name("M8 bolt (rev 2).step"), "M8 bolt (rev 2).step");
+        assert_eq!(file_name("../../etc/passwd"), ".._.._etc_passwd");
+        assert_eq!(file_name("a\\b/c"), "a_b_c");
+        assert_eq!(file_name("/"), "_");
+        assert_eq!(file_name("..."), "...");
+        for name in ["", ".", ".."] {
+            assert_eq!(file_name(name), "_", "{:?}", name);
+        }
+        for name in ["..", "../x", "a/../../b", "\\..\\x"] {
+            let path = Path::new("root").join(file_name(name));
+            assert_eq!(path.parent(), Some(Path::new("root")), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn only_whole_files_are_present() {
+        let dir = std::env::temp_dir().join(format!("pcli2-tui-present-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("bracket.step");
+        std::fs::write(&file, b"0123456789").unwrap();
+
+        assert!(is_present(&file, Some(10)));
+        // Without a recorded size any file will do
+        assert!(is_present(&file, None));
+        // A file of another size is a partial or different download
+        assert!(!is_present(&file, Some(11)));
+        assert!(!is_present(&file, Some(0)));
+        assert!(!is_present(&dir.join("missing.step"), None));
+        // A directory by that name is not the file
+        assert!(!is_present(&dir, None));
+        // The part file of an unfinished download doesn't count for the file itself
+        std::fs::write(part_file(&dir.join("gear.step")), b"01234").unwrap();
+        assert!(!is_present(&dir.join("gear.step"), Some(5)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
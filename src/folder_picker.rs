@@ -0,0 +1,65 @@
+use crate::text_input::TextInput;
+
+// Choose a destination among the folders seen so far, narrowed down by typing; `action`
+// receives the chosen folder path ("" for the top level)
+#[derive(Debug, Clone)]
+pub struct FolderPicker<A> {
+    pub title: String,
+    pub input: TextInput,
+    pub matches: Vec<String>,
+    pub selected: usize,
+    pub action: A,
+    folders: Vec<String>, // Candidates, sorted
+}
+
+// How the top level is listed
+pub const TOP_LEVEL: &str = "/ (top level)";
+
+pub fn label(folder: &str) -> &str {
+    if folder.is_empty() { TOP_LEVEL } else { folder }
+}
+
+impl<A> FolderPicker<A> {
+    pub fn new(title: impl Into<String>, mut folders: Vec<String>, action: A) -> Self {
+        folders.sort();
+        folders.dedup();
+        let mut picker = Self {
+            title: title.into(),
+            input: TextInput::default(),
+            matches: Vec::new(),
+            selected: 0,
+            action,
+            folders,
+        };
+        picker.refresh();
+        picker
+    }
+
+    // Keep the folders whose path contains the input, ignoring case
+    pub fn refresh(&mut self) {
+        let query = self.input.as_str().trim().to_lowercase();
+        self.matches = self
+            .folders
+            .iter()
+            .filter(|folder| label(folder).to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    // The selected folder; a typed path when it matches no folder seen so far
+    pub fn chosen(&self) -> Option<String> {
+        if let Some(folder) = self.matches.get(self.selected) {
+            return Some(folder.clone());
+        }
+        match self.input.as_str().trim().trim_matches('/') {
+            "" => None,
+            path => Some(path.to_string()),
+        }
+    }
+}
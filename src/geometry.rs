@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+use crate::pcli_commands::{self, Geometry};
+
+// Length units geometry is reported and displayed in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthUnit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+}
+
+impl LengthUnit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => {
+                Some(LengthUnit::Millimeter)
+            }
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => {
+                Some(LengthUnit::Centimeter)
+            }
+            "m" | "meter" | "meters" | "metre" | "metres" => Some(LengthUnit::Meter),
+            "in" | "inch" | "inches" => Some(LengthUnit::Inch),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Inch => "in",
+        }
+    }
+
+    fn millimeters(self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Centimeter => 10.0,
+            LengthUnit::Meter => 1000.0,
+            LengthUnit::Inch => 25.4,
+        }
+    }
+
+    // Convert a length (power 1), area (2) or volume (3) from this unit to another
+    pub fn convert(self, value: f64, to: LengthUnit, power: i32) -> f64 {
+        value * (self.millimeters() / to.millimeters()).powi(power)
+    }
+}
+
+// Geometry of an asset formatted in the display unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeometryText {
+    pub size: String,         // Bounding box, e.g. "120.0 × 45.5 × 10.0 mm"
+    pub volume: String,       // e.g. "54600.0 mm³"
+    pub surface_area: String, // e.g. "13465.0 mm²"
+}
+
+impl GeometryText {
+    // Unknown source units are assumed to be millimeters, pcli2's default
+    pub fn new(geometry: &Geometry, display: LengthUnit) -> Self {
+        let source = LengthUnit::parse(&geometry.units).unwrap_or(LengthUnit::Millimeter);
+        let unit = display.symbol();
+        let length = |value: f64| format_number(source.convert(value, display, 1));
+
+        let size = geometry.bounding_box.map_or_else(String::new, |bbox| {
+            format!(
+                "{} × {} × {} {}",
+                length(bbox.x),
+                length(bbox.y),
+                length(bbox.z),
+                unit
+            )
+        });
+        let volume = geometry.volume.map_or_else(String::new, |volume| {
+            format!(
+                "{} {}³",
+                format_number(source.convert(volume, display, 3)),
+                unit
+            )
+        });
+        let surface_area = geometry.surface_area.map_or_else(String::new, |area| {
+            format!(
+                "{} {}²",
+                format_number(source.convert(area, display, 2)),
+                unit
+            )
+        });
+        Self {
+            size,
+            volume,
+            surface_area,
+        }
+    }
+}
+
+// Three significant decimals for small values, one for large ones
+fn format_number(value: f64) -> String {
+    if value.abs() < 10.0 {
+        format!("{:.3}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GeometryState {
+    Loading,
+    Ready(Geometry),
+    Unavailable(String),
+}
+
+// Fetches asset geometry in the background and caches it by asset UUID
+#[derive(Debug)]
+pub struct GeometryFetcher {
+    cache: HashMap<String, GeometryState>,
+    sender: mpsc::UnboundedSender<(String, GeometryState)>,
+    receiver: mpsc::UnboundedReceiver<(String, GeometryState)>,
+}
+
+impl GeometryFetcher {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            cache: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&GeometryState> {
+        self.cache.get(uuid)
+    }
+
+    // Start fetching an asset's geometry unless it is cached or on its way
+    pub fn request(&mut self, uuid: &str) {
+        if self.cache.contains_key(uuid) {
+            return;
+        }
+        self.cache.insert(uuid.to_string(), GeometryState::Loading);
+
+        let uuid = uuid.to_string();
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let geometry =
+                pcli_commands::in_background(pcli_commands::get_asset_geometry(&uuid)).await;
+            let state = match geometry {
+                Ok(geometry) => GeometryState::Ready(geometry),
+                Err(e) => {
+                    tracing::debug!(uuid = %uuid, error = %e, "geometry unavailable");
+                    GeometryState::Unavailable(format!("{:#}", e))
+                }
+            };
+            // The receiver only goes away when the app shuts down
+            let _ = sender.send((uuid, state));
+        });
+    }
+
+    // Move fetched geometry into the cache; returns whether any arrived
+    pub fn apply_ready(&mut self) -> bool {
+        let mut arrived = false;
+        while let Ok((uuid, state)) = self.receiver.try_recv() {
+            self.cache.insert(uuid, state);
+            arrived = true;
+        }
+        arrived
+    }
+}
@@ -0,0 +1,53 @@
+use crate::keymap::{KeyContext, KeySection};
+use crate::text_input::TextInput;
+
+// The help screen: every key by context, the context it was opened from first, narrowed to the
+// keys matching the typed filter
+#[derive(Debug)]
+pub struct Help {
+    pub context: KeyContext,
+    pub filter: TextInput,
+    pub scroll: usize, // First line shown
+}
+
+impl Help {
+    pub fn new(context: KeyContext) -> Self {
+        Self {
+            context,
+            filter: TextInput::default(),
+            scroll: 0,
+        }
+    }
+
+    // The sections to show, in order, with the keys that don't match the filter left out. A
+    // section whose title matches keeps all of its keys.
+    pub fn visible(&self, mut sections: Vec<KeySection>) -> Vec<KeySection> {
+        if let Some(i) = sections
+            .iter()
+            .position(|section| section.context == self.context)
+        {
+            let current = sections.remove(i);
+            sections.insert(0, current);
+        }
+        let filter = self.filter.as_str().trim().to_lowercase();
+        if filter.is_empty() {
+            return sections;
+        }
+        sections
+            .into_iter()
+            .filter_map(|mut section| {
+                if !section.context.title().to_lowercase().contains(&filter) {
+                    section.keys.retain(|(key, description)| {
+                        key.to_lowercase().contains(&filter)
+                            || description.to_lowercase().contains(&filter)
+                    });
+                }
+                (!section.keys.is_empty()).then_some(section)
+            })
+            .collect()
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use crate::pcli_commands;
+
+// How long a hook script may run before it is killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Lifecycle events that hook scripts can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    AssetDownloaded,
+    AssetUploaded,
+    MatchCompleted,
+    FolderChanged,
+}
+
+impl HookEvent {
+    // Name used as the key in the config file and in the JSON payload
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::AssetDownloaded => "asset_downloaded",
+            HookEvent::AssetUploaded => "asset_uploaded",
+            HookEvent::MatchCompleted => "match_completed",
+            HookEvent::FolderChanged => "folder_changed",
+        }
+    }
+}
+
+// A hook script that failed, reported back to the app for the log
+#[derive(Debug)]
+pub struct HookFailure {
+    pub event: HookEvent,
+    pub command_line: String,
+    pub error: String,
+}
+
+// Runs the configured scripts for each event in the background, with the event as JSON on stdin
+#[derive(Debug)]
+pub struct Hooks {
+    commands: HashMap<String, Vec<String>>, // Command lines keyed by event name
+    sender: mpsc::UnboundedSender<HookFailure>,
+    receiver: mpsc::UnboundedReceiver<HookFailure>,
+}
+
+impl Hooks {
+    pub fn new(commands: HashMap<String, Vec<String>>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            commands,
+            sender,
+            receiver,
+        }
+    }
+
+    // Run the event's scripts; `data` is merged into the payload next to "event" and "timestamp"
+    pub fn emit(&self, event: HookEvent, data: Value) {
+        let Some(command_lines) = self.commands.get(event.name()) else {
+            return;
+        };
+
+        let mut payload = json!({
+            "event": event.name(),
+            "timestamp": Local::now().to_rfc3339(),
+        });
+        if let (Some(payload), Value::Object(data)) = (payload.as_object_mut(), data) {
+            payload.extend(data);
+        }
+        let payload = payload.to_string();
+
+        for command_line in command_lines {
+            let command_line = command_line.clone();
+            let payload = payload.clone();
+            let sender = self.sender.clone();
+            tokio::spawn(async move {
+                if let Err(e) = run(&command_line, &payload).await {
+                    tracing::warn!(event = event.name(), command = %command_line, error = %e, "hook failed");
+                    // The receiver only goes away when the app shuts down
+                    let _ = sender.send(HookFailure {
+                        event,
+                        command_line,
+                        error: format!("{:#}", e),
+                    });
+                }
+            });
+        }
+    }
+
+    // Failures reported since the last call
+    pub fn take_failures(&mut self) -> Vec<HookFailure> {
+        let mut failures = Vec::new();
+        while let Ok(failure) = self.receiver.try_recv() {
+            failures.push(failure);
+        }
+        failures
+    }
+}
+
+async fn run(command_line: &str, payload: &str) -> Result<()> {
+    let args = pcli_commands::split_command_line(command_line);
+    let (program, rest) = args.split_first().context("Empty hook command")?;
+    tracing::debug!(program = %program, args = ?rest, "running hook");
+
+    let mut child = tokio::process::Command::new(program)
+        .args(rest)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    // A script that doesn't read its input closes the pipe early, which is fine
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload.as_bytes()).await;
+    }
+
+    let output = tokio::time::timeout(HOOK_TIMEOUT, child.wait_with_output())
+        .await
+        .with_context(|| format!("{} killed after {}s", program, HOOK_TIMEOUT.as_secs()))??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            stderr.trim()
+        );
+    }
+    Ok(())
+}
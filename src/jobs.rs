@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use crate::app::Asset;
+use crate::journal::Journal;
+
+// What a job or a long command does, for the alerts configured per kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Download,   // Downloads, of one asset or many
+    Upload,     // Uploads of single files
+    Match,      // Geometric matches and comparisons
+    Batch,      // Moves, metadata changes, copies and syncs of many assets
+    Processing, // Waiting for an uploaded asset to be processed
+}
+
+// A long-running batch of pcli2 commands, run in the background
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: usize,
+    pub kind: JobKind,
+    pub title: String,
+    pub done: usize,     // Steps completed so far
+    pub total: usize,    // Steps known so far; may grow while the job discovers more work
+    pub current: String, // What the job is doing right now
+    pub started: Instant,
+    pub folders: Vec<String>, // Folders whose contents the job changes
+}
+
+impl Job {
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+// Outcome of a job, reported once when it ends
+#[derive(Debug, Clone)]
+pub struct JobResult {
+    pub kind: JobKind,
+    pub title: String,
+    pub success: bool,
+    pub summary: String,
+    pub details: String, // Per-item results, for the log entry
+    pub started: Instant,
+    pub folders: Vec<String>, // Folders whose contents the job changed
+}
+
+#[derive(Debug)]
+enum JobUpdate {
+    Progress {
+        id: usize,
+        done: usize,
+        total: usize,
+        current: String,
+    },
+    Finished {
+        id: usize,
+        success: bool,
+        summary: String,
+        details: String,
+    },
+    Deleted {
+        asset: Asset,
+    },
+}
+
+// Lets a job task report its progress back to the UI
+#[derive(Debug, Clone)]
+pub struct JobHandle {
+    id: usize,
+    sender: mpsc::UnboundedSender<JobUpdate>,
+    journal: Option<Journal>, // Where the progress is recorded, so the job can be resumed
+}
+
+impl JobHandle {
+    // Record the job's progress in its journal, which is removed when it finishes
+    pub fn journaled(self, journal: Journal) -> Self {
+        Self {
+            journal: Some(journal),
+            ..self
+        }
+    }
+
+    // `done` counts the steps completed, in order, before the one described by `current`
+    pub fn progress(&self, done: usize, total: usize, current: impl Into<String>) {
+        if let Some(ref journal) = self.journal {
+            journal.record(done);
+        }
+        // The receiver only goes away when the app shuts down
+        let _ = self.sender.send(JobUpdate::Progress {
+            id: self.id,
+            done,
+            total,
+            current: current.into(),
+        });
+    }
+
+    // An asset the job deleted, for the app to drop from everything that lists it
+    pub fn deleted(&self, asset: Asset) {
+        let _ = self.sender.send(JobUpdate::Deleted { asset });
+    }
+
+    pub fn finish(&self, success: bool, summary: impl Into<String>, details: impl Into<String>) {
+        if let Some(ref journal) = self.journal
+            && let Err(e) = journal.remove()
+        {
+            tracing::warn!(error = %format!("{:#}", e), "failed to remove a job journal");
+        }
+        let _ = self.sender.send(JobUpdate::Finished {
+            id: self.id,
+            success,
+            summary: summary.into(),
+            details: details.into(),
+        });
+    }
+}
+
+// Running jobs and the channel their tasks report on
+#[derive(Debug)]
+pub struct Jobs {
+    running: Vec<Job>,
+    deleted: Vec<Asset>, // Assets deleted since the app last took them
+    next_id: usize,
+    sender: mpsc::UnboundedSender<JobUpdate>,
+    receiver: mpsc::UnboundedReceiver<JobUpdate>,
+}
+
+impl Jobs {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            running: Vec::new(),
+            deleted: Vec::new(),
+            next_id: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    // Register a job; its task reports through the returned handle
+    pub fn start(&mut self, kind: JobKind, title: impl Into<String>, total: usize) -> JobHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.push(Job {
+            id,
+            kind,
+            title: title.into(),
+            done: 0,
+            total,
+            current: String::new(),
+            started: Instant::now(),
+            folders: Vec::new(),
+        });
+        JobHandle {
+            id,
+            sender: self.sender.clone(),
+            journal: None,
+        }
+    }
+
+    // Note the folders a job adds assets to or takes them from, to refresh when it ends
+    pub fn touches(&mut self, job: &JobHandle, folders: impl IntoIterator<Item = String>) {
+        if let Some(running) = self.running.iter_mut().find(|running| running.id == job.id) {
+            running.folders.extend(folders);
+            running.folders.sort();
+            running.folders.dedup();
+        }
+    }
+
+    pub fn running(&self) -> &[Job] {
+        &self.running
+    }
+
+    // Apply progress reported since the last call and return the jobs that ended
+    pub fn apply_updates(&mut self) -> Vec<JobResult> {
+        let mut finished = Vec::new();
+        while let Ok(update) = self.receiver.try_recv() {
+            match update {
+                JobUpdate::Progress {
+                    id,
+                    done,
+                    total,
+                    current,
+                } => {
+                    if let Some(job) = self.running.iter_mut().find(|job| job.id == id) {
+                        job.done = done;
+                        job.total = total;
+                        job.current = current;
+                    }
+                }
+                JobUpdate::Finished {
+                    id,
+                    success,
+                    summary,
+                    details,
+                } => {
+                    if let Some(index) = self.running.iter().position(|job| job.id == id) {
+                        let job = self.running.remove(index);
+                        finished.push(JobResult {
+                            kind: job.kind,
+                            title: job.title,
+                            success,
+                            summary,
+                            details,
+                            started: job.started,
+                            folders: job.folders,
+                        });
+                    }
+                }
+                JobUpdate::Deleted { asset } => self.deleted.push(asset),
+            }
+        }
+        finished
+    }
+
+    // The assets jobs deleted since the last call
+    pub fn take_deleted(&mut self) -> Vec<Asset> {
+        std::mem::take(&mut self.deleted)
+    }
+}
@@ -0,0 +1,430 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::app::Asset;
+use crate::config::data_dir;
+use crate::csv_import::ImportRow;
+use crate::folder_download::FolderDownload;
+use crate::jobs::JobKind;
+use crate::sync::SyncEntry;
+
+const JOURNAL_DIR_NAME: &str = "journal";
+
+// What a batch job works through, item by item, in the order it does them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Work {
+    Sync {
+        folder: String,
+        directory: PathBuf,
+        entries: Vec<SyncEntry>,
+    },
+    FolderDownload {
+        download: FolderDownload,
+    },
+    Metadata {
+        name: String,
+        value: String,
+        assets: Vec<Asset>,
+    },
+    Import {
+        rows: Vec<ImportRow>,
+    },
+    Delete {
+        assets: Vec<Asset>,
+    },
+}
+
+impl Work {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            Work::FolderDownload { .. } => JobKind::Download,
+            Work::Sync { .. }
+            | Work::Metadata { .. }
+            | Work::Import { .. }
+            | Work::Delete { .. } => JobKind::Batch,
+        }
+    }
+
+    // How many items it has
+    pub fn count(&self) -> usize {
+        match self {
+            Work::Sync { entries, .. } => entries.len(),
+            Work::FolderDownload { download } => download.files.len(),
+            Work::Metadata { assets, .. } => assets.len(),
+            Work::Import { rows } => rows.len(),
+            Work::Delete { assets } => assets.len(),
+        }
+    }
+
+    // The work left once the first `done` items are done
+    fn skip(mut self, done: usize) -> Work {
+        let done = done.min(self.count());
+        match self {
+            Work::Sync {
+                ref mut entries, ..
+            } => {
+                entries.drain(..done);
+            }
+            Work::FolderDownload { ref mut download } => {
+                download.files.drain(..done);
+                download.present += done;
+            }
+            Work::Metadata { ref mut assets, .. } => {
+                assets.drain(..done);
+            }
+            Work::Import { ref mut rows } => {
+                rows.drain(..done);
+            }
+            Work::Delete { ref mut assets } => {
+                assets.drain(..done);
+            }
+        }
+        self
+    }
+}
+
+// The journal file: a job as it was started
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    title: String,
+    started: DateTime<Local>,
+    work: Work,
+}
+
+fn journal_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(JOURNAL_DIR_NAME))
+}
+
+// A running job's journal: its work, written when it starts, and next to it a count of the
+// items done so far, rewritten as it progresses. Both are removed when the job ends, so a
+// journal found on launch is a job the app stopped in the middle of. A lock file next to them
+// is held for as long as the journal is, so that another instance of the app doesn't resume
+// the job or rewrite its count; the lock goes away with the instance, even when it crashes.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    _lock: Arc<File>, // Released when the last clone is dropped
+}
+
+impl Journal {
+    pub fn create(title: &str, work: &Work) -> Result<Journal> {
+        let Some(dir) = journal_dir() else {
+            anyhow::bail!("No data directory (HOME is not set)");
+        };
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let started = Local::now();
+        let path = dir.join(format!("{}.json", started.format("%Y%m%d-%H%M%S-%9f")));
+        let Some(lock) = lock(&path)? else {
+            anyhow::bail!("Journal {} is in use by another instance", path.display());
+        };
+        let contents = serde_json::to_string(&Entry {
+            title: title.to_string(),
+            started,
+            work: work.clone(),
+        })?;
+        if let Err(e) = std::fs::write(&path, contents) {
+            let _ = std::fs::remove_file(lock_path(&path));
+            return Err(e).with_context(|| format!("Failed to write journal {}", path.display()));
+        }
+        Ok(Journal {
+            path,
+            _lock: Arc::new(lock),
+        })
+    }
+
+    fn done_path(&self) -> PathBuf {
+        self.path.with_extension("done")
+    }
+
+    // The first `done` items are done. A count that fails to be written only means those
+    // items are done again when the job is resumed.
+    pub fn record(&self, done: usize) {
+        let _ = std::fs::write(self.done_path(), done.to_string());
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        remove_file(&self.done_path())?;
+        remove_file(&self.path)?;
+        // A lock file left behind, e.g. where open files can't be removed, locks nothing
+        let _ = std::fs::remove_file(lock_path(&self.path));
+        Ok(())
+    }
+}
+
+fn lock_path(journal: &Path) -> PathBuf {
+    journal.with_extension("lock")
+}
+
+// Lock the journal at `path` for this instance; None when another instance holds it
+fn lock(path: &Path) -> Result<Option<File>> {
+    let path = lock_path(path);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => {
+            Err(e).with_context(|| format!("Failed to lock {}", path.display()))
+        }
+    }
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+// A job the app stopped before it was done, found in the journal on launch
+#[derive(Debug)]
+pub struct Interrupted {
+    pub title: String,
+    pub started: DateTime<Local>,
+    pub done: usize,
+    pub total: usize,
+    work: Work,
+    journal: Journal,
+}
+
+impl Interrupted {
+    // What is left of the job, and its journal, to be replaced by the resumed job's
+    pub fn resume(self) -> (Work, Journal) {
+        (self.work.skip(self.done), self.journal)
+    }
+
+    // Give up on the rest of the job
+    pub fn discard(self) -> Result<()> {
+        self.journal.remove()
+    }
+}
+
+// The interrupted jobs of the last sessions, oldest first
+#[derive(Debug, Default)]
+pub struct InterruptedJobs {
+    pub jobs: Vec<Interrupted>,
+    pub selected: usize,
+    pub unreadable: usize, // Journal files that could not be read or parsed
+}
+
+impl InterruptedJobs {
+    pub fn load() -> Result<InterruptedJobs> {
+        let Some(dir) = journal_dir() else {
+            anyhow::bail!("No data directory (HOME is not set)");
+        };
+        let mut interrupted = InterruptedJobs::default();
+        if !dir.exists() {
+            return Ok(interrupted);
+        }
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            // Another instance is running the job or offering it too
+            let lock = match lock(&path) {
+                Ok(Some(lock)) => lock,
+                Ok(None) => continue,
+                Err(_) => {
+                    interrupted.unreadable += 1;
+                    continue;
+                }
+            };
+            let journal = Journal {
+                path,
+                _lock: Arc::new(lock),
+            };
+            let entry = match std::fs::read_to_string(&journal.path) {
+                // The job ended between listing the journals and locking this one
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    let _ = journal.remove();
+                    continue;
+                }
+                contents => contents
+                    .ok()
+                    .and_then(|contents| serde_json::from_str::<Entry>(&contents).ok()),
+            };
+            let Some(entry) = entry else {
+                interrupted.unreadable += 1;
+                continue;
+            };
+            // No count yet: the job stopped before its first item was done
+            let done = std::fs::read_to_string(journal.done_path())
+                .ok()
+                .and_then(|done| done.trim().parse().ok())
+                .unwrap_or(0);
+            interrupted.jobs.push(Interrupted {
+                title: entry.title,
+                started: entry.started,
+                done,
+                total: entry.work.count(),
+                work: entry.work,
+                journal,
+            });
+        }
+        interrupted.jobs.sort_by_key(|job| job.started);
+        Ok(interrupted)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.jobs.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    // Take the selected job off the list
+    pub fn take_selected(&mut self) -> Option<Interrupted> {
+        if self.selected >= self.jobs.len() {
+            return None;
+        }
+        let job = self.jobs.remove(self.selected);
+        self.move_selection(0);
+        Some(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::folder_download::FolderFile;
+    use crate::sync::SyncAction;
+
+    fn asset(name: &str) -> Asset {
+        Asset {
+            uuid: format!("uuid-{}", name),
+            name: name.to_string(),
+            folder_uuid: String::new(),
+            file_type: "step".to_string(),
+            size: None,
+            path: format!("Parts/{}", name),
+            metadata: serde_json::Value::Null,
+            is_assembly: false,
+            processing_status: String::new(),
+        }
+    }
+
+    // The items of the work, by name
+    fn items(work: &Work) -> Vec<String> {
+        match work {
+            Work::Sync { entries, .. } => entries.iter().map(|entry| entry.name.clone()).collect(),
+            Work::FolderDownload { download } => download
+                .files
+                .iter()
+                .map(|file| file.remote_path.clone())
+                .collect(),
+            Work::Metadata { assets, .. } | Work::Delete { assets } => {
+                assets.iter().map(|asset| asset.name.clone()).collect()
+            }
+            Work::Import { rows } => rows.iter().map(|row| row.target.clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn skipping_leaves_the_items_not_done() {
+        let assets = vec![asset("a.step"), asset("b.step"), asset("c.step")];
+        let work = Work::Delete {
+            assets: assets.clone(),
+        };
+        assert_eq!(items(&work.clone().skip(0)), ["a.step", "b.step", "c.step"]);
+        assert_eq!(items(&work.clone().skip(2)), ["c.step"]);
+        assert!(items(&work.clone().skip(3)).is_empty());
+        // A count beyond the items, e.g. from a journal edited by hand, leaves nothing
+        assert!(items(&work.skip(7)).is_empty());
+
+        let work = Work::Metadata {
+            name: "material".to_string(),
+            value: "steel".to_string(),
+            assets,
+        };
+        let Work::Metadata { name, value, .. } = work.clone().skip(1) else {
+            panic!("skipping changed the kind of work");
+        };
+        assert_eq!((name.as_str(), value.as_str()), ("material", "steel"));
+        assert_eq!(items(&work.skip(1)), ["b.step", "c.step"]);
+    }
+
+    #[test]
+    fn skipping_covers_every_kind_of_work() {
+        let entry = |name: &str| SyncEntry {
+            name: name.to_string(),
+            action: SyncAction::Upload,
+            reason: "",
+            included: true,
+            local: None,
+            remote: None,
+        };
+        let work = Work::Sync {
+            folder: "Parts".to_string(),
+            directory: PathBuf::from("parts"),
+            entries: vec![entry("a.step"), entry("b.step")],
+        };
+        assert_eq!(items(&work.skip(1)), ["b.step"]);
+
+        let row = |target: &str| ImportRow {
+            line: 2,
+            target: target.to_string(),
+            uuid: None,
+            asset: None,
+            changes: Vec::new(),
+            problem: None,
+        };
+        let work = Work::Import {
+            rows: vec![row("u1"), row("u2"), row("u3")],
+        };
+        assert_eq!(items(&work.skip(2)), ["u3"]);
+    }
+
+    #[test]
+    fn skipped_downloads_count_as_present() {
+        let file = |name: &str| FolderFile {
+            uuid: format!("uuid-{}", name),
+            remote_path: format!("Parts/{}", name),
+            size: Some(10),
+            file: PathBuf::from(name),
+        };
+        let work = Work::FolderDownload {
+            download: FolderDownload {
+                folder: "Parts".to_string(),
+                root: PathBuf::from("Parts"),
+                subfolders: 0,
+                files: vec![file("a.step"), file("b.step"), file("c.step")],
+                present: 4,
+                errors: Vec::new(),
+            },
+        };
+        let Work::FolderDownload { download } = work.skip(2) else {
+            panic!("skipping changed the kind of work");
+        };
+        assert_eq!(download.files.len(), 1);
+        assert_eq!(download.files[0].remote_path, "Parts/c.step");
+        assert_eq!(download.present, 6);
+    }
+
+    #[test]
+    fn a_journal_is_locked_by_one_holder_at_a_time() {
+        let dir = std::env::temp_dir().join(format!("pcli2-tui-journal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("20240101-120000-000000000.json");
+
+        let held = lock(&path).unwrap();
+        assert!(held.is_some());
+        assert!(lock(&path).unwrap().is_none());
+        drop(held);
+        assert!(lock(&path).unwrap().is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+// A piece of a rendered JSON line, tagged for syntax highlighting
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonToken {
+    Key(String),
+    String(String),
+    Number(String),
+    Literal(String), // true, false and null
+    Punct(String),
+    Summary(String), // Placeholder for the contents of a folded node
+    Plain(String),   // Output that isn't JSON
+}
+
+impl JsonToken {
+    pub fn text(&self) -> &str {
+        match self {
+            JsonToken::Key(s)
+            | JsonToken::String(s)
+            | JsonToken::Number(s)
+            | JsonToken::Literal(s)
+            | JsonToken::Punct(s)
+            | JsonToken::Summary(s)
+            | JsonToken::Plain(s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JsonLine {
+    pub depth: usize,
+    pub path: String,   // JSON pointer of the value the line belongs to
+    pub foldable: bool, // Opening line of a non-empty object or array
+    pub closing: bool,  // Closing bracket of an object or array
+    pub tokens: Vec<JsonToken>,
+}
+
+impl JsonLine {
+    pub fn text(&self) -> String {
+        self.tokens.iter().map(JsonToken::text).collect()
+    }
+}
+
+// Escape a key for use in a JSON pointer
+fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn flatten(
+    lines: &mut Vec<JsonLine>,
+    value: &Value,
+    key: Option<&str>,
+    path: String,
+    depth: usize,
+    comma: bool,
+    collapsed: &HashSet<String>,
+) {
+    let mut tokens = Vec::new();
+    if let Some(key) = key {
+        tokens.push(JsonToken::Key(Value::String(key.to_string()).to_string()));
+        tokens.push(JsonToken::Punct(": ".to_string()));
+    }
+    let comma = if comma { "," } else { "" };
+
+    let (open, close, len) = match value {
+        Value::Object(map) if !map.is_empty() => ("{", "}", map.len()),
+        Value::Array(items) if !items.is_empty() => ("[", "]", items.len()),
+        _ => {
+            tokens.push(match value {
+                Value::Object(_) => JsonToken::Punct("{}".to_string()),
+                Value::Array(_) => JsonToken::Punct("[]".to_string()),
+                Value::String(_) => JsonToken::String(value.to_string()),
+                Value::Number(n) => JsonToken::Number(n.to_string()),
+                _ => JsonToken::Literal(value.to_string()),
+            });
+            tokens.push(JsonToken::Punct(comma.to_string()));
+            lines.push(JsonLine {
+                depth,
+                path,
+                foldable: false,
+                closing: false,
+                tokens,
+            });
+            return;
+        }
+    };
+
+    tokens.push(JsonToken::Punct(open.to_string()));
+
+    if collapsed.contains(&path) {
+        let noun = match (value.is_object(), len) {
+            (true, 1) => "key",
+            (true, _) => "keys",
+            (false, 1) => "item",
+            (false, _) => "items",
+        };
+        tokens.push(JsonToken::Summary(format!(" … {} {} ", len, noun)));
+        tokens.push(JsonToken::Punct(format!("{}{}", close, comma)));
+        lines.push(JsonLine {
+            depth,
+            path,
+            foldable: true,
+            closing: false,
+            tokens,
+        });
+        return;
+    }
+
+    lines.push(JsonLine {
+        depth,
+        path: path.clone(),
+        foldable: true,
+        closing: false,
+        tokens,
+    });
+
+    match value {
+        Value::Object(map) => {
+            for (i, (child_key, child)) in map.iter().enumerate() {
+                let child_path = format!("{}/{}", path, pointer_segment(child_key));
+                flatten(
+                    lines,
+                    child,
+                    Some(child_key),
+                    child_path,
+                    depth + 1,
+                    i + 1 < len,
+                    collapsed,
+                );
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let child_path = format!("{}/{}", path, i);
+                flatten(
+                    lines,
+                    child,
+                    None,
+                    child_path,
+                    depth + 1,
+                    i + 1 < len,
+                    collapsed,
+                );
+            }
+        }
+        _ => {}
+    }
+
+    lines.push(JsonLine {
+        depth,
+        path,
+        foldable: false,
+        closing: true,
+        tokens: vec![JsonToken::Punct(format!("{}{}", close, comma))],
+    });
+}
+
+// Foldable, searchable view of a pcli2 command's raw output
+#[derive(Debug)]
+pub struct JsonViewer {
+    pub title: String,
+    root: Option<Value>,
+    raw: String,
+    collapsed: HashSet<String>,
+    lines: Vec<JsonLine>,
+    pub selected: usize,
+    pub search: String,
+    pub search_editing: bool,
+}
+
+impl JsonViewer {
+    pub fn new(title: String, raw: String) -> Self {
+        let root = serde_json::from_str(&raw).ok();
+        let mut viewer = Self {
+            title,
+            root,
+            raw,
+            collapsed: HashSet::new(),
+            lines: Vec::new(),
+            selected: 0,
+            search: String::new(),
+            search_editing: false,
+        };
+        viewer.refresh();
+        viewer
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.root.is_some()
+    }
+
+    pub fn lines(&self) -> &[JsonLine] {
+        &self.lines
+    }
+
+    fn render(&self, collapsed: &HashSet<String>) -> Vec<JsonLine> {
+        let mut lines = Vec::new();
+        match self.root {
+            Some(ref root) => flatten(&mut lines, root, None, String::new(), 0, false, collapsed),
+            None => {
+                for line in self.raw.lines() {
+                    lines.push(JsonLine {
+                        depth: 0,
+                        path: String::new(),
+                        foldable: false,
+                        closing: false,
+                        tokens: vec![JsonToken::Plain(line.to_string())],
+                    });
+                }
+            }
+        }
+        lines
+    }
+
+    fn refresh(&mut self) {
+        self.lines = self.render(&self.collapsed);
+        self.selected = self.selected.min(self.lines.len().saturating_sub(1));
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.lines.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).clamp(0, last.max(0)) as usize;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.selected = self.lines.len().saturating_sub(1);
+    }
+
+    // Fold or unfold the object or array the selected line belongs to
+    pub fn toggle_fold(&mut self) {
+        let Some(line) = self.lines.get(self.selected) else {
+            return;
+        };
+        if !line.foldable && !line.closing {
+            return;
+        }
+
+        let path = line.path.clone();
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path.clone());
+        }
+        self.refresh();
+
+        // Keep the selection on the node that was toggled
+        if let Some(index) = self.lines.iter().position(|l| l.path == path && !l.closing) {
+            self.selected = index;
+        }
+    }
+
+    // Fold every nested object and array, leaving the top level open
+    pub fn collapse_all(&mut self) {
+        self.collapsed = self
+            .render(&HashSet::new())
+            .into_iter()
+            .filter(|line| line.foldable && line.depth > 0)
+            .map(|line| line.path)
+            .collect();
+        self.selected = 0;
+        self.refresh();
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+        self.refresh();
+    }
+
+    // Select the next (or previous) line containing the search text, unfolding it if needed.
+    // Returns false when nothing matches.
+    pub fn find(&mut self, forward: bool) -> bool {
+        let needle = self.search.to_lowercase();
+        if needle.is_empty() {
+            return false;
+        }
+
+        // Search the fully expanded document so folded content is found too
+        let all = self.render(&HashSet::new());
+        if all.is_empty() {
+            return false;
+        }
+        let current = self
+            .lines
+            .get(self.selected)
+            .and_then(|selected| {
+                all.iter()
+                    .position(|l| l.path == selected.path && l.closing == selected.closing)
+            })
+            .unwrap_or(0);
+
+        let count = all.len();
+        let found = (1..=count)
+            .map(|step| {
+                if forward {
+                    (current + step) % count
+                } else {
+                    (current + count - step) % count
+                }
+            })
+            .find(|&i| all[i].text().to_lowercase().contains(&needle));
+        let Some(index) = found else {
+            return false;
+        };
+
+        // For raw text every line shares the same path, so select by position
+        if !self.is_json() {
+            self.selected = index;
+            return true;
+        }
+
+        let target = &all[index];
+        self.collapsed
+            .retain(|path| !target.path.starts_with(&format!("{}/", path)));
+        self.refresh();
+        if let Some(i) = self
+            .lines
+            .iter()
+            .position(|l| l.path == target.path && l.closing == target.closing)
+        {
+            self.selected = i;
+        }
+        true
+    }
+}
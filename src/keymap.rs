@@ -0,0 +1,1670 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::action::{self, Action, Binding, GLOBAL_KEYS, Modifier, When};
+use crate::app::{ActivePane, AppState};
+use crate::config::CustomCommand;
+use crate::permissions::{self, Permission};
+
+// The views the help screen is organized by, in the order they are listed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyContext {
+    Global,
+    Browsing, // Either pane of the folder and asset views
+    FolderPane,
+    AssetsPane,
+    LogPane,
+    CellEdit,  // Moving between metadata cells
+    CellInput, // Typing in a metadata cell
+    Search,
+    SavedSearches,
+    Finder,
+    GeometricMatch,
+    MatchReview,
+    MatchHistory,
+    AssetDetails,
+    WhereUsed,
+    Log,
+    LogDetail,
+    ErrorDetails,
+    CommandHistory,
+    JsonView,
+    RawOutput,
+    Basket,
+    Deletions,
+    Jobs,
+    InterruptedJobs,
+    PaneResize,
+    UploadMode,
+    DownloadMode,
+    AssemblyDownload,
+    FolderPicker,
+    TagPicker,
+    CustomCommands,
+    SyncPlan,
+    FolderDiff,
+    MetadataDiff,
+    PropertySchema,
+    Validation,
+    MetadataImport,
+    ProfilePicker,
+    Confirm,
+    Prompt, // Any line of text being typed
+}
+
+// The setup, the tour and the help itself aren't listed: they show their keys as they go
+pub const CONTEXTS: [KeyContext; 41] = [
+    KeyContext::Global,
+    KeyContext::Browsing,
+    KeyContext::FolderPane,
+    KeyContext::AssetsPane,
+    KeyContext::LogPane,
+    KeyContext::CellEdit,
+    KeyContext::CellInput,
+    KeyContext::Search,
+    KeyContext::SavedSearches,
+    KeyContext::Finder,
+    KeyContext::GeometricMatch,
+    KeyContext::MatchReview,
+    KeyContext::MatchHistory,
+    KeyContext::AssetDetails,
+    KeyContext::WhereUsed,
+    KeyContext::Log,
+    KeyContext::LogDetail,
+    KeyContext::ErrorDetails,
+    KeyContext::CommandHistory,
+    KeyContext::JsonView,
+    KeyContext::RawOutput,
+    KeyContext::Basket,
+    KeyContext::Deletions,
+    KeyContext::Jobs,
+    KeyContext::InterruptedJobs,
+    KeyContext::PaneResize,
+    KeyContext::UploadMode,
+    KeyContext::DownloadMode,
+    KeyContext::AssemblyDownload,
+    KeyContext::FolderPicker,
+    KeyContext::TagPicker,
+    KeyContext::CustomCommands,
+    KeyContext::SyncPlan,
+    KeyContext::FolderDiff,
+    KeyContext::MetadataDiff,
+    KeyContext::PropertySchema,
+    KeyContext::Validation,
+    KeyContext::MetadataImport,
+    KeyContext::ProfilePicker,
+    KeyContext::Confirm,
+    KeyContext::Prompt,
+];
+
+impl KeyContext {
+    // The view the keys are going to, for listing its keys first
+    pub fn of(state: AppState, pane: ActivePane) -> Self {
+        match state {
+            AppState::Folders | AppState::Assets => match pane {
+                ActivePane::Folders => KeyContext::FolderPane,
+                ActivePane::Assets => KeyContext::AssetsPane,
+                ActivePane::Log => KeyContext::LogPane,
+            },
+            AppState::Uploading => KeyContext::UploadMode,
+            AppState::Downloading => KeyContext::DownloadMode,
+            AppState::CommandHistory => KeyContext::CommandHistory,
+            AppState::Log => KeyContext::Log,
+            AppState::PaneResize => KeyContext::PaneResize,
+            AppState::RawOutput => KeyContext::RawOutput,
+            AppState::JsonView => KeyContext::JsonView,
+            AppState::Basket => KeyContext::Basket,
+            AppState::Deletions => KeyContext::Deletions,
+            AppState::Jobs => KeyContext::Jobs,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            KeyContext::Global => "Everywhere",
+            KeyContext::Browsing => "Folder and asset views",
+            KeyContext::FolderPane => "Folders pane",
+            KeyContext::AssetsPane => "Assets pane",
+            KeyContext::LogPane => "Log pane",
+            KeyContext::CellEdit => "Metadata cells (e)",
+            KeyContext::CellInput => "Typing in a metadata cell",
+            KeyContext::Search => "Search dialog (/)",
+            KeyContext::SavedSearches => "Saved searches (F3 in the search dialog)",
+            KeyContext::Finder => "Finder (Ctrl+F; typing narrows the assets by name)",
+            KeyContext::GeometricMatch => "Geometric match results (g)",
+            KeyContext::MatchReview => "Match review (r in the geometric match results)",
+            KeyContext::MatchHistory => "Match history (G)",
+            KeyContext::AssetDetails => "Asset details (Enter)",
+            KeyContext::WhereUsed => "Where-used (w)",
+            KeyContext::Log => "Log view (l)",
+            KeyContext::LogDetail => "Log entry (Enter in the log view)",
+            KeyContext::ErrorDetails => "Error details (E)",
+            KeyContext::CommandHistory => "Command history (c)",
+            KeyContext::JsonView => "JSON viewer (J)",
+            KeyContext::RawOutput => "Raw output (:)",
+            KeyContext::Basket => "Basket (B)",
+            KeyContext::Deletions => "Pending deletions (Ctrl+X)",
+            KeyContext::Jobs => "Jobs (W)",
+            KeyContext::InterruptedJobs => "Interrupted jobs (offered at the start)",
+            KeyContext::PaneResize => "Pane resizing (Ctrl+N)",
+            KeyContext::UploadMode => "Upload mode (u)",
+            KeyContext::DownloadMode => "Download mode (d)",
+            KeyContext::AssemblyDownload => "Downloading an assembly",
+            KeyContext::FolderPicker => "Folder picker (typing narrows the folders)",
+            KeyContext::TagPicker => "Tag picker (t, F; typing narrows the tags)",
+            KeyContext::CustomCommands => "Custom commands (!; a command's own key runs it)",
+            KeyContext::SyncPlan => "Sync plan (S)",
+            KeyContext::FolderDiff => "Folder comparison (= on two folders)",
+            KeyContext::MetadataDiff => "Metadata comparison (= on two assets)",
+            KeyContext::PropertySchema => "Property schema (P)",
+            KeyContext::Validation => "Metadata rule violations (A)",
+            KeyContext::MetadataImport => "Metadata import preview (I)",
+            KeyContext::ProfilePicker => "Profile picker (at the start)",
+            KeyContext::Confirm => "Questions",
+            KeyContext::Prompt => "Prompts and other text fields",
+        }
+    }
+
+    // The section a global binding is listed in
+    fn of_binding(binding: &Binding) -> Self {
+        match binding.when {
+            When::Always | When::NotIn(_) => KeyContext::Global,
+            When::Browsing => KeyContext::Browsing,
+            When::In(state) => KeyContext::of(state, ActivePane::Folders),
+        }
+    }
+
+    // Keys handled by the view itself; the global ones come from GLOBAL_KEYS
+    pub fn bindings(self) -> &'static [ViewBinding] {
+        match self {
+            KeyContext::Global | KeyContext::Browsing => &[],
+            KeyContext::FolderPane => FOLDER_PANE_KEYS,
+            KeyContext::AssetsPane => ASSETS_PANE_KEYS,
+            KeyContext::LogPane => LOG_PANE_KEYS,
+            KeyContext::CellEdit => CELL_EDIT_KEYS,
+            KeyContext::CellInput => CELL_INPUT_KEYS,
+            KeyContext::Search => SEARCH_KEYS,
+            KeyContext::SavedSearches => SAVED_SEARCHES_KEYS,
+            KeyContext::Finder => FINDER_KEYS,
+            KeyContext::GeometricMatch => GEOMETRIC_MATCH_KEYS,
+            KeyContext::MatchReview => MATCH_REVIEW_KEYS,
+            KeyContext::MatchHistory => MATCH_HISTORY_KEYS,
+            KeyContext::AssetDetails => ASSET_DETAILS_KEYS,
+            KeyContext::WhereUsed => WHERE_USED_KEYS,
+            KeyContext::Log => LOG_KEYS,
+            KeyContext::LogDetail => LOG_DETAIL_KEYS,
+            KeyContext::ErrorDetails => ERROR_DETAILS_KEYS,
+            KeyContext::CommandHistory => COMMAND_HISTORY_KEYS,
+            KeyContext::JsonView => JSON_VIEW_KEYS,
+            KeyContext::RawOutput => RAW_OUTPUT_KEYS,
+            KeyContext::Basket => BASKET_KEYS,
+            KeyContext::Deletions => DELETIONS_KEYS,
+            KeyContext::Jobs => JOBS_KEYS,
+            KeyContext::InterruptedJobs => INTERRUPTED_JOBS_KEYS,
+            KeyContext::PaneResize => PANE_RESIZE_KEYS,
+            KeyContext::UploadMode => UPLOAD_MODE_KEYS,
+            KeyContext::DownloadMode => DOWNLOAD_MODE_KEYS,
+            KeyContext::AssemblyDownload => ASSEMBLY_DOWNLOAD_KEYS,
+            KeyContext::FolderPicker => FOLDER_PICKER_KEYS,
+            KeyContext::TagPicker => TAG_PICKER_KEYS,
+            KeyContext::CustomCommands => CUSTOM_COMMANDS_KEYS,
+            KeyContext::SyncPlan => SYNC_PLAN_KEYS,
+            KeyContext::FolderDiff => FOLDER_DIFF_KEYS,
+            KeyContext::MetadataDiff => METADATA_DIFF_KEYS,
+            KeyContext::PropertySchema => PROPERTY_SCHEMA_KEYS,
+            KeyContext::Validation => VALIDATION_KEYS,
+            KeyContext::MetadataImport => METADATA_IMPORT_KEYS,
+            KeyContext::ProfilePicker => PROFILE_PICKER_KEYS,
+            KeyContext::Confirm => CONFIRM_KEYS,
+            KeyContext::Prompt => PROMPT_KEYS,
+        }
+    }
+
+    // What the key does in the view, when the view has a binding for it
+    pub fn action(self, key: KeyEvent) -> Option<ViewAction> {
+        self.bindings()
+            .iter()
+            .find(|binding| binding.code == key.code && binding.modifier.matches(key.modifiers))
+            .map(|binding| binding.action)
+    }
+
+    // The key listed first for the action
+    fn first_binding(self, action: ViewAction) -> Option<&'static ViewBinding> {
+        self.bindings()
+            .iter()
+            .find(|binding| binding.action == action)
+    }
+}
+
+impl ViewBinding {
+    pub fn label(&self) -> String {
+        self.modifier.label(self.code)
+    }
+}
+
+// What a key does in the view that has it. Each view maps its keys to these through its table
+// below, which its handler dispatches through and the help lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewAction {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Open, // Enter: what the view is for
+    Back, // Esc: close the view or go up
+    ListAssets,
+    Compare,
+    Sync,
+    DownloadFolder,
+    NewFolder,
+    Move,
+    UploadMode,
+    DownloadMode,
+    Details,
+    GeometricMatch,
+    Download,
+    WhereUsed,
+    Browser,
+    Basket,
+    Tag,
+    TagFilter,
+    Rename,
+    EditCells,
+    Report,
+    StageDeletion,
+    CopyUuid,
+    CopyPath,
+    Edit,
+    SwitchFocus,
+    CycleScope,
+    CycleMatching,
+    SaveSearch,
+    SavedSearches,
+    Sort,
+    ReverseSort,
+    SearchServer,
+    Queue,
+    Review,
+    MinSimilarity,
+    HideSelf,
+    SameFolder,
+    Note,
+    Copy,
+    StatusFilter,
+    Notifications,
+    Export,
+    ExportScript,
+    Fold,
+    FoldAll,
+    UnfoldAll,
+    NextHit,
+    PreviousHit,
+    Remove,
+    Clear,
+    SetMetadata,
+    Upload,
+    Yes,
+    No,
+    Toggle,
+    Create,
+    Refresh,
+    CopyAcross,
+    CopyAllAcross,
+    OnlyDifferences,
+    Keep,
+    Duplicate,
+    Ignore,
+    ShowError,
+}
+
+// A key a view handles itself, what it does there and its line in the help
+#[derive(Debug, Clone, Copy)]
+pub struct ViewBinding {
+    pub code: KeyCode,
+    pub modifier: Modifier,
+    pub action: ViewAction,
+    pub help: &'static str, // Empty for another key of the action listed before it
+}
+
+const fn bind(code: KeyCode, action: ViewAction, help: &'static str) -> ViewBinding {
+    ViewBinding {
+        code,
+        modifier: Modifier::Any,
+        action,
+        help,
+    }
+}
+
+const fn ctrl(c: char, action: ViewAction, help: &'static str) -> ViewBinding {
+    ViewBinding {
+        code: KeyCode::Char(c),
+        modifier: Modifier::Control,
+        action,
+        help,
+    }
+}
+
+// Another key for the action of the binding before it
+const fn also(code: KeyCode, action: ViewAction) -> ViewBinding {
+    bind(code, action, "")
+}
+
+// Each view's keys, in the order they are tried and listed. Keys the global keymap takes first
+// (q in most views) are left out, since they never reach the view.
+const FOLDER_PANE_KEYS: &[ViewBinding] = &[
+    bind(
+        KeyCode::Char('j'),
+        ViewAction::Down,
+        "Next folder; its assets are listed once the selection settles",
+    ),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous folder"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Open the folder, or go up on ..",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Go up to the parent folder"),
+    also(KeyCode::Backspace, ViewAction::Back),
+    bind(
+        KeyCode::Char('a'),
+        ViewAction::ListAssets,
+        "List the folder's assets and switch to the assets view",
+    ),
+    bind(
+        KeyCode::Char('='),
+        ViewAction::Compare,
+        "Mark the folder, then = on another folder to compare them",
+    ),
+    bind(
+        KeyCode::Char('S'),
+        ViewAction::Sync,
+        "Sync the folder with a local directory",
+    ),
+    bind(
+        KeyCode::Char('D'),
+        ViewAction::DownloadFolder,
+        "Download the folder and its subfolders, after a summary (again to resume)",
+    ),
+    bind(
+        KeyCode::Char('n'),
+        ViewAction::NewFolder,
+        "Create a folder in the current folder",
+    ),
+    bind(
+        KeyCode::Char('m'),
+        ViewAction::Move,
+        "Move the folder under another parent",
+    ),
+    bind(KeyCode::Char('u'), ViewAction::UploadMode, "Upload mode"),
+    bind(
+        KeyCode::Char('d'),
+        ViewAction::DownloadMode,
+        "Download mode (until a switches to the assets view)",
+    ),
+];
+
+const ASSETS_PANE_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next asset"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous asset"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Details,
+        "Show the asset's details",
+    ),
+    bind(
+        KeyCode::Char('g'),
+        ViewAction::GeometricMatch,
+        "Find geometric matches of the asset",
+    ),
+    bind(
+        KeyCode::Char('d'),
+        ViewAction::Download,
+        "Download the asset (assemblies can include their dependencies)",
+    ),
+    bind(
+        KeyCode::Char('w'),
+        ViewAction::WhereUsed,
+        "List the assemblies that use the asset (Enter jumps to one)",
+    ),
+    bind(
+        KeyCode::Char('='),
+        ViewAction::Compare,
+        "Mark the asset, then = on another asset to compare their metadata",
+    ),
+    bind(
+        KeyCode::Char('b'),
+        ViewAction::Browser,
+        "Open the asset's web page (asset_url in the config)",
+    ),
+    bind(
+        KeyCode::Char(' '),
+        ViewAction::Basket,
+        "Put the asset in the basket, or take it out",
+    ),
+    bind(
+        KeyCode::Char('t'),
+        ViewAction::Tag,
+        "Tag the asset (Space checks a tag, Enter creates one or is done)",
+    ),
+    bind(
+        KeyCode::Char('F'),
+        ViewAction::TagFilter,
+        "List only the assets with a tag; F again lists all of them",
+    ),
+    bind(
+        KeyCode::Char('m'),
+        ViewAction::Move,
+        "Move the asset to another folder",
+    ),
+    bind(KeyCode::Char('r'), ViewAction::Rename, "Rename the asset"),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::EditCells,
+        "Edit the metadata cells of the table in place",
+    ),
+    bind(
+        KeyCode::Char('v'),
+        ViewAction::Report,
+        "Write a Markdown and an HTML report on the asset and its shown matches",
+    ),
+    bind(
+        KeyCode::Char('X'),
+        ViewAction::StageDeletion,
+        "Stage the asset for deletion, or take it off the list",
+    ),
+    also(KeyCode::Delete, ViewAction::StageDeletion),
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::CopyUuid,
+        "Copy the asset's UUID",
+    ),
+    bind(
+        KeyCode::Char('Y'),
+        ViewAction::CopyPath,
+        "Copy the asset's full path",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Go up to the parent folder"),
+    also(KeyCode::Backspace, ViewAction::Back),
+];
+
+const LOG_PANE_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('j'), ViewAction::Down, "Scroll the log down"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Char('k'), ViewAction::Up, "Scroll the log up"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Esc, ViewAction::Back, "Go up to the parent folder"),
+    also(KeyCode::Backspace, ViewAction::Back),
+];
+
+const CELL_EDIT_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Left, ViewAction::Left, "Previous metadata column"),
+    also(KeyCode::Char('h'), ViewAction::Left),
+    bind(KeyCode::Right, ViewAction::Right, "Next metadata column"),
+    also(KeyCode::Char('l'), ViewAction::Right),
+    bind(KeyCode::Up, ViewAction::Up, "Previous asset"),
+    also(KeyCode::Char('k'), ViewAction::Up),
+    bind(KeyCode::Down, ViewAction::Down, "Next asset"),
+    also(KeyCode::Char('j'), ViewAction::Down),
+    bind(KeyCode::Enter, ViewAction::Edit, "Edit the cell"),
+    also(KeyCode::F(2), ViewAction::Edit),
+    bind(KeyCode::Char('q'), ViewAction::Back, "Stop editing cells"),
+    also(KeyCode::Esc, ViewAction::Back),
+];
+
+const CELL_INPUT_KEYS: &[ViewBinding] = &[
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Set the value (pcli2 asset metadata create) and go to the next asset",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Leave the cell unchanged"),
+];
+
+const SEARCH_KEYS: &[ViewBinding] = &[
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Search, or go to the selected result's folder",
+    ),
+    bind(
+        KeyCode::Tab,
+        ViewAction::SwitchFocus,
+        "Move the focus between the query and the results",
+    ),
+    also(KeyCode::BackTab, ViewAction::SwitchFocus),
+    ctrl(
+        's',
+        ViewAction::CycleScope,
+        "Cycle the scope: whole tenant, current folder, folder subtree",
+    ),
+    ctrl(
+        't',
+        ViewAction::CycleMatching,
+        "Cycle matching: plain text, glob (BRKT-10*), regex",
+    ),
+    bind(
+        KeyCode::Up,
+        ViewAction::Up,
+        "Earlier query in the input; the result above in the results",
+    ),
+    bind(
+        KeyCode::Down,
+        ViewAction::Down,
+        "Later query in the input; the result below in the results",
+    ),
+    bind(
+        KeyCode::F(2),
+        ViewAction::SaveSearch,
+        "Save the query under a name",
+    ),
+    bind(KeyCode::F(3), ViewAction::SavedSearches, "Saved searches"),
+    bind(
+        KeyCode::Char('s'),
+        ViewAction::Sort,
+        "Sort the results by the next column",
+    ),
+    bind(
+        KeyCode::Char('S'),
+        ViewAction::ReverseSort,
+        "Reverse the order of the results",
+    ),
+    bind(
+        KeyCode::Left,
+        ViewAction::Left,
+        "Scroll the result columns left (the name stays in view)",
+    ),
+    bind(
+        KeyCode::Right,
+        ViewAction::Right,
+        "Scroll the result columns right",
+    ),
+    bind(
+        KeyCode::Char('i'),
+        ViewAction::Details,
+        "Show the result's details",
+    ),
+    bind(
+        KeyCode::Char('g'),
+        ViewAction::GeometricMatch,
+        "Find geometric matches of the result",
+    ),
+    bind(
+        KeyCode::Char('d'),
+        ViewAction::Download,
+        "Download the result",
+    ),
+    bind(
+        KeyCode::Char('w'),
+        ViewAction::WhereUsed,
+        "List the assemblies that use the result",
+    ),
+    bind(
+        KeyCode::Char('b'),
+        ViewAction::Browser,
+        "Open the result's web page",
+    ),
+    bind(
+        KeyCode::Char('m'),
+        ViewAction::Move,
+        "Move the result to another folder",
+    ),
+    bind(KeyCode::Char('r'), ViewAction::Rename, "Rename the result"),
+    bind(
+        KeyCode::Char('X'),
+        ViewAction::StageDeletion,
+        "Stage the result for deletion, or take it off the list",
+    ),
+    also(KeyCode::Delete, ViewAction::StageDeletion),
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::CopyUuid,
+        "Copy the result's UUID",
+    ),
+    bind(
+        KeyCode::Char('Y'),
+        ViewAction::CopyPath,
+        "Copy the result's full path",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the search dialog"),
+];
+
+const FINDER_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Previous asset"),
+    bind(KeyCode::Down, ViewAction::Down, "Next asset"),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten assets up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten assets down"),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Go to the asset in its folder",
+    ),
+    ctrl('r', ViewAction::SearchServer, "Search the server too"),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the finder"),
+];
+
+const GEOMETRIC_MATCH_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Previous match"),
+    bind(KeyCode::Down, ViewAction::Down, "Next match"),
+    bind(KeyCode::Left, ViewAction::Left, "Scroll the columns left"),
+    bind(
+        KeyCode::Right,
+        ViewAction::Right,
+        "Scroll the columns right",
+    ),
+    bind(
+        KeyCode::Char('='),
+        ViewAction::Compare,
+        "Compare the metadata of the matched asset and the selected match",
+    ),
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::CopyUuid,
+        "Copy the match's UUID",
+    ),
+    bind(
+        KeyCode::Char('Y'),
+        ViewAction::CopyPath,
+        "Copy the match's full path",
+    ),
+    bind(
+        KeyCode::Char(' '),
+        ViewAction::Queue,
+        "Queue the match for review, or take it out of the queue",
+    ),
+    bind(
+        KeyCode::Char('r'),
+        ViewAction::Review,
+        "Review the queued matches (k/d/i decide)",
+    ),
+    bind(
+        KeyCode::Char('>'),
+        ViewAction::MinSimilarity,
+        "Hide matches below a minimum similarity",
+    ),
+    bind(
+        KeyCode::Char('s'),
+        ViewAction::HideSelf,
+        "Hide or show the matched asset itself",
+    ),
+    bind(
+        KeyCode::Char('f'),
+        ViewAction::SameFolder,
+        "Only show matches in the matched asset's folder, or all",
+    ),
+    bind(KeyCode::Char('q'), ViewAction::Back, "Close the results"),
+    also(KeyCode::Esc, ViewAction::Back),
+];
+
+const ASSET_DETAILS_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Scroll up"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Scroll down"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Scroll a page up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Scroll a page down",
+    ),
+    bind(KeyCode::Home, ViewAction::Top, "Back to the top"),
+    bind(
+        KeyCode::Char('n'),
+        ViewAction::Note,
+        "Write a note on the asset",
+    ),
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::Copy,
+        "Copy the details as JSON",
+    ),
+    bind(KeyCode::Char('q'), ViewAction::Back, "Close the details"),
+    also(KeyCode::Esc, ViewAction::Back),
+];
+
+const LOG_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Previous entry"),
+    bind(KeyCode::Down, ViewAction::Down, "Next entry"),
+    bind(KeyCode::Enter, ViewAction::Open, "Show the entry in full"),
+    bind(
+        KeyCode::Char('c'),
+        ViewAction::Copy,
+        "Copy the command of the entry",
+    ),
+    bind(
+        KeyCode::Char('s'),
+        ViewAction::StatusFilter,
+        "Cycle the status filter: all, success, error, cached",
+    ),
+    bind(
+        KeyCode::Char('n'),
+        ViewAction::Notifications,
+        "Show the notification history instead, or back",
+    ),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Export,
+        "Export the log to text and JSON files",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Back to the folders"),
+];
+
+const COMMAND_HISTORY_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Newer command"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Older command"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Enter, ViewAction::Open, "Run the command again"),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::Edit,
+        "Edit the command before running it",
+    ),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Export,
+        "Export the history to text and JSON files",
+    ),
+    bind(
+        KeyCode::Char('s'),
+        ViewAction::ExportScript,
+        "Export the history as a runnable shell script",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Back to the folders"),
+];
+
+const JSON_VIEW_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous line"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next line"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "A page up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "A page down"),
+    bind(KeyCode::Home, ViewAction::Top, "First line"),
+    also(KeyCode::Char('g'), ViewAction::Top),
+    bind(KeyCode::End, ViewAction::Bottom, "Last line"),
+    also(KeyCode::Char('G'), ViewAction::Bottom),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Fold,
+        "Fold or unfold the object or array",
+    ),
+    also(KeyCode::Char(' '), ViewAction::Fold),
+    bind(KeyCode::Char('-'), ViewAction::FoldAll, "Fold everything"),
+    bind(
+        KeyCode::Char('+'),
+        ViewAction::UnfoldAll,
+        "Unfold everything",
+    ),
+    also(KeyCode::Char('='), ViewAction::UnfoldAll),
+    bind(KeyCode::Char('n'), ViewAction::NextHit, "Next search hit"),
+    bind(
+        KeyCode::Char('N'),
+        ViewAction::PreviousHit,
+        "Previous search hit",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the viewer"),
+];
+
+const RAW_OUTPUT_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Scroll up"),
+    bind(KeyCode::Down, ViewAction::Down, "Scroll down"),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Scroll ten lines up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Scroll ten lines down",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Back to the folders"),
+];
+
+const BASKET_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous asset"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next asset"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten assets up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten assets down"),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Remove,
+        "Take the asset out of the basket",
+    ),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(KeyCode::Char('C'), ViewAction::Clear, "Empty the basket"),
+    bind(
+        KeyCode::Char('d'),
+        ViewAction::Download,
+        "Download every asset",
+    ),
+    bind(
+        KeyCode::Char('m'),
+        ViewAction::Move,
+        "Move every asset to another folder",
+    ),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::SetMetadata,
+        "Set a metadata property on every asset",
+    ),
+    bind(
+        KeyCode::Char('v'),
+        ViewAction::Report,
+        "Write a Markdown and an HTML report on every asset",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the basket"),
+];
+
+const DELETIONS_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous asset"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next asset"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten assets up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten assets down"),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Remove,
+        "Keep the asset: take it off the list",
+    ),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(
+        KeyCode::Char('C'),
+        ViewAction::Clear,
+        "Keep them all: empty the list",
+    ),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::Export,
+        "Export the list as CSV and JSON",
+    ),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Export the list, then delete every asset on it (asks first)",
+    ),
+    bind(
+        KeyCode::Esc,
+        ViewAction::Back,
+        "Close the pending deletions",
+    ),
+];
+
+const JOBS_KEYS: &[ViewBinding] = &[bind(KeyCode::Esc, ViewAction::Back, "Close the jobs view")];
+
+const PANE_RESIZE_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Left, ViewAction::Left, "Narrow the active pane"),
+    bind(KeyCode::Right, ViewAction::Right, "Widen the active pane"),
+    bind(KeyCode::Up, ViewAction::Up, "Make the active pane taller"),
+    bind(
+        KeyCode::Down,
+        ViewAction::Down,
+        "Make the active pane shorter",
+    ),
+    bind(KeyCode::Enter, ViewAction::Open, "Keep the new sizes"),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel"),
+];
+
+const UPLOAD_MODE_KEYS: &[ViewBinding] = &[
+    bind(
+        KeyCode::Char('u'),
+        ViewAction::Upload,
+        "Choose a file and upload it to the current folder",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Leave upload mode"),
+];
+
+const DOWNLOAD_MODE_KEYS: &[ViewBinding] =
+    &[bind(KeyCode::Esc, ViewAction::Back, "Leave download mode")];
+
+const SAVED_SEARCHES_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous search"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next search"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Enter, ViewAction::Open, "Run the search"),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::Edit,
+        "Edit the query before searching",
+    ),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Remove,
+        "Delete the saved search",
+    ),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(KeyCode::Esc, ViewAction::Back, "Back to the search dialog"),
+    also(KeyCode::F(3), ViewAction::Back),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const MATCH_REVIEW_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Left, ViewAction::Left, "Previous match"),
+    also(KeyCode::Char('p'), ViewAction::Left),
+    bind(KeyCode::Right, ViewAction::Right, "Next match"),
+    also(KeyCode::Char('n'), ViewAction::Right),
+    bind(KeyCode::Up, ViewAction::Up, "Previous metadata row"),
+    bind(KeyCode::Down, ViewAction::Down, "Next metadata row"),
+    bind(
+        KeyCode::Char('k'),
+        ViewAction::Keep,
+        "Keep: a different part",
+    ),
+    bind(KeyCode::Char('d'), ViewAction::Duplicate, "A duplicate"),
+    bind(KeyCode::Char('i'), ViewAction::Ignore, "Ignore the match"),
+    bind(
+        KeyCode::Char('b'),
+        ViewAction::Browser,
+        "Open the comparison of the two assets (compare_url in the config)",
+    ),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::Export,
+        "Export the decisions so far",
+    ),
+    bind(
+        KeyCode::Esc,
+        ViewAction::Back,
+        "Close; r in the results continues",
+    ),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const MATCH_HISTORY_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous match"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next match"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Reopen the match's results",
+    ),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Remove,
+        "Delete the match from the history",
+    ),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the history"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const WHERE_USED_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous assembly"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next assembly"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Go to the assembly in its folder",
+    ),
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::CopyUuid,
+        "Copy the assembly's UUID",
+    ),
+    bind(
+        KeyCode::Char('Y'),
+        ViewAction::CopyPath,
+        "Copy the assembly's full path",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the list"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const LOG_DETAIL_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Scroll up"),
+    bind(KeyCode::Down, ViewAction::Down, "Scroll down"),
+    bind(KeyCode::Char('c'), ViewAction::Copy, "Copy the command"),
+    bind(
+        KeyCode::Char('e'),
+        ViewAction::ShowError,
+        "Show the full output of the failed command",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the entry"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const ERROR_DETAILS_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Scroll up"),
+    bind(KeyCode::Down, ViewAction::Down, "Scroll down"),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Scroll ten lines up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Scroll ten lines down",
+    ),
+    bind(KeyCode::Home, ViewAction::Top, "Back to the top"),
+    bind(
+        KeyCode::Char('c'),
+        ViewAction::Copy,
+        "Copy the details as text",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the details"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const INTERRUPTED_JOBS_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous job"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next job"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Resume the job where it stopped",
+    ),
+    bind(KeyCode::Char('x'), ViewAction::Remove, "Discard the job"),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(
+        KeyCode::Esc,
+        ViewAction::Back,
+        "Decide later; the jobs are offered again on the next start",
+    ),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const ASSEMBLY_DOWNLOAD_KEYS: &[ViewBinding] = &[
+    bind(
+        KeyCode::Char('y'),
+        ViewAction::Yes,
+        "Download the assembly with its dependencies",
+    ),
+    also(KeyCode::Enter, ViewAction::Yes),
+    bind(
+        KeyCode::Char('n'),
+        ViewAction::No,
+        "Download the assembly only",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Don't download"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const FOLDER_PICKER_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Previous folder"),
+    bind(KeyCode::Down, ViewAction::Down, "Next folder"),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten folders up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten folders down"),
+    bind(KeyCode::Enter, ViewAction::Open, "Pick the folder"),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel"),
+];
+
+const TAG_PICKER_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Up, ViewAction::Up, "Previous tag"),
+    bind(KeyCode::Down, ViewAction::Down, "Next tag"),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten tags up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten tags down"),
+    bind(
+        KeyCode::Char(' '),
+        ViewAction::Toggle,
+        "Give the asset the tag, or take it away",
+    ),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Create the typed tag, or be done; when filtering, list the assets with the tag",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel"),
+];
+
+const CUSTOM_COMMANDS_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous command"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next command"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Run the command on the selected asset",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the menu"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const SYNC_PLAN_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous file"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next file"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten files up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten files down"),
+    bind(
+        KeyCode::Char(' '),
+        ViewAction::Toggle,
+        "Leave the file out of the sync, or put it back",
+    ),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Run the sync as a background job",
+    ),
+    also(KeyCode::Char('x'), ViewAction::Open),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const FOLDER_DIFF_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Left, ViewAction::Left, "Previous column"),
+    also(KeyCode::BackTab, ViewAction::Left),
+    bind(KeyCode::Right, ViewAction::Right, "Next column"),
+    also(KeyCode::Tab, ViewAction::Right),
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous asset"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next asset"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten assets up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten assets down"),
+    bind(
+        KeyCode::Char('c'),
+        ViewAction::CopyAcross,
+        "Copy the asset to the folder it is missing from",
+    ),
+    bind(
+        KeyCode::Char('C'),
+        ViewAction::CopyAllAcross,
+        "Copy every asset of the column to the other folder",
+    ),
+    bind(
+        KeyCode::Char('g'),
+        ViewAction::GeometricMatch,
+        "Pair the assets with different names that are the same model",
+    ),
+    bind(KeyCode::Char('r'), ViewAction::Refresh, "Compare again"),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the comparison"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const METADATA_DIFF_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous property"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next property"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten properties up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Ten properties down",
+    ),
+    bind(
+        KeyCode::Char('d'),
+        ViewAction::OnlyDifferences,
+        "Only show the properties that differ, or all of them",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the comparison"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const PROPERTY_SCHEMA_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous property"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next property"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten properties up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Ten properties down",
+    ),
+    bind(
+        KeyCode::Char('n'),
+        ViewAction::Create,
+        "Define a property (name, or name:type)",
+    ),
+    bind(
+        KeyCode::Char('x'),
+        ViewAction::Remove,
+        "Delete the property from the tenant (asks first)",
+    ),
+    also(KeyCode::Delete, ViewAction::Remove),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the schema"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const VALIDATION_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous violation"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next violation"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten violations up"),
+    bind(
+        KeyCode::PageDown,
+        ViewAction::PageDown,
+        "Ten violations down",
+    ),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Go to the asset in its folder",
+    ),
+    bind(KeyCode::Esc, ViewAction::Back, "Close the report"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const METADATA_IMPORT_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous row"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next row"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::PageUp, ViewAction::PageUp, "Ten rows up"),
+    bind(KeyCode::PageDown, ViewAction::PageDown, "Ten rows down"),
+    bind(
+        KeyCode::Enter,
+        ViewAction::Open,
+        "Apply the changes as a background job",
+    ),
+    also(KeyCode::Char('y'), ViewAction::Open),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel the import"),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+const PROFILE_PICKER_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('k'), ViewAction::Up, "Previous profile"),
+    also(KeyCode::Up, ViewAction::Up),
+    bind(KeyCode::Char('j'), ViewAction::Down, "Next profile"),
+    also(KeyCode::Down, ViewAction::Down),
+    bind(KeyCode::Enter, ViewAction::Open, "Use the profile"),
+    bind(
+        KeyCode::Esc,
+        ViewAction::Back,
+        "Go on without a profile, with pcli2 as it is set up",
+    ),
+];
+
+const CONFIRM_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Char('y'), ViewAction::Yes, "Go ahead"),
+    bind(KeyCode::Char('n'), ViewAction::Back, "Don't"),
+    also(KeyCode::Esc, ViewAction::Back),
+    also(KeyCode::Char('q'), ViewAction::Back),
+];
+
+// Any other key edits the text, as TextInput has it
+const PROMPT_KEYS: &[ViewBinding] = &[
+    bind(KeyCode::Enter, ViewAction::Open, "Accept the text"),
+    bind(KeyCode::Esc, ViewAction::Back, "Cancel"),
+];
+
+// A context's keys: what to press and what it does
+#[derive(Debug, Clone)]
+pub struct KeySection {
+    pub context: KeyContext,
+    pub keys: Vec<(String, String)>,
+}
+
+// Every key the app handles, by context. Global bindings for the same action are listed
+// together (F5 / Ctrl+R), and custom commands with a key join the Assets pane.
+pub fn sections(custom_commands: &[CustomCommand]) -> Vec<KeySection> {
+    CONTEXTS
+        .iter()
+        .map(|&context| {
+            let mut keys: Vec<(String, String)> = Vec::new();
+            let mut actions = Vec::new();
+            for binding in GLOBAL_KEYS
+                .iter()
+                .filter(|binding| KeyContext::of_binding(binding) == context)
+            {
+                match actions.iter().position(|action| *action == binding.action) {
+                    // BackTab and Shift+Tab are the same key to the user
+                    Some(i) if !keys[i].0.split(" / ").any(|key| key == binding.label()) => {
+                        let label = &mut keys[i].0;
+                        label.push_str(" / ");
+                        label.push_str(&binding.label());
+                    }
+                    Some(_) => {}
+                    None => {
+                        actions.push(binding.action);
+                        keys.push((binding.label(), binding.action.description().to_string()));
+                    }
+                }
+            }
+            for binding in context.bindings() {
+                match (binding.help, keys.last_mut()) {
+                    ("", Some((label, _))) => {
+                        label.push_str(" / ");
+                        label.push_str(&binding.label());
+                    }
+                    (help, _) => keys.push((binding.label(), help.to_string())),
+                }
+            }
+            if context == KeyContext::AssetsPane {
+                keys.extend(custom_commands.iter().filter_map(|command| {
+                    command
+                        .key
+                        .map(|key| (key.to_string(), format!("Run \"{}\"", command.name)))
+                }));
+            }
+            KeySection { context, keys }
+        })
+        .collect()
+}
+
+// A key in the hint bar: the key cap, what it does and, unless it stands for several keys
+// (j/k), the key a click on it presses
+#[derive(Debug, Clone)]
+pub struct Hint {
+    pub cap: String,
+    pub label: String,
+    pub key: Option<KeyEvent>,
+}
+
+impl Hint {
+    fn key(code: KeyCode, label: &str) -> Self {
+        Self {
+            cap: action::key_label(code),
+            label: label.to_string(),
+            key: Some(KeyEvent::new(code, KeyModifiers::NONE)),
+        }
+    }
+
+    // The hint, unless the user lacks the permission its key needs
+    fn permitted(self, permission: Permission) -> Option<Self> {
+        permissions::denied(permission).is_none().then_some(self)
+    }
+
+    // The first key the global keymap binds to the action
+    fn global(action: Action, label: &str) -> Option<Self> {
+        let binding = GLOBAL_KEYS
+            .iter()
+            .find(|binding| binding.action == action)?;
+        Some(Self {
+            cap: binding.label(),
+            label: label.to_string(),
+            key: Some(binding.modifier.key_event(binding.code)),
+        })
+    }
+
+    // The first key the view binds to the action
+    fn view(context: KeyContext, action: ViewAction, label: &str) -> Option<Self> {
+        let binding = context.first_binding(action)?;
+        Some(Self {
+            cap: binding.label(),
+            label: label.to_string(),
+            key: Some(binding.modifier.key_event(binding.code)),
+        })
+    }
+
+    // The first keys the view binds to each of the actions, e.g. "j/k" for moving down and up
+    fn views(context: KeyContext, actions: &[ViewAction], label: &str) -> Option<Self> {
+        let caps: Option<Vec<String>> = actions
+            .iter()
+            .map(|&action| context.first_binding(action).map(ViewBinding::label))
+            .collect();
+        Some(Self {
+            cap: caps?.join("/"),
+            label: label.to_string(),
+            key: None,
+        })
+    }
+}
+
+impl KeyContext {
+    // The most used keys of the view, for the hint bar. The keys come from the view's table, so
+    // the hints follow its bindings.
+    pub fn hints(self, custom_commands: &[CustomCommand]) -> Vec<Hint> {
+        use ViewAction::*;
+
+        let global = Hint::global;
+        let view = |action, label| Hint::view(self, action, label);
+        let views = |actions: &[ViewAction], label| Hint::views(self, actions, label);
+        let mut hints = match self {
+            KeyContext::FolderPane => vec![
+                global(Action::NextPane, "switch"),
+                views(&[Down, Up], "nav"),
+                view(Open, "open"),
+                view(ListAssets, "assets"),
+                view(NewFolder, "new folder")
+                    .and_then(|hint| hint.permitted(Permission::CreateFolder)),
+                view(Move, "move"),
+                view(Compare, "compare"),
+                view(Sync, "sync"),
+                global(Action::OpenSearch, "search"),
+            ],
+            KeyContext::AssetsPane => vec![
+                global(Action::NextPane, "switch"),
+                views(&[Down, Up], "nav"),
+                view(Details, "details"),
+                view(GeometricMatch, "geom-match"),
+                view(WhereUsed, "where-used"),
+                view(Move, "move"),
+                view(Rename, "rename"),
+                view(EditCells, "edit cells")
+                    .and_then(|hint| hint.permitted(Permission::EditMetadata)),
+                view(Compare, "compare"),
+                view(Report, "report"),
+                view(Tag, "tag"),
+                view(TagFilter, "tag filter"),
+                view(Basket, "basket"),
+                view(StageDeletion, "stage delete")
+                    .and_then(|hint| hint.permitted(Permission::Delete)),
+                views(&[CopyUuid, CopyPath], "copy uuid/path"),
+                global(Action::OpenSearch, "search"),
+                global(Action::SearchRevisions, "revisions"),
+            ],
+            KeyContext::LogPane => vec![
+                global(Action::NextPane, "switch"),
+                views(&[Down, Up], "scroll"),
+                global(Action::ShowLog, "log view"),
+                global(Action::ToggleLogPane, "hide log"),
+                global(Action::OpenSearch, "search"),
+            ],
+            KeyContext::CellEdit => vec![
+                views(&[Left, Right], "column"),
+                views(&[Up, Down], "asset"),
+                view(Edit, "edit"),
+                view(Back, "done"),
+            ],
+            KeyContext::CellInput => vec![view(Open, "set"), view(Back, "cancel")],
+            KeyContext::Search => vec![
+                view(Open, "search/go to"),
+                view(Back, "cancel"),
+                view(CycleScope, "scope"),
+                view(CycleMatching, "text/glob/regex"),
+                views(&[Up, Down], "history/nav"),
+                view(SaveSearch, "save"),
+                view(SavedSearches, "saved"),
+                views(&[Sort, ReverseSort], "sort"),
+                views(&[Left, Right], "columns"),
+                view(Details, "details"),
+                view(GeometricMatch, "geom-match"),
+                view(Download, "download"),
+                view(WhereUsed, "where-used"),
+                view(Browser, "browser"),
+                view(Move, "move"),
+                view(Rename, "rename"),
+                view(StageDeletion, "stage delete")
+                    .and_then(|hint| hint.permitted(Permission::Delete)),
+                views(&[CopyUuid, CopyPath], "copy uuid/path"),
+            ],
+            KeyContext::Log => vec![
+                views(&[Up, Down], "scroll"),
+                view(StatusFilter, "status"),
+                global(Action::FilterLog, "filter"),
+                view(Open, "details"),
+                view(Copy, "copy"),
+                view(Export, "export"),
+                view(Notifications, "notifications"),
+                view(Back, "back"),
+            ],
+            KeyContext::CommandHistory => vec![
+                views(&[Down, Up], "nav"),
+                view(Open, "run"),
+                view(Edit, "edit"),
+                view(Export, "export"),
+                view(ExportScript, "export script"),
+                view(Back, "close"),
+            ],
+            KeyContext::PaneResize => vec![
+                views(&[Left, Right, Up, Down], "resize"),
+                view(Open, "ok"),
+                view(Back, "cancel"),
+            ],
+            KeyContext::JsonView => vec![
+                views(&[Down, Up], "nav"),
+                view(Fold, "fold"),
+                views(&[FoldAll, UnfoldAll], "fold/unfold all"),
+                global(Action::SearchJson, "search"),
+                views(&[NextHit, PreviousHit], "next/prev"),
+                view(Back, "close"),
+            ],
+            KeyContext::RawOutput => vec![
+                views(&[Up, Down, PageUp, PageDown], "scroll"),
+                global(Action::OpenCommandLine, "new command"),
+                view(Back, "close"),
+            ],
+            KeyContext::Basket => vec![
+                views(&[Down, Up], "nav"),
+                view(Download, "download all"),
+                view(Move, "move all"),
+                view(SetMetadata, "set metadata")
+                    .and_then(|hint| hint.permitted(Permission::EditMetadata)),
+                view(Report, "report"),
+                view(Remove, "remove"),
+                view(Clear, "clear"),
+                view(Back, "close"),
+            ],
+            KeyContext::Deletions => vec![
+                views(&[Down, Up], "nav"),
+                view(Open, "delete all").and_then(|hint| hint.permitted(Permission::Delete)),
+                view(Export, "export"),
+                view(Remove, "keep"),
+                view(Clear, "keep all"),
+                view(Back, "close"),
+            ],
+            KeyContext::Jobs => vec![view(Back, "close")],
+            KeyContext::UploadMode => vec![view(Upload, "upload a file"), view(Back, "leave")],
+            KeyContext::DownloadMode => vec![view(Back, "leave")],
+            // Dialogs show their keys in their own borders
+            KeyContext::Global
+            | KeyContext::Browsing
+            | KeyContext::SavedSearches
+            | KeyContext::Finder
+            | KeyContext::GeometricMatch
+            | KeyContext::MatchReview
+            | KeyContext::MatchHistory
+            | KeyContext::AssetDetails
+            | KeyContext::WhereUsed
+            | KeyContext::LogDetail
+            | KeyContext::ErrorDetails
+            | KeyContext::InterruptedJobs
+            | KeyContext::AssemblyDownload
+            | KeyContext::FolderPicker
+            | KeyContext::TagPicker
+            | KeyContext::CustomCommands
+            | KeyContext::SyncPlan
+            | KeyContext::FolderDiff
+            | KeyContext::MetadataDiff
+            | KeyContext::PropertySchema
+            | KeyContext::Validation
+            | KeyContext::MetadataImport
+            | KeyContext::ProfilePicker
+            | KeyContext::Confirm
+            | KeyContext::Prompt => Vec::new(),
+        };
+        if self == KeyContext::AssetsPane {
+            hints.extend(custom_commands.iter().map(|command| {
+                command
+                    .key
+                    .map(|key| Hint::key(KeyCode::Char(key), &command.name))
+            }));
+        }
+        if matches!(
+            self,
+            KeyContext::FolderPane | KeyContext::AssetsPane | KeyContext::LogPane
+        ) {
+            hints.push(global(Action::ShowHelp, "help"));
+            hints.push(global(Action::Quit, "quit"));
+        }
+        hints.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The state each view with a table of its own is shown in, when it isn't a dialog
+    const VIEWS: [(KeyContext, AppState); 13] = [
+        (KeyContext::FolderPane, AppState::Folders),
+        (KeyContext::AssetsPane, AppState::Assets),
+        (KeyContext::LogPane, AppState::Folders),
+        (KeyContext::Log, AppState::Log),
+        (KeyContext::CommandHistory, AppState::CommandHistory),
+        (KeyContext::JsonView, AppState::JsonView),
+        (KeyContext::RawOutput, AppState::RawOutput),
+        (KeyContext::Basket, AppState::Basket),
+        (KeyContext::Deletions, AppState::Deletions),
+        (KeyContext::Jobs, AppState::Jobs),
+        (KeyContext::PaneResize, AppState::PaneResize),
+        (KeyContext::UploadMode, AppState::Uploading),
+        (KeyContext::DownloadMode, AppState::Downloading),
+    ];
+
+    fn event(binding: &ViewBinding) -> KeyEvent {
+        binding.modifier.key_event(binding.code)
+    }
+
+    #[test]
+    fn other_keys_follow_their_action() {
+        for context in CONTEXTS {
+            let mut previous: Option<&ViewBinding> = None;
+            for binding in context.bindings() {
+                if binding.help.is_empty() {
+                    let action = previous.map(|previous| previous.action);
+                    assert_eq!(action, Some(binding.action), "{:?} {:?}", context, binding);
+                }
+                previous = Some(binding);
+            }
+        }
+    }
+
+    #[test]
+    fn every_view_key_does_what_it_is_listed_for() {
+        for context in CONTEXTS {
+            for binding in context.bindings() {
+                assert_eq!(
+                    context.action(event(binding)),
+                    Some(binding.action),
+                    "{:?} {}",
+                    context,
+                    binding.label()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn global_keys_leave_the_view_keys_alone() {
+        for (context, state) in VIEWS {
+            for binding in context.bindings() {
+                let global = action::global_action(event(binding), &state);
+                assert_eq!(global, None, "{:?} {}", context, binding.label());
+            }
+        }
+    }
+
+    #[test]
+    fn every_hint_is_a_key_of_its_view() {
+        for context in CONTEXTS {
+            let state = VIEWS
+                .iter()
+                .find(|(view, _)| *view == context)
+                .map(|(_, state)| *state);
+            for hint in context.hints(&[]) {
+                let Some(key) = hint.key else {
+                    continue;
+                };
+                let handled = context.action(key).is_some()
+                    || state.is_some_and(|state| action::global_action(key, &state).is_some());
+                assert!(handled, "{:?} {} {}", context, hint.cap, hint.label);
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Local};
+use std::time::Duration;
+
+use crate::app::ErrorDetail;
+use crate::pcli_commands::{JobCommand, PcliCommand};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogStatus {
+    Success,
+    Error,
+    Warning,
+    Timeout,
+    Retry,
+    Cached,
+}
+
+impl LogStatus {
+    pub fn icon(self) -> &'static str {
+        match self {
+            LogStatus::Success => "✓",
+            LogStatus::Error => "✗",
+            LogStatus::Warning => "⚠",
+            LogStatus::Timeout => "⏱",
+            LogStatus::Retry => "↻",
+            LogStatus::Cached => "🗂️",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogStatus::Success => "SUCCESS",
+            LogStatus::Error => "ERROR",
+            LogStatus::Warning => "WARNING",
+            LogStatus::Timeout => "TIMEOUT",
+            LogStatus::Retry => "RETRY",
+            LogStatus::Cached => "CACHED",
+        }
+    }
+}
+
+// One entry in the command log
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Local>,
+    pub status: LogStatus,
+    pub command: String,            // The pcli2 command line the entry is about
+    pub duration: Option<Duration>, // How long the command ran, when it actually ran
+    pub exit_code: Option<i32>,     // How the process exited, when it ran to the end
+    pub message: Option<String>,    // Short explanation, e.g. the error or retry reason
+    pub error: Option<ErrorDetail>, // Full output of a failed command
+}
+
+impl LogEntry {
+    pub fn new(status: LogStatus, command: impl Into<String>) -> Self {
+        Self {
+            timestamp: Local::now(),
+            status,
+            command: command.into(),
+            duration: None,
+            exit_code: None,
+            message: None,
+            error: None,
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_error(mut self, error: ErrorDetail) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    // Command, duration, exit code and message without the timestamp or status
+    pub fn description(&self) -> String {
+        let mut text = self.command.clone();
+        match (self.duration, self.exit_code) {
+            (Some(duration), Some(code)) => {
+                text.push_str(&format!(" ({:.1}s, exit {})", duration.as_secs_f64(), code));
+            }
+            (Some(duration), None) => text.push_str(&format!(" ({:.1}s)", duration.as_secs_f64())),
+            (None, Some(code)) => text.push_str(&format!(" (exit {})", code)),
+            (None, None) => {}
+        }
+        if let Some(ref message) = self.message {
+            text.push_str(" - ");
+            text.push_str(message);
+        }
+        text
+    }
+
+    // Status and description, e.g. "✓ SUCCESS: pcli2 folder list --format json (0.8s, exit 0)"
+    pub fn summary_without_timestamp(&self) -> String {
+        format!(
+            "{} {}: {}",
+            self.status.icon(),
+            self.status.label(),
+            self.description()
+        )
+    }
+
+    // Single-line rendering used for filtering and plain-text output
+    pub fn summary(&self) -> String {
+        format!(
+            "[{}] {}",
+            self.timestamp.format("%H:%M:%S"),
+            self.summary_without_timestamp()
+        )
+    }
+}
+
+// A command in the command history, with how it went once its outcome was logged
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub command: PcliCommand,
+    pub status: Option<LogStatus>, // None until it finished
+    pub duration: Option<Duration>,
+    pub exit_code: Option<i32>,
+}
+
+impl HistoryEntry {
+    pub fn new(command: PcliCommand) -> Self {
+        Self {
+            command,
+            status: None,
+            duration: None,
+            exit_code: None,
+        }
+    }
+
+    // A command a batch job ran, which has already finished
+    pub fn ran(run: JobCommand) -> Self {
+        Self {
+            status: Some(if run.succeeded() {
+                LogStatus::Success
+            } else {
+                LogStatus::Error
+            }),
+            duration: run.duration,
+            exit_code: run.exit_code,
+            command: run.command,
+        }
+    }
+
+    // Take the outcome from the log entry of the command, unless it is about a retried attempt
+    // or a warning rather than how the command ended
+    pub fn finish(&mut self, entry: &LogEntry) {
+        if self.status.is_some()
+            || matches!(entry.status, LogStatus::Retry | LogStatus::Warning)
+            || entry.command != self.command.to_string()
+        {
+            return;
+        }
+        self.status = Some(entry.status);
+        self.duration = entry.duration;
+        self.exit_code = entry.exit_code;
+    }
+
+    // Status and duration, e.g. "✓ 1.8s"; empty while the command runs
+    pub fn outcome(&self) -> String {
+        match (self.status, self.duration) {
+            (Some(status), Some(duration)) => {
+                format!("{} {:.1}s", status.icon(), duration.as_secs_f64())
+            }
+            (Some(status), None) => status.icon().to_string(),
+            (None, _) => String::new(),
+        }
+    }
+}
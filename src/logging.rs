@@ -0,0 +1,156 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::config::LoggingConfig;
+
+const LOG_FILE_NAME: &str = "pcli2-tui.log";
+
+// Directory holding the log files, following the XDG base directory convention
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+
+    Some(base.join("pcli2-tui"))
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| anyhow!("Unknown log level '{}'", level))
+}
+
+// Shift pcli2-tui.log -> .1 -> .2 ..., dropping the oldest
+fn rotate(path: &Path, max_files: usize) -> io::Result<()> {
+    let rotated = |n: usize| path.with_extension(format!("log.{}", n));
+
+    if max_files == 0 {
+        return fs::remove_file(path);
+    }
+
+    let _ = fs::remove_file(rotated(max_files));
+    for n in (1..max_files).rev() {
+        if rotated(n).exists() {
+            fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+// The log file, rotated as soon as it grows past the size limit, so a long session doesn't
+// keep writing to one file
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_file_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, config: &LoggingConfig) -> io::Result<Self> {
+        let mut file = Self {
+            file: open(&path)?,
+            path,
+            size: 0,
+            max_file_bytes: config.max_file_bytes,
+            max_files: config.max_files,
+        };
+        file.size = file.file.metadata()?.len();
+        if file.size >= file.max_file_bytes {
+            file.rotate()?;
+        }
+        Ok(file)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        rotate(&self.path, self.max_files)?;
+        self.file = open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Each event is written at once, so it never straddles two files
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_file_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+// Send tracing events to the log file; returns the file path, or None when logging is off
+pub fn init(config: &LoggingConfig) -> Result<Option<PathBuf>> {
+    let level = parse_level(&config.level)?;
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+
+    let dir = state_dir().ok_or_else(|| anyhow!("Cannot determine the state directory"))?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+
+    let path = dir.join(LOG_FILE_NAME);
+    let file = RotatingFile::open(path.clone(), config)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(level)
+        .try_init()
+        .map_err(|e| anyhow!("Failed to initialize logging: {}", e))?;
+
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_file_is_rotated_once_it_would_grow_past_the_limit() {
+        let dir = std::env::temp_dir().join(format!("pcli2-tui-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+        let config = LoggingConfig {
+            level: "info".to_string(),
+            max_file_bytes: 10,
+            max_files: 2,
+        };
+        let mut file = RotatingFile::open(path.clone(), &config).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap_or_default();
+        let files = [
+            read(path.clone()),
+            read(path.with_extension("log.1")),
+            read(path.with_extension("log.2")),
+            read(path.with_extension("log.3")),
+        ];
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files, ["fourth\n", "third\n", "second\n", ""]);
+    }
+}
@@ -1,29 +1,186 @@
 use anyhow::Result;
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, MouseEventKind,
+    },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    style::Print,
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
+    },
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io;
+use std::time::{Duration, Instant};
 
+mod action;
+mod alert;
 mod app;
+mod assembly;
+mod asset_table;
+mod basket;
+mod cell_edit;
+mod config;
+mod csv_import;
+mod demo;
+mod dialog;
+mod export;
+mod external;
+mod finder;
+mod folder_diff;
+mod folder_download;
+mod folder_picker;
+mod geometry;
+mod help;
+mod hooks;
+mod jobs;
+mod journal;
+mod json_view;
+mod keymap;
+mod log_entry;
+mod logging;
+mod match_history;
+mod metadata_diff;
+mod metrics;
+mod modal;
+mod notes;
+mod notifications;
+mod offline;
 mod pcli_commands;
+mod permissions;
+mod pipeline;
+mod prefetch;
+mod preview;
+mod processing;
+mod rate_limit;
+mod report;
+mod review;
+mod schema;
+mod script;
+mod search_pattern;
+mod setup;
+mod sync;
+mod tags;
+mod text;
+mod text_input;
+mod theme;
+mod tour;
 mod ui;
+mod undo;
+mod validation;
 
 use app::App;
+use config::Config;
+
+// How often the busy indicator is redrawn while a pcli2 command runs
+const BUSY_TICK_MS: u64 = 100;
+
+// How often an interruptible handler checks for new input
+const INPUT_POLL_MS: u64 = 16;
+
+// Save the window title on the terminal's title stack, and put it back (xterm's window
+// operations, which terminals without a title stack ignore)
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Run the steps of a JSON script without the terminal UI ("-" reads it from stdin)
+    #[arg(long, value_name = "FILE")]
+    script: Option<String>,
+
+    /// Browse a bundled demo tenant instead of running pcli2
+    #[arg(long)]
+    demo: bool,
+
+    /// Browse the folders cached by earlier sessions without running pcli2
+    #[arg(long)]
+    offline: bool,
+
+    /// Run the first-start setup and tour again
+    #[arg(long)]
+    setup: bool,
+
+    /// Use a profile of the config file (its tenant, pcli2 and start folder) instead of picking one
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // load the user configuration before touching the terminal so errors print normally
+    let first_start = Config::path().is_some_and(|path| !path.exists());
+    let mut config = Config::load()?;
+    if let Some(ref profile) = cli.profile {
+        config.use_profile(profile)?;
+    }
+    // Several profiles and none given: the user picks one before anything is loaded
+    let pick_profile = cli.profile.is_none() && !config.profiles.is_empty() && !cli.demo;
+
+    // File logging is best effort; a failure is reported in the UI once it is up
+    let logging_result = logging::init(&config.logging);
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "pcli2-tui starting");
+    if cli.demo {
+        demo::enable();
+    }
+    if cli.offline {
+        offline::enable();
+    }
+
+    // Headless: no terminal UI, one JSON line per step on stdout
+    if let Some(ref script) = cli.script {
+        if !script::run(script, &config).await? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        Print(PUSH_TITLE),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let app = App::new();
+    let mut app = App::new(config);
+    if let Err(e) = logging_result {
+        app.notify(
+            notifications::NotificationLevel::Warning,
+            format!("File logging disabled: {:#}", e),
+        );
+    }
+    if cli.demo {
+        app.notify(
+            notifications::NotificationLevel::Info,
+            "Demo mode: browsing bundled sample data; changes are not saved".to_string(),
+        );
+    }
+    if cli.offline {
+        app.notify(
+            notifications::NotificationLevel::Info,
+            "Offline: browsing the folders cached by earlier sessions; changes are disabled"
+                .to_string(),
+        );
+    }
+    // New users are guided through the setup; the demo and offline modes have nothing to set up
+    if cli.setup || (first_start && !cli.demo && !cli.offline) {
+        app.start_setup();
+    }
+    if pick_profile {
+        app.open_profile_picker();
+    }
     let res = run_app(&mut terminal, app).await;
 
     // restore terminal
@@ -31,7 +188,9 @@ async fn main() -> Result<()> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableFocusChange,
+        Print(POP_TITLE)
     )?;
     terminal.show_cursor()?;
 
@@ -42,42 +201,170 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+// Draw the app and keep the frame, so a busy indicator can be drawn over it while a handler runs
+fn snapshot(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    app: &mut App,
+) -> Result<ui::BusyFrame> {
+    let buffer = terminal.draw(|f| ui::draw(f, app))?.buffer.clone();
+    Ok(ui::BusyFrame {
+        buffer,
+        pane: app.active_pane_area,
+        theme: app.theme,
+        show_log: app.show_log_pane,
+    })
+}
+
+// Await a handler that holds the app, animating the busy indicator whenever pcli2 is running.
+// The regular draw can't run until the handler returns, so this redraws the last frame instead.
+// An interruptible handler is abandoned as soon as new input arrives, and that input is returned.
+async fn with_busy_indicator(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    frame: ui::BusyFrame,
+    handler: impl Future<Output = ()>,
+    interruptible: bool,
+) -> Result<Option<Event>> {
+    tokio::pin!(handler);
+    let mut ticker = tokio::time::interval(Duration::from_millis(BUSY_TICK_MS));
+    let mut input = tokio::time::interval(Duration::from_millis(INPUT_POLL_MS));
+
+    loop {
+        tokio::select! {
+            _ = &mut handler => return Ok(None),
+            _ = ticker.tick() => {
+                if let Some(running) = pcli_commands::running_command() {
+                    let transfer = pcli_commands::transfer_progress();
+                    terminal.draw(|f| ui::draw_busy(f, &frame, &running, transfer.as_ref()))?;
+                }
+            }
+            _ = input.tick(), if interruptible => {
+                if event::poll(Duration::ZERO)? {
+                    match event::read()? {
+                        Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved => {}
+                        event @ (Event::Key(_) | Event::Mouse(_)) => return Ok(Some(event)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Handle input that interrupted a handler; returns true when the app should quit
+async fn handle_interrupting_event(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    app: &mut App,
+    event: Event,
+) -> Result<bool> {
+    let frame = snapshot(terminal, app)?;
+    match event {
+        Event::Key(key) => {
+            with_busy_indicator(terminal, frame, app.handle_key_event(key), false).await?;
+        }
+        Event::Mouse(mouse) => {
+            with_busy_indicator(terminal, frame, app.handle_mouse_event(mouse), false).await?;
+        }
+        _ => {}
+    }
+    Ok(app.should_quit)
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     mut app: App,
 ) -> Result<()> {
-    // Load initial folder data
-    app.load_folders_for_current_context().await;
-
-    // Pre-fetch assets for the first folder if available
-    if !app.folders.is_empty() {
-        // Select the first folder (skip parent indicator if present)
-        if app.folders[0].uuid == ".." && app.folders.len() > 1 {
-            app.selected_folder_index = 1;
-        } else {
-            app.selected_folder_index = 0;
-        }
-
-        // Load assets for the selected folder
-        app.load_assets_for_selected_folder().await;
+    // Load initial folder data, unless it waits for a profile to be picked
+    if app.profile_picker.is_none() {
+        let frame = snapshot(terminal, &mut app)?;
+        with_busy_indicator(terminal, frame, app.load_start(), false).await?;
     }
 
+    let mut title = String::new(); // The window title last set
     loop {
-        terminal.draw(|f| ui::draw(f, &mut app))?;
+        // Run a program the last action asked for (e.g. a viewer) with the TUI suspended
+        if let Some(command) = app.take_external_command() {
+            let result = external::run(terminal, &command).await;
+            app.external_command_finished(&command, result);
+            app.previewer.invalidate();
+            title.clear(); // The program may have set a title of its own
+        }
+
+        app.apply_prefetched();
+        app.report_hook_failures();
+        app.report_finished_jobs();
+        app.update_preview();
+        app.update_geometry();
+        app.update_folder_diff();
+        app.update_pipeline();
+        app.update_finder();
+        app.update_offline();
+        app.update_rate_limit();
+        let started = Instant::now();
+        let mut build = Duration::ZERO;
+        terminal.draw(|f| {
+            ui::draw(f, &mut app);
+            build = started.elapsed();
+        })?;
+        app.metrics.frame_drawn(started.elapsed(), build);
+
+        let window_title = app.window_title();
+        if window_title != title {
+            execute!(terminal.backend_mut(), SetTitle(&window_title))?;
+            title = window_title;
+        }
+
+        // Thumbnails shown with a graphics protocol go on top of the frame
+        let graphics = app.preview_graphics.clone();
+        if app.previewer.present(terminal.backend_mut(), graphics)? {
+            terminal.clear()?;
+            continue;
+        }
 
-        if event::poll(std::time::Duration::from_millis(16))? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') {
+        // Load the selected folder's assets once the selection has settled. New input abandons
+        // the load (killing pcli2), so scrolling through the folders stays smooth.
+        if app.selection_load_due() {
+            let frame = snapshot(terminal, &mut app)?;
+            let load = app.load_assets_for_selected_folder();
+            if let Some(event) = with_busy_indicator(terminal, frame, load, true).await? {
+                app.cancel_selection_load();
+                if handle_interrupting_event(terminal, &mut app, event).await? {
+                    app.save_cache();
                     return Ok(());
                 }
+                continue;
+            }
+        }
 
-                app.handle_key_event(key).await;
-            } else if let Event::Mouse(mouse) = event::read()? {
-                app.handle_mouse_event(mouse).await;
+        // One read per event, so a mouse event isn't taken for a key that never came
+        if event::poll(app.tick())? {
+            let event = event::read()?;
+            if !matches!(event, Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved) {
+                app.metrics.event_read();
+            }
+            if matches!(event, Event::Key(_) | Event::Mouse(_)) {
+                app.input_received();
+            }
+            match event {
+                Event::FocusGained => app.focus_changed(true),
+                Event::FocusLost => app.focus_changed(false),
+                Event::Key(key) => {
+                    let frame = snapshot(terminal, &mut app)?;
+                    with_busy_indicator(terminal, frame, app.handle_key_event(key), false).await?;
+                }
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved => {
+                    app.hover(ratatui::layout::Position::new(mouse.column, mouse.row));
+                }
+                Event::Mouse(mouse) => {
+                    let frame = snapshot(terminal, &mut app)?;
+                    with_busy_indicator(terminal, frame, app.handle_mouse_event(mouse), false)
+                        .await?;
+                }
+                _ => {}
             }
         }
 
         if app.should_quit {
+            app.save_cache();
             return Ok(());
         }
     }
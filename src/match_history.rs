@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::app::Asset;
+use crate::config::data_dir;
+use crate::review::ReviewPair;
+
+const MATCHES_DIR_NAME: &str = "matches";
+
+// A geometric match run with everything needed to pick it up again later: the results, how
+// they were filtered, the review queue and the decisions made so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSession {
+    pub created: DateTime<Local>,
+    pub source_uuid: String,
+    pub source: Option<Asset>, // The matched asset, when it was known
+    pub source_folder: Option<String>,
+    pub min_similarity: Option<f64>,
+    pub hide_self: bool,
+    pub hide_same_folder: bool,
+    pub results: Vec<(Asset, f64)>,
+    pub queue: Vec<String>, // UUIDs of the queued matches, in the order queued
+    pub decisions: Vec<ReviewPair>, // The review of the queue, if one was started
+}
+
+impl MatchSession {
+    // File name, unique per run: when it started and which asset it matched
+    fn file_name(&self) -> String {
+        let uuid: String = self.source_uuid.chars().take(8).collect();
+        format!("{}-{}.json", self.created.format("%Y%m%d-%H%M%S"), uuid)
+    }
+
+    pub fn label(&self) -> &str {
+        self.source
+            .as_ref()
+            .map_or(self.source_uuid.as_str(), |source| source.name.as_str())
+    }
+
+    pub fn decided(&self) -> usize {
+        self.decisions
+            .iter()
+            .filter(|pair| pair.decision.is_some())
+            .count()
+    }
+}
+
+fn sessions_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(MATCHES_DIR_NAME))
+}
+
+// Write the session, replacing the earlier state of the same run; without a data directory
+// sessions are not kept
+pub fn save(session: &MatchSession) -> Result<()> {
+    let Some(dir) = sessions_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(session.file_name());
+    let contents = serde_json::to_string(session)?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write match session {}", path.display()))
+}
+
+// The saved match sessions, newest first
+#[derive(Debug, Default)]
+pub struct MatchHistory {
+    pub sessions: Vec<MatchSession>,
+    pub selected: usize,
+    pub unreadable: usize, // Files that could not be read or parsed, e.g. from an older version
+}
+
+impl MatchHistory {
+    pub fn load() -> Result<MatchHistory> {
+        let Some(dir) = sessions_dir() else {
+            anyhow::bail!("No data directory (HOME is not set)");
+        };
+        let mut history = MatchHistory::default();
+        if !dir.exists() {
+            return Ok(history);
+        }
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let session = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<MatchSession>(&contents).ok());
+            match session {
+                Some(session) => history.sessions.push(session),
+                None => history.unreadable += 1,
+            }
+        }
+        history
+            .sessions
+            .sort_by_key(|session| std::cmp::Reverse(session.created));
+        Ok(history)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.sessions.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn chosen(&self) -> Option<&MatchSession> {
+        self.sessions.get(self.selected)
+    }
+
+    // Delete the selected session's file and take it off the list
+    pub fn remove_selected(&mut self) -> Result<()> {
+        let Some(session) = self.sessions.get(self.selected) else {
+            return Ok(());
+        };
+        if let Some(dir) = sessions_dir() {
+            let path = dir.join(session.file_name());
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        self.sessions.remove(self.selected);
+        self.move_selection(0);
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// One metadata property of the two assets; None where an asset doesn't have it
+#[derive(Debug, Clone)]
+pub struct DiffRow {
+    pub key: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+impl DiffRow {
+    pub fn differs(&self) -> bool {
+        self.a != self.b
+    }
+}
+
+// The metadata of two assets side by side, e.g. to decide which of two near-duplicates to keep
+#[derive(Debug)]
+pub struct MetadataDiff {
+    pub a: String, // Asset names
+    pub b: String,
+    pub rows: Vec<DiffRow>, // Every property of either asset, by name
+    pub selected: usize,
+    pub only_differences: bool, // Hide the properties with the same value
+}
+
+// The properties of an asset's metadata as text; pcli2 sometimes nests them in a "meta" object
+fn properties(metadata: &Value) -> BTreeMap<String, String> {
+    let object = match metadata.get("meta") {
+        Some(Value::Object(meta)) => meta,
+        _ => match metadata {
+            Value::Object(object) => object,
+            _ => return BTreeMap::new(),
+        },
+    };
+    object
+        .iter()
+        .map(|(key, value)| {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                value => value.to_string(),
+            };
+            (key.clone(), text)
+        })
+        .collect()
+}
+
+impl MetadataDiff {
+    pub fn new(a: String, a_metadata: &Value, b: String, b_metadata: &Value) -> Self {
+        let mut a_properties = properties(a_metadata);
+        let mut b_properties = properties(b_metadata);
+        let mut keys: Vec<String> = a_properties
+            .keys()
+            .chain(b_properties.keys())
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        let rows = keys
+            .into_iter()
+            .map(|key| DiffRow {
+                a: a_properties.remove(&key),
+                b: b_properties.remove(&key),
+                key,
+            })
+            .collect();
+        Self {
+            a,
+            b,
+            rows,
+            selected: 0,
+            only_differences: false,
+        }
+    }
+
+    pub fn visible_rows(&self) -> Vec<&DiffRow> {
+        self.rows
+            .iter()
+            .filter(|row| !self.only_differences || row.differs())
+            .collect()
+    }
+
+    pub fn differences(&self) -> usize {
+        self.rows.iter().filter(|row| row.differs()).count()
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.visible_rows().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn toggle_only_differences(&mut self) {
+        self.only_differences = !self.only_differences;
+        self.move_selection(0);
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How many recent samples the averages and maxima are taken over
+const SAMPLES: usize = 120;
+
+// Recent durations of something that happens over and over, e.g. drawing a frame
+#[derive(Debug, Default)]
+pub struct Samples {
+    recent: VecDeque<Duration>,
+}
+
+impl Samples {
+    fn record(&mut self, duration: Duration) {
+        if self.recent.len() == SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(duration);
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.recent.back().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let count = self.recent.len() as u32;
+        (count > 0).then(|| self.recent.iter().sum::<Duration>() / count)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.recent.iter().max().copied()
+    }
+}
+
+// What the performance overlay shows about the TUI itself; pcli2 keeps its own counts
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub frames: u64,
+    pub frame: Samples,          // Drawing a frame and writing it to the terminal
+    pub build: Samples,          // Laying the frame out, before it is written
+    pub latency: Samples,        // From reading an input event to the frame that shows its effect
+    event_read: Option<Instant>, // When the input event waiting for its frame was read
+    pub cache_hits: usize,       // Listings served from the folder cache
+}
+
+impl Metrics {
+    pub fn event_read(&mut self) {
+        self.event_read.get_or_insert_with(Instant::now);
+    }
+
+    // A frame was drawn in `frame`, of which `build` went into laying it out
+    pub fn frame_drawn(&mut self, frame: Duration, build: Duration) {
+        self.frames += 1;
+        self.frame.record(frame);
+        self.build.record(build);
+        if let Some(read) = self.event_read.take() {
+            self.latency.record(read.elapsed());
+        }
+    }
+}
+
+// "1.2 ms" for the overlay
+pub fn format_ms(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.1} ms", duration.as_secs_f64() * 1000.0),
+        None => "-".to_string(),
+    }
+}
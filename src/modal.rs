@@ -0,0 +1,72 @@
+// The dialogs and prompts that can be open over the main view. Their contents live in the
+// corresponding App fields; the stack only decides which of them is in front.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modal {
+    Search,
+    Finder,
+    FolderPicker,
+    TagPicker,
+    AssetDetails,
+    GeometricMatch,
+    WhereUsed,
+    CustomCommands,
+    AssemblyDownload,
+    Confirm,
+    SyncPlan,
+    FolderDiff,
+    MetadataDiff,
+    MatchReview,
+    MatchHistory,
+    InterruptedJobs,
+    PropertySchema,
+    Validation,
+    MetadataImport,
+    Setup,
+    ProfilePicker,
+    Tour,
+    Help,
+    LogDetail,
+    Error,
+    Prompt,      // Typed in the status bar
+    CommandLine, // The ':' command line, typed in the status bar
+}
+
+impl Modal {
+    // Prompts are typed in the status bar and leave the rest of the screen visible
+    pub fn is_prompt(self) -> bool {
+        matches!(self, Modal::Prompt | Modal::CommandLine)
+    }
+}
+
+// Open modals, the most recently opened last; only the top one receives keys
+#[derive(Debug, Default)]
+pub struct ModalStack {
+    stack: Vec<Modal>,
+}
+
+impl ModalStack {
+    // Open a modal on top of the others; one that is already open moves to the top
+    pub fn push(&mut self, modal: Modal) {
+        self.close(modal);
+        self.stack.push(modal);
+    }
+
+    // Close a modal wherever it is in the stack
+    pub fn close(&mut self, modal: Modal) {
+        self.stack.retain(|open| *open != modal);
+    }
+
+    pub fn top(&self) -> Option<Modal> {
+        self.stack.last().copied()
+    }
+
+    // Whether a dialog (not just a status bar prompt) covers the main view
+    pub fn covers_view(&self) -> bool {
+        self.stack.iter().any(|modal| !modal.is_prompt())
+    }
+
+    // From the bottom to the top, the order they are drawn in
+    pub fn iter(&self) -> impl Iterator<Item = Modal> + '_ {
+        self.stack.iter().copied()
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+
+const NOTES_FILE_NAME: &str = "notes.json";
+
+// Free-text notes on assets, by UUID; they stay on this machine and never reach the tenant
+#[derive(Debug, Default)]
+pub struct Notes {
+    notes: HashMap<String, String>,
+    path: Option<PathBuf>, // Where they are saved; None keeps them for this session only
+}
+
+impl Notes {
+    // Load the notes file; there are no notes until the first one is written
+    pub fn load() -> Result<Notes> {
+        let Some(path) = data_dir().map(|dir| dir.join(NOTES_FILE_NAME)) else {
+            anyhow::bail!("No data directory (HOME is not set)");
+        };
+        if !path.exists() {
+            return Ok(Notes {
+                notes: HashMap::new(),
+                path: Some(path),
+            });
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read notes file {}", path.display()))?;
+        let notes = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse notes file {}", path.display()))?;
+        Ok(Notes {
+            notes,
+            path: Some(path),
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.notes)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write notes file {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&str> {
+        self.notes.get(uuid).map(String::as_str)
+    }
+
+    // Replace the asset's note and write the file; an empty note removes it
+    pub fn set(&mut self, uuid: &str, note: &str) -> Result<()> {
+        if note.is_empty() {
+            self.notes.remove(uuid);
+        } else {
+            self.notes.insert(uuid.to_string(), note.to_string());
+        }
+        self.save()
+    }
+}
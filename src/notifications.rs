@@ -0,0 +1,97 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Keep this many notifications in the history
+const HISTORY_LIMIT: usize = 200;
+
+// Maximum number of toasts shown at once
+pub const MAX_VISIBLE_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl NotificationLevel {
+    // How long a toast of this level stays on screen
+    fn lifetime(self) -> Duration {
+        match self {
+            NotificationLevel::Info => Duration::from_millis(2500),
+            NotificationLevel::Success => Duration::from_secs(4),
+            NotificationLevel::Warning => Duration::from_secs(6),
+            NotificationLevel::Error => Duration::from_secs(8),
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            NotificationLevel::Info => "ℹ",
+            NotificationLevel::Success => "✓",
+            NotificationLevel::Warning => "⚠",
+            NotificationLevel::Error => "✗",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub message: String,
+    pub timestamp: DateTime<Local>,
+    pub expires_at: Instant,
+}
+
+// Queue of notifications: recent ones are shown as toasts, all are kept in the history
+#[derive(Debug, Default)]
+pub struct Notifications {
+    history: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: NotificationLevel, message: String) {
+        let now = Instant::now();
+
+        // A new info message retires the previous info toast instead of stacking on top of it,
+        // so routine messages can't push important toasts off the screen
+        if level == NotificationLevel::Info {
+            for notification in self.history.iter_mut() {
+                if notification.level == NotificationLevel::Info && notification.expires_at > now {
+                    notification.expires_at = now;
+                }
+            }
+        }
+
+        self.history.push_back(Notification {
+            level,
+            message,
+            timestamp: Local::now(),
+            expires_at: now + level.lifetime(),
+        });
+
+        while self.history.len() > HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+    }
+
+    // Toasts that haven't expired yet, oldest first
+    pub fn active_toasts(&self) -> Vec<&Notification> {
+        let now = Instant::now();
+        let mut toasts: Vec<&Notification> = self
+            .history
+            .iter()
+            .rev()
+            .filter(|notification| notification.expires_at > now)
+            .take(MAX_VISIBLE_TOASTS)
+            .collect();
+        toasts.reverse();
+        toasts
+    }
+
+    pub fn history(&self) -> &VecDeque<Notification> {
+        &self.history
+    }
+}
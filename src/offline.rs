@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::app::FolderCache;
+use crate::config::data_dir;
+
+const CACHE_FILE_NAME: &str = "cache.json";
+
+// Whether pcli2 is taken to be unreachable; the app then browses the persisted folder cache
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+// Set when a network failure switched to offline mode, until the app has reported it
+static DETECTED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+pub fn disable() {
+    OFFLINE.store(false, Ordering::Relaxed);
+    DETECTED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+// pcli2 failed to reach the tenant: go offline until the user goes back online
+pub fn detected() {
+    if !OFFLINE.swap(true, Ordering::Relaxed) {
+        DETECTED.store(true, Ordering::Relaxed);
+    }
+}
+
+// Whether a network failure switched to offline mode since the last call
+pub fn take_detected() -> bool {
+    DETECTED.swap(false, Ordering::Relaxed)
+}
+
+fn cache_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(CACHE_FILE_NAME))
+}
+
+// The folder cache of the last session; empty until one was saved
+pub fn load_cache() -> Result<HashMap<String, FolderCache>> {
+    let Some(path) = cache_path() else {
+        anyhow::bail!("No data directory (HOME is not set)");
+    };
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read cache file {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse cache file {}", path.display()))
+}
+
+// Keep the folder cache for the next session, and for browsing it offline
+pub fn save_cache(cache: &HashMap<String, FolderCache>) -> Result<()> {
+    let Some(path) = cache_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let contents = serde_json::to_string(cache)?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write cache file {}", path.display()))
+}
@@ -1,6 +1,1624 @@
 use anyhow::Result;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use thiserror::Error;
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+
+use crate::config::{self, Config, RetryConfig, TimeoutConfig};
+use crate::demo;
+use crate::offline;
+use crate::permissions;
+use crate::rate_limit;
+
+// Records skipped because they didn't have the expected shape, waiting to be picked up by the log
+static MALFORMED_RECORDS: Mutex<Vec<MalformedRecord>> = Mutex::new(Vec::new());
+
+// Listings whose records didn't match any known pcli2 output schema, waiting to be picked up by the log
+static SCHEMA_WARNINGS: Mutex<Vec<SchemaWarning>> = Mutex::new(Vec::new());
+
+// Retry policy shared by every pcli2 invocation; defaults apply until the app configures it
+static RETRY_POLICY: Mutex<Option<RetryConfig>> = Mutex::new(None);
+
+// Timeouts applied to each pcli2 invocation
+static TIMEOUT_POLICY: Mutex<Option<TimeoutConfig>> = Mutex::new(None);
+
+// How pcli2 is run, from the config and the profile in use; "pcli2" on the PATH until then
+static INVOCATION: Mutex<Option<Invocation>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+struct Invocation {
+    program: String,
+    env: HashMap<String, String>,
+    args: Vec<String>, // Appended to every invocation, e.g. --tenant or proxy settings
+}
+
+// One-shot timeout that replaces the configured one for the next invocation
+static TIMEOUT_OVERRIDE: Mutex<Option<Duration>> = Mutex::new(None);
+
+tokio::task_local! {
+    // Set while a background job such as prefetching runs pcli2
+    static BACKGROUND: bool;
+    // Set while a batch job runs pcli2, e.g. a sync or a basket deletion
+    static JOB: bool;
+}
+
+// Run a job whose pcli2 invocations stay out of the busy indicator, the JSON viewer and the log
+pub async fn in_background<F: Future>(job: F) -> F::Output {
+    BACKGROUND.scope(true, job).await
+}
+
+fn is_background() -> bool {
+    BACKGROUND
+        .try_with(|background| *background)
+        .unwrap_or(false)
+}
+
+// Run a batch job, whose pcli2 invocations are recorded in the command history like the user's
+pub async fn in_job<F: Future>(job: F) -> F::Output {
+    JOB.scope(true, job).await
+}
+
+fn is_job() -> bool {
+    JOB.try_with(|job| *job).unwrap_or(false)
+}
+
+// Limits how many pcli2 processes run at once. Background jobs yield to user actions: they
+// never start while a user action is waiting, and leave one slot free for user actions.
+static SCHEDULER: Scheduler = Scheduler {
+    state: Mutex::new(SchedulerState {
+        limit: DEFAULT_CONCURRENCY,
+        running: 0,
+        waiting: 0,
+    }),
+    changed: tokio::sync::Notify::const_new(),
+};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+    changed: tokio::sync::Notify,
+}
+
+struct SchedulerState {
+    limit: usize,
+    running: usize,
+    waiting: usize, // User actions waiting for a slot
+}
+
+impl SchedulerState {
+    fn can_start(&self, background: bool) -> bool {
+        if background {
+            self.waiting == 0 && self.running < self.limit.saturating_sub(1).max(1)
+        } else {
+            self.running < self.limit
+        }
+    }
+}
+
+impl Scheduler {
+    fn state(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        // The state is a few counters that are always left consistent, so a poisoned lock is fine
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Wait for a slot to run a pcli2 process in; the slot is released when dropped
+    async fn acquire(&'static self, background: bool) -> Slot {
+        let _waiting = (!background).then(|| WaitingGuard::new(self));
+        loop {
+            // Register for wakeups before checking, so a release in between isn't missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            {
+                let mut state = self.state();
+                if state.can_start(background) {
+                    state.running += 1;
+                    return Slot(self);
+                }
+            }
+            changed.await;
+        }
+    }
+}
+
+// A running pcli2 process's claim on the scheduler
+struct Slot(&'static Scheduler);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.state().running -= 1;
+        self.0.changed.notify_waiters();
+    }
+}
+
+// Counts a user action as waiting for as long as it is, including when its future is dropped
+struct WaitingGuard(&'static Scheduler);
+
+impl WaitingGuard {
+    fn new(scheduler: &'static Scheduler) -> Self {
+        scheduler.state().waiting += 1;
+        WaitingGuard(scheduler)
+    }
+}
+
+impl Drop for WaitingGuard {
+    fn drop(&mut self) {
+        self.0.state().waiting -= 1;
+        self.0.changed.notify_waiters();
+    }
+}
+
+// Set how many pcli2 processes may run at once (at least one)
+pub fn set_concurrency_limit(limit: usize) {
+    SCHEDULER.state().limit = limit.max(1);
+    SCHEDULER.changed.notify_waiters();
+}
+
+// The pcli2 invocation in flight, for the busy indicator
+static RUNNING: Mutex<Option<RunningCommand>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+pub struct RunningCommand {
+    pub operation: String,
+    pub started: Instant,
+    pub records: usize, // Records of its listing parsed so far, while the output streams in
+}
+
+pub fn running_command() -> Option<RunningCommand> {
+    RUNNING.lock().ok().and_then(|running| running.clone())
+}
+
+// Marks a command as running for as long as it is alive, including when its future is dropped
+struct RunningGuard;
+
+impl RunningGuard {
+    fn start(operation: &str) -> Self {
+        if let Ok(mut running) = RUNNING.lock() {
+            *running = Some(RunningCommand {
+                operation: operation.to_string(),
+                started: Instant::now(),
+                records: 0,
+            });
+        }
+        RunningGuard
+    }
+}
+
+// Count records of the running command's listing as they are parsed
+fn count_records(records: usize) {
+    if records > 0
+        && !is_background()
+        && let Ok(mut running) = RUNNING.lock()
+        && let Some(ref mut running) = *running
+    {
+        running.records += records;
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING.lock() {
+            *running = None;
+        }
+    }
+}
+
+// Progress of the download in flight, for the transfer gauge
+static TRANSFER: Mutex<Option<TransferProgress>> = Mutex::new(None);
+
+// How often the destination file is checked while a download runs
+const TRANSFER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// A file being downloaded; progress is measured by polling its size on disk
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub label: String,
+    pub path: PathBuf,
+    pub total: Option<u64>, // Expected size, when the listing reported it
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferProgress {
+    pub label: String,
+    pub bytes: u64,
+    pub total: Option<u64>,
+    pub started: Instant,
+}
+
+impl TransferProgress {
+    pub fn ratio(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.bytes as f64 / total as f64).min(1.0))
+    }
+
+    // Average bytes per second since the transfer started
+    pub fn rate(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.bytes as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn transfer_progress() -> Option<TransferProgress> {
+    TRANSFER.lock().ok().and_then(|progress| progress.clone())
+}
+
+// Clears the transfer progress when the download finishes or is dropped
+struct TransferGuard;
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        if let Ok(mut progress) = TRANSFER.lock() {
+            *progress = None;
+        }
+    }
+}
+
+async fn record_transfer(transfer: &Transfer, started: Instant, started_at: SystemTime) {
+    // A file left over from an earlier download doesn't count until pcli2 rewrites it
+    let bytes = match tokio::fs::metadata(&transfer.path).await {
+        Ok(metadata)
+            if metadata
+                .modified()
+                .is_ok_and(|modified| modified >= started_at) =>
+        {
+            metadata.len()
+        }
+        _ => 0,
+    };
+    if let Ok(mut progress) = TRANSFER.lock() {
+        *progress = Some(TransferProgress {
+            label: transfer.label.clone(),
+            bytes,
+            total: transfer.total,
+            started,
+        });
+    }
+}
+
+// Raw stdout of the most recent pcli2 invocation, for the JSON viewer
+static LAST_OUTPUT: Mutex<Option<LastOutput>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+pub struct LastOutput {
+    pub command_line: String,
+    pub stdout: Option<String>, // None when the output was too large to keep
+}
+
+pub fn last_output() -> Option<LastOutput> {
+    LAST_OUTPUT.lock().ok().and_then(|output| output.clone())
+}
+
+fn record_output(command_line: &str, stdout: Option<&[u8]>) {
+    if let Ok(mut last) = LAST_OUTPUT.lock() {
+        *last = Some(LastOutput {
+            command_line: command_line.to_string(),
+            stdout: stdout.map(|stdout| String::from_utf8_lossy(stdout).into_owned()),
+        });
+    }
+}
+
+// pcli2 invocations since startup, for the performance overlay
+static INVOCATIONS: Mutex<Invocations> = Mutex::new(Invocations {
+    foreground: 0,
+    background: 0,
+    failed: 0,
+    listings: 0,
+});
+
+#[derive(Debug, Clone, Copy)]
+pub struct Invocations {
+    pub foreground: usize,
+    pub background: usize,
+    pub failed: usize, // Exited with an error or timed out, counting each retried attempt
+    pub listings: usize, // Foreground folder and asset listings, the ones the cache can save
+}
+
+pub fn invocations() -> Option<Invocations> {
+    INVOCATIONS.lock().ok().map(|invocations| *invocations)
+}
+
+fn count_listing() {
+    if !is_background()
+        && let Ok(mut invocations) = INVOCATIONS.lock()
+    {
+        invocations.listings += 1;
+    }
+}
+
+fn count_invocation(succeeded: bool) {
+    if let Ok(mut invocations) = INVOCATIONS.lock() {
+        match is_background() {
+            true => invocations.background += 1,
+            false => invocations.foreground += 1,
+        }
+        if !succeeded {
+            invocations.failed += 1;
+        }
+    }
+}
+
+// How the most recent foreground pcli2 invocation went, for the log
+static LAST_RUN: Mutex<Option<CommandRun>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandRun {
+    pub started: Instant,
+    pub duration: Duration,     // Wall-clock time of the pcli2 process
+    pub exit_code: Option<i32>, // None when it was killed, e.g. on timeout
+}
+
+pub fn last_run() -> Option<CommandRun> {
+    LAST_RUN.lock().ok().and_then(|run| *run)
+}
+
+fn record_run(started: Instant, exit_code: Option<i32>) {
+    count_invocation(exit_code == Some(0));
+    if !is_background()
+        && let Ok(mut last) = LAST_RUN.lock()
+    {
+        *last = Some(CommandRun {
+            started,
+            duration: started.elapsed(),
+            exit_code,
+        });
+    }
+}
+
+// Commands run by batch jobs, waiting to be picked up by the command history
+static JOB_COMMANDS: Mutex<Vec<JobCommand>> = Mutex::new(Vec::new());
+
+// A pcli2 command a batch job ran, and how its last attempt went
+#[derive(Debug, Clone)]
+pub struct JobCommand {
+    pub command: PcliCommand,
+    pub duration: Option<Duration>, // None when it never got to run, e.g. while offline
+    pub exit_code: Option<i32>,     // None when it was killed, e.g. on timeout
+}
+
+impl JobCommand {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+// Take the commands batch jobs ran since the last call
+pub fn take_job_commands() -> Vec<JobCommand> {
+    JOB_COMMANDS
+        .lock()
+        .map(|mut commands| std::mem::take(&mut *commands))
+        .unwrap_or_default()
+}
+
+fn record_job_command(args: &[&str], started: Option<Instant>, exit_code: Option<i32>) {
+    if is_job()
+        && let Ok(mut commands) = JOB_COMMANDS.lock()
+    {
+        commands.push(JobCommand {
+            command: PcliCommand::new(args),
+            duration: started.map(|started| started.elapsed()),
+            exit_code,
+        });
+    }
+}
+
+// Failed attempts that were retried, waiting to be picked up by the log
+static RETRY_EVENTS: Mutex<Vec<RetryEvent>> = Mutex::new(Vec::new());
+
+// A failed attempt that is about to be retried
+#[derive(Debug, Clone)]
+pub struct RetryEvent {
+    pub command_line: String,
+    pub attempt: u32,  // The attempt that failed, starting at 1
+    pub attempts: u32, // Total attempts allowed by the policy
+    pub delay: Duration,
+    pub reason: String,
+}
+
+// A record in a pcli2 listing that couldn't be deserialized and was left out
+#[derive(Debug, Clone)]
+pub struct MalformedRecord {
+    pub command_line: String,
+    pub index: usize, // Position of the record in the listing, starting at 0
+    pub error: String,
+    pub raw: String, // The record as pcli2 printed it
+}
+
+// Take the malformed records skipped since the last call
+pub fn take_malformed_records() -> Vec<MalformedRecord> {
+    MALFORMED_RECORDS
+        .lock()
+        .map(|mut records| std::mem::take(&mut *records))
+        .unwrap_or_default()
+}
+
+// Output schemas of the pcli2 releases we know about. Older releases print snake_case keys
+// (created_at, file_size), newer ones camelCase (createdAt, fileSize); some commands mix both.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputSchema {
+    SnakeCase,
+    CamelCase,
+}
+
+impl OutputSchema {
+    // Keys that only this schema uses
+    fn markers(self) -> &'static [&'static str] {
+        match self {
+            OutputSchema::SnakeCase => &[
+                "created_at",
+                "updated_at",
+                "file_size",
+                "file_type",
+                "processing_status",
+                "is_assembly",
+                "assets_count",
+                "folders_count",
+            ],
+            OutputSchema::CamelCase => &[
+                "createdAt",
+                "updatedAt",
+                "fileSize",
+                "fileType",
+                "processingStatus",
+                "isAssembly",
+                "assetsCount",
+                "foldersCount",
+                "tenantId",
+                "folderId",
+            ],
+        }
+    }
+
+    // Tell the schema of a record by probing its keys; None when it has no key we recognize
+    fn detect(record: &serde_json::Map<String, serde_json::Value>) -> Option<Self> {
+        let score = |schema: OutputSchema| {
+            schema
+                .markers()
+                .iter()
+                .filter(|key| record.contains_key(**key))
+                .count()
+        };
+        match (
+            score(OutputSchema::SnakeCase),
+            score(OutputSchema::CamelCase),
+        ) {
+            (0, 0) => None,
+            (snake, camel) if camel > snake => Some(OutputSchema::CamelCase),
+            _ => Some(OutputSchema::SnakeCase),
+        }
+    }
+}
+
+// Keys a struct deserializes from, each with the names other pcli2 schemas use for the same field
+type FieldAliases = [(&'static str, &'static [&'static str])];
+
+const FOLDER_FIELDS: &FieldAliases = &[
+    ("id", &["uuid"]),
+    ("assetsCount", &["assets_count"]),
+    ("foldersCount", &["folders_count"]),
+];
+
+const ASSET_FIELDS: &FieldAliases = &[
+    ("uuid", &["id"]),
+    ("file_type", &["fileType", "type"]),
+    ("file_size", &["fileSize"]),
+    ("processing_status", &["processingStatus", "state"]),
+    ("created_at", &["createdAt"]),
+    ("updated_at", &["updatedAt"]),
+    ("is_assembly", &["isAssembly"]),
+];
+
+const PROPERTY_FIELDS: &FieldAliases = &[("type", &["dataType", "data_type", "propertyType"])];
+
+const ASSET_DETAIL_FIELDS: &FieldAliases = &[
+    ("id", &["uuid"]),
+    ("type", &["file_type", "fileType"]),
+    ("file_size", &["fileSize"]),
+    ("processing_status", &["processingStatus"]),
+    ("created_at", &["createdAt"]),
+    ("updated_at", &["updatedAt"]),
+    ("is_assembly", &["isAssembly"]),
+    ("tenantId", &["tenant_id"]),
+    ("folderId", &["folder_id"]),
+];
+
+const SEARCH_ASSET_FIELDS: &FieldAliases = &[
+    ("id", &["uuid"]),
+    ("type", &["file_type", "fileType"]),
+    ("file_size", &["fileSize"]),
+    ("processing_status", &["processingStatus"]),
+    ("createdAt", &["created_at"]),
+    ("updatedAt", &["updated_at"]),
+    ("isAssembly", &["is_assembly"]),
+    ("tenantId", &["tenant_id"]),
+    ("folderId", &["folder_id"]),
+];
+
+// Rename the keys of a record to the ones our structs expect and return the schema it was in
+fn adapt_record(value: &mut serde_json::Value, fields: &FieldAliases) -> Option<OutputSchema> {
+    let record = value.as_object_mut()?;
+    let schema = OutputSchema::detect(record);
+    for (field, aliases) in fields {
+        if record.contains_key(*field) {
+            continue;
+        }
+        if let Some(found) = aliases.iter().find_map(|alias| record.remove(*alias)) {
+            record.insert(field.to_string(), found);
+        }
+    }
+    schema
+}
+
+// A listing whose records didn't look like any pcli2 output schema we know
+#[derive(Debug, Clone)]
+pub struct SchemaWarning {
+    pub command_line: String,
+    pub unrecognized: usize, // Records without any key we recognize
+    pub total: usize,
+}
+
+// Take the schema warnings recorded since the last call
+pub fn take_schema_warnings() -> Vec<SchemaWarning> {
+    SCHEMA_WARNINGS
+        .lock()
+        .map(|mut warnings| std::mem::take(&mut *warnings))
+        .unwrap_or_default()
+}
+
+// Log the schemas a command's output was in and flag records in an unknown one
+fn report_schemas(command_line: &str, schemas: &[Option<OutputSchema>]) {
+    let count = |schema| schemas.iter().filter(|s| **s == schema).count();
+    let unrecognized = count(None);
+    tracing::debug!(
+        command = %command_line,
+        snake_case = count(Some(OutputSchema::SnakeCase)),
+        camel_case = count(Some(OutputSchema::CamelCase)),
+        unrecognized,
+        "detected pcli2 output schema"
+    );
+
+    if unrecognized > 0 {
+        tracing::warn!(command = %command_line, unrecognized, "unknown pcli2 output schema");
+        if !is_background()
+            && let Ok(mut pending) = SCHEMA_WARNINGS.lock()
+        {
+            pending.push(SchemaWarning {
+                command_line: command_line.to_string(),
+                unrecognized,
+                total: schemas.len(),
+            });
+        }
+    }
+}
+
+// Adapt a single object from pcli2 to the given struct's keys and deserialize it
+fn parse_record<T: DeserializeOwned>(
+    args: &[&str],
+    mut value: serde_json::Value,
+    fields: &FieldAliases,
+) -> Result<T> {
+    let schema = adapt_record(&mut value, fields);
+    report_schemas(&format_command_line(args), &[schema]);
+    Ok(serde_json::from_value(value)?)
+}
+
+// Takes the records of a listing one at a time as they are split out of pcli2's output, each
+// with the bytes it was printed as
+trait RecordSink {
+    fn record(&mut self, value: serde_json::Value, raw: &[u8]);
+}
+
+// Deserializes each record of a listing on its own, so one record with an unexpected shape
+// is skipped and reported instead of failing the whole listing
+struct RecordParser<T> {
+    command_line: String,
+    fields: &'static FieldAliases,
+    records: Vec<T>,
+    schemas: Vec<Option<OutputSchema>>,
+    malformed: Vec<MalformedRecord>,
+}
+
+impl<T: DeserializeOwned> RecordParser<T> {
+    fn new(args: &[&str], fields: &'static FieldAliases) -> Self {
+        Self {
+            command_line: format_command_line(args),
+            fields,
+            records: Vec::new(),
+            schemas: Vec::new(),
+            malformed: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> Vec<T> {
+        report_schemas(&self.command_line, &self.schemas);
+        if !self.malformed.is_empty()
+            && !is_background()
+            && let Ok(mut pending) = MALFORMED_RECORDS.lock()
+        {
+            pending.extend(self.malformed);
+        }
+        self.records
+    }
+}
+
+impl<T: DeserializeOwned> RecordSink for RecordParser<T> {
+    fn record(&mut self, mut value: serde_json::Value, raw: &[u8]) {
+        let index = self.schemas.len();
+        self.schemas.push(adapt_record(&mut value, self.fields));
+        match T::deserialize(&value) {
+            Ok(record) => self.records.push(record),
+            Err(e) => {
+                tracing::warn!(
+                    command = %self.command_line,
+                    index,
+                    error = %e,
+                    "skipped malformed record"
+                );
+                // Pretty-printed from what pcli2 printed, before the keys were adapted
+                let raw = serde_json::from_slice::<serde_json::Value>(raw)
+                    .ok()
+                    .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                    .unwrap_or_else(|| String::from_utf8_lossy(raw).into_owned());
+                self.malformed.push(MalformedRecord {
+                    command_line: self.command_line.clone(),
+                    index,
+                    error: e.to_string(),
+                    raw,
+                });
+            }
+        }
+    }
+}
+
+// Splits the records out of a JSON listing as its bytes arrive: the elements of a top-level
+// array, or of the array under `key` when the listing is an object wrapping it. Only the record
+// being read is held, never the whole listing.
+struct RecordSplitter {
+    key: Option<&'static str>,
+    top: Option<u8>, // The listing's opening bracket, once seen
+    depth: usize,
+    records_depth: Option<usize>, // Depth of the records while inside their array
+    found: bool,                  // Whether the records array was found
+    in_string: bool,
+    escaped: bool,
+    key_text: Option<Vec<u8>>, // A string being read at the top of a wrapping object
+    last_string: Option<Vec<u8>>,
+    key_matched: bool, // The wrapping object's current value is under `key`
+    record: Option<Vec<u8>>,
+    invalid: bool,
+}
+
+const WHITESPACE: &[u8] = b" \t\r\n";
+
+impl RecordSplitter {
+    fn new(key: Option<&'static str>) -> Self {
+        Self {
+            key,
+            top: None,
+            depth: 0,
+            records_depth: None,
+            found: false,
+            in_string: false,
+            escaped: false,
+            key_text: None,
+            last_string: None,
+            key_matched: false,
+            record: None,
+            invalid: false,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8], mut emit: impl FnMut(&[u8])) {
+        for &byte in bytes {
+            if self.invalid {
+                return;
+            }
+            if self.in_string {
+                self.string_byte(byte, &mut emit);
+                continue;
+            }
+
+            let at_records = self.records_depth == Some(self.depth);
+            // A number or literal record ends at the first delimiter after it
+            if at_records
+                && (byte == b',' || byte == b']' || WHITESPACE.contains(&byte))
+                && let Some(record) = self.record.take()
+            {
+                emit(&record);
+            }
+            if at_records
+                && self.record.is_none()
+                && !b",]".contains(&byte)
+                && !WHITESPACE.contains(&byte)
+            {
+                self.record = Some(Vec::new());
+            }
+            if let Some(ref mut record) = self.record {
+                record.push(byte);
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    if self.depth == 1 && self.top == Some(b'{') && self.records_depth.is_none() {
+                        self.key_text = Some(Vec::new());
+                    }
+                }
+                b'[' | b'{' => {
+                    if self.depth == 0 {
+                        if self.top.is_some() {
+                            continue; // Anything after the listing is ignored
+                        }
+                        self.top = Some(byte);
+                        if byte == b'[' {
+                            self.records_depth = Some(1);
+                            self.found = true;
+                        }
+                    } else if self.depth == 1 && byte == b'[' && self.key_matched && !self.found {
+                        self.records_depth = Some(2);
+                        self.found = true;
+                    }
+                    self.depth += 1;
+                }
+                b']' | b'}' => {
+                    let Some(depth) = self.depth.checked_sub(1) else {
+                        self.invalid = true;
+                        continue;
+                    };
+                    self.depth = depth;
+                    if self.records_depth == Some(depth + 1) {
+                        self.records_depth = None; // The records array closed
+                    } else if self.records_depth == Some(depth)
+                        && let Some(record) = self.record.take()
+                    {
+                        emit(&record);
+                    }
+                }
+                b':' if self.depth == 1 => {
+                    let key = self.last_string.take();
+                    self.key_matched = key.as_deref() == self.key.map(str::as_bytes);
+                }
+                b',' if self.depth == 1 => self.key_matched = false,
+                _ if self.depth == 0 && !WHITESPACE.contains(&byte) && self.top.is_none() => {
+                    self.invalid = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn string_byte(&mut self, byte: u8, emit: &mut impl FnMut(&[u8])) {
+        if let Some(ref mut record) = self.record {
+            record.push(byte);
+        }
+        if self.escaped {
+            self.escaped = false;
+        } else if byte == b'\\' {
+            self.escaped = true;
+        } else if byte == b'"' {
+            self.in_string = false;
+            if let Some(key) = self.key_text.take() {
+                self.last_string = Some(key);
+            }
+            if self.records_depth == Some(self.depth)
+                && let Some(record) = self.record.take()
+            {
+                emit(&record);
+            }
+            return;
+        }
+        if let Some(ref mut key) = self.key_text {
+            key.push(byte);
+        }
+    }
+
+    // Whether the records array was found, once the whole listing was read
+    fn finish(&self) -> Result<bool> {
+        if self.invalid || self.top.is_none() {
+            anyhow::bail!("pcli2 didn't print a JSON listing");
+        }
+        if self.depth > 0 || self.in_string {
+            anyhow::bail!("The JSON output of pcli2 ended early");
+        }
+        if self.key.is_none() && !self.found {
+            anyhow::bail!("pcli2 printed a JSON object where a list was expected");
+        }
+        Ok(self.found)
+    }
+}
+
+pub fn set_retry_policy(policy: RetryConfig) {
+    if let Ok(mut current) = RETRY_POLICY.lock() {
+        *current = Some(policy);
+    }
+}
+
+fn retry_policy() -> RetryConfig {
+    RETRY_POLICY
+        .lock()
+        .ok()
+        .and_then(|policy| policy.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_timeout_policy(policy: TimeoutConfig) {
+    if let Ok(mut current) = TIMEOUT_POLICY.lock() {
+        *current = Some(policy);
+    }
+}
+
+// Run the pcli2 of the active profile with its environment, adding the configured arguments
+// and then the profile's to every invocation
+pub fn set_invocation(config: &Config) {
+    let profile = config.profile();
+    let program = match profile.and_then(|profile| profile.pcli2.as_deref()) {
+        Some(path) => config::expand_home(path).display().to_string(),
+        None => "pcli2".to_string(),
+    };
+    let mut args = config.pcli2_args.clone();
+    args.extend(
+        profile
+            .iter()
+            .flat_map(|profile| profile.pcli2_args.iter().cloned()),
+    );
+    if let Ok(mut current) = INVOCATION.lock() {
+        *current = Some(Invocation {
+            program,
+            env: profile
+                .map(|profile| profile.env.clone())
+                .unwrap_or_default(),
+            args,
+        });
+    }
+}
+
+fn invocation() -> Invocation {
+    INVOCATION
+        .lock()
+        .ok()
+        .and_then(|invocation| invocation.clone())
+        .unwrap_or_else(|| Invocation {
+            program: "pcli2".to_string(),
+            env: HashMap::new(),
+            args: Vec::new(),
+        })
+}
+
+// The pcli2 executable to run
+pub fn program() -> String {
+    invocation().program
+}
+
+// Use the given timeout for the next pcli2 invocation only (None clears a pending override)
+pub fn set_timeout_override(timeout: Option<Duration>) {
+    if let Ok(mut current) = TIMEOUT_OVERRIDE.lock() {
+        *current = timeout;
+    }
+}
+
+fn command_timeout(args: &[&str]) -> Duration {
+    // The override is meant for the operation being retried, not for whatever runs in the background
+    let overridden = if is_background() {
+        None
+    } else {
+        TIMEOUT_OVERRIDE
+            .lock()
+            .ok()
+            .and_then(|mut timeout| timeout.take())
+    };
+    overridden.unwrap_or_else(|| {
+        TIMEOUT_POLICY
+            .lock()
+            .ok()
+            .and_then(|policy| policy.clone())
+            .unwrap_or_default()
+            .for_command(args)
+    })
+}
+
+// Take the retry events recorded since the last call
+pub fn take_retry_events() -> Vec<RetryEvent> {
+    RETRY_EVENTS
+        .lock()
+        .map(|mut events| std::mem::take(&mut *events))
+        .unwrap_or_default()
+}
+
+// The HTTP status codes an error reports as such: after "HTTP", "status" or "code" (as in
+// "HTTP 502", "status: 403", "HTTP/1.1 429" or "HTTP status server error (502 Bad Gateway)"),
+// or followed by their reason phrase ("503 Service Unavailable"). Other numbers in the output
+// are more likely sizes, ids or parts of paths.
+pub fn http_statuses(stderr: &str) -> Vec<u16> {
+    // Words that may come between "HTTP" or "status" and the code
+    const FILLER: [&str; 4] = ["server", "client", "error", "is"];
+    // The first word of the reason phrase of the codes that matter here
+    const REASONS: [(u16, &str); 8] = [
+        (401, "unauthorized"),
+        (403, "forbidden"),
+        (404, "not"),
+        (429, "too"),
+        (500, "internal"),
+        (502, "bad"),
+        (503, "service"),
+        (504, "gateway"),
+    ];
+
+    let stderr = stderr.to_lowercase();
+    let tokens: Vec<&str> = stderr
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .collect();
+    // The version of "HTTP/1.1" or "HTTP/2"
+    let version = |token: &str| token.len() == 1 && token.bytes().all(|b| b.is_ascii_digit());
+    let mut statuses = Vec::new();
+    let mut after_status = false;
+    for (i, token) in tokens.iter().enumerate() {
+        let code = (token.len() == 3)
+            .then(|| token.parse::<u16>().ok())
+            .flatten()
+            .filter(|code| (100..600).contains(code));
+        match code {
+            Some(code) => {
+                let reason = REASONS.iter().find(|(status, _)| *status == code);
+                let followed_by_reason = reason
+                    .is_some_and(|(_, word)| tokens.get(i + 1).is_some_and(|next| next == word));
+                if after_status || followed_by_reason {
+                    statuses.push(code);
+                }
+                after_status = false;
+            }
+            None if after_status && (version(token) || FILLER.contains(token)) => {}
+            None => after_status = matches!(*token, "http" | "https" | "status" | "code"),
+        }
+    }
+    statuses
+}
+
+// Commands that only read from the tenant, so running one again can't do anything twice. A
+// command that changes the tenant isn't retried: the server may have carried it out before
+// the failure, and a second upload would create a duplicate asset.
+fn is_read_only(args: &[&str]) -> bool {
+    matches!(
+        args,
+        ["folder", "list", ..]
+            | ["property", "list", ..]
+            | ["--version"]
+            | [
+                "asset",
+                "list"
+                    | "get"
+                    | "download"
+                    | "thumbnail"
+                    | "geometry"
+                    | "dependencies"
+                    | "where-used"
+                    | "text-match"
+                    | "geometric-match",
+                ..
+            ]
+    )
+}
+
+// Whether the failure looks like a network hiccup worth retrying rather than a real error
+fn is_transient_failure(stderr: &str) -> bool {
+    const TRANSIENT_MARKERS: [&str; 12] = [
+        "timed out",
+        "timeout",
+        "connection reset",
+        "connection refused",
+        "connection closed",
+        "broken pipe",
+        "temporarily unavailable",
+        "bad gateway",
+        "service unavailable",
+        "gateway timeout",
+        "internal server error",
+        "dns error",
+    ];
+
+    let lowercase = stderr.to_lowercase();
+    TRANSIENT_MARKERS
+        .iter()
+        .any(|marker| lowercase.contains(marker))
+        || http_statuses(stderr)
+            .iter()
+            .any(|status| (500..600).contains(status))
+}
+
+// Whether the failure means pcli2 could not reach the tenant at all, e.g. without a network
+fn is_network_failure(stderr: &str) -> bool {
+    const NETWORK_MARKERS: [&str; 8] = [
+        "dns error",
+        "failed to lookup address",
+        "name or service not known",
+        "network is unreachable",
+        "no route to host",
+        "connection refused",
+        "error sending request",
+        "failed to connect",
+    ];
+
+    let stderr = stderr.to_lowercase();
+    NETWORK_MARKERS.iter().any(|marker| stderr.contains(marker))
+}
+
+// Whether the API turned the request down as forbidden. Only an HTTP 403 counts: "permission
+// denied" is as likely a local file pcli2 couldn't read, and a bare "403" anything at all.
+fn is_permission_failure(stderr: &str) -> bool {
+    http_statuses(stderr).contains(&403)
+}
+
+// Failure of a pcli2 invocation, retaining everything needed to diagnose it
+#[derive(Debug, Clone, Error)]
+#[error("{operation} failed: {}", .stderr.trim())]
+pub struct PcliError {
+    pub operation: String,    // Short description such as "pcli2 folder list"
+    pub command_line: String, // The exact command line that was executed
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// A pcli2 invocation that ran longer than its timeout and was killed
+#[derive(Debug, Clone, Error)]
+#[error("{operation} timed out after {}s", .timeout.as_secs())]
+pub struct PcliTimeout {
+    pub operation: String,
+    pub command_line: String,
+    pub timeout: Duration,
+}
+
+// A pcli2 invocation turned down because the app is offline
+#[derive(Debug, Clone, Error)]
+#[error("{operation} needs pcli2, and the app is offline (O goes back online)")]
+pub struct PcliOffline {
+    pub operation: String,
+}
+
+// A downloaded file whose size differs from the asset's recorded size, e.g. because the
+// transfer was cut short
+#[derive(Debug, Clone, Error)]
+#[error("{} is {actual} bytes but the asset is {expected} bytes", .path.display())]
+pub struct SizeMismatch {
+    pub path: PathBuf,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+// Check a downloaded file against the asset's recorded size, when there is one. pcli2 doesn't
+// report checksums, so the size is all there is to compare.
+pub fn verify_download(path: &Path, expected: Option<u64>) -> Result<()> {
+    let actual = std::fs::metadata(path)?.len();
+    match expected {
+        Some(expected) if expected != actual => Err(SizeMismatch {
+            path: path.to_path_buf(),
+            expected,
+            actual,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+// A command as its program and arguments, kept as they were run so it can be run again
+// exactly; it only becomes a shell-quoted line to be shown, copied or exported as a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcliCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl PcliCommand {
+    pub fn new(args: &[&str]) -> Self {
+        Self {
+            program: "pcli2".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    // A command line as typed, e.g. an edited history entry, with or without a leading "pcli2"
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut args = split_command_line(line);
+        if args.first().is_some_and(|program| program == "pcli2") {
+            args.remove(0);
+        }
+        (!args.is_empty()).then(|| Self {
+            program: "pcli2".to_string(),
+            args,
+        })
+    }
+}
+
+impl Default for PcliCommand {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl std::fmt::Display for PcliCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", shell_quote(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", shell_quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+// Render a command line the way a user would type it in a shell
+pub fn format_command_line(args: &[&str]) -> String {
+    PcliCommand::new(args).to_string()
+}
+
+// Quote an argument for a POSIX shell: as it is when it only has safe characters, in single
+// quotes otherwise, and as $'...' with escapes when it has control characters such as newlines
+pub fn shell_quote(arg: &str) -> Cow<'_, str> {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        return Cow::Borrowed(arg);
+    }
+    if !arg.chars().any(char::is_control) {
+        return Cow::Owned(format!("'{}'", arg.replace('\'', "'\\''")));
+    }
+    let mut quoted = String::from("$'");
+    for c in arg.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\x{:02x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    Cow::Owned(quoted)
+}
+
+// Split a command line into arguments the way a POSIX shell would: single quotes keep
+// everything, double quotes and backslashes escape, and $'...' understands \n, \t and \xHH
+// (the inverse of format_command_line)
+pub fn split_command_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut has_arg = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                has_arg = true;
+            }
+            '\'' => {
+                current.extend(chars.by_ref().take_while(|&c| c != '\''));
+                has_arg = true;
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\'' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some('x') => {
+                                let hex: String = chars.by_ref().take(2).collect();
+                                if let Some(c) =
+                                    u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                {
+                                    current.push(c);
+                                }
+                            }
+                            Some(c) => current.push(c),
+                            None => {}
+                        },
+                        c => current.push(c),
+                    }
+                }
+                has_arg = true;
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            current.extend(chars.next());
+                        }
+                        c => current.push(c),
+                    }
+                }
+                has_arg = true;
+            }
+            c if c.is_whitespace() => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+// Where the stdout of a pcli2 invocation goes as it is read
+trait Output {
+    fn write(&mut self, bytes: &[u8]);
+    // Everything pcli2 printed, unless it was too large to keep
+    fn printed(&self) -> Option<&[u8]>;
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn printed(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+// Up to this much of a streamed listing is kept as printed, for the JSON viewer and error details
+const KEPT_OUTPUT_LIMIT: usize = 8 * 1024 * 1024;
+
+// Stdout parsed record by record while pcli2 prints it
+struct Listing<S> {
+    splitter: RecordSplitter,
+    sink: S,
+    printed: Option<Vec<u8>>, // Dropped once it grows past KEPT_OUTPUT_LIMIT
+    error: Option<serde_json::Error>,
+}
+
+impl<S: RecordSink> Listing<S> {
+    fn new(key: Option<&'static str>, sink: S) -> Self {
+        Self {
+            splitter: RecordSplitter::new(key),
+            sink,
+            printed: Some(Vec::new()),
+            error: None,
+        }
+    }
+
+    // The sink with every record, and whether the records array was found
+    fn finish(self) -> Result<(S, bool)> {
+        if let Some(e) = self.error {
+            return Err(e.into());
+        }
+        let found = self.splitter.finish()?;
+        Ok((self.sink, found))
+    }
+}
+
+impl<S: RecordSink> Output for Listing<S> {
+    fn write(&mut self, bytes: &[u8]) {
+        if let Some(ref mut printed) = self.printed {
+            printed.extend_from_slice(bytes);
+            if printed.len() > KEPT_OUTPUT_LIMIT {
+                self.printed = None;
+            }
+        }
+        let mut records = 0;
+        let (sink, error) = (&mut self.sink, &mut self.error);
+        self.splitter.push(bytes, |raw| {
+            match serde_json::from_slice(raw) {
+                Ok(value) => sink.record(value, raw),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+            records += 1;
+        });
+        count_records(records);
+    }
+
+    fn printed(&self) -> Option<&[u8]> {
+        self.printed.as_deref()
+    }
+}
+
+// Size of the reads from pcli2's stdout
+const READ_CHUNK: usize = 64 * 1024;
+
+// Feed a child's stdout to `output` as it arrives and collect its stderr, then wait for it to
+// exit. Returns its exit status and the number of bytes it printed to stdout.
+async fn read_child(
+    child: &mut Child,
+    output: &mut impl Output,
+    stderr: &mut Vec<u8>,
+) -> std::io::Result<(ExitStatus, usize)> {
+    let stdout_pipe = child.stdout.take();
+    let stderr_pipe = child.stderr.take();
+    let read_stdout = async {
+        let mut printed = 0;
+        if let Some(mut pipe) = stdout_pipe {
+            let mut buffer = vec![0; READ_CHUNK];
+            loop {
+                let read = pipe.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                printed += read;
+                output.write(&buffer[..read]);
+            }
+        }
+        Ok::<_, std::io::Error>(printed)
+    };
+    let read_stderr = async {
+        if let Some(mut pipe) = stderr_pipe {
+            pipe.read_to_end(stderr).await?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    let (printed, ()) = tokio::try_join!(read_stdout, read_stderr)?;
+    Ok((child.wait().await?, printed))
+}
+
+// Run pcli2 with the given arguments and return its stdout, or a PcliError with the full output
+async fn run_pcli(operation: &str, args: &[&str]) -> Result<String> {
+    let stdout = execute(operation, args, Vec::new).await?;
+    Ok(String::from_utf8(stdout)?)
+}
+
+// Run pcli2 and hand each record of its JSON listing to a sink as the output arrives, so a
+// large listing is parsed while pcli2 still prints it instead of being buffered whole. `key`
+// names the array holding the records when the listing is an object; returns whether the
+// array was found.
+async fn stream_pcli<S: RecordSink>(
+    operation: &str,
+    args: &[&str],
+    key: Option<&'static str>,
+    sink: impl Fn() -> S,
+) -> Result<(S, bool)> {
+    let listing = execute(operation, args, || Listing::new(key, sink())).await?;
+    listing.finish()
+}
+
+// Run pcli2 with the given arguments, writing its stdout to a fresh output for each attempt.
+// Transient failures are retried with exponential backoff according to the retry policy, and a
+// command that exceeds its timeout is killed and reported as a PcliTimeout.
+async fn execute<O: Output>(
+    operation: &str,
+    args: &[&str],
+    new_output: impl Fn() -> O,
+) -> Result<O> {
+    let policy = retry_policy();
+    let timeout = command_timeout(args);
+    let attempts = policy.attempts.max(1);
+    // The configured arguments are left out for the demo tenant, which wouldn't know them
+    let invocation = invocation();
+    let mut full_args = args.to_vec();
+    if !demo::is_enabled() {
+        full_args.extend(invocation.args.iter().map(String::as_str));
+    }
+    let command_line = format_command_line(&full_args);
+    let mut attempt = 1;
+    let background = is_background();
+    let _running = (!background).then(|| RunningGuard::start(operation));
+
+    if offline::is_enabled() {
+        record_job_command(args, None, None);
+        return Err(PcliOffline {
+            operation: operation.to_string(),
+        }
+        .into());
+    }
+
+    if demo::is_enabled() {
+        let started = Instant::now();
+        let response = demo::respond(args);
+        let exit_code = Some(if response.is_ok() { 0 } else { 1 });
+        record_run(started, exit_code);
+        record_job_command(args, Some(started), exit_code);
+        return match response {
+            Ok(stdout) => {
+                let mut output = new_output();
+                output.write(stdout.as_bytes());
+                if !background {
+                    record_output(&command_line, output.printed());
+                }
+                tracing::info!(command = operation, args = %command_line, "demo command succeeded");
+                Ok(output)
+            }
+            Err(stderr) => Err(PcliError {
+                operation: operation.to_string(),
+                command_line,
+                exit_code: Some(1),
+                stdout: String::new(),
+                stderr,
+            }
+            .into()),
+        };
+    }
+
+    let mut rate_limited_attempts = 0;
+    loop {
+        // Commands queued behind a rate limit start once it has passed
+        rate_limit::wait().await;
+        let slot = SCHEDULER.acquire(background).await;
+        let started = Instant::now();
+        let mut child = Command::new(&invocation.program)
+            .args(&full_args)
+            .envs(&invocation.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // The child is dropped when returning on timeout, which kills it
+        let mut output = new_output();
+        let mut stderr = Vec::new();
+        let read = read_child(&mut child, &mut output, &mut stderr);
+        let (status, stdout_bytes) = match tokio::time::timeout(timeout, read).await {
+            Ok(result) => result?,
+            Err(_) => {
+                tracing::error!(
+                    command = operation,
+                    args = %command_line,
+                    attempt,
+                    timeout_secs = timeout.as_secs(),
+                    "pcli2 command timed out and was killed"
+                );
+                record_run(started, None);
+                record_job_command(args, Some(started), None);
+                return Err(PcliTimeout {
+                    operation: operation.to_string(),
+                    command_line,
+                    timeout,
+                }
+                .into());
+            }
+        };
+        let duration_ms = started.elapsed().as_millis() as u64;
+        record_run(started, status.code());
+        if !background {
+            record_output(&command_line, output.printed());
+        }
+
+        if status.success() {
+            record_job_command(args, Some(started), status.code());
+            rate_limit::cleared();
+            permissions::succeeded(args);
+            tracing::info!(
+                command = operation,
+                args = %command_line,
+                attempt,
+                duration_ms,
+                exit_code = status.code(),
+                stdout_bytes,
+                stderr_bytes = stderr.len(),
+                "pcli2 command succeeded"
+            );
+            return Ok(output);
+        }
+
+        let error = PcliError {
+            operation: operation.to_string(),
+            command_line: command_line.clone(),
+            exit_code: status.code(),
+            stdout: output
+                .printed()
+                .map(|stdout| String::from_utf8_lossy(stdout).into_owned())
+                .unwrap_or_default(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        };
+
+        let rate_limited = rate_limit::is_rate_limited(&error.stderr);
+        let transient = !rate_limited && is_transient_failure(&error.stderr);
+        let retried = transient && is_read_only(args);
+        // Only an HTTP 429 shows a command that changes the tenant wasn't carried out
+        let retried_rate_limited =
+            rate_limited && (is_read_only(args) || rate_limit::is_refused(&error.stderr));
+        tracing::warn!(
+            command = operation,
+            args = %command_line,
+            attempt,
+            duration_ms,
+            exit_code = error.exit_code,
+            stdout_bytes,
+            stderr_bytes = error.stderr.len(),
+            transient,
+            rate_limited,
+            error = error.stderr.trim(),
+            "pcli2 command failed"
+        );
+
+        // Rate-limited attempts don't use up those for transient failures, and have their own
+        // limit; the wait before the next one is the rate limit's, which every command observes.
+        // A request the API answered with HTTP 429 was turned down before it was carried out, so
+        // commands that change the tenant are tried again too; a mere mention of a rate limit
+        // doesn't show that, so those are only retried when they are read-only.
+        if retried_rate_limited {
+            rate_limited_attempts += 1;
+        }
+        if retried_rate_limited && rate_limited_attempts < policy.rate_limit_attempts.max(1) {
+            let delay = rate_limit::hit(&error.stderr, &policy);
+            tracing::warn!(
+                command = operation,
+                delay_secs = delay.as_secs(),
+                "rate limited"
+            );
+            if !background && let Ok(mut events) = RETRY_EVENTS.lock() {
+                events.push(RetryEvent {
+                    command_line: error.command_line.clone(),
+                    attempt: rate_limited_attempts,
+                    attempts: policy.rate_limit_attempts.max(1),
+                    delay,
+                    reason: "rate limited".to_string(),
+                });
+            }
+            continue;
+        }
+
+        if attempt >= attempts || !retried {
+            if is_network_failure(&error.stderr) {
+                tracing::warn!(command = operation, "tenant unreachable, going offline");
+                offline::detected();
+            }
+            if is_permission_failure(&error.stderr) {
+                tracing::warn!(command = operation, "refused for want of permission");
+                permissions::refused(args, &error.stderr);
+            }
+            record_job_command(args, Some(started), error.exit_code);
+            return Err(error.into());
+        }
+
+        let delay = policy.backoff(attempt);
+        if !background && let Ok(mut events) = RETRY_EVENTS.lock() {
+            events.push(RetryEvent {
+                command_line: error.command_line.clone(),
+                attempt,
+                attempts,
+                delay,
+                reason: error
+                    .stderr
+                    .trim()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+
+        // Don't hold on to the slot while waiting to retry
+        drop(slot);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// Run pcli2 with arbitrary arguments typed by the user and return its stdout
+pub async fn run_raw(args: &[String]) -> Result<String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run_pcli("pcli2", &args).await
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetDetails {
@@ -61,100 +1679,238 @@ pub struct PcliAsset {
 }
 
 // Functions to interact with pcli2
-pub fn list_folders() -> Result<Vec<PcliFolder>> {
-    let output = Command::new("pcli2")
-        .args(["folder", "list", "--format", "json"])
-        .output()?;
+pub async fn list_folders() -> Result<Vec<PcliFolder>> {
+    count_listing();
+    let args = ["folder", "list", "--format", "json"];
+    let (folders, _) = stream_pcli("pcli2 folder list", &args, None, || {
+        RecordParser::new(&args, FOLDER_FIELDS)
+    })
+    .await?;
+
+    Ok(folders.finish())
+}
+
+pub async fn list_subfolders_of_folder(folder_path: &str) -> Result<Vec<PcliFolder>> {
+    count_listing();
+    // Use folder list with --folder-path to get subfolders of a specific folder
+    let args = [
+        "folder",
+        "list",
+        "--folder-path",
+        folder_path,
+        "--format",
+        "json",
+    ];
+    let (subfolders, _) = stream_pcli("pcli2 folder list", &args, None, || {
+        RecordParser::new(&args, FOLDER_FIELDS)
+    })
+    .await?;
+
+    Ok(subfolders.finish())
+}
+
+pub async fn list_assets_in_folder(folder_path: &str) -> Result<Vec<PcliAsset>> {
+    count_listing();
+    let args = [
+        "asset",
+        "list",
+        "--folder-path",
+        folder_path,
+        "--format",
+        "json",
+        "--metadata", // Include metadata in the asset listing
+    ];
+    let (assets, _) = stream_pcli("pcli2 asset list", &args, None, || {
+        RecordParser::new(&args, ASSET_FIELDS)
+    })
+    .await?;
+
+    Ok(assets.finish())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 folder list failed: {}", stderr));
+// Download an asset to `path`, reporting progress when a transfer is given
+pub async fn download_asset(
+    asset_uuid: &str,
+    path: &Path,
+    transfer: Option<Transfer>,
+) -> Result<()> {
+    let file = path.display().to_string();
+    let args = ["asset", "download", "--uuid", asset_uuid, "--file", &file];
+    let download = run_pcli("pcli2 asset download", &args);
+    let Some(transfer) = transfer else {
+        download.await?;
+        return Ok(());
+    };
+
+    // Poll the destination file for the transfer gauge until pcli2 finishes
+    let _progress = TransferGuard;
+    let started = Instant::now();
+    let started_at = SystemTime::now();
+    record_transfer(&transfer, started, started_at).await;
+    tokio::pin!(download);
+    loop {
+        tokio::select! {
+            result = &mut download => {
+                result?;
+                return Ok(());
+            }
+            _ = tokio::time::sleep(TRANSFER_POLL_INTERVAL) => {
+                record_transfer(&transfer, started, started_at).await;
+            }
+        }
     }
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let folders: Vec<PcliFolder> = serde_json::from_str(&stdout)?;
+// Download an asset to `path` without reporting progress
+pub async fn download_asset_to(asset_uuid: &str, path: &Path) -> Result<()> {
+    download_asset(asset_uuid, path, None).await
+}
 
-    Ok(folders)
+// Save an asset's thumbnail (PNG) to `path`
+pub async fn download_thumbnail(asset_uuid: &str, path: &Path) -> Result<()> {
+    let file = path.display().to_string();
+    let args = ["asset", "thumbnail", "--uuid", asset_uuid, "--file", &file];
+    run_pcli("pcli2 asset thumbnail", &args).await?;
+    Ok(())
 }
 
-pub fn list_subfolders_of_folder(folder_path: &str) -> Result<Vec<PcliFolder>> {
-    // Use folder list with --folder-path to get subfolders of a specific folder
-    let output = Command::new("pcli2")
-        .args([
-            "folder",
-            "list",
-            "--folder-path",
-            folder_path,
-            "--format",
-            "json",
-        ])
-        .output()?;
+// Upload a file and return the asset pcli2 created for it
+pub async fn create_asset(file_path: &str, folder_path: &str) -> Result<PcliAsset> {
+    let args = [
+        "asset",
+        "create",
+        "--file",
+        file_path,
+        "--folder",
+        folder_path,
+        "--format",
+        "json",
+    ];
+    let stdout = run_pcli("pcli2 asset upload", &args).await?;
+    let asset: PcliAsset = parse_record(&args, serde_json::from_str(&stdout)?, ASSET_FIELDS)?;
+    Ok(asset)
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 folder list failed: {}", stderr));
-    }
+// Delete an asset from the tenant
+pub async fn delete_asset(asset_uuid: &str) -> Result<()> {
+    run_pcli(
+        "pcli2 asset delete",
+        &["asset", "delete", "--uuid", asset_uuid],
+    )
+    .await?;
+    Ok(())
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let subfolders: Vec<PcliFolder> = serde_json::from_str(&stdout)?;
+// Move an asset into another folder
+pub async fn move_asset(asset_uuid: &str, folder_path: &str) -> Result<()> {
+    let args = [
+        "asset",
+        "move",
+        "--uuid",
+        asset_uuid,
+        "--folder-path",
+        folder_path,
+    ];
+    run_pcli("pcli2 asset move", &args).await?;
+    Ok(())
+}
 
-    Ok(subfolders)
+// Give an asset a new name, keeping it in its folder
+pub async fn rename_asset(asset_uuid: &str, name: &str) -> Result<()> {
+    let args = ["asset", "rename", "--uuid", asset_uuid, "--name", name];
+    run_pcli("pcli2 asset rename", &args).await?;
+    Ok(())
 }
 
-pub fn list_assets_in_folder(folder_path: &str) -> Result<Vec<PcliAsset>> {
-    let output = Command::new("pcli2")
-        .args([
-            "asset",
-            "list",
-            "--folder-path",
-            folder_path,
-            "--format",
-            "json",
-            "--metadata",  // Include metadata in the asset listing
-        ])
-        .output()?;
+// Set one metadata property of an asset, adding it when the asset has none by that name
+pub async fn set_metadata(asset_uuid: &str, name: &str, value: &str) -> Result<()> {
+    let args = [
+        "asset", "metadata", "create", "--uuid", asset_uuid, "--name", name, "--value", value,
+    ];
+    run_pcli("pcli2 asset metadata create", &args).await?;
+    Ok(())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 asset list failed: {}", stderr));
-    }
+// A metadata property defined in the tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PcliProperty {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub property_type: String,
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let assets: Vec<PcliAsset> = serde_json::from_str(&stdout)?;
+// The metadata properties defined in the tenant
+pub async fn list_properties() -> Result<Vec<PcliProperty>> {
+    let args = ["property", "list", "--format", "json"];
+    let stdout = run_pcli("pcli2 property list", &args).await?;
+    // Property records have none of the keys that tell the asset and folder schemas apart, so
+    // they are adapted without being checked against them
+    let properties = wrapped_listing(&stdout, "properties")?
+        .into_iter()
+        .map(|mut value| {
+            adapt_record(&mut value, PROPERTY_FIELDS);
+            serde_json::from_value(value)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(properties)
+}
 
-    Ok(assets)
+pub async fn create_property(name: &str, property_type: &str) -> Result<()> {
+    let args = [
+        "property",
+        "create",
+        "--name",
+        name,
+        "--type",
+        property_type,
+    ];
+    run_pcli("pcli2 property create", &args).await?;
+    Ok(())
 }
 
-pub fn download_asset(asset_uuid: &str) -> Result<()> {
-    let output = Command::new("pcli2")
-        .args(["asset", "download", "--uuid", asset_uuid])
-        .output()?;
+pub async fn delete_property(name: &str) -> Result<()> {
+    let args = ["property", "delete", "--name", name];
+    run_pcli("pcli2 property delete", &args).await?;
+    Ok(())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 asset download failed: {}", stderr));
+// Create a folder inside `parent_path`, or at the top level
+pub async fn create_folder(name: &str, parent_path: Option<&str>) -> Result<()> {
+    let mut args = vec!["folder", "create", "--name", name];
+    if let Some(parent_path) = parent_path {
+        args.extend(["--parent-folder-path", parent_path]);
     }
+    run_pcli("pcli2 folder create", &args).await?;
+    Ok(())
+}
 
+// Move a folder, with everything in it, into `parent_path`, or to the top level
+pub async fn move_folder(folder_path: &str, parent_path: Option<&str>) -> Result<()> {
+    let args = [
+        "folder",
+        "move",
+        "--folder-path",
+        folder_path,
+        "--parent-folder-path",
+        parent_path.unwrap_or("/"),
+    ];
+    run_pcli("pcli2 folder move", &args).await?;
     Ok(())
 }
 
-#[allow(dead_code)]
-pub fn upload_asset_to_folder(file_path: &str, folder_uuid: &str) -> Result<()> {
-    let output = Command::new("pcli2")
-        .args([
+pub async fn upload_asset_to_folder(file_path: &str, folder_uuid: &str) -> Result<()> {
+    run_pcli(
+        "pcli2 asset upload",
+        &[
             "asset",
             "create",
             "--file",
             file_path,
             "--folder",
             folder_uuid,
-        ])
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 asset upload failed: {}", stderr));
-    }
+        ],
+    )
+    .await?;
 
     Ok(())
 }
@@ -184,71 +1940,147 @@ struct SearchResultAsset {
     pub file_size: Option<u64>,
     #[serde(rename = "processing_status")]
     pub processing_status: Option<String>,
-    #[serde(rename = "created_at")]
-    pub created_at_legacy: Option<String>,
-    #[serde(rename = "updated_at")]
-    pub updated_at_legacy: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SearchResultMatch {
-    asset: SearchResultAsset,
-    #[serde(rename = "comparisonUrl")]
-    comparison_url: String,
+pub async fn get_asset_details(asset_uuid: &str) -> Result<AssetDetails> {
+    let args = [
+        "asset",
+        "get",
+        "--uuid",
+        asset_uuid,
+        "--format",
+        "json",
+        "--metadata",
+    ];
+    let stdout = run_pcli("pcli2 asset get", &args).await?;
+    let asset_details: AssetDetails =
+        parse_record(&args, serde_json::from_str(&stdout)?, ASSET_DETAIL_FIELDS)?;
+
+    Ok(asset_details)
 }
 
+// Bounding box extents along each axis
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+// Geometric properties of an asset, in `units` (millimeters unless pcli2 says otherwise)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct SearchResponse {
-    #[serde(rename = "searchQuery")]
-    search_query: String,
-    matches: Vec<SearchResultMatch>,
+pub struct Geometry {
+    #[serde(default, alias = "boundingBox", alias = "bbox")]
+    pub bounding_box: Option<BoundingBox>,
+    #[serde(default)]
+    pub volume: Option<f64>,
+    #[serde(default, alias = "surfaceArea")]
+    pub surface_area: Option<f64>,
+    #[serde(default = "default_geometry_units", alias = "unit")]
+    pub units: String,
 }
 
-pub fn get_asset_details(asset_uuid: &str) -> Result<AssetDetails> {
-    let output = Command::new("pcli2")
-        .args(["asset", "get", "--uuid", asset_uuid, "--format", "json", "--metadata"])
-        .output()?;
+fn default_geometry_units() -> String {
+    "mm".to_string()
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 asset get failed: {}", stderr));
-    }
+pub async fn get_asset_geometry(asset_uuid: &str) -> Result<Geometry> {
+    let args = [
+        "asset", "geometry", "--uuid", asset_uuid, "--format", "json",
+    ];
+    let stdout = run_pcli("pcli2 asset geometry", &args).await?;
+    let geometry: Geometry = serde_json::from_str(&stdout)?;
+    Ok(geometry)
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let asset_details: AssetDetails = serde_json::from_str(&stdout)?;
+// A listing that is either a bare JSON array or an object wrapping the array in `key`
+fn wrapped_listing(stdout: &str, key: &str) -> Result<Vec<serde_json::Value>> {
+    Ok(match serde_json::from_str(stdout)? {
+        serde_json::Value::Object(mut object) => match object.remove(key) {
+            Some(values) => serde_json::from_value(values)?,
+            None => Vec::new(),
+        },
+        value => serde_json::from_value(value)?,
+    })
+}
 
-    Ok(asset_details)
+// Assemblies that reference an asset, directly or through sub-assemblies
+pub async fn where_used(asset_uuid: &str) -> Result<Vec<PcliAsset>> {
+    let args = [
+        "asset",
+        "where-used",
+        "--uuid",
+        asset_uuid,
+        "--format",
+        "json",
+    ];
+    let (assets, _) = stream_pcli("pcli2 asset where-used", &args, Some("assemblies"), || {
+        RecordParser::new(&args, ASSET_FIELDS)
+    })
+    .await?;
+    Ok(assets.finish())
 }
 
-pub fn search_assets(query: &str) -> Result<Vec<PcliAsset>> {
-    // Use the exact working command with JSON format: pcli2 asset text-match --text <query> --format json --metadata
-    let output = Command::new("pcli2")
-        .args(["asset", "text-match", "--text", query, "--format", "json", "--metadata"])
-        .output()?;
+// Assets an assembly references directly; sub-assemblies list their own
+pub async fn asset_dependencies(asset_uuid: &str) -> Result<Vec<PcliAsset>> {
+    let args = [
+        "asset",
+        "dependencies",
+        "--uuid",
+        asset_uuid,
+        "--format",
+        "json",
+    ];
+    let (assets, _) = stream_pcli(
+        "pcli2 asset dependencies",
+        &args,
+        Some("dependencies"),
+        || RecordParser::new(&args, ASSET_FIELDS),
+    )
+    .await?;
+    Ok(assets.finish())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 asset search failed: {}", stderr));
+// Search the whole tenant, or only below `folder` when one is given
+pub async fn search_assets(query: &str, folder: Option<&str>) -> Result<Vec<PcliAsset>> {
+    // Use the exact working command with JSON format: pcli2 asset text-match --text <query> --format json --metadata
+    let mut args = vec!["asset", "text-match", "--text", query];
+    if let Some(folder) = folder {
+        args.extend(["--folder-path", folder]);
     }
-
-    let stdout = String::from_utf8(output.stdout)?;
-
-    // Parse the search results specifically using the search result structures
-    match serde_json::from_str::<SearchResponse>(&stdout) {
-        Ok(search_response) => {
-            let assets: Vec<PcliAsset> = search_response.matches.into_iter()
-                .map(|match_result| {
-                    let search_asset = match_result.asset;
+    args.extend(["--format", "json", "--metadata"]);
+    let (search_matches, found) = stream_pcli("pcli2 asset search", &args, Some("matches"), || {
+        SearchMatches(RecordParser::new(&args, SEARCH_ASSET_FIELDS))
+    })
+    .await?;
+
+    match found {
+        true => {
+            let search_assets = search_matches.0.finish();
+            let assets: Vec<PcliAsset> = search_assets
+                .into_iter()
+                .map(|search_asset| {
                     PcliAsset {
                         uuid: search_asset.uuid,
-                        name: search_asset.path.split('/').last().unwrap_or(&search_asset.path).to_string(), // Extract filename from path
+                        name: search_asset
+                            .path
+                            .split('/')
+                            .next_back()
+                            .unwrap_or(&search_asset.path)
+                            .to_string(), // Extract filename from path
                         path: search_asset.path,
                         file_type: search_asset.file_type,
                         file_size: search_asset.file_size,
-                        processing_status: search_asset.state.unwrap_or_else(|| "unknown".to_string()),
-                        created_at: search_asset.created_at.unwrap_or_else(|| search_asset.created_at_legacy.unwrap_or("unknown".to_string())),
-                        updated_at: search_asset.updated_at.unwrap_or_else(|| search_asset.updated_at_legacy.unwrap_or("unknown".to_string())),
+                        processing_status: search_asset
+                            .state
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        created_at: search_asset
+                            .created_at
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        updated_at: search_asset
+                            .updated_at
+                            .unwrap_or_else(|| "unknown".to_string()),
                         metadata: search_asset.metadata.unwrap_or(serde_json::Value::Null),
                         is_assembly: search_asset.is_assembly.unwrap_or(false),
                     }
@@ -257,16 +2089,24 @@ pub fn search_assets(query: &str) -> Result<Vec<PcliAsset>> {
 
             Ok(assets)
         }
-        Err(_) => {
-            // If parsing with dedicated structures fails, return an error with the raw output
-            Err(anyhow::anyhow!(
-                "Failed to parse search results as assets. Raw output: {}",
-                stdout
-            ))
-        }
+        false => Err(anyhow::anyhow!(
+            "Failed to parse search results as assets: no matches in the output"
+        )),
     }
 }
 
+// Search matches each wrap the asset found, parsed into SearchResultAsset
+struct SearchMatches(RecordParser<SearchResultAsset>);
+
+impl RecordSink for SearchMatches {
+    fn record(&mut self, mut value: serde_json::Value, raw: &[u8]) {
+        let asset = match value.get_mut("asset") {
+            Some(asset) => asset.take(),
+            None => value,
+        };
+        self.0.record(asset, raw);
+    }
+}
 
 // Structure to represent a geometric match result with the asset and its similarity score
 #[derive(Debug, Clone)]
@@ -275,10 +2115,11 @@ pub struct GeometricMatchEntry {
     pub similarity_score: f64,
 }
 
-pub fn geometric_match(asset_uuid: &str) -> Result<Vec<GeometricMatchEntry>> {
+pub async fn geometric_match(asset_uuid: &str) -> Result<Vec<GeometricMatchEntry>> {
     // Use the geometric-match command with JSON format and metadata
-    let output = Command::new("pcli2")
-        .args([
+    let (matches, found) = stream_pcli(
+        "pcli2 geometric match",
+        &[
             "asset",
             "geometric-match",
             "--uuid",
@@ -286,210 +2127,344 @@ pub fn geometric_match(asset_uuid: &str) -> Result<Vec<GeometricMatchEntry>> {
             "--format",
             "json",
             "--metadata",
-        ])
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!("pcli2 geometric match failed: {}", stderr));
-    }
-
-    let stdout = String::from_utf8(output.stdout)?;
-
-    // Parse the geometric match response with more flexible parsing
-    match serde_json::from_str::<serde_json::Value>(&stdout) {
-        Ok(json_value) => {
-            // Try different possible JSON structures that the geometric-match command might return
-
-            // Case 1: Standard structure with matches array
-            if let Some(matches_array) = json_value.get("matches").and_then(|v| v.as_array()) {
-                let mut match_entries = Vec::new();
-
-                for match_item in matches_array {
-                    if let Some(asset_obj) = match_item.get("asset").and_then(|v| v.as_object()) {
-                        // Try to extract asset properties from the object
-                        let uuid = asset_obj.get("id")
-                            .or_else(|| asset_obj.get("uuid"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let path = asset_obj.get("path")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let name = asset_obj.get("name")
-                            .or_else(|| asset_obj.get("filename"))  // Alternative field name
-                            .and_then(|v| v.as_str())
-                            .unwrap_or(path.split('/').last().unwrap_or(""))
-                            .to_string();
-
-                        let file_type = asset_obj.get("type")
-                            .or_else(|| asset_obj.get("file_type"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let file_size = asset_obj.get("file_size")
-                            .or_else(|| asset_obj.get("size"))
-                            .and_then(|v| v.as_u64());
-
-                        let processing_status = asset_obj.get("state")
-                            .or_else(|| asset_obj.get("processing_status"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let created_at = asset_obj.get("created_at")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let updated_at = asset_obj.get("updated_at")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let metadata = asset_obj.get("metadata")
-                            .cloned()
-                            .unwrap_or(serde_json::Value::Null);
-
-                        let is_assembly = asset_obj.get("is_assembly")
-                            .or_else(|| asset_obj.get("isAssembly"))
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false);
-
-                        let asset = PcliAsset {
-                            uuid: uuid,
-                            name: name,
-                            path: path,
-                            file_type: file_type,
-                            file_size: file_size,
-                            processing_status: processing_status,
-                            created_at: created_at,
-                            updated_at: updated_at,
-                            metadata: metadata,
-                            is_assembly: is_assembly,
-                        };
-
-                        // Extract the similarity score from the match item
-                        let similarity_score = match_item.get("similarityScore")
-                            .or_else(|| match_item.get("score"))
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0);
-
-                        let match_entry = GeometricMatchEntry {
-                            asset,
-                            similarity_score,
-                        };
-
-                        match_entries.push(match_entry);
-                    }
-                }
+        ],
+        Some("matches"),
+        || GeometricMatches(Vec::new()),
+    )
+    .await?;
+
+    if !found {
+        // If no known structure is found, return an error
+        return Err(anyhow::anyhow!(
+            "Failed to parse geometric match results as assets. Unknown JSON structure."
+        ));
+    }
+    Ok(matches.0)
+}
 
-                return Ok(match_entries);
-            }
+// The matches of a geometric match, parsed as they stream in. The response is either an object
+// whose `matches` wrap each asset with its similarity score, or a bare array of assets.
+struct GeometricMatches(Vec<GeometricMatchEntry>);
+
+impl RecordSink for GeometricMatches {
+    fn record(&mut self, value: serde_json::Value, _raw: &[u8]) {
+        // Standard structure: the asset with the similarity score next to it
+        if let Some(asset_obj) = value.get("asset").and_then(|v| v.as_object()) {
+            // Extract the similarity score from the match item
+            let similarity_score = value
+                .get("similarityScore")
+                .or_else(|| value.get("score"))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            self.0.push(GeometricMatchEntry {
+                asset: match_asset(asset_obj),
+                similarity_score,
+            });
+        } else if let Some(asset_obj) = value.as_object() {
+            // For direct arrays, assign a default similarity score
+            self.0.push(GeometricMatchEntry {
+                asset: match_asset(asset_obj),
+                similarity_score: 0.0, // Default score for fallback case
+            });
+        }
+    }
+}
 
-            // Case 2: Direct array of assets (fallback)
-            if let Some(assets_array) = json_value.as_array() {
-                let mut match_entries = Vec::new();
-
-                for asset_value in assets_array {
-                    if let Some(asset_obj) = asset_value.as_object() {
-                        // Extract asset properties from the object
-                        let uuid = asset_obj.get("id")
-                            .or_else(|| asset_obj.get("uuid"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let path = asset_obj.get("path")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let name = asset_obj.get("name")
-                            .or_else(|| asset_obj.get("filename"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or(path.split('/').last().unwrap_or(""))
-                            .to_string();
-
-                        let file_type = asset_obj.get("type")
-                            .or_else(|| asset_obj.get("file_type"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let file_size = asset_obj.get("file_size")
-                            .or_else(|| asset_obj.get("size"))
-                            .and_then(|v| v.as_u64());
-
-                        let processing_status = asset_obj.get("state")
-                            .or_else(|| asset_obj.get("processing_status"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let created_at = asset_obj.get("created_at")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let updated_at = asset_obj.get("updated_at")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let metadata = asset_obj.get("metadata")
-                            .cloned()
-                            .unwrap_or(serde_json::Value::Null);
-
-                        let is_assembly = asset_obj.get("is_assembly")
-                            .or_else(|| asset_obj.get("isAssembly"))
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false);
-
-                        let asset = PcliAsset {
-                            uuid: uuid,
-                            name: name,
-                            path: path,
-                            file_type: file_type,
-                            file_size: file_size,
-                            processing_status: processing_status,
-                            created_at: created_at,
-                            updated_at: updated_at,
-                            metadata: metadata,
-                            is_assembly: is_assembly,
-                        };
-
-                        // For direct arrays, assign a default similarity score
-                        let match_entry = GeometricMatchEntry {
-                            asset,
-                            similarity_score: 0.0, // Default score for fallback case
-                        };
-
-                        match_entries.push(match_entry);
-                    }
-                }
+// Extract the asset properties of a match, under whichever names the response uses
+fn match_asset(asset_obj: &serde_json::Map<String, serde_json::Value>) -> PcliAsset {
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| asset_obj.get(*key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let path = text(&["path"]).unwrap_or_default();
+    PcliAsset {
+        uuid: text(&["id", "uuid"]).unwrap_or_default(),
+        name: text(&["name", "filename"]) // Alternative field name
+            .unwrap_or_else(|| path.split('/').next_back().unwrap_or("").to_string()),
+        file_type: text(&["type", "file_type"]).unwrap_or_default(),
+        file_size: asset_obj
+            .get("file_size")
+            .or_else(|| asset_obj.get("size"))
+            .and_then(|v| v.as_u64()),
+        processing_status: text(&["state", "processing_status"]).unwrap_or_default(),
+        created_at: text(&["created_at"]).unwrap_or_default(),
+        updated_at: text(&["updated_at"]).unwrap_or_default(),
+        metadata: asset_obj
+            .get("metadata")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null),
+        is_assembly: asset_obj
+            .get("is_assembly")
+            .or_else(|| asset_obj.get("isAssembly"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The records split out of a listing fed in the given chunks, and how the listing ended
+    fn split(key: Option<&'static str>, chunks: &[&[u8]]) -> (Vec<String>, Result<bool>) {
+        let mut splitter = RecordSplitter::new(key);
+        let mut records = Vec::new();
+        for chunk in chunks {
+            splitter.push(chunk, |raw| {
+                records.push(String::from_utf8_lossy(raw).into_owned())
+            });
+        }
+        (records, splitter.finish())
+    }
 
-                return Ok(match_entries);
+    // Every way of cutting the listing in two, and byte by byte
+    fn split_every_way(key: Option<&'static str>, listing: &str) -> Vec<Vec<String>> {
+        let bytes = listing.as_bytes();
+        let mut results: Vec<Vec<String>> = (0..=bytes.len())
+            .map(|at| {
+                let (records, found) = split(key, &[&bytes[..at], &bytes[at..]]);
+                assert!(found.is_ok(), "cut at {}: {:?}", at, found);
+                records
+            })
+            .collect();
+        let single: Vec<&[u8]> = bytes.chunks(1).collect();
+        results.push(split(key, &single).0);
+        results
+    }
+
+    #[test]
+    fn records_split_across_chunks() {
+        let listing = r#"[ {"name": "a", "size": 1}, {"name": "b"}, 3, null ]"#;
+        for records in split_every_way(None, listing) {
+            assert_eq!(
+                records,
+                [
+                    r#"{"name": "a", "size": 1}"#,
+                    r#"{"name": "b"}"#,
+                    "3",
+                    "null"
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn strings_with_brackets_and_escaped_quotes() {
+        let listing = r#"[{"name": "a}b{c]"}, {"name": "say \"hi\" [", "path": "x\\"}]"#;
+        for records in split_every_way(None, listing) {
+            assert_eq!(
+                records,
+                [
+                    r#"{"name": "a}b{c]"}"#,
+                    r#"{"name": "say \"hi\" [", "path": "x\\"}"#
+                ]
+            );
+            for record in &records {
+                assert!(serde_json::from_str::<serde_json::Value>(record).is_ok());
             }
+        }
+    }
 
-            // If no known structure is found, return an error with the raw output
-            Err(anyhow::anyhow!(
-                "Failed to parse geometric match results as assets. Unknown JSON structure. Raw output: {}",
-                stdout
-            ))
+    #[test]
+    fn wrapped_listing() {
+        let listing = concat!(
+            r#"{"total": 2, "note": "assets", "#,
+            r#""assets": [{"a": 1}, {"a": [2]}], "more": [{"b": 3}]}"#
+        );
+        for records in split_every_way(Some("assets"), listing) {
+            assert_eq!(records, [r#"{"a": 1}"#, r#"{"a": [2]}"#]);
         }
-        Err(e) => {
-            // If JSON parsing fails completely, return an error
-            Err(anyhow::anyhow!(
-                "Failed to parse geometric match response as JSON: {}. Raw output: {}",
-                e,
-                stdout
-            ))
+        assert!(matches!(
+            split(Some("assets"), &[listing.as_bytes()]).1,
+            Ok(true)
+        ));
+
+        // An object without the key has no records, which isn't an error
+        let (records, found) = split(Some("assets"), &[br#"{"total": 0, "items": [1]}"#]);
+        assert!(records.is_empty());
+        assert!(matches!(found, Ok(false)));
+
+        // Without a key only a top-level array is a listing
+        assert!(split(None, &[br#"{"assets": []}"#]).1.is_err());
+    }
+
+    #[test]
+    fn output_that_is_not_a_listing() {
+        assert!(split(None, &[b"Error: not logged in"]).1.is_err());
+        assert!(split(None, &[b""]).1.is_err());
+        assert!(split(None, &[br#"[{"a": 1}, {"b": "#]).1.is_err());
+        assert!(split(None, &[br#"[{"a": "open]"#]).1.is_err());
+        assert!(split(None, &[b"[1]]"]).1.is_err());
+    }
+
+    #[test]
+    fn status_codes_in_a_status_context() {
+        let reqwest = "Error: HTTP status server error (502 Bad Gateway) for url (https://x/api)";
+        assert_eq!(http_statuses(reqwest), [502]);
+        assert_eq!(http_statuses("HTTP/1.1 429 Too Many Requests"), [429]);
+        assert_eq!(http_statuses("request failed with status: 403"), [403]);
+        assert_eq!(http_statuses("status code 500"), [500]);
+        assert_eq!(http_statuses("HTTP 503"), [503]);
+        assert_eq!(http_statuses("503 Service Unavailable"), [503]);
+        assert_eq!(http_statuses("API error: 403 Forbidden"), [403]);
+    }
+
+    #[test]
+    fn numbers_that_are_not_status_codes() {
+        assert!(http_statuses("wrote 512 bytes to Parts/500/bracket-503.stp").is_empty());
+        assert!(http_statuses("asset 5a0c-503 not found in folder 500").is_empty());
+        assert!(http_statuses("status: ok, 512 records").is_empty());
+        assert!(!is_transient_failure(
+            "Failed to read Parts/502/hinge.stp: No such file"
+        ));
+        assert!(!is_transient_failure("Error: no asset with id 550e8400"));
+    }
+
+    #[test]
+    fn transient_failures() {
+        assert!(is_transient_failure(
+            "HTTP status server error (504 Gateway Timeout)"
+        ));
+        assert!(is_transient_failure(
+            "error sending request: connection reset by peer"
+        ));
+        assert!(is_transient_failure("Error: status 500"));
+        assert!(!is_transient_failure(
+            "HTTP status client error (400 Bad Request)"
+        ));
+    }
+
+    #[test]
+    fn only_http_403_is_a_refusal() {
+        assert!(is_permission_failure(
+            "HTTP status client error (403 Forbidden) for url"
+        ));
+        assert!(is_permission_failure("Error: API returned status 403"));
+        assert!(!is_permission_failure("Permission denied (os error 13)"));
+        assert!(!is_permission_failure("Error: no folder named 403"));
+        assert!(!is_permission_failure(
+            "HTTP status client error (404 Not Found)"
+        ));
+    }
+
+    #[test]
+    fn only_read_only_commands_are_retried() {
+        assert!(is_read_only(&["folder", "list", "--format", "json"]));
+        assert!(is_read_only(&[
+            "asset", "download", "--uuid", "u", "--file", "f"
+        ]));
+        assert!(is_read_only(&["asset", "geometric-match", "--uuid", "u"]));
+        for args in [
+            &["asset", "create", "--file", "f", "--folder", "F"][..],
+            &["asset", "delete", "--uuid", "u"],
+            &["asset", "move", "--uuid", "u", "--folder-path", "F"],
+            &["asset", "rename", "--uuid", "u", "--name", "n"],
+            &["asset", "metadata", "create", "--uuid", "u"],
+            &["folder", "create", "--name", "n"],
+            &["property", "delete", "--name", "n"],
+            &["some", "future", "command"],
+        ] {
+            assert!(!is_read_only(args), "{:?}", args);
         }
     }
-}
 
+    fn round_trip(arg: &str) {
+        let quoted = shell_quote(arg);
+        assert_eq!(split_command_line(&quoted), [arg], "quoted as {}", quoted);
+    }
+
+    #[test]
+    fn quoting_round_trips() {
+        for arg in [
+            "plain",
+            "Parts/Brackets/bracket-A.stp",
+            "with space",
+            "  leading and trailing  ",
+            "it's",
+            "'",
+            "\"double\" quotes",
+            "back\\slash",
+            "$HOME `id` $(id) *?[a] ~ # ; & | < > !",
+            "line one\nline two",
+            "\n",
+            "tab\there\r\n",
+            "bell\x07 escape\x1b[31m delete\x7f nul-free",
+            "it's\na 'quoted' \\ line",
+            "\u{85}next line, non-breaking\u{a0}space",
+            "unicode: ü ß 日本語 😀",
+            "",
+        ] {
+            round_trip(arg);
+        }
+    }
+
+    #[test]
+    fn quoted_command_lines_split_into_their_arguments() {
+        let args = [
+            "asset",
+            "metadata",
+            "create",
+            "--value",
+            "",
+            "--name",
+            "it's\nhere",
+            "a b",
+        ];
+        let line = format_command_line(&args);
+        assert_eq!(split_command_line(&line)[1..], args);
+        assert_eq!(shell_quote("safe-arg_1.stp"), "safe-arg_1.stp");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Named {
+        name: String,
+    }
+
+    const NO_ALIASES: &FieldAliases = &[];
+
+    #[test]
+    fn malformed_records_are_skipped() {
+        let mut listing = Listing::new(None, RecordParser::<Named>::new(&["test"], NO_ALIASES));
+        listing.write(br#"[{"name": "a"}, {"name": 7}, {"nam"#);
+        listing.write(br#"e": "b"}, {"size": 1}]"#);
+        let (parser, found) = listing.finish().expect("the listing is valid JSON");
+        assert!(found);
+        let names: Vec<&str> = parser
+            .records
+            .iter()
+            .map(|named| named.name.as_str())
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        let skipped: Vec<usize> = parser.malformed.iter().map(|record| record.index).collect();
+        assert_eq!(skipped, [1, 3]);
+        assert!(parser.malformed[0].raw.contains("\"name\": 7"));
+    }
+
+    #[test]
+    fn invalid_json_in_a_record_fails_the_listing() {
+        let mut listing = Listing::new(None, RecordParser::<Named>::new(&["test"], NO_ALIASES));
+        listing.write(br#"[{"name": "a"}, {"name": }]"#);
+        assert!(listing.finish().is_err());
+    }
+
+    #[tokio::test]
+    async fn only_commands_of_batch_jobs_are_recorded() {
+        record_job_command(&["folder", "list"], None, Some(0));
+        in_job(async {
+            record_job_command(&["asset", "delete", "--uuid", "1"], None, Some(0));
+            record_job_command(&["asset", "delete", "--uuid", "2"], None, Some(1));
+        })
+        .await;
+
+        let commands = take_job_commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command.args, ["asset", "delete", "--uuid", "1"]);
+        assert!(commands[0].succeeded());
+        assert!(!commands[1].succeeded());
+        assert!(take_job_commands().is_empty());
+    }
+}
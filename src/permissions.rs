@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::config::Config;
+
+// Changes to the tenant a user's role may not allow. pcli2 has no command listing what the
+// signed-in user may do, so these are denied by the config; what the API refused is only
+// remembered until it's allowed again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Delete,       // Deleting assets
+    EditMetadata, // Setting metadata values, also through tags kept in a property
+    CreateFolder, // Creating folders
+}
+
+impl Permission {
+    // What is not permitted, e.g. "Deleting assets isn't permitted"
+    pub fn describe(self) -> &'static str {
+        match self {
+            Permission::Delete => "Deleting assets",
+            Permission::EditMetadata => "Editing metadata",
+            Permission::CreateFolder => "Creating folders",
+        }
+    }
+
+    // What it allows, as in the status line's "🔒 can't delete"
+    pub fn verb(self) -> &'static str {
+        match self {
+            Permission::Delete => "delete",
+            Permission::EditMetadata => "edit metadata",
+            Permission::CreateFolder => "create folders",
+        }
+    }
+
+    // The permission a pcli2 command needs, if it is one of these
+    fn of_command(args: &[&str]) -> Option<Permission> {
+        match args {
+            ["asset", "delete", ..] => Some(Permission::Delete),
+            ["asset", "metadata", "create" | "delete", ..] => Some(Permission::EditMetadata),
+            ["folder", "create", ..] => Some(Permission::CreateFolder),
+            _ => None,
+        }
+    }
+}
+
+// The permissions the config denies, with why
+static DENIED: Mutex<Vec<(Permission, String)>> = Mutex::new(Vec::new());
+
+// The permissions the API last refused a command for, with its error. A 403 may be about one
+// folder or asset only, so these are never enforced, and a command that then succeeds clears
+// its own.
+static REFUSED: Mutex<Vec<(Permission, String)>> = Mutex::new(Vec::new());
+
+fn deny(permission: Permission, reason: String) {
+    if let Ok(mut denied) = DENIED.lock()
+        && !denied.iter().any(|(denied, _)| *denied == permission)
+    {
+        denied.push((permission, reason));
+    }
+}
+
+// Start over from what the config denies, and its profile's; refusals under another profile
+// were another user's
+pub fn configure(config: &Config) {
+    if let Ok(mut denied) = DENIED.lock() {
+        denied.clear();
+    }
+    if let Ok(mut refused) = REFUSED.lock() {
+        refused.clear();
+    }
+    for &permission in &config.deny {
+        deny(permission, "the config denies it".to_string());
+    }
+    if let (Some(name), Some(profile)) = (config.active_profile.as_ref(), config.profile()) {
+        for &permission in &profile.deny {
+            deny(permission, format!("the {} profile denies it", name));
+        }
+    }
+}
+
+// The API refused a command with a 403: remember it for the permission the command needs
+pub fn refused(args: &[&str], stderr: &str) {
+    let Some(permission) = Permission::of_command(args) else {
+        return;
+    };
+    let error = stderr.trim().lines().next().unwrap_or_default().to_string();
+    if let Ok(mut refused) = REFUSED.lock() {
+        refused.retain(|(refused, _)| *refused != permission);
+        refused.push((permission, error));
+    }
+}
+
+// A command went through, so what it needs is no longer refused
+pub fn succeeded(args: &[&str]) {
+    if let Some(permission) = Permission::of_command(args)
+        && let Ok(mut refused) = REFUSED.lock()
+    {
+        refused.retain(|(refused, _)| *refused != permission);
+    }
+}
+
+// The error the API last refused the permission with, until a command needing it succeeds
+pub fn refusal(permission: Permission) -> Option<String> {
+    REFUSED.lock().ok().and_then(|refused| {
+        refused
+            .iter()
+            .find(|(refused, _)| *refused == permission)
+            .map(|(_, error)| error.clone())
+    })
+}
+
+// Why the user lacks the permission; None when what needs it may be tried
+pub fn denied(permission: Permission) -> Option<String> {
+    DENIED.lock().ok().and_then(|denied| {
+        denied
+            .iter()
+            .find(|(denied, _)| *denied == permission)
+            .map(|(_, reason)| reason.clone())
+    })
+}
+
+// The permissions the config denies, in the order they were denied
+pub fn all_denied() -> Vec<Permission> {
+    DENIED
+        .lock()
+        .map(|denied| denied.iter().map(|(permission, _)| *permission).collect())
+        .unwrap_or_default()
+}
+
+// The permissions the API refused and nothing has succeeded with since, the config's aside
+pub fn all_refused() -> Vec<Permission> {
+    let denied = all_denied();
+    REFUSED
+        .lock()
+        .map(|refused| {
+            refused
+                .iter()
+                .map(|(permission, _)| *permission)
+                .filter(|permission| !denied.contains(permission))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refusals_are_scoped_to_the_command_and_cleared_by_success() {
+        let error = "HTTP status client error (403 Forbidden)";
+        refused(&["asset", "delete", "--uuid", "u"], error);
+        refused(&["asset", "list", "--folder-path", "F"], error);
+        assert_eq!(refusal(Permission::Delete).as_deref(), Some(error));
+        assert_eq!(all_refused(), vec![Permission::Delete]);
+        assert_eq!(denied(Permission::Delete), None);
+
+        succeeded(&["folder", "create", "--name", "n"]);
+        assert_eq!(all_refused(), vec![Permission::Delete]);
+        succeeded(&["asset", "delete", "--uuid", "v"]);
+        assert_eq!(refusal(Permission::Delete), None);
+        assert!(all_refused().is_empty());
+    }
+}
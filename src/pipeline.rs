@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+use crate::config::ProcessingConfig;
+use crate::jobs::JobHandle;
+use crate::pcli_commands::{self, GeometricMatchEntry};
+use crate::processing;
+
+// Steps of the pipeline: upload, processing, geometric match
+const STEPS: usize = 3;
+
+// Matches of a file that went through the pipeline
+#[derive(Debug)]
+pub struct PipelineResult {
+    pub file: PathBuf,
+    pub folder: String,
+    pub uuid: String, // The uploaded asset
+    pub name: String,
+    pub matches: Vec<GeometricMatchEntry>,
+}
+
+// Upload-and-match: uploads a local file to a staging folder, waits for it to be processed
+// and geometric-matches it, in the background
+#[derive(Debug)]
+pub struct MatchPipeline {
+    sender: mpsc::UnboundedSender<PipelineResult>,
+    receiver: mpsc::UnboundedReceiver<PipelineResult>,
+}
+
+impl MatchPipeline {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { sender, receiver }
+    }
+
+    pub fn start(&self, job: JobHandle, file: PathBuf, folder: String, config: ProcessingConfig) {
+        let sender = self.sender.clone();
+        tokio::spawn(pcli_commands::in_job(async move {
+            let name = file.file_name().map_or_else(
+                || file.display().to_string(),
+                |name| name.to_string_lossy().to_string(),
+            );
+
+            job.progress(0, STEPS, format!("Uploading {}", name));
+            let path = file.display().to_string();
+            let asset =
+                pcli_commands::in_background(pcli_commands::create_asset(&path, &folder)).await;
+            let asset = match asset {
+                Ok(asset) => asset,
+                Err(e) => {
+                    job.finish(
+                        false,
+                        format!("Upload of {} failed", name),
+                        format!("{:#}", e),
+                    );
+                    return;
+                }
+            };
+
+            job.progress(1, STEPS, "Processing");
+            if let Err(e) =
+                processing::wait_until_processed(&job, 1, STEPS, &asset.uuid, &config).await
+            {
+                job.finish(
+                    false,
+                    format!("{} was uploaded but not processed", name),
+                    format!("{:#}", e),
+                );
+                return;
+            }
+
+            job.progress(2, STEPS, "Geometric match");
+            let matches =
+                pcli_commands::in_background(pcli_commands::geometric_match(&asset.uuid)).await;
+            let matches: Vec<GeometricMatchEntry> = match matches {
+                // The upload matches itself; leave it out
+                Ok(matches) => matches
+                    .into_iter()
+                    .filter(|m| m.asset.uuid != asset.uuid)
+                    .collect(),
+                Err(e) => {
+                    job.finish(
+                        false,
+                        format!("Geometric match of {} failed", name),
+                        format!("{:#}", e),
+                    );
+                    return;
+                }
+            };
+
+            let details: Vec<String> = matches
+                .iter()
+                .map(|m| format!("{:.2}%  {}", m.similarity_score, m.asset.path))
+                .collect();
+            job.finish(
+                true,
+                format!("{} matches for {}", matches.len(), name),
+                details.join("\n"),
+            );
+            // The receiver only goes away when the app shuts down
+            let _ = sender.send(PipelineResult {
+                file,
+                folder,
+                uuid: asset.uuid,
+                name,
+                matches,
+            });
+        }));
+    }
+
+    // Results that arrived since the last call
+    pub fn take_ready(&mut self) -> Vec<PipelineResult> {
+        let mut ready = Vec::new();
+        while let Ok(result) = self.receiver.try_recv() {
+            ready.push(result);
+        }
+        ready
+    }
+}
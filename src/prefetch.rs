@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Semaphore, mpsc, watch};
+
+use crate::app::{Folder, folder_listing};
+use crate::pcli_commands::{self, PcliAsset};
+
+// Prefetch commands allowed to run at once, so prefetching can't flood the API
+const CONCURRENCY: usize = 2;
+
+// Assets of a folder fetched ahead of time, with the listing the folder appears in
+#[derive(Debug)]
+pub struct Prefetched {
+    pub path: String,
+    pub listing: Vec<Folder>,
+    pub assets: Vec<PcliAsset>,
+}
+
+// State shared with the prefetch tasks
+#[derive(Debug, Clone)]
+struct Shared {
+    sender: mpsc::UnboundedSender<Prefetched>,
+    listings: mpsc::UnboundedSender<Vec<Folder>>, // Listings fetched for their folder counts
+    in_flight: Arc<Mutex<HashSet<String>>>, // Folders being prefetched, to avoid fetching twice
+    active: Arc<Mutex<Vec<String>>>, // What the running prefetch tasks fetch, for the jobs view
+    limit: Arc<Semaphore>,
+    paused: watch::Receiver<bool>, // Tasks wait before running pcli2 while this is true
+}
+
+impl Shared {
+    // Claim a folder for prefetching; false when it is already being fetched
+    fn claim(&self, path: &str) -> bool {
+        self.in_flight
+            .lock()
+            .map(|mut in_flight| in_flight.insert(path.to_string()))
+            .unwrap_or(false)
+    }
+
+    fn release(&self, path: &str) {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(path);
+        }
+    }
+
+    // Wait while prefetching is paused; commands already running are left to finish
+    async fn unpaused(&self) {
+        let mut paused = self.paused.clone();
+        // The sender only goes away when the app shuts down
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+
+    // Note a task that started, and take it off again once it ends
+    fn begin(&self, label: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.push(label.to_string());
+        }
+    }
+
+    fn end(&self, label: &str) {
+        if let Ok(mut active) = self.active.lock()
+            && let Some(index) = active.iter().position(|running| running == label)
+        {
+            active.remove(index);
+        }
+    }
+
+    fn fetch_assets(&self, path: String, listing: Vec<Folder>) {
+        if !self.claim(&path) {
+            return;
+        }
+
+        let shared = self.clone();
+        let label = format!("Assets of {}", path);
+        shared.begin(&label);
+        tokio::spawn(async move {
+            shared.unpaused().await;
+            let assets = match shared.limit.acquire().await {
+                Ok(_permit) => {
+                    pcli_commands::in_background(pcli_commands::list_assets_in_folder(&path)).await
+                }
+                Err(e) => Err(e.into()),
+            };
+            shared.release(&path);
+            shared.end(&label);
+
+            match assets {
+                Ok(assets) => {
+                    tracing::debug!(path = %path, assets = assets.len(), "prefetched assets");
+                    // The receiver only goes away when the app shuts down
+                    let _ = shared.sender.send(Prefetched {
+                        path,
+                        listing,
+                        assets,
+                    });
+                }
+                Err(e) => tracing::debug!(path = %path, error = %e, "prefetch failed"),
+            }
+        });
+    }
+}
+
+// Fetches folder contents in the background so that moving to them later is instant
+#[derive(Debug)]
+pub struct Prefetcher {
+    shared: Shared,
+    receiver: mpsc::UnboundedReceiver<Prefetched>,
+    listings: mpsc::UnboundedReceiver<Vec<Folder>>,
+    pause: watch::Sender<bool>,
+}
+
+impl Prefetcher {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let (listings_sender, listings) = mpsc::unbounded_channel();
+        let (pause, paused) = watch::channel(false);
+        Self {
+            shared: Shared {
+                sender,
+                listings: listings_sender,
+                in_flight: Arc::new(Mutex::new(HashSet::new())),
+                active: Arc::new(Mutex::new(Vec::new())),
+                limit: Arc::new(Semaphore::new(CONCURRENCY)),
+                paused,
+            },
+            receiver,
+            listings,
+            pause,
+        }
+    }
+
+    // Hold back prefetches, those asked for from now on and those waiting for their turn,
+    // e.g. while the terminal isn't focused
+    pub fn set_paused(&self, paused: bool) {
+        self.pause.send_replace(paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.pause.borrow()
+    }
+
+    // Prefetch the assets of a folder shown in the given listing
+    pub fn fetch_assets(&self, path: String, listing: Vec<Folder>) {
+        self.shared.fetch_assets(path, listing);
+    }
+
+    // List a folder's subfolders and prefetch the assets of the first `count` of them
+    pub fn fetch_children(&self, path: String, count: usize) {
+        let shared = self.shared.clone();
+        let label = format!("Subfolders of {}", path);
+        shared.begin(&label);
+        tokio::spawn(async move {
+            shared.unpaused().await;
+            let subfolders = match shared.limit.acquire().await {
+                Ok(_permit) => {
+                    pcli_commands::in_background(pcli_commands::list_subfolders_of_folder(&path))
+                        .await
+                }
+                Err(e) => Err(e.into()),
+            };
+            shared.end(&label);
+
+            match subfolders {
+                Ok(subfolders) => {
+                    let listing = folder_listing(&path, subfolders);
+                    for child in listing.iter().filter(|f| f.uuid != "..").take(count) {
+                        shared.fetch_assets(child.path.clone(), listing.clone());
+                    }
+                }
+                Err(e) => tracing::debug!(path = %path, error = %e, "prefetch failed"),
+            }
+        });
+    }
+
+    // List a folder's subfolders again for their current folder and asset counts; "" lists
+    // the top-level folders
+    pub fn fetch_listing(&self, path: String) {
+        let shared = self.shared.clone();
+        let label = if path.is_empty() {
+            "Folder counts at the top level".to_string()
+        } else {
+            format!("Folder counts in {}", path)
+        };
+        shared.begin(&label);
+        tokio::spawn(async move {
+            shared.unpaused().await;
+            let subfolders = match shared.limit.acquire().await {
+                Ok(_permit) if path.is_empty() => {
+                    let folders = pcli_commands::in_background(pcli_commands::list_folders()).await;
+                    folders.map(|folders| {
+                        folders
+                            .into_iter()
+                            .filter(|f| !f.path.trim_matches('/').contains('/'))
+                            .collect()
+                    })
+                }
+                Ok(_permit) => {
+                    pcli_commands::in_background(pcli_commands::list_subfolders_of_folder(&path))
+                        .await
+                }
+                Err(e) => Err(e.into()),
+            };
+            shared.end(&label);
+
+            match subfolders {
+                Ok(subfolders) => {
+                    let _ = shared.listings.send(folder_listing(&path, subfolders));
+                }
+                Err(e) => tracing::debug!(path = %path, error = %e, "folder count refresh failed"),
+            }
+        });
+    }
+
+    // What the prefetch tasks still running or waiting for their turn are fetching
+    pub fn active(&self) -> Vec<String> {
+        self.shared
+            .active
+            .lock()
+            .map(|active| active.clone())
+            .unwrap_or_default()
+    }
+
+    // Results that arrived since the last call
+    pub fn take_ready(&mut self) -> Vec<Prefetched> {
+        let mut ready = Vec::new();
+        while let Ok(prefetched) = self.receiver.try_recv() {
+            ready.push(prefetched);
+        }
+        ready
+    }
+
+    pub fn take_listings(&mut self) -> Vec<Vec<Folder>> {
+        let mut listings = Vec::new();
+        while let Ok(listing) = self.listings.try_recv() {
+            listings.push(listing);
+        }
+        listings
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::pcli_commands;
+
+// Cell size assumed when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_PIXELS: (u32, u32) = (8, 16);
+
+// Largest thumbnail kept in memory; bigger images are scaled down when they arrive
+const MAX_THUMBNAIL_PIXELS: u32 = 512;
+
+// How the terminal can show images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    Blocks, // Unicode half blocks drawn with ratatui, which works everywhere
+}
+
+impl Protocol {
+    // The configured protocol, or for "auto" one guessed from the environment
+    pub fn detect(configured: &str) -> Self {
+        match configured {
+            "kitty" => return Protocol::Kitty,
+            "iterm2" => return Protocol::Iterm2,
+            "sixel" => return Protocol::Sixel,
+            "blocks" => return Protocol::Blocks,
+            _ => {}
+        }
+
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+        // Multiplexers swallow graphics escape sequences
+        if std::env::var_os("TMUX").is_some() || term.starts_with("screen") {
+            Protocol::Blocks
+        } else if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || program == "ghostty"
+        {
+            Protocol::Kitty
+        } else if program == "iTerm.app" || program == "WezTerm" {
+            Protocol::Iterm2
+        } else if term.starts_with("foot") || term.contains("mlterm") || term.contains("sixel") {
+            Protocol::Sixel
+        } else {
+            Protocol::Blocks
+        }
+    }
+}
+
+// A thumbnail as pcli2 saved it (PNG) and decoded
+#[derive(Debug)]
+pub struct Thumbnail {
+    pub png: Vec<u8>,
+    pub image: RgbaImage,
+}
+
+#[derive(Debug, Clone)]
+pub enum ThumbnailState {
+    Loading,
+    Ready(Arc<Thumbnail>),
+    Failed(String),
+}
+
+// Fetches asset thumbnails in the background and draws them with the terminal's protocol
+#[derive(Debug)]
+pub struct Previewer {
+    protocol: Protocol,
+    cache: HashMap<String, ThumbnailState>,
+    sender: mpsc::UnboundedSender<(String, ThumbnailState)>,
+    receiver: mpsc::UnboundedReceiver<(String, ThumbnailState)>,
+    shown: Option<(String, Rect)>, // Image currently on screen and where
+    stale: bool,                   // Whether the image may have been drawn over since
+}
+
+impl Previewer {
+    pub fn new(protocol: Protocol) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            protocol,
+            cache: HashMap::new(),
+            sender,
+            receiver,
+            shown: None,
+            stale: false,
+        }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&ThumbnailState> {
+        self.cache.get(uuid)
+    }
+
+    // Start fetching an asset's thumbnail unless it is cached or on its way
+    pub fn request(&mut self, uuid: &str) {
+        if self.cache.contains_key(uuid) {
+            return;
+        }
+        self.cache.insert(uuid.to_string(), ThumbnailState::Loading);
+
+        let uuid = uuid.to_string();
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let state = match pcli_commands::in_background(fetch(&uuid)).await {
+                Ok(thumbnail) => ThumbnailState::Ready(Arc::new(thumbnail)),
+                Err(e) => {
+                    tracing::debug!(uuid = %uuid, error = %e, "thumbnail unavailable");
+                    ThumbnailState::Failed(format!("{:#}", e))
+                }
+            };
+            // The receiver only goes away when the app shuts down
+            let _ = sender.send((uuid, state));
+        });
+    }
+
+    // Move fetched thumbnails into the cache
+    pub fn apply_ready(&mut self) {
+        while let Ok((uuid, state)) = self.receiver.try_recv() {
+            self.cache.insert(uuid, state);
+        }
+    }
+
+    // Something may have been drawn over the image; draw it again on the next present
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    // Show the wanted thumbnail (asset UUID and area) with a graphics protocol after a frame
+    // was drawn. Returns true when the terminal must be cleared to remove an old image; the
+    // next frame then shows the wanted one.
+    pub fn present(
+        &mut self,
+        out: &mut impl Write,
+        wanted: Option<(String, Rect)>,
+    ) -> Result<bool> {
+        if self.protocol == Protocol::Blocks || (self.shown == wanted && !self.stale) {
+            return Ok(false);
+        }
+        self.stale = false;
+
+        if let Some(shown) = self.shown.take() {
+            if self.protocol == Protocol::Kitty {
+                write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+            } else if wanted.as_ref() != Some(&shown) {
+                // Sixel and iTerm2 images are part of the screen contents
+                return Ok(true);
+            }
+        }
+
+        let Some((uuid, area)) = wanted else {
+            out.flush()?;
+            return Ok(false);
+        };
+        let Some(ThumbnailState::Ready(thumbnail)) = self.cache.get(&uuid) else {
+            return Ok(false);
+        };
+
+        let thumbnail = Arc::clone(thumbnail);
+        self.draw(out, &thumbnail, area)?;
+        self.shown = Some((uuid, area));
+        Ok(false)
+    }
+
+    fn draw(&self, out: &mut impl Write, thumbnail: &Thumbnail, area: Rect) -> io::Result<()> {
+        if area.width == 0 || area.height == 0 {
+            return Ok(());
+        }
+        let (cell_width, cell_height) = cell_pixels();
+        let (width, height) = fit(
+            thumbnail.image.width(),
+            thumbnail.image.height(),
+            area.width as u32 * cell_width,
+            area.height as u32 * cell_height,
+        );
+        let columns = width.div_ceil(cell_width).clamp(1, area.width as u32) as u16;
+        let rows = height.div_ceil(cell_height).clamp(1, area.height as u32) as u16;
+
+        // Center the image in the area
+        let x = area.x + (area.width - columns) / 2;
+        let y = area.y + (area.height - rows) / 2;
+        crossterm::queue!(out, crossterm::cursor::MoveTo(x, y))?;
+
+        match self.protocol {
+            Protocol::Kitty => {
+                let data = base64(&thumbnail.png);
+                let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    let chunk = String::from_utf8_lossy(chunk);
+                    if i == 0 {
+                        write!(
+                            out,
+                            "\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\",
+                            columns, rows, more, chunk
+                        )?;
+                    } else {
+                        write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk)?;
+                    }
+                }
+            }
+            Protocol::Iterm2 => {
+                write!(
+                    out,
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                    thumbnail.png.len(),
+                    columns,
+                    rows,
+                    base64(&thumbnail.png)
+                )?;
+            }
+            Protocol::Sixel => {
+                let image = image::imageops::thumbnail(&thumbnail.image, width, height);
+                out.write_all(sixel(&image).as_bytes())?;
+            }
+            Protocol::Blocks => {}
+        }
+        out.flush()
+    }
+}
+
+async fn fetch(uuid: &str) -> Result<Thumbnail> {
+    let path = std::env::temp_dir().join(format!("pcli2-tui-thumbnail-{}.png", uuid));
+    pcli_commands::download_thumbnail(uuid, &path).await?;
+    let png = tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()));
+    let _ = tokio::fs::remove_file(&path).await;
+    let png = png?;
+
+    let mut image = image::load_from_memory(&png)
+        .context("Thumbnail is not a PNG image")?
+        .to_rgba8();
+    if image.width() > MAX_THUMBNAIL_PIXELS || image.height() > MAX_THUMBNAIL_PIXELS {
+        let (width, height) = fit(
+            image.width(),
+            image.height(),
+            MAX_THUMBNAIL_PIXELS,
+            MAX_THUMBNAIL_PIXELS,
+        );
+        image = image::imageops::thumbnail(&image, width, height);
+    }
+    Ok(Thumbnail { png, image })
+}
+
+// Size of one cell in pixels, from the terminal when it reports it
+fn cell_pixels() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns).max(1) as u32,
+            (size.height / size.rows).max(1) as u32,
+        ),
+        _ => DEFAULT_CELL_PIXELS,
+    }
+}
+
+// Largest size with the image's aspect ratio that fits the bounds
+fn fit(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width == 0 || height == 0 {
+        return (1, 1);
+    }
+    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+    (
+        ((width as f64 * scale) as u32).max(1),
+        ((height as f64 * scale) as u32).max(1),
+    )
+}
+
+// Draw the image with "▀" cells (top pixel as foreground, bottom as background), centered in
+// an area of the given size
+pub fn half_blocks(image: &RgbaImage, columns: u16, rows: u16) -> Vec<Line<'static>> {
+    let (width, height) = fit(
+        image.width(),
+        image.height(),
+        columns as u32,
+        rows as u32 * 2,
+    );
+    let image = image::imageops::thumbnail(image, width, height);
+    let pad = " ".repeat(((columns as u32).saturating_sub(width) / 2) as usize);
+    let color = |x: u32, y: u32| -> Option<Color> {
+        let pixel = image.get_pixel_checked(x, y)?;
+        (pixel[3] >= 128).then(|| Color::Rgb(pixel[0], pixel[1], pixel[2]))
+    };
+
+    (0..height.div_ceil(2))
+        .map(|row| {
+            let mut spans = vec![Span::raw(pad.clone())];
+            for x in 0..width {
+                let mut style = Style::default();
+                let (top, bottom) = (color(x, row * 2), color(x, row * 2 + 1));
+                if let Some(top) = top {
+                    style = style.fg(top);
+                }
+                if let Some(bottom) = bottom {
+                    style = style.bg(bottom);
+                }
+                let symbol = if top.is_some() { "▀" } else { " " };
+                spans.push(Span::styled(symbol, style));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Encode the image as sixels using a fixed 6x6x6 color cube
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+    for index in 0..216u32 {
+        let level = |v: u32| v * 100 / 5;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            index,
+            level(index / 36),
+            level(index / 6 % 6),
+            level(index % 6)
+        ));
+    }
+
+    let quantize = |v: u8| (v as u32 * 5 + 127) / 255;
+    for band in (0..height).step_by(6) {
+        // Bits of each color in this band of six pixel rows, one byte per column
+        let mut colors: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+        for y in band..(band + 6).min(height) {
+            for x in 0..width {
+                let pixel = image.get_pixel(x, y);
+                if pixel[3] < 128 {
+                    continue;
+                }
+                let index = quantize(pixel[0]) * 36 + quantize(pixel[1]) * 6 + quantize(pixel[2]);
+                colors
+                    .entry(index)
+                    .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << (y - band);
+            }
+        }
+
+        for (i, (index, bits)) in colors.iter().enumerate() {
+            if i > 0 {
+                out.push('$'); // Back to the start of the band for the next color
+            }
+            out.push_str(&format!("#{}", index));
+            let mut run = 0;
+            let mut previous = None;
+            for &byte in bits.iter().chain(std::iter::once(&u8::MAX)) {
+                if previous == Some(byte) {
+                    run += 1;
+                    continue;
+                }
+                if let Some(previous) = previous {
+                    push_sixel_run(&mut out, previous, run);
+                }
+                previous = Some(byte);
+                run = 1;
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_sixel_run(out: &mut String, bits: u8, run: usize) {
+    let symbol = (63 + bits) as char;
+    if run > 3 {
+        out.push_str(&format!("!{}{}", run, symbol));
+    } else {
+        out.extend(std::iter::repeat_n(symbol, run));
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{Result, bail};
+use std::time::{Duration, Instant};
+
+use crate::config::ProcessingConfig;
+use crate::jobs::JobHandle;
+use crate::pcli_commands;
+
+// Where an asset's processing stands, judged from its processing_status
+#[derive(Debug, Clone, PartialEq)]
+pub enum Processing {
+    Running(String),
+    Ready,
+    Failed(String),
+}
+
+impl Processing {
+    pub fn from_status(status: &str) -> Self {
+        match status.trim().to_lowercase().as_str() {
+            "ready" | "finished" | "complete" | "completed" | "processed" | "indexed"
+            | "success" | "succeeded" => Processing::Ready,
+            "failed" | "failure" | "error" | "errored" => Processing::Failed(status.to_string()),
+            _ => Processing::Running(status.to_string()),
+        }
+    }
+}
+
+// Poll `pcli2 asset get` until the asset is processed, reporting each status on the job as
+// step `step` of `total`. Fails when processing fails or takes longer than the timeout.
+pub async fn wait_until_processed(
+    job: &JobHandle,
+    step: usize,
+    total: usize,
+    uuid: &str,
+    config: &ProcessingConfig,
+) -> Result<()> {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(config.timeout_secs);
+    loop {
+        let details = pcli_commands::in_background(pcli_commands::get_asset_details(uuid)).await?;
+        match Processing::from_status(&details.processing_status) {
+            Processing::Ready => return Ok(()),
+            Processing::Failed(status) => bail!("processing {}", status),
+            Processing::Running(status) => {
+                job.progress(step, total, format!("Processing: {}", status));
+            }
+        }
+        if started.elapsed() >= timeout {
+            bail!(
+                "still processing after {}s (status {})",
+                timeout.as_secs(),
+                details.processing_status
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(config.poll_secs.max(1))).await;
+    }
+}
+
+// Follow an uploaded asset until it is processed and end the job with the outcome
+pub async fn watch(job: JobHandle, uuid: String, name: String, config: ProcessingConfig) {
+    match wait_until_processed(&job, 0, 1, &uuid, &config).await {
+        Ok(()) => job.finish(
+            true,
+            format!("{} is processed and ready to match", name),
+            String::new(),
+        ),
+        Err(e) => job.finish(
+            false,
+            format!("Processing of {} did not finish", name),
+            format!("{:#}", e),
+        ),
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::RetryConfig;
+use crate::pcli_commands::http_statuses;
+
+// When the tenant's API rate limit was last hit, pcli2 commands wait it out before starting
+// instead of failing one after the other
+static STATE: Mutex<RateLimit> = Mutex::new(RateLimit {
+    until: None,
+    hits: 0,
+});
+
+// Set when the rate limit was hit while it wasn't in effect, until the app has reported it
+static DETECTED: AtomicBool = AtomicBool::new(false);
+
+struct RateLimit {
+    until: Option<Instant>, // When commands may start again
+    hits: u32,              // Rate-limited failures since a command last succeeded
+}
+
+// Whether the failure means the tenant's API turned the request down for coming too often.
+// Only an HTTP 429 or a phrase saying so counts: a bare "429" may as well be a file name.
+pub fn is_rate_limited(stderr: &str) -> bool {
+    const RATE_LIMIT_MARKERS: [&str; 7] = [
+        "too many requests",
+        "rate limit exceeded",
+        "rate-limit exceeded",
+        "rate limited",
+        "rate-limited",
+        "throttled",
+        "quota exceeded",
+    ];
+
+    let lowercase = stderr.to_lowercase();
+    is_refused(stderr)
+        || RATE_LIMIT_MARKERS
+            .iter()
+            .any(|marker| lowercase.contains(marker))
+}
+
+// Whether the API answered HTTP 429, so the request was turned down before it was carried out
+// and sending it again can't do anything twice
+pub fn is_refused(stderr: &str) -> bool {
+    http_statuses(stderr).contains(&429)
+}
+
+// How long the API asked to wait, when the error says, e.g. "Retry-After: 30"
+fn retry_after(stderr: &str) -> Option<Duration> {
+    const RETRY_AFTER_MARKERS: [&str; 4] =
+        ["retry-after", "retry after", "retry in", "try again in"];
+
+    let stderr = stderr.to_lowercase();
+    RETRY_AFTER_MARKERS.iter().find_map(|marker| {
+        let rest = &stderr[stderr.find(marker)? + marker.len()..];
+        let rest = rest.trim_start_matches([':', '=', ' ']);
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        rest[..digits].parse().ok().map(Duration::from_secs)
+    })
+}
+
+// A command was rate limited: pause every command for as long as the API asked, or else for
+// the configured pause, doubled for each further hit before a command succeeds. Returns how
+// long until commands resume.
+pub fn hit(stderr: &str, policy: &RetryConfig) -> Duration {
+    let Ok(mut state) = STATE.lock() else {
+        return Duration::from_secs(policy.rate_limit_secs);
+    };
+    let now = Instant::now();
+    if state.until.is_none_or(|until| until <= now) {
+        DETECTED.store(true, Ordering::Relaxed);
+    }
+    state.hits += 1;
+    let max = Duration::from_secs(policy.max_rate_limit_secs.max(1));
+    let delay = retry_after(stderr)
+        .unwrap_or_else(|| {
+            let factor = 2u64.saturating_pow(state.hits - 1);
+            Duration::from_secs(policy.rate_limit_secs.saturating_mul(factor))
+        })
+        .min(max);
+    let until = now + delay;
+    state.until = Some(state.until.map_or(until, |current| current.max(until)));
+    state.until.map_or(delay, |until| until - now)
+}
+
+// A command went through, so the next hit starts over at the configured pause
+pub fn cleared() {
+    if let Ok(mut state) = STATE.lock() {
+        state.hits = 0;
+    }
+}
+
+// How long until commands resume, while a rate limit is in effect
+pub fn remaining() -> Option<Duration> {
+    let until = STATE.lock().ok()?.until?;
+    until
+        .checked_duration_since(Instant::now())
+        .filter(|remaining| !remaining.is_zero())
+}
+
+// Whether the rate limit was hit since the last call
+pub fn take_detected() -> bool {
+    DETECTED.swap(false, Ordering::Relaxed)
+}
+
+// Wait until commands may start again; another hit meanwhile makes it wait longer
+pub async fn wait() {
+    while let Some(remaining) = remaining() {
+        tokio::time::sleep(remaining).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_an_http_429_or_a_throttling_phrase_is_a_rate_limit() {
+        assert!(is_rate_limited(
+            "Error: HTTP status client error (429 Too Many Requests) for url (https://api)"
+        ));
+        assert!(is_rate_limited("API error: status 429"));
+        assert!(is_rate_limited(
+            "Error: rate limit exceeded, retry after 30s"
+        ));
+        assert!(is_rate_limited("Error: Too Many Requests"));
+        assert!(!is_rate_limited(
+            "Error: failed to read /parts/429/429.step"
+        ));
+        assert!(!is_rate_limited(
+            "Error: asset 429 not found in folder Brackets"
+        ));
+        assert!(!is_rate_limited("Error: file throttle-body.step is empty"));
+    }
+
+    #[test]
+    fn only_an_http_429_shows_the_request_was_refused() {
+        assert!(is_refused(
+            "HTTP status client error (429 Too Many Requests)"
+        ));
+        assert!(!is_refused("Error: rate limit exceeded"));
+        assert!(!is_refused("Error: uploading 429.step failed"));
+    }
+
+    #[test]
+    fn retry_after_is_read_from_the_error() {
+        assert_eq!(
+            retry_after("429 Too Many Requests, Retry-After: 30"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after("try again in 5 seconds"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(retry_after("429 Too Many Requests"), None);
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::Local;
+
+use crate::app::{Asset, metadata_keys};
+use crate::text;
+
+// An asset to report on, with the geometric matches to list under it (none when it wasn't
+// matched)
+pub struct ReportAsset<'a> {
+    pub asset: &'a Asset,
+    pub matches: Vec<&'a (Asset, f64)>,
+}
+
+// Web pages linked from the report: asset_url and compare_url of the config
+#[derive(Clone, Copy)]
+pub struct Links<'a> {
+    pub asset_url: Option<&'a str>,
+    pub compare_url: Option<&'a str>,
+}
+
+impl Links<'_> {
+    fn asset(&self, asset: &Asset) -> Option<String> {
+        self.asset_url.map(|template| {
+            template
+                .replace("{uuid}", &asset.uuid)
+                .replace("{path}", &asset.path)
+        })
+    }
+
+    fn compare(&self, source: &Asset, matched: &Asset) -> Option<String> {
+        self.compare_url.map(|template| {
+            template
+                .replace("{uuid}", &source.uuid)
+                .replace("{match_uuid}", &matched.uuid)
+        })
+    }
+}
+
+// A piece of text, possibly a link
+struct Text {
+    text: String,
+    link: Option<String>,
+}
+
+impl Text {
+    fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            link: None,
+        }
+    }
+
+    fn linked(text: impl Into<String>, link: Option<String>) -> Self {
+        Self {
+            text: text.into(),
+            link,
+        }
+    }
+}
+
+// The report as a list of blocks, rendered to Markdown or HTML
+enum Block {
+    Heading(u8, Text),
+    Paragraph(String),
+    Table(Vec<&'static str>, Vec<Vec<Text>>),
+}
+
+fn blocks(assets: &[ReportAsset], links: Links) -> Vec<Block> {
+    let mut blocks = vec![
+        Block::Heading(1, Text::plain("Asset report")),
+        Block::Paragraph(format!(
+            "{} asset(s), generated by pcli2-tui on {}",
+            assets.len(),
+            Local::now().format("%Y-%m-%d %H:%M")
+        )),
+    ];
+    for ReportAsset { asset, matches } in assets {
+        blocks.push(Block::Heading(
+            2,
+            Text::linked(&asset.name, links.asset(asset)),
+        ));
+        let size = asset
+            .size
+            .map(text::format_bytes)
+            .unwrap_or_else(|| "—".to_string());
+        let mut details = vec![
+            ("Path", asset.path.clone()),
+            ("UUID", asset.uuid.clone()),
+            ("Type", asset.file_type.clone()),
+            ("Size", size),
+            (
+                "Assembly",
+                if asset.is_assembly { "yes" } else { "no" }.to_string(),
+            ),
+        ];
+        if !asset.processing_status.is_empty() {
+            details.push(("Processing", asset.processing_status.clone()));
+        }
+        blocks.push(Block::Table(
+            vec!["Detail", "Value"],
+            details
+                .into_iter()
+                .map(|(name, value)| vec![Text::plain(name), Text::plain(value)])
+                .collect(),
+        ));
+
+        blocks.push(Block::Heading(3, Text::plain("Metadata")));
+        let keys = metadata_keys([*asset]);
+        if keys.is_empty() {
+            blocks.push(Block::Paragraph("No metadata".to_string()));
+        } else {
+            let rows = keys
+                .into_iter()
+                .map(|key| {
+                    let value = asset.metadata_value(&key).unwrap_or_default();
+                    vec![Text::plain(key), Text::plain(value)]
+                })
+                .collect();
+            blocks.push(Block::Table(vec!["Property", "Value"], rows));
+        }
+
+        if !matches.is_empty() {
+            blocks.push(Block::Heading(3, Text::plain("Geometric matches")));
+            let rows = matches
+                .iter()
+                .map(|(matched, similarity)| {
+                    let mut row = vec![
+                        Text::linked(&matched.name, links.asset(matched)),
+                        Text::plain(&matched.path),
+                        Text::plain(format!("{:.2}%", similarity)),
+                    ];
+                    if let Some(url) = links.compare(asset, matched) {
+                        row.push(Text::linked("compare", Some(url)));
+                    }
+                    row
+                })
+                .collect();
+            let mut headers = vec!["Match", "Path", "Similarity"];
+            if links.compare_url.is_some() {
+                headers.push("Comparison");
+            }
+            blocks.push(Block::Table(headers, rows));
+        }
+    }
+    blocks
+}
+
+// Table cells can't hold pipes or line breaks
+pub fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown_text(text: &Text) -> String {
+    let escaped = markdown_cell(&text.text);
+    match text.link {
+        Some(ref link) => format!("[{}]({})", escaped, link),
+        None => escaped,
+    }
+}
+
+pub fn markdown(assets: &[ReportAsset], links: Links) -> String {
+    let mut out = String::new();
+    for block in blocks(assets, links) {
+        match block {
+            Block::Heading(level, text) => {
+                let marks = "#".repeat(level as usize);
+                out.push_str(&format!("{} {}\n\n", marks, markdown_text(&text)));
+            }
+            Block::Paragraph(text) => out.push_str(&format!("{}\n\n", text)),
+            Block::Table(headers, rows) => {
+                out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(markdown_text).collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_text(text: &Text) -> String {
+    match text.link {
+        Some(ref link) => {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(link),
+                escape_html(&text.text)
+            )
+        }
+        None => escape_html(&text.text),
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:60em;margin:2em auto;padding:0 1em}\
+table{border-collapse:collapse;margin-bottom:1em}\
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:left}\
+th{background:#f3f3f3}";
+
+pub fn html(assets: &[ReportAsset], links: Links) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Asset report</title>\n\
+         <style>{}</style>\n</head>\n<body>\n",
+        HTML_STYLE
+    );
+    for block in blocks(assets, links) {
+        match block {
+            Block::Heading(level, text) => {
+                out.push_str(&format!("<h{0}>{1}</h{0}>\n", level, html_text(&text)));
+            }
+            Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", escape_html(&text))),
+            Block::Table(headers, rows) => {
+                out.push_str("<table>\n<tr>");
+                for header in headers {
+                    out.push_str(&format!("<th>{}</th>", escape_html(header)));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in &row {
+                        out.push_str(&format!("<td>{}</td>", html_text(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::Asset;
+use crate::metadata_diff::MetadataDiff;
+
+// What the reviewer made of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Keep,      // A different part after all; both stay
+    Duplicate, // The same part as the matched asset
+    Ignore,    // Not worth a decision, e.g. an expected match
+}
+
+impl Decision {
+    pub fn label(self) -> &'static str {
+        match self {
+            Decision::Keep => "keep",
+            Decision::Duplicate => "duplicate",
+            Decision::Ignore => "ignore",
+        }
+    }
+
+    // What happens to the matched asset: only duplicates are to be removed
+    pub fn action(self) -> &'static str {
+        match self {
+            Decision::Duplicate => "remove",
+            Decision::Keep | Decision::Ignore => "keep",
+        }
+    }
+}
+
+// A queued match and the decision on it, if there is one yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPair {
+    pub asset: Asset,
+    pub similarity: f64,
+    pub decision: Option<Decision>,
+}
+
+// Stepping through queued geometric matches one by one, each next to the matched asset
+#[derive(Debug)]
+pub struct Review {
+    pub source: Asset,
+    pub pairs: Vec<ReviewPair>,
+    pub current: usize,
+    pub diff: MetadataDiff, // Metadata of the source and the current match
+}
+
+impl Review {
+    pub fn new(source: Asset, pairs: Vec<ReviewPair>) -> Self {
+        let diff = pair_diff(&source, pairs.first());
+        let mut review = Self {
+            source,
+            pairs,
+            current: 0,
+            diff,
+        };
+        // Pick up where an earlier review of the same matches stopped
+        if let Some(undecided) = review.next_undecided(0) {
+            review.go_to(undecided);
+        }
+        review
+    }
+
+    pub fn pair(&self) -> Option<&ReviewPair> {
+        self.pairs.get(self.current)
+    }
+
+    pub fn decided(&self) -> usize {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.decision.is_some())
+            .count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.decided() == self.pairs.len()
+    }
+
+    pub fn step(&mut self, delta: isize) {
+        let last = self.pairs.len().saturating_sub(1);
+        self.go_to(self.current.saturating_add_signed(delta).min(last));
+    }
+
+    // Record the decision on the current pair and move on to the next one without a decision
+    pub fn decide(&mut self, decision: Decision) {
+        if let Some(pair) = self.pairs.get_mut(self.current) {
+            pair.decision = Some(decision);
+        }
+        let next = self
+            .next_undecided(self.current + 1)
+            .or_else(|| self.next_undecided(0));
+        if let Some(next) = next {
+            self.go_to(next);
+        }
+    }
+
+    fn next_undecided(&self, from: usize) -> Option<usize> {
+        (from..self.pairs.len()).find(|&index| self.pairs[index].decision.is_none())
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.current = index;
+        self.diff = pair_diff(&self.source, self.pairs.get(index));
+    }
+}
+
+fn pair_diff(source: &Asset, pair: Option<&ReviewPair>) -> MetadataDiff {
+    let (name, metadata) = match pair {
+        Some(pair) => (pair.asset.name.clone(), pair.asset.metadata.clone()),
+        None => (String::new(), serde_json::Value::Null),
+    };
+    MetadataDiff::new(source.name.clone(), &source.metadata, name, &metadata)
+}
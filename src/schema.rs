@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::app::{Asset, metadata_keys};
+use crate::pcli_commands::PcliProperty;
+
+// Type given to a new property when none is typed
+pub const DEFAULT_PROPERTY_TYPE: &str = "text";
+
+// A metadata property: its definition in the tenant, if it has one, and how many of the
+// cached assets have a value for it
+#[derive(Debug, Clone)]
+pub struct PropertyRow {
+    pub name: String,
+    pub property_type: Option<String>, // None when assets have it without it being defined
+    pub assets: usize,
+}
+
+// The tenant's property definitions next to the keys actually found in the cached listings
+#[derive(Debug, Default)]
+pub struct PropertySchema {
+    pub rows: Vec<PropertyRow>, // By name
+    pub selected: usize,
+    pub scanned: usize, // Cached assets the usage counts come from
+    pub defined: bool,  // Whether the definitions were listed; offline only the usage is known
+}
+
+impl PropertySchema {
+    pub fn new<'a>(
+        definitions: Option<Vec<PcliProperty>>,
+        assets: impl IntoIterator<Item = &'a Asset>,
+    ) -> Self {
+        let mut rows: BTreeMap<String, PropertyRow> = BTreeMap::new();
+        let defined = definitions.is_some();
+        for property in definitions.into_iter().flatten() {
+            rows.insert(
+                property.name.clone(),
+                PropertyRow {
+                    name: property.name,
+                    property_type: Some(property.property_type),
+                    assets: 0,
+                },
+            );
+        }
+
+        // The same asset can be cached in several listings
+        let mut seen = HashSet::new();
+        let mut scanned = 0;
+        for asset in assets {
+            if !seen.insert(asset.uuid.as_str()) {
+                continue;
+            }
+            scanned += 1;
+            for key in metadata_keys([asset]) {
+                rows.entry(key.clone())
+                    .or_insert_with(|| PropertyRow {
+                        name: key,
+                        property_type: None,
+                        assets: 0,
+                    })
+                    .assets += 1;
+            }
+        }
+
+        Self {
+            rows: rows.into_values().collect(),
+            selected: 0,
+            scanned,
+            defined,
+        }
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn chosen(&self) -> Option<&PropertyRow> {
+        self.rows.get(self.selected)
+    }
+
+    // A property was created in the tenant; keys already in use become defined
+    pub fn define(&mut self, name: &str, property_type: &str) {
+        match self.rows.iter().position(|row| row.name == name) {
+            Some(i) => self.rows[i].property_type = Some(property_type.to_string()),
+            None => {
+                let i = self.rows.partition_point(|row| row.name.as_str() < name);
+                self.rows.insert(
+                    i,
+                    PropertyRow {
+                        name: name.to_string(),
+                        property_type: Some(property_type.to_string()),
+                        assets: 0,
+                    },
+                );
+                self.selected = i;
+            }
+        }
+    }
+
+    // A definition was deleted; keys still found on assets stay listed as undefined
+    pub fn undefine(&mut self, name: &str) {
+        if let Some(i) = self.rows.iter().position(|row| row.name == name) {
+            if self.rows[i].assets > 0 {
+                self.rows[i].property_type = None;
+            } else {
+                self.rows.remove(i);
+                self.move_selection(0);
+            }
+        }
+    }
+}
+
+// "name" or "name:type", as typed into the new property prompt
+pub fn parse_definition(input: &str) -> (&str, &str) {
+    match input.split_once(':') {
+        Some((name, property_type)) if !property_type.trim().is_empty() => {
+            (name.trim(), property_type.trim())
+        }
+        Some((name, _)) => (name.trim(), DEFAULT_PROPERTY_TYPE),
+        None => (input.trim(), DEFAULT_PROPERTY_TYPE),
+    }
+}
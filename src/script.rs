@@ -0,0 +1,228 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::app::SearchScope;
+use crate::config::{self, Config, DownloadConfig};
+use crate::pcli_commands::{self, PcliAsset};
+use crate::search_pattern::{SearchMode, SearchPattern};
+
+// One step of a script, e.g. {"action": "search", "query": "bracket", "mode": "glob"}
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Step {
+    // Open a folder, or the top level without one, and list what is in it
+    Navigate {
+        folder: Option<String>,
+    },
+    Search {
+        query: String,
+        #[serde(default)]
+        mode: SearchMode,
+        #[serde(default)]
+        scope: SearchScope, // Folder scopes are relative to the folder navigated to
+    },
+    // The asset is given by UUID, or by name in the folder navigated to
+    Download {
+        uuid: Option<String>,
+        name: Option<String>,
+        to: Option<String>, // Directory; the configured download directory by default
+    },
+    Match {
+        uuid: Option<String>,
+        name: Option<String>,
+        min_similarity: Option<f64>, // In percent
+    },
+    // Write the results of the steps so far to a JSON file
+    Export {
+        path: String,
+    },
+}
+
+impl Step {
+    fn name(&self) -> &'static str {
+        match self {
+            Step::Navigate { .. } => "navigate",
+            Step::Search { .. } => "search",
+            Step::Download { .. } => "download",
+            Step::Match { .. } => "match",
+            Step::Export { .. } => "export",
+        }
+    }
+}
+
+// Where the script is, as far as later steps are concerned
+#[derive(Default)]
+struct Session {
+    folder: Option<String>,
+    assets: Vec<PcliAsset>, // Of the folder navigated to
+    results: Vec<Value>,    // Output of the steps so far, for export
+    downloads: DownloadConfig,
+}
+
+fn asset_json(asset: &PcliAsset) -> Value {
+    json!({
+        "uuid": asset.uuid,
+        "name": asset.name,
+        "path": asset.path,
+        "size": asset.file_size,
+    })
+}
+
+impl Session {
+    // The UUID of an asset given by UUID or by name in the current folder
+    fn resolve(&self, uuid: Option<String>, name: Option<String>) -> Result<(String, String)> {
+        match (uuid, name) {
+            (Some(uuid), name) => Ok((uuid.clone(), name.unwrap_or(uuid))),
+            (None, Some(name)) => match self.assets.iter().find(|asset| asset.name == name) {
+                Some(asset) => Ok((asset.uuid.clone(), name)),
+                None => bail!(
+                    "no asset named {} in {}",
+                    name,
+                    self.folder.as_deref().unwrap_or("the top level")
+                ),
+            },
+            (None, None) => bail!("give the asset's uuid or name"),
+        }
+    }
+
+    async fn run(&mut self, step: Step) -> Result<Value> {
+        match step {
+            Step::Navigate { folder } => {
+                let (folders, assets) = match folder {
+                    Some(ref folder) => (
+                        pcli_commands::list_subfolders_of_folder(folder).await?,
+                        pcli_commands::list_assets_in_folder(folder).await?,
+                    ),
+                    None => (pcli_commands::list_folders().await?, Vec::new()),
+                };
+                self.folder = folder;
+                self.assets = assets;
+                Ok(json!({
+                    "folder": self.folder,
+                    "folders": folders
+                        .iter()
+                        .map(|f| json!({ "name": f.name, "path": f.path }))
+                        .collect::<Vec<_>>(),
+                    "assets": self.assets.iter().map(asset_json).collect::<Vec<_>>(),
+                }))
+            }
+            Step::Search { query, mode, scope } => {
+                let pattern = SearchPattern::new(mode, &query)?;
+                let folder = match scope {
+                    SearchScope::Tenant => None,
+                    SearchScope::Folder | SearchScope::Subtree => self.folder.clone(),
+                };
+                let results: Vec<Value> =
+                    pcli_commands::search_assets(&pattern.text, folder.as_deref())
+                        .await?
+                        .iter()
+                        .filter(|asset| {
+                            scope != SearchScope::Folder
+                                || asset.path.rsplit_once('/').map(|(parent, _)| parent)
+                                    == folder.as_deref()
+                        })
+                        .filter(|asset| pattern.matches(&asset.name))
+                        .map(asset_json)
+                        .collect();
+                Ok(json!({ "query": query, "results": results }))
+            }
+            Step::Download { uuid, name, to } => {
+                let (uuid, name) = self.resolve(uuid, name)?;
+                let directory = match to {
+                    Some(to) => config::expand_home(&to),
+                    None => self
+                        .downloads
+                        .directory_for(self.folder.as_deref().unwrap_or_default()),
+                };
+                std::fs::create_dir_all(&directory)
+                    .with_context(|| format!("Failed to create {}", directory.display()))?;
+                let file = directory.join(&name);
+                pcli_commands::download_asset_to(&uuid, &file).await?;
+                let expected = self
+                    .assets
+                    .iter()
+                    .find(|asset| asset.uuid == uuid)
+                    .and_then(|asset| asset.file_size);
+                pcli_commands::verify_download(&file, expected)?;
+                let size = std::fs::metadata(&file).map(|m| m.len()).ok();
+                Ok(json!({ "uuid": uuid, "file": file, "size": size }))
+            }
+            Step::Match {
+                uuid,
+                name,
+                min_similarity,
+            } => {
+                let (uuid, _) = self.resolve(uuid, name)?;
+                let matches: Vec<Value> = pcli_commands::geometric_match(&uuid)
+                    .await?
+                    .iter()
+                    .filter(|m| m.similarity_score >= min_similarity.unwrap_or(0.0))
+                    .map(|m| {
+                        let mut entry = asset_json(&m.asset);
+                        entry["similarity"] = json!(m.similarity_score);
+                        entry
+                    })
+                    .collect();
+                Ok(json!({ "uuid": uuid, "matches": matches }))
+            }
+            Step::Export { path } => {
+                let path = config::expand_home(&path);
+                std::fs::write(&path, serde_json::to_string_pretty(&self.results)?)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(json!({ "file": path, "steps": self.results.len() }))
+            }
+        }
+    }
+}
+
+fn read_script(source: &str) -> Result<String> {
+    if source == "-" {
+        let mut script = String::new();
+        std::io::stdin().read_to_string(&mut script)?;
+        return Ok(script);
+    }
+    let path = PathBuf::from(source);
+    std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+// Run the steps of a script (a file, or stdin for "-") without the terminal UI, printing one
+// JSON line per step. Stops at the first failing step; returns whether all of them succeeded.
+pub async fn run(source: &str, config: &Config) -> Result<bool> {
+    let script = read_script(source)?;
+    let steps: Vec<Step> = serde_json::from_str(&script).context("Invalid script")?;
+
+    pcli_commands::set_retry_policy(config.retry.clone());
+    pcli_commands::set_timeout_policy(config.timeouts.clone());
+    pcli_commands::set_concurrency_limit(config.concurrency.max_commands);
+    pcli_commands::set_invocation(config);
+
+    let mut session = Session {
+        downloads: config.downloads.clone(),
+        ..Session::default()
+    };
+    for (index, step) in steps.into_iter().enumerate() {
+        let action = step.name();
+        let (line, ok) = match session.run(step).await {
+            Ok(result) => {
+                let line =
+                    json!({ "step": index + 1, "action": action, "ok": true, "result": result });
+                session.results.push(line.clone());
+                (line, true)
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                let line =
+                    json!({ "step": index + 1, "action": action, "ok": false, "error": error });
+                (line, false)
+            }
+        };
+        println!("{}", line);
+        if !ok {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
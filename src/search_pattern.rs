@@ -0,0 +1,253 @@
+use anyhow::{Result, bail};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+// How the text typed into the search dialog is matched against asset names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Text,
+    Glob,  // `*` and `?` wildcards and `[...]` classes, matched against the whole name
+    Regex, // Matched anywhere in the name
+}
+
+impl SearchMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Text => "text",
+            SearchMode::Glob => "glob",
+            SearchMode::Regex => "regex",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Text => SearchMode::Glob,
+            SearchMode::Glob => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Text,
+        }
+    }
+}
+
+// A typed pattern split into what pcli2 can search for and what is checked here. pcli2's text
+// search knows no wildcards, so it gets the longest literal part of the pattern and the
+// results are filtered with the full pattern.
+#[derive(Debug)]
+pub struct SearchPattern {
+    pub text: String,      // Passed to pcli2 asset text-match
+    filter: Option<Regex>, // None for plain text, which pcli2 matches by itself
+}
+
+impl SearchPattern {
+    pub fn new(mode: SearchMode, input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (text, source) = match mode {
+            SearchMode::Text => {
+                return Ok(Self {
+                    text: input.to_string(),
+                    filter: None,
+                });
+            }
+            SearchMode::Glob => (glob_literal(input), glob_to_regex(input)),
+            SearchMode::Regex => (regex_literal(input), input.to_string()),
+        };
+
+        let filter = RegexBuilder::new(&source)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid {} pattern: {}", mode.label(), e))?;
+        if text.is_empty() {
+            bail!(
+                "The {} pattern needs some literal text outside of wildcards and groups to search for",
+                mode.label()
+            );
+        }
+        Ok(Self {
+            text,
+            filter: Some(filter),
+        })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.is_match(name))
+    }
+}
+
+// Anchored regex equivalent of a glob
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                // Character classes carry over, with "[!...]" for negation
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+// Longest stretch of a glob without wildcards or classes
+fn glob_literal(glob: &str) -> String {
+    let mut runs = vec![String::new()];
+    let mut in_class = false;
+    for c in glob.chars() {
+        match c {
+            // The class stands for a character, so the text on either side isn't adjacent
+            '[' => {
+                in_class = true;
+                runs.push(String::new());
+            }
+            ']' if in_class => in_class = false,
+            _ if in_class => {}
+            '*' | '?' => runs.push(String::new()),
+            _ => {
+                if let Some(run) = runs.last_mut() {
+                    run.push(c);
+                }
+            }
+        }
+    }
+    longest(runs)
+}
+
+// Longest stretch of literal characters every match of the regex contains. Only text outside
+// of groups and classes counts, and none at all when the pattern has top-level alternatives.
+fn regex_literal(regex: &str) -> String {
+    let mut runs = vec![String::new()];
+    let mut depth = 0usize;
+    let mut chars = regex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // The group matches text of its own between the runs on either side
+            '(' => {
+                depth += 1;
+                runs.push(String::new());
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                runs.push(String::new());
+            }
+            '|' if depth == 0 => return String::new(),
+            _ if depth > 0 => {
+                if c == '\\' {
+                    chars.next();
+                }
+            }
+            '[' => {
+                // Skip the class, which matches one of several characters
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+                runs.push(String::new());
+            }
+            // The preceding character may be absent; {n,m} may allow zero repetitions
+            '*' | '?' | '{' => {
+                if let Some(run) = runs.last_mut() {
+                    run.pop();
+                }
+                if c == '{' {
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                }
+                runs.push(String::new());
+            }
+            '+' | '.' | '^' | '$' => runs.push(String::new()),
+            '\\' => match chars.next() {
+                // \d, \w and the like are classes, not literals
+                Some(escaped) if escaped.is_ascii_alphanumeric() => runs.push(String::new()),
+                Some(escaped) => {
+                    if let Some(run) = runs.last_mut() {
+                        run.push(escaped);
+                    }
+                }
+                None => {}
+            },
+            _ => {
+                if let Some(run) = runs.last_mut() {
+                    run.push(c);
+                }
+            }
+        }
+    }
+    longest(runs)
+}
+
+fn longest(runs: Vec<String>) -> String {
+    runs.into_iter()
+        .map(|run| run.trim().to_string())
+        .max_by_key(|run| run.chars().count())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_literal_is_the_longest_run_between_wildcards_and_classes() {
+        assert_eq!(glob_literal("BRKT[0-9]X"), "BRKT");
+        assert_eq!(glob_literal("A[0-9]BRACKET"), "BRACKET");
+        assert_eq!(glob_literal("*bracket-l-??.stl"), "bracket-l-");
+        assert_eq!(glob_literal("[!a]*"), "");
+        assert_eq!(glob_literal("washer"), "washer");
+    }
+
+    #[test]
+    fn regex_literal_is_the_longest_run_outside_of_groups_and_classes() {
+        assert_eq!(regex_literal("BRKT-(10|20)X"), "BRKT-");
+        assert_eq!(regex_literal("(a|b)HOUSING"), "HOUSING");
+        assert_eq!(regex_literal("BRKT[0-9]X"), "BRKT");
+        assert_eq!(regex_literal(r"hex-\w+-m8"), "hex-");
+        assert_eq!(regex_literal(r"bolt\.stl$"), "bolt.stl");
+        assert_eq!(regex_literal("washers?"), "washer");
+        assert_eq!(regex_literal("bolt|nut"), "");
+    }
+
+    #[test]
+    fn the_literal_is_a_substring_of_every_match() {
+        for (mode, input, name) in [
+            (SearchMode::Glob, "BRKT[0-9]X", "BRKT7X"),
+            (SearchMode::Regex, "BRKT-(10|20)X", "BRKT-20X"),
+            (SearchMode::Glob, "*-m8*.stl", "hex-nut-m8.stl"),
+        ] {
+            let pattern = SearchPattern::new(mode, input).unwrap();
+            assert!(pattern.matches(name), "{} should match {}", input, name);
+            assert!(
+                name.contains(&pattern.text),
+                "{:?} not in {}",
+                pattern.text,
+                name
+            );
+        }
+        assert!(SearchPattern::new(SearchMode::Regex, "(a|b)").is_err());
+        assert!(SearchPattern::new(SearchMode::Regex, "BRKT(").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::text_input::TextInput;
+
+// The pages of the first-run setup, in order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetupStep {
+    Welcome,
+    Checks,    // Where pcli2 is and whether it is signed in
+    Downloads, // Where downloads go
+    Theme,
+    Done, // Saved; the tour is offered
+}
+
+// Guided setup on the first start (no config file yet) or with --setup: checks pcli2, asks for
+// the download directory and theme, and writes them to the config file
+#[derive(Debug)]
+pub struct Setup {
+    pub step: SetupStep,
+    pub pcli2: Option<PathBuf>,  // Where pcli2 was found on PATH
+    pub version: Option<String>, // What `pcli2 --version` printed
+    pub sign_in: Option<Result<usize, String>>, // Top-level folders listed, or why listing failed
+    pub directory: TextInput,
+    pub themes: Vec<String>,
+    pub theme: usize, // Selected in `themes`
+}
+
+impl Setup {
+    pub fn new(directory: String, themes: Vec<String>, theme: &str) -> Self {
+        let theme = themes.iter().position(|name| name == theme).unwrap_or(0);
+        Self {
+            step: SetupStep::Welcome,
+            pcli2: None,
+            version: None,
+            sign_in: None,
+            directory: TextInput::new(directory),
+            themes,
+            theme,
+        }
+    }
+
+    // Page number and count, for the title
+    pub fn page(&self) -> (usize, usize) {
+        let page = match self.step {
+            SetupStep::Welcome => 1,
+            SetupStep::Checks => 2,
+            SetupStep::Downloads => 3,
+            SetupStep::Theme => 4,
+            SetupStep::Done => 5,
+        };
+        (page, 5)
+    }
+
+    pub fn move_theme(&mut self, delta: isize) {
+        let last = self.themes.len().saturating_sub(1);
+        self.theme = self.theme.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn chosen_theme(&self) -> Option<&String> {
+        self.themes.get(self.theme)
+    }
+
+    // Whether pcli2 can be used as it is: found, and listing folders worked
+    pub fn checks_passed(&self) -> bool {
+        self.pcli2.is_some() && matches!(self.sign_in, Some(Ok(_)))
+    }
+}
+
+// The first file named `program` (plus .exe on Windows) in a directory of PATH; a program given
+// as a path, e.g. by a profile, is only checked to exist
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        let path = PathBuf::from(program);
+        return path.is_file().then_some(path);
+    }
+    let path = std::env::var_os("PATH")?;
+    let file_name = format!("{}{}", program, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
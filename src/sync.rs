@@ -0,0 +1,387 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::jobs::JobHandle;
+use crate::pcli_commands::{self, PcliAsset};
+
+// Modification times closer than this count as equal; remote timestamps are rounded
+const MTIME_TOLERANCE_SECS: i64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SyncAction {
+    Upload,
+    Download,
+    Skip,
+    Conflict, // Several assets of the folder have the file's name
+}
+
+impl SyncAction {
+    pub fn icon(self) -> &'static str {
+        match self {
+            SyncAction::Upload => "↑",
+            SyncAction::Download => "↓",
+            SyncAction::Skip => "=",
+            SyncAction::Conflict => "!",
+        }
+    }
+
+    // Whether running the plan transfers the file
+    pub fn transfers(self) -> bool {
+        matches!(self, SyncAction::Upload | SyncAction::Download)
+    }
+}
+
+// One file name in the plan: what to do with it and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub name: String,
+    pub action: SyncAction,
+    #[serde(skip)] // Only shown in the plan, which a journaled job is past
+    pub reason: &'static str,
+    pub included: bool, // Whether it runs when the plan is executed
+    pub local: Option<LocalFile>,
+    pub remote: Option<RemoteFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteFile {
+    pub uuid: String,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl From<PcliAsset> for RemoteFile {
+    fn from(asset: PcliAsset) -> Self {
+        Self {
+            uuid: asset.uuid,
+            size: asset.file_size,
+            modified: DateTime::parse_from_rfc3339(&asset.updated_at)
+                .ok()
+                .map(|updated| updated.with_timezone(&Utc)),
+        }
+    }
+}
+
+// What syncing a remote folder with a local directory would do
+#[derive(Debug)]
+pub struct SyncPlan {
+    pub folder: String,
+    pub directory: PathBuf,
+    pub entries: Vec<SyncEntry>,
+    pub selected: usize,
+}
+
+impl SyncPlan {
+    // Compare the files of the directory (not its subdirectories) with the folder's assets
+    pub fn new(folder: String, directory: PathBuf, remote: Vec<PcliAsset>) -> Result<Self> {
+        let mut local: HashMap<String, LocalFile> = HashMap::new();
+        for entry in std::fs::read_dir(&directory)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            local.insert(
+                entry.file_name().to_string_lossy().to_string(),
+                LocalFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                },
+            );
+        }
+
+        // Files are matched by name, so a name several assets share can't be told apart
+        let mut by_name: HashMap<String, Vec<PcliAsset>> = HashMap::new();
+        for asset in remote {
+            by_name.entry(asset.name.clone()).or_default().push(asset);
+        }
+
+        let mut entries = Vec::new();
+        for (name, mut assets) in by_name {
+            let file = local.remove(&name);
+            if assets.len() > 1 {
+                entries.push(SyncEntry {
+                    name,
+                    action: SyncAction::Conflict,
+                    reason: "several assets in the folder have this name",
+                    included: false,
+                    local: file,
+                    remote: None,
+                });
+                continue;
+            }
+            let Some(asset) = assets.pop() else {
+                continue;
+            };
+            let remote = RemoteFile::from(asset);
+            let (action, reason) = match file {
+                None => (SyncAction::Download, "only in the folder"),
+                Some(ref file) => compare(file, &remote),
+            };
+            entries.push(SyncEntry {
+                name,
+                action,
+                reason,
+                included: action.transfers(),
+                local: file,
+                remote: Some(remote),
+            });
+        }
+        for (name, file) in local {
+            entries.push(SyncEntry {
+                name,
+                action: SyncAction::Upload,
+                reason: "only in the directory",
+                included: true,
+                local: Some(file),
+                remote: None,
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            folder,
+            directory,
+            entries,
+            selected: 0,
+        })
+    }
+
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.included && entry.action == action)
+            .count()
+    }
+
+    pub fn conflicts(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.action == SyncAction::Conflict)
+            .count()
+    }
+}
+
+// The side modified last wins, whatever the sizes: an edit may keep the size. Transferred
+// files get the other side's modification time, so they compare as the same afterwards.
+fn compare(file: &LocalFile, remote: &RemoteFile) -> (SyncAction, &'static str) {
+    let same_size = remote.size == Some(file.size);
+    match (file.modified, remote.modified) {
+        (Some(local), Some(remote)) if (local - remote).num_seconds() > MTIME_TOLERANCE_SECS => {
+            (SyncAction::Upload, "newer in the directory")
+        }
+        (Some(local), Some(remote)) if (remote - local).num_seconds() > MTIME_TOLERANCE_SECS => {
+            (SyncAction::Download, "newer in the folder")
+        }
+        (Some(_), Some(_)) if same_size => (SyncAction::Skip, "unchanged"),
+        (Some(_), Some(_)) => (SyncAction::Skip, "same time, different size"),
+        _ => (SyncAction::Skip, "modification time unknown"),
+    }
+}
+
+// Upload and download the included entries of a plan, one file at a time
+pub async fn execute(job: JobHandle, folder: String, directory: PathBuf, entries: Vec<SyncEntry>) {
+    let total = entries.len();
+    let mut details = Vec::with_capacity(total);
+    let mut failed = 0;
+    for (done, entry) in entries.into_iter().enumerate() {
+        job.progress(done, total, entry.name.clone());
+        let result = match (entry.action, entry.local, entry.remote) {
+            (SyncAction::Upload, Some(file), None) => upload(&file, &folder).await,
+            (SyncAction::Upload, Some(file), Some(remote)) => {
+                replace(&file, &remote, &entry.name, &folder).await
+            }
+            (SyncAction::Download, _, Some(remote)) => {
+                download(&remote, &directory.join(&entry.name)).await
+            }
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => details.push(format!("{} {}", entry.action.icon(), entry.name)),
+            Err(e) => {
+                failed += 1;
+                details.push(format!("✗ {}: {:#}", entry.name, e));
+            }
+        }
+    }
+
+    job.finish(
+        failed == 0,
+        format!(
+            "Synced {} with {}: {} of {} files transferred",
+            folder,
+            directory.display(),
+            total - failed,
+            total
+        ),
+        details.join("\n"),
+    );
+}
+
+// Upload the file and give it the new asset's modification time, so the next plan sees it as
+// up to date
+async fn upload(file: &LocalFile, folder: &str) -> Result<()> {
+    let path = file.path.display().to_string();
+    let asset = pcli_commands::in_background(pcli_commands::create_asset(&path, folder)).await?;
+    if let Some(modified) = RemoteFile::from(asset).modified {
+        let local = std::fs::File::options().write(true).open(&file.path)?;
+        local.set_modified(SystemTime::from(modified))?;
+    }
+    Ok(())
+}
+
+// Upload the file in place of the folder's older asset of the same name. The old asset is
+// renamed out of the way first, as the folder can't hold two assets of one name, and only
+// deleted once the upload went through; a failed upload gives it its name back.
+async fn replace(file: &LocalFile, remote: &RemoteFile, name: &str, folder: &str) -> Result<()> {
+    let aside = format!("{}.replaced", name);
+    pcli_commands::in_background(pcli_commands::rename_asset(&remote.uuid, &aside))
+        .await
+        .context("Cannot set the folder's copy aside")?;
+    if let Err(e) = upload(file, folder).await {
+        let restored =
+            pcli_commands::in_background(pcli_commands::rename_asset(&remote.uuid, name)).await;
+        return match restored {
+            Ok(()) => Err(e),
+            Err(_) => Err(e.context(format!("the folder's copy is left as {}", aside))),
+        };
+    }
+    pcli_commands::in_background(pcli_commands::delete_asset(&remote.uuid))
+        .await
+        .with_context(|| format!("Uploaded, but the previous version is left as {}", aside))
+}
+
+// Download into the directory and give the file the remote modification time, so the next
+// plan sees it as up to date
+async fn download(remote: &RemoteFile, path: &Path) -> Result<()> {
+    pcli_commands::in_background(pcli_commands::download_asset_to(&remote.uuid, path)).await?;
+    if let Some(modified) = remote.modified {
+        let file = std::fs::File::options().write(true).open(path)?;
+        file.set_modified(SystemTime::from(modified))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(secs: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0)
+    }
+
+    fn local(size: u64, modified: Option<DateTime<Utc>>) -> LocalFile {
+        LocalFile {
+            path: PathBuf::from("part.step"),
+            size,
+            modified,
+        }
+    }
+
+    fn remote(size: u64, modified: Option<DateTime<Utc>>) -> RemoteFile {
+        RemoteFile {
+            uuid: "u".to_string(),
+            size: Some(size),
+            modified,
+        }
+    }
+
+    fn asset(uuid: &str, name: &str, size: u64, updated_at: &str) -> PcliAsset {
+        PcliAsset {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            path: format!("/Parts/{}", name),
+            file_type: "step".to_string(),
+            file_size: Some(size),
+            processing_status: "finished".to_string(),
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+            metadata: serde_json::Value::Null,
+            is_assembly: false,
+        }
+    }
+
+    #[test]
+    fn the_side_modified_last_wins_whatever_the_sizes() {
+        let compare = |file, remote| compare(&file, &remote).0;
+        assert_eq!(
+            compare(local(10, time(60)), remote(10, time(0))),
+            SyncAction::Upload
+        );
+        assert_eq!(
+            compare(local(10, time(0)), remote(10, time(60))),
+            SyncAction::Download
+        );
+        assert_eq!(
+            compare(local(10, time(0)), remote(20, time(60))),
+            SyncAction::Download
+        );
+        assert_eq!(
+            compare(local(10, time(1)), remote(10, time(0))),
+            SyncAction::Skip
+        );
+        assert_eq!(
+            compare(local(10, time(1)), remote(20, time(0))),
+            SyncAction::Skip
+        );
+        assert_eq!(
+            compare(local(10, None), remote(20, time(0))),
+            SyncAction::Skip
+        );
+    }
+
+    #[test]
+    fn plan_matches_files_by_name_and_leaves_shared_names_alone() {
+        let directory = std::env::temp_dir().join(format!("pcli2-tui-sync-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("sub")).unwrap();
+        for name in ["edited.step", "new.step", "same.step", "twice.step"] {
+            let file = std::fs::File::create(directory.join(name)).unwrap();
+            file.set_len(10).unwrap();
+            file.set_modified(SystemTime::from(time(0).unwrap()))
+                .unwrap();
+        }
+        let newer = "2023-11-14T22:14:20Z";
+        let same = "2023-11-14T22:13:20Z";
+        let remote = vec![
+            asset("u1", "edited.step", 10, newer),
+            asset("u2", "same.step", 10, same),
+            asset("u3", "twice.step", 10, same),
+            asset("u4", "twice.step", 12, newer),
+            asset("u5", "remote.step", 10, same),
+        ];
+        let plan = SyncPlan::new("/Parts".to_string(), directory.clone(), remote);
+        std::fs::remove_dir_all(&directory).unwrap();
+        let plan = plan.unwrap();
+
+        let entries: Vec<(&str, SyncAction, bool)> = plan
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.action, entry.included))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("edited.step", SyncAction::Download, true),
+                ("new.step", SyncAction::Upload, true),
+                ("remote.step", SyncAction::Download, true),
+                ("same.step", SyncAction::Skip, false),
+                ("twice.step", SyncAction::Conflict, false),
+            ]
+        );
+        assert_eq!(plan.count(SyncAction::Download), 2);
+        assert_eq!(plan.count(SyncAction::Upload), 1);
+        assert_eq!(plan.conflicts(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use crate::app::Asset;
+use crate::config::{TagConfig, data_dir};
+use crate::text_input::TextInput;
+
+const TAGS_FILE_NAME: &str = "tags.json";
+
+// Tags of assets: in a metadata property of each asset when one is configured, otherwise in a
+// local file by UUID
+#[derive(Debug, Default)]
+pub struct Tags {
+    metadata_key: Option<String>,
+    local: HashMap<String, Vec<String>>,
+    path: Option<PathBuf>, // Where local tags are saved; None keeps them for this session only
+}
+
+// The tags in a comma-separated list, trimmed and without empty entries
+pub fn parse(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Set a metadata property, inside the "meta" object when pcli2 nests the metadata in one
+pub fn set_metadata_value(metadata: &mut Value, key: &str, value: &str) {
+    if !metadata.is_object() {
+        *metadata = Value::Object(Default::default());
+    }
+    let target = match metadata.get_mut("meta") {
+        Some(meta) if meta.is_object() => meta,
+        _ => metadata,
+    };
+    target[key] = Value::String(value.to_string());
+}
+
+impl Tags {
+    // Read the local tags, unless tags are kept in the tenant's metadata
+    pub fn load(config: &TagConfig) -> Result<Tags> {
+        if let Some(ref key) = config.metadata_key {
+            return Ok(Tags {
+                metadata_key: Some(key.clone()),
+                ..Tags::default()
+            });
+        }
+        let Some(path) = data_dir().map(|dir| dir.join(TAGS_FILE_NAME)) else {
+            anyhow::bail!("No data directory (HOME is not set)");
+        };
+        let local = if path.exists() {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read tags file {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse tags file {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Tags {
+            metadata_key: None,
+            local,
+            path: Some(path),
+        })
+    }
+
+    // The metadata property holding the tags; None when they are kept locally
+    pub fn metadata_key(&self) -> Option<&str> {
+        self.metadata_key.as_deref()
+    }
+
+    pub fn of(&self, asset: &Asset) -> Vec<String> {
+        match self.metadata_key {
+            Some(ref key) => asset
+                .metadata_value(key)
+                .map(|value| parse(&value))
+                .unwrap_or_default(),
+            None => self.local.get(&asset.uuid).cloned().unwrap_or_default(),
+        }
+    }
+
+    // Every tag given to any of `assets` or kept locally, sorted
+    pub fn known<'a>(&self, assets: impl Iterator<Item = &'a Asset>) -> Vec<String> {
+        let mut known: BTreeSet<String> = self.local.values().flatten().cloned().collect();
+        for asset in assets {
+            known.extend(self.of(asset));
+        }
+        known.into_iter().collect()
+    }
+
+    // Replace the local tags of an asset and write the file
+    pub fn set_local(&mut self, uuid: &str, tags: Vec<String>) -> Result<()> {
+        if tags.is_empty() {
+            self.local.remove(uuid);
+        } else {
+            self.local.insert(uuid.to_string(), tags);
+        }
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.local)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write tags file {}", path.display()))?;
+        Ok(())
+    }
+}
+
+// What the tag picker is open for
+#[derive(Debug, Clone)]
+pub enum TagPickerMode {
+    Edit(Asset), // Choose the tags of the asset
+    Filter,      // Choose the tag to list assets by
+}
+
+// The known tags, narrowed down by typing; in edit mode each is checked or not
+#[derive(Debug, Clone)]
+pub struct TagPicker {
+    pub mode: TagPickerMode,
+    pub input: TextInput,
+    pub matches: Vec<String>,
+    pub selected: usize,
+    pub checked: BTreeSet<String>, // Tags the asset is to have
+    tags: Vec<String>,             // Candidates, sorted
+}
+
+impl TagPicker {
+    pub fn new(mode: TagPickerMode, tags: Vec<String>, checked: Vec<String>) -> Self {
+        let mut picker = Self {
+            mode,
+            input: TextInput::default(),
+            matches: Vec::new(),
+            selected: 0,
+            checked: checked.into_iter().collect(),
+            tags,
+        };
+        picker.refresh();
+        picker
+    }
+
+    // Keep the tags that contain the input, ignoring case
+    pub fn refresh(&mut self) {
+        let query = self.input.as_str().trim().to_lowercase();
+        self.matches = self
+            .tags
+            .iter()
+            .filter(|tag| tag.to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(tag) = self.matches.get(self.selected)
+            && !self.checked.remove(tag)
+        {
+            self.checked.insert(tag.clone());
+        }
+    }
+
+    // The typed tag when no known tag has that name, e.g. to create it
+    pub fn new_tag(&self) -> Option<String> {
+        let typed = self.input.as_str().trim();
+        (!typed.is_empty() && !typed.contains(',') && !self.tags.iter().any(|tag| tag == typed))
+            .then(|| typed.to_string())
+    }
+
+    // Check a new tag and list it with the others
+    pub fn add(&mut self, tag: String) {
+        self.checked.insert(tag.clone());
+        self.tags.push(tag);
+        self.tags.sort();
+        self.input.clear();
+        self.refresh();
+    }
+
+    pub fn chosen(&self) -> Option<&String> {
+        self.matches.get(self.selected)
+    }
+}
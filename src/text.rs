@@ -0,0 +1,244 @@
+use std::borrow::Cow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Widest a table cell may grow before its content is ellipsized
+pub const MAX_COLUMN_WIDTH: usize = 40;
+
+const ELLIPSIS: char = '…';
+
+// Number of terminal columns the string occupies (CJK and emoji count double,
+// combining characters count zero)
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+// Shorten the string to at most `max_width` columns, ending it with "…" when cut
+pub fn truncate_to_width(s: &str, max_width: usize) -> Cow<'_, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+
+    if max_width == 0 {
+        return Cow::Borrowed("");
+    }
+
+    // Reserve one column for the ellipsis
+    let budget = max_width - 1;
+    let mut width = 0;
+    let mut truncated = String::new();
+
+    for c in s.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > budget {
+            break;
+        }
+        width += char_width;
+        truncated.push(c);
+    }
+
+    truncated.push(ELLIPSIS);
+    Cow::Owned(truncated)
+}
+
+// Width a column needs for the given content, capped at MAX_COLUMN_WIDTH
+pub fn column_width(s: &str) -> usize {
+    display_width(s).min(MAX_COLUMN_WIDTH)
+}
+
+// A table column as fit_columns sees it
+#[derive(Debug, Clone, Copy)]
+pub struct FitColumn {
+    pub width: usize,    // What the content needs
+    pub min: usize,      // Narrowest it may be ellipsized to
+    pub priority: usize, // Lower gives way first; among equals, the rightmost
+    pub droppable: bool, // May be left out of the table altogether
+}
+
+// Widths that fit the columns into `available` terminal columns, `spacing` apart. The
+// lowest-priority droppable columns are left out (None) until the others fit in full next to
+// the rest of them at their minimum width; then the columns grow back towards what they need,
+// most important first, so the lowest-priority ones are the ones ellipsized
+pub fn fit_columns(columns: &[FitColumn], available: usize, spacing: usize) -> Vec<Option<usize>> {
+    let total = |widths: &[Option<usize>]| {
+        let shown = widths.iter().flatten().count();
+        widths.iter().flatten().sum::<usize>() + spacing * shown.saturating_sub(1)
+    };
+    let mut order: Vec<usize> = (0..columns.len()).collect();
+    order.sort_by_key(|&i| (columns[i].priority, std::cmp::Reverse(i)));
+
+    let mut widths: Vec<Option<usize>> = columns
+        .iter()
+        .map(|column| {
+            Some(if column.droppable {
+                column.min.min(column.width)
+            } else {
+                column.width
+            })
+        })
+        .collect();
+    for &i in &order {
+        if total(&widths) <= available {
+            break;
+        }
+        if columns[i].droppable {
+            widths[i] = None;
+        }
+    }
+    for (width, column) in widths.iter_mut().zip(columns) {
+        *width = width.map(|_| column.min.min(column.width));
+    }
+    for &i in order.iter().rev() {
+        let Some(width) = widths[i] else {
+            continue;
+        };
+        let spare = available.saturating_sub(total(&widths));
+        widths[i] = Some((width + spare).min(columns[i].width));
+    }
+    widths
+}
+
+// Rough age for labels like "cached 3m ago": seconds, minutes, hours or days
+pub fn format_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+// Human-readable byte count, e.g. "512 B", "1.5 KB", "27.0 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_and_combining_characters_are_measured_in_columns() {
+        assert_eq!(display_width("bolt.step"), 9);
+        assert_eq!(display_width("歯車.step"), 9);
+        assert_eq!(display_width("🔩 bolt"), 7);
+        assert_eq!(display_width("e\u{301}crou"), 5);
+    }
+
+    #[test]
+    fn text_that_fits_is_left_alone() {
+        assert!(matches!(
+            truncate_to_width("bolt", 4),
+            Cow::Borrowed("bolt")
+        ));
+        assert_eq!(truncate_to_width("歯車", 4), "歯車");
+        assert_eq!(truncate_to_width("🔩🔩", 4), "🔩🔩");
+        assert_eq!(truncate_to_width("", 0), "");
+    }
+
+    #[test]
+    fn text_is_cut_to_the_width_with_an_ellipsis() {
+        assert_eq!(truncate_to_width("gearbox-mount.step", 8), "gearbox…");
+        assert_eq!(truncate_to_width("bolt", 1), "…");
+        assert_eq!(truncate_to_width("bolt", 0), "");
+        // The combining accent stays with its letter
+        assert_eq!(truncate_to_width("e\u{301}crou-m8", 3), "e\u{301}c…");
+    }
+
+    #[test]
+    fn wide_characters_are_not_split_at_the_boundary() {
+        // The second character would end one column past the width, so it's left out
+        assert_eq!(truncate_to_width("歯車の部品", 4), "歯…");
+        assert_eq!(display_width(&truncate_to_width("歯車の部品", 4)), 3);
+        assert_eq!(truncate_to_width("歯車の部品", 5), "歯車…");
+        assert_eq!(truncate_to_width("🔩🔧⚙️", 4), "🔩…");
+        assert_eq!(truncate_to_width("a🔩🔧", 4), "a🔩…");
+        assert_eq!(truncate_to_width("歯車", 1), "…");
+    }
+
+    #[test]
+    fn column_width_is_capped() {
+        assert_eq!(column_width("歯車"), 4);
+        assert_eq!(column_width(&"🔩".repeat(30)), MAX_COLUMN_WIDTH);
+    }
+
+    fn column(width: usize, min: usize, priority: usize, droppable: bool) -> FitColumn {
+        FitColumn {
+            width,
+            min,
+            priority,
+            droppable,
+        }
+    }
+
+    #[test]
+    fn columns_that_fit_get_what_they_need() {
+        let columns = [
+            column(20, 8, 3, false),
+            column(8, 8, 0, true),
+            column(15, 6, 1, false),
+        ];
+        assert_eq!(fit_columns(&columns, 45, 1), [Some(20), Some(8), Some(15)]);
+        assert_eq!(fit_columns(&columns, 60, 1), [Some(20), Some(8), Some(15)]);
+    }
+
+    #[test]
+    fn the_least_important_columns_are_ellipsized_first() {
+        let columns = [
+            column(20, 8, 3, false),
+            column(6, 4, 1, false),
+            column(15, 6, 1, false),
+        ];
+        // Among columns of equal priority the rightmost gives way
+        assert_eq!(fit_columns(&columns, 35, 1), [Some(20), Some(6), Some(7)]);
+        assert_eq!(fit_columns(&columns, 30, 1), [Some(18), Some(4), Some(6)]);
+        // Too narrow for even the minimum widths
+        assert_eq!(fit_columns(&columns, 10, 1), [Some(8), Some(4), Some(6)]);
+    }
+
+    #[test]
+    fn droppable_columns_are_left_out_before_others_shrink() {
+        let columns = [
+            column(20, 8, 3, false),
+            column(8, 8, 0, true),
+            column(15, 6, 1, false),
+        ];
+        assert_eq!(fit_columns(&columns, 36, 1), [Some(20), None, Some(15)]);
+        assert_eq!(fit_columns(&columns, 20, 1), [Some(13), None, Some(6)]);
+    }
+
+    #[test]
+    fn wide_names_fit_the_column_they_are_given() {
+        for name in ["歯車の部品.step", "🔩🔩🔩 bolts.step"] {
+            let columns = [column(column_width(name), 6, 3, false)];
+            for available in 6..=display_width(name) {
+                let width = fit_columns(&columns, available, 1)[0];
+                assert_eq!(width, Some(available));
+                let cell = truncate_to_width(name, available);
+                assert!(
+                    display_width(&cell) <= available,
+                    "{:?} in {}",
+                    cell,
+                    available
+                );
+                assert!(
+                    display_width(&cell) + 1 >= available,
+                    "{:?} in {}",
+                    cell,
+                    available
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Span;
+
+// A single-line text field with a cursor and readline-style editing keys
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    text: String,
+    cursor: usize, // Byte offset into `text`, always on a char boundary
+}
+
+impl TextInput {
+    // A field holding `text`, with the cursor at its end
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            cursor: text.len(),
+            text,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    // Replace the text, e.g. with a history entry, and put the cursor at its end
+    pub fn set(&mut self, text: impl Into<String>) {
+        *self = Self::new(text);
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    // Apply an editing or cursor key. Returns false for keys the field doesn't use, which are
+    // left to the caller (Enter, Esc, Up, Down, ...).
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::Char('e') if control => self.cursor = self.text.len(),
+            KeyCode::Char('b') if control => self.cursor = self.previous_char(),
+            KeyCode::Char('f') if control => self.cursor = self.next_char(),
+            KeyCode::Char('b') if alt => self.cursor = self.previous_word(),
+            KeyCode::Char('f') if alt => self.cursor = self.next_word(),
+            KeyCode::Char('w') if control => self.delete_to(self.previous_word()),
+            KeyCode::Char('d') if alt => self.delete_to(self.next_word()),
+            KeyCode::Char('u') if control => self.delete_to(0),
+            KeyCode::Char('k') if control => self.text.truncate(self.cursor),
+            KeyCode::Char('d') if control => self.delete_to(self.next_char()),
+            KeyCode::Char(_) if control || alt => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace if alt || control => self.delete_to(self.previous_word()),
+            KeyCode::Backspace => self.delete_to(self.previous_char()),
+            KeyCode::Delete => self.delete_to(self.next_char()),
+            KeyCode::Left if control || alt => self.cursor = self.previous_word(),
+            KeyCode::Right if control || alt => self.cursor = self.next_word(),
+            KeyCode::Left => self.cursor = self.previous_char(),
+            KeyCode::Right => self.cursor = self.next_char(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    // The text with the cursor drawn on the character under it, or as `end_cursor` after the
+    // last character
+    pub fn spans(&self, end_cursor: &'static str) -> Vec<Span<'_>> {
+        let (before, after) = self.text.split_at(self.cursor);
+        let mut chars = after.chars();
+        match chars.next() {
+            Some(c) => vec![
+                Span::raw(before),
+                Span::styled(
+                    &after[..c.len_utf8()],
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ),
+                Span::raw(chars.as_str()),
+            ],
+            None => vec![Span::raw(before), Span::raw(end_cursor)],
+        }
+    }
+
+    // Remove the text between the cursor and `position`, on either side of it
+    fn delete_to(&mut self, position: usize) {
+        let (start, end) = if position < self.cursor {
+            (position, self.cursor)
+        } else {
+            (self.cursor, position)
+        };
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+    }
+
+    fn previous_char(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_char(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    // Start of the word before the cursor, skipping separators in between
+    fn previous_word(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let end = before.trim_end_matches(is_separator).len();
+        before[..end].rfind(is_separator).map_or(0, |i| {
+            i + before[i..].chars().next().map_or(1, char::len_utf8)
+        })
+    }
+
+    // End of the word after the cursor, skipping separators in between
+    fn next_word(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let start = after.len() - after.trim_start_matches(is_separator).len();
+        let end = after[start..]
+            .find(is_separator)
+            .map_or(after.len(), |i| start + i);
+        self.cursor + end
+    }
+}
+
+// Words are separated by whitespace and path or argument punctuation
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '/' | '-' | '_' | '.' | ',' | '=' | ':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) {
+        assert!(input.handle_key(key(code, modifiers)));
+    }
+
+    // The text before and after the cursor
+    fn split(input: &TextInput) -> (&str, &str) {
+        input.text.split_at(input.cursor)
+    }
+
+    #[test]
+    fn cursor_moves_by_character_and_to_either_end() {
+        let mut input = TextInput::new("héllo");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("héll", "o"));
+        press(&mut input, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("", "héllo"));
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("", "héllo"));
+        press(&mut input, KeyCode::Char('f'), KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("hé", "llo"));
+        press(&mut input, KeyCode::Char('b'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("h", "éllo"));
+        press(&mut input, KeyCode::End, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("héllo", ""));
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("", "héllo"));
+    }
+
+    #[test]
+    fn cursor_moves_by_word() {
+        let mut input = TextInput::new("asset list --folder-path /Parts");
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("asset list --folder-path /", "Parts"));
+        press(&mut input, KeyCode::Char('b'), KeyModifiers::ALT);
+        assert_eq!(split(&input), ("asset list --folder-", "path /Parts"));
+        press(&mut input, KeyCode::Left, KeyModifiers::ALT);
+        press(&mut input, KeyCode::Left, KeyModifiers::ALT);
+        assert_eq!(split(&input), ("asset ", "list --folder-path /Parts"));
+        press(&mut input, KeyCode::Right, KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("asset list", " --folder-path /Parts"));
+        press(&mut input, KeyCode::Char('f'), KeyModifiers::ALT);
+        assert_eq!(split(&input), ("asset list --folder", "-path /Parts"));
+    }
+
+    #[test]
+    fn characters_are_inserted_and_deleted_at_the_cursor() {
+        let mut input = TextInput::new("bolt");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('ø'), KeyModifiers::NONE);
+        assert_eq!(split(&input), ("bolø", "t"));
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("bol", "t"));
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(split(&input), ("bol", ""));
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("", "ol"));
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(input.as_str(), "ol");
+    }
+
+    #[test]
+    fn words_are_deleted_on_either_side_of_the_cursor() {
+        let mut input = TextInput::new("metadata set --name material");
+        press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("metadata set --name ", ""));
+        press(&mut input, KeyCode::Backspace, KeyModifiers::ALT);
+        assert_eq!(split(&input), ("metadata set --", ""));
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Char('d'), KeyModifiers::ALT);
+        assert_eq!(split(&input), ("", " set --"));
+        press(&mut input, KeyCode::Backspace, KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("", " set --"));
+    }
+
+    #[test]
+    fn lines_are_deleted_up_to_and_from_the_cursor() {
+        let mut input = TextInput::new("folder list --format json");
+        for _ in 0..5 {
+            press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        }
+        press(&mut input, KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("folder list --format", ""));
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(split(&input), ("", "format"));
+    }
+
+    #[test]
+    fn other_keys_are_left_to_the_caller() {
+        let mut input = TextInput::new("bolt");
+        for (code, modifiers) in [
+            (KeyCode::Enter, KeyModifiers::NONE),
+            (KeyCode::Esc, KeyModifiers::NONE),
+            (KeyCode::Up, KeyModifiers::NONE),
+            (KeyCode::Char('s'), KeyModifiers::CONTROL),
+            (KeyCode::Char('x'), KeyModifiers::ALT),
+        ] {
+            assert!(!input.handle_key(key(code, modifiers)));
+        }
+        assert_eq!(split(&input), ("bolt", ""));
+    }
+}
@@ -0,0 +1,687 @@
+use anyhow::{Result, anyhow};
+use ratatui::style::{Color, Modifier, Style};
+use std::str::FromStr;
+
+use crate::config::Config;
+
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "colorblind"];
+pub const DEFAULT_THEME: &str = "dark";
+
+// Standard xterm values of the 16 basic ANSI colors, used to approximate RGB colors
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// How many colors the terminal can display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Monochrome,
+}
+
+impl ColorSupport {
+    // Detect color support from the environment, honoring the NO_COLOR convention
+    pub fn detect() -> Self {
+        if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::Monochrome;
+        }
+
+        let colorterm = std::env::var("COLORTERM")
+            .unwrap_or_default()
+            .to_lowercase();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            return ColorSupport::TrueColor;
+        }
+
+        let term = std::env::var("TERM").unwrap_or_default().to_lowercase();
+        if term.is_empty() || term == "dumb" {
+            ColorSupport::Monochrome
+        } else if term.contains("direct") || term.contains("truecolor") {
+            ColorSupport::TrueColor
+        } else if term.contains("256") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    // Parse the `color_mode` config value; "auto" (or nothing) means detect
+    pub fn from_config(mode: Option<&str>) -> Result<Self> {
+        match mode.unwrap_or("auto") {
+            "auto" => Ok(ColorSupport::detect()),
+            "truecolor" | "24bit" => Ok(ColorSupport::TrueColor),
+            "256" => Ok(ColorSupport::Ansi256),
+            "16" => Ok(ColorSupport::Ansi16),
+            "none" | "monochrome" => Ok(ColorSupport::Monochrome),
+            other => Err(anyhow!("Unknown color mode '{}'", other)),
+        }
+    }
+}
+
+// Named palette used by every draw function instead of hardcoded colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub accent: Color,                // Active borders, headers, folder and asset names
+    pub border_inactive: Color,       // Borders of inactive panes
+    pub selection_bg: Color,          // Background of the selected row
+    pub selection_fg: Color,          // Text of the selected row
+    pub text: Color,                  // Regular text
+    pub text_muted: Color,            // Secondary text (stats, empty placeholders)
+    pub secondary: Color,             // Parent folder indicator, similarity scores
+    pub secondary_selected_bg: Color, // Background of the selected parent folder indicator
+    pub info: Color,                  // Loading indicators, log view border
+    pub success: Color,
+    pub error: Color,
+    pub warning: Color, // Cached entries, in-progress messages
+    pub modal_bg: Color,
+    pub panel_bg: Color,
+    pub input_bg: Color,
+    pub highlight_bg: Color, // Highlighted log entry
+    pub history: Color,      // Command history view
+    pub hint_fg: Color,      // Key bindings line
+    pub hint_bg: Color,
+    pub monochrome: bool, // No colors: emphasis comes from modifiers only
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            accent: Color::Rgb(255, 215, 0),            // Gold
+            border_inactive: Color::Rgb(100, 100, 100), // Muted gray
+            selection_bg: Color::Rgb(34, 139, 34),      // Forest green
+            selection_fg: Color::White,
+            text: Color::Rgb(220, 220, 220),
+            text_muted: Color::Rgb(150, 150, 150),
+            secondary: Color::Rgb(173, 216, 230), // Light blue
+            secondary_selected_bg: Color::Rgb(106, 90, 205), // Indigo
+            info: Color::Rgb(100, 149, 237),      // Cornflower blue
+            success: Color::Green,
+            error: Color::Red,
+            warning: Color::Yellow,
+            modal_bg: Color::Rgb(30, 30, 40),
+            panel_bg: Color::Rgb(30, 30, 30),
+            input_bg: Color::Rgb(40, 40, 40),
+            highlight_bg: Color::Rgb(70, 130, 180), // Steel blue
+            history: Color::Rgb(147, 112, 219),     // Medium purple
+            hint_fg: Color::Rgb(220, 220, 220),
+            hint_bg: Color::Rgb(60, 60, 60),
+            monochrome: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            accent: Color::Rgb(160, 100, 0),
+            border_inactive: Color::Rgb(170, 170, 170),
+            selection_bg: Color::Rgb(0, 100, 190),
+            selection_fg: Color::White,
+            text: Color::Rgb(30, 30, 30),
+            text_muted: Color::Rgb(110, 110, 110),
+            secondary: Color::Rgb(30, 80, 150),
+            secondary_selected_bg: Color::Rgb(90, 70, 180),
+            info: Color::Rgb(30, 90, 200),
+            success: Color::Rgb(0, 120, 0),
+            error: Color::Rgb(190, 0, 0),
+            warning: Color::Rgb(160, 100, 0),
+            modal_bg: Color::Rgb(245, 245, 240),
+            panel_bg: Color::Rgb(250, 250, 250),
+            input_bg: Color::Rgb(230, 230, 230),
+            highlight_bg: Color::Rgb(70, 110, 170),
+            history: Color::Rgb(110, 60, 170),
+            hint_fg: Color::Rgb(30, 30, 30),
+            hint_bg: Color::Rgb(210, 210, 210),
+            monochrome: false,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Theme {
+            accent: Color::Rgb(255, 255, 0),
+            border_inactive: Color::White,
+            selection_bg: Color::Rgb(255, 255, 0),
+            selection_fg: Color::Black,
+            text: Color::White,
+            text_muted: Color::Rgb(210, 210, 210),
+            secondary: Color::Rgb(0, 255, 255),
+            secondary_selected_bg: Color::Rgb(0, 160, 160),
+            info: Color::Rgb(0, 255, 255),
+            success: Color::Rgb(0, 255, 0),
+            error: Color::Rgb(255, 80, 80),
+            warning: Color::Rgb(255, 255, 0),
+            modal_bg: Color::Black,
+            panel_bg: Color::Black,
+            input_bg: Color::Black,
+            highlight_bg: Color::Rgb(0, 0, 200),
+            history: Color::Rgb(255, 0, 255),
+            hint_fg: Color::Black,
+            hint_bg: Color::White,
+            monochrome: false,
+        }
+    }
+
+    // Okabe-Ito palette, distinguishable with the common forms of color blindness
+    pub fn colorblind() -> Self {
+        Theme {
+            accent: Color::Rgb(230, 159, 0), // Orange
+            border_inactive: Color::Rgb(100, 100, 100),
+            selection_bg: Color::Rgb(0, 114, 178), // Blue
+            selection_fg: Color::White,
+            text: Color::Rgb(220, 220, 220),
+            text_muted: Color::Rgb(150, 150, 150),
+            secondary: Color::Rgb(86, 180, 233), // Sky blue
+            secondary_selected_bg: Color::Rgb(204, 121, 167), // Reddish purple
+            info: Color::Rgb(86, 180, 233),
+            success: Color::Rgb(0, 158, 115),  // Bluish green
+            error: Color::Rgb(213, 94, 0),     // Vermillion
+            warning: Color::Rgb(240, 228, 66), // Yellow
+            modal_bg: Color::Rgb(30, 30, 40),
+            panel_bg: Color::Rgb(30, 30, 30),
+            input_bg: Color::Rgb(40, 40, 40),
+            highlight_bg: Color::Rgb(0, 114, 178),
+            history: Color::Rgb(204, 121, 167),
+            hint_fg: Color::Rgb(220, 220, 220),
+            hint_bg: Color::Rgb(60, 60, 60),
+            monochrome: false,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "colorblind" => Some(Theme::colorblind()),
+            _ => None,
+        }
+    }
+
+    // Resolve a theme by name, looking at custom themes from the config first
+    pub fn resolve(name: &str, config: &Config) -> Result<Theme> {
+        if let Some(custom) = config.themes.get(name) {
+            let base_name = custom.base.as_deref().unwrap_or(DEFAULT_THEME);
+            let mut theme = Theme::builtin(base_name).ok_or_else(|| {
+                anyhow!("Theme '{}' uses unknown base theme '{}'", name, base_name)
+            })?;
+
+            for (key, value) in &custom.colors {
+                let color = Color::from_str(value).map_err(|_| {
+                    anyhow!(
+                        "Theme '{}' has an invalid color for '{}': {}",
+                        name,
+                        key,
+                        value
+                    )
+                })?;
+                theme.set_color(key, color)?;
+            }
+
+            return Ok(theme);
+        }
+
+        Theme::builtin(name).ok_or_else(|| anyhow!("Unknown theme '{}'", name))
+    }
+
+    // All theme names available for runtime switching: built-ins followed by custom themes
+    pub fn available_names(config: &Config) -> Vec<String> {
+        let mut custom: Vec<String> = config
+            .themes
+            .keys()
+            .filter(|name| Theme::builtin(name).is_none())
+            .cloned()
+            .collect();
+        custom.sort();
+
+        BUILTIN_THEMES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(custom)
+            .collect()
+    }
+
+    fn set_color(&mut self, key: &str, color: Color) -> Result<()> {
+        let slot = match key {
+            "accent" => &mut self.accent,
+            "border_inactive" => &mut self.border_inactive,
+            "selection_bg" => &mut self.selection_bg,
+            "selection_fg" => &mut self.selection_fg,
+            "text" => &mut self.text,
+            "text_muted" => &mut self.text_muted,
+            "secondary" => &mut self.secondary,
+            "secondary_selected_bg" => &mut self.secondary_selected_bg,
+            "info" => &mut self.info,
+            "success" => &mut self.success,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "modal_bg" => &mut self.modal_bg,
+            "panel_bg" => &mut self.panel_bg,
+            "input_bg" => &mut self.input_bg,
+            "highlight_bg" => &mut self.highlight_bg,
+            "history" => &mut self.history,
+            "hint_fg" => &mut self.hint_fg,
+            "hint_bg" => &mut self.hint_bg,
+            _ => return Err(anyhow!("Unknown theme color '{}'", key)),
+        };
+        *slot = color;
+
+        Ok(())
+    }
+
+    // Downgrade the palette to what the terminal can display
+    pub fn adapt(self, support: ColorSupport) -> Theme {
+        let convert: fn(Color) -> Color = match support {
+            ColorSupport::TrueColor => return self,
+            ColorSupport::Ansi256 => to_ansi256,
+            ColorSupport::Ansi16 => to_ansi16,
+            ColorSupport::Monochrome => |_| Color::Reset,
+        };
+
+        Theme {
+            accent: convert(self.accent),
+            border_inactive: convert(self.border_inactive),
+            selection_bg: convert(self.selection_bg),
+            selection_fg: convert(self.selection_fg),
+            text: convert(self.text),
+            text_muted: convert(self.text_muted),
+            secondary: convert(self.secondary),
+            secondary_selected_bg: convert(self.secondary_selected_bg),
+            info: convert(self.info),
+            success: convert(self.success),
+            error: convert(self.error),
+            warning: convert(self.warning),
+            modal_bg: convert(self.modal_bg),
+            panel_bg: convert(self.panel_bg),
+            input_bg: convert(self.input_bg),
+            highlight_bg: convert(self.highlight_bg),
+            history: convert(self.history),
+            hint_fg: convert(self.hint_fg),
+            hint_bg: convert(self.hint_bg),
+            monochrome: support == ColorSupport::Monochrome,
+        }
+    }
+
+    // Border style for a pane, highlighted when the pane is active
+    pub fn border(&self, active: bool) -> Style {
+        let color = if active {
+            self.accent
+        } else {
+            self.border_inactive
+        };
+        let style = Style::default().fg(color);
+
+        // Without colors only the active border stands out
+        if active || !self.monochrome {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style.add_modifier(Modifier::DIM)
+        }
+    }
+
+    // Style of the selected row in lists and tables
+    pub fn selected(&self) -> Style {
+        self.emphasize(Style::default().bg(self.selection_bg).fg(self.selection_fg))
+    }
+
+    // Style of a highlighted entry (e.g. the current log entry)
+    pub fn highlighted(&self) -> Style {
+        self.emphasize(Style::default().bg(self.highlight_bg).fg(self.selection_fg))
+    }
+
+    // Style of the key bindings line
+    pub fn hints(&self) -> Style {
+        self.emphasize(Style::default().fg(self.hint_fg).bg(self.hint_bg))
+    }
+
+    // Styles relying on a background color fall back to inverse video without colors
+    fn emphasize(&self, style: Style) -> Style {
+        if self.monochrome {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
+}
+
+fn rgb_components(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) if index >= 16 => Some(ansi256_to_rgb(index)),
+        _ => None,
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let dr = r1 as i32 - r2 as i32;
+    let dg = g1 as i32 - g2 as i32;
+    let db = b1 as i32 - b2 as i32;
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+// Nearest of the 16 basic ANSI colors; named colors are kept as they are
+fn to_ansi16(color: Color) -> Color {
+    let Some(rgb) = rgb_components(color) else {
+        return color;
+    };
+
+    ANSI_16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(color, |(ansi_color, _)| *ansi_color)
+}
+
+// Nearest entry of the xterm 256-color palette (6x6x6 cube or grayscale ramp)
+fn to_ansi256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+
+    let cube_level = |value: u8| -> u8 {
+        if value < 48 {
+            0
+        } else if value < 115 {
+            1
+        } else {
+            (value - 35) / 40
+        }
+    };
+    let (cr, cg, cb) = (cube_level(r), cube_level(g), cube_level(b));
+    let cube_index = 16 + 36 * cr + 6 * cg + cb;
+
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray_index = if average > 238 {
+        255
+    } else {
+        232 + average.saturating_sub(3) / 10
+    };
+
+    let cube_distance = distance((r, g, b), ansi256_to_rgb(cube_index));
+    let gray_distance = distance((r, g, b), ansi256_to_rgb(gray_index));
+
+    if gray_distance < cube_distance {
+        Color::Indexed(gray_index)
+    } else {
+        Color::Indexed(cube_index)
+    }
+}
+
+fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match index {
+        0..=15 => ANSI_16[index as usize].1,
+        16..=231 => {
+            let offset = index - 16;
+            (
+                CUBE_LEVELS[(offset / 36) as usize],
+                CUBE_LEVELS[((offset / 6) % 6) as usize],
+                CUBE_LEVELS[(offset % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ThemeConfig;
+
+    fn with_theme(base: Option<&str>, colors: &[(&str, &str)]) -> Config {
+        let theme = ThemeConfig {
+            base: base.map(str::to_string),
+            colors: colors
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        Config {
+            themes: [("mine".to_string(), theme)].into(),
+            ..Config::default()
+        }
+    }
+
+    fn error(result: Result<Theme>) -> String {
+        result.map_or_else(|e| e.to_string(), |_| String::new())
+    }
+
+    #[test]
+    fn builtin_themes_resolve_by_name() {
+        let config = Config::default();
+        for name in BUILTIN_THEMES {
+            assert!(Theme::resolve(name, &config).is_ok(), "{}", name);
+        }
+        assert_eq!(
+            Theme::resolve(DEFAULT_THEME, &config).ok(),
+            Some(Theme::default())
+        );
+        assert_eq!(
+            error(Theme::resolve("solarized", &config)),
+            "Unknown theme 'solarized'"
+        );
+    }
+
+    #[test]
+    fn custom_colors_are_parsed_over_the_base_theme() {
+        let config = with_theme(
+            Some("light"),
+            &[("accent", "#1e90ff"), ("error", "red"), ("text", "42")],
+        );
+        let theme = Theme::resolve("mine", &config).ok();
+        assert_eq!(
+            theme,
+            Some(Theme {
+                accent: Color::Rgb(30, 144, 255),
+                error: Color::Red,
+                text: Color::Indexed(42),
+                ..Theme::light()
+            })
+        );
+
+        let config = with_theme(None, &[("accent", "#000000")]);
+        assert_eq!(
+            Theme::resolve("mine", &config).ok(),
+            Some(Theme {
+                accent: Color::Rgb(0, 0, 0),
+                ..Theme::dark()
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_custom_themes_are_refused() {
+        assert_eq!(
+            error(Theme::resolve(
+                "mine",
+                &with_theme(None, &[("accent", "#12345")])
+            )),
+            "Theme 'mine' has an invalid color for 'accent': #12345"
+        );
+        assert_eq!(
+            error(Theme::resolve(
+                "mine",
+                &with_theme(None, &[("accent", "golden")])
+            )),
+            "Theme 'mine' has an invalid color for 'accent': golden"
+        );
+        assert_eq!(
+            error(Theme::resolve(
+                "mine",
+                &with_theme(None, &[("borders", "red")])
+            )),
+            "Unknown theme color 'borders'"
+        );
+        assert_eq!(
+            error(Theme::resolve("mine", &with_theme(Some("sepia"), &[]))),
+            "Theme 'mine' uses unknown base theme 'sepia'"
+        );
+    }
+
+    #[test]
+    fn custom_themes_are_listed_after_the_builtin_ones() {
+        let mut config = with_theme(None, &[]);
+        config
+            .themes
+            .insert("dark".to_string(), ThemeConfig::default());
+        config
+            .themes
+            .insert("amber".to_string(), ThemeConfig::default());
+        assert_eq!(
+            Theme::available_names(&config),
+            [
+                "dark",
+                "light",
+                "high-contrast",
+                "colorblind",
+                "amber",
+                "mine"
+            ]
+        );
+    }
+
+    fn colors(theme: &Theme) -> [Color; 19] {
+        [
+            theme.accent,
+            theme.border_inactive,
+            theme.selection_bg,
+            theme.selection_fg,
+            theme.text,
+            theme.text_muted,
+            theme.secondary,
+            theme.secondary_selected_bg,
+            theme.info,
+            theme.success,
+            theme.error,
+            theme.warning,
+            theme.modal_bg,
+            theme.panel_bg,
+            theme.input_bg,
+            theme.highlight_bg,
+            theme.history,
+            theme.hint_fg,
+            theme.hint_bg,
+        ]
+    }
+
+    #[test]
+    fn color_modes_are_parsed() {
+        assert_eq!(
+            ColorSupport::from_config(Some("truecolor")).ok(),
+            Some(ColorSupport::TrueColor)
+        );
+        assert_eq!(
+            ColorSupport::from_config(Some("24bit")).ok(),
+            Some(ColorSupport::TrueColor)
+        );
+        assert_eq!(
+            ColorSupport::from_config(Some("256")).ok(),
+            Some(ColorSupport::Ansi256)
+        );
+        assert_eq!(
+            ColorSupport::from_config(Some("16")).ok(),
+            Some(ColorSupport::Ansi16)
+        );
+        assert_eq!(
+            ColorSupport::from_config(Some("none")).ok(),
+            Some(ColorSupport::Monochrome)
+        );
+        assert_eq!(
+            ColorSupport::from_config(Some("monochrome")).ok(),
+            Some(ColorSupport::Monochrome)
+        );
+        assert!(ColorSupport::from_config(None).is_ok());
+        for invalid in ["8", "TrueColor", ""] {
+            assert_eq!(
+                ColorSupport::from_config(Some(invalid)).map_err(|e| e.to_string()),
+                Err(format!("Unknown color mode '{}'", invalid))
+            );
+        }
+    }
+
+    #[test]
+    fn colors_are_approximated_by_the_nearest_ansi_color() {
+        assert_eq!(to_ansi16(Color::Rgb(200, 10, 10)), Color::Red);
+        assert_eq!(to_ansi16(Color::Rgb(250, 250, 250)), Color::White);
+        assert_eq!(to_ansi16(Color::Rgb(120, 130, 125)), Color::DarkGray);
+        assert_eq!(to_ansi16(Color::Indexed(196)), Color::LightRed);
+        // Colors the terminal already knows are kept
+        assert_eq!(to_ansi16(Color::Indexed(5)), Color::Indexed(5));
+        assert_eq!(to_ansi16(Color::Blue), Color::Blue);
+        assert_eq!(to_ansi16(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn colors_are_approximated_by_the_nearest_of_256() {
+        assert_eq!(to_ansi256(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_ansi256(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(to_ansi256(Color::Rgb(255, 255, 255)), Color::Indexed(231));
+        // Grays are closer on the grayscale ramp than in the color cube
+        assert_eq!(to_ansi256(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(to_ansi256(Color::Indexed(42)), Color::Indexed(42));
+        assert_eq!(to_ansi256(Color::Green), Color::Green);
+
+        assert_eq!(ansi256_to_rgb(9), (255, 0, 0));
+        assert_eq!(ansi256_to_rgb(16), (0, 0, 0));
+        assert_eq!(ansi256_to_rgb(231), (255, 255, 255));
+        assert_eq!(ansi256_to_rgb(232), (8, 8, 8));
+        assert_eq!(ansi256_to_rgb(255), (238, 238, 238));
+    }
+
+    #[test]
+    fn themes_are_downgraded_to_what_the_terminal_displays() {
+        for name in BUILTIN_THEMES {
+            let Some(theme) = Theme::builtin(name) else {
+                panic!("{} isn't a builtin theme", name);
+            };
+            assert_eq!(theme.adapt(ColorSupport::TrueColor), theme);
+            for color in colors(&theme.adapt(ColorSupport::Ansi256)) {
+                assert!(!matches!(color, Color::Rgb(..)), "{}: {:?}", name, color);
+            }
+            for color in colors(&theme.adapt(ColorSupport::Ansi16)) {
+                assert!(
+                    !matches!(color, Color::Rgb(..) | Color::Indexed(16..)),
+                    "{}: {:?}",
+                    name,
+                    color
+                );
+            }
+            let monochrome = theme.adapt(ColorSupport::Monochrome);
+            assert!(
+                colors(&monochrome)
+                    .iter()
+                    .all(|&color| color == Color::Reset)
+            );
+            assert!(
+                monochrome
+                    .selected()
+                    .add_modifier
+                    .contains(Modifier::REVERSED)
+            );
+            assert!(!theme.selected().add_modifier.contains(Modifier::REVERSED));
+        }
+    }
+}
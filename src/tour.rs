@@ -0,0 +1,79 @@
+// The part of the screen a stop of the tour points at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TourTarget {
+    Folders,
+    Assets,
+    Log,
+    StatusLine,
+    Hints,
+    Screen, // Nothing in particular
+}
+
+#[derive(Debug)]
+pub struct TourStop {
+    pub target: TourTarget,
+    pub title: &'static str,
+    pub text: &'static str,
+}
+
+pub const STOPS: [TourStop; 6] = [
+    TourStop {
+        target: TourTarget::Folders,
+        title: "Folders",
+        text: "The folders of the tenant. j/k or ↑/↓ select one and its assets show up on the \
+               right; Enter opens it and Enter on .. goes back up.",
+    },
+    TourStop {
+        target: TourTarget::Assets,
+        title: "Assets",
+        text: "Tab moves here. Enter shows an asset's details, d downloads it, g finds \
+               geometric matches and Space puts it in the basket for batch work.",
+    },
+    TourStop {
+        target: TourTarget::Log,
+        title: "Log",
+        text: "Every pcli2 command the app runs, with its outcome. l opens the full log, E the \
+               last error and L hides this pane.",
+    },
+    TourStop {
+        target: TourTarget::StatusLine,
+        title: "Status line",
+        text: "The last message on the left; where you are, the basket and background jobs on \
+               the right.",
+    },
+    TourStop {
+        target: TourTarget::Hints,
+        title: "Keys",
+        text: "The keys of the view you are in. h shows all of them at any time.",
+    },
+    TourStop {
+        target: TourTarget::Screen,
+        title: "Finding things",
+        text: "/ searches the tenant, Ctrl+F finds assets by name in the folders seen so far \
+               and : runs any pcli2 command. q quits.",
+    },
+];
+
+// A walk through the screen, one stop at a time
+#[derive(Debug, Default)]
+pub struct Tour {
+    pub stop: usize,
+}
+
+impl Tour {
+    pub fn current(&self) -> &'static TourStop {
+        &STOPS[self.stop.min(STOPS.len() - 1)]
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.stop + 1 >= STOPS.len()
+    }
+
+    pub fn next(&mut self) {
+        self.stop = (self.stop + 1).min(STOPS.len() - 1);
+    }
+
+    pub fn previous(&mut self) {
+        self.stop = self.stop.saturating_sub(1);
+    }
+}
@@ -1,91 +1,722 @@
-use crate::app::{App, AppState, Asset};
+use crate::app::{
+    ActivePane, App, AppState, Asset, DataSource, LogFilter, ScreenAreas, SearchSortColumn,
+};
+use crate::asset_table::{ASSEMBLY_MARK, AssetTable, NOTE_MARK};
+use crate::config::Profile;
+use crate::csv_import::ImportPlan;
+use crate::dialog::Confirm;
+use crate::finder::Finder;
+use crate::folder_diff::{DiffColumn, FolderDiff};
+use crate::folder_picker::{self, FolderPicker};
+use crate::geometry::{GeometryState, GeometryText};
+use crate::jobs::Job;
+use crate::journal::InterruptedJobs;
+use crate::json_view::JsonToken;
+use crate::log_entry::{LogEntry, LogStatus};
+use crate::match_history::MatchHistory;
+use crate::metadata_diff::MetadataDiff;
+use crate::metrics::{self, Samples};
+use crate::modal::Modal;
+use crate::notifications::NotificationLevel;
+use crate::pcli_commands::{self, RunningCommand, TransferProgress};
+use crate::permissions;
+use crate::preview::{self, Protocol, ThumbnailState};
+use crate::processing::Processing;
+use crate::rate_limit;
+use crate::review::Review;
+use crate::schema::PropertySchema;
+use crate::setup::{Setup, SetupStep};
+use crate::sync::{SyncAction, SyncPlan};
+use crate::tags::{TagPicker, TagPickerMode};
+use crate::text::{self, column_width, truncate_to_width};
+use crate::theme::Theme;
+use crate::tour::{Tour, TourTarget};
+use crate::validation::{Rules, ValidationReport};
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Clear,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
-    widgets::{Cell, Row, Table},
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Cell, Row, Table, TableState},
 };
+use std::collections::BTreeMap;
 
 pub fn draw(f: &mut Frame, app: &mut App) {
-    // Define the main layout - without the top bar
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Min(10),   // Main content area (now starts at the top)
-                Constraint::Length(6), // Multi-line log window
-                Constraint::Length(1), // Contextual key bindings line
-            ]
-            .as_ref(),
-        )
-        .split(f.area());
+    // Resolve the palette once per frame and hand it to every draw function
+    let theme = app.theme;
+    app.preview_graphics = None;
+    app.screen_areas = ScreenAreas::default();
+
+    // A zoomed pane takes the rows of the log pane too
+    let zoom = app.zoomed_pane();
+    let layout = main_layout(f.area(), app.show_log_pane && zoom.is_none());
 
     // Draw the main content area based on current state (this starts at the top now)
-    draw_main_content(f, main_chunks[0], app);
+    if zoom == Some(ActivePane::Log) {
+        draw_log_pane(f, layout.content, app, &theme);
+        app.active_pane_area = Some(layout.content);
+        app.screen_areas.log = Some(layout.content);
+    } else {
+        draw_main_content(f, layout.content, app, &theme);
+    }
+
+    if let Some(log) = layout.log {
+        draw_log_pane(f, log, app, &theme);
+        app.screen_areas.log = Some(log);
+        if matches!(app.active_pane, ActivePane::Log) {
+            app.active_pane_area = Some(log);
+        }
+    }
+
+    // Background jobs show their progress over the log pane, or over the bottom of the
+    // content while the log pane is hidden
+    if let Some(job) = app.jobs.running().first() {
+        draw_job_gauge(
+            f,
+            layout.gauge_area(),
+            job,
+            app.jobs.running().len() - 1,
+            &theme,
+        );
+    }
 
-    // Draw the status bar
-    draw_status_bar(f, main_chunks[1], app);
+    draw_status_line(f, layout.status, app, &theme);
+    app.screen_areas.status = Some(layout.status);
+    app.screen_areas.hints = Some(layout.hints);
 
     // Draw contextual key bindings at the bottom of the screen
-    draw_contextual_key_bindings(f, app, main_chunks[2]);
+    draw_contextual_key_bindings(f, app, layout.hints, &theme);
+
+    // Draw the open dialogs from the bottom of the stack up, so the one with the keys is on top
+    let modals: Vec<Modal> = app.modals.iter().collect();
+    for modal in modals {
+        draw_modal(f, app, modal, &theme);
+    }
+
+    if app.show_metrics {
+        draw_metrics_overlay(f, layout.content, app, &theme);
+    }
+
+    // Draw toasts last so they stay on top of everything else
+    draw_toasts(f, f.area(), app, &theme);
+
+    // Graphics protocol images are drawn over the text, so leave them out under a modal
+    if app.modals.covers_view() {
+        app.preview_graphics = None;
+    }
+}
+
+fn draw_modal(f: &mut Frame, app: &mut App, modal: Modal, theme: &Theme) {
+    let area = f.area();
+    match modal {
+        Modal::Search => draw_search_modal(f, area, app, theme),
+        Modal::Finder => {
+            if let Some(ref finder) = app.finder {
+                draw_finder_modal(f, area, app, finder, theme);
+            }
+        }
+        Modal::FolderPicker => {
+            if let Some(ref picker) = app.folder_picker {
+                draw_folder_picker(f, area, picker, theme);
+            }
+        }
+        Modal::TagPicker => {
+            if let Some(ref picker) = app.tag_picker {
+                draw_tag_picker(f, area, picker, theme);
+            }
+        }
+        Modal::AssetDetails => draw_asset_details_modal(f, area, app, theme),
+        Modal::GeometricMatch => draw_geometric_match_modal(f, area, app, theme),
+        Modal::WhereUsed => {
+            if let Some(ref part) = app.where_used_part {
+                draw_where_used_modal(f, area, app, part, theme);
+            }
+        }
+        Modal::CustomCommands => {
+            if let Some(selected) = app.custom_command_menu {
+                draw_custom_command_menu(f, area, app, selected, theme);
+            }
+        }
+        Modal::AssemblyDownload => {
+            if let Some(ref asset) = app.assembly_download_prompt {
+                draw_assembly_download_prompt(f, area, asset, theme);
+            }
+        }
+        Modal::Confirm => {
+            if let Some(ref confirm) = app.confirm {
+                draw_confirm(f, area, confirm, theme);
+            }
+        }
+        Modal::SyncPlan => {
+            if let Some(ref plan) = app.sync_plan {
+                draw_sync_plan_modal(f, area, plan, theme);
+            }
+        }
+        Modal::FolderDiff => {
+            if let Some(ref diff) = app.folder_diff {
+                draw_folder_diff_modal(f, area, diff, theme);
+            }
+        }
+        Modal::MetadataDiff => {
+            if let Some(ref diff) = app.metadata_diff {
+                draw_metadata_diff_modal(f, area, diff, theme);
+            }
+        }
+        Modal::MatchReview => {
+            if let Some(ref review) = app.match_review {
+                draw_match_review_modal(f, area, review, theme);
+            }
+        }
+        Modal::MatchHistory => {
+            if let Some(ref history) = app.match_history {
+                draw_match_history_modal(f, area, history, theme);
+            }
+        }
+        Modal::InterruptedJobs => {
+            if let Some(ref interrupted) = app.interrupted_jobs {
+                draw_interrupted_jobs_modal(f, area, interrupted, theme);
+            }
+        }
+        Modal::PropertySchema => {
+            if let Some(ref schema) = app.property_schema {
+                draw_property_schema_modal(f, area, schema, theme);
+            }
+        }
+        Modal::Validation => {
+            if let Some(ref report) = app.validation_report {
+                draw_validation_modal(f, area, report, &app.metadata_rules, theme);
+            }
+        }
+        Modal::MetadataImport => {
+            if let Some(ref plan) = app.metadata_import {
+                draw_metadata_import_modal(f, area, plan, theme);
+            }
+        }
+        Modal::Setup => {
+            if let Some(ref setup) = app.setup {
+                draw_setup_modal(f, area, setup, theme);
+            }
+        }
+        Modal::ProfilePicker => {
+            if let Some(selected) = app.profile_picker {
+                draw_profile_picker_modal(f, area, &app.config.profiles, selected, theme);
+            }
+        }
+        Modal::Tour => {
+            if let Some(ref tour) = app.tour {
+                draw_tour(f, area, app.screen_areas, tour, theme);
+            }
+        }
+        Modal::Help => draw_help_modal(f, area, app, theme),
+        Modal::LogDetail => draw_log_detail_modal(f, area, app, theme),
+        Modal::Error => draw_error_modal(f, area, app, theme),
+        // Prompts are part of the status bar
+        Modal::Prompt | Modal::CommandLine => {}
+    }
+}
+
+// Main content, log window and key bindings line, from top to bottom
+// Where the parts of the screen go, from the top: the content, the log pane when it is shown,
+// the status line and the key bindings line
+struct MainLayout {
+    content: Rect,
+    log: Option<Rect>,
+    status: Rect,
+    hints: Rect,
+}
 
-    // Draw search modal if active
-    if app.show_search_modal {
-        draw_search_modal(f, f.area(), app);
+impl MainLayout {
+    // Progress gauges go over the log pane, or the bottom of the content without it
+    fn gauge_area(&self) -> Rect {
+        self.log.unwrap_or(self.content)
     }
+}
+
+fn main_layout(area: Rect, show_log: bool) -> MainLayout {
+    let log_height = if show_log { 6 } else { 0 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(10),            // Main content area
+            Constraint::Length(log_height), // Multi-line log pane
+            Constraint::Length(1),          // Status line
+            Constraint::Length(1),          // Contextual key bindings line
+        ])
+        .split(area);
+    MainLayout {
+        content: chunks[0],
+        log: show_log.then_some(chunks[1]),
+        status: chunks[2],
+        hints: chunks[3],
+    }
+}
+
+// The last frame drawn before a handler started, kept so the busy indicator can be animated over it
+pub struct BusyFrame {
+    pub buffer: Buffer,
+    pub pane: Option<Rect>,
+    pub theme: Theme,
+    pub show_log: bool, // Whether the log pane was shown, for placing the transfer gauge
+}
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+// Redraw the last frame with the active pane dimmed and a spinner with the elapsed time and the
+// records parsed so far in its title, plus a gauge over the log window while a download runs
+pub fn draw_busy(
+    f: &mut Frame,
+    frame: &BusyFrame,
+    running: &RunningCommand,
+    transfer: Option<&TransferProgress>,
+) {
+    f.buffer_mut().clone_from(&frame.buffer);
+
+    if let Some(transfer) = transfer {
+        let layout = main_layout(f.area(), frame.show_log);
+        draw_transfer_gauge(f, layout.gauge_area(), transfer, &frame.theme);
+    }
+
+    let Some(pane) = frame.pane else {
+        return;
+    };
+    let pane = pane.intersection(f.area());
+    if pane.width < 4 || pane.height < 2 {
+        return;
+    }
+
+    // Dim the pane's contents, leaving the border and title alone, to signal they're stale
+    let inner = Rect {
+        x: pane.x + 1,
+        y: pane.y + 1,
+        width: pane.width - 2,
+        height: pane.height.saturating_sub(2),
+    };
+    f.buffer_mut()
+        .set_style(inner, Style::default().add_modifier(Modifier::DIM));
+
+    let elapsed = running.started.elapsed();
+    let spinner = SPINNER_FRAMES[(elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len()];
+    let records = match running.records {
+        0 => String::new(),
+        1 => " · 1 record".to_string(),
+        n => format!(" · {} records", n),
+    };
+    let rate_limited = rate_limit::remaining().map_or(String::new(), |remaining| {
+        format!(
+            " · ⏳ rate limited — resuming in {}s",
+            remaining.as_secs_f64().ceil()
+        )
+    });
+    let label = format!(
+        " {} {} {:.1}s{}{} ",
+        spinner,
+        running.operation,
+        elapsed.as_secs_f64(),
+        records,
+        rate_limited
+    );
+    let label = truncate_to_width(&label, pane.width.saturating_sub(4) as usize).into_owned();
+    let width = text::display_width(&label) as u16;
+    let label_area = Rect {
+        x: pane.x + pane.width - width - 2,
+        y: pane.y,
+        width,
+        height: 1,
+    };
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            label,
+            Style::default()
+                .fg(frame.theme.warning)
+                .add_modifier(Modifier::BOLD),
+        )),
+        label_area,
+    );
+}
+
+// Percent, size and transfer rate of a download, in the bottom rows of the given area
+fn draw_transfer_gauge(f: &mut Frame, area: Rect, transfer: &TransferProgress, theme: &Theme) {
+    const GAUGE_HEIGHT: u16 = 3;
+
+    let gauge_area = Rect {
+        x: area.x,
+        y: area.y + area.height.saturating_sub(GAUGE_HEIGHT),
+        width: area.width,
+        height: area.height.min(GAUGE_HEIGHT),
+    };
+
+    let rate = format!("{}/s", text::format_bytes(transfer.rate() as u64));
+    let label = match (transfer.ratio(), transfer.total) {
+        (Some(ratio), Some(total)) => format!(
+            "{:.0}% · {} of {} · {}",
+            ratio * 100.0,
+            text::format_bytes(transfer.bytes),
+            text::format_bytes(total),
+            rate
+        ),
+        _ => format!("{} · {}", text::format_bytes(transfer.bytes), rate),
+    };
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" ⬇ {} ", transfer.label))
+                .border_style(Style::default().fg(theme.accent))
+                .style(Style::default().bg(theme.modal_bg)),
+        )
+        .gauge_style(Style::default().fg(theme.accent).bg(theme.modal_bg))
+        .ratio(transfer.ratio().unwrap_or(0.0))
+        .label(Span::styled(label, Style::default().fg(theme.text)));
+
+    f.render_widget(Clear, gauge_area);
+    f.render_widget(gauge, gauge_area);
+}
+
+// Progress of a background job, in the bottom rows of the given area
+fn draw_job_gauge(f: &mut Frame, area: Rect, job: &Job, others: usize, theme: &Theme) {
+    const GAUGE_HEIGHT: u16 = 3;
+
+    let gauge_area = Rect {
+        x: area.x,
+        y: area.y + area.height.saturating_sub(GAUGE_HEIGHT),
+        width: area.width,
+        height: area.height.min(GAUGE_HEIGHT),
+    };
+
+    let title = if others > 0 {
+        format!(" ⚙ {} (+{} more) ", job.title, others)
+    } else {
+        format!(" ⚙ {} ", job.title)
+    };
+    let label = format!(
+        "{} of {} · {} · {}s",
+        job.done,
+        job.total,
+        job.current,
+        job.started.elapsed().as_secs()
+    );
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.accent))
+                .style(Style::default().bg(theme.modal_bg)),
+        )
+        .gauge_style(Style::default().fg(theme.accent).bg(theme.modal_bg))
+        .ratio(job.ratio())
+        .label(Span::styled(label, Style::default().fg(theme.text)));
+
+    f.render_widget(Clear, gauge_area);
+    f.render_widget(gauge, gauge_area);
+}
+
+// Frame times, input latency, cache hit rate and pcli2 invocations since startup, in the bottom
+// right corner of the content
+fn draw_metrics_overlay(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    const WIDTH: u16 = 50;
+    const HEIGHT: u16 = 9;
+
+    let label = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let timing = |name: &'static str, samples: &Samples| {
+        Line::from(vec![
+            Span::styled(format!("{:<9}", name), label),
+            Span::raw(format!(
+                "{:>8}  avg {:>8}  max {:>8}",
+                metrics::format_ms(samples.last()),
+                metrics::format_ms(samples.average()),
+                metrics::format_ms(samples.max())
+            )),
+        ])
+    };
+
+    let metrics = &app.metrics;
+    let invocations = pcli_commands::invocations();
+    let listings = invocations.map_or(0, |invocations| invocations.listings);
+    let loads = metrics.cache_hits + listings;
+    let cache = match loads {
+        0 => "no listings yet".to_string(),
+        _ => format!(
+            "{} of {} listings ({:.0}%)",
+            metrics.cache_hits,
+            loads,
+            metrics.cache_hits as f64 * 100.0 / loads as f64
+        ),
+    };
+    let pcli2 = match invocations {
+        Some(invocations) => format!(
+            "{} run · {} background · {} failed",
+            invocations.foreground, invocations.background, invocations.failed
+        ),
+        None => "-".to_string(),
+    };
+    let lines = vec![
+        timing("frame", &metrics.frame),
+        timing("layout", &metrics.build),
+        timing("latency", &metrics.latency),
+        Line::from(vec![
+            Span::styled(format!("{:<9}", "frames"), label),
+            Span::raw(metrics.frames.to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled(format!("{:<9}", "cache"), label),
+            Span::raw(cache),
+        ]),
+        Line::from(vec![
+            Span::styled(format!("{:<9}", "pcli2"), label),
+            Span::raw(pcli2),
+        ]),
+        Line::from(Span::styled(
+            "latency: input to the frame showing it",
+            Style::default().fg(theme.text_muted),
+        )),
+    ];
+
+    let overlay = Rect {
+        x: area.x + area.width.saturating_sub(WIDTH + 1),
+        y: area.y + area.height.saturating_sub(HEIGHT + 1),
+        width: area.width.min(WIDTH),
+        height: area.height.min(HEIGHT),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Performance ")
+        .title_bottom(Line::from(" F12: close ").right_aligned())
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().fg(theme.text).bg(theme.modal_bg));
+    f.render_widget(Clear, overlay);
+    f.render_widget(Paragraph::new(lines).block(block), overlay);
+}
 
-    // Draw help modal if active
-    if matches!(app.current_state, AppState::Help) {
-        draw_help_modal(f, f.area(), app);
+fn level_color(level: NotificationLevel, theme: &Theme) -> ratatui::style::Color {
+    match level {
+        NotificationLevel::Info => theme.info,
+        NotificationLevel::Success => theme.success,
+        NotificationLevel::Warning => theme.warning,
+        NotificationLevel::Error => theme.error,
     }
+}
+
+fn draw_toasts(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    const TOAST_HEIGHT: u16 = 3;
+
+    // Toasts are stacked in the top-right corner, oldest at the top
+    let width = area.width.saturating_sub(2).min(60);
+    let mut y = area.y + 1;
+
+    for toast in app.notifications.active_toasts() {
+        if y + TOAST_HEIGHT > area.y + area.height {
+            break;
+        }
+
+        let toast_area = Rect {
+            x: area.x + area.width.saturating_sub(width + 1),
+            y,
+            width,
+            height: TOAST_HEIGHT,
+        };
+        let color = level_color(toast.level, theme);
+        let mut message = format!("{} {}", toast.level.icon(), toast.message);
+        if toast.level == NotificationLevel::Error && app.last_error.is_some() {
+            message.push_str(" (E: details)");
+        }
+
+        let paragraph = Paragraph::new(Line::from(Span::styled(
+            truncate_to_width(&message, width.saturating_sub(2) as usize).into_owned(),
+            Style::default().fg(color),
+        )))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+                .style(Style::default().bg(theme.modal_bg)),
+        );
 
-    // Draw geometric match modal if active
-    if app.show_geometric_match_modal {
-        draw_geometric_match_modal(f, f.area(), app);
+        f.render_widget(Clear, toast_area);
+        f.render_widget(paragraph, toast_area);
+        y += TOAST_HEIGHT;
     }
 }
 
+fn draw_main_content(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    // Views without panes are busy as a whole; the folder and asset panels narrow this down
+    app.active_pane_area = Some(area);
 
-fn draw_main_content(f: &mut Frame, area: Rect, app: &mut App) {
     match app.current_state {
-        AppState::Folders | AppState::Assets => draw_folder_asset_view(f, area, app),
-        AppState::Search => draw_search_view(f, area, app),
-        AppState::Uploading | AppState::Downloading => draw_upload_download_view(f, area, app),
-        AppState::Help => draw_folder_asset_view(f, area, app), // Show folder/asset view underneath help modal
-        AppState::CommandHistory => draw_command_history_view(f, area, app),
-        AppState::Log => draw_log_view(f, area, app),
-        AppState::PaneResize => draw_folder_asset_view(f, area, app), // Use the same view but indicate resize mode
+        AppState::Folders | AppState::Assets => draw_folder_asset_view(f, area, app, theme),
+        AppState::Uploading | AppState::Downloading => {
+            draw_upload_download_view(f, area, app, theme)
+        }
+        AppState::CommandHistory => draw_command_history_view(f, area, app, theme),
+        AppState::Log => draw_log_view(f, area, app, theme),
+        AppState::PaneResize => draw_folder_asset_view(f, area, app, theme), // Use the same view but indicate resize mode
+        AppState::RawOutput => draw_raw_output_view(f, area, app, theme),
+        AppState::JsonView => draw_json_view(f, area, app, theme),
+        AppState::Basket => draw_basket_view(f, area, app, theme),
+        AppState::Deletions => draw_deletions_view(f, area, app, theme),
+        AppState::Jobs => draw_jobs_view(f, area, app, theme),
     }
 }
 
-fn draw_folder_asset_view(f: &mut Frame, area: Rect, app: &mut App) {
-    // Split the main area into left (folders) and right (assets) panels
+fn draw_folder_asset_view(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    match app.zoomed_pane() {
+        Some(ActivePane::Folders) => return draw_folders_panel(f, area, app, theme),
+        Some(ActivePane::Assets) => return draw_assets_panel(f, area, app, theme),
+        _ => {}
+    }
+
+    // Split the main area into left (folders) and right (assets) panels, or top and bottom in
+    // narrow terminals
+    let direction = if app.stacked(area.width) {
+        Direction::Vertical
+    } else {
+        Direction::Horizontal
+    };
     let horizontal_chunks = Layout::default()
-        .direction(Direction::Horizontal)
+        .direction(direction)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(area);
 
     // Draw folders on the left
-    draw_folders_panel(f, horizontal_chunks[0], app);
+    draw_folders_panel(f, horizontal_chunks[0], app, theme);
 
-    // Draw assets on the right
-    draw_assets_panel(f, horizontal_chunks[1], app);
+    // Draw assets on the right, with the metadata strip and then the thumbnail preview below
+    // them when they are shown
+    let strip_lines = match app.config.metadata_strip.lines {
+        0 => 0,
+        1 | 2 => 1,
+        _ => 3,
+    };
+    let (assets_area, strip_area) = if strip_lines > 0 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(strip_lines + 2)])
+            .split(horizontal_chunks[1]);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (horizontal_chunks[1], None)
+    };
+    if app.show_preview {
+        let right_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(assets_area);
+        draw_assets_panel(f, right_chunks[0], app, theme);
+        draw_preview_panel(f, right_chunks[1], app, theme);
+    } else {
+        draw_assets_panel(f, assets_area, app, theme);
+    }
+    if let Some(area) = strip_area {
+        draw_metadata_strip(f, area, app, theme);
+    }
 }
 
-fn draw_folders_panel(f: &mut Frame, area: Rect, app: &mut App) {
-    let is_active = matches!(app.active_pane, crate::app::ActivePane::Folders);
-    let border_color = if is_active {
-        Color::Rgb(255, 215, 0)  // Gold color for active pane (consistent with other panes)
-    } else {
-        Color::Rgb(100, 100, 100)  // Muted gray for inactive
+// The configured metadata of the asset under the mouse pointer, or else the selected one
+fn draw_metadata_strip(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let asset = app
+        .hovered_asset
+        .or(Some(app.selected_asset_index))
+        .and_then(|i| app.assets.get(i));
+    let title = asset.map_or(" ℹ Metadata ".to_string(), |asset| {
+        format!(" ℹ {} ", asset.name)
+    });
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(theme.border(false));
+
+    let Some(asset) = asset else {
+        let empty = Paragraph::new("No asset selected")
+            .style(Style::default().fg(theme.text_muted))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
     };
-    let title = format!(
+    let mut spans = Vec::new();
+    for key in &app.config.metadata_strip.keys {
+        let value = asset.metadata_value_ignoring_case(key).or_else(|| {
+            (key.eq_ignore_ascii_case("size"))
+                .then_some(asset.size)
+                .flatten()
+                .map(text::format_bytes)
+        });
+        if !spans.is_empty() {
+            spans.push(Span::styled("  ·  ", Style::default().fg(theme.text_muted)));
+        }
+        spans.push(Span::styled(
+            format!("{}: ", key),
+            Style::default().fg(theme.text_muted),
+        ));
+        spans.push(match value {
+            Some(value) => Span::styled(value, Style::default().fg(theme.text)),
+            None => Span::styled("—", Style::default().fg(theme.text_muted)),
+        });
+    }
+    let paragraph = Paragraph::new(Line::from(spans))
+        .block(block)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
+
+fn draw_preview_panel(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" 🖼 Preview ")
+        .border_style(theme.border(false))
+        .style(Style::default().bg(theme.panel_bg));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let placeholder = |f: &mut Frame, message: String| {
+        let paragraph = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.text_muted))
+            .wrap(ratatui::widgets::Wrap { trim: true });
+        f.render_widget(paragraph, inner);
+    };
+
+    let Some(asset) = app.assets.get(app.selected_asset_index) else {
+        placeholder(f, "No asset selected".to_string());
+        return;
+    };
+    match app.previewer.get(&asset.uuid) {
+        Some(ThumbnailState::Ready(thumbnail)) => {
+            if app.previewer.protocol() == Protocol::Blocks {
+                let lines = preview::half_blocks(&thumbnail.image, inner.width, inner.height);
+                f.render_widget(Paragraph::new(lines), inner);
+            } else {
+                // Drawn by the main loop once the frame is on screen
+                app.preview_graphics = Some((asset.uuid.clone(), inner));
+            }
+        }
+        Some(ThumbnailState::Failed(error)) => {
+            placeholder(f, format!("No thumbnail: {}", error));
+        }
+        Some(ThumbnailState::Loading) | None => {
+            placeholder(f, "Loading thumbnail...".to_string());
+        }
+    }
+}
+
+fn draw_folders_panel(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let is_active = matches!(app.active_pane, crate::app::ActivePane::Folders);
+    if is_active {
+        app.active_pane_area = Some(area);
+    }
+    app.screen_areas.folders = Some(area);
+    app.screen_areas.folder_rows = Some(area.inner(Margin::new(1, 1)));
+    let mut title = format!(
         " 📁 Folder(s) [{}] ",
         app.current_folder.as_deref().unwrap_or("/")
     );
+    push_source_age(&mut title, app.folders_source.as_ref());
     let items: Vec<ListItem> = app
         .folders
         .iter()
@@ -95,13 +726,13 @@ fn draw_folders_panel(f: &mut Frame, area: Rect, app: &mut App) {
 
             let content = if folder.uuid == ".." {
                 let special_style = if is_selected {
-                    Style::default()
-                        .bg(Color::Rgb(106, 90, 205))  // Indigo for parent folder
-                        .fg(Color::White)
+                    theme
+                        .selected()
+                        .bg(theme.secondary_selected_bg)
                         .add_modifier(Modifier::ITALIC)
                 } else {
                     Style::default()
-                        .fg(Color::Rgb(173, 216, 230))  // Light blue for parent folder
+                        .fg(theme.secondary)
                         .add_modifier(Modifier::ITALIC)
                 };
                 Line::from(vec![Span::styled(
@@ -113,19 +744,19 @@ fn draw_folders_panel(f: &mut Frame, area: Rect, app: &mut App) {
                 let name_span = Span::styled(
                     format!("📂 {}", folder.name),
                     if is_selected {
-                        Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White)  // Forest green bg with white text when selected (same as assets)
+                        theme.selected() // Same selection style as assets
                     } else {
-                        Style::default().fg(Color::Rgb(255, 215, 0))  // Gold text for folder name (same as assets)
-                    }
+                        Style::default().fg(theme.accent) // Same name color as assets
+                    },
                 );
 
                 let stats_span = Span::styled(
                     format!(" ({} 📁, {} 📎)", folder.folders_count, folder.assets_count),
                     if is_selected {
-                        Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::Rgb(200, 200, 200))  // Lighter gray stats when selected
+                        Style::default().bg(theme.selection_bg).fg(theme.text) // Lighter stats when selected
                     } else {
-                        Style::default().fg(Color::Rgb(150, 150, 150))  // Subdued gray for stats
-                    }
+                        Style::default().fg(theme.text_muted) // Subdued stats
+                    },
                 );
 
                 Line::from(vec![name_span, stats_span])
@@ -140,129 +771,69 @@ fn draw_folders_panel(f: &mut Frame, area: Rect, app: &mut App) {
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD)),
+                .border_style(theme.border(is_active)),
         )
-        .highlight_style(Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White));  // Forest green highlight (same as assets)
+        .highlight_style(theme.selected()); // Same highlight as assets
 
     f.render_widget(list, area);
 }
 
-fn draw_assets_panel(f: &mut Frame, area: Rect, app: &mut App) {
+fn draw_assets_panel(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
     let is_active = matches!(app.active_pane, crate::app::ActivePane::Assets);
-    let border_color = if is_active {
-        Color::Rgb(255, 215, 0)  // Gold color for active pane (consistent with other panes)
-    } else {
-        Color::Rgb(100, 100, 100)  // Muted gray for inactive
-    };
+    if is_active {
+        app.active_pane_area = Some(area);
+    }
+    app.screen_areas.assets = Some(area);
 
-    let title = if app.assets_loading_for_selection {
+    let mut title = if app.assets_loading_for_selection {
         " 📎 Assets - Loading... ".to_string()
+    } else if !app.basket.is_empty() {
+        format!(" 📎 Asset(s) | 🧺 {} in basket ", app.basket.len())
     } else {
         " 📎 Asset(s) ".to_string()
     };
-
-    // Extract all unique metadata keys from assets
-    let mut all_metadata_keys = std::collections::HashSet::<String>::new();
-    for asset in &app.assets {
-        if let Some(obj) = asset.metadata.as_object() {
-            for key in obj.keys() {
-                // Special handling for the case where metadata contains a "meta" key that wraps actual metadata
-                if key == "meta" {
-                    // If the "meta" key contains an object, extract keys from that object instead
-                    if let Some(meta_obj) = obj.get(key).and_then(|v| v.as_object()) {
-                        for meta_key in meta_obj.keys() {
-                            all_metadata_keys.insert(meta_key.clone());
-                        }
-                    } else {
-                        // If "meta" doesn't contain an object, use it as a regular key
-                        all_metadata_keys.insert(key.clone());
-                    }
-                } else {
-                    // Regular handling for other keys
-                    all_metadata_keys.insert(key.clone());
-                }
-            }
-        }
+    if let Some(ref tag) = app.tag_filter {
+        title.push_str(&format!(
+            "| 🏷 {} ({}/{}) ",
+            tag,
+            app.assets.len(),
+            app.all_assets.len()
+        ));
     }
-
-    // Convert to sorted vector
-    let mut sorted_metadata_keys: Vec<String> = all_metadata_keys.into_iter().collect();
-    sorted_metadata_keys.sort();
-
-    // Define headers for the table
-    let mut headers = vec!["", "Name", "Path"]; // Icon, Name, Path (removed Type column)
-    for key in &sorted_metadata_keys {
-        headers.push(key.as_str());
+    if !app.assets_loading_for_selection {
+        push_source_age(&mut title, app.assets_source.as_ref());
     }
 
-    // Calculate optimal column widths based on content
-    let column_widths = if app.assets.is_empty() {
-        // Default widths when no assets
-        let mut widths = vec![
-            Constraint::Length(3),  // Icon column (single character + padding)
-            Constraint::Min(15),    // Name column (minimum width for readability)
-            Constraint::Min(15),    // Path column (minimum width for readability)
-        ];
-
-        // Add constraints for metadata columns
-        for _ in &sorted_metadata_keys {
-            widths.push(Constraint::Min(10)); // Minimum width for metadata columns
-        }
-        widths
-    } else {
-        // Calculate max lengths for each column based on content
-        let max_icon_len = 1; // Icons are single characters (don't need mut)
-        let mut max_name_len = "Name".len(); // Minimum width based on header
-        let mut max_path_len = "Path".len(); // Minimum width based on header
-
-        // Calculate max lengths for metadata columns
-        let mut max_metadata_lengths = Vec::new();
-        for key in &sorted_metadata_keys {
-            // Initialize with header length
-            max_metadata_lengths.push(key.len());
-        }
-
-        // Iterate through assets to find max content lengths
-        for asset in &app.assets {
-            // Update max name length
-            max_name_len = std::cmp::max(max_name_len, asset.name.len());
-
-            // Update max path length
-            max_path_len = std::cmp::max(max_path_len, asset.folder_uuid.len());
-
-            // Update max metadata lengths
-            if let Some(obj) = asset.metadata.as_object() {
-                for (i, key) in sorted_metadata_keys.iter().enumerate() {
-                    if let Some(value) = obj.get(key) {
-                        // Handle string values to get actual length without quotes
-                        let value_str = if let Some(str_val) = value.as_str() {
-                            str_val
-                        } else {
-                            &value.to_string()
-                        };
-
-                        if i < max_metadata_lengths.len() {
-                            max_metadata_lengths[i] = std::cmp::max(max_metadata_lengths[i], value_str.len());
-                        }
-                    }
-                }
-            }
-        }
+    // The table is laid out anew only when the assets or the pane changed since the last frame
+    let table = match app.asset_table.take() {
+        Some(table) if table.is_current(app, area.width) => table,
+        _ => AssetTable::build(app, area.width),
+    };
+    if table.hidden > 0 {
+        title.push_str(&format!("| +{} more columns ", table.hidden));
+    }
+    if table.breaking > 0 {
+        title.push_str(&format!("| ⚠ {} break metadata rules (A) ", table.breaking));
+    }
+    let first_metadata_column = table.headers.len() - table.metadata_keys.len();
 
-        // Create constraints based on calculated widths - optimizing for minimal real estate
-        let mut widths = vec![
-            Constraint::Length((max_icon_len + 1) as u16),  // Icon column with minimal padding
-            Constraint::Length((max_name_len + 1) as u16), // Name column with minimal padding
-            Constraint::Length((max_path_len + 1) as u16), // Path column with minimal padding
-        ];
+    // The metadata cell being edited, if any: its column and, while typing, the input
+    let edited_cell = app.cell_editor.as_ref().map(|editor| {
+        let column = editor
+            .column
+            .min(table.metadata_keys.len().saturating_sub(1));
+        (column, editor.input.as_ref())
+    });
 
-        // Add constraints for each metadata column with minimal padding
-        for max_len in max_metadata_lengths {
-            widths.push(Constraint::Length((max_len + 1) as u16)); // Minimal padding of 1 character
+    // The metadata cell being edited, named in the title
+    if let Some((column, _)) = edited_cell
+        && let Some(key) = table.metadata_keys.get(column)
+    {
+        title.push_str(&format!("| ✎ {} ", key));
+        if table.fitted[first_metadata_column + column].is_none() {
+            title.push_str("(column hidden, widen the pane) ");
         }
-
-        widths
-    };
+    }
 
     if app.assets_loading_for_selection {
         // Show a loading indicator in a centered way with the frame
@@ -271,10 +842,10 @@ fn draw_assets_panel(f: &mut Frame, area: Rect, app: &mut App) {
                 Block::default()
                     .borders(Borders::ALL)
                     .title(title)
-                    .border_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD)),
+                    .border_style(theme.border(is_active)),
             )
             .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::Rgb(100, 149, 237))); // Cornflower blue
+            .style(Style::default().fg(theme.info));
 
         f.render_widget(loading_text, area);
     } else if app.assets.is_empty() {
@@ -284,26 +855,43 @@ fn draw_assets_panel(f: &mut Frame, area: Rect, app: &mut App) {
                 Block::default()
                     .borders(Borders::ALL)
                     .title(title)
-                    .border_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD)),
+                    .border_style(theme.border(is_active)),
             )
             .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::Rgb(100, 100, 100))); // Muted gray
+            .style(Style::default().fg(theme.border_inactive)); // Muted
 
         f.render_widget(no_data_text, area);
     } else {
-        // Create table rows
-        let rows = app.assets
+        // Rows start below the border, the header and its margin
+        let inner = area.inner(Margin::new(1, 1));
+        app.screen_areas.asset_rows = Some(Rect {
+            y: inner.y + 2,
+            height: inner.height.saturating_sub(2),
+            ..inner
+        });
+        let dragged = app.dragged_assets();
+        let warning = Style::default().fg(theme.warning);
+
+        // Create table rows; the table is drawn from its first row, so only those that fit
+        let rows = app
+            .assets
             .iter()
+            .zip(&table.rows)
+            .take(inner.height.saturating_sub(2) as usize)
             .enumerate()
-            .map(|(i, asset)| {
+            .map(|(i, (asset, row))| {
                 let is_selected = i == app.selected_asset_index;
                 let row_style = if is_selected {
-                    Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White)  // Forest green for selection
+                    theme.selected()
+                } else if dragged.as_ref().is_some_and(|range| range.contains(&i)) {
+                    Style::default().bg(theme.selection_bg).fg(theme.text)
                 } else {
-                    Style::default().fg(Color::Rgb(255, 215, 0))  // Gold for unselected
+                    Style::default().fg(theme.accent)
                 };
 
                 let icon = match asset.file_type.as_str() {
+                    _ if app.deletions.contains(&asset.uuid) => "🚮", // Staged for deletion
+                    _ if app.basket.contains(&asset.uuid) => "🧺",    // In the basket
                     "model" => "🏗️",    // Building/construction icon for 3D models
                     "document" => "📝", // Document icon
                     "image" => "🖼️",    // Image icon
@@ -317,98 +905,150 @@ fn draw_assets_panel(f: &mut Frame, area: Rect, app: &mut App) {
                 // Create cells for the basic columns
                 let mut cells = vec![
                     Cell::from(icon), // Icon cell
-                    Cell::from(asset.name.as_str()), // Name cell
-                    Cell::from(asset.folder_uuid.as_str()), // Path cell
+                    // Name cell, marked when the asset breaks a metadata rule
+                    Cell::from(row.name.as_str()).style(if row.breaks_rules {
+                        warning
+                    } else {
+                        Style::default()
+                    }),
+                    Cell::from(row.path.as_str()), // Path cell
                 ];
+                if table.notes_column {
+                    cells.insert(1, Cell::from(if row.note { NOTE_MARK } else { "" }));
+                }
+                if table.processing_column {
+                    let cell = match row.processing {
+                        Some((ref status, ref label)) => Cell::from(Span::styled(
+                            label.as_str(),
+                            processing_style(status, theme),
+                        )),
+                        None => Cell::from(""),
+                    };
+                    cells.push(cell);
+                }
+                if table.assembly_column {
+                    cells.push(Cell::from(if asset.is_assembly {
+                        ASSEMBLY_MARK
+                    } else {
+                        ""
+                    }));
+                }
+                if table.tags_column {
+                    let chips: Vec<Span> = row
+                        .tags
+                        .iter()
+                        .flat_map(|tag| [tag_chip(tag, theme), Span::raw(" ")])
+                        .collect();
+                    cells.push(Cell::from(Line::from(chips)));
+                }
 
-                // Add cells for each metadata key
-                if let Some(obj) = asset.metadata.as_object() {
-                    for key in &sorted_metadata_keys {
-                        // Special handling for the case where actual metadata is nested under a "meta" key
-                        let value = if obj.contains_key("meta") {
-                            // Check if the key exists in the nested "meta" object
-                            if let Some(meta_obj) = obj.get("meta").and_then(|v| v.as_object()) {
-                                if let Some(meta_value) = meta_obj.get(key) {
-                                    // Handle string values to remove quotes
-                                    if let Some(str_val) = meta_value.as_str() {
-                                        str_val.to_string()
-                                    } else {
-                                        meta_value.to_string() // For non-string values, keep the JSON representation
-                                    }
-                                } else {
-                                    "".to_string()
-                                }
-                            } else {
-                                // If "meta" doesn't contain an object, fall back to regular lookup
-                                if let Some(value) = obj.get(key) {
-                                    if let Some(str_val) = value.as_str() {
-                                        str_val.to_string()
-                                    } else {
-                                        value.to_string() // For non-string values, keep the JSON representation
-                                    }
-                                } else {
-                                    "".to_string()
-                                }
-                            }
-                        } else {
-                            // Regular lookup if there's no "meta" key
-                            if let Some(value) = obj.get(key) {
-                                if let Some(str_val) = value.as_str() {
-                                    str_val.to_string()
-                                } else {
-                                    value.to_string() // For non-string values, keep the JSON representation
+                // Geometry cells, right-aligned like other numbers
+                for value in row.geometry.iter().flatten() {
+                    cells.push(Cell::from(
+                        Line::from(value.as_str()).alignment(Alignment::Right),
+                    ));
+                }
+
+                // Add cells for each metadata key; the edited one is marked, or typed in
+                for (j, cell) in row.metadata.iter().enumerate() {
+                    match edited_cell {
+                        Some((column, input)) if is_selected && column == j => {
+                            let style = Style::default().fg(theme.text).bg(theme.input_bg);
+                            let line = match input {
+                                Some(input) => Line::from(
+                                    input
+                                        .spans("█")
+                                        .into_iter()
+                                        .map(|span| {
+                                            Span::styled(span.content.into_owned(), span.style)
+                                        })
+                                        .collect::<Vec<_>>(),
+                                ),
+                                None => {
+                                    let value = asset
+                                        .metadata_value(&table.metadata_keys[j])
+                                        .filter(|value| !value.is_empty())
+                                        .unwrap_or_else(|| " ".to_string());
+                                    Line::from(value).patch_style(
+                                        Style::default().add_modifier(Modifier::REVERSED),
+                                    )
                                 }
-                            } else {
-                                "".to_string()
-                            }
-                        };
-                        cells.push(create_cell_with_alignment(value));
-                    }
-                } else {
-                    // If no metadata, add empty cells for all metadata columns
-                    for _ in &sorted_metadata_keys {
-                        cells.push(create_cell_with_alignment("".to_string()));
+                            };
+                            cells.push(Cell::from(line).style(style));
+                        }
+                        _ if cell.broken => {
+                            let style = Style::default().fg(theme.error);
+                            cells.push(Cell::from(cell.text.as_str()).style(style));
+                        }
+                        _ if cell.numeric => cells.push(Cell::from(
+                            Line::from(cell.text.as_str()).alignment(Alignment::Right),
+                        )),
+                        _ => cells.push(Cell::from(cell.text.as_str())),
                     }
                 }
 
-                Row::new(cells).style(row_style)
+                Row::new(fitted_cells(cells, &table.fitted)).style(row_style)
             })
             .collect::<Vec<Row>>();
 
         // Create the table
-        let table = Table::new(
+        let header_cells = table
+            .headers
+            .iter()
+            .map(|header| Cell::from(header.as_str()))
+            .collect();
+        let widget = Table::new(
             rows,
-            column_widths,
+            table
+                .fitted
+                .iter()
+                .flatten()
+                .map(|&width| Constraint::Length(width as u16)),
         )
-            .header(
-                Row::new(headers.iter().map(|&h| Cell::from(h)))
-                .style(Style::default().fg(Color::Rgb(255, 215, 0))) // Gold header text
-                .bottom_margin(1)
-            )
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(title)
-                    .border_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD)),
-            )
-            .highlight_style(Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White)) // Forest green highlight
-            .column_spacing(1); // Add spacing between columns for better readability
+        .header(
+            Row::new(fitted_cells(header_cells, &table.fitted))
+                .style(Style::default().fg(theme.accent)) // Accent header text
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(theme.border(is_active)),
+        )
+        .highlight_style(theme.selected())
+        .column_spacing(1); // Add spacing between columns for better readability
+
+        f.render_widget(widget, area);
+    }
+    app.asset_table = Some(table);
+}
 
-        f.render_widget(table, area);
+fn processing_style(processing: &Processing, theme: &Theme) -> Style {
+    match processing {
+        Processing::Ready => Style::default().fg(theme.success),
+        Processing::Running(_) => Style::default().fg(theme.warning),
+        Processing::Failed(_) => Style::default().fg(theme.error),
     }
 }
 
-fn draw_search_view(f: &mut Frame, area: Rect, app: &App) {
-    // Show search input with instructions
-    let search_block = Block::default().borders(Borders::ALL).title(format!(
-        "Search: {} [Press Enter to search, Esc to cancel]",
-        app.search_query
-    ));
+// Tell in a pane title how old its listing is, and how to get a new one
+fn push_source_age(title: &mut String, source: Option<&DataSource>) {
+    if let Some(age) = source.and_then(DataSource::age_label) {
+        title.push_str(&format!("| ⏱ {} · F5 refreshes ", age));
+    }
+}
 
-    f.render_widget(search_block, area);
+// The cells of the columns that fit_columns kept
+fn fitted_cells<'a>(cells: Vec<Cell<'a>>, fitted: &[Option<usize>]) -> Vec<Cell<'a>> {
+    cells
+        .into_iter()
+        .zip(fitted)
+        .filter_map(|(cell, width)| width.map(|_| cell))
+        .collect()
 }
 
-fn draw_upload_download_view(f: &mut Frame, area: Rect, app: &App) {
+fn draw_upload_download_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let title = match app.current_state {
         AppState::Uploading => "Upload Mode",
         AppState::Downloading => "Download Mode",
@@ -420,14 +1060,14 @@ fn draw_upload_download_view(f: &mut Frame, area: Rect, app: &App) {
             vec![
                 Line::from("Upload Mode Active"),
                 Line::from("Press 'u' to select a file to upload"),
-                Line::from("Press 'q' to return to main view"),
+                Line::from("Press Esc to return to main view"),
             ]
         }
         AppState::Downloading => {
             vec![
                 Line::from("Download Mode Active"),
                 Line::from("Select an asset and press 'd' to download"),
-                Line::from("Press 'q' to return to main view"),
+                Line::from("Press Esc to return to main view"),
             ]
         }
         _ => vec![Line::from("Unknown mode")],
@@ -435,387 +1075,2525 @@ fn draw_upload_download_view(f: &mut Frame, area: Rect, app: &App) {
 
     let paragraph = Paragraph::new(text)
         .block(Block::default().borders(Borders::ALL).title(title))
-        .style(Style::default().fg(Color::Cyan));
+        .style(Style::default().fg(theme.info));
 
     f.render_widget(paragraph, area);
 }
 
+// Every key by context, the current view's first; typing filters the keys
+fn draw_help_modal(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let sections = app.key_sections();
+    let Some(ref mut help) = app.help else {
+        return;
+    };
+    let popup_area = centered_rect(70, 80, area);
+    f.render_widget(Clear, popup_area);
 
-fn draw_help_modal(f: &mut Frame, area: Rect, _app: &App) {
-    // Create a centered modal window
-    let popup_area = centered_rect(60, 80, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" 💡 Help ")
+        .title_bottom(" type:filter | ↑↓/PgUp/PgDn:scroll | esc:clear filter/close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
 
-    // Clear the background first
-    f.render_widget(Clear, popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+    let mut spans = vec![Span::styled("› ", Style::default().fg(theme.accent))];
+    spans.extend(help.filter.spans("█"));
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.input_bg)),
+        chunks[0],
+    );
 
-    let help_text = vec![
-        Line::from(""),
-        Line::from(Span::styled(
-            "PCLI2-TUI Help",
-            Style::default().add_modifier(Modifier::BOLD),
-        )),
-        Line::from(""),
-        Line::from("Navigation:"),
-        Line::from("  j / Down Arrow - Move down in current pane"),
-        Line::from("  k / Up Arrow   - Move up in current pane"),
-        Line::from("  Tab            - Switch between panes (forward)"),
-        Line::from("  Shift+Tab      - Switch between panes (reverse)"),
-        Line::from("  Enter          - Open selected folder or perform action on asset"),
-        Line::from("  Backspace      - Go back to parent folder"),
-        Line::from(""),
-        Line::from("View Controls:"),
-        Line::from("  a              - Switch to assets view"),
-        Line::from("  h              - Show this help screen"),
-        Line::from("  /              - Enter search mode"),
-        Line::from(""),
-        Line::from("Asset Operations:"),
-        Line::from("  d              - Download selected asset (in Assets view)"),
-        Line::from("  g              - Perform geometric match on selected asset (in Assets view)"),
-        Line::from(""),
-        Line::from("Mode Switching:"),
-        Line::from("  u              - Upload mode"),
-        Line::from("  d              - Download mode"),
-        Line::from(""),
-        Line::from("Search Dialog:"),
-        Line::from("  /              - Open search dialog"),
-        Line::from("  Tab            - Switch focus in search dialog (forward)"),
-        Line::from("  Shift+Tab      - Switch focus in search dialog (reverse)"),
-        Line::from("  Enter          - Perform search or close search results"),
-        Line::from("  Esc            - Close search dialog"),
-        Line::from(""),
-        Line::from("General:"),
-        Line::from("  Ctrl+N         - Enter pane resize mode"),
-        Line::from("  q / Ctrl+C     - Quit application"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Press 'q' or 'Esc' to close this help screen",
-            Style::default().add_modifier(Modifier::BOLD),
-        )),
-    ];
+    let sections = help.visible(sections);
+    if sections.is_empty() {
+        let empty = Paragraph::new("No keys match; Esc clears the filter")
+            .style(Style::default().fg(theme.text_muted));
+        f.render_widget(empty, chunks[2]);
+        return;
+    }
+
+    let key_width = sections
+        .iter()
+        .flat_map(|section| section.keys.iter())
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let title_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+    for section in &sections {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section.context.title(),
+            title_style,
+        )));
+        for (key, description) in &section.keys {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}  ", key, width = key_width),
+                    Style::default().fg(theme.warning),
+                ),
+                Span::raw(description.clone()),
+            ]));
+        }
+    }
+
+    // Keep the last line at the bottom rather than scrolling past it
+    let height = chunks[2].height as usize;
+    help.scroll = help.scroll.min(lines.len().saturating_sub(height));
+    let paragraph = Paragraph::new(lines).scroll((help.scroll as u16, 0));
+    f.render_widget(paragraph, chunks[2]);
+}
+
+// Helper function to create a centered rect
+
+fn draw_contextual_key_bindings(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    app.hint_targets.clear();
+
+    // The ':' command line takes over the hint bar while it is open
+    if let Some(ref input) = app.raw_command_input {
+        let mut spans = vec![Span::styled(
+            ":",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )];
+        spans.extend(input.spans("▏"));
+        let prompt = Paragraph::new(Line::from(spans))
+            .style(Style::default().fg(theme.text).bg(theme.input_bg));
+        f.render_widget(prompt, area);
+        return;
+    }
+
+    // So does a prompt for a path or name, with the reason the last input was refused
+    if let Some(ref prompt) = app.prompt {
+        let mut spans = vec![Span::styled(
+            prompt.label.clone(),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )];
+        spans.extend(prompt.input.spans("▏"));
+        if let Some(ref error) = prompt.error {
+            spans.push(Span::styled(
+                format!("  ✗ {}", error),
+                Style::default().fg(theme.error),
+            ));
+        }
+        let prompt = Paragraph::new(Line::from(spans))
+            .style(Style::default().fg(theme.text).bg(theme.input_bg));
+        f.render_widget(prompt, area);
+        return;
+    }
+
+    // Each key is a key cap in inverse video followed by what it does; where a hint is drawn
+    // is kept so a click on it can press its key
+    let context = app.key_context();
+    let cap_style = theme
+        .hints()
+        .add_modifier(Modifier::REVERSED | Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut x = area.x;
+    for hint in context.hints(&app.config.custom_commands) {
+        let cap = format!(" {} ", hint.cap);
+        let label = format!(" {} ", hint.label);
+        let width = (text::display_width(&cap) + text::display_width(&label)) as u16;
+        if let Some(key) = hint.key
+            && x < area.right()
+        {
+            let target = Rect::new(x, area.y, width.min(area.right() - x), 1);
+            app.hint_targets.push((target, key));
+        }
+        x = x.saturating_add(width);
+        spans.push(Span::styled(cap, cap_style));
+        spans.push(Span::raw(label));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)).style(theme.hints()), area);
+}
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+fn draw_log_pane(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    // Show as many entries as fit, keeping the selected one in the middle once they overflow
+    let visible = area.height.saturating_sub(2) as usize;
+    let start_idx = app
+        .log_scroll_position
+        .saturating_sub(visible / 2)
+        .min(app.log_entries.len().saturating_sub(visible));
+
+    let log_lines: Vec<ratatui::text::Line> = app
+        .log_entries
+        .iter()
+        .skip(start_idx)
+        .take(visible)
+        .map(|entry| log_entry_line(entry, theme))
+        .collect();
+    let list_items = if log_lines.is_empty() {
+        vec![Line::styled(
+            "No commands run yet",
+            Style::default().fg(theme.text_muted),
+        )]
+    } else {
+        log_lines
+    };
+
+    // Determine the border style based on whether this pane is active
+    let is_active = matches!(app.active_pane, crate::app::ActivePane::Log);
+
+    let list = ratatui::widgets::List::new(list_items)
+        .block(
+            ratatui::widgets::Block::default()
+                .borders(ratatui::widgets::Borders::ALL)
+                .title(format!(
+                    " 📝 Log [{}/{}] ", // Added log emoji
+                    app.log_scroll_position + 1,
+                    app.log_entries.len()
+                ))
+                .border_style(theme.border(is_active)),
+        )
+        .style(Style::default().fg(theme.text)) // Same text color as other panes
+        .highlight_style(theme.highlighted());
+
+    f.render_widget(list, area);
+}
+
+// One line with the status message on the left and where you are on the right: the folder,
+// the selected folder and asset, active filters, the basket and the tenant
+fn draw_status_line(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let style = Style::default().fg(theme.text).bg(theme.panel_bg);
+    let muted = style.fg(theme.text_muted);
+
+    let mut right = vec![Span::styled(
+        format!("📁 /{}", app.current_folder.as_deref().unwrap_or_default()),
+        style,
+    )];
+    let mut item = |text: String, style: Style| {
+        right.push(Span::styled(" · ", muted));
+        right.push(Span::styled(text, style));
+    };
+    if !app.folders.is_empty() {
+        item(
+            format!(
+                "folder {}/{}",
+                app.selected_folder_index + 1,
+                app.folders.len()
+            ),
+            muted,
+        );
+    }
+    if !app.assets.is_empty() {
+        item(
+            format!(
+                "asset {}/{}",
+                app.selected_asset_index + 1,
+                app.assets.len()
+            ),
+            muted,
+        );
+    }
+    if let Some(ref tag) = app.tag_filter {
+        item(format!("🏷 {}", tag), style.fg(theme.accent));
+    }
+    if !app.basket.is_empty() {
+        item(format!("🧺 {}", app.basket.len()), style.fg(theme.accent));
+    }
+    if !app.deletions.is_empty() {
+        item(
+            format!("🚮 {}", app.deletions.len()),
+            style.fg(theme.warning),
+        );
+    }
+    let denied = permissions::all_denied();
+    if !denied.is_empty() {
+        let verbs: Vec<&str> = denied.iter().map(|permission| permission.verb()).collect();
+        item(format!("🔒 can't {}", verbs.join(", ")), muted);
+    }
+    let refused = permissions::all_refused();
+    if !refused.is_empty() {
+        let verbs: Vec<&str> = refused.iter().map(|permission| permission.verb()).collect();
+        item(
+            format!("🔒 refused: {}", verbs.join(", ")),
+            style.fg(theme.warning),
+        );
+    }
+    if !app.show_log_pane && !app.log_entries.is_empty() {
+        item(format!("📝 {}", app.log_entries.len()), muted);
+    }
+    if app.zoomed_pane().is_some() {
+        item("⤢ zoomed".to_string(), style.fg(theme.warning));
+    }
+    if let Some(tenant) = app.tenant_label() {
+        item(format!("⌂ {}", tenant), style.fg(theme.info));
+    }
+    if crate::offline::is_enabled() {
+        item("⚡ offline".to_string(), style.fg(theme.warning));
+    }
+    if let Some(remaining) = rate_limit::remaining() {
+        let seconds = remaining.as_secs_f64().ceil();
+        item(
+            format!("⏳ rate limited — resuming in {}s", seconds),
+            style.fg(theme.warning),
+        );
+    }
+    if app.prefetcher.is_paused() {
+        item("⏸ paused (unfocused)".to_string(), muted);
+    }
+    // Background work is counted last, where a click on it opens the jobs view
+    let tasks = app.background_tasks();
+    let mut indicator = None;
+    if tasks > 0 {
+        let label = format!("⟳ {} {}", tasks, if tasks == 1 { "job" } else { "jobs" });
+        item(label, style.fg(theme.accent));
+        let width = right.last().map_or(0, |span| span.width() as u16);
+        let offset = right.iter().map(|span| span.width() as u16).sum::<u16>() - width;
+        indicator = Some((offset, width));
+    }
+    right.push(Span::styled(" ", style));
+
+    let busy = app.command_in_progress || !app.jobs.running().is_empty();
+    let mut left = vec![Span::styled(" ", style)];
+    if busy {
+        let tick = chrono::Local::now().timestamp_subsec_millis() / 100;
+        left.push(Span::styled(
+            format!("{} ", SPINNER_FRAMES[tick as usize % SPINNER_FRAMES.len()]),
+            style.fg(theme.warning),
+        ));
+    }
+    left.push(Span::styled(app.status_message.clone(), style));
+
+    // The location stays in full; the message gets what is left
+    let right = Line::from(right);
+    let right_width = (right.width() as u16).min(area.width);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(right_width)])
+        .split(area);
+    f.render_widget(Paragraph::new(Line::from(left)).style(style), chunks[0]);
+    f.render_widget(Paragraph::new(right).style(style), chunks[1]);
+    app.jobs_indicator = indicator.map(|(offset, width)| Rect {
+        x: chunks[1].x + offset,
+        y: chunks[1].y,
+        width,
+        height: 1,
+    });
+}
+
+fn log_status_color(status: LogStatus, theme: &Theme) -> ratatui::style::Color {
+    match status {
+        LogStatus::Success => theme.success,
+        LogStatus::Error | LogStatus::Timeout => theme.error,
+        LogStatus::Warning | LogStatus::Retry | LogStatus::Cached => theme.warning,
+    }
+}
+
+// Status icon followed by the command, colored by outcome
+fn log_entry_line<'a>(entry: &LogEntry, theme: &Theme) -> Line<'a> {
+    let color = log_status_color(entry.status, theme);
+    let text_style = match entry.status {
+        LogStatus::Cached => Style::default().fg(color).bg(theme.hint_bg),
+        _ => Style::default().fg(color),
+    };
+
+    Line::from(vec![
+        Span::styled(
+            format!("{} ", entry.status.icon()),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(entry.description(), text_style),
+    ])
+}
+
+fn draw_basket_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let assets = app.basket.assets();
+    let title = format!(" 🧺 Basket [{} assets] ", assets.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if assets.is_empty() {
+        let empty = Paragraph::new("The basket is empty; Space on an asset puts it in")
+            .block(block)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.border_inactive));
+        f.render_widget(empty, area);
+        return;
+    }
+
+    // Keep the selected asset in view
+    let visible = area.height.saturating_sub(3) as usize;
+    let skip = app
+        .basket
+        .selected
+        .saturating_sub(visible.saturating_sub(1));
+    let rows = assets
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, asset)| {
+            let style = if i == app.basket.selected {
+                theme.selected()
+            } else {
+                Style::default().fg(theme.accent)
+            };
+            Row::new(vec![
+                Cell::from(truncate_to_width(&asset.name, text::MAX_COLUMN_WIDTH)),
+                Cell::from(folder_picker::label(asset.folder_path())),
+                Cell::from(asset.size.map(text::format_bytes).unwrap_or_default()),
+            ])
+            .style(style)
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(40),
+            Constraint::Percentage(45),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(Row::new(vec!["Name", "Folder", "Size"]).style(Style::default().fg(theme.accent)))
+    .block(block);
+
+    f.render_widget(table, area);
+}
+
+// The assets staged for deletion, to review before deleting them together
+fn draw_deletions_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let assets = app.deletions.assets();
+    let size: u64 = assets.iter().filter_map(|asset| asset.size).sum();
+    let title = format!(
+        " 🗑 Pending Deletions [{} assets, {}] ",
+        assets.len(),
+        text::format_bytes(size)
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(
+            Style::default()
+                .fg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if assets.is_empty() {
+        let empty = Paragraph::new("Nothing is staged for deletion; X on an asset stages it")
+            .block(block)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.border_inactive));
+        f.render_widget(empty, area);
+        return;
+    }
+
+    // Keep the selected asset in view
+    let visible = area.height.saturating_sub(3) as usize;
+    let skip = app
+        .deletions
+        .selected
+        .saturating_sub(visible.saturating_sub(1));
+    let rows = assets
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, asset)| {
+            let style = if i == app.deletions.selected {
+                theme.selected()
+            } else {
+                Style::default().fg(theme.accent)
+            };
+            // Whether U can upload it again after the delete
+            let downloaded = app
+                .downloaded
+                .get(&asset.uuid)
+                .is_some_and(|file| file.is_file());
+            Row::new(vec![
+                Cell::from(truncate_to_width(&asset.name, text::MAX_COLUMN_WIDTH)),
+                Cell::from(folder_picker::label(asset.folder_path())),
+                Cell::from(asset.size.map(text::format_bytes).unwrap_or_default()),
+                Cell::from(if downloaded { "downloaded" } else { "" }),
+            ])
+            .style(style)
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(35),
+            Constraint::Percentage(40),
+            Constraint::Percentage(10),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(
+        Row::new(vec!["Name", "Folder", "Size", "Local copy"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .block(block);
+
+    f.render_widget(table, area);
+}
+
+// The batch jobs with their progress, then the prefetches running or waiting for their turn
+fn draw_jobs_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let prefetches = app.prefetcher.active();
+    let title = format!(" ⟳ Background Jobs [{}] ", app.background_tasks());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if app.jobs.running().is_empty() && prefetches.is_empty() {
+        let empty = Paragraph::new("Nothing is running in the background")
+            .block(block)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.border_inactive));
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let mut lines = Vec::new();
+    for job in app.jobs.running() {
+        lines.push(Line::from(vec![
+            Span::styled("⚙ ", Style::default().fg(theme.accent)),
+            Span::styled(job.title.clone(), Style::default().fg(theme.text)),
+            Span::styled(
+                format!(
+                    "  {} of {} · {}s",
+                    job.done,
+                    job.total,
+                    job.started.elapsed().as_secs()
+                ),
+                muted,
+            ),
+        ]));
+        if !job.current.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("    {}", job.current),
+                muted,
+            )));
+        }
+    }
+    if !prefetches.is_empty() {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        let paused = if app.prefetcher.is_paused() {
+            ", paused (unfocused)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(Span::styled(
+            format!("Prefetching ({}{})", prefetches.len(), paused),
+            Style::default().fg(theme.accent),
+        )));
+        for prefetch in prefetches {
+            lines.push(Line::from(Span::styled(format!("⟳ {}", prefetch), muted)));
+        }
+    }
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_command_history_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let title = format!(
+        " 📋 Command History [{}/{}] ",
+        app.command_history
+            .len()
+            .saturating_sub(app.command_history_index),
+        app.command_history.len()
+    );
+
+    // Reserve a line at the bottom for the command being edited
+    let (list_area, edit_area) = if app.command_edit_buffer.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    // Show most recent first, keeping the selected entry in view
+    let visible = list_area.height.saturating_sub(2) as usize;
+    let selected_row = app
+        .command_history
+        .len()
+        .saturating_sub(1)
+        .saturating_sub(app.command_history_index);
+    let skip = selected_row.saturating_sub(visible.saturating_sub(1));
+
+    let commands: Vec<ratatui::text::Line> = app
+        .command_history
+        .iter()
+        .enumerate()
+        .rev() // Show most recent first
+        .skip(skip)
+        .take(visible)
+        .map(|(i, entry)| {
+            // Status and duration in a column of their own, e.g. "✓ 1.8s"
+            let outcome = format!("{:<9}", entry.outcome());
+            let color = entry
+                .status
+                .map_or(theme.text_muted, |status| log_status_color(status, theme));
+            if i == app.command_history_index {
+                Line::from(vec![
+                    Span::styled("▶ ", theme.highlighted().fg(theme.accent)),
+                    Span::styled(outcome, theme.highlighted().fg(color)),
+                    Span::styled(
+                        entry.command.to_string(),
+                        theme.highlighted().add_modifier(Modifier::BOLD),
+                    ),
+                ])
+            } else {
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(outcome, Style::default().fg(color)),
+                    Span::raw(entry.command.to_string()),
+                ])
+            }
+        })
+        .collect();
+
+    let list = ratatui::widgets::List::new(commands)
+        .block(
+            ratatui::widgets::Block::default()
+                .borders(ratatui::widgets::Borders::ALL)
+                .title(title)
+                .border_style(
+                    Style::default()
+                        .fg(theme.history)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(Style::default().bg(theme.history).fg(theme.selection_fg));
+
+    f.render_widget(list, list_area);
+
+    if let (Some(edit_area), Some(buffer)) = (edit_area, app.command_edit_buffer.as_ref()) {
+        let input = Paragraph::new(Line::from(buffer.spans("▏")))
+            .style(Style::default().fg(theme.text).bg(theme.input_bg))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Edit command (enter:run | esc:cancel) ")
+                    .border_style(theme.border(true)),
+            );
+        f.render_widget(input, edit_area);
+    }
+}
+
+fn draw_raw_output_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let Some(ref result) = app.raw_result else {
+        return;
+    };
+
+    let label = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let exit_code = result
+        .exit_code
+        .map_or_else(|| "n/a".to_string(), |code| code.to_string());
+    let exit_color = if result.exit_code == Some(0) {
+        theme.success
+    } else {
+        theme.error
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Command:   ", label),
+            Span::raw(result.command_line.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Exit code: ", label),
+            Span::styled(exit_code, Style::default().fg(exit_color)),
+        ]),
+    ];
+
+    if let Some(ref message) = result.message {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(theme.error),
+        )));
+    }
+
+    // Pretty-print JSON output; anything else is shown as it came
+    let stdout = match serde_json::from_str::<serde_json::Value>(&result.stdout) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| result.stdout.clone()),
+        Err(_) => result.stdout.clone(),
+    };
+
+    for (title, output, color) in [
+        ("stdout", stdout.as_str(), theme.text),
+        ("stderr", result.stderr.as_str(), theme.error),
+    ] {
+        if output.trim().is_empty() {
+            continue;
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("--- {} ---", title),
+            label,
+        )));
+        lines.extend(
+            output
+                .trim_end()
+                .lines()
+                .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(color)))),
+        );
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" ⌨ Command Output ")
+                .border_style(theme.border(true)),
+        )
+        .style(Style::default().fg(theme.text).bg(theme.panel_bg))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .scroll((app.raw_output_scroll, 0));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_json_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let Some(ref viewer) = app.json_viewer else {
+        return;
+    };
+
+    let lines = viewer.lines();
+    let mut title = format!(
+        " {{}} {} [{}/{}] ",
+        viewer.title,
+        viewer.selected + 1,
+        lines.len()
+    );
+    if viewer.search_editing {
+        title.push_str(&format!("search: /{}▏ ", viewer.search));
+    } else if !viewer.search.is_empty() {
+        title.push_str(&format!("search: \"{}\" ", viewer.search));
+    }
+    if !viewer.is_json() {
+        title.push_str("(not JSON) ");
+    }
+
+    // Keep the selected line in view
+    let height = area.height.saturating_sub(2) as usize;
+    let start = viewer.selected.saturating_sub(height.saturating_sub(1) / 2);
+    let start = start.min(lines.len().saturating_sub(height));
+
+    let items: Vec<Line> = lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(i, line)| {
+            let mut spans = vec![Span::raw("  ".repeat(line.depth))];
+            spans.extend(line.tokens.iter().map(|token| {
+                let style = match token {
+                    JsonToken::Key(_) => Style::default().fg(theme.accent),
+                    JsonToken::String(_) => Style::default().fg(theme.success),
+                    JsonToken::Number(_) => Style::default().fg(theme.info),
+                    JsonToken::Literal(_) => Style::default().fg(theme.warning),
+                    JsonToken::Punct(_) => Style::default().fg(theme.text_muted),
+                    JsonToken::Summary(_) => Style::default()
+                        .fg(theme.text_muted)
+                        .add_modifier(Modifier::ITALIC),
+                    JsonToken::Plain(_) => Style::default().fg(theme.text),
+                };
+                Span::styled(token.text().to_string(), style)
+            }));
+
+            let line = Line::from(spans);
+            if i == viewer.selected {
+                line.style(theme.highlighted())
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(theme.border(true)),
+        )
+        .style(Style::default().fg(theme.text).bg(theme.panel_bg));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_log_view(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    if app.show_notification_history {
+        draw_notification_history(f, area, app, theme);
+        return;
+    }
+
+    // Only entries passing the filters are listed; the selection is an index into log_entries
+    let visible = app.visible_log_indices();
+    let selected = visible
+        .iter()
+        .position(|&i| i == app.log_scroll_position)
+        .unwrap_or(visible.len().saturating_sub(1));
+
+    // Show the active filters in the title
+    let mut filters = Vec::new();
+    if app.log_filter != LogFilter::All {
+        filters.push(app.log_filter.label().to_string());
+    }
+    if app.log_filter_editing {
+        filters.push(format!("/{}▏", app.log_filter_text));
+    } else if !app.log_filter_text.is_empty() {
+        filters.push(format!("\"{}\"", app.log_filter_text));
+    }
+    let title = if filters.is_empty() {
+        format!(" 📝 Log [{}/{}] ", selected + 1, app.log_entries.len())
+    } else {
+        format!(
+            " 📝 Log [{}/{} of {}] filter: {} ",
+            (selected + 1).min(visible.len()),
+            visible.len(),
+            app.log_entries.len(),
+            filters.join(" ")
+        )
+    };
+
+    // Show a portion of the visible entries based on scroll position
+    let start_idx = selected.saturating_sub(10); // Show 10 entries before current position
+    let end_idx = std::cmp::min(start_idx + 20, visible.len()); // Show 20 entries total
+
+    // Create list items with highlighting for the selected item
+    let list_items: Vec<ratatui::widgets::ListItem> = visible[start_idx..end_idx]
+        .iter()
+        .map(|&i| {
+            let entry = &app.log_entries[i];
+            let timestamp = format!("[{}] ", entry.timestamp.format("%H:%M:%S"));
+            // Check if this item corresponds to the current scroll position
+            let is_selected = i == app.log_scroll_position;
+
+            if is_selected {
+                // Style for selected item - use a more prominent highlight
+                ratatui::widgets::ListItem::new(ratatui::text::Line::from(vec![
+                    ratatui::text::Span::styled(
+                        "▶ ",
+                        theme
+                            .highlighted()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    ratatui::text::Span::styled(
+                        format!("{}{}", timestamp, entry.summary_without_timestamp()),
+                        theme.highlighted().add_modifier(Modifier::BOLD),
+                    ),
+                ]))
+            } else {
+                // Style for non-selected items, colored by outcome
+                let mut line = log_entry_line(entry, theme);
+                line.spans.insert(
+                    0,
+                    Span::styled(timestamp, Style::default().fg(theme.text_muted)),
+                );
+                ratatui::widgets::ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = ratatui::widgets::List::new(list_items)
+        .block(
+            ratatui::widgets::Block::default()
+                .borders(ratatui::widgets::Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.info).add_modifier(Modifier::BOLD)),
+        )
+        .style(Style::default().bg(theme.panel_bg).fg(theme.text));
+
+    f.render_widget(list, area);
+}
+
+fn draw_notification_history(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let history = app.notifications.history();
+    let title = format!(" 🔔 Notifications [{}] ", history.len());
+
+    // Show the most recent notifications that fit, newest at the bottom
+    let visible = area.height.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = history
+        .iter()
+        .skip(history.len().saturating_sub(visible))
+        .map(|notification| {
+            let color = level_color(notification.level, theme);
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", notification.timestamp.format("%H:%M:%S")),
+                    Style::default().fg(theme.text_muted),
+                ),
+                Span::styled(
+                    format!("{} ", notification.level.icon()),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(notification.message.as_str(), Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.info).add_modifier(Modifier::BOLD)),
+        )
+        .style(Style::default().bg(theme.panel_bg).fg(theme.text));
+
+    f.render_widget(list, area);
+}
+
+fn draw_log_detail_modal(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let Some(entry) = app.log_detail_index.and_then(|i| app.log_entries.get(i)) else {
+        return;
+    };
+
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" 📝 Log Entry ")
+        .title_bottom(" ↑↓:scroll | c:copy command | e:error output | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let label = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let status_color = log_status_color(entry.status, theme);
+    let duration = entry
+        .duration
+        .map_or_else(|| "-".to_string(), |d| format!("{:.2}s", d.as_secs_f64()));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Time:     ", label),
+            Span::raw(entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("Status:   ", label),
+            Span::styled(
+                format!("{} {}", entry.status.icon(), entry.status.label()),
+                Style::default()
+                    .fg(status_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![Span::styled("Duration: ", label), Span::raw(duration)]),
+        Line::from(vec![
+            Span::styled("Exit:     ", label),
+            Span::raw(
+                entry
+                    .exit_code
+                    .map_or_else(|| "-".to_string(), |code| code.to_string()),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("Command:", label)),
+        Line::from(entry.command.clone()),
+    ];
+
+    if let Some(ref message) = entry.message {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Message:", label)));
+        lines.push(Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(status_color),
+        )));
+    }
+
+    if entry.error.is_some() {
+        let output = if entry.status == LogStatus::Success {
+            "Press 'e' to see the full output"
+        } else {
+            "Press 'e' to see the full error output"
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            output,
+            Style::default().fg(theme.text_muted),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.text))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .scroll((app.log_detail_scroll, 0));
+    f.render_widget(paragraph, chunks[0]);
+
+    // Copy button, centered on the last row; its area is remembered for mouse clicks
+    let label_text = "[ 📋 Copy command ]";
+    let button_width = (text::display_width(label_text) as u16).min(chunks[1].width);
+    let button_area = Rect {
+        x: chunks[1].x + (chunks[1].width - button_width) / 2,
+        y: chunks[1].y,
+        width: button_width,
+        height: 1,
+    };
+    f.render_widget(
+        Paragraph::new(label_text).style(theme.selected().add_modifier(Modifier::BOLD)),
+        button_area,
+    );
+    app.log_detail_copy_button = Some(button_area);
+}
+
+fn draw_error_modal(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let Some(detail) = app.error_modal.as_ref() else {
+        return;
+    };
+
+    let popup_area = centered_rect(80, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let label = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let exit_code = detail
+        .exit_code
+        .map_or_else(|| "n/a".to_string(), |code| code.to_string());
+    // Output of a command that succeeded (e.g. a custom command) is shown the same way
+    let (title, color) = if detail.exit_code == Some(0) {
+        (" ✓ Command Output ", theme.success)
+    } else {
+        (" ✗ Error Details ", theme.error)
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Time:      ", label),
+            Span::raw(detail.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled("Command:   ", label),
+            Span::raw(detail.command_line.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Exit code: ", label),
+            Span::raw(exit_code),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            detail.message.clone(),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    for (title, output) in [("stderr", &detail.stderr), ("stdout", &detail.stdout)] {
+        if output.trim().is_empty() {
+            continue;
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("--- {} ---", title),
+            label,
+        )));
+        lines.extend(
+            output
+                .trim_end()
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        );
+    }
+
+    // Keep the scroll offset within the wrapped content so scrolling back up responds immediately
+    let inner_width = popup_area.width.saturating_sub(2).max(1) as usize;
+    let inner_height = popup_area.height.saturating_sub(2) as usize;
+    let wrapped_height: usize = lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(inner_width))
+        .sum();
+    let max_scroll = wrapped_height.saturating_sub(inner_height) as u16;
+    app.error_modal_scroll = app.error_modal_scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(" ↑↓/PgUp/PgDn:scroll | c:copy | q/esc:close ")
+                .border_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+                .style(Style::default().bg(theme.modal_bg)),
+        )
+        .style(Style::default().fg(theme.text))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .scroll((app.error_modal_scroll, 0));
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_custom_command_menu(f: &mut Frame, area: Rect, app: &App, selected: usize, theme: &Theme) {
+    let commands = &app.config.custom_commands;
+    let popup_area = centered_rect(50, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = commands
+        .iter()
+        .enumerate()
+        .map(|(i, command)| {
+            let key = command
+                .key
+                .map_or_else(|| "   ".to_string(), |key| format!("[{}]", key));
+            let line = Line::from(vec![
+                Span::styled(format!("{} ", key), Style::default().fg(theme.accent)),
+                Span::raw(command.name.clone()),
+                Span::styled(
+                    format!("  {}", command.command),
+                    Style::default().fg(theme.text_muted),
+                ),
+            ]);
+            if i == selected {
+                ListItem::new(line).style(theme.highlighted())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" ⚙ Custom Commands ")
+            .title_bottom(" ↑↓:nav | enter:run | q/esc:close ")
+            .border_style(theme.border(true))
+            .style(Style::default().bg(theme.modal_bg).fg(theme.text)),
+    );
+    f.render_widget(list, popup_area);
+}
+
+fn draw_sync_plan_modal(f: &mut Frame, area: Rect, plan: &SyncPlan, theme: &Theme) {
+    let popup_area = centered_rect(80, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " ⇅ Sync {} ↔ {} ",
+            plan.folder,
+            plan.directory.display()
+        ))
+        .title_bottom(" ↑↓:nav | space:include/exclude | enter:run | q/esc:cancel ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+
+    let up_to_date = plan
+        .entries
+        .iter()
+        .filter(|entry| entry.action == SyncAction::Skip)
+        .count();
+    let mut summary = vec![Span::styled(
+        format!(
+            "↑ {} to upload · ↓ {} to download · {} unchanged",
+            plan.count(SyncAction::Upload),
+            plan.count(SyncAction::Download),
+            up_to_date
+        ),
+        Style::default().fg(theme.accent),
+    )];
+    if plan.conflicts() > 0 {
+        summary.push(Span::styled(
+            format!(
+                " · ! {} left alone, names shared by several assets",
+                plan.conflicts()
+            ),
+            Style::default().fg(theme.warning),
+        ));
+    }
+    f.render_widget(Paragraph::new(Line::from(summary)), chunks[0]);
+
+    if plan.entries.is_empty() {
+        f.render_widget(
+            Paragraph::new("The folder and the directory are both empty")
+                .style(Style::default().fg(theme.text_muted)),
+            chunks[1],
+        );
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let items: Vec<ListItem> = plan
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mark = match (entry.action.transfers(), entry.included) {
+                (false, _) => "   ",
+                (true, true) => "[x]",
+                (true, false) => "[ ]",
+            };
+            let style = match entry.action {
+                SyncAction::Conflict => Style::default().fg(theme.warning),
+                SyncAction::Skip => muted,
+                _ if !entry.included => muted,
+                _ => Style::default(),
+            };
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{} {} {}", mark, entry.action.icon(), entry.name),
+                    style,
+                ),
+                Span::styled(format!("  {}", entry.reason), muted),
+            ]);
+            if i == plan.selected {
+                ListItem::new(line).style(theme.highlighted())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    state.select(Some(plan.selected));
+    f.render_stateful_widget(List::new(items), chunks[1], &mut state);
+}
+
+fn draw_folder_diff_modal(f: &mut Frame, area: Rect, diff: &FolderDiff, theme: &Theme) {
+    let popup_area = centered_rect(90, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let hints = if diff.matching {
+        " ←→:column | ↑↓:nav | c/C:copy selected/all across | r:refresh | q/esc:close | matching… "
+    } else {
+        " ←→:column | ↑↓:nav | c/C:copy selected/all across | g:match geometry | r:refresh | q/esc:close "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" ⇄ A: {}  ·  B: {} ", diff.a, diff.b))
+        .title_bottom(hints)
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(inner);
+
+    let muted = Style::default().fg(theme.text_muted);
+    let only = |assets: &[Asset]| -> Vec<Line> {
+        assets
+            .iter()
+            .map(|asset| Line::from(asset.name.clone()))
+            .collect()
+    };
+    let both: Vec<Line> = diff
+        .both
+        .iter()
+        .map(|pair| match pair.similarity {
+            Some(similarity) => Line::from(vec![
+                Span::raw(format!("{} ≈ {}", pair.a.name, pair.b.name)),
+                Span::styled(format!("  {:.2}%", similarity), muted),
+            ]),
+            None => Line::from(pair.a.name.clone()),
+        })
+        .collect();
+
+    for (column, title, lines) in [
+        (DiffColumn::OnlyA, "Only in A", only(&diff.only_a)),
+        (DiffColumn::Both, "In both", both),
+        (DiffColumn::OnlyB, "Only in B", only(&diff.only_b)),
+    ] {
+        let focused = diff.column == column;
+        let selected = diff.selected[column.index()];
+        let count = lines.len();
+        let items: Vec<ListItem> = lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                if focused && i == selected {
+                    ListItem::new(line).style(theme.highlighted())
+                } else {
+                    ListItem::new(line)
+                }
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        state.select((count > 0).then_some(selected));
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ({}) ", title, count))
+                .border_style(theme.border(focused)),
+        );
+        f.render_stateful_widget(list, columns[column.index()], &mut state);
+    }
+}
+
+fn draw_metadata_diff_modal(f: &mut Frame, area: Rect, diff: &MetadataDiff, theme: &Theme) {
+    let popup_area = centered_rect(80, 70, area);
+    f.render_widget(Clear, popup_area);
+
+    let hints = if diff.only_differences {
+        " ↑↓:nav | d:show all | q/esc:close "
+    } else {
+        " ↑↓:nav | d:differences only | q/esc:close "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " ⇄ Metadata — {} of {} differ ",
+            diff.differences(),
+            diff.rows.len()
+        ))
+        .title_bottom(hints)
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    let rows = diff.visible_rows();
+    if rows.is_empty() {
+        let text = if diff.rows.is_empty() {
+            "Neither asset has metadata"
+        } else {
+            "The metadata is the same"
+        };
+        let empty = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.text_muted))
+            .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let value = |value: &Option<String>| match value {
+        Some(value) => Cell::from(value.clone()),
+        None => Cell::from("—").style(muted),
+    };
+    let rows = rows.into_iter().map(|row| {
+        let style = if row.differs() {
+            Style::default().fg(theme.warning)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(row.key.clone()),
+            value(&row.a),
+            value(&row.b),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Property".to_string(),
+            format!("A: {}", diff.a),
+            format!("B: {}", diff.b),
+        ])
+        .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(diff.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+fn draw_match_review_modal(f: &mut Frame, area: Rect, review: &Review, theme: &Theme) {
+    let popup_area = centered_rect(85, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " ⚖ Review: match {} of {} · {} decided ",
+            review.current + 1,
+            review.pairs.len(),
+            review.decided()
+        ))
+        .title_bottom(" k:keep | d:duplicate | i:ignore | ←→:prev/next | ↑↓:metadata | b:open comparison | e:export | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let Some(pair) = review.pair() else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(8), Constraint::Min(3)])
+        .split(inner);
+
+    let size = |asset: &Asset| asset.size.map(text::format_bytes).unwrap_or_default();
+    let decision = match pair.decision {
+        Some(decision) => Cell::from(decision.label()).style(Style::default().fg(theme.accent)),
+        None => Cell::from("—").style(Style::default().fg(theme.text_muted)),
+    };
+    let rows = vec![
+        Row::new(vec![
+            Cell::from("Name"),
+            Cell::from(review.source.name.clone()),
+            Cell::from(pair.asset.name.clone()),
+        ]),
+        Row::new(vec![
+            Cell::from("Folder"),
+            Cell::from(folder_picker::label(review.source.folder_path())),
+            Cell::from(folder_picker::label(pair.asset.folder_path())),
+        ]),
+        Row::new(vec![
+            Cell::from("Type"),
+            Cell::from(review.source.file_type.clone()),
+            Cell::from(pair.asset.file_type.clone()),
+        ]),
+        Row::new(vec![
+            Cell::from("Size"),
+            Cell::from(size(&review.source)),
+            Cell::from(size(&pair.asset)),
+        ]),
+        Row::new(vec![
+            Cell::from("Similarity"),
+            Cell::from(""),
+            Cell::from(format!("{:.2}%", pair.similarity))
+                .style(similarity_band(pair.similarity, theme)),
+        ]),
+        Row::new(vec![Cell::from("Decision"), Cell::from(""), decision]),
+    ];
+    let widths = [
+        Constraint::Percentage(20),
+        Constraint::Percentage(40),
+        Constraint::Percentage(40),
+    ];
+    let details = Table::new(rows, widths)
+        .header(
+            Row::new(vec!["", "Matched asset", "Match"]).style(Style::default().fg(theme.accent)),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(theme.border(false)),
+        );
+    f.render_widget(details, chunks[0]);
+
+    let diff = &review.diff;
+    let muted = Style::default().fg(theme.text_muted);
+    let value = |value: &Option<String>| match value {
+        Some(value) => Cell::from(value.clone()),
+        None => Cell::from("—").style(muted),
+    };
+    let rows = diff.visible_rows().into_iter().map(|row| {
+        let style = if row.differs() {
+            Style::default().fg(theme.warning)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(row.key.clone()),
+            value(&row.a),
+            value(&row.b),
+        ])
+        .style(style)
+    });
+    let metadata = Table::new(rows, widths)
+        .header(
+            Row::new(vec![format!("Metadata ({} differ)", diff.differences())])
+                .style(Style::default().fg(theme.accent)),
+        )
+        .highlight_style(theme.highlighted());
+    let mut state = TableState::default().with_selected(Some(diff.selected));
+    f.render_stateful_widget(metadata, chunks[1], &mut state);
+}
+
+fn draw_match_history_modal(f: &mut Frame, area: Rect, history: &MatchHistory, theme: &Theme) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" 🕘 Match History ({}) ", history.sessions.len()))
+        .title_bottom(" ↑↓:nav | enter:reopen | x:delete | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    if history.sessions.is_empty() {
+        let empty = Paragraph::new("No saved matches yet; every geometric match is saved here")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.text_muted))
+            .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = history.sessions.iter().map(|session| {
+        let folder = session
+            .source_folder
+            .as_deref()
+            .map(folder_picker::label)
+            .unwrap_or_default();
+        let review = if session.decisions.is_empty() {
+            Cell::from("")
+        } else {
+            Cell::from(format!(
+                "{} of {} decided",
+                session.decided(),
+                session.decisions.len()
+            ))
+        };
+        Row::new(vec![
+            Cell::from(session.created.format("%Y-%m-%d %H:%M").to_string()).style(muted),
+            Cell::from(truncate_to_width(session.label(), text::MAX_COLUMN_WIDTH)),
+            Cell::from(folder),
+            Cell::from(format!("{} matches", session.results.len())),
+            review,
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(17),
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+            Constraint::Length(12),
+            Constraint::Min(16),
+        ],
+    )
+    .header(
+        Row::new(vec!["Matched", "Asset", "Folder", "Results", "Review"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(history.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// Jobs an earlier session stopped in the middle of, with how far they got
+fn draw_interrupted_jobs_modal(
+    f: &mut Frame,
+    area: Rect,
+    interrupted: &InterruptedJobs,
+    theme: &Theme,
+) {
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" ⏯ Interrupted Jobs ({}) ", interrupted.jobs.len()))
+        .title_bottom(" ↑↓:nav | enter:resume | x:discard | esc:later ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = interrupted.jobs.iter().map(|job| {
+        Row::new(vec![
+            Cell::from(job.started.format("%Y-%m-%d %H:%M").to_string()).style(muted),
+            Cell::from(job.title.clone()),
+            Cell::from(format!("{} of {} done", job.done, job.total)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(17),
+            Constraint::Min(20),
+            Constraint::Length(20),
+        ],
+    )
+    .header(Row::new(vec!["Started", "Job", "Progress"]).style(Style::default().fg(theme.accent)))
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(interrupted.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// The configured profiles to start with, each with its tenant, pcli2 and start folder
+fn draw_profile_picker_modal(
+    f: &mut Frame,
+    area: Rect,
+    profiles: &BTreeMap<String, Profile>,
+    selected: usize,
+    theme: &Theme,
+) {
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" 👤 Profiles ({}) ", profiles.len()))
+        .title_bottom(" ↑↓:nav | enter:use | esc:no profile ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = profiles.iter().map(|(name, profile)| {
+        let folder = profile.default_folder.as_deref().unwrap_or_default();
+        Row::new(vec![
+            Cell::from(name.clone()),
+            Cell::from(profile.tenant.clone().unwrap_or_default()),
+            Cell::from(profile.pcli2.as_deref().unwrap_or("pcli2").to_string()).style(muted),
+            Cell::from(folder_picker::label(folder.trim_matches('/')).to_string()).style(muted),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Min(16),
+        ],
+    )
+    .header(
+        Row::new(vec!["Profile", "Tenant", "pcli2", "Start folder"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// The tenant's property definitions and how many cached assets use each; keys used without a
+// definition are flagged
+fn draw_property_schema_modal(f: &mut Frame, area: Rect, schema: &PropertySchema, theme: &Theme) {
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut title = format!(" 🏷 Metadata Properties ({}) ", schema.rows.len());
+    if !schema.defined {
+        title.push_str("| definitions unknown, usage only ");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title(
+            Line::from(format!(" usage in {} cached assets ", schema.scanned))
+                .alignment(Alignment::Right),
+        )
+        .title_bottom(" ↑↓:nav | n:new | x:delete | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    if schema.rows.is_empty() {
+        let empty =
+            Paragraph::new("No properties defined or used by the cached assets; n creates one")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(theme.text_muted))
+                .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = schema.rows.iter().map(|row| {
+        let property_type = match row.property_type {
+            Some(ref property_type) => Cell::from(property_type.as_str()),
+            None if schema.defined => {
+                Cell::from("not defined").style(Style::default().fg(theme.warning))
+            }
+            None => Cell::from("?").style(muted),
+        };
+        let share = match schema.scanned {
+            0 => String::new(),
+            scanned => format!("{:.0}%", row.assets as f64 * 100.0 / scanned as f64),
+        };
+        let usage = if row.assets == 0 {
+            muted
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(truncate_to_width(&row.name, text::MAX_COLUMN_WIDTH)),
+            property_type,
+            Cell::from(row.assets.to_string()).style(usage),
+            Cell::from(share).style(muted),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(50),
+            Constraint::Min(12),
+            Constraint::Length(8),
+            Constraint::Length(6),
+        ],
+    )
+    .header(
+        Row::new(vec!["Property", "Type", "Assets", "Share"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(schema.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// Every broken metadata rule in a folder and below it, one row per asset and rule
+fn draw_validation_modal(
+    f: &mut Frame,
+    area: Rect,
+    report: &ValidationReport,
+    rules: &Rules,
+    theme: &Theme,
+) {
+    let popup_area = centered_rect(80, 70, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " ⚠ Metadata Rule Violations in {} ({}) ",
+            folder_picker::label(&report.folder),
+            report.rows.len()
+        ))
+        .title(
+            Line::from(format!(
+                " {} of {} cached assets ",
+                report.assets(),
+                report.checked
+            ))
+            .alignment(Alignment::Right),
+        )
+        .title_bottom(" ↑↓:nav | enter:go to asset | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    if report.rows.is_empty() {
+        let empty = Paragraph::new(format!(
+            "The {} cached assets here follow the metadata rules",
+            report.checked
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(theme.success))
+        .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = report.rows.iter().map(|(asset, violation)| {
+        Row::new(vec![
+            Cell::from(truncate_to_width(&asset.name, text::MAX_COLUMN_WIDTH)),
+            Cell::from(folder_picker::label(asset.folder_path())).style(muted),
+            Cell::from(violation.key.as_str()),
+            Cell::from(violation.problem.describe(rules.pattern(&violation.key)))
+                .style(Style::default().fg(theme.error)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(15),
+            Constraint::Min(20),
+        ],
+    )
+    .header(
+        Row::new(vec!["Asset", "Folder", "Property", "Problem"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(report.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// What each row of a CSV metadata import changes, before it is applied
+fn draw_metadata_import_modal(f: &mut Frame, area: Rect, plan: &ImportPlan, theme: &Theme) {
+    let popup_area = centered_rect(85, 70, area);
+    f.render_widget(Clear, popup_area);
+
+    let file = plan.file.file_name().map_or_else(
+        || plan.file.display().to_string(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" 📥 Metadata Import: {} ", file))
+        .title(
+            Line::from(format!(
+                " {} assets · {} values · {} rows skipped ",
+                plan.applicable().count(),
+                plan.changes(),
+                plan.problems()
+            ))
+            .alignment(Alignment::Right),
+        )
+        .title_bottom(" ↑↓:nav | enter/y:apply | q/esc:cancel ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+
+    if plan.rows.is_empty() {
+        let empty = Paragraph::new("The file has a header line but no rows")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.text_muted))
+            .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let rows = plan.rows.iter().map(|row| {
+        let mut changes = Vec::new();
+        for (i, change) in row.changes.iter().enumerate() {
+            if i > 0 {
+                changes.push(Span::styled(" · ", muted));
+            }
+            changes.push(Span::raw(format!("{}: ", change.name)));
+            if let Some(ref old) = change.old {
+                changes.push(Span::styled(format!("{} → ", old), muted));
+            }
+            changes.push(Span::styled(
+                change.new.clone(),
+                Style::default().fg(theme.success),
+            ));
+        }
+        let status = match row.problem {
+            Some(ref problem) => {
+                Cell::from(problem.as_str()).style(Style::default().fg(theme.error))
+            }
+            None if row.changes.is_empty() => Cell::from("unchanged").style(muted),
+            None if row.asset.is_none() => Cell::from("not cached, set as is").style(muted),
+            None => Cell::from(""),
+        };
+        Row::new(vec![
+            Cell::from(row.line.to_string()).style(muted),
+            Cell::from(truncate_to_width(row.label(), text::MAX_COLUMN_WIDTH).into_owned()),
+            Cell::from(Line::from(changes)),
+            status,
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Percentage(30),
+            Constraint::Percentage(45),
+            Constraint::Min(16),
+        ],
+    )
+    .header(
+        Row::new(vec!["Line", "Asset", "Changes", "Status"])
+            .style(Style::default().fg(theme.accent)),
+    )
+    .highlight_style(theme.highlighted())
+    .block(block);
+
+    let mut state = TableState::default().with_selected(Some(plan.selected));
+    f.render_stateful_widget(table, popup_area, &mut state);
+}
+
+// One page of the first-run setup at a time, with the keys of the page at the bottom
+fn draw_setup_modal(f: &mut Frame, area: Rect, setup: &Setup, theme: &Theme) {
+    const SETUP_WIDTH: u16 = 78;
+
+    let muted = Style::default().fg(theme.text_muted);
+    let good = Style::default().fg(theme.success);
+    let bad = Style::default().fg(theme.error);
+    let (page, pages) = setup.page();
+    let (mut lines, keys) = match setup.step {
+        SetupStep::Welcome => (
+            vec![
+                Line::from(
+                    "Welcome to pcli2-tui, a terminal UI for the Physna tenant behind pcli2.",
+                ),
+                Line::from(""),
+                Line::from("This setup checks that pcli2 works, then asks where downloads go and"),
+                Line::from(
+                    "which colors to use. Everything can be changed later in the config file.",
+                ),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Enter checks pcli2; it may take a moment.",
+                    muted,
+                )),
+            ],
+            " enter:check pcli2 | esc:skip setup ",
+        ),
+        SetupStep::Checks => {
+            let mut lines = Vec::new();
+            match setup.pcli2 {
+                Some(ref path) => {
+                    lines.push(Line::from(vec![
+                        Span::styled("✓ ", good),
+                        Span::raw(format!("pcli2 found at {}", path.display())),
+                    ]));
+                    if let Some(ref version) = setup.version {
+                        lines.push(Line::from(Span::styled(format!("  {}", version), muted)));
+                    }
+                }
+                None => {
+                    lines.push(Line::from(vec![
+                        Span::styled("✗ ", bad),
+                        Span::raw("pcli2 is not on PATH"),
+                    ]));
+                    lines.push(Line::from(Span::styled(
+                        "  Install pcli2 and make sure `pcli2 --help` runs in this terminal",
+                        muted,
+                    )));
+                }
+            }
+            match setup.sign_in {
+                Some(Ok(folders)) => lines.push(Line::from(vec![
+                    Span::styled("✓ ", good),
+                    Span::raw(format!("Signed in: {} top-level folders", folders)),
+                ])),
+                Some(Err(ref error)) => {
+                    lines.push(Line::from(vec![
+                        Span::styled("✗ ", bad),
+                        Span::raw("pcli2 could not list the folders of the tenant"),
+                    ]));
+                    lines.push(Line::from(Span::styled(format!("  {}", error), muted)));
+                    lines.push(Line::from(Span::styled(
+                        "  Set up pcli2's credentials (see `pcli2 --help`), then press r",
+                        muted,
+                    )));
+                }
+                None => {}
+            }
+            lines.push(Line::from(""));
+            if setup.checks_passed() {
+                lines.push(Line::from("pcli2 is ready."));
+            } else {
+                lines.push(Line::from(Span::styled(
+                    "The app needs a working pcli2 to show the tenant; you can go on anyway.",
+                    muted,
+                )));
+            }
+            (lines, " enter:next | r:check again | esc:skip setup ")
+        }
+        SetupStep::Downloads => {
+            let mut input = vec![Span::styled("> ", Style::default().fg(theme.accent))];
+            input.extend(setup.directory.spans("▏"));
+            (
+                vec![
+                    Line::from("Where should downloaded assets go?"),
+                    Line::from(""),
+                    Line::from(input),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "~ is your home directory; it is created on the first download.",
+                        muted,
+                    )),
+                ],
+                " enter:next | esc:skip setup ",
+            )
+        }
+        SetupStep::Theme => {
+            let mut lines = vec![
+                Line::from("Pick the colors; the screen behind shows them as you go."),
+                Line::from(""),
+            ];
+            for (i, name) in setup.themes.iter().enumerate() {
+                if i == setup.theme {
+                    lines.push(Line::from(Span::styled(
+                        format!("▶ {}", name),
+                        theme.selected(),
+                    )));
+                } else {
+                    lines.push(Line::from(format!("  {}", name)));
+                }
+            }
+            (lines, " ↑↓:choose | enter:save | esc:skip setup ")
+        }
+        SetupStep::Done => (
+            vec![
+                Line::from(Span::styled("✓ All set.", good)),
+                Line::from(""),
+                Line::from("New here? The tour points out the parts of the screen and the keys"),
+                Line::from("that matter most; it takes a minute."),
+            ],
+            " enter/t:take the tour | esc/q:start browsing ",
+        ),
+    };
+    lines.insert(0, Line::from(""));
+
+    // As tall as the page, with a blank line below it
+    let width = SETUP_WIDTH.min(area.width);
+    let height = (lines.len() as u16 + 3).min(area.height);
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" 🚀 Setup {}/{} ", page, pages))
+        .title_bottom(keys)
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let body = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(body, popup_area);
+}
+
+// The part of the screen the current stop points at, outlined, with a callout next to it
+fn draw_tour(f: &mut Frame, area: Rect, areas: ScreenAreas, tour: &Tour, theme: &Theme) {
+    const CALLOUT_WIDTH: u16 = 56;
+    const CALLOUT_HEIGHT: u16 = 7;
+
+    let stop = tour.current();
+    let target = match stop.target {
+        TourTarget::Folders => areas.folders,
+        TourTarget::Assets => areas.assets,
+        TourTarget::Log => areas.log,
+        TourTarget::StatusLine => areas.status,
+        TourTarget::Hints => areas.hints,
+        TourTarget::Screen => None,
+    };
+
+    // Outline the target without hiding what is in it
+    let highlight = Style::default()
+        .fg(theme.warning)
+        .add_modifier(Modifier::BOLD);
+    if let Some(target) = target {
+        if target.height >= 3 {
+            let outline = Block::default()
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Thick)
+                .border_style(highlight);
+            f.render_widget(outline, target);
+        } else {
+            f.buffer_mut().set_style(target, theme.highlighted());
+        }
+    }
+
+    // The callout goes inside a pane, or above a line at the bottom
+    let width = CALLOUT_WIDTH.min(area.width);
+    let height = CALLOUT_HEIGHT.min(area.height);
+    let callout = match target {
+        Some(target) if target.height >= CALLOUT_HEIGHT + 2 => Rect {
+            x: target.x + target.width.saturating_sub(width) / 2,
+            y: target.y + target.height.saturating_sub(height + 1),
+            width: width.min(target.width),
+            height,
+        },
+        Some(target) => Rect {
+            x: target.x + target.width.saturating_sub(width) / 2,
+            y: target.y.saturating_sub(height),
+            width: width.min(target.width),
+            height,
+        },
+        None => Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        },
+    };
+
+    let mut text = vec![Line::from(stop.text)];
+    if target.is_none() && stop.target != TourTarget::Screen {
+        text.push(Line::from(Span::styled(
+            "(hidden right now)",
+            Style::default().fg(theme.text_muted),
+        )));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " 🧭 {} · {}/{} ",
+            stop.title,
+            tour.stop + 1,
+            crate::tour::STOPS.len()
+        ))
+        .title_bottom(" enter/→:next | ←:back | esc:end tour ")
+        .border_style(highlight)
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    f.render_widget(Clear, callout);
+    f.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+        callout,
+    );
+}
+
+fn draw_assembly_download_prompt(f: &mut Frame, area: Rect, asset: &Asset, theme: &Theme) {
+    let popup_area = centered_rect(50, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(format!("{} is an assembly.", asset.name)),
+        Line::from(""),
+        Line::from("Also download every part it references, with a manifest?"),
+    ];
+    let prompt = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" ⬇ Download Assembly ")
+                .title_bottom(" y/enter:with dependencies | n:assembly only | esc:cancel ")
+                .border_style(theme.border(true))
+                .style(Style::default().bg(theme.modal_bg).fg(theme.text)),
+        );
+    f.render_widget(prompt, popup_area);
+}
+
+fn draw_finder_modal(f: &mut Frame, area: Rect, app: &App, finder: &Finder, theme: &Theme) {
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let cached: usize = app
+        .folder_cache
+        .values()
+        .map(|cache| cache.assets.len())
+        .sum();
+    let mut title = format!(" 🔎 Find ({} cached assets) ", cached);
+    if finder.searching {
+        title.push_str("· searching the server… ");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(" enter:go to | ctrl+r:search server | ↑↓:nav | esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+    let mut spans = vec![Span::styled("› ", Style::default().fg(theme.accent))];
+    spans.extend(finder.input.spans("█"));
+    let input = Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.input_bg));
+    f.render_widget(input, chunks[0]);
 
-    let paragraph = Paragraph::new(help_text)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" 💡 Help ")  // Changed title with padding spaces and emoji
-                .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD))  // Gold border
-                .padding(ratatui::widgets::Padding::uniform(1))  // Add 1 space padding on all sides
-                .style(Style::default().bg(Color::Rgb(40, 40, 50))),  // Dark blue-gray background
-        )
-        .style(Style::default().fg(Color::Rgb(220, 220, 220)))  // Light gray text for better readability
-        .wrap(ratatui::widgets::Wrap { trim: true });
+    if finder.matches.is_empty() {
+        let empty = Paragraph::new("No matching assets; ctrl+r searches the server")
+            .style(Style::default().fg(theme.text_muted));
+        f.render_widget(empty, chunks[2]);
+        return;
+    }
 
-    f.render_widget(paragraph, popup_area);
+    // Matched characters stand out; the folder follows in muted text
+    let highlight = Style::default()
+        .fg(theme.warning)
+        .add_modifier(Modifier::BOLD);
+    let items: Vec<ListItem> = finder
+        .matches
+        .iter()
+        .map(|m| {
+            let mut spans = vec![Span::raw(if m.remote { "☁ " } else { "  " })];
+            for (i, c) in m.asset.name.chars().enumerate() {
+                if m.positions.contains(&i) {
+                    spans.push(Span::styled(c.to_string(), highlight));
+                } else {
+                    spans.push(Span::raw(c.to_string()));
+                }
+            }
+            let folder = m
+                .asset
+                .path
+                .rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or_default();
+            spans.push(Span::styled(
+                format!("  {}", folder),
+                Style::default().fg(theme.text_muted),
+            ));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(theme.selected());
+    let mut state = ListState::default().with_selected(Some(finder.selected));
+    f.render_stateful_widget(list, chunks[2], &mut state);
 }
 
-// Helper function to create a centered rect
-
-fn draw_contextual_key_bindings(f: &mut Frame, app: &App, area: Rect) {
-    // Define key bindings based on current state
-    let key_bindings_text = match app.current_state {
-        crate::app::AppState::Folders => {
-            "tab:switch | j/k:nav | enter:sel | /:search | h:help | q:quit"
-        }
-        crate::app::AppState::Assets => {
-            "tab:switch | j/k:nav | enter:sel | g:geom-match | /:search | h:help | q:quit"
-        }
-        crate::app::AppState::Search => {
-            "enter:search | esc:cancel | ↑↓:nav | d:download | q:quit"
-        }
-        crate::app::AppState::Uploading | crate::app::AppState::Downloading => "q:quit",
-        crate::app::AppState::Help => "q/esc:close",
-        crate::app::AppState::CommandHistory => "q/esc:close",
-        crate::app::AppState::Log => "↑↓:scroll | q:quit",
-        crate::app::AppState::PaneResize => "↑↓←→:resize | enter:ok | esc/q:cancel",
-    };
+fn draw_folder_picker<A>(f: &mut Frame, area: Rect, picker: &FolderPicker<A>, theme: &Theme) {
+    let popup_area = centered_rect(50, 50, area);
+    f.render_widget(Clear, popup_area);
 
-    let key_bindings_paragraph = Paragraph::new(ratatui::text::Line::from(key_bindings_text))
-        .style(
-            ratatui::style::Style::default()
-                .fg(ratatui::style::Color::Rgb(220, 220, 220))  // Light gray text
-                .bg(ratatui::style::Color::Rgb(60, 60, 60)),   // Darker background
-        );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(picker.title.clone())
+        .title_bottom(" enter:move here | ↑↓:nav | esc:cancel ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
 
-    f.render_widget(key_bindings_paragraph, area);
-}
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
+    let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
         ])
-        .split(r);
+        .split(inner);
+    let mut spans = vec![Span::styled("› ", Style::default().fg(theme.accent))];
+    spans.extend(picker.input.spans("█"));
+    let input = Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.input_bg));
+    f.render_widget(input, chunks[0]);
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+    // Only folders seen so far are listed; a typed path is taken as it is
+    if picker.matches.is_empty() {
+        let empty = Paragraph::new("No folder seen so far matches; enter moves to the typed path")
+            .style(Style::default().fg(theme.text_muted));
+        f.render_widget(empty, chunks[2]);
+        return;
+    }
+    let items: Vec<ListItem> = picker
+        .matches
+        .iter()
+        .map(|folder| ListItem::new(format!("📂 {}", folder_picker::label(folder))))
+        .collect();
+    let list = List::new(items).highlight_style(theme.selected());
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list, chunks[2], &mut state);
 }
 
-fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
-    // Show a portion of the log entries based on scroll position
-    let start_idx = if app.log_entries.len() < 7 {
-        // If we have fewer than 7 entries, show from the beginning
-        0
-    } else {
-        // Otherwise, use the scroll position logic
-        app.log_scroll_position.saturating_sub(3) // Show 3 entries before current position to allow for 7 total
+fn draw_tag_picker(f: &mut Frame, area: Rect, picker: &TagPicker, theme: &Theme) {
+    let popup_area = centered_rect(40, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let (title, hints) = match picker.mode {
+        TagPickerMode::Edit(ref asset) => (
+            format!(" Tags of {} ", asset.name),
+            " space:check | enter:new tag/done | ↑↓:nav | esc:cancel ",
+        ),
+        TagPickerMode::Filter => (
+            " List Assets Tagged ".to_string(),
+            " enter:filter | ↑↓:nav | esc:cancel ",
+        ),
     };
-    let end_idx = std::cmp::min(start_idx + 7, app.log_entries.len()); // Show up to 7 entries to fill the pane
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(hints)
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
 
-    let log_lines: Vec<ratatui::text::Line> = app
-        .log_entries
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+    let mut spans = vec![Span::styled("› ", Style::default().fg(theme.accent))];
+    spans.extend(picker.input.spans("█"));
+    let input = Paragraph::new(Line::from(spans)).style(Style::default().bg(theme.input_bg));
+    f.render_widget(input, chunks[0]);
+
+    if picker.matches.is_empty() {
+        let text = match picker.new_tag() {
+            Some(tag) if matches!(picker.mode, TagPickerMode::Edit(_)) => {
+                format!("enter creates the tag {}", tag)
+            }
+            _ => "No tag matches".to_string(),
+        };
+        let empty = Paragraph::new(text).style(Style::default().fg(theme.text_muted));
+        f.render_widget(empty, chunks[2]);
+        return;
+    }
+    let items: Vec<ListItem> = picker
+        .matches
         .iter()
-        .skip(start_idx)
-        .take(end_idx - start_idx)
-        .map(|entry| {
-            // Check if the entry contains success or error indicators
-            if entry.contains("✓ SUCCESS:") {
-                // Success entry - green color
-                let parts: Vec<&str> = entry.splitn(2, "✓ SUCCESS:").collect();
-                if parts.len() == 2 {
-                    ratatui::text::Line::from(vec![
-                        ratatui::text::Span::styled(
-                            "✓ ",
-                            ratatui::style::Style::default()
-                                .fg(ratatui::style::Color::Green)
-                                .add_modifier(ratatui::style::Modifier::BOLD),
-                        ),
-                        ratatui::text::Span::styled(
-                            parts[1].trim_start(),
-                            ratatui::style::Style::default().fg(ratatui::style::Color::Green),
-                        ),
-                    ])
-                } else {
-                    ratatui::text::Line::from(entry.as_str())
-                }
-            } else if entry.contains("✗ ERROR:") {
-                // Error entry - red color
-                let parts: Vec<&str> = entry.splitn(2, "✗ ERROR:").collect();
-                if parts.len() == 2 {
-                    ratatui::text::Line::from(vec![
-                        ratatui::text::Span::styled(
-                            "✗ ",
-                            ratatui::style::Style::default()
-                                .fg(ratatui::style::Color::Red)
-                                .add_modifier(ratatui::style::Modifier::BOLD),
-                        ),
-                        ratatui::text::Span::styled(
-                            parts[1].trim_start(),
-                            ratatui::style::Style::default().fg(ratatui::style::Color::Red),
-                        ),
-                    ])
-                } else {
-                    ratatui::text::Line::from(entry.as_str())
-                }
-            } else if entry.contains("✓ CACHED:") {
-                // Cached entry - yellow color with cache icon
-                let parts: Vec<&str> = entry.splitn(2, "✓ CACHED:").collect();
-                if parts.len() == 2 {
-                    ratatui::text::Line::from(vec![
-                        ratatui::text::Span::styled(
-                            "🗂️ ", // Cache icon
-                            ratatui::style::Style::default()
-                                .fg(ratatui::style::Color::Yellow)
-                                .add_modifier(ratatui::style::Modifier::BOLD),
-                        ),
-                        ratatui::text::Span::styled(
-                            parts[1].trim_start(),
-                            ratatui::style::Style::default()
-                                .fg(ratatui::style::Color::Yellow)
-                                .bg(ratatui::style::Color::DarkGray),
-                        ),
-                    ])
-                } else {
-                    ratatui::text::Line::from(entry.as_str())
+        .map(|tag| {
+            let chip = tag_chip(tag, theme);
+            match picker.mode {
+                TagPickerMode::Edit(_) => {
+                    let mark = if picker.checked.contains(tag) {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    };
+                    ListItem::new(Line::from(vec![Span::raw(mark), chip]))
                 }
-            } else {
-                // Regular entry - white color
-                ratatui::text::Line::from(entry.as_str())
+                TagPickerMode::Filter => ListItem::new(Line::from(chip)),
             }
         })
         .collect();
+    let list = List::new(items).highlight_style(theme.selected());
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list, chunks[2], &mut state);
+}
 
-    // If no log entries, show status information
-    let list_items = if log_lines.is_empty() {
-        vec![
-            ratatui::text::Line::from(format!(
-                "Status: {} | Path: {}",
-                app.status_message,
-                app.current_folder.as_deref().unwrap_or("/")
-            )),
-            ratatui::text::Line::from(format!("Last Cmd: {}", app.last_executed_command)),
-            ratatui::text::Line::from(match app.current_state {
-                AppState::Folders => {
-                    "Folders View (j/k: nav, Enter: open, a: assets, /: search, h: help, c: cmd history, l: log, Tab/Shift+Tab: switch pane, F10: menu, Backspace: back, q: quit)"
-                }
-                AppState::Assets => {
-                    "Assets View (j/k: nav, d: download, h: help, c: cmd history, l: log, Tab/Shift+Tab: switch pane, F10: menu, Backspace: back, q: quit)"
-                }
-                AppState::Search => {
-                    "Search Mode (type and Enter: search, h: help, c: cmd history, l: log, Tab/Shift+Tab: switch pane, F10: menu, Esc: cancel, q: quit)"
-                }
-                AppState::Uploading => {
-                    "Upload Mode (u: upload, h: help, c: cmd history, l: log, q: quit)"
-                }
-                AppState::Downloading => {
-                    "Download Mode (select and d: download, h: help, c: cmd history, l: log, q: quit)"
-                }
-                AppState::Help => "Help Screen (q/Esc: close help)",
-                AppState::CommandHistory => "Command History (q/Esc: close)",
-                AppState::Log => "Log View (Arrow keys: scroll, q/Esc: close)",
-                AppState::PaneResize => {
-                    "Pane Resize Mode (↑↓←→: resize, Enter: apply, Esc/q: cancel)"
-                }
-            }),
-            ratatui::text::Line::from(match app.current_state {
-                AppState::Log => "↑/↓: scroll | q/Esc: exit | F10: menu | Ctrl+N: resize",
-                AppState::Search => "Enter: search | Esc: cancel | F10: menu | Ctrl+N: resize",
-                AppState::PaneResize => {
-                    "↑/↓/←/→: resize | Enter: apply | Esc/q: cancel | F10: exit"
-                }
-                _ => {
-                    // Default help for main browsing modes
-                    "Tab: switch panes | Shift+Tab: reverse switch | F10: menu | j/k: nav | Enter: select | h: help | Ctrl+N: resize | q: quit"
-                }
-            }),
-        ]
-    } else {
-        log_lines
-    };
-
-    // Determine the border color based on whether this pane is active
-    let border_color = if matches!(app.active_pane, crate::app::ActivePane::Log) {
-        Color::Rgb(255, 215, 0)  // Gold color for active pane (consistent with other panes)
+// A tag in a color of its own, the same one wherever it appears
+fn tag_chip<'a>(tag: &'a str, theme: &Theme) -> Span<'a> {
+    let colors = [
+        theme.accent,
+        theme.info,
+        theme.success,
+        theme.warning,
+        theme.secondary,
+        theme.history,
+    ];
+    let hash = tag.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    let style = if theme.monochrome {
+        Style::default().add_modifier(Modifier::REVERSED)
     } else {
-        Color::Rgb(80, 80, 80)   // Darker gray for inactive
+        Style::default()
+            .fg(theme.modal_bg)
+            .bg(colors[hash % colors.len()])
     };
-
-    let list = ratatui::widgets::List::new(list_items)
-        .block(
-            ratatui::widgets::Block::default()
-                .borders(ratatui::widgets::Borders::ALL)
-                .title(format!(
-                    " 📝 Log [{}/{}] ", // Added log emoji
-                    app.log_scroll_position + 1,
-                    app.log_entries.len()
-                ))
-                .border_style(ratatui::style::Style::default().fg(border_color).add_modifier(Modifier::BOLD)),
-        )
-        .style(
-            ratatui::style::Style::default()
-                .fg(ratatui::style::Color::Rgb(200, 200, 200)),  // Same text color as other panes
-        )
-        .highlight_style(
-            ratatui::style::Style::default()
-                .bg(ratatui::style::Color::Rgb(70, 130, 180))  // Steel blue highlight
-                .fg(ratatui::style::Color::White),
-        );
-
-    f.render_widget(list, area);
+    Span::styled(format!(" {} ", tag), style)
 }
 
-fn draw_command_history_view(f: &mut Frame, area: Rect, app: &App) {
-    let title = " 📋 Command History ";
-    let commands: Vec<ratatui::text::Line> = app
-        .command_history
-        .iter()
-        .rev() // Show most recent first
-        .take(50) // Limit to last 50 commands
-        .map(|cmd| ratatui::text::Line::from(cmd.as_str()))
-        .collect();
+fn draw_confirm<A>(f: &mut Frame, area: Rect, confirm: &Confirm<A>, theme: &Theme) {
+    let popup_area = centered_rect(50, 20, area);
+    f.render_widget(Clear, popup_area);
 
-    let list = ratatui::widgets::List::new(commands)
+    let mut text = vec![Line::from(confirm.message.clone())];
+    if let Some(ref detail) = confirm.detail {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            detail.clone(),
+            Style::default().fg(theme.text_muted),
+        )));
+    }
+    let prompt = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(
-            ratatui::widgets::Block::default()
-                .borders(ratatui::widgets::Borders::ALL)
-                .title(title)
-                .border_style(ratatui::style::Style::default()
-                    .fg(ratatui::style::Color::Rgb(147, 112, 219))  // Medium purple
-                    .add_modifier(ratatui::style::Modifier::BOLD)),
-        )
-        .highlight_style(
-            ratatui::style::Style::default()
-                .bg(ratatui::style::Color::Rgb(147, 112, 219))  // Medium purple
-                .fg(ratatui::style::Color::White),
+            Block::default()
+                .borders(Borders::ALL)
+                .title(confirm.title.clone())
+                .title_bottom(" y:yes | n/esc:no ")
+                .border_style(theme.border(true))
+                .style(Style::default().bg(theme.modal_bg).fg(theme.text)),
         );
-
-    f.render_widget(list, area);
+    f.render_widget(prompt, popup_area);
 }
 
-fn draw_log_view(f: &mut Frame, area: Rect, app: &App) {
-    let title = format!(
-        " 📝 Log [{}/{}] ",
-        app.log_scroll_position + 1,
-        app.log_entries.len()
-    );
+fn draw_where_used_modal(f: &mut Frame, area: Rect, app: &App, part: &str, theme: &Theme) {
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
 
-    // Show a portion of the log entries based on scroll position
-    let start_idx = app.log_scroll_position.saturating_sub(10); // Show 10 entries before current position
-    let end_idx = std::cmp::min(start_idx + 20, app.log_entries.len()); // Show 20 entries total
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" ⤴ Where Used: {} ", part))
+        .title_bottom(" ↑↓:nav | enter:go to | y/Y:copy uuid/path | q/esc:close ")
+        .border_style(theme.border(true))
+        .style(Style::default().bg(theme.modal_bg).fg(theme.text));
 
-    // Create list items with highlighting for the selected item
-    let list_items: Vec<ratatui::widgets::ListItem> = app
-        .log_entries
+    if app.where_used_results.is_empty() {
+        let empty = Paragraph::new("No assemblies use this asset")
+            .style(Style::default().fg(theme.text_muted))
+            .block(block);
+        f.render_widget(empty, popup_area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .where_used_results
         .iter()
-        .skip(start_idx)
-        .take(end_idx - start_idx)
         .enumerate()
-        .map(|(idx, entry)| {
-            // Check if this item corresponds to the current scroll position
-            let is_selected = start_idx + idx == app.log_scroll_position;
-
-            if is_selected {
-                // Style for selected item - use a more prominent highlight
-                ratatui::widgets::ListItem::new(ratatui::text::Line::from(vec![
-                    ratatui::text::Span::styled(
-                        "▶ ",
-                        ratatui::style::Style::default()
-                            .bg(ratatui::style::Color::Rgb(70, 130, 180))  // Steel blue
-                            .fg(ratatui::style::Color::Rgb(255, 215, 0))   // Gold
-                            .add_modifier(ratatui::style::Modifier::BOLD),
-                    ),
-                    ratatui::text::Span::styled(
-                        entry.as_str(),
-                        ratatui::style::Style::default()
-                            .bg(ratatui::style::Color::Rgb(70, 130, 180))  // Steel blue
-                            .fg(ratatui::style::Color::White)
-                            .add_modifier(ratatui::style::Modifier::BOLD),
-                    ),
-                ]))
+        .map(|(i, asset)| {
+            let line = Line::from(vec![
+                Span::raw(asset.name.clone()),
+                Span::styled(
+                    format!("  {}", asset.path),
+                    Style::default().fg(theme.text_muted),
+                ),
+            ]);
+            if i == app.where_used_selected {
+                ListItem::new(line).style(theme.highlighted())
             } else {
-                // Style for non-selected items
-                ratatui::widgets::ListItem::new(ratatui::text::Line::from(entry.as_str()))
+                ListItem::new(line)
             }
         })
         .collect();
 
-    let list = ratatui::widgets::List::new(list_items).block(
-        ratatui::widgets::Block::default()
-            .borders(ratatui::widgets::Borders::ALL)
-            .title(title)
-            .border_style(ratatui::style::Style::default()
-                .fg(ratatui::style::Color::Rgb(100, 149, 237))  // Cornflower blue border
-                .add_modifier(ratatui::style::Modifier::BOLD)),
-    )
-    .style(
-        ratatui::style::Style::default()
-            .bg(ratatui::style::Color::Rgb(30, 30, 30))  // Same background as other panes
-            .fg(ratatui::style::Color::Rgb(200, 200, 200)),  // Same text color as other panes
-    );
+    let mut state = ListState::default();
+    state.select(Some(app.where_used_selected));
+    f.render_stateful_widget(List::new(items).block(block), popup_area, &mut state);
+}
 
-    f.render_widget(list, area);
+fn draw_asset_details_modal(f: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let Some(details) = app.selected_asset_details.as_ref() else {
+        return;
+    };
+
+    let popup_area = centered_rect(80, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let label = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let size = details
+        .file_size
+        .map_or_else(|| "n/a".to_string(), text::format_bytes);
+    let fields = [
+        ("UUID:       ", details.uuid.clone()),
+        ("Name:       ", details.name.clone()),
+        ("Path:       ", details.path.clone()),
+        ("Type:       ", details.file_type.clone()),
+        ("Size:       ", size),
+        ("Processing: ", details.processing_status.clone()),
+        ("State:      ", details.state.clone()),
+        (
+            "Assembly:   ",
+            if details.is_assembly { "yes" } else { "no" }.to_string(),
+        ),
+        ("Created:    ", details.created_at.clone()),
+        ("Updated:    ", details.updated_at.clone()),
+        ("Folder:     ", details.folder_id.clone()),
+        ("Tenant:     ", details.tenant_id.clone()),
+    ];
+    let mut lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(name, value)| Line::from(vec![Span::styled(name, label), Span::raw(value)]))
+        .collect();
+
+    if let Some(note) = app.notes.get(&details.uuid) {
+        lines.push(Line::from(vec![
+            Span::styled("Note:       ", label),
+            Span::styled(note.to_string(), Style::default().fg(theme.info)),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    let muted = Style::default().fg(theme.text_muted);
+    match app.geometry.get(&details.uuid) {
+        Some(GeometryState::Ready(geometry)) => {
+            let text = GeometryText::new(geometry, app.geometry_unit);
+            for (name, value) in [
+                ("Size:       ", text.size),
+                ("Volume:     ", text.volume),
+                ("Area:       ", text.surface_area),
+            ] {
+                if !value.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::styled(name, label),
+                        Span::raw(value),
+                    ]));
+                }
+            }
+        }
+        Some(GeometryState::Unavailable(error)) => lines.push(Line::from(vec![
+            Span::styled("Geometry:   ", label),
+            Span::styled(format!("unavailable ({})", error), muted),
+        ])),
+        Some(GeometryState::Loading) | None => lines.push(Line::from(vec![
+            Span::styled("Geometry:   ", label),
+            Span::styled("loading...", muted),
+        ])),
+    }
+
+    if !details.metadata.is_null() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("--- metadata ---", label)));
+        let metadata = serde_json::to_string_pretty(&details.metadata).unwrap_or_default();
+        lines.extend(metadata.lines().map(|line| Line::from(line.to_string())));
+    }
+
+    // Keep the scroll offset within the wrapped content so scrolling back up responds immediately
+    let inner_width = popup_area.width.saturating_sub(2).max(1) as usize;
+    let inner_height = popup_area.height.saturating_sub(2) as usize;
+    let wrapped_height: usize = lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(inner_width))
+        .sum();
+    let max_scroll = wrapped_height.saturating_sub(inner_height) as u16;
+    app.asset_details_scroll = app.asset_details_scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" 📎 {} ", details.name))
+                .title_bottom(" ↑↓/PgUp/PgDn:scroll | n:note | y:copy JSON | q/esc:close ")
+                .border_style(theme.border(true))
+                .style(Style::default().bg(theme.modal_bg)),
+        )
+        .style(Style::default().fg(theme.text))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .scroll((app.asset_details_scroll, 0));
+
+    f.render_widget(paragraph, popup_area);
 }
 
-fn draw_search_modal(f: &mut Frame, area: Rect, app: &App) {
-    // Create a centered modal window
-    let popup_area = centered_rect(60, 40, area);
+fn draw_search_modal(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    // Create a centered modal window, wide enough for a few result columns
+    let popup_area = centered_rect(80, 60, area);
 
     // Clear the background first
     f.render_widget(Clear, popup_area);
@@ -823,9 +3601,19 @@ fn draw_search_modal(f: &mut Frame, area: Rect, app: &App) {
     // Draw outer frame for the modal
     let modal_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD))  // Gold border to match other panes
-        .title(" 🔍 Search ")  // Added spaces for padding
-        .style(Style::default().bg(Color::Rgb(30, 30, 40))); // Slightly different dark background
+        .border_style(theme.border(true)) // Same border as other active panes
+        .title(format!(
+            " 🔍 Search: {} · {} ",
+            app.search_scope.label(),
+            app.search_mode.label()
+        )) // Added spaces for padding
+        .title_bottom(
+            Line::from(
+                " Ctrl+S: scope | Ctrl+T: mode | F2: save | F3: saved | s/S: sort | ←→: columns ",
+            )
+            .right_aligned(),
+        )
+        .style(Style::default().bg(theme.modal_bg));
 
     f.render_widget(modal_block, popup_area);
 
@@ -847,98 +3635,241 @@ fn draw_search_modal(f: &mut Frame, area: Rect, app: &App) {
 
     // Input section - now just the input field without a label
     // Draw the search input field with proper alignment and enhanced visual cues
-    let input_border_color = if matches!(app.search_modal_focus, crate::app::SearchModalFocus::Input) {
-        Color::Yellow // Highlight with yellow when focused (consistent with other panes)
-    } else {
-        Color::Gray // More visible color when not focused
-    };
+    let input_focused = matches!(app.search_modal_focus, crate::app::SearchModalFocus::Input);
 
-    let input_field = Paragraph::new(format!("{}█", app.search_input_buffer)) // Add a visual cursor
+    // While a query is being saved, the field takes its name
+    let (input_text, input_title) = match app.search_save_name {
+        Some(ref name) => (
+            Line::from(name.spans("█")),
+            format!(
+                " Save \"{}\" as (Enter: save, Esc: cancel) ",
+                app.search_input_buffer.as_str()
+            ),
+        ),
+        None => (
+            Line::from(app.search_input_buffer.spans("█")),
+            String::new(),
+        ), // Add a visual cursor
+    };
+    let input_field = Paragraph::new(input_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(input_border_color).add_modifier(Modifier::BOLD)) // Highlight when focused
-                .style(Style::default().bg(Color::Rgb(40, 40, 40))), // Slightly lighter background
+                .border_style(theme.border(input_focused || app.search_save_name.is_some())) // Highlight when focused
+                .style(Style::default().bg(theme.input_bg)) // Slightly lighter background
+                .title(input_title),
         )
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
     f.render_widget(input_field, chunks[0]); // Use the whole input section for the field
 
-    // Results section
-    let results_title = format!(" Results ({}) ", app.search_results.len()); // Renamed to "Results" and padded with spaces
+    if app.show_saved_searches {
+        draw_saved_searches(f, chunks[1], app, theme);
+        return;
+    }
 
-    let results_list_items = if app.command_in_progress {
-        // Show a searching indicator when command is in progress
-        vec![ListItem::new(
-            Line::from(Span::styled(
-                "Searching...",
-                Style::default().fg(Color::Yellow)
-            ))
-        )]
-    } else if app.search_results.is_empty() {
-        // Show a message when there are no search results
-        vec![ListItem::new(
-            Line::from(Span::styled(
-                "No results found",
-                Style::default().fg(Color::DarkGray)
-            ))
-        )]
-    } else {
-        app.search_results
+    // Results section: a table with the name pinned on the left and the other columns
+    // scrolling sideways
+    let results_focused = matches!(
+        app.search_modal_focus,
+        crate::app::SearchModalFocus::Results
+    );
+    let mut results_title = format!(" Results ({}) ", app.search_results.len());
+    if let Some((ref column, descending)) = app.search_sort {
+        results_title.push_str(&format!(
+            "sorted by {} {} ",
+            column.label(),
+            sort_arrow(descending)
+        ));
+    }
+    let results_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border(results_focused)) // Highlight when focused
+        .title(results_title);
+
+    if app.command_in_progress || app.search_results.is_empty() {
+        // Show a searching indicator when command is in progress, or that nothing was found
+        let (message, color) = if app.command_in_progress {
+            ("Searching...", theme.warning)
+        } else {
+            ("No results found", theme.text_muted)
+        };
+        let message = Paragraph::new(message)
+            .style(Style::default().fg(color))
+            .block(results_block);
+        f.render_widget(message, chunks[1]);
+        return;
+    }
+
+    let mut columns: Vec<(SearchSortColumn, Vec<String>)> = vec![
+        (
+            SearchSortColumn::Folder,
+            app.search_results
+                .iter()
+                .map(|asset| {
+                    let folder = asset
+                        .path
+                        .rsplit_once('/')
+                        .map(|(dir, _)| dir)
+                        .unwrap_or(&asset.path);
+                    folder.to_string()
+                })
+                .collect(),
+        ),
+        (
+            SearchSortColumn::Size,
+            app.search_results
+                .iter()
+                .map(|asset| asset.size.map(text::format_bytes).unwrap_or_default())
+                .collect(),
+        ),
+    ];
+    for key in crate::app::metadata_keys(&app.search_results) {
+        let values = app
+            .search_results
             .iter()
-            .enumerate()
-            .map(|(i, asset)| {
-                let is_selected = i == app.selected_search_result_index;
-                let style = if is_selected {
-                    Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White)  // Forest green to match other selections
+            .map(|asset| asset.metadata_value(&key).unwrap_or_default())
+            .collect();
+        columns.push((SearchSortColumn::Metadata(key), values));
+    }
+    let offset = app.search_column_offset.min(columns.len() - 1);
+    let columns = &columns[offset..];
+
+    // Headers carry the sort direction of the sorted column
+    let header = |column: &SearchSortColumn| match app.search_sort {
+        Some((ref sorted, descending)) if sorted == column => {
+            format!("{} {}", column.label(), sort_arrow(descending))
+        }
+        _ => column.label().to_string(),
+    };
+    let mut headers = vec![String::new(), header(&SearchSortColumn::Name)];
+    headers.extend(columns.iter().map(|(column, _)| header(column)));
+
+    // Columns are as wide as their widest value or header
+    let name_width = app
+        .search_results
+        .iter()
+        .map(|asset| column_width(&asset.name))
+        .fold(column_width(&headers[1]), std::cmp::max);
+    let mut widths = vec![
+        Constraint::Length(2), // Icon column
+        Constraint::Length(name_width as u16 + 1),
+    ];
+    for (i, (_, values)) in columns.iter().enumerate() {
+        let width = values
+            .iter()
+            .map(|value| column_width(value))
+            .fold(column_width(&headers[i + 2]), std::cmp::max);
+        widths.push(Constraint::Length(width as u16 + 1));
+    }
+
+    let rows = app
+        .search_results
+        .iter()
+        .enumerate()
+        .map(|(i, asset)| {
+            let icon = match asset.file_type.as_str() {
+                "model" => "🏗️",    // Building/construction icon for 3D models
+                "document" => "📄", // Document icon
+                "image" => "🖼️",    // Image icon
+                "video" => "🎥",    // Video icon
+                "audio" => "🎵",    // Audio icon
+                "archive" => "📦",  // Archive icon
+                "code" => "💻",     // Code/icon
+                _ => "📁",          // Default folder icon
+            };
+            let mut cells = vec![
+                Cell::from(icon),
+                Cell::from(truncate_to_width(&asset.name, text::MAX_COLUMN_WIDTH).into_owned()),
+            ];
+            for (column, values) in columns {
+                let value = values[i].clone();
+                // Sizes are numbers too, even with their unit
+                if *column == SearchSortColumn::Size {
+                    cells.push(Cell::from(Line::from(value).alignment(Alignment::Right)));
                 } else {
-                    Style::default().fg(Color::Rgb(255, 255, 0))  // Gold to match other unselected items
-                };
+                    cells.push(create_cell_with_alignment(value));
+                }
+            }
+            Row::new(cells).style(Style::default().fg(theme.accent)) // Match other unselected items
+        })
+        .collect::<Vec<Row>>();
 
-                let icon = match asset.file_type.as_str() {
-                    "model" => "🏗️",    // Building/construction icon for 3D models
-                    "document" => "📄", // Document icon
-                    "image" => "🖼️",    // Image icon
-                    "video" => "🎬",    // Video icon
-                    "audio" => "🎵",    // Audio icon
-                    "archive" => "📦",  // Archive icon
-                    "code" => "💻",     // Code/icon
-                    _ => "📁",          // Default folder icon
-                };
+    let table =
+        Table::new(rows, widths)
+            .header(
+                Row::new(headers.into_iter().map(|h| {
+                    Cell::from(truncate_to_width(&h, text::MAX_COLUMN_WIDTH).into_owned())
+                }))
+                .style(Style::default().fg(theme.accent)) // Accent header text
+                .bottom_margin(1),
+            )
+            .block(results_block)
+            .highlight_style(theme.selected()) // Match other selections
+            .column_spacing(1);
 
-                let content = Line::from(vec![Span::styled(
-                    format!("{} {}", icon, asset.name),
-                    style,
-                )]);
+    // Keep the selected result in view
+    let mut state = TableState::default().with_selected(Some(app.selected_search_result_index));
+    f.render_stateful_widget(table, chunks[1], &mut state);
+}
 
-                ListItem::new(content)
-            })
-            .collect::<Vec<ListItem>>()
-    };
+fn sort_arrow(descending: bool) -> &'static str {
+    if descending { "▼" } else { "▲" }
+}
 
-    // Determine border color based on focus state
-    let results_border_color = if matches!(app.search_modal_focus, crate::app::SearchModalFocus::Results) {
-        Color::Rgb(255, 215, 0) // Gold/yellow when focused (to match search input field)
+// The saved searches, listed in place of the results
+fn draw_saved_searches(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let searches = &app.config.saved_searches;
+    let items: Vec<ListItem> = if searches.is_empty() {
+        vec![ListItem::new(Line::from(Span::styled(
+            "No saved searches yet; F2 saves the query in the input",
+            Style::default().fg(theme.text_muted),
+        )))]
     } else {
-        Color::Rgb(100, 100, 100) // More visible color when not focused
+        searches
+            .iter()
+            .enumerate()
+            .map(|(i, search)| {
+                let style = if i == app.selected_saved_search {
+                    theme.selected()
+                } else {
+                    Style::default().fg(theme.accent)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("★ {}", search.name), style),
+                    Span::styled(
+                        format!("  {}", search.query),
+                        Style::default().fg(theme.text_muted),
+                    ),
+                ]))
+            })
+            .collect()
     };
 
-    let results_list = List::new(results_list_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(results_border_color).add_modifier(Modifier::BOLD)) // Highlight when focused
-                .title(results_title)
-        ) // Consistent border styling
-        .highlight_style(Style::default().bg(Color::Rgb(135, 206, 235)).fg(Color::Black)); // Light sky blue for contrast
-
-    // Render the results list
-    f.render_widget(results_list, chunks[1]);
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.border(true))
+            .title(format!(" Saved searches ({}) ", searches.len()))
+            .title_bottom(
+                Line::from(" enter:run | e:edit | x/del:delete | esc:back ").right_aligned(),
+            ),
+    );
+    f.render_widget(list, area);
 }
 
 // Helper function to determine if a value is numeric and format it appropriately
 fn create_cell_with_alignment(value: String) -> Cell<'static> {
+    // Ellipsize overly long values instead of letting them blow out the table
+    create_cell_with_width(value, text::MAX_COLUMN_WIDTH)
+}
+
+// A cell ellipsized to `width` columns, right-aligned when it holds a number
+fn create_cell_with_width(value: String, width: usize) -> Cell<'static> {
+    let is_number = value.parse::<f64>().is_ok();
+    let value = truncate_to_width(&value, width).into_owned();
+
     // Try to parse as a number (integer or float)
-    if value.parse::<f64>().is_ok() {
+    if is_number {
         // If it's a valid number, right-align it by wrapping it in a right-aligned Line
         Cell::from(Line::from(Span::raw(value)).alignment(Alignment::Right))
     } else {
@@ -947,38 +3878,27 @@ fn create_cell_with_alignment(value: String) -> Cell<'static> {
     }
 }
 
-// Generic function to extract metadata keys from a list of assets
-fn extract_metadata_keys(assets: &[(Asset, f64)]) -> Vec<String> {
-    let mut all_metadata_keys = std::collections::HashSet::<String>::new();
-    for (asset, _) in assets {
-        if let Some(obj) = asset.metadata.as_object() {
-            for key in obj.keys() {
-                // Special handling for the case where metadata contains a "meta" key that wraps actual metadata
-                if key == "meta" {
-                    // If the "meta" key contains an object, extract keys from that object instead
-                    if let Some(meta_obj) = obj.get(key).and_then(|v| v.as_object()) {
-                        for meta_key in meta_obj.keys() {
-                            all_metadata_keys.insert(meta_key.clone());
-                        }
-                    } else {
-                        // If "meta" doesn't contain an object, use it as a regular key
-                        all_metadata_keys.insert(key.clone());
-                    }
-                } else {
-                    // Regular handling for other keys
-                    all_metadata_keys.insert(key.clone());
-                }
-            }
-        }
+// Similarity scores (%) from which a match is a near-certain duplicate, or a likely variant
+const SIMILARITY_HIGH: f64 = 95.0;
+const SIMILARITY_MEDIUM: f64 = 80.0;
+
+// The color of a geometric match by how similar it is
+fn similarity_band(similarity: f64, theme: &Theme) -> Style {
+    if similarity > SIMILARITY_HIGH {
+        Style::default().fg(theme.success)
+    } else if similarity >= SIMILARITY_MEDIUM {
+        Style::default().fg(theme.warning)
+    } else {
+        Style::default().fg(theme.text)
     }
+}
 
-    // Convert to sorted vector
-    let mut sorted_metadata_keys: Vec<String> = all_metadata_keys.into_iter().collect();
-    sorted_metadata_keys.sort();
-    sorted_metadata_keys
+// Metadata keys of the geometric match results
+fn extract_metadata_keys(assets: &[(Asset, f64)]) -> Vec<String> {
+    crate::app::metadata_keys(assets.iter().map(|(asset, _)| asset))
 }
 
-fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
+fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     // Create a larger centered modal window (80% of screen)
     let popup_area = centered_rect(80, 80, area);
 
@@ -988,9 +3908,10 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
     // Draw outer frame for the modal
     let modal_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD))  // Gold border
+        .border_style(theme.border(true))
         .title(" 🔍 Geometric Match Results ")  // Added spaces for padding
-        .style(Style::default().bg(Color::Rgb(30, 30, 40))); // Dark background matching theme
+        .title_bottom(" ↑↓:nav | ←→:scroll | space:queue | r:review queue | >:min similarity | s/f:hide self/same folder | =:compare metadata | y/Y:copy uuid/path | q/esc:close ")
+        .style(Style::default().bg(theme.modal_bg));
 
     f.render_widget(modal_block, popup_area);
 
@@ -1022,29 +3943,33 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
     } else {
         // Calculate max lengths for each column
         let max_icon_len = 1; // Icons are single characters (don't need mut)
-        let mut max_name_len = "Name".len(); // Minimum width based on header
-        let mut max_path_len = "Folder Path".len(); // Minimum width based on header
-        let mut max_similarity_len = "Similarity".len(); // Minimum width based on header
+        let mut max_name_len = column_width("Name"); // Minimum width based on header
+        let mut max_path_len = column_width("Folder Path"); // Minimum width based on header
+        let mut max_similarity_len = column_width("Similarity"); // Minimum width based on header
 
         // Calculate max lengths for metadata columns
         let mut max_metadata_lengths = Vec::new();
         for key in &sorted_metadata_keys {
-            // Initialize with header length
-            max_metadata_lengths.push(key.len());
+            // Initialize with header width
+            max_metadata_lengths.push(column_width(key));
         }
 
-        // Iterate through results to find max content lengths
+        // Iterate through results to find max content widths (in terminal columns, not bytes)
         for (asset, similarity_score) in &app.geometric_match_results {
             // Update max name length
-            max_name_len = std::cmp::max(max_name_len, asset.name.len());
+            max_name_len = std::cmp::max(max_name_len, column_width(&asset.name));
 
             // Update max path length
-            let folder_path = asset.path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(&asset.path);
-            max_path_len = std::cmp::max(max_path_len, folder_path.len());
+            let folder_path = asset
+                .path
+                .rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or(&asset.path);
+            max_path_len = std::cmp::max(max_path_len, column_width(folder_path));
 
             // Update max similarity length
             let similarity_text = format!("{:.2}%", (similarity_score * 100.0).round() / 100.0);
-            max_similarity_len = std::cmp::max(max_similarity_len, similarity_text.len());
+            max_similarity_len = std::cmp::max(max_similarity_len, column_width(&similarity_text));
 
             // Update max metadata lengths
             if let Some(obj) = asset.metadata.as_object() {
@@ -1058,7 +3983,8 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
                         };
 
                         if i < max_metadata_lengths.len() {
-                            max_metadata_lengths[i] = std::cmp::max(max_metadata_lengths[i], value_str.len());
+                            max_metadata_lengths[i] =
+                                std::cmp::max(max_metadata_lengths[i], column_width(value_str));
                         }
                     }
                 }
@@ -1067,7 +3993,7 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
 
         // Create constraints based on calculated widths - optimizing for minimal real estate
         let mut widths = vec![
-            Constraint::Length(max_icon_len as u16 + 1),  // Icon column with minimal padding
+            Constraint::Length(max_icon_len as u16 + 1), // Icon column with minimal padding
             Constraint::Length(max_name_len as u16 + 1), // Name column with minimal padding
             Constraint::Length(max_path_len as u16 + 1), // Folder Path column with minimal padding
             Constraint::Length(max_similarity_len as u16 + 1), // Similarity Score column with minimal padding
@@ -1086,11 +4012,11 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD)) // Gold border
+                    .border_style(theme.border(true))
                     .title(" 🔍 Geometric Match Results "), // Title for consistency
             )
             .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::Yellow));
+            .style(Style::default().fg(theme.warning));
 
         f.render_widget(searching_text, inner_area);
     } else if app.geometric_match_results.is_empty() {
@@ -1099,55 +4025,89 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD)) // Gold border
+                    .border_style(theme.border(true))
                     .title(format!(" Results ({}) ", app.geometric_match_results.len())), // Title with count
             )
             .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::Rgb(100, 100, 100))); // Muted gray
+            .style(Style::default().fg(theme.border_inactive)); // Muted
 
         f.render_widget(no_data_text, inner_area);
     } else {
+        let matches = app.visible_geometric_matches();
+        let mut filters = Vec::new();
+        if let Some(minimum) = app.geometric_match_min_similarity {
+            filters.push(format!("≥ {}%", minimum));
+        }
+        if app.geometric_match_hide_self {
+            filters.push("no self".to_string());
+        }
+        if app.geometric_match_hide_same_folder {
+            filters.push("other folders".to_string());
+        }
+        if !app.match_queue.is_empty() {
+            filters.push(format!("{} queued", app.match_queue.len()));
+        }
+        let title = if filters.is_empty() {
+            format!(" Results ({}) ", matches.len())
+        } else {
+            format!(
+                " Results ({} of {}) · {} ",
+                matches.len(),
+                app.geometric_match_results.len(),
+                filters.join(" · ")
+            )
+        };
+
         // Create table rows
-        let rows = app.geometric_match_results
-            .iter()
+        let rows = matches
+            .into_iter()
             .enumerate()
             .map(|(i, (asset, similarity_score))| {
                 let is_selected = i == app.geometric_match_scroll_position; // Use geometric match scroll position
+                let band = similarity_band(*similarity_score, theme);
                 let row_style = if is_selected {
-                    Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White) // Forest green to match other selections
+                    theme.selected() // Match other selections
                 } else {
-                    Style::default().fg(Color::Rgb(200, 200, 200)) // Light gray for readability
+                    band
                 };
 
+                let queued = app
+                    .match_queue
+                    .iter()
+                    .any(|(queued, _)| queued.uuid == asset.uuid);
                 let icon = match asset.file_type.as_str() {
-                    "model" => "🏗️",    // Building/construction icon for 3D models
-                    "document" => "📝", // Document icon
-                    "image" => "🖼️",    // Image icon
-                    "video" => "🎥",    // Video icon
-                    "audio" => "🎧",    // Audio icon
-                    "archive" => "📦",  // Archive icon
-                    "code" => "💻",     // Code/icon
-                    _ => "📁",          // Default folder icon
+                    _ if queued => "📌", // Queued for review
+                    "model" => "🏗️",     // Building/construction icon for 3D models
+                    "document" => "📝",  // Document icon
+                    "image" => "🖼️",     // Image icon
+                    "video" => "🎥",     // Video icon
+                    "audio" => "🎧",     // Audio icon
+                    "archive" => "📦",   // Archive icon
+                    "code" => "💻",      // Code/icon
+                    _ => "📁",           // Default folder icon
                 };
 
                 // Format the similarity score as a percentage with right alignment
                 let similarity_percent = (similarity_score * 100.0).round() / 100.0; // Round to 2 decimal places
                 let similarity_formatted = format!("{:>8.2}%", similarity_percent); // Right-align with padding
-                let similarity_cell = Cell::from(similarity_formatted)
-                    .style(if is_selected {
-                        Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::Rgb(173, 216, 230)) // Lighter text for similarity in selected item
-                    } else {
-                        Style::default().fg(Color::Rgb(173, 216, 230)) // Light blue for similarity in unselected items
-                    });
+                let similarity_cell = Cell::from(similarity_formatted).style(if is_selected {
+                    theme.selected()
+                } else {
+                    band.add_modifier(Modifier::BOLD)
+                });
 
                 // Extract folder path from asset path
-                let folder_path = asset.path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(&asset.path);
+                let folder_path = asset
+                    .path
+                    .rsplit_once('/')
+                    .map(|(dir, _)| dir)
+                    .unwrap_or(&asset.path);
 
                 // Create cells for the basic columns
                 let mut cells = vec![
-                    Cell::from(icon), // Icon cell
-                    Cell::from(asset.name.as_str()), // Name cell (left-aligned by default)
-                    Cell::from(folder_path), // Folder Path cell (left-aligned by default)
+                    Cell::from(icon),                                                   // Icon cell
+                    Cell::from(truncate_to_width(&asset.name, text::MAX_COLUMN_WIDTH)), // Name cell (left-aligned by default)
+                    Cell::from(truncate_to_width(folder_path, text::MAX_COLUMN_WIDTH)), // Folder Path cell (left-aligned by default)
                     similarity_cell, // Similarity cell (right-aligned)
                 ];
 
@@ -1215,26 +4175,23 @@ fn draw_geometric_match_modal(f: &mut Frame, area: Rect, app: &App) {
 
         // Add headers for each metadata key
         for key in &sorted_metadata_keys {
-            headers.push(Cell::from(key.as_str()));
+            headers.push(Cell::from(truncate_to_width(key, text::MAX_COLUMN_WIDTH)));
         }
 
         // Create the table
-        let table = Table::new(
-            rows,
-            column_widths,
-        )
+        let table = Table::new(rows, column_widths)
             .header(
                 Row::new(headers)
-                .style(Style::default().fg(Color::Rgb(255, 215, 0))) // Gold header text
-                .bottom_margin(1)
+                    .style(Style::default().fg(theme.accent)) // Accent header text
+                    .bottom_margin(1),
             )
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Rgb(255, 215, 0)).add_modifier(Modifier::BOLD)) // Gold border
-                    .title(format!(" Results ({}) ", app.geometric_match_results.len())), // Title with count
+                    .border_style(theme.border(true))
+                    .title(title),
             )
-            .highlight_style(Style::default().bg(Color::Rgb(34, 139, 34)).fg(Color::White)) // Forest green highlight
+            .highlight_style(theme.selected())
             .column_spacing(1); // Add spacing between columns for better readability
 
         // Render the table
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use crate::app::{Asset, Folder};
+use crate::folder_picker;
+
+// Keep this many operations to undo
+const UNDO_LIMIT: usize = 50;
+
+// A completed operation and what it takes to reverse it
+#[derive(Debug, Clone)]
+pub enum Undoable {
+    Move { asset: Asset, from: String }, // Move the asset back into `from`
+    Rename { asset: Asset, previous: String }, // Give the asset its previous name back
+    Delete { asset: Asset, file: PathBuf }, // Upload the local download of the asset again
+    MoveFolder { folder: Folder, from: String }, // Move the folder back under `from`
+}
+
+impl Undoable {
+    // What undoing it will do, for the notification
+    pub fn describe(&self) -> String {
+        match self {
+            Undoable::Move { asset, from } => format!("move {} back to {}", asset.name, from),
+            Undoable::Rename { asset, previous } => {
+                format!("rename {} back to {}", asset.name, previous)
+            }
+            Undoable::Delete { asset, file } => {
+                format!("upload {} again from {}", asset.name, file.display())
+            }
+            Undoable::MoveFolder { folder, from } => {
+                format!(
+                    "move folder {} back to {}",
+                    folder.name,
+                    folder_picker::label(from)
+                )
+            }
+        }
+    }
+}
+
+// Operations of this session that can be undone, the most recent last
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    entries: Vec<Undoable>,
+}
+
+impl UndoHistory {
+    pub fn push(&mut self, entry: Undoable) {
+        if self.entries.len() == UNDO_LIMIT {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn pop(&mut self) -> Option<Undoable> {
+        self.entries.pop()
+    }
+}
@@ -0,0 +1,170 @@
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::app::Asset;
+use crate::config::MetadataRule;
+
+// How an asset breaks a metadata rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    Missing,          // A required property has no value
+    Mismatch(String), // The value doesn't match the pattern
+}
+
+impl Problem {
+    pub fn describe(&self, pattern: Option<&str>) -> String {
+        match self {
+            Problem::Missing => "missing".to_string(),
+            Problem::Mismatch(value) => {
+                format!(
+                    "\"{}\" doesn't match {}",
+                    value,
+                    pattern.unwrap_or_default()
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub key: String,
+    pub problem: Problem,
+}
+
+// The configured rules with their patterns compiled, anchored so they match whole values
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<(MetadataRule, Option<Regex>)>,
+}
+
+impl Rules {
+    // Rules whose pattern doesn't compile are left out, each with a warning
+    pub fn compile(rules: &[MetadataRule]) -> (Self, Vec<String>) {
+        let mut compiled = Vec::new();
+        let mut warnings = Vec::new();
+        for rule in rules {
+            let pattern = match rule.pattern {
+                Some(ref pattern) => match Regex::new(&format!("^(?:{})$", pattern)) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        warnings.push(format!(
+                            "Metadata rule for {} ignored, invalid pattern: {}",
+                            rule.key, e
+                        ));
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            compiled.push((rule.clone(), pattern));
+        }
+        (Self { rules: compiled }, warnings)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn pattern(&self, key: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(rule, _)| rule.key == key)
+            .and_then(|(rule, _)| rule.pattern.as_deref())
+    }
+
+    // Every rule the asset breaks, in the order the rules are configured
+    pub fn check(&self, asset: &Asset) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (rule, pattern) in &self.rules {
+            let problem = match asset
+                .metadata_value(&rule.key)
+                .filter(|value| !value.is_empty())
+            {
+                None if rule.required => Problem::Missing,
+                None => continue,
+                Some(value) => match pattern {
+                    Some(pattern) if !pattern.is_match(&value) => Problem::Mismatch(value),
+                    _ => continue,
+                },
+            };
+            violations.push(Violation {
+                key: rule.key.clone(),
+                problem,
+            });
+        }
+        violations
+    }
+
+    // The keys of the rules the asset breaks, for marking its cells
+    pub fn broken_keys(&self, asset: &Asset) -> HashSet<String> {
+        self.check(asset)
+            .into_iter()
+            .map(|violation| violation.key)
+            .collect()
+    }
+}
+
+// The violations found in a folder and the folders below it, one row per broken rule
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub folder: String, // "" for the whole tenant
+    pub rows: Vec<(Asset, Violation)>,
+    pub checked: usize, // Assets checked, from the cached listings
+    pub selected: usize,
+}
+
+impl ValidationReport {
+    pub fn new<'a>(
+        rules: &Rules,
+        folder: &str,
+        assets: impl IntoIterator<Item = &'a Asset>,
+    ) -> Self {
+        let subtree = format!("{}/", folder);
+        let mut seen = HashSet::new();
+        let mut rows = Vec::new();
+        let mut checked = 0;
+        for asset in assets {
+            let in_subtree = folder.is_empty()
+                || asset.folder_path() == folder
+                || asset.folder_path().starts_with(&subtree);
+            if !in_subtree || !seen.insert(asset.uuid.as_str()) {
+                continue;
+            }
+            checked += 1;
+            rows.extend(
+                rules
+                    .check(asset)
+                    .into_iter()
+                    .map(|violation| (asset.clone(), violation)),
+            );
+        }
+        rows.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+        Self {
+            folder: folder.to_string(),
+            rows,
+            checked,
+            selected: 0,
+        }
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn chosen(&self) -> Option<&Asset> {
+        self.rows.get(self.selected).map(|(asset, _)| asset)
+    }
+
+    // Assets with at least one violation
+    pub fn assets(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|(asset, _)| asset.uuid.as_str())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}